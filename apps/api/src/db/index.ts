@@ -4,7 +4,7 @@ import { migrate as migrateSqlite } from 'drizzle-orm/bun-sqlite/migrator'
 import { migrate as migratePg } from 'drizzle-orm/postgres-js/migrator'
 import { Database } from 'bun:sqlite'
 import postgres from 'postgres'
-import { existsSync, mkdirSync } from 'fs'
+import { existsSync, mkdirSync, readFileSync } from 'fs'
 import { join, dirname } from 'path'
 
 import * as pgSchema from './schema.pg'
@@ -81,6 +81,19 @@ export function getDatabaseType(): DatabaseType {
   return isPostgres ? 'postgres' : 'sqlite'
 }
 
+/**
+ * Number of migrations in the folder's journal that aren't applied yet
+ */
+async function countPendingMigrations(migrationsPath: string): Promise<number> {
+  try {
+    const journal = JSON.parse(readFileSync(join(migrationsPath, 'meta', '_journal.json'), 'utf8'))
+    const bundled = Array.isArray(journal.entries) ? journal.entries.length : 0
+    return Math.max(0, bundled - ((await getSchemaVersion()) ?? 0))
+  } catch {
+    return 0
+  }
+}
+
 /**
  * Run migrations for the database (exported for use at app startup)
  */
@@ -93,6 +106,11 @@ export async function runMigrations() {
   }
 
   logger.debug(`[DB] Running migrations from ${migrationsPath}`)
+  // Printed at every log level: the desktop app waits longer for startup while migrating
+  const pending = await countPendingMigrations(migrationsPath)
+  if (pending > 0) {
+    console.log(`[DB] Applying ${pending} migrations...`)
+  }
   try {
     if (isPostgres) {
       await migratePg(db as unknown as ReturnType<typeof drizzlePg>, {
@@ -104,6 +122,9 @@ export async function runMigrations() {
       })
     }
     logger.debug('[DB] Migrations completed successfully')
+    if (pending > 0) {
+      console.log('[DB] Migrations applied')
+    }
   } catch (error) {
     // If migrations fail due to already applied, that's OK
     const errorMessage = error instanceof Error ? error.message : String(error)
@@ -267,6 +288,26 @@ export function closeDatabase() {
   }
 }
 
+/**
+ * Number of applied migrations, which the desktop app compares with the ones it bundles.
+ * Null when the migrations table can't be read (e.g. before the first migration).
+ */
+export async function getSchemaVersion(): Promise<number | null> {
+  try {
+    if (isPostgres) {
+      const rows = await (client as ReturnType<typeof postgres>)`
+        SELECT COUNT(*)::int AS count FROM drizzle.__drizzle_migrations`
+      return rows[0]?.count ?? null
+    }
+    const row = (client as Database)
+      .query('SELECT COUNT(*) AS count FROM __drizzle_migrations')
+      .get() as { count: number } | null
+    return row?.count ?? null
+  } catch {
+    return null
+  }
+}
+
 /**
  * Check database health
  */
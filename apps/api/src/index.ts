@@ -7,6 +7,7 @@ import { Hono } from 'hono'
 import { cors } from 'hono/cors'
 import { serveStatic } from 'hono/bun'
 import { join } from 'path'
+import { createHash, timingSafeEqual } from 'crypto'
 import {
   initializeBinaryEnvironment,
   isDevelopment,
@@ -20,7 +21,7 @@ import {
 initializeBinaryEnvironment()
 
 import { validateEnv } from './lib/env'
-import { dbType, checkDatabaseHealth, runMigrations } from './db'
+import { dbType, checkDatabaseHealth, closeDatabase, getSchemaVersion, runMigrations } from './db'
 import { deleteConfig } from './services/config'
 import authRoutes from './routes/auth'
 import setupRoutes from './routes/setup'
@@ -126,6 +127,32 @@ app.get('/health', async (c) => {
   })
 })
 
+// Build details, so the desktop app can tell when it and the server it runs are out of sync
+app.get('/info', async (c) => {
+  return c.json({
+    version: getVersion(),
+    database: { type: dbType },
+    schemaVersion: await getSchemaVersion(),
+    runtime: `Bun ${Bun.version}`,
+  })
+})
+
+// Graceful stop for the desktop app on Windows, which can't send SIGTERM.
+// Only enabled when the app passes a token for this run.
+const shutdownToken = process.env.SHUTDOWN_TOKEN
+if (shutdownToken) {
+  // Compared as digests so both sides have the same length and the check takes constant time
+  const digest = (value: string) => createHash('sha256').update(value).digest()
+  const expected = digest(shutdownToken)
+  app.post('/internal/shutdown', (c) => {
+    if (!timingSafeEqual(digest(c.req.header('x-shutdown-token') ?? ''), expected)) {
+      return c.json({ error: 'forbidden' }, 403)
+    }
+    setTimeout(() => void shutdown('shutdown request'), 0)
+    return c.json({ status: 'stopping' })
+  })
+}
+
 // API routes - all under /api prefix
 app.route('/api/setup', setupRoutes)
 app.route('/api/auth', authRoutes)
@@ -186,12 +213,46 @@ if (!isDevelopment()) {
 }
 
 const port = process.env.PORT ? parseInt(process.env.PORT) : 17777
+// The desktop app can talk to the server over a Unix socket instead of TCP
+const socketPath = process.env.SERVER_SOCKET
+// The desktop app sets HOST to keep the server to localhost unless LAN access is on;
+// without it Bun listens on all interfaces (Docker)
+const hostname = process.env.HOST
+const listenOn = socketPath ? { unix: socketPath } : hostname ? { port, hostname } : { port }
+// The desktop app's self-signed certificate when it serves over https (server.tls)
+const tlsCert = process.env.TLS_CERT_FILE
+const tlsKey = process.env.TLS_KEY_FILE
+const tls = tlsCert && tlsKey ? { tls: { cert: Bun.file(tlsCert), key: Bun.file(tlsKey) } } : {}
+
+let server: ReturnType<typeof Bun.serve> | undefined
+let shuttingDown = false
+
+/**
+ * Stop taking requests, let the running ones finish and close the database,
+ * so a stop never cuts a write short. The desktop app force-kills after a grace period.
+ */
+async function shutdown(reason: string) {
+  if (shuttingDown) return
+  shuttingDown = true
+  logger.info(`[Server] Shutting down (${reason})`)
+  try {
+    await server?.stop()
+    closeDatabase()
+  } catch (error) {
+    logger.error('[Server] Error during shutdown:', error)
+  }
+  process.exit(0)
+}
+
+process.on('SIGTERM', () => void shutdown('SIGTERM'))
+process.on('SIGINT', () => void shutdown('SIGINT'))
 
 // Start the server
 if (!isDevelopment()) {
   // Production mode - use Bun.serve directly
-  Bun.serve({
-    port,
+  server = Bun.serve({
+    ...listenOn,
+    ...tls,
     fetch(req, server) {
       return app.fetch(req, { ip: server.requestIP(req)?.address })
     },
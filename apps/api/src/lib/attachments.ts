@@ -0,0 +1,58 @@
+/**
+ * Stored copies of uploaded documents
+ *
+ * Statements, insurance policies and loan documents are kept as
+ * `<data dir>/attachments/<kind>/<record id>.<ext>`, next to the database.
+ * The desktop app reads this folder for usage stats, orphan detection and
+ * yearly archives. Storing is best effort: a failed write is logged and
+ * doesn't fail the upload.
+ */
+
+import { mkdir, readdir, unlink, writeFile } from 'fs/promises'
+import { join } from 'path'
+import type { FileType } from './constants'
+import { getDataDir } from './startup'
+import { logger } from './logger'
+
+export type AttachmentKind = 'statements' | 'insurance' | 'loans'
+
+/**
+ * Folder holding the stored documents
+ */
+export function getAttachmentsDir(): string {
+  return join(getDataDir(), 'attachments')
+}
+
+/**
+ * Store the uploaded file for a record
+ */
+export async function saveAttachment(
+  kind: AttachmentKind,
+  recordId: string,
+  fileType: FileType,
+  data: Uint8Array
+): Promise<void> {
+  const dir = join(getAttachmentsDir(), kind)
+  try {
+    await mkdir(dir, { recursive: true })
+    await writeFile(join(dir, `${recordId}.${fileType}`), data)
+  } catch (error) {
+    logger.warn(`[Attachments] Failed to store ${kind}/${recordId}:`, error)
+  }
+}
+
+/**
+ * Remove the stored file of a deleted record
+ */
+export async function deleteAttachment(kind: AttachmentKind, recordId: string): Promise<void> {
+  const dir = join(getAttachmentsDir(), kind)
+  // No folder yet means nothing was stored
+  const names = await readdir(dir).catch(() => [] as string[])
+  for (const name of names.filter((n) => n.startsWith(`${recordId}.`))) {
+    try {
+      await unlink(join(dir, name))
+    } catch (error) {
+      logger.warn(`[Attachments] Failed to remove ${kind}/${name}:`, error)
+    }
+  }
+}
@@ -16,6 +16,7 @@ import {
 import { getProfileById } from '../services/profiles'
 import { extractPdfText } from '../lib/file-parser'
 import { logger } from '../lib/logger'
+import { deleteAttachment, saveAttachment } from '../lib/attachments'
 
 const insuranceRoutes = new Hono<{ Variables: AuthVariables }>()
 
@@ -189,6 +190,7 @@ insuranceRoutes.post('/upload', async (c) => {
     }
 
     const policy = await createPolicy(policyInput)
+    await saveAttachment('insurance', policy.id, 'pdf', buffer)
 
     // Queue for processing
     queueInsuranceDocument({
@@ -249,6 +251,7 @@ insuranceRoutes.delete('/policies/:id', async (c) => {
 
   try {
     await deletePolicy(policyId, userId)
+    await deleteAttachment('insurance', policyId)
     return c.json({ success: true })
   } catch (error) {
     const message = error instanceof Error ? error.message : 'Failed to delete policy'
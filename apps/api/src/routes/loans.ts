@@ -16,6 +16,7 @@ import {
 import { getProfileById } from '../services/profiles'
 import { extractPdfText } from '../lib/file-parser'
 import { logger } from '../lib/logger'
+import { deleteAttachment, saveAttachment } from '../lib/attachments'
 
 const loanRoutes = new Hono<{ Variables: AuthVariables }>()
 
@@ -189,6 +190,7 @@ loanRoutes.post('/upload', async (c) => {
     }
 
     const loan = await createLoan(loanInput)
+    await saveAttachment('loans', loan.id, 'pdf', buffer)
 
     // Queue for processing
     queueLoanDocument({
@@ -249,6 +251,7 @@ loanRoutes.delete('/:id', async (c) => {
 
   try {
     await deleteLoan(loanId, userId)
+    await deleteAttachment('loans', loanId)
     return c.json({ success: true })
   } catch (error) {
     const message = error instanceof Error ? error.message : 'Failed to delete loan'
@@ -17,6 +17,7 @@ import type { FileType } from '../lib/constants'
 import { decryptOptional } from '../lib/encryption'
 import { SUPPORTED_FILE_TYPES, type CountryCode } from '../lib/constants'
 import { logger } from '../lib/logger'
+import { deleteAttachment, saveAttachment } from '../lib/attachments'
 
 const statementRoutes = new Hono<{ Variables: AuthVariables }>()
 
@@ -240,6 +241,7 @@ statementRoutes.post('/upload', async (c) => {
         fileSizeBytes: buffer.length,
         documentType: documentType || undefined,
       })
+      await saveAttachment('statements', statement.id, fileType, buffer)
 
       statements.push({
         statementId: statement.id,
@@ -303,6 +305,7 @@ statementRoutes.delete('/:id', async (c) => {
 
   try {
     await deleteStatement(statementId, userId)
+    await deleteAttachment('statements', statementId)
     return c.json({ success: true })
   } catch (error) {
     const message = error instanceof Error ? error.message : 'Failed to delete statement'
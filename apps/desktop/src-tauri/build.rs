@@ -1,3 +1,42 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use sha2::{Digest, Sha256};
+
 fn main() {
+    // Build metadata for the About window
+    let commit = Command::new("git")
+        .args(["rev-parse", "--short=10", "HEAD"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .unwrap_or_default();
+    println!("cargo:rustc-env=MONEYWRIGHT_BUILD_COMMIT={}", commit);
+    println!("cargo:rustc-env=MONEYWRIGHT_BUILD_TARGET={}", std::env::var("TARGET").unwrap_or_default());
+    println!("cargo:rustc-env=MONEYWRIGHT_BUILD_PROFILE={}", std::env::var("PROFILE").unwrap_or_default());
+    println!("cargo:rerun-if-changed=../../../.git/HEAD");
+    println!("cargo:rerun-if-changed=../../../.git/refs/heads");
+
+    // The bundled server binary, checked before every start (see src/integrity.rs)
+    let target = std::env::var("TARGET").unwrap_or_default();
+    let suffix = if target.contains("windows") { ".exe" } else { "" };
+    let sidecar = format!("binaries/moneywright-{}{}", target, suffix);
+    let (sha256, size) = match fs::read(Path::new(&sidecar)) {
+        Ok(bytes) => (hex(&Sha256::digest(&bytes)), bytes.len().to_string()),
+        Err(_) => (String::new(), String::new()),
+    };
+    println!("cargo:rustc-env=MONEYWRIGHT_SIDECAR_SHA256={}", sha256);
+    println!("cargo:rustc-env=MONEYWRIGHT_SIDECAR_SIZE={}", size);
+    println!("cargo:rerun-if-changed={}", sidecar);
+
+    // The team the macOS release is signed by, required of the sidecar's signature
+    println!("cargo:rustc-env=MONEYWRIGHT_APPLE_TEAM_ID={}", std::env::var("APPLE_TEAM_ID").unwrap_or_default());
+    println!("cargo:rerun-if-env-changed=APPLE_TEAM_ID");
+
     tauri_build::build()
 }
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -0,0 +1,167 @@
+// OS accessibility preferences: high contrast, reduced motion and text size
+//
+// Read from the platform settings (universalaccess defaults on macOS, the
+// Accessibility registry keys on Windows, GNOME settings on Linux) and polled, since
+// none of them can be watched without native bindings. Changes are emitted as
+// `system-a11y-changed`; native windows apply them through windows::a11y_script and
+// the web app can listen for the same event.
+//
+// The text size ("Make text bigger" on Windows, "Large Text" / the text scaling factor
+// on GNOME) zooms the native windows and the splash, lock and error pages and enlarges
+// the native windows to match (see display.rs), since webviews don't follow it on
+// their own. macOS has no system-wide text size for apps; its "Larger Text" display
+// modes and screen zoom already scale every window, so the scale stays 1 there.
+
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::Duration;
+use serde::Serialize;
+use tauri::AppHandle;
+use crate::display;
+use crate::events::{self, Event};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+/// Windows goes up to 225%; smaller than normal text is not followed
+const MAX_TEXT_SCALE: f64 = 2.25;
+
+static CURRENT: Mutex<Option<A11yPrefs>> = Mutex::new(None);
+
+#[derive(Clone, Copy, PartialEq, Serialize)]
+pub struct A11yPrefs {
+    pub high_contrast: bool,
+    pub reduced_motion: bool,
+    /// OS text size as a factor of the normal size, 1 to MAX_TEXT_SCALE
+    pub text_scale: f64,
+}
+
+impl Default for A11yPrefs {
+    fn default() -> Self {
+        A11yPrefs {
+            high_contrast: false,
+            reduced_motion: false,
+            text_scale: 1.0,
+        }
+    }
+}
+
+#[cfg_attr(not(any(target_os = "linux", target_os = "windows")), allow(dead_code))]
+fn clamp_text_scale(scale: f64) -> f64 {
+    if scale.is_finite() {
+        scale.clamp(1.0, MAX_TEXT_SCALE)
+    } else {
+        1.0
+    }
+}
+
+/// Stdout of a helper command, trimmed; None if it couldn't run
+#[cfg_attr(not(any(target_os = "linux", target_os = "macos", target_os = "windows")), allow(dead_code))]
+pub fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let mut command = Command::new(program);
+    command.args(args);
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        // No console window flashing up on every poll
+        command.creation_flags(0x0800_0000);
+    }
+    let output = command.output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(target_os = "macos")]
+fn detect() -> A11yPrefs {
+    let enabled = |key: &str| command_output("defaults", &["read", "com.apple.universalaccess", key]).as_deref() == Some("1");
+    A11yPrefs {
+        high_contrast: enabled("increaseContrast"),
+        reduced_motion: enabled("reduceMotion"),
+        text_scale: 1.0,
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn detect() -> A11yPrefs {
+    // `reg query` prints "    Flags    REG_SZ    126"; the value is the last field
+    let value = |key: &str, name: &str| {
+        command_output("reg", &["query", key, "/v", name])
+            .and_then(|out| out.lines().find(|l| l.contains(name)).and_then(|l| l.split_whitespace().last().map(String::from)))
+    };
+    // HCF_HIGHCONTRASTON is bit 0 of the HighContrast flags
+    let high_contrast = value(r"HKCU\Control Panel\Accessibility\HighContrast", "Flags")
+        .and_then(|flags| flags.parse::<u32>().ok())
+        .is_some_and(|flags| flags & 1 == 1);
+    // "Animation effects" off in Settings clears MinAnimate
+    let reduced_motion = value(r"HKCU\Control Panel\Desktop\WindowMetrics", "MinAnimate").as_deref() == Some("0");
+    // "Make text bigger" is a percentage, printed as a hex DWORD ("0x7d" for 125%)
+    let text_scale = value(r"HKCU\Software\Microsoft\Accessibility", "TextScaleFactor")
+        .and_then(|percent| u32::from_str_radix(percent.trim_start_matches("0x"), 16).ok())
+        .map_or(1.0, |percent| clamp_text_scale(percent as f64 / 100.0));
+    A11yPrefs { high_contrast, reduced_motion, text_scale }
+}
+
+#[cfg(target_os = "linux")]
+fn detect() -> A11yPrefs {
+    let setting = |schema: &str, key: &str| command_output("gsettings", &["get", schema, key]);
+    let high_contrast = setting("org.gnome.desktop.a11y.interface", "high-contrast").as_deref() == Some("true")
+        || setting("org.gnome.desktop.interface", "gtk-theme").is_some_and(|theme| theme.contains("HighContrast"));
+    // "Large Text" in the accessibility settings sets this to 1.25
+    let text_scale = setting("org.gnome.desktop.interface", "text-scaling-factor")
+        .and_then(|factor| factor.parse::<f64>().ok())
+        .map_or(1.0, clamp_text_scale);
+    A11yPrefs {
+        high_contrast,
+        reduced_motion: setting("org.gnome.desktop.interface", "enable-animations").as_deref() == Some("false"),
+        text_scale,
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn detect() -> A11yPrefs {
+    A11yPrefs::default()
+}
+
+/// Current preferences, detected on first use
+pub fn system_a11y_prefs() -> A11yPrefs {
+    let mut current = CURRENT.lock().unwrap_or_else(|e| e.into_inner());
+    *current.get_or_insert_with(detect)
+}
+
+/// OS text size factor for the native windows
+pub fn text_scale() -> f64 {
+    system_a11y_prefs().text_scale
+}
+
+/// Poll the OS preferences and emit `system-a11y-changed` when they change
+pub fn start_a11y_watcher(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        // Detect once up front so opening a window never waits for the platform tools
+        let _ = tauri::async_runtime::spawn_blocking(system_a11y_prefs).await;
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            let Ok(prefs) = tauri::async_runtime::spawn_blocking(detect).await else {
+                continue;
+            };
+            let previous = {
+                let mut current = CURRENT.lock().unwrap_or_else(|e| e.into_inner());
+                current.replace(prefs)
+            };
+            let Some(previous) = previous.filter(|p| *p != prefs) else {
+                continue;
+            };
+            if previous.text_scale != prefs.text_scale {
+                display::on_text_scale_changed(&app, previous.text_scale, prefs.text_scale);
+            }
+            let _ = events::emit(&app, Event::SystemA11yChanged(&prefs));
+        }
+    });
+}
+
+/// OS high-contrast, reduced-motion and text size preferences
+#[tauri::command]
+pub async fn get_system_a11y_prefs() -> Result<A11yPrefs, String> {
+    tauri::async_runtime::spawn_blocking(system_a11y_prefs)
+        .await
+        .map_err(|e| e.to_string())
+}
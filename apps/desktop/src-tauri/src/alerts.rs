@@ -0,0 +1,235 @@
+// Alerts for unattended installs (`[alerts]` in settings.toml)
+//
+// On a home server nobody sees the error dialog when the sidecar fails to start, so
+// the same events can also go to a webhook (a JSON POST, e.g. for ntfy or a chat
+// bot) and/or an email over SMTP. A watchdog polls the server's /health while it's
+// meant to be running and alerts once it has been unreachable for
+// `alerts.downtime_minutes`, then again when it comes back. A server the user
+// stopped isn't watched.
+//
+// The SMTP password is kept in the keychain, set with `set_alert_smtp_password`.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use lettre::message::header::ContentType;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde_json::json;
+use tauri::{AppHandle, Manager};
+use crate::keychain;
+use crate::logs::{log_line, SharedLogStore};
+use crate::netusage::{self, Subsystem};
+use crate::server::{fetch_health, ServerStatus, SharedServerManager};
+use crate::settings::{AlertSettings, SharedSettings};
+
+pub const SMTP_PASSWORD_KEY: &str = "alerts-smtp-password";
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// A "down" alert went out and no "recovered" one yet
+static ALERTED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Clone, Copy)]
+enum Event {
+    StartFailed,
+    Down,
+    Recovered,
+    Test,
+}
+
+impl Event {
+    fn as_str(self) -> &'static str {
+        match self {
+            Event::StartFailed => "start_failed",
+            Event::Down => "down",
+            Event::Recovered => "recovered",
+            Event::Test => "test",
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            Event::StartFailed => "Server failed to start",
+            Event::Down => "Server is down",
+            Event::Recovered => "Server is back up",
+            Event::Test => "Test alert",
+        }
+    }
+}
+
+fn is_configured(settings: &AlertSettings) -> bool {
+    !settings.webhook_url.is_empty() || !settings.smtp_host.is_empty()
+}
+
+fn host_name() -> String {
+    sysinfo::System::host_name().unwrap_or_else(|| "unknown host".to_string())
+}
+
+async fn post_webhook(url: &str, event: Event, message: &str) -> Result<(), String> {
+    let payload = json!({
+        "event": event.as_str(),
+        "title": event.title(),
+        "message": message,
+        "host": host_name(),
+        "version": env!("CARGO_PKG_VERSION"),
+        "timestamp": chrono::Local::now().to_rfc3339(),
+    });
+    let body = payload.to_string();
+    netusage::record(Subsystem::Alerts, body.len() as u64, 0);
+    let response = reqwest::Client::new()
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(body)
+        .timeout(REQUEST_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| format!("webhook: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("webhook returned {}", response.status()));
+    }
+    Ok(())
+}
+
+async fn send_email(settings: &AlertSettings, event: Event, message: &str) -> Result<(), String> {
+    let email = Message::builder()
+        .from(settings.email_from.parse().map_err(|e| format!("email_from: {}", e))?)
+        .to(settings.email_to.parse().map_err(|e| format!("email_to: {}", e))?)
+        .subject(format!("Moneywright on {}: {}", host_name(), event.title()))
+        .header(ContentType::TEXT_PLAIN)
+        .body(format!("{}\n\nMoneywright {}\n", message, env!("CARGO_PKG_VERSION")))
+        .map_err(|e| format!("email: {}", e))?;
+
+    let transport = if settings.smtp_port == 465 {
+        AsyncSmtpTransport::<Tokio1Executor>::relay(&settings.smtp_host)
+    } else {
+        AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&settings.smtp_host)
+    };
+    let mut transport = transport
+        .map_err(|e| format!("email: {}", e))?
+        .port(settings.smtp_port)
+        .timeout(Some(REQUEST_TIMEOUT));
+    if !settings.smtp_username.is_empty() {
+        let password = keychain::get_secret(SMTP_PASSWORD_KEY)?.unwrap_or_default();
+        transport = transport.credentials(Credentials::new(settings.smtp_username.clone(), password));
+    }
+    transport
+        .build()
+        .send(email)
+        .await
+        .map(|_| ())
+        .map_err(|e| format!("email: {}", e))
+}
+
+/// Deliver to every configured channel, reporting the ones that failed
+async fn deliver(settings: &AlertSettings, event: Event, message: &str) -> Result<(), String> {
+    let mut errors = Vec::new();
+    if !settings.webhook_url.is_empty() {
+        if let Err(e) = post_webhook(&settings.webhook_url, event, message).await {
+            errors.push(e);
+        }
+    }
+    if !settings.smtp_host.is_empty() {
+        if let Err(e) = send_email(settings, event, message).await {
+            errors.push(e);
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join("; "))
+    }
+}
+
+async fn send_alert(app: &AppHandle, event: Event, message: &str) {
+    let settings = app.state::<SharedSettings>().lock().await.get().alerts;
+    if !is_configured(&settings) {
+        return;
+    }
+    let log_store = app.state::<SharedLogStore>().inner().clone();
+    match deliver(&settings, event, message).await {
+        Ok(()) => log_line(app, &log_store, format!("Sent alert: {}", event.title()), "info").await,
+        Err(e) => log_line(app, &log_store, format!("Failed to send alert: {}", e), "error").await,
+    }
+}
+
+/// Alert that the server didn't come up (from startup or a restart)
+pub fn report_start_failure(app: &AppHandle, error: &str) {
+    ALERTED.store(true, Ordering::Relaxed);
+    let app = app.clone();
+    let message = format!("The Moneywright server failed to start: {}", error);
+    tauri::async_runtime::spawn(async move {
+        send_alert(&app, Event::StartFailed, &message).await;
+    });
+}
+
+/// Watch the server's health and alert on prolonged downtime and on recovery
+pub fn start_watchdog(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut down_since: Option<Instant> = None;
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            let settings = app.state::<SharedSettings>().lock().await.get().alerts;
+            if !is_configured(&settings) {
+                down_since = None;
+                ALERTED.store(false, Ordering::Relaxed);
+                continue;
+            }
+
+            let (status, url) = {
+                let manager = app.state::<SharedServerManager>();
+                let mgr = manager.lock().await;
+                (mgr.status().clone(), mgr.url())
+            };
+            let up = match status {
+                // Stopped on purpose (or never started): nothing to watch
+                ServerStatus::Stopped | ServerStatus::Paused => {
+                    down_since = None;
+                    continue;
+                }
+                ServerStatus::Running => fetch_health(&url).await.is_some(),
+                ServerStatus::Starting | ServerStatus::Error(_) => false,
+            };
+
+            if up {
+                down_since = None;
+                if ALERTED.swap(false, Ordering::Relaxed) {
+                    send_alert(&app, Event::Recovered, "The Moneywright server is reachable again.").await;
+                }
+                continue;
+            }
+            let since = *down_since.get_or_insert_with(Instant::now);
+            let limit = Duration::from_secs(settings.downtime_minutes as u64 * 60);
+            if since.elapsed() >= limit && !ALERTED.swap(true, Ordering::Relaxed) {
+                let reason = match status {
+                    ServerStatus::Error(e) => e,
+                    ServerStatus::Starting => "still starting".to_string(),
+                    _ => "not answering health checks".to_string(),
+                };
+                let message = format!(
+                    "The Moneywright server has been unreachable for {} minutes ({}).",
+                    settings.downtime_minutes, reason
+                );
+                send_alert(&app, Event::Down, &message).await;
+            }
+        }
+    });
+}
+
+/// Send a test alert to the configured webhook and email
+#[tauri::command]
+pub async fn send_test_alert(settings: tauri::State<'_, SharedSettings>) -> Result<(), String> {
+    let alerts = settings.lock().await.get().alerts;
+    if !is_configured(&alerts) {
+        return Err("No webhook or SMTP server is configured".to_string());
+    }
+    deliver(&alerts, Event::Test, &format!("Alerts from {} are working.", host_name())).await
+}
+
+/// Store (or with None, remove) the SMTP password for email alerts
+#[tauri::command]
+pub async fn set_alert_smtp_password(password: Option<String>) -> Result<(), String> {
+    match password.filter(|p| !p.is_empty()) {
+        Some(password) => keychain::set_secret(SMTP_PASSWORD_KEY, &password),
+        None => keychain::delete_secret(SMTP_PASSWORD_KEY),
+    }
+}
@@ -0,0 +1,315 @@
+// Opt-in, local-only usage statistics
+//
+// Contract: these counters never leave this machine. This module has no network
+// access, nothing here is sent to the server sidecar, and exports are refused for
+// anything that isn't a plain local path. Recording only happens while
+// `general.usage_stats` is enabled in settings.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use crate::server::SharedServerManager;
+use crate::settings::SharedSettings;
+use crate::windows::open_injected_window;
+
+const STATS_FILE: &str = "usage-stats.json";
+/// Number of startup times kept for the average
+const MAX_STARTUP_SAMPLES: usize = 50;
+const MAX_FEATURE_NAME_LEN: usize = 64;
+
+/// Serializes read-modify-write cycles on the stats file
+static STATS_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct UsageStats {
+    /// When counting started (unix seconds)
+    pub since: u64,
+    pub launches: u64,
+    pub features: BTreeMap<String, u64>,
+    pub startup_ms: Vec<u64>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct UsageReport {
+    pub enabled: bool,
+    pub stats: UsageStats,
+    pub avg_startup_ms: Option<u64>,
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn stats_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(STATS_FILE)
+}
+
+fn read_stats(data_dir: &Path) -> UsageStats {
+    fs::read_to_string(stats_path(data_dir))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn update_stats(data_dir: &Path, f: impl FnOnce(&mut UsageStats)) {
+    let _guard = STATS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut stats = read_stats(data_dir);
+    if stats.since == 0 {
+        stats.since = unix_now();
+    }
+    f(&mut stats);
+
+    match serde_json::to_string_pretty(&stats) {
+        Ok(json) => {
+            if let Err(e) = fs::write(stats_path(data_dir), json) {
+                eprintln!("Warning: Failed to write usage stats: {}", e);
+            }
+        }
+        Err(e) => eprintln!("Warning: Failed to serialize usage stats: {}", e),
+    }
+}
+
+/// Apply `f` to the stored counters if the user opted in
+fn record(app: &AppHandle, f: impl FnOnce(&mut UsageStats) + Send + 'static) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let Some(settings) = app.try_state::<SharedSettings>() else { return };
+        if !settings.lock().await.get().general.usage_stats {
+            return;
+        }
+        let data_dir = app.state::<SharedServerManager>().lock().await.data_dir().clone();
+        update_stats(&data_dir, f);
+    });
+}
+
+/// Count a launch and its startup time
+pub fn record_launch(app: &AppHandle, startup_ms: u64) {
+    record(app, move |stats| {
+        stats.launches += 1;
+        stats.startup_ms.push(startup_ms);
+        if stats.startup_ms.len() > MAX_STARTUP_SAMPLES {
+            let excess = stats.startup_ms.len() - MAX_STARTUP_SAMPLES;
+            stats.startup_ms.drain(..excess);
+        }
+    });
+}
+
+/// Count one use of a feature; names are fixed identifiers, never user content
+pub fn record_feature(app: &AppHandle, feature: &str) {
+    if !is_valid_feature_name(feature) {
+        return;
+    }
+    let feature = feature.to_string();
+    record(app, move |stats| {
+        *stats.features.entry(feature).or_insert(0) += 1;
+    });
+}
+
+fn is_valid_feature_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= MAX_FEATURE_NAME_LEN
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.')
+}
+
+/// Reject destinations that could put the stats on another machine
+fn ensure_local_destination(path: &Path) -> Result<(), String> {
+    let raw = path.to_string_lossy();
+    if raw.contains("://") || raw.starts_with("\\\\") || raw.starts_with("//") {
+        return Err("Usage statistics can only be exported to a local folder".to_string());
+    }
+    if !path.is_absolute() {
+        return Err("Export path must be absolute".to_string());
+    }
+    Ok(())
+}
+
+/// Get the local usage counters and whether recording is enabled
+#[tauri::command]
+pub async fn get_usage_stats(
+    settings: tauri::State<'_, SharedSettings>,
+    manager: tauri::State<'_, SharedServerManager>,
+) -> Result<UsageReport, String> {
+    let enabled = settings.lock().await.get().general.usage_stats;
+    let data_dir = manager.lock().await.data_dir().clone();
+    let stats = read_stats(&data_dir);
+    let avg_startup_ms = if stats.startup_ms.is_empty() {
+        None
+    } else {
+        Some(stats.startup_ms.iter().sum::<u64>() / stats.startup_ms.len() as u64)
+    };
+    Ok(UsageReport { enabled, stats, avg_startup_ms })
+}
+
+/// Count a feature used in the web UI (no-op unless the user opted in)
+#[tauri::command]
+pub async fn record_feature_usage(app: AppHandle, feature: String) -> Result<(), String> {
+    if !is_valid_feature_name(&feature) {
+        return Err(format!("Invalid feature name: {}", feature));
+    }
+    record_feature(&app, &feature);
+    Ok(())
+}
+
+/// Export the counters as JSON (defaults to the Downloads folder) and reveal the file
+#[tauri::command]
+pub async fn export_usage_stats(
+    manager: tauri::State<'_, SharedServerManager>,
+    path: Option<String>,
+) -> Result<String, String> {
+    let data_dir = manager.lock().await.data_dir().clone();
+    let target = match path {
+        Some(p) => PathBuf::from(p),
+        None => dirs::download_dir()
+            .or_else(dirs::home_dir)
+            .ok_or_else(|| "No Downloads folder found".to_string())?
+            .join(format!("moneywright-usage-{}.json", unix_now())),
+    };
+    ensure_local_destination(&target)?;
+
+    let json = serde_json::to_string_pretty(&read_stats(&data_dir)).map_err(|e| e.to_string())?;
+    fs::write(&target, json).map_err(|e| format!("Failed to export usage stats: {}", e))?;
+    if let Some(parent) = target.parent() {
+        let _ = open::that(parent);
+    }
+    Ok(target.to_string_lossy().to_string())
+}
+
+/// Delete all recorded counters
+#[tauri::command]
+pub async fn clear_usage_stats(manager: tauri::State<'_, SharedServerManager>) -> Result<(), String> {
+    let data_dir = manager.lock().await.data_dir().clone();
+    let _guard = STATS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    match fs::remove_file(stats_path(&data_dir)) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("Failed to clear usage stats: {}", e)),
+    }
+}
+
+/// Open the usage statistics window
+pub fn open_usage_window(app: &AppHandle) {
+    // Static UI; counter names are inserted with escaping on the JS side
+    let script = r#"
+        const tauriApi = window.__TAURI__;
+
+        document.documentElement.innerHTML = `
+<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <title>Usage Statistics</title>
+    <style>
+        __BASE_STYLE__
+        #content { flex: 1; overflow-y: auto; padding: 16px; }
+        .notice { margin-bottom: 16px; line-height: 1.5; }
+        label { display: flex; align-items: center; gap: 8px; cursor: pointer; }
+        .summary { display: flex; gap: 24px; margin-bottom: 16px; }
+        .summary .value { font-size: 22px; }
+        table { width: 100%; border-collapse: collapse; }
+        th, td { text-align: left; padding: 6px 0; border-bottom: 1px solid rgba(255, 255, 255, 0.04); }
+        th { color: #71717a; font-weight: 500; }
+        td.count { text-align: right; }
+        h3.section { margin: 24px 0 8px; font-size: 14px; font-weight: 500; }
+    </style>
+</head>
+<body>
+    <div class="toolbar">
+        <label><input type="checkbox" id="enabled"> Record usage statistics</label>
+        <button id="exportBtn" style="margin-left: auto">Export</button>
+        <button id="clearBtn" class="danger">Clear</button>
+    </div>
+    <div id="content">
+        <p class="notice muted">Counters are stored only in this computer's data folder. They are never uploaded or sent to the server.</p>
+        <div class="summary">
+            <div><div class="muted">Launches</div><div class="value" id="launches">0</div></div>
+            <div><div class="muted">Avg. startup</div><div class="value" id="startup">-</div></div>
+            <div><div class="muted">Counting since</div><div class="value" id="since">-</div></div>
+        </div>
+        <table aria-label="Feature usage">
+            <thead><tr><th>Feature</th><th style="text-align: right">Uses</th></tr></thead>
+            <tbody id="features"></tbody>
+        </table>
+        <h3 class="section">Network this month</h3>
+        <p class="notice muted">Data Moneywright downloads and uploads itself, such as updates and exchange rates. Recorded whether or not usage statistics are on.</p>
+        <table aria-label="Network usage">
+            <thead><tr><th>Used for</th><th style="text-align: right">Received</th><th style="text-align: right">Sent</th></tr></thead>
+            <tbody id="network"></tbody>
+        </table>
+        <p class="notice">
+            <label>Monthly limit <input type="number" id="limit" min="0" step="100" style="width: 90px"> MB</label>
+            <span class="muted">0 for none; automatic downloads stop once it's reached</span>
+        </p>
+        <p id="limitStatus" class="warn" role="status" aria-live="polite"></p>
+    </div>
+</body>
+</html>`;
+
+        const $ = id => document.getElementById(id);
+
+        async function refresh() {
+            const report = await tauriApi.core.invoke('get_usage_stats');
+            const s = report.stats;
+            $('enabled').checked = report.enabled;
+            $('launches').textContent = s.launches;
+            $('startup').textContent = report.avg_startup_ms == null ? '-' : report.avg_startup_ms + ' ms';
+            $('since').textContent = s.since ? new Date(s.since * 1000).toLocaleDateString() : '-';
+            const rows = Object.entries(s.features).sort((a, b) => b[1] - a[1]);
+            $('features').innerHTML = rows.length === 0
+                ? '<tr><td class="muted" colspan="2">Nothing recorded yet</td></tr>'
+                : rows.map(([name, count]) => '<tr><td class="mono">' + escapeHtml(name) + '</td><td class="count">' + count + '</td></tr>').join('');
+        }
+
+        function size(bytes) {
+            if (bytes < 1024) return bytes + ' B';
+            if (bytes < 1024 * 1024) return (bytes / 1024).toFixed(1) + ' KB';
+            if (bytes < 1024 * 1024 * 1024) return (bytes / 1024 / 1024).toFixed(1) + ' MB';
+            return (bytes / 1024 / 1024 / 1024).toFixed(2) + ' GB';
+        }
+
+        async function refreshNetwork() {
+            const usage = await tauriApi.core.invoke('get_network_usage', { months: 1 });
+            const month = usage.months[0];
+            $('network').innerHTML = month.subsystems.length === 0
+                ? '<tr><td class="muted" colspan="3">Nothing downloaded yet this month</td></tr>'
+                : month.subsystems.map(s =>
+                    '<tr><td>' + escapeHtml(s.label) + '</td><td class="count">' + size(s.received) + '</td><td class="count">' + size(s.sent) + '</td></tr>'
+                ).join('') + '<tr><th>Total</th><td class="count">' + size(month.received) + '</td><td class="count">' + size(month.sent) + '</td></tr>';
+            if (document.activeElement !== $('limit')) {
+                $('limit').value = usage.limit == null ? 0 : Math.round(usage.limit / 1024 / 1024);
+            }
+            $('limitStatus').textContent = usage.limit_reached ? 'This month\'s limit is reached; automatic downloads wait until next month.' : '';
+        }
+
+        $('limit').onchange = async (e) => {
+            try {
+                await tauriApi.core.invoke('update_settings', { changes: { network: { monthly_limit_mb: Math.max(0, parseInt(e.target.value, 10) || 0) } } });
+                $('limitStatus').textContent = '';
+            } catch (err) {
+                $('limitStatus').textContent = String(err);
+                return;
+            }
+            refreshNetwork();
+        };
+
+        $('enabled').onchange = async (e) => {
+            await tauriApi.core.invoke('update_settings', { changes: { general: { usage_stats: e.target.checked } } });
+            refresh();
+        };
+        $('exportBtn').onclick = () => tauriApi.core.invoke('export_usage_stats');
+        $('clearBtn').onclick = async () => {
+            await tauriApi.core.invoke('clear_usage_stats');
+            refresh();
+        };
+        refresh();
+        refreshNetwork();
+    "#;
+
+    open_injected_window(app, "usage", "Usage Statistics", (560.0, 520.0), true, script);
+}
@@ -0,0 +1,246 @@
+// Running on the wrong architecture: the x64 build under Rosetta on Apple Silicon, or
+// under emulation on ARM Windows
+//
+// Everything works translated, just slower, and the server (PDF parsing, imports)
+// feels it most. At startup the mismatch is logged and the user is told once per
+// version. The server then runs natively when it can: from a sidecar bundled next to
+// the app as `moneywright-<arch>`, or from the CLI build of the same release, which is
+// downloaded into `<data dir>/sidecars/<version>/` and used from the next start. The
+// desktop app itself stays translated until the native installer replaces it.
+
+use std::fs;
+use std::io::{Cursor, Read};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use crate::logs::{log_line, SharedLogStore};
+use crate::netusage::{self, Subsystem};
+use crate::notifications::{self, Kind};
+use crate::scheduler;
+use crate::server::{sidecar_path, SharedServerManager};
+
+const RELEASES_URL: &str = "https://github.com/moneywright/moneywright/releases/download";
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(600);
+const SIDECARS_DIR: &str = "sidecars";
+/// Holds the version the translation notice was last shown for
+const NOTICE_FILE: &str = "arch-notice";
+
+#[derive(Clone, Copy)]
+pub struct Translation {
+    /// The machine's architecture, as in release file names ("arm64")
+    pub native: &'static str,
+    /// What this build was compiled for ("x64")
+    pub build: &'static str,
+    /// What translates it
+    pub layer: &'static str,
+}
+
+#[cfg(target_os = "macos")]
+fn detect_translation() -> Option<Translation> {
+    // 1 when translated by Rosetta, 0 when native; missing on Intel Macs
+    let output = std::process::Command::new("sysctl")
+        .args(["-in", "sysctl.proc_translated"])
+        .output()
+        .ok()?;
+    (String::from_utf8_lossy(&output.stdout).trim() == "1").then_some(Translation {
+        native: "arm64",
+        build: "x64",
+        layer: "Rosetta",
+    })
+}
+
+#[cfg(windows)]
+fn detect_translation() -> Option<Translation> {
+    use windows_sys::Win32::System::SystemInformation::{IMAGE_FILE_MACHINE_ARM64, IMAGE_FILE_MACHINE_UNKNOWN};
+    use windows_sys::Win32::System::Threading::{GetCurrentProcess, IsWow64Process2};
+
+    if !cfg!(target_arch = "x86_64") {
+        return None;
+    }
+    let mut process = IMAGE_FILE_MACHINE_UNKNOWN;
+    let mut native = IMAGE_FILE_MACHINE_UNKNOWN;
+    // SAFETY: both pointers are to live locals; the pseudo handle needs no closing
+    let ok = unsafe { IsWow64Process2(GetCurrentProcess(), &mut process, &mut native) };
+    (ok != 0 && native == IMAGE_FILE_MACHINE_ARM64).then_some(Translation {
+        native: "arm64",
+        build: "x64",
+        layer: "x64 emulation",
+    })
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn detect_translation() -> Option<Translation> {
+    None
+}
+
+/// Whether this build runs translated on a machine of another architecture
+pub fn translation() -> Option<Translation> {
+    static TRANSLATION: OnceLock<Option<Translation>> = OnceLock::new();
+    *TRANSLATION.get_or_init(detect_translation)
+}
+
+fn os_name() -> &'static str {
+    match std::env::consts::OS {
+        "macos" => "darwin",
+        os => os,
+    }
+}
+
+fn binary_name() -> String {
+    format!("moneywright{}", std::env::consts::EXE_SUFFIX)
+}
+
+fn downloaded_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(SIDECARS_DIR).join(env!("CARGO_PKG_VERSION")).join(binary_name())
+}
+
+fn bundled_path(translation: &Translation) -> Option<PathBuf> {
+    let default = sidecar_path()?;
+    Some(default.with_file_name(format!("moneywright-{}{}", translation.native, std::env::consts::EXE_SUFFIX)))
+}
+
+/// A native server binary to run instead of the translated sidecar, if there is one
+pub fn native_sidecar(data_dir: &Path) -> Option<PathBuf> {
+    let translation = translation()?;
+    bundled_path(&translation)
+        .into_iter()
+        .chain([downloaded_path(data_dir)])
+        .find(|path| fs::metadata(path).is_ok_and(|meta| meta.len() > 0))
+}
+
+/// Pull the server binary out of a CLI release archive
+fn extract_binary(archive: &[u8], zipped: bool) -> Result<Vec<u8>, String> {
+    let name = binary_name();
+    let mut binary = Vec::new();
+    if zipped {
+        let mut zip = zip::ZipArchive::new(Cursor::new(archive)).map_err(|e| e.to_string())?;
+        let index = (0..zip.len())
+            .find(|&i| zip.name_for_index(i).is_some_and(|n| Path::new(n).file_name().is_some_and(|f| f == name.as_str())))
+            .ok_or_else(|| format!("{} isn't in the archive", name))?;
+        zip.by_index(index).map_err(|e| e.to_string())?.read_to_end(&mut binary).map_err(|e| e.to_string())?;
+        return Ok(binary);
+    }
+    let mut tar = tar::Archive::new(flate2::read::GzDecoder::new(archive));
+    for entry in tar.entries().map_err(|e| e.to_string())? {
+        let mut entry = entry.map_err(|e| e.to_string())?;
+        let matches = entry.path().is_ok_and(|p| p.file_name().is_some_and(|f| f == name.as_str()));
+        if matches {
+            entry.read_to_end(&mut binary).map_err(|e| e.to_string())?;
+            return Ok(binary);
+        }
+    }
+    Err(format!("{} isn't in the archive", name))
+}
+
+/// Save the binary for this version and drop the ones kept for earlier versions
+fn install_binary(data_dir: &Path, binary: &[u8]) -> Result<PathBuf, String> {
+    let path = downloaded_path(data_dir);
+    let dir = path.parent().ok_or("Invalid sidecar path")?;
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let partial = path.with_extension("partial");
+    fs::write(&partial, binary).map_err(|e| format!("Failed to write {}: {}", partial.display(), e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&partial, fs::Permissions::from_mode(0o755)).map_err(|e| e.to_string())?;
+    }
+    fs::rename(&partial, &path).map_err(|e| format!("Failed to save {}: {}", path.display(), e))?;
+
+    if let Ok(entries) = fs::read_dir(data_dir.join(SIDECARS_DIR)) {
+        for entry in entries.flatten() {
+            if entry.file_name() != env!("CARGO_PKG_VERSION") {
+                let _ = fs::remove_dir_all(entry.path());
+            }
+        }
+    }
+    Ok(path)
+}
+
+/// Download the native CLI build of this release, None if the release doesn't have one
+async fn download_native(data_dir: &Path, translation: &Translation) -> Result<Option<PathBuf>, String> {
+    let zipped = cfg!(windows);
+    let file = format!(
+        "moneywright-{}-{}.{}",
+        os_name(),
+        translation.native,
+        if zipped { "zip" } else { "tar.gz" }
+    );
+    let url = format!("{}/v{}/{}", RELEASES_URL, env!("CARGO_PKG_VERSION"), file);
+    let response = reqwest::Client::new()
+        .get(&url)
+        .timeout(DOWNLOAD_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to download {}: {}", file, e))?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !response.status().is_success() {
+        return Err(format!("Failed to download {}: {}", file, response.status()));
+    }
+    let archive = response.bytes().await.map_err(|e| format!("Failed to download {}: {}", file, e))?;
+    netusage::record(Subsystem::NativeServer, 0, archive.len() as u64);
+
+    let data_dir = data_dir.to_path_buf();
+    tauri::async_runtime::spawn_blocking(move || {
+        let binary = extract_binary(&archive, zipped)?;
+        install_binary(&data_dir, &binary).map(Some)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Whether the translation notice still has to be shown for this version
+fn take_notice(data_dir: &Path) -> bool {
+    let path = data_dir.join(NOTICE_FILE);
+    if fs::read_to_string(&path).is_ok_and(|v| v.trim() == env!("CARGO_PKG_VERSION")) {
+        return false;
+    }
+    let _ = fs::write(&path, env!("CARGO_PKG_VERSION"));
+    true
+}
+
+/// Report a translated build and fetch a native server for the next start
+pub fn start(app: AppHandle) {
+    let Some(translation) = translation() else {
+        return;
+    };
+    tauri::async_runtime::spawn(async move {
+        let log_store = app.state::<SharedLogStore>().inner().clone();
+        let data_dir = app.state::<SharedServerManager>().lock().await.data_dir().clone();
+        let msg = format!(
+            "Moneywright's {} build is running through {} on this {} computer; it works, but more slowly",
+            translation.build, translation.layer, translation.native
+        );
+        log_line(&app, &log_store, msg, "error").await;
+        if take_notice(&data_dir) {
+            let body = format!(
+                "This is the {} version running through {}. Download the {} version of Moneywright for full speed.",
+                translation.build, translation.layer, translation.native
+            );
+            notifications::notify(&app, Kind::Updates, "Moneywright isn't running natively", body).await;
+        }
+
+        if let Some(path) = native_sidecar(&data_dir) {
+            log_line(&app, &log_store, format!("Running the native server from {}", path.display()), "info").await;
+            return;
+        }
+        scheduler::wait_for_heavy_work(&app, "Native server download").await;
+        if let Some(reason) = netusage::limit_reason(&app).await {
+            log_line(&app, &log_store, format!("Native server download skipped: {}", reason), "info").await;
+            return;
+        }
+        match download_native(&data_dir, &translation).await {
+            Ok(Some(path)) => {
+                let msg = format!("Downloaded the native server to {}; it's used from the next start", path.display());
+                log_line(&app, &log_store, msg, "info").await;
+            }
+            Ok(None) => {
+                let msg = format!("No native {} server is published for this release", translation.native);
+                log_line(&app, &log_store, msg, "info").await;
+            }
+            Err(e) => log_line(&app, &log_store, e, "error").await,
+        }
+    });
+}
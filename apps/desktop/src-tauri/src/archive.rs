@@ -0,0 +1,371 @@
+// Full data archive: everything the app stores, in open formats
+//
+// "Download All My Data" writes one zip that stays readable without Moneywright:
+//
+//   README.txt           what each part is and how to check it
+//   manifest.json        archive format, app and schema version, row counts, and the
+//                        size and SHA-256 of every other file
+//   database/app.db      consistent SQLite snapshot of the whole database
+//   database/schema.sql  CREATE statements for the tables
+//   tables/<name>.csv    one CSV per table (header row, blobs as hex)
+//   tables/<name>.json   the same rows as a JSON array of objects
+//   attachments/...      stored documents, as they are on disk
+//
+// Unlike backups, the archive is meant to leave the app, so it carries its own
+// documentation and checksums.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use rusqlite::types::ValueRef;
+use rusqlite::{Connection, OpenFlags};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, State};
+use crate::attachments::{list_attachments, Attachment};
+use crate::backup::{snapshot, sqlite_db_path};
+use crate::jobs::{start_job, JobHandle, JobKind};
+use crate::server::{read_database_url, SharedServerManager};
+
+/// Bumped when the layout above changes incompatibly
+const FORMAT_VERSION: u32 = 1;
+
+const README: &str = "\
+Moneywright data archive
+========================
+
+Everything Moneywright stored for you when this archive was created.
+None of it needs Moneywright to read.
+
+manifest.json
+    Archive format version, app version, database schema version (number of
+    applied migrations and the newest one), row count per table, and the size
+    and SHA-256 checksum of every other file in this archive.
+
+database/app.db
+    The complete database as a SQLite 3 file. Open it with any SQLite tool,
+    e.g. `sqlite3 database/app.db`, or restore it into Moneywright from the
+    Backups window.
+
+database/schema.sql
+    CREATE statements for every table, describing columns and types.
+
+tables/<name>.csv, tables/<name>.json
+    The rows of each table. CSV files start with a header row; JSON files are
+    arrays of objects keyed by column name. Amounts are plain numbers in the
+    currency given by the row's currency column, dates are ISO 8601. Binary
+    values are written as hexadecimal.
+
+attachments/
+    Statements, policies and other documents you uploaded, unchanged.
+
+Checking the archive
+    Every file listed in manifest.json must exist with the given size, and
+    `sha256sum <file>` (or `shasum -a 256 <file>`) must print the listed hash.
+";
+
+#[derive(Serialize)]
+struct ManifestFile {
+    path: String,
+    size: u64,
+    sha256: String,
+}
+
+#[derive(Serialize)]
+struct SchemaVersion {
+    /// Applied migrations
+    migrations: i64,
+    /// Hash of the newest migration
+    latest: Option<String>,
+}
+
+#[derive(Serialize)]
+struct Manifest {
+    format: &'static str,
+    format_version: u32,
+    app_version: String,
+    created_at: String,
+    schema: SchemaVersion,
+    /// Row count per table
+    tables: BTreeMap<String, i64>,
+    files: Vec<ManifestFile>,
+}
+
+/// Passes bytes through to the archive while counting and hashing them
+struct Hashing<W: Write> {
+    inner: W,
+    hasher: Sha256,
+    size: u64,
+}
+
+impl<W: Write> Write for Hashing<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+struct ArchiveWriter {
+    zip: zip::ZipWriter<fs::File>,
+    files: Vec<ManifestFile>,
+}
+
+impl ArchiveWriter {
+    /// Add one file, filled by `write`, and record its checksum
+    fn add(&mut self, path: &str, write: impl FnOnce(&mut dyn Write) -> Result<(), String>) -> Result<(), String> {
+        let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        self.zip
+            .start_file(path, options)
+            .map_err(|e| format!("Failed to add {}: {}", path, e))?;
+        let mut out = Hashing {
+            inner: &mut self.zip,
+            hasher: Sha256::new(),
+            size: 0,
+        };
+        write(&mut out)?;
+        self.files.push(ManifestFile {
+            path: path.to_string(),
+            size: out.size,
+            sha256: hex::encode(out.hasher.finalize()),
+        });
+        Ok(())
+    }
+
+    fn add_bytes(&mut self, path: &str, bytes: &[u8]) -> Result<(), String> {
+        self.add(path, |out| out.write_all(bytes).map_err(|e| format!("Failed to add {}: {}", path, e)))
+    }
+
+    fn add_file(&mut self, path: &str, source: &Path) -> Result<(), String> {
+        self.add(path, |out| {
+            let mut file = fs::File::open(source).map_err(|e| format!("Failed to read {}: {}", source.display(), e))?;
+            io::copy(&mut file, out).map(|_| ()).map_err(|e| format!("Failed to add {}: {}", path, e))
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tables
+// ---------------------------------------------------------------------------
+
+fn table_names(conn: &Connection) -> Result<Vec<(String, String)>, String> {
+    let mut stmt = conn
+        .prepare("SELECT name, sql FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name")
+        .map_err(|e| e.to_string())?;
+    let tables = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get::<_, Option<String>>(1)?.unwrap_or_default())))
+        .map_err(|e| e.to_string())?
+        .flatten()
+        .collect();
+    Ok(tables)
+}
+
+fn json_value(value: ValueRef) -> Value {
+    match value {
+        ValueRef::Null => Value::Null,
+        ValueRef::Integer(i) => json!(i),
+        ValueRef::Real(f) => json!(f),
+        ValueRef::Text(t) => Value::String(String::from_utf8_lossy(t).to_string()),
+        ValueRef::Blob(b) => Value::String(hex::encode(b)),
+    }
+}
+
+fn csv_value(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// All rows of a table as column names plus JSON values
+fn read_table(conn: &Connection, table: &str) -> Result<(Vec<String>, Vec<Vec<Value>>), String> {
+    let sql = format!("SELECT * FROM \"{}\"", table.replace('"', "\"\""));
+    let mut stmt = conn.prepare(&sql).map_err(|e| format!("Failed to read {}: {}", table, e))?;
+    let columns: Vec<String> = stmt.column_names().into_iter().map(String::from).collect();
+    let mut rows = Vec::new();
+    let mut query = stmt.query([]).map_err(|e| format!("Failed to read {}: {}", table, e))?;
+    while let Some(row) = query.next().map_err(|e| format!("Failed to read {}: {}", table, e))? {
+        let mut values = Vec::with_capacity(columns.len());
+        for index in 0..columns.len() {
+            values.push(row.get_ref(index).map(json_value).unwrap_or(Value::Null));
+        }
+        rows.push(values);
+    }
+    Ok((columns, rows))
+}
+
+fn table_csv(columns: &[String], rows: &[Vec<Value>]) -> Result<Vec<u8>, String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(columns).map_err(|e| e.to_string())?;
+    for row in rows {
+        writer
+            .write_record(row.iter().map(csv_value))
+            .map_err(|e| e.to_string())?;
+    }
+    writer.into_inner().map_err(|e| e.to_string())
+}
+
+fn table_json(columns: &[String], rows: Vec<Vec<Value>>) -> Result<Vec<u8>, String> {
+    let objects: Vec<Value> = rows
+        .into_iter()
+        .map(|row| Value::Object(columns.iter().cloned().zip(row).collect::<Map<_, _>>()))
+        .collect();
+    serde_json::to_vec_pretty(&objects).map_err(|e| e.to_string())
+}
+
+fn schema_version(conn: &Connection) -> SchemaVersion {
+    let migrations = conn
+        .query_row("SELECT COUNT(*) FROM __drizzle_migrations", [], |row| row.get::<_, i64>(0))
+        .unwrap_or(0);
+    let latest = conn
+        .query_row("SELECT hash FROM __drizzle_migrations ORDER BY created_at DESC LIMIT 1", [], |row| row.get(0))
+        .ok();
+    SchemaVersion { migrations, latest }
+}
+
+// ---------------------------------------------------------------------------
+// Archive
+// ---------------------------------------------------------------------------
+
+fn write_archive(
+    job: &JobHandle<tauri::Wry>,
+    app_version: String,
+    snapshot_path: &Path,
+    attachments: &[Attachment],
+    target: &Path,
+) -> Result<(), String> {
+    let file = fs::File::create(target).map_err(|e| format!("Failed to create {}: {}", target.display(), e))?;
+    let mut archive = ArchiveWriter {
+        zip: zip::ZipWriter::new(file),
+        files: Vec::new(),
+    };
+    let cancelled = || Err("Export cancelled".to_string());
+
+    let conn = Connection::open_with_flags(snapshot_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| format!("Failed to open database snapshot: {}", e))?;
+    let tables = table_names(&conn)?;
+    // Tables and attachments make up nearly all of the work
+    let total = (tables.len() + attachments.len() + 1) as f64;
+
+    job.progress(Some(0.0), Some("Database".to_string()));
+    archive.add_file("database/app.db", snapshot_path)?;
+    let schema: Vec<String> = tables
+        .iter()
+        .filter(|(_, sql)| !sql.is_empty())
+        .map(|(_, sql)| format!("{};\n", sql))
+        .collect();
+    archive.add_bytes("database/schema.sql", schema.join("\n").as_bytes())?;
+
+    let mut row_counts = BTreeMap::new();
+    for (index, (table, _)) in tables.iter().enumerate() {
+        if job.is_cancelled() {
+            return cancelled();
+        }
+        job.progress(Some((index + 1) as f64 / total), Some(format!("Table {}", table)));
+        let (columns, rows) = read_table(&conn, table)?;
+        row_counts.insert(table.clone(), rows.len() as i64);
+        archive.add_bytes(&format!("tables/{}.csv", table), &table_csv(&columns, &rows)?)?;
+        archive.add_bytes(&format!("tables/{}.json", table), &table_json(&columns, rows)?)?;
+    }
+
+    for (index, attachment) in attachments.iter().enumerate() {
+        if job.is_cancelled() {
+            return cancelled();
+        }
+        job.progress(Some((tables.len() + index + 1) as f64 / total), Some("Attachments".to_string()));
+        let name = format!("attachments/{}", attachment.name.replace('\\', "/"));
+        archive.add_file(&name, Path::new(&attachment.path))?;
+    }
+
+    archive.add_bytes("README.txt", README.as_bytes())?;
+    let manifest = Manifest {
+        format: "moneywright-archive",
+        format_version: FORMAT_VERSION,
+        app_version,
+        created_at: chrono::Local::now().to_rfc3339(),
+        schema: schema_version(&conn),
+        tables: row_counts,
+        files: archive.files,
+    };
+    let manifest = serde_json::to_vec_pretty(&manifest).map_err(|e| e.to_string())?;
+    let mut zip = archive.zip;
+    zip.start_file("manifest.json", zip::write::SimpleFileOptions::default())
+        .and_then(|_| zip.write_all(&manifest).map_err(Into::into))
+        .map_err(|e| format!("Failed to add manifest: {}", e))?;
+    zip.finish().map_err(|e| format!("Failed to write archive: {}", e))?;
+    Ok(())
+}
+
+/// Snapshot the database, then build the archive via staging files removed on failure or cancel
+fn build(job: &JobHandle<tauri::Wry>, app_version: String, data_dir: &Path, target: &Path) -> Result<(), String> {
+    let db_path = sqlite_db_path(data_dir);
+    if !db_path.exists() {
+        return Err(format!("Database not found at {}", db_path.display()));
+    }
+    let file_name = target.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let staging = target.with_file_name(format!(".{}.tmp", file_name));
+    let snapshot_path = target.with_file_name(format!(".{}.db.tmp", file_name));
+    let _ = fs::remove_file(&snapshot_path);
+
+    job.progress(None, Some("Copying database".to_string()));
+    let token = job.token();
+    let result = snapshot(&db_path, &snapshot_path, Some(&token))
+        .and_then(|_| write_archive(job, app_version, &snapshot_path, &list_attachments(data_dir), &staging))
+        .and_then(|_| fs::rename(&staging, target).map_err(|e| format!("Failed to write {}: {}", target.display(), e)));
+    let _ = fs::remove_file(&snapshot_path);
+    if result.is_err() {
+        let _ = fs::remove_file(&staging);
+    }
+    result
+}
+
+/// Write everything the app stores to one documented zip, in Downloads unless `destination` is given
+pub async fn export_all(app: &AppHandle, data_dir: PathBuf, destination: Option<String>) -> Result<PathBuf, String> {
+    if read_database_url(&data_dir).is_some() {
+        return Err("Full exports are only supported for SQLite databases (use pg_dump for PostgreSQL)".to_string());
+    }
+    let target = match destination {
+        Some(d) => PathBuf::from(d),
+        None => dirs::download_dir()
+            .or_else(dirs::home_dir)
+            .ok_or_else(|| "No Downloads folder found".to_string())?
+            .join(format!("moneywright-data-{}.zip", chrono::Local::now().format("%Y-%m-%d"))),
+    };
+
+    let app_version = app.package_info().version.to_string();
+    let job = start_job(app, JobKind::Export, "Exporting all data", true);
+    let (job, result) = tauri::async_runtime::spawn_blocking(move || {
+        let result = build(&job, app_version, &data_dir, &target).map(|_| target);
+        (job, result)
+    })
+    .await
+    .map_err(|e| format!("Export task failed: {}", e))?;
+    job.finish(&result);
+
+    let target = result?;
+    if let Some(parent) = target.parent() {
+        let _ = open::that(parent);
+    }
+    Ok(target)
+}
+
+/// Download all data as a single archive, returning its path
+#[tauri::command]
+pub async fn export_all_data(
+    app: AppHandle,
+    manager: State<'_, SharedServerManager>,
+    destination: Option<String>,
+) -> Result<String, String> {
+    let data_dir = manager.lock().await.data_dir().clone();
+    export_all(&app, data_dir, destination)
+        .await
+        .map(|path| path.to_string_lossy().to_string())
+}
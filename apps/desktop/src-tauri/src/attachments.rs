@@ -0,0 +1,428 @@
+// Attachment storage: usage, orphan detection and per-year archives
+//
+// The API keeps every uploaded statement, insurance policy and loan document as
+// `attachments/<kind>/<record id>.<ext>` in the data dir (see apps/api/src/lib/attachments.ts)
+// and removes it with its record. A file is an orphan when no record of the signed-in
+// user refers to it, e.g. after a profile was deleted. Orphans are only reported:
+// with several users one user's files look orphaned to the others, so removing them
+// is left to the user. Uploads are PDF, CSV and Excel files, so there are no receipt
+// images to recompress; archives are plain zips.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use chrono::{Datelike, Local, Timelike};
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, State};
+use crate::importer::session_cookies;
+use crate::jobs::{start_job, CancelToken, JobKind};
+use crate::server::{get_server_url, SharedServerManager};
+use crate::transport;
+use crate::windows::open_injected_window;
+
+const ATTACHMENTS_DIR: &str = "attachments";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Attachment folders, with the API list of the records owning their files and the key holding the array
+const REFERENCE_SOURCES: &[(&str, &str, &str)] = &[
+    ("statements", "/api/statements", "statements"),
+    ("insurance", "/api/insurance/policies", "policies"),
+    ("loans", "/api/loans", "loans"),
+];
+
+#[derive(Clone, Serialize)]
+pub struct Attachment {
+    pub path: String,
+    /// Path relative to the attachments folder
+    pub name: String,
+    pub size: u64,
+    pub modified: u64,
+    pub year: i32,
+}
+
+#[derive(Default, Serialize)]
+pub struct YearUsage {
+    pub year: i32,
+    pub files: usize,
+    pub size: u64,
+}
+
+#[derive(Serialize)]
+pub struct AttachmentUsage {
+    pub directory: String,
+    pub files: usize,
+    pub size: u64,
+    /// Newest year first
+    pub years: Vec<YearUsage>,
+    /// Size per lowercase extension ("" for none)
+    pub extensions: BTreeMap<String, u64>,
+}
+
+fn attachments_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join(ATTACHMENTS_DIR)
+}
+
+fn list_files(dir: &Path, root: &Path, out: &mut Vec<Attachment>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+        if meta.is_dir() {
+            list_files(&path, root, out);
+            continue;
+        }
+        // Skip staging files and OS metadata such as .DS_Store
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !meta.is_file() || name.starts_with('.') {
+            continue;
+        }
+        let modified_at = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+        out.push(Attachment {
+            name: path.strip_prefix(root).unwrap_or(&path).to_string_lossy().to_string(),
+            path: path.to_string_lossy().to_string(),
+            size: meta.len(),
+            modified: modified_at
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            year: chrono::DateTime::<Local>::from(modified_at).year(),
+        });
+    }
+}
+
+/// All attachment files, oldest first
+pub fn list_attachments(data_dir: &Path) -> Vec<Attachment> {
+    let root = attachments_dir(data_dir);
+    let mut files = Vec::new();
+    list_files(&root, &root, &mut files);
+    files.sort_by_key(|f| f.modified);
+    files
+}
+
+fn extension(name: &str) -> String {
+    Path::new(name)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+// ---------------------------------------------------------------------------
+// Orphans
+// ---------------------------------------------------------------------------
+
+/// `<kind>/<record id>` of every record that can own an attachment
+async fn fetch_references(app: &AppHandle) -> Result<HashSet<String>, String> {
+    let cookies = session_cookies(app)?;
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let mut references = HashSet::new();
+    for (kind, path, key) in REFERENCE_SOURCES {
+        let request = client
+            .get(format!("{}{}", get_server_url(), path))
+            .header(reqwest::header::COOKIE, &cookies);
+        let response = transport::send(request)
+            .await
+            .map_err(|e| format!("Request to {} failed: {}", path, e))?;
+        if !response.status().is_success() {
+            // Without a complete reference list, every file would look orphaned
+            return Err(format!("{} returned {}", path, response.status()));
+        }
+        let body: Value = response.json().await.map_err(|e| format!("Invalid response from {}: {}", path, e))?;
+        for record in body[key].as_array().into_iter().flatten() {
+            if let Some(id) = record["id"].as_str().filter(|id| !id.is_empty()) {
+                references.insert(format!("{}/{}", kind, id));
+            }
+        }
+    }
+    Ok(references)
+}
+
+fn is_referenced(attachment: &Attachment, references: &HashSet<String>) -> bool {
+    let path = Path::new(&attachment.name);
+    let kind = path.parent().and_then(|p| p.to_str());
+    let stem = path.file_stem().and_then(|s| s.to_str());
+    let (Some(kind), Some(stem)) = (kind, stem) else {
+        return false;
+    };
+    references.contains(&format!("{}/{}", kind, stem))
+}
+
+// ---------------------------------------------------------------------------
+// Archives
+// ---------------------------------------------------------------------------
+
+fn zip_time(modified: u64) -> zip::DateTime {
+    let local = chrono::DateTime::from_timestamp(modified as i64, 0)
+        .map(|t| t.with_timezone(&Local).naive_local());
+    local
+        .and_then(|t| {
+            zip::DateTime::from_date_and_time(
+                t.year().clamp(1980, 2107) as u16,
+                t.month() as u8,
+                t.day() as u8,
+                t.hour() as u8,
+                t.minute() as u8,
+                t.second() as u8,
+            )
+            .ok()
+        })
+        .unwrap_or_default()
+}
+
+fn write_archive(files: &[Attachment], target: &Path, cancel: &CancelToken) -> Result<(), String> {
+    let file = fs::File::create(target).map_err(|e| format!("Failed to create {}: {}", target.display(), e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    for attachment in files {
+        if cancel.is_cancelled() {
+            return Err("Export cancelled".to_string());
+        }
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated)
+            .last_modified_time(zip_time(attachment.modified));
+        zip.start_file(attachment.name.replace('\\', "/"), options)
+            .map_err(|e| format!("Failed to add {}: {}", attachment.name, e))?;
+        let mut source = fs::File::open(&attachment.path)
+            .map_err(|e| format!("Failed to read {}: {}", attachment.name, e))?;
+        io::copy(&mut source, &mut zip).map_err(|e| format!("Failed to add {}: {}", attachment.name, e))?;
+    }
+    zip.finish().map_err(|e| format!("Failed to write archive: {}", e))?;
+    Ok(())
+}
+
+/// Zip one year of attachments via a staging file, removed on failure or cancel
+fn archive_year(files: Vec<Attachment>, target: PathBuf, cancel: CancelToken) -> Result<PathBuf, String> {
+    let file_name = target.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let staging = target.with_file_name(format!(".{}.tmp", file_name));
+    let result = write_archive(&files, &staging, &cancel)
+        .and_then(|_| fs::rename(&staging, &target).map_err(|e| format!("Failed to write {}: {}", target.display(), e)));
+    if result.is_err() {
+        let _ = fs::remove_file(&staging);
+    }
+    result.map(|_| target)
+}
+
+// ---------------------------------------------------------------------------
+// Commands
+// ---------------------------------------------------------------------------
+
+/// Size and file counts of the attachments folder, per year and extension
+#[tauri::command]
+pub async fn get_attachment_usage(manager: State<'_, SharedServerManager>) -> Result<AttachmentUsage, String> {
+    let data_dir = manager.lock().await.data_dir().clone();
+    let files = list_attachments(&data_dir);
+
+    let mut years: BTreeMap<i32, YearUsage> = BTreeMap::new();
+    let mut extensions = BTreeMap::new();
+    for file in &files {
+        let usage = years.entry(file.year).or_default();
+        usage.year = file.year;
+        usage.files += 1;
+        usage.size += file.size;
+        *extensions.entry(extension(&file.name)).or_insert(0) += file.size;
+    }
+    Ok(AttachmentUsage {
+        directory: attachments_dir(&data_dir).to_string_lossy().to_string(),
+        files: files.len(),
+        size: files.iter().map(|f| f.size).sum(),
+        years: years.into_values().rev().collect(),
+        extensions,
+    })
+}
+
+/// Attachments that no statement, policy or loan refers to
+#[tauri::command]
+pub async fn find_orphan_attachments(
+    app: AppHandle,
+    manager: State<'_, SharedServerManager>,
+) -> Result<Vec<Attachment>, String> {
+    let data_dir = manager.lock().await.data_dir().clone();
+    let files = list_attachments(&data_dir);
+    if files.is_empty() {
+        return Ok(files);
+    }
+    let references = fetch_references(&app).await?;
+    Ok(files.into_iter().filter(|f| !is_referenced(f, &references)).collect())
+}
+
+/// Zip all attachments from one year into the Downloads folder (or `destination`)
+#[tauri::command]
+pub async fn export_attachments(
+    app: AppHandle,
+    manager: State<'_, SharedServerManager>,
+    year: i32,
+    destination: Option<String>,
+) -> Result<String, String> {
+    let data_dir = manager.lock().await.data_dir().clone();
+    let files: Vec<Attachment> = list_attachments(&data_dir)
+        .into_iter()
+        .filter(|f| f.year == year)
+        .collect();
+    if files.is_empty() {
+        return Err(format!("No attachments from {}", year));
+    }
+    let target = match destination {
+        Some(d) => PathBuf::from(d),
+        None => dirs::download_dir()
+            .or_else(dirs::home_dir)
+            .ok_or_else(|| "No Downloads folder found".to_string())?
+            .join(format!("moneywright-attachments-{}.zip", year)),
+    };
+
+    let job = start_job(&app, JobKind::Export, format!("Exporting {} attachments", year), true);
+    let token = job.token();
+    let result = tauri::async_runtime::spawn_blocking(move || archive_year(files, target, token))
+        .await
+        .map_err(|e| format!("Export task failed: {}", e))
+        .and_then(|r| r);
+    job.finish(&result);
+
+    let target = result?;
+    if let Some(parent) = target.parent() {
+        let _ = open::that(parent);
+    }
+    Ok(target.to_string_lossy().to_string())
+}
+
+/// Open the attachments folder in the file manager
+#[tauri::command]
+pub async fn open_attachments_folder(manager: State<'_, SharedServerManager>) -> Result<(), String> {
+    let dir = attachments_dir(manager.lock().await.data_dir());
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    open::that(&dir).map_err(|e| e.to_string())
+}
+
+pub fn open_attachments_window(app: &AppHandle) {
+    // Static UI; file names are inserted with escaping on the JS side
+    let script = r#"
+        const tauriApi = window.__TAURI__;
+
+        document.documentElement.innerHTML = `
+<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <title>Attachments</title>
+    <style>
+        __BASE_STYLE__
+        #content { flex: 1; overflow-y: auto; padding: 12px 16px; }
+        h2 { font-size: 14px; margin: 16px 0 8px; }
+        h2:first-child { margin-top: 0; }
+        table { width: 100%; border-collapse: collapse; }
+        th, td { text-align: left; padding: 6px 8px 6px 0; border-bottom: 1px solid rgba(255, 255, 255, 0.04); white-space: nowrap; }
+        th { color: #71717a; font-weight: 500; }
+        td.actions { text-align: right; }
+        td.actions button { padding: 3px 8px; font-size: 12px; }
+    </style>
+</head>
+<body>
+    <div class="toolbar">
+        <button id="orphansBtn" class="primary">Find Orphans</button>
+        <button id="folderBtn">Open Folder</button>
+        <button id="refreshBtn">Refresh</button>
+        <span id="status" class="muted" role="status" style="margin-left: auto"></span>
+    </div>
+    <div id="content">
+        <p id="summary" class="muted"></p>
+        <h2>By year</h2>
+        <table aria-label="Attachments by year">
+            <thead><tr><th>Year</th><th>Files</th><th>Size</th><th></th></tr></thead>
+            <tbody id="years"></tbody>
+        </table>
+        <h2>By type</h2>
+        <table aria-label="Attachments by type">
+            <thead><tr><th>Type</th><th>Size</th></tr></thead>
+            <tbody id="types"></tbody>
+        </table>
+        <div id="orphans" aria-live="polite"></div>
+    </div>
+</body>
+</html>`;
+
+        const $ = id => document.getElementById(id);
+        let orphans = [];
+
+        function formatSize(bytes) {
+            if (bytes < 1024) return bytes + ' B';
+            if (bytes < 1024 * 1024) return (bytes / 1024).toFixed(1) + ' KB';
+            return (bytes / 1024 / 1024).toFixed(1) + ' MB';
+        }
+
+        function setStatus(text, cls) {
+            $('status').className = cls || 'muted';
+            $('status').textContent = text;
+        }
+
+        async function run(label, fn) {
+            setStatus(label + '...');
+            try {
+                const result = await fn();
+                setStatus(label + ' done', 'pass');
+                return result;
+            } catch (e) {
+                setStatus(String(e), 'fail');
+                return undefined;
+            }
+        }
+
+        async function refresh() {
+            const usage = await tauriApi.core.invoke('get_attachment_usage');
+            $('summary').innerHTML = usage.files + ' file(s), ' + formatSize(usage.size) + ' in <span class="mono">' + escapeHtml(usage.directory) + '</span>';
+            $('years').innerHTML = usage.years.length === 0
+                ? '<tr><td class="muted" colspan="4">No attachments</td></tr>'
+                : usage.years.map(y =>
+                    '<tr><td>' + y.year + '</td><td>' + y.files + '</td><td>' + formatSize(y.size) + '</td>' +
+                    '<td class="actions"><button data-year="' + y.year + '">Export</button></td></tr>'
+                ).join('');
+            const types = Object.entries(usage.extensions).sort((a, b) => b[1] - a[1]);
+            $('types').innerHTML = types.length === 0
+                ? '<tr><td class="muted" colspan="2">-</td></tr>'
+                : types.map(([ext, size]) =>
+                    '<tr><td class="mono">' + (ext ? escapeHtml(ext) : '<span class="muted">none</span>') + '</td><td>' + formatSize(size) + '</td></tr>'
+                ).join('');
+        }
+
+        function renderOrphans() {
+            if (orphans.length === 0) {
+                $('orphans').innerHTML = '<h2>Orphans</h2><p class="pass">Every attachment belongs to a statement, policy or loan.</p>';
+                return;
+            }
+            $('orphans').innerHTML = '<h2>Orphans</h2>' +
+                '<p class="muted">No statement, policy or loan of yours refers to these files. Other users\' files show up here too, so check before removing them from the folder.</p>' +
+                '<table aria-label="Orphaned attachments"><thead><tr><th>File</th><th>Size</th><th>Year</th></tr></thead><tbody>' +
+                orphans.map(o =>
+                    '<tr><td class="mono" title="' + escapeHtml(o.path) + '">' + escapeHtml(o.name) + '</td>' +
+                    '<td>' + formatSize(o.size) + '</td><td>' + o.year + '</td></tr>'
+                ).join('') + '</tbody></table>';
+        }
+
+        $('years').onclick = async (e) => {
+            const btn = e.target.closest('button');
+            if (!btn) return;
+            const year = Number(btn.dataset.year);
+            const path = await run('Exporting ' + year, () => tauriApi.core.invoke('export_attachments', { year }));
+            if (path) setStatus('Saved ' + path.split(/[\\/]/).pop(), 'pass');
+        };
+        $('orphansBtn').onclick = async () => {
+            const found = await run('Checking', () => tauriApi.core.invoke('find_orphan_attachments'));
+            if (found) {
+                orphans = found;
+                renderOrphans();
+            }
+        };
+        $('folderBtn').onclick = () => run('Opening', () => tauriApi.core.invoke('open_attachments_folder'));
+        $('refreshBtn').onclick = refresh;
+        refresh();
+    "#;
+
+    open_injected_window(app, "attachments", "Attachments", (720.0, 560.0), true, script);
+}
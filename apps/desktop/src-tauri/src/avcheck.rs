@@ -0,0 +1,181 @@
+// Spotting antivirus and firewall interference with the server
+//
+// Security software sometimes quarantines the server binary, stops it from starting, or
+// won't let it open its port, and the user only sees a server that never comes up.
+// `start_server` reports a failed spawn, the server's exit code and output lines here;
+// the patterns such blocks leave (access denied, "contains a virus", EACCES on listen)
+// are remembered for the session. The doctor's "Security software" check puts them
+// together with the antivirus products Windows knows about (Security Center) and, when
+// something looks blocked, lists the steps to add the install folder and the server to
+// the exclusions.
+
+use std::path::Path;
+use std::sync::Mutex;
+use crate::doctor::{CheckStatus, DoctorCheck};
+use crate::server::sidecar_path;
+
+/// NTSTATUS of a process the OS refused to run (STATUS_ACCESS_DENIED, STATUS_VIRUS_INFECTED)
+const BLOCKED_EXIT_CODES: &[i32] = &[0xC000_0022_u32 as i32, 0xC000_0906_u32 as i32];
+
+/// What was seen this session, newest last
+static SYMPTOMS: Mutex<Vec<Symptom>> = Mutex::new(Vec::new());
+const MAX_SYMPTOMS: usize = 20;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    /// The server binary couldn't be started
+    Spawn,
+    /// The OS ended the server as soon as it started
+    Exit,
+    /// The server wasn't allowed to listen on its port
+    Port,
+}
+
+#[derive(Clone)]
+struct Symptom {
+    kind: Kind,
+    detail: String,
+}
+
+fn record(kind: Kind, detail: &str) {
+    let mut symptoms = SYMPTOMS.lock().unwrap_or_else(|e| e.into_inner());
+    if symptoms.len() >= MAX_SYMPTOMS {
+        symptoms.remove(0);
+    }
+    symptoms.push(Symptom { kind, detail: detail.to_string() });
+}
+
+/// A spawn error that looks like a block rather than a missing file
+fn is_blocked_spawn(error: &str) -> bool {
+    let error = error.to_lowercase();
+    // os error 5 (access denied), 225 (contains a virus), 1260 (blocked by policy)
+    ["access is denied", "permission denied", "virus", "potentially unwanted", "os error 225", "os error 1260", "blocked"]
+        .iter()
+        .any(|pattern| error.contains(pattern))
+}
+
+/// The server binary failed to start
+pub fn spawn_failed(error: &str) {
+    if is_blocked_spawn(error) {
+        record(Kind::Spawn, error);
+    }
+}
+
+/// The server exited; some codes mean the OS stopped it
+pub fn server_exited(code: i32) {
+    if BLOCKED_EXIT_CODES.contains(&code) {
+        record(Kind::Exit, &format!("The server was stopped by the system right away (exit code {:#x})", code as u32));
+    }
+}
+
+/// A line of server output
+pub fn check_output(line: &str) {
+    let lower = line.to_lowercase();
+    if lower.contains("eacces") && (lower.contains("listen") || lower.contains("bind") || lower.contains("port")) {
+        record(Kind::Port, line);
+    }
+}
+
+/// Antivirus products registered with Windows Security Center
+#[cfg(target_os = "windows")]
+fn antivirus_products() -> Vec<String> {
+    let output = crate::a11y::command_output(
+        "powershell",
+        &[
+            "-NoProfile",
+            "-NonInteractive",
+            "-Command",
+            "Get-CimInstance -Namespace root/SecurityCenter2 -ClassName AntivirusProduct | ForEach-Object { $_.displayName }",
+        ],
+    );
+    let mut products: Vec<String> = output
+        .unwrap_or_default()
+        .lines()
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect();
+    products.dedup();
+    products
+}
+
+#[cfg(not(target_os = "windows"))]
+fn antivirus_products() -> Vec<String> {
+    Vec::new()
+}
+
+/// Steps for adding the exclusions
+fn exclusion_steps(install_dir: &Path, binary: &str, products: &[String]) -> Vec<String> {
+    let mut steps = Vec::new();
+    if cfg!(target_os = "windows") {
+        steps.push("Open Windows Security and go to Virus & threat protection.".to_string());
+        steps.push("Under Protection history, restore the Moneywright server if it was quarantined (or reinstall Moneywright).".to_string());
+        steps.push("Under Virus & threat protection settings, choose Manage settings, then Add or remove exclusions.".to_string());
+        steps.push(format!("Add a Folder exclusion for {}.", install_dir.display()));
+        steps.push(format!("Add a Process exclusion for {}.", binary));
+        if products.iter().any(|p| !p.to_lowercase().contains("defender")) {
+            steps.push(format!(
+                "In {}, add the same folder and file to its exclusions (also called exceptions or trusted apps).",
+                products.iter().filter(|p| !p.to_lowercase().contains("defender")).cloned().collect::<Vec<_>>().join(", ")
+            ));
+        }
+        steps.push("If Windows Firewall asks about Moneywright, allow it on private networks.".to_string());
+    } else {
+        steps.push(format!(
+            "In your security software, add {} and {} to its exclusions or trusted apps.",
+            install_dir.display(),
+            binary
+        ));
+    }
+    steps.push("Start the server again from the menu, then run the diagnostics again.".to_string());
+    steps
+}
+
+/// Doctor check: signs of security software getting in the server's way
+/// Blocking: it may ask PowerShell for the antivirus products
+pub fn check_security_software() -> DoctorCheck {
+    const ID: &str = "security_software";
+    const NAME: &str = "Security software";
+
+    let symptoms = SYMPTOMS.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    let products = antivirus_products();
+    let installed = match products.as_slice() {
+        [] => String::new(),
+        products => format!(" Antivirus: {}.", products.join(", ")),
+    };
+    let binary_path = sidecar_path();
+    // A release build whose binary vanished was most likely quarantined
+    let missing = !cfg!(debug_assertions) && binary_path.as_ref().is_some_and(|path| !path.exists());
+
+    if symptoms.is_empty() && !missing {
+        return DoctorCheck::new(ID, NAME, CheckStatus::Pass, format!("No signs of the server being blocked.{}", installed));
+    }
+
+    let mut findings: Vec<String> = Vec::new();
+    if missing {
+        findings.push("The server binary is missing from the install folder, security software may have quarantined it".to_string());
+    }
+    for (kind, summary) in [
+        (Kind::Spawn, "Starting the server was refused"),
+        (Kind::Exit, "The system stopped the server as it started"),
+        (Kind::Port, "The server wasn't allowed to open its port"),
+    ] {
+        if let Some(last) = symptoms.iter().rev().find(|s| s.kind == kind) {
+            findings.push(format!("{}: {}", summary, last.detail));
+        }
+    }
+    let (install_dir, binary) = match binary_path {
+        Some(path) => (
+            path.parent().map(Path::to_path_buf).unwrap_or_default(),
+            path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+        ),
+        None => (Default::default(), format!("moneywright{}", std::env::consts::EXE_SUFFIX)),
+    };
+    DoctorCheck::new(
+        ID,
+        NAME,
+        CheckStatus::Fail,
+        format!("{}. This usually means security software is blocking it.{}", findings.join(". "), installed),
+    )
+    .with_steps(exclusion_steps(&install_dir, &binary, &products))
+}
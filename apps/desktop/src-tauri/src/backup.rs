@@ -0,0 +1,722 @@
+// Database backups for the Moneywright desktop app
+//
+// Backups are local files: the data folder's `backups/` and the `backups.directory`
+// setting. There are no remote backup targets; a synced folder (Dropbox, a NAS mount)
+// set as the backup directory is the way to keep copies off the machine. Backups
+// aren't encrypted either, so the browser only tells SQLite files from unreadable ones.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use rusqlite::{Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+use crate::diskspace::{self, Operation};
+use crate::logs::{log_line, SharedLogStore};
+use crate::quarantine;
+use crate::jobs::{start_job, CancelToken, JobKind};
+use crate::server::{read_database_url, SharedServerManager};
+use crate::settings::{Settings, SharedSettings};
+use crate::windows::open_injected_window;
+
+/// Verification results, keyed by backup path
+const VERIFICATIONS_FILE: &str = "backup-verifications.json";
+/// First bytes of every SQLite database
+const SQLITE_HEADER: &[u8; 16] = b"SQLite format 3\0";
+
+/// Directory where backups are written (`backups.directory` setting, or inside the data dir)
+pub fn backups_dir(data_dir: &Path, settings: &Settings) -> PathBuf {
+    if settings.backups.directory.is_empty() {
+        data_dir.join("backups")
+    } else {
+        PathBuf::from(&settings.backups.directory)
+    }
+}
+
+/// Path of the SQLite database used by the sidecar (DATA_DIR/data/app.db)
+pub fn sqlite_db_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("data").join("app.db")
+}
+
+/// Create a consistent snapshot of the SQLite database in `dir`
+/// Uses `VACUUM INTO` so the backup is safe to take while the server is running
+pub fn create_backup(data_dir: &Path, dir: &Path, cancel: &CancelToken) -> Result<PathBuf, String> {
+    if read_database_url(data_dir).is_some() {
+        return Err("Backups are only supported for SQLite databases (use pg_dump for PostgreSQL)".to_string());
+    }
+
+    let db_path = sqlite_db_path(data_dir);
+    if !db_path.exists() {
+        return Err(format!("Database not found at {}", db_path.display()));
+    }
+
+    fs::create_dir_all(dir)
+        .map_err(|e| format!("Failed to create backups directory: {}", e))?;
+
+    let backup_path = dir.join(format!("app-{}.db", unix_now()));
+    snapshot(&db_path, &backup_path, Some(cancel))?;
+    Ok(backup_path)
+}
+
+/// Take a backup as a tracked job (which also keeps scheduled restarts from
+/// pulling the database out from under us)
+pub async fn run_backup(app: &AppHandle, data_dir: PathBuf, dir: PathBuf) -> Result<PathBuf, String> {
+    // The snapshot is about the size of the database
+    let needed = fs::metadata(sqlite_db_path(&data_dir)).map(|meta| meta.len()).unwrap_or(0);
+    diskspace::preflight(app, Operation::Backup, &dir, needed).await?;
+    let job = start_job(app, JobKind::Backup, "Backing up database", true);
+    let token = job.token();
+    let result = tauri::async_runtime::spawn_blocking(move || create_backup(&data_dir, &dir, &token))
+        .await
+        .map_err(|e| format!("Backup task failed: {}", e))
+        .and_then(|r| r);
+    job.finish(&result);
+    result
+}
+
+/// Move the oldest automatic backups (`app-<timestamp>.db`) in `dir` beyond `keep` to the
+/// quarantine; pre-restore snapshots and other files are left alone
+pub fn prune_backups(dir: &Path, keep: usize) -> Result<usize, String> {
+    let mut automatic: Vec<(u64, PathBuf)> = fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let timestamp = name.strip_prefix("app-")?.strip_suffix(".db")?.parse().ok()?;
+            Some((timestamp, entry.path()))
+        })
+        .collect();
+    automatic.sort_by_key(|(timestamp, _)| std::cmp::Reverse(*timestamp));
+
+    let mut removed = 0;
+    for (_, path) in automatic.into_iter().skip(keep) {
+        match quarantine::quarantine(&path, "backup") {
+            Ok(()) => removed += 1,
+            Err(e) => eprintln!("Warning: Failed to remove old backup {}: {}", path.display(), e),
+        }
+    }
+    Ok(removed)
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// `VACUUM INTO` a new file; on failure or cancellation the partial file is removed
+pub fn snapshot(db_path: &Path, target: &Path, cancel: Option<&CancelToken>) -> Result<(), String> {
+    let conn = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+
+    // Interrupt the running VACUUM as soon as the job is cancelled
+    let watcher = cancel.map(|token| {
+        let token = token.clone();
+        let interrupt = conn.get_interrupt_handle();
+        tauri::async_runtime::spawn(async move {
+            token.cancelled().await;
+            interrupt.interrupt();
+        })
+    });
+    let result = conn.execute("VACUUM INTO ?1", [target.to_string_lossy().to_string()]);
+    if let Some(watcher) = watcher {
+        watcher.abort();
+    }
+
+    match result {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            let _ = fs::remove_file(target);
+            if cancel.is_some_and(|token| token.is_cancelled()) {
+                Err("Backup cancelled".to_string())
+            } else {
+                Err(format!("Backup failed: {}", e))
+            }
+        }
+    }
+}
+
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Verification {
+    pub ok: bool,
+    pub detail: String,
+    pub verified_at: u64,
+    /// Size and mtime when verified; a changed file needs verifying again
+    size: u64,
+    modified: u64,
+}
+
+#[derive(Clone, Serialize)]
+pub struct BackupInfo {
+    pub path: String,
+    pub file_name: String,
+    /// "data" for the data folder, "folder" for the configured backup folder
+    pub target: &'static str,
+    pub size: u64,
+    pub created: u64,
+    /// Starts with the SQLite header; false for truncated or foreign files
+    pub sqlite: bool,
+    pub verification: Option<Verification>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct TableComparison {
+    pub name: String,
+    pub backup_rows: i64,
+    /// None if the table doesn't exist in the current database
+    pub current_rows: Option<i64>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct RestorePreview {
+    pub backup: BackupInfo,
+    pub tables: Vec<TableComparison>,
+    pub backup_migrations: Option<i64>,
+    pub current_migrations: Option<i64>,
+}
+
+/// Backup folders the browser knows about, with their target label
+fn backup_targets(data_dir: &Path, settings: &Settings) -> Vec<(&'static str, PathBuf)> {
+    let mut targets = vec![("data", data_dir.join("backups"))];
+    let configured = backups_dir(data_dir, settings);
+    if configured != targets[0].1 {
+        targets.push(("folder", configured));
+    }
+    targets
+}
+
+fn modified_secs(meta: &fs::Metadata) -> u64 {
+    meta.modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn is_sqlite_file(path: &Path) -> bool {
+    let mut header = [0u8; 16];
+    match fs::File::open(path).and_then(|mut f| f.read_exact(&mut header)) {
+        Ok(()) => &header == SQLITE_HEADER,
+        Err(_) => false,
+    }
+}
+
+fn read_verifications(data_dir: &Path) -> BTreeMap<String, Verification> {
+    fs::read_to_string(data_dir.join(VERIFICATIONS_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn write_verifications(data_dir: &Path, verifications: &BTreeMap<String, Verification>) {
+    match serde_json::to_string_pretty(verifications) {
+        Ok(json) => {
+            if let Err(e) = fs::write(data_dir.join(VERIFICATIONS_FILE), json) {
+                eprintln!("Warning: Failed to write backup verifications: {}", e);
+            }
+        }
+        Err(e) => eprintln!("Warning: Failed to serialize backup verifications: {}", e),
+    }
+}
+
+fn backup_info(
+    path: &Path,
+    target: &'static str,
+    verifications: &BTreeMap<String, Verification>,
+) -> Option<BackupInfo> {
+    let meta = fs::metadata(path).ok()?;
+    let key = path.to_string_lossy().to_string();
+    let size = meta.len();
+    let modified = modified_secs(&meta);
+    let verification = verifications
+        .get(&key)
+        .filter(|v| v.size == size && v.modified == modified)
+        .cloned();
+    Some(BackupInfo {
+        file_name: path.file_name()?.to_string_lossy().to_string(),
+        path: key,
+        target,
+        size,
+        created: modified,
+        sqlite: is_sqlite_file(path),
+        verification,
+    })
+}
+
+/// All backups in the known folders, newest first
+pub fn list_all(data_dir: &Path, settings: &Settings) -> Vec<BackupInfo> {
+    let verifications = read_verifications(data_dir);
+    let mut backups: Vec<BackupInfo> = backup_targets(data_dir, settings)
+        .into_iter()
+        .flat_map(|(target, dir)| {
+            fs::read_dir(dir)
+                .into_iter()
+                .flatten()
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    path.is_file()
+                        && path.file_name().is_some_and(|n| n.to_string_lossy().starts_with("app-"))
+                })
+                .filter_map(|path| backup_info(&path, target, &verifications))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    backups.sort_by_key(|b| std::cmp::Reverse(b.created));
+    backups
+}
+
+/// Only files listed by the browser may be acted on
+pub fn find_backup(data_dir: &Path, settings: &Settings, path: &str) -> Result<BackupInfo, String> {
+    list_all(data_dir, settings)
+        .into_iter()
+        .find(|b| b.path == path)
+        .ok_or_else(|| format!("Not a known backup: {}", path))
+}
+
+fn open_read_only(path: &Path) -> Result<Connection, String> {
+    Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| format!("Failed to open backup: {}", e))
+}
+
+pub fn integrity_check(path: &Path) -> Result<(), String> {
+    if !is_sqlite_file(path) {
+        return Err("Backup is not a SQLite database".to_string());
+    }
+    let result = open_read_only(path)?
+        .query_row("PRAGMA integrity_check", [], |row| row.get::<_, String>(0))
+        .map_err(|e| format!("Integrity check failed: {}", e))?;
+    if result == "ok" {
+        Ok(())
+    } else {
+        Err(format!("Integrity check reported: {}", result))
+    }
+}
+
+pub fn table_counts(path: &Path) -> Result<BTreeMap<String, i64>, String> {
+    let conn = open_read_only(path)?;
+    let mut stmt = conn
+        .prepare("SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' AND name NOT LIKE '__drizzle%'")
+        .map_err(|e| e.to_string())?;
+    let names: Vec<String> = stmt
+        .query_map([], |row| row.get(0))
+        .map_err(|e| e.to_string())?
+        .flatten()
+        .collect();
+
+    let mut counts = BTreeMap::new();
+    for name in names {
+        let sql = format!("SELECT COUNT(*) FROM \"{}\"", name.replace('"', "\"\""));
+        let count = conn.query_row(&sql, [], |row| row.get::<_, i64>(0)).map_err(|e| e.to_string())?;
+        counts.insert(name, count);
+    }
+    Ok(counts)
+}
+
+pub fn migration_count(path: &Path) -> Option<i64> {
+    open_read_only(path)
+        .ok()?
+        .query_row("SELECT COUNT(*) FROM __drizzle_migrations", [], |row| row.get::<_, i64>(0))
+        .ok()
+}
+
+async fn browser_context(
+    manager: &State<'_, SharedServerManager>,
+    settings: &State<'_, SharedSettings>,
+) -> (PathBuf, Settings) {
+    let data_dir = manager.lock().await.data_dir().clone();
+    (data_dir, settings.lock().await.get())
+}
+
+/// List backups in the data folder and the configured backup folder
+#[tauri::command]
+pub async fn list_backups(
+    manager: State<'_, SharedServerManager>,
+    settings: State<'_, SharedSettings>,
+) -> Result<Vec<BackupInfo>, String> {
+    let (data_dir, settings) = browser_context(&manager, &settings).await;
+    Ok(list_all(&data_dir, &settings))
+}
+
+/// Take a backup now into the configured backup folder
+#[tauri::command]
+pub async fn create_backup_now(
+    app: AppHandle,
+    manager: State<'_, SharedServerManager>,
+    settings: State<'_, SharedSettings>,
+) -> Result<String, String> {
+    let (data_dir, settings) = browser_context(&manager, &settings).await;
+    let dir = backups_dir(&data_dir, &settings);
+    let path = run_backup(&app, data_dir, dir).await?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Run an integrity check on a backup and remember the result
+#[tauri::command]
+pub async fn verify_backup(
+    manager: State<'_, SharedServerManager>,
+    settings: State<'_, SharedSettings>,
+    path: String,
+) -> Result<BackupInfo, String> {
+    let (data_dir, settings) = browser_context(&manager, &settings).await;
+    let backup = find_backup(&data_dir, &settings, &path)?;
+
+    let file = PathBuf::from(&backup.path);
+    let result = tauri::async_runtime::spawn_blocking(move || integrity_check(&file))
+        .await
+        .map_err(|e| format!("Verification task failed: {}", e))?;
+
+    let mut verifications = read_verifications(&data_dir);
+    verifications.insert(
+        backup.path.clone(),
+        Verification {
+            ok: result.is_ok(),
+            detail: result.err().unwrap_or_else(|| "Integrity check passed".to_string()),
+            verified_at: unix_now(),
+            size: backup.size,
+            modified: backup.created,
+        },
+    );
+    write_verifications(&data_dir, &verifications);
+
+    find_backup(&data_dir, &settings, &path)
+}
+
+/// Compare a backup with the current database before restoring it
+#[tauri::command]
+pub async fn preview_restore(
+    manager: State<'_, SharedServerManager>,
+    settings: State<'_, SharedSettings>,
+    path: String,
+) -> Result<RestorePreview, String> {
+    let (data_dir, settings) = browser_context(&manager, &settings).await;
+    let backup = find_backup(&data_dir, &settings, &path)?;
+    if !backup.sqlite {
+        return Err("Only SQLite backups can be previewed".to_string());
+    }
+
+    let backup_path = PathBuf::from(&backup.path);
+    let db_path = sqlite_db_path(&data_dir);
+    // Counting rows reads every table of both databases
+    tauri::async_runtime::spawn_blocking(move || {
+        let backup_counts = table_counts(&backup_path)?;
+        let current_counts = if db_path.exists() { table_counts(&db_path).unwrap_or_default() } else { BTreeMap::new() };
+
+        let tables = backup_counts
+            .into_iter()
+            .map(|(name, backup_rows)| TableComparison {
+                current_rows: current_counts.get(&name).copied(),
+                name,
+                backup_rows,
+            })
+            .collect();
+
+        Ok(RestorePreview {
+            backup,
+            tables,
+            backup_migrations: migration_count(&backup_path),
+            current_migrations: migration_count(&db_path),
+        })
+    })
+    .await
+    .map_err(|e| format!("Preview task failed: {}", e))?
+}
+
+/// Stop the server and swap the backup in, returning where the old database was saved
+pub async fn replace_database(
+    manager: &SharedServerManager,
+    db_path: &Path,
+    backup_path: &Path,
+    dir: &Path,
+) -> Result<PathBuf, String> {
+    // In dev mode the API runs separately (`bun run dev`) and must be restarted by hand
+    #[cfg(not(debug_assertions))]
+    crate::server::stop_server(manager.clone()).await?;
+    #[cfg(debug_assertions)]
+    let _ = manager;
+
+    // Keep the database being replaced, so a restore can itself be undone
+    let safety_path = dir.join(format!("app-{}-pre-restore.db", unix_now()));
+    if db_path.exists() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create backups directory: {}", e))?;
+        snapshot(db_path, &safety_path, None)?;
+    }
+
+    let staging = db_path.with_extension("db.restore");
+    fs::copy(backup_path, &staging).map_err(|e| format!("Failed to copy backup: {}", e))?;
+    for suffix in ["-wal", "-shm"] {
+        let _ = fs::remove_file(format!("{}{}", db_path.display(), suffix));
+    }
+    fs::rename(&staging, db_path).map_err(|e| format!("Failed to replace database: {}", e))?;
+    Ok(safety_path)
+}
+
+/// Replace the database with a backup; the current database is saved first
+#[tauri::command]
+pub async fn restore_backup(
+    app: AppHandle,
+    manager: State<'_, SharedServerManager>,
+    settings: State<'_, SharedSettings>,
+    log_store: State<'_, SharedLogStore>,
+    path: String,
+) -> Result<String, String> {
+    let (data_dir, settings) = browser_context(&manager, &settings).await;
+    if read_database_url(&data_dir).is_some() {
+        return Err("Restoring is only supported for SQLite databases".to_string());
+    }
+    let backup = find_backup(&data_dir, &settings, &path)?;
+    let backup_path = PathBuf::from(&backup.path);
+    let check_path = backup_path.clone();
+    tauri::async_runtime::spawn_blocking(move || integrity_check(&check_path))
+        .await
+        .map_err(|e| format!("Verification task failed: {}", e))??;
+
+    let job = start_job(&app, JobKind::Backup, format!("Restoring {}", backup.file_name), false);
+    let dir = backups_dir(&data_dir, &settings);
+    let result = replace_database(manager.inner(), &sqlite_db_path(&data_dir), &backup_path, &dir).await;
+    job.finish(&result);
+    match &result {
+        Ok(_) => log_line(&app, &log_store, format!("Restored database from {}", backup.file_name), "info").await,
+        Err(e) => log_line(&app, &log_store, format!("Restoring {} failed: {}", backup.file_name, e), "error").await,
+    }
+
+    // The database is only swapped in as the last step, so after a failure the
+    // server comes back on the one it had
+    #[cfg(not(debug_assertions))]
+    let started = crate::start_server_cmd(app.clone(), manager, log_store).await;
+    let safety_path = result?;
+    #[cfg(not(debug_assertions))]
+    started?;
+    crate::refresh_main_window(&app);
+
+    Ok(safety_path.to_string_lossy().to_string())
+}
+
+/// Copy a backup out (defaults to the Downloads folder) and reveal it
+#[tauri::command]
+pub async fn export_backup(
+    manager: State<'_, SharedServerManager>,
+    settings: State<'_, SharedSettings>,
+    path: String,
+    destination: Option<String>,
+) -> Result<String, String> {
+    let (data_dir, settings) = browser_context(&manager, &settings).await;
+    let backup = find_backup(&data_dir, &settings, &path)?;
+    let target = match destination {
+        Some(d) => PathBuf::from(d),
+        None => dirs::download_dir()
+            .or_else(dirs::home_dir)
+            .ok_or_else(|| "No Downloads folder found".to_string())?
+            .join(format!("moneywright-{}", backup.file_name)),
+    };
+
+    fs::copy(&backup.path, &target).map_err(|e| format!("Failed to export backup: {}", e))?;
+    if let Some(parent) = target.parent() {
+        let _ = open::that(parent);
+    }
+    Ok(target.to_string_lossy().to_string())
+}
+
+/// Delete a backup file
+#[tauri::command]
+pub async fn delete_backup(
+    manager: State<'_, SharedServerManager>,
+    settings: State<'_, SharedSettings>,
+    path: String,
+) -> Result<(), String> {
+    let (data_dir, settings) = browser_context(&manager, &settings).await;
+    let backup = find_backup(&data_dir, &settings, &path)?;
+    quarantine::quarantine(Path::new(&backup.path), "backup").map_err(|e| format!("Failed to delete backup: {}", e))?;
+    quarantine::purge(settings.backups.quarantine_days);
+
+    let mut verifications = read_verifications(&data_dir);
+    if verifications.remove(&backup.path).is_some() {
+        write_verifications(&data_dir, &verifications);
+    }
+    Ok(())
+}
+
+/// Open the backup browser window (from the recovery window)
+#[tauri::command]
+pub async fn open_backups(app: AppHandle) -> Result<(), String> {
+    open_backups_window(&app);
+    Ok(())
+}
+
+/// Open the backup browser window
+pub fn open_backups_window(app: &AppHandle) {
+    // Static UI; file names and paths are inserted with escaping on the JS side
+    let script = r#"
+        const tauriApi = window.__TAURI__;
+
+        document.documentElement.innerHTML = `
+<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <title>Backups</title>
+    <style>
+        __BASE_STYLE__
+        #content { flex: 1; overflow-y: auto; padding: 12px 16px; }
+        table { width: 100%; border-collapse: collapse; }
+        th, td { text-align: left; padding: 6px 8px 6px 0; border-bottom: 1px solid rgba(255, 255, 255, 0.04); white-space: nowrap; }
+        th { color: #71717a; font-weight: 500; }
+        td.actions { text-align: right; }
+        td.actions button { padding: 3px 8px; font-size: 12px; }
+        #preview { display: none; border-top: 1px solid rgba(255, 255, 255, 0.06); padding: 12px 16px; max-height: 45%; overflow-y: auto; }
+        #preview h2 { font-size: 14px; margin-bottom: 8px; }
+        #preview .buttons { display: flex; gap: 8px; margin-top: 12px; }
+        .changed { color: #f59e0b; }
+    </style>
+</head>
+<body>
+    <div class="toolbar">
+        <button id="createBtn" class="primary">Back Up Now</button>
+        <button id="refreshBtn">Refresh</button>
+        <span id="power" class="muted"></span>
+        <button id="powerBtn" style="display: none">Run Anyway</button>
+        <span id="status" class="muted" role="status" style="margin-left: auto"></span>
+    </div>
+    <div id="content">
+        <table aria-label="Backups">
+            <thead><tr><th>Backup</th><th>Location</th><th>Size</th><th>Age</th><th>Format</th><th>Verified</th><th></th></tr></thead>
+            <tbody id="backups"></tbody>
+        </table>
+    </div>
+    <div id="preview" aria-live="polite"></div>
+    <p class="muted" style="padding: 8px 16px; flex-shrink: 0">Lists the data folder and the backup folder from Settings. Remote backup targets aren't supported; point the backup folder at a synced drive to keep copies elsewhere.</p>
+</body>
+</html>`;
+
+        const $ = id => document.getElementById(id);
+        let backups = [];
+
+        function formatSize(bytes) {
+            if (bytes < 1024) return bytes + ' B';
+            if (bytes < 1024 * 1024) return (bytes / 1024).toFixed(1) + ' KB';
+            return (bytes / 1024 / 1024).toFixed(1) + ' MB';
+        }
+
+        function formatAge(secs) {
+            const age = Math.max(0, Date.now() / 1000 - secs);
+            if (age < 3600) return Math.floor(age / 60) + ' min';
+            if (age < 86400) return Math.floor(age / 3600) + ' h';
+            return Math.floor(age / 86400) + ' d';
+        }
+
+        function verifiedCell(v) {
+            if (!v) return '<span class="muted">Not verified</span>';
+            const cls = v.ok ? 'pass' : 'fail';
+            return '<span class="' + cls + '" title="' + escapeHtml(v.detail) + '">' + (v.ok ? 'OK' : 'Failed') + '</span>';
+        }
+
+        function setStatus(text, cls) {
+            $('status').className = cls || 'muted';
+            $('status').textContent = text;
+        }
+
+        async function refreshPower() {
+            const power = await tauriApi.core.invoke('get_power_status');
+            $('power').textContent = power.deferring ? 'Automatic backups wait: ' + power.deferring : '';
+            $('powerBtn').style.display = power.deferring ? '' : 'none';
+        }
+
+        async function refresh() {
+            refreshPower();
+            backups = await tauriApi.core.invoke('list_backups');
+            $('backups').innerHTML = backups.length === 0
+                ? '<tr><td class="muted" colspan="7">No backups yet</td></tr>'
+                : backups.map((b, i) =>
+                    '<tr><td class="mono" title="' + escapeHtml(b.path) + '">' + escapeHtml(b.file_name) + '</td>' +
+                    '<td>' + (b.target === 'data' ? 'Data folder' : 'Backup folder') + '</td>' +
+                    '<td>' + formatSize(b.size) + '</td>' +
+                    '<td>' + formatAge(b.created) + '</td>' +
+                    '<td>' + (b.sqlite ? 'SQLite' : '<span class="fail">Unreadable</span>') + '</td>' +
+                    '<td>' + verifiedCell(b.verification) + '</td>' +
+                    '<td class="actions">' +
+                        '<button data-action="verify" data-index="' + i + '">Verify</button> ' +
+                        '<button data-action="restore" data-index="' + i + '"' + (b.sqlite ? '' : ' disabled') + '>Restore</button> ' +
+                        '<button data-action="sandbox" data-index="' + i + '"' + (b.sqlite ? '' : ' disabled') + ' title="Try changes on a copy of this backup">Open in Sandbox</button> ' +
+                        '<button data-action="export" data-index="' + i + '">Export</button> ' +
+                        '<button data-action="delete" data-index="' + i + '" class="danger">Delete</button>' +
+                    '</td></tr>'
+                ).join('');
+        }
+
+        async function run(label, fn) {
+            setStatus(label + '...');
+            try {
+                const result = await fn();
+                setStatus(label + ' done', 'pass');
+                return result;
+            } catch (e) {
+                setStatus(String(e), 'fail');
+                return undefined;
+            } finally {
+                refresh();
+            }
+        }
+
+        async function showPreview(backup) {
+            const p = await run('Comparing', () => tauriApi.core.invoke('preview_restore', { path: backup.path }));
+            if (!p) return;
+            const rows = p.tables.map(t => {
+                const changed = t.current_rows !== t.backup_rows;
+                return '<tr><td class="mono">' + escapeHtml(t.name) + '</td><td>' + (t.current_rows == null ? '-' : t.current_rows) +
+                    '</td><td class="' + (changed ? 'changed' : '') + '">' + t.backup_rows + '</td></tr>';
+            }).join('');
+            $('preview').innerHTML =
+                '<h2>Restore ' + escapeHtml(backup.file_name) + '?</h2>' +
+                '<p class="muted">The current database is saved as a new backup first. Migrations: ' +
+                (p.current_migrations == null ? '-' : p.current_migrations) + ' now, ' + (p.backup_migrations == null ? '-' : p.backup_migrations) + ' in backup.</p>' +
+                '<table aria-label="Restore preview"><thead><tr><th>Table</th><th>Current rows</th><th>Backup rows</th></tr></thead><tbody>' + rows + '</tbody></table>' +
+                '<div class="buttons"><button id="confirmRestore" class="danger">Restore</button><button id="cancelRestore">Cancel</button></div>';
+            $('preview').style.display = 'block';
+            $('cancelRestore').onclick = () => { $('preview').style.display = 'none'; };
+            $('confirmRestore').onclick = async () => {
+                $('preview').style.display = 'none';
+                const saved = await run('Restoring', () => tauriApi.core.invoke('restore_backup', { path: backup.path }));
+                if (saved) setStatus('Restored. Previous database saved as ' + saved.split(/[\\/]/).pop(), 'pass');
+            };
+        }
+
+        $('backups').onclick = async (e) => {
+            const btn = e.target.closest('button');
+            if (!btn) return;
+            const backup = backups[Number(btn.dataset.index)];
+            switch (btn.dataset.action) {
+                case 'verify':
+                    await run('Verifying', () => tauriApi.core.invoke('verify_backup', { path: backup.path }));
+                    break;
+                case 'restore':
+                    await showPreview(backup);
+                    break;
+                case 'sandbox':
+                    await run('Starting the sandbox', () => tauriApi.core.invoke('start_sandbox', { path: backup.path }));
+                    break;
+                case 'export':
+                    await run('Exporting', () => tauriApi.core.invoke('export_backup', { path: backup.path }));
+                    break;
+                case 'delete':
+                    // Second click confirms
+                    if (btn.dataset.armed) {
+                        await run('Deleting', () => tauriApi.core.invoke('delete_backup', { path: backup.path }));
+                    } else {
+                        btn.dataset.armed = '1';
+                        btn.textContent = 'Confirm';
+                    }
+                    break;
+            }
+        };
+        $('createBtn').onclick = () => run('Backing up', () => tauriApi.core.invoke('create_backup_now'));
+        $('powerBtn').onclick = () => run('Allowing on battery', () => tauriApi.core.invoke('run_on_battery_anyway'));
+        $('refreshBtn').onclick = refresh;
+        refresh();
+    "#;
+
+    open_injected_window(app, "backups", "Backups", (820.0, 560.0), true, script);
+}
@@ -0,0 +1,148 @@
+// Startup benchmarking: per-phase wall-clock timings persisted across launches
+
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+
+const BENCHMARK_FILE: &str = "startup-benchmarks.json";
+/// Number of launches kept in the benchmark history
+const MAX_LAUNCHES: usize = 20;
+/// A launch more than this long after the previous one is treated as a cold start
+/// (OS file caches for the sidecar binary are likely evicted by then)
+const COLD_START_GAP: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PhaseTiming {
+    pub name: String,
+    pub ms: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LaunchRecord {
+    pub timestamp: u64,
+    pub version: String,
+    pub cold: bool,
+    pub total_ms: u64,
+    pub phases: Vec<PhaseTiming>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct StartupBenchmark {
+    pub launches: Vec<LaunchRecord>,
+    pub cold_avg_ms: Option<u64>,
+    pub warm_avg_ms: Option<u64>,
+}
+
+/// Records the duration of each startup phase since the previous mark
+pub struct StartupTimer {
+    started: Instant,
+    last: Instant,
+    phases: Vec<PhaseTiming>,
+}
+
+impl StartupTimer {
+    pub fn start() -> Self {
+        let now = Instant::now();
+        Self {
+            started: now,
+            last: now,
+            phases: Vec::new(),
+        }
+    }
+
+    /// Close the current phase under the given name
+    pub fn mark(&mut self, name: &str) {
+        let now = Instant::now();
+        self.phases.push(PhaseTiming {
+            name: name.to_string(),
+            ms: now.duration_since(self.last).as_millis() as u64,
+        });
+        self.last = now;
+    }
+
+    /// Persist this launch to the benchmark history in the data directory
+    /// Persist this launch and return the total startup time in milliseconds
+    pub fn finish(self, data_dir: &Path) -> u64 {
+        let mut launches = read_launches(data_dir);
+        let timestamp = unix_now();
+
+        let cold = match launches.last() {
+            Some(prev) => {
+                prev.version != env!("CARGO_PKG_VERSION")
+                    || timestamp.saturating_sub(prev.timestamp) > COLD_START_GAP.as_secs()
+            }
+            None => true,
+        };
+
+        let record = LaunchRecord {
+            timestamp,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            cold,
+            total_ms: self.started.elapsed().as_millis() as u64,
+            phases: self.phases,
+        };
+
+        println!(
+            "Startup took {}ms ({} start)",
+            record.total_ms,
+            if record.cold { "cold" } else { "warm" }
+        );
+
+        let total_ms = record.total_ms;
+        launches.push(record);
+        if launches.len() > MAX_LAUNCHES {
+            let excess = launches.len() - MAX_LAUNCHES;
+            launches.drain(..excess);
+        }
+
+        match serde_json::to_string_pretty(&launches) {
+            Ok(json) => {
+                if let Err(e) = fs::write(data_dir.join(BENCHMARK_FILE), json) {
+                    eprintln!("Warning: Failed to write startup benchmarks: {}", e);
+                }
+            }
+            Err(e) => eprintln!("Warning: Failed to serialize startup benchmarks: {}", e),
+        }
+
+        total_ms
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn read_launches(data_dir: &Path) -> Vec<LaunchRecord> {
+    fs::read_to_string(data_dir.join(BENCHMARK_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn average(launches: &[LaunchRecord], cold: bool) -> Option<u64> {
+    let totals: Vec<u64> = launches
+        .iter()
+        .filter(|l| l.cold == cold)
+        .map(|l| l.total_ms)
+        .collect();
+
+    if totals.is_empty() {
+        None
+    } else {
+        Some(totals.iter().sum::<u64>() / totals.len() as u64)
+    }
+}
+
+/// Load the benchmark history with cold/warm averages
+pub fn load_benchmark(data_dir: &Path) -> StartupBenchmark {
+    let launches = read_launches(data_dir);
+    StartupBenchmark {
+        cold_avg_ms: average(&launches, true),
+        warm_avg_ms: average(&launches, false),
+        launches,
+    }
+}
@@ -0,0 +1,136 @@
+// moneywrightctl - Control a running Moneywright desktop app from scripts
+//
+// Usage:
+//   moneywrightctl [--data-dir <dir>] status
+//   moneywrightctl [--data-dir <dir>] restart
+//   moneywrightctl [--data-dir <dir>] backup
+//   moneywrightctl [--data-dir <dir>] export-logs [path]
+//   moneywrightctl [--data-dir <dir>] import <file>
+
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use serde_json::{json, Value};
+
+/// Must match the Tauri app identifier (app data dir name)
+const APP_IDENTIFIER: &str = "com.moneywright.desktop";
+/// Must match `DATA_LOCATION_FILE` in server.rs
+const DATA_LOCATION_FILE: &str = "data-location";
+#[cfg(unix)]
+const SOCKET_FILE: &str = "control.sock";
+/// Must match `pipe_name` in control.rs
+#[cfg(windows)]
+fn pipe_name() -> String {
+    let user: String = std::env::var("USERNAME")
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        .collect();
+    format!(r"\\.\pipe\moneywright-control-{}", user)
+}
+
+const USAGE: &str = "Usage: moneywrightctl [--data-dir <dir>] <status|restart|backup|export-logs [path]|import <file>>";
+
+fn main() -> ExitCode {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+
+    let mut data_dir = std::env::var_os("MONEYWRIGHT_DATA_DIR").map(PathBuf::from);
+    if args.first().map(String::as_str) == Some("--data-dir") {
+        if args.len() < 2 {
+            eprintln!("{}", USAGE);
+            return ExitCode::from(2);
+        }
+        data_dir = Some(PathBuf::from(args.remove(1)));
+        args.remove(0);
+    }
+
+    let Some(command) = args.first().cloned() else {
+        eprintln!("{}", USAGE);
+        return ExitCode::from(2);
+    };
+
+    // Paths are resolved here since the app runs with a different working directory
+    let path = match args.get(1) {
+        Some(p) => match std::fs::canonicalize(p) {
+            Ok(abs) => Some(abs.to_string_lossy().to_string()),
+            // export-logs may target a file that doesn't exist yet
+            Err(_) => Some(absolute(Path::new(p)).to_string_lossy().to_string()),
+        },
+        None => None,
+    };
+
+    let request = json!({ "command": command, "path": path });
+
+    let data_dir = data_dir
+        .or_else(|| dirs::data_dir().map(|d| follow_data_location(d.join(APP_IDENTIFIER))))
+        .unwrap_or_else(|| PathBuf::from("."));
+
+    match send(&data_dir, &request) {
+        Ok(response) => {
+            if response["ok"].as_bool() == Some(true) {
+                let data = &response["data"];
+                println!("{}", serde_json::to_string_pretty(data).unwrap_or_default());
+                ExitCode::SUCCESS
+            } else {
+                eprintln!("Error: {}", response["error"].as_str().unwrap_or("unknown error"));
+                ExitCode::FAILURE
+            }
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// The app records a relocated data dir in a pointer file inside the default one
+fn follow_data_location(default_dir: PathBuf) -> PathBuf {
+    match std::fs::read_to_string(default_dir.join(DATA_LOCATION_FILE)) {
+        Ok(custom) if !custom.trim().is_empty() => PathBuf::from(custom.trim()),
+        _ => default_dir,
+    }
+}
+
+fn absolute(path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir()
+            .map(|cwd| cwd.join(path))
+            .unwrap_or_else(|_| path.to_path_buf())
+    }
+}
+
+#[cfg(unix)]
+fn send(data_dir: &Path, request: &Value) -> Result<Value, String> {
+    let socket_path = data_dir.join(SOCKET_FILE);
+    let stream = std::os::unix::net::UnixStream::connect(&socket_path).map_err(|e| {
+        format!("Moneywright does not appear to be running ({}: {})", socket_path.display(), e)
+    })?;
+    exchange(stream, request)
+}
+
+#[cfg(windows)]
+fn send(_data_dir: &Path, request: &Value) -> Result<Value, String> {
+    let pipe = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(pipe_name())
+        .map_err(|e| format!("Moneywright does not appear to be running ({})", e))?;
+    exchange(pipe, request)
+}
+
+fn exchange<S: std::io::Read + Write>(mut stream: S, request: &Value) -> Result<Value, String> {
+    let mut line = serde_json::to_string(request).map_err(|e| e.to_string())?;
+    line.push('\n');
+    stream
+        .write_all(line.as_bytes())
+        .map_err(|e| format!("Failed to send command: {}", e))?;
+
+    let mut response = String::new();
+    BufReader::new(stream)
+        .read_line(&mut response)
+        .map_err(|e| format!("Failed to read response: {}", e))?;
+
+    serde_json::from_str(&response).map_err(|e| format!("Invalid response: {}", e))
+}
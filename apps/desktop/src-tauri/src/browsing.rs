@@ -0,0 +1,278 @@
+// Selective clearing of browsing data (Edit > Clear Browsing Data...)
+//
+// "Clear Cookies" used to wipe everything, including the sign-in users wanted to
+// keep. The dialog lists the sites with data in the webviews (the app itself, local
+// profiles, sign-in providers) and clears cookies, local storage and/or the cache of
+// one of them, or of all.
+//
+// Cookies go through the webview's cookie store and are matched by host, so for
+// "localhost" they cover the main app and every local profile alike. Local storage
+// and the cache can only be cleared from a page of that site, so they apply to the
+// windows currently showing it.
+
+use std::collections::BTreeMap;
+use serde::Serialize;
+use tauri::{AppHandle, Manager, WebviewWindow};
+use crate::server::get_server_url;
+use crate::transport;
+use crate::webcache::{page_script, CLEAR_CACHE_STEPS};
+use crate::windows::open_injected_window;
+
+const CLEAR_STORAGE_STEPS: &str = r#"
+        localStorage.clear();
+        sessionStorage.clear();
+"#;
+
+#[derive(Serialize)]
+pub struct SiteData {
+    host: String,
+    cookies: usize,
+    /// Windows showing the site, where storage and cache can be cleared
+    windows: usize,
+    /// The Moneywright server (and any local profiles)
+    app: bool,
+}
+
+#[derive(Serialize)]
+pub struct ClearResult {
+    cookies: usize,
+    windows: usize,
+}
+
+/// Host of a cookie domain, without the leading dot of domain cookies
+fn cookie_host(domain: &str) -> String {
+    domain.trim_start_matches('.').to_lowercase()
+}
+
+/// Whether a cookie set for `domain` is sent to `host`
+fn cookie_matches(domain: &str, host: &str) -> bool {
+    let domain = cookie_host(domain);
+    host == domain || host.ends_with(&format!(".{}", domain))
+}
+
+fn window_host(window: &WebviewWindow) -> Option<String> {
+    let url = window.url().ok()?;
+    if !matches!(url.scheme(), "http" | "https" | transport::SCHEME) {
+        return None;
+    }
+    url.host_str().map(str::to_lowercase)
+}
+
+/// Any window, the cookie store is shared between them
+fn cookie_window(app: &AppHandle) -> Result<WebviewWindow, String> {
+    app.get_webview_window("main")
+        .or_else(|| app.webview_windows().into_values().next())
+        .ok_or_else(|| "No window open".to_string())
+}
+
+fn site_entry<'a>(sites: &'a mut BTreeMap<String, SiteData>, host: String, app_host: Option<&str>) -> &'a mut SiteData {
+    let app = app_host == Some(host.as_str());
+    sites.entry(host.clone()).or_insert(SiteData { host, cookies: 0, windows: 0, app })
+}
+
+/// Sites with cookies or open windows, the app's own first
+#[tauri::command]
+pub async fn list_site_data(app: AppHandle) -> Result<Vec<SiteData>, String> {
+    let app_host = tauri::Url::parse(&get_server_url())
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_lowercase));
+    let mut sites: BTreeMap<String, SiteData> = BTreeMap::new();
+
+    let cookies = cookie_window(&app)?.cookies().map_err(|e| format!("Failed to read cookies: {}", e))?;
+    for domain in cookies.iter().filter_map(|cookie| cookie.domain()) {
+        site_entry(&mut sites, cookie_host(domain), app_host.as_deref()).cookies += 1;
+    }
+    for host in app.webview_windows().values().filter_map(window_host) {
+        site_entry(&mut sites, host, app_host.as_deref()).windows += 1;
+    }
+    // Over the socket transport the app's cookies are kept by the shell, see transport.rs
+    if let Some(host) = app_host.clone().filter(|_| transport::socket_path().is_some()) {
+        let count = transport::cookie_count();
+        if count > 0 {
+            site_entry(&mut sites, host, app_host.as_deref()).cookies += count;
+        }
+    }
+
+    let mut sites: Vec<SiteData> = sites.into_values().collect();
+    sites.sort_by_key(|s| !s.app);
+    Ok(sites)
+}
+
+/// Clear the chosen kinds of data for one site (`host`), or for all sites when None
+#[tauri::command]
+pub async fn clear_site_data(
+    app: AppHandle,
+    host: Option<String>,
+    cookies: bool,
+    storage: bool,
+    cache: bool,
+) -> Result<ClearResult, String> {
+    if !(cookies || storage || cache) {
+        return Err("Choose what to clear".to_string());
+    }
+    let host = host.map(|h| h.to_lowercase());
+
+    // Everything for every site is what the old Clear Cookies did
+    if host.is_none() && cookies && storage && cache {
+        let window = cookie_window(&app)?;
+        window.clear_all_browsing_data().map_err(|e| format!("Failed to clear browsing data: {}", e))?;
+        transport::clear_cookies();
+        if let Some(main) = app.get_webview_window("main") {
+            // Using Tauri's webview eval API to navigate - this is safe as we control the URL
+            let _ = main.eval(format!("window.location.href = '{}'", get_server_url()));
+        }
+        return Ok(ClearResult { cookies: 0, windows: 1 });
+    }
+
+    let mut cleared_cookies = 0;
+    if cookies {
+        let window = cookie_window(&app)?;
+        let all = window.cookies().map_err(|e| format!("Failed to read cookies: {}", e))?;
+        for cookie in all {
+            let matches = match (&host, cookie.domain()) {
+                (None, _) => true,
+                (Some(host), Some(domain)) => cookie_matches(domain, host),
+                (Some(_), None) => false,
+            };
+            if matches {
+                window.delete_cookie(cookie).map_err(|e| format!("Failed to delete cookie: {}", e))?;
+                cleared_cookies += 1;
+            }
+        }
+        let app_host = tauri::Url::parse(&get_server_url()).ok().and_then(|url| url.host_str().map(str::to_lowercase));
+        if transport::socket_path().is_some() && (host.is_none() || host == app_host) {
+            cleared_cookies += transport::clear_cookies();
+        }
+    }
+
+    let mut steps = String::new();
+    if storage {
+        steps.push_str(CLEAR_STORAGE_STEPS);
+    }
+    if cache {
+        steps.push_str(CLEAR_CACHE_STEPS);
+    }
+    let windows: Vec<WebviewWindow> = app
+        .webview_windows()
+        .into_values()
+        .filter(|w| match (window_host(w), &host) {
+            (Some(_), None) => true,
+            (Some(window_host), Some(host)) => &window_host == host,
+            (None, _) => false,
+        })
+        .collect();
+    if !steps.is_empty() || cleared_cookies > 0 {
+        // Reload so pages pick up the cleared state (a removed session signs out)
+        let script = page_script(&steps);
+        for window in &windows {
+            let _ = window.eval(&script);
+        }
+    }
+
+    Ok(ClearResult { cookies: cleared_cookies, windows: windows.len() })
+}
+
+/// Open the Clear Browsing Data window
+pub fn open_clear_data_window(app: &AppHandle) {
+    // Static UI; hosts are inserted with escaping on the JS side
+    let script = r#"
+        const tauriApi = window.__TAURI__;
+
+        document.documentElement.innerHTML = `
+<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <title>Clear Browsing Data</title>
+    <style>
+        __BASE_STYLE__
+        #content { flex: 1; overflow-y: auto; padding: 16px; display: grid; grid-template-columns: 90px 1fr; gap: 12px; align-content: start; align-items: center; }
+        select { width: 100%; }
+        .checks { display: flex; flex-direction: column; gap: 8px; }
+        .checks label { display: flex; align-items: center; gap: 6px; cursor: pointer; }
+        #note { grid-column: 2; }
+    </style>
+</head>
+<body>
+    <div id="content">
+        <label for="site">Site</label>
+        <select id="site"></select>
+        <span>Clear</span>
+        <div class="checks">
+            <label><input type="checkbox" id="cookies"> Cookies (signs you out of the site)</label>
+            <label><input type="checkbox" id="storage"> Local storage</label>
+            <label><input type="checkbox" id="cache" checked> Cached files</label>
+        </div>
+        <p id="note" class="muted"></p>
+    </div>
+    <div class="toolbar">
+        <button id="clearBtn" class="primary">Clear</button>
+        <span id="status" class="muted" role="status" aria-live="polite" style="margin-left: auto"></span>
+    </div>
+</body>
+</html>`;
+
+        const $ = id => document.getElementById(id);
+        let sites = [];
+
+        function describe(site) {
+            const parts = [site.cookies + ' cookie' + (site.cookies === 1 ? '' : 's')];
+            if (site.windows) parts.push(site.windows + ' open window' + (site.windows === 1 ? '' : 's'));
+            return (site.app ? 'Moneywright (' + site.host + ')' : site.host) + ' - ' + parts.join(', ');
+        }
+
+        function updateNote() {
+            const site = sites.find(s => s.host === $('site').value);
+            if (!site) {
+                $('note').textContent = 'Clearing everything for all sites signs you out and reloads the app.';
+            } else if (site.app) {
+                $('note').textContent = 'Cookies for ' + site.host + ' are shared by the app and all local profiles. Local storage and cached files are cleared in the windows showing it.';
+            } else if (!site.windows) {
+                $('note').textContent = 'Local storage and cached files can only be cleared while a window shows this site.';
+            } else {
+                $('note').textContent = '';
+            }
+        }
+
+        async function load() {
+            try {
+                sites = await tauriApi.core.invoke('list_site_data');
+                $('site').innerHTML = sites.map(s => '<option value="' + escapeHtml(s.host) + '">' + escapeHtml(describe(s)) + '</option>').join('') +
+                    '<option value="">All sites</option>';
+                updateNote();
+            } catch (e) {
+                $('status').textContent = String(e);
+            }
+        }
+
+        $('site').onchange = updateNote;
+        $('clearBtn').onclick = async () => {
+            const btn = $('clearBtn');
+            if (!btn.dataset.armed) {
+                // Second click confirms
+                btn.dataset.armed = '1';
+                btn.textContent = 'Confirm';
+                return;
+            }
+            delete btn.dataset.armed;
+            btn.textContent = 'Clear';
+            btn.disabled = true;
+            try {
+                const result = await tauriApi.core.invoke('clear_site_data', {
+                    host: $('site').value || null,
+                    cookies: $('cookies').checked,
+                    storage: $('storage').checked,
+                    cache: $('cache').checked,
+                });
+                $('status').textContent = 'Cleared ' + result.cookies + ' cookie(s), reloaded ' + result.windows + ' window(s)';
+                await load();
+            } catch (e) {
+                $('status').textContent = String(e);
+            }
+            btn.disabled = false;
+        };
+        load();
+    "#;
+
+    open_injected_window(app, "clear_data", "Clear Browsing Data", (520.0, 340.0), false, script);
+}
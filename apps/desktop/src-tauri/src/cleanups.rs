@@ -0,0 +1,171 @@
+// Record of forced cleanups: leftover processes stopped and stale locks taken over
+//
+// Before starting its server the app stops whatever of this OS user still listens on
+// the port (see ports.rs), killing it when SIGTERM isn't enough, and a new session
+// takes over the session marker of one that died (see crash.rs). Both used to happen
+// silently. Each time it's recorded here with what was stopped, so the doctor can show
+// it and recurring conflicts (a second install, a script starting servers) can be
+// reported with data.
+//
+// Entries are appended to `cleanups.jsonl` in the data dir, one JSON object per line,
+// and trimmed to the newest MAX_ENTRIES once the file grows past twice that.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+
+const CLEANUPS_FILE: &str = "cleanups.jsonl";
+const MAX_ENTRIES: usize = 200;
+
+static PATH: OnceLock<PathBuf> = OnceLock::new();
+/// Serializes appends and trimming
+static WRITE: Mutex<()> = Mutex::new(());
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    /// Exited after SIGTERM
+    Terminated,
+    /// Still running after the grace period (or no SIGTERM here), killed
+    Killed,
+    /// The kill failed, the process may still hold the port
+    KillFailed,
+    /// A stale lock of a session that is gone was taken over
+    LockTaken,
+}
+
+impl Action {
+    fn describe(self) -> &'static str {
+        match self {
+            Action::Terminated => "stopped",
+            Action::Killed => "killed",
+            Action::KillFailed => "failed to kill",
+            Action::LockTaken => "took over the lock of",
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Cleanup {
+    /// Unix time in seconds
+    pub timestamp: u64,
+    pub action: Action,
+    pub pid: u32,
+    /// Process name, when it could be looked up
+    pub name: Option<String>,
+    /// Executable path, when it could be looked up
+    pub exe: Option<String>,
+    /// Port the process listened on
+    pub port: Option<u16>,
+    /// The lock file, for taken-over locks
+    pub lock: Option<String>,
+}
+
+impl Cleanup {
+    pub fn process(action: Action, pid: u32, name: Option<String>, exe: Option<String>, port: u16) -> Self {
+        Cleanup {
+            timestamp: unix_now(),
+            action,
+            pid,
+            name,
+            exe,
+            port: Some(port),
+            lock: None,
+        }
+    }
+
+    pub fn lock(pid: u32, lock: &Path) -> Self {
+        Cleanup {
+            timestamp: unix_now(),
+            action: Action::LockTaken,
+            pid,
+            name: None,
+            exe: None,
+            port: None,
+            lock: Some(lock.to_string_lossy().to_string()),
+        }
+    }
+
+    /// "killed moneywright (pid 1234) on port 17777" style summary
+    pub fn describe(&self) -> String {
+        let what = match (&self.name, &self.lock) {
+            (_, Some(lock)) => format!("{} (pid {})", lock, self.pid),
+            (Some(name), None) => format!("{} (pid {})", name, self.pid),
+            (None, None) => format!("pid {}", self.pid),
+        };
+        match self.port {
+            Some(port) => format!("{} {} on port {}", self.action.describe(), what, port),
+            None => format!("{} {}", self.action.describe(), what),
+        }
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Where cleanups are kept; called once the data dir is known
+pub fn init(data_dir: &Path) {
+    let _ = PATH.set(data_dir.join(CLEANUPS_FILE));
+}
+
+fn read_entries(path: &Path) -> Vec<Cleanup> {
+    fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+fn append(entry: &Cleanup) -> Result<(), String> {
+    let Some(path) = PATH.get() else {
+        return Ok(());
+    };
+    let line = serde_json::to_string(entry).map_err(|e| e.to_string())?;
+    let _guard = WRITE.lock().unwrap_or_else(|e| e.into_inner());
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    writeln!(file, "{}", line).map_err(|e| e.to_string())?;
+    drop(file);
+
+    let lines = fs::read_to_string(path).map(|c| c.lines().count()).unwrap_or(0);
+    if lines > MAX_ENTRIES * 2 {
+        let entries = read_entries(path);
+        let keep = &entries[entries.len().saturating_sub(MAX_ENTRIES)..];
+        let content: String = keep
+            .iter()
+            .filter_map(|e| serde_json::to_string(e).ok())
+            .map(|line| line + "\n")
+            .collect();
+        fs::write(path, content).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Add an entry, also printing it, and logging to stderr if the file can't be written
+pub fn record(entry: Cleanup) {
+    println!("Cleanup: {}", entry.describe());
+    if let Err(e) = append(&entry) {
+        eprintln!("Warning: Failed to record cleanup: {}", e);
+    }
+}
+
+/// Cleanups in a data dir from the last `secs` seconds, newest first
+pub fn recent(data_dir: &Path, secs: u64) -> Vec<Cleanup> {
+    let since = unix_now().saturating_sub(secs);
+    let mut entries: Vec<Cleanup> = read_entries(&data_dir.join(CLEANUPS_FILE))
+        .into_iter()
+        .filter(|e| e.timestamp >= since)
+        .collect();
+    entries.reverse();
+    entries
+}
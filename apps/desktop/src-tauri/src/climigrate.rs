@@ -0,0 +1,201 @@
+// Bringing a Moneywright CLI installation into the desktop app
+//
+// The CLI keeps its data in ~/.moneywright or similar (see `get_cli_install_dir`).
+// Onboarding offers to bring it over, and `migrate_cli_data` does the same later on a
+// data dir without a database. The database is copied with `VACUUM INTO`, so a CLI
+// server that's still running can't leave a torn copy; the rest of the CLI's data
+// folder and its .env are copied as they are. The copy is then checked with
+// `PRAGMA integrity_check` and per-table row counts against the original, and removed
+// again if either fails.
+//
+// Only after that is the CLI dir touched: when moving, its data folder and .env are
+// deleted. Either way a `moved-to-desktop.json` marker can be left in it, so the CLI
+// can tell its data now lives in the desktop app instead of starting over empty.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use crate::backup::{integrity_check, snapshot, sqlite_db_path, table_counts};
+use crate::onboarding::copy_dir_recursive;
+use crate::server::{get_cli_install_dir, SharedServerManager};
+
+const MARKER_FILE: &str = "moved-to-desktop.json";
+/// Database files in the CLI's data folder that the snapshot replaces
+const DATABASE_FILES: &[&str] = &["app.db", "app.db-wal", "app.db-shm", "app.db-journal"];
+
+#[derive(Clone, Serialize)]
+pub struct CliInstall {
+    pub path: String,
+    /// Size of the CLI's SQLite database, None without one (e.g. it used PostgreSQL)
+    pub database_bytes: Option<u64>,
+    pub has_env: bool,
+    /// Data dir it was already migrated to, from its marker
+    pub migrated_to: Option<String>,
+}
+
+#[derive(Clone, Default, Deserialize)]
+pub struct CliMigrateOptions {
+    /// Delete the CLI's data folder and .env once the copy is verified
+    #[serde(default)]
+    pub remove_original: bool,
+    /// Leave a marker in the CLI dir (always done when removing the original)
+    #[serde(default)]
+    pub leave_marker: bool,
+}
+
+#[derive(Clone, Serialize)]
+pub struct CliMigration {
+    pub from: String,
+    pub tables: usize,
+    pub rows: i64,
+    pub env_copied: bool,
+    pub removed_original: bool,
+    /// Where the marker was written
+    pub marker: Option<String>,
+}
+
+/// The CLI installation on this computer, if there is one
+pub fn detect() -> Option<CliInstall> {
+    let dir = get_cli_install_dir()?;
+    let migrated_to = fs::read_to_string(dir.join(MARKER_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+        .and_then(|marker| marker["data_dir"].as_str().map(str::to_string));
+    Some(CliInstall {
+        path: dir.to_string_lossy().to_string(),
+        database_bytes: fs::metadata(sqlite_db_path(&dir)).ok().map(|meta| meta.len()),
+        has_env: dir.join(".env").is_file(),
+        migrated_to,
+    })
+}
+
+/// Copy what's in the CLI's data folder besides its database, returning what was created
+fn copy_data_files(from: &Path, to: &Path, created: &mut Vec<PathBuf>) -> Result<(), String> {
+    fs::create_dir_all(to).map_err(|e| format!("Failed to create {}: {}", to.display(), e))?;
+    let entries = fs::read_dir(from).map_err(|e| format!("Failed to read {}: {}", from.display(), e))?;
+    for entry in entries {
+        let entry = entry.map_err(|e| e.to_string())?;
+        let name = entry.file_name();
+        if DATABASE_FILES.iter().any(|db| name == *db) {
+            continue;
+        }
+        let target = to.join(&name);
+        if target.exists() {
+            continue;
+        }
+        created.push(target.clone());
+        if entry.file_type().map_err(|e| e.to_string())?.is_dir() {
+            copy_dir_recursive(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), &target).map_err(|e| format!("Failed to copy {}: {}", entry.path().display(), e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Check the copied database against the original
+fn verify_copy(original: &Path, copy: &Path) -> Result<(usize, i64), String> {
+    integrity_check(copy).map_err(|e| format!("The copied database failed its check: {}", e))?;
+    let expected = table_counts(original)?;
+    let copied = table_counts(copy)?;
+    if let Some((table, rows)) = expected.iter().find(|(table, rows)| copied.get(*table) != Some(rows)) {
+        return Err(format!(
+            "The copy of {} has {} rows instead of {}; if the CLI server is running, quit it and try again",
+            table,
+            copied.get(table).copied().unwrap_or(0),
+            rows
+        ));
+    }
+    Ok((expected.len(), expected.values().sum()))
+}
+
+fn write_marker(cli_dir: &Path, data_dir: &Path, removed: bool) -> Result<PathBuf, String> {
+    let path = cli_dir.join(MARKER_FILE);
+    let marker = json!({
+        "data_dir": data_dir.to_string_lossy(),
+        "migrated_at": chrono::Utc::now().to_rfc3339(),
+        "removed": removed,
+        "desktop_version": env!("CARGO_PKG_VERSION"),
+    });
+    fs::write(&path, marker.to_string()).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+/// Copy (or move) a CLI installation into `data_dir`, which must not have a database yet
+pub fn migrate(cli_dir: &Path, data_dir: &Path, options: &CliMigrateOptions) -> Result<CliMigration, String> {
+    let target_db = sqlite_db_path(data_dir);
+    if target_db.exists() {
+        return Err(format!("{} already contains a database", data_dir.display()));
+    }
+
+    let mut created = Vec::new();
+    let copied = (|| {
+        copy_data_files(&cli_dir.join("data"), &data_dir.join("data"), &mut created)?;
+        let cli_db = sqlite_db_path(cli_dir);
+        if !cli_db.exists() {
+            return Ok((0, 0));
+        }
+        created.push(target_db.clone());
+        snapshot(&cli_db, &target_db, None)?;
+        verify_copy(&cli_db, &target_db)
+    })();
+    let (tables, rows) = match copied {
+        Ok(counts) => counts,
+        Err(e) => {
+            for path in created.iter().rev() {
+                let _ = if path.is_dir() { fs::remove_dir_all(path) } else { fs::remove_file(path) };
+            }
+            return Err(e);
+        }
+    };
+
+    let cli_env = cli_dir.join(".env");
+    let env = data_dir.join(".env");
+    let env_copied = cli_env.exists() && !env.exists();
+    if env_copied {
+        fs::copy(&cli_env, &env).map_err(|e| format!("Failed to copy .env: {}", e))?;
+    }
+
+    // The marker goes first, so a CLI started in between doesn't set up an empty database
+    let marker = if options.leave_marker || options.remove_original {
+        Some(write_marker(cli_dir, data_dir, options.remove_original)?)
+    } else {
+        None
+    };
+    if options.remove_original {
+        fs::remove_dir_all(cli_dir.join("data"))
+            .map_err(|e| format!("Copied, but failed to remove the CLI's data: {}", e))?;
+        if cli_env.exists() {
+            fs::remove_file(&cli_env).map_err(|e| format!("Copied, but failed to remove the CLI's .env: {}", e))?;
+        }
+    }
+
+    Ok(CliMigration {
+        from: cli_dir.to_string_lossy().to_string(),
+        tables,
+        rows,
+        env_copied,
+        removed_original: options.remove_original,
+        marker: marker.map(|path| path.to_string_lossy().to_string()),
+    })
+}
+
+/// Look for a Moneywright CLI installation to migrate
+#[tauri::command]
+pub async fn detect_cli_install() -> Result<Option<CliInstall>, String> {
+    tauri::async_runtime::spawn_blocking(detect).await.map_err(|e| e.to_string())
+}
+
+/// Copy (or move) the CLI installation into this data dir and verify the database
+#[tauri::command]
+pub async fn migrate_cli_data(
+    manager: tauri::State<'_, SharedServerManager>,
+    options: CliMigrateOptions,
+) -> Result<CliMigration, String> {
+    let data_dir = manager.lock().await.data_dir().clone();
+    let cli_dir = get_cli_install_dir().ok_or("No CLI installation found")?;
+    tauri::async_runtime::spawn_blocking(move || migrate(&cli_dir, &data_dir, &options))
+        .await
+        .map_err(|e| e.to_string())?
+}
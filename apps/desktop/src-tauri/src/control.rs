@@ -0,0 +1,240 @@
+// Local control socket for scripting the desktop app (used by moneywrightctl)
+//
+// Listens on a Unix socket in the data directory (named pipe on Windows).
+// Protocol: one JSON request per line, answered by one JSON response per line.
+//   request:  {"command": "status" | "restart" | "backup" | "export-logs" | "import", "path": "..."}
+//   response: {"ok": true, "data": ...} or {"ok": false, "error": "..."}
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use crate::backup::{backups_dir, run_backup};
+use crate::events::{self, Event};
+use crate::importer::{open_import_window, sniff};
+use crate::logs::SharedLogStore;
+use crate::server::{get_server_url, SharedServerManager};
+use crate::settings::SharedSettings;
+
+/// Socket file name inside the data directory (Unix)
+#[cfg(unix)]
+pub const SOCKET_FILE: &str = "control.sock";
+/// Named pipe used on Windows; pipe names are machine-wide, so it's per OS user
+#[cfg(windows)]
+pub fn pipe_name() -> String {
+    let user: String = std::env::var("USERNAME")
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        .collect();
+    format!(r"\\.\pipe\moneywright-control-{}", user)
+}
+
+#[derive(Deserialize)]
+struct ControlRequest {
+    command: String,
+    #[serde(default)]
+    path: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ControlResponse {
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct ImportRequest {
+    path: String,
+}
+
+/// Start listening for control connections in the background
+pub fn start_control_server(app: AppHandle, data_dir: PathBuf) {
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = serve(app, &data_dir).await {
+            eprintln!("Warning: Control socket unavailable: {}", e);
+        }
+    });
+}
+
+#[cfg(unix)]
+async fn serve(app: AppHandle, data_dir: &Path) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+    use tokio::net::UnixListener;
+
+    let socket_path = data_dir.join(SOCKET_FILE);
+
+    // Remove a stale socket left behind by a previous run
+    let _ = fs::remove_file(&socket_path);
+
+    let listener = UnixListener::bind(&socket_path)
+        .map_err(|e| format!("Failed to bind {}: {}", socket_path.display(), e))?;
+
+    // Only the current user may control the app
+    fs::set_permissions(&socket_path, fs::Permissions::from_mode(0o600))
+        .map_err(|e| format!("Failed to restrict control socket permissions: {}", e))?;
+
+    println!("Control socket: {:?}", socket_path);
+
+    loop {
+        let (stream, _) = listener
+            .accept()
+            .await
+            .map_err(|e| format!("Accept failed: {}", e))?;
+        tauri::async_runtime::spawn(handle_connection(app.clone(), stream));
+    }
+}
+
+#[cfg(windows)]
+async fn serve(app: AppHandle, _data_dir: &Path) -> Result<(), String> {
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    let pipe_name = pipe_name();
+    let mut server = ServerOptions::new()
+        .first_pipe_instance(true)
+        .reject_remote_clients(true)
+        .create(&pipe_name)
+        .map_err(|e| format!("Failed to create {}: {}", pipe_name, e))?;
+
+    println!("Control pipe: {}", pipe_name);
+
+    loop {
+        server
+            .connect()
+            .await
+            .map_err(|e| format!("Pipe connect failed: {}", e))?;
+
+        // Create the next instance before handing off the connected one
+        let connected = server;
+        server = ServerOptions::new()
+            .reject_remote_clients(true)
+            .create(&pipe_name)
+            .map_err(|e| format!("Failed to create {}: {}", pipe_name, e))?;
+
+        tauri::async_runtime::spawn(handle_connection(app.clone(), connected));
+    }
+}
+
+async fn handle_connection<S>(app: AppHandle, stream: S)
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut lines = BufReader::new(reader).lines();
+
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let result = match serde_json::from_str::<ControlRequest>(&line) {
+            Ok(request) => dispatch(&app, request).await,
+            Err(e) => Err(format!("Invalid request: {}", e)),
+        };
+
+        let response = match result {
+            Ok(data) => ControlResponse { ok: true, data: Some(data), error: None },
+            Err(e) => ControlResponse { ok: false, data: None, error: Some(e) },
+        };
+
+        let mut out = serde_json::to_string(&response).unwrap_or_default();
+        out.push('\n');
+        if writer.write_all(out.as_bytes()).await.is_err() {
+            break;
+        }
+    }
+}
+
+async fn dispatch(app: &AppHandle, request: ControlRequest) -> Result<Value, String> {
+    match request.command.as_str() {
+        "status" => status(app).await,
+        "restart" => restart(app).await,
+        "backup" => backup(app).await,
+        "export-logs" => export_logs(app, request.path).await,
+        "import" => import_file(app, request.path),
+        other => Err(format!("Unknown command: {}", other)),
+    }
+}
+
+async fn data_dir(app: &AppHandle) -> PathBuf {
+    let manager = app.state::<SharedServerManager>();
+    let mgr = manager.lock().await;
+    mgr.data_dir().clone()
+}
+
+async fn status(app: &AppHandle) -> Result<Value, String> {
+    let manager = app.state::<SharedServerManager>();
+    let mgr = manager.lock().await;
+
+    Ok(json!({
+        "status": mgr.status().as_str(),
+        "url": get_server_url(),
+        "version": env!("CARGO_PKG_VERSION"),
+        "data_dir": mgr.data_dir().to_string_lossy(),
+    }))
+}
+
+async fn restart(app: &AppHandle) -> Result<Value, String> {
+    let manager = app.state::<SharedServerManager>().inner().clone();
+    let log_store = app.state::<SharedLogStore>().inner().clone();
+    crate::restart_server(app.clone(), manager, log_store).await?;
+    Ok(json!({ "url": get_server_url() }))
+}
+
+async fn backup(app: &AppHandle) -> Result<Value, String> {
+    let data_dir = data_dir(app).await;
+    let settings = app.state::<SharedSettings>().lock().await.get();
+    let dir = backups_dir(&data_dir, &settings);
+    let path = run_backup(app, data_dir, dir).await?;
+    Ok(json!({ "path": path.to_string_lossy() }))
+}
+
+async fn export_logs(app: &AppHandle, path: Option<String>) -> Result<Value, String> {
+    let path = match path {
+        Some(p) => PathBuf::from(p),
+        None => {
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            data_dir(app).await.join(format!("moneywright-logs-{}.txt", timestamp))
+        }
+    };
+
+    let lines = app.state::<SharedLogStore>().lock().await.get_all();
+    let mut content = lines.join("\n");
+    content.push('\n');
+    fs::write(&path, content)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+
+    Ok(json!({ "path": path.to_string_lossy(), "lines": lines.len() }))
+}
+
+/// Hand a file to the web app's import flow in the main window
+fn import_file(app: &AppHandle, path: Option<String>) -> Result<Value, String> {
+    let path = path.ok_or_else(|| "import requires a file path".to_string())?;
+    if !Path::new(&path).is_file() {
+        return Err(format!("Not a file: {}", path));
+    }
+
+    // Mint/YNAB/Quicken exports go through the converter window
+    if sniff(Path::new(&path)).is_some() {
+        open_import_window(app, Some(path.clone()));
+        return Ok(json!({ "path": path, "converter": true }));
+    }
+
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+
+    events::emit(app, Event::ImportFileRequested(&ImportRequest { path: path.clone() }))?;
+
+    Ok(json!({ "path": path }))
+}
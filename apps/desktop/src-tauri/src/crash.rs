@@ -0,0 +1,385 @@
+// Crash reporting: reports are written to data_dir/crashes and never leave the machine
+//
+// Rust panics are captured by a panic hook with a full backtrace. Native crashes
+// (e.g. inside the webview) and forced kills can't be handled reliably in-process,
+// so a session marker is written at startup and removed on clean exit; if it is
+// still present on the next launch, an "unclean exit" report is recorded instead,
+// dated by the session's last heartbeat (see heartbeat.rs).
+//
+// Minidump capture for native crashes is not done yet and stays open: it needs an
+// out-of-process handler (crash-handler and minidumper, with the app relaunching
+// itself as the watcher process) writing .dmp files next to these reports, and those
+// crates aren't in the dependency tree. Until then a segfault or abort in the shell or
+// the webview leaves no stack or register state behind, only the unclean exit report.
+
+use std::backtrace::Backtrace;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use crate::cleanups::{self, Cleanup};
+use crate::heartbeat;
+use crate::server::SharedServerManager;
+use crate::windows::open_injected_window;
+
+const CRASHES_DIR: &str = "crashes";
+const SESSION_MARKER: &str = "session.lock";
+
+/// Data directory of the current session, set when the handler is installed
+static SESSION_DATA_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CrashReport {
+    pub id: String,
+    pub timestamp: u64,
+    /// "panic" or "unclean_exit"
+    pub kind: String,
+    pub version: String,
+    pub os: String,
+    pub arch: String,
+    pub message: String,
+    pub location: Option<String>,
+    pub thread: Option<String>,
+    pub backtrace: Option<String>,
+    /// Unix time of the crashed session's last heartbeat (unclean exits only);
+    /// it died within a heartbeat interval after this
+    #[serde(default)]
+    pub last_heartbeat: Option<u64>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct CrashSummary {
+    pub id: String,
+    pub timestamp: u64,
+    pub kind: String,
+    pub version: String,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SessionMarker {
+    started: u64,
+    pid: u32,
+    version: String,
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+pub fn crashes_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join(CRASHES_DIR)
+}
+
+fn new_report(kind: &str, message: String) -> CrashReport {
+    let timestamp = unix_now();
+    CrashReport {
+        id: format!("{}-{}-{}", kind.replace('_', "-"), timestamp, std::process::id()),
+        timestamp,
+        kind: kind.to_string(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        message,
+        location: None,
+        thread: None,
+        backtrace: None,
+        last_heartbeat: None,
+    }
+}
+
+fn write_report(data_dir: &Path, report: &CrashReport) -> Result<PathBuf, String> {
+    let dir = crashes_dir(data_dir);
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create crashes directory: {}", e))?;
+    let path = dir.join(format!("{}.json", report.id));
+    let json = serde_json::to_string_pretty(report).map_err(|e| e.to_string())?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write crash report: {}", e))?;
+    Ok(path)
+}
+
+/// PID of another running instance whose session marker is in this data dir
+pub fn live_session(data_dir: &Path) -> Option<u32> {
+    let content = fs::read_to_string(data_dir.join(SESSION_MARKER)).ok()?;
+    let marker: SessionMarker = serde_json::from_str(&content).ok()?;
+    if marker.pid == std::process::id() {
+        return None;
+    }
+    let pid = sysinfo::Pid::from_u32(marker.pid);
+    let mut system = sysinfo::System::new();
+    system.refresh_processes(sysinfo::ProcessesToUpdate::Some(&[pid]), true);
+    // PIDs get reused, so it must also still be a Moneywright
+    let process = system.process(pid)?;
+    process
+        .name()
+        .to_string_lossy()
+        .to_lowercase()
+        .contains("moneywright")
+        .then_some(marker.pid)
+}
+
+/// Install the panic hook and check whether the previous session ended uncleanly
+/// Returns true if it did
+pub fn install_crash_handler(data_dir: PathBuf) -> bool {
+    let _ = SESSION_DATA_DIR.set(data_dir.clone());
+
+    // A leftover marker means the previous session never reached a clean exit
+    let marker_path = data_dir.join(SESSION_MARKER);
+    // Another instance still running on this data dir hasn't exited at all, see isolation.rs
+    let previous_unclean = marker_path.exists() && live_session(&data_dir).is_none();
+    if let Some(content) = fs::read_to_string(&marker_path).ok().filter(|_| previous_unclean) {
+        let previous: Option<SessionMarker> = serde_json::from_str(&content).ok();
+        // Only a beat of that same session tells when it died
+        let beat = heartbeat::read(&data_dir).filter(|b| previous.as_ref().is_some_and(|m| m.pid == b.pid));
+        let mut message = match &previous {
+            Some(m) => format!(
+                "Moneywright {} (pid {}, started at {}) exited without shutting down cleanly. \
+                 This usually means a native crash, a forced quit, or a power loss.",
+                m.version, m.pid, m.started
+            ),
+            None => "The previous session exited without shutting down cleanly.".to_string(),
+        };
+        if let Some(beat) = &beat {
+            message.push_str(&format!(
+                " Its last heartbeat was at {} with the server {}, so it stopped within {} seconds after that.",
+                beat.timestamp, beat.status, beat.interval_secs
+            ));
+        }
+        if let Some(previous) = &previous {
+            cleanups::record(Cleanup::lock(previous.pid, &marker_path));
+        }
+        let mut report = new_report("unclean_exit", message);
+        report.last_heartbeat = beat.map(|b| b.timestamp);
+        if let Err(e) = write_report(&data_dir, &report) {
+            eprintln!("Warning: {}", e);
+        }
+    }
+
+    let marker = SessionMarker {
+        started: unix_now(),
+        pid: std::process::id(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+    };
+    if let Ok(json) = serde_json::to_string(&marker) {
+        let _ = fs::write(&marker_path, json);
+    }
+
+    let previous_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let message = if let Some(s) = info.payload().downcast_ref::<&str>() {
+            s.to_string()
+        } else if let Some(s) = info.payload().downcast_ref::<String>() {
+            s.clone()
+        } else {
+            "Unknown panic".to_string()
+        };
+
+        let mut report = new_report("panic", message);
+        report.location = info.location().map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()));
+        report.thread = std::thread::current().name().map(|n| n.to_string());
+        report.backtrace = Some(Backtrace::force_capture().to_string());
+
+        match write_report(&data_dir, &report) {
+            Ok(path) => eprintln!("Crash report written to {:?}", path),
+            Err(e) => eprintln!("Failed to write crash report: {}", e),
+        }
+
+        previous_hook(info);
+    }));
+
+    previous_unclean
+}
+
+/// Remove the session marker so the next launch doesn't report an unclean exit
+pub fn mark_clean_exit() {
+    if let Some(data_dir) = SESSION_DATA_DIR.get() {
+        let _ = fs::remove_file(data_dir.join(SESSION_MARKER));
+    }
+}
+
+/// Only ids generated by `new_report` are accepted, to keep lookups inside the crashes dir
+fn validate_id(id: &str) -> Result<(), String> {
+    if !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        Ok(())
+    } else {
+        Err(format!("Invalid crash report id: {}", id))
+    }
+}
+
+pub fn list_reports(data_dir: &Path) -> Vec<CrashSummary> {
+    let mut reports: Vec<CrashSummary> = fs::read_dir(crashes_dir(data_dir))
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|e| e.path().extension().is_some_and(|ext| ext == "json"))
+                .filter_map(|e| fs::read_to_string(e.path()).ok())
+                .filter_map(|content| serde_json::from_str::<CrashReport>(&content).ok())
+                .map(|r| CrashSummary {
+                    id: r.id,
+                    timestamp: r.timestamp,
+                    kind: r.kind,
+                    version: r.version,
+                    message: r.message,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    reports.sort_by_key(|r| std::cmp::Reverse(r.timestamp));
+    reports
+}
+
+pub fn read_report(data_dir: &Path, id: &str) -> Result<CrashReport, String> {
+    validate_id(id)?;
+    let content = fs::read_to_string(crashes_dir(data_dir).join(format!("{}.json", id)))
+        .map_err(|e| format!("Failed to read crash report: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Invalid crash report: {}", e))
+}
+
+pub fn delete_report(data_dir: &Path, id: &str) -> Result<(), String> {
+    validate_id(id)?;
+    fs::remove_file(crashes_dir(data_dir).join(format!("{}.json", id)))
+        .map_err(|e| format!("Failed to delete crash report: {}", e))
+}
+
+/// Copy a crash report to the user's Downloads folder so it can be attached to an issue
+pub fn export_report(data_dir: &Path, id: &str) -> Result<PathBuf, String> {
+    validate_id(id)?;
+    let source = crashes_dir(data_dir).join(format!("{}.json", id));
+    let target_dir = dirs::download_dir()
+        .or_else(dirs::home_dir)
+        .ok_or_else(|| "No Downloads folder found".to_string())?;
+    let target = target_dir.join(format!("moneywright-crash-{}.json", id));
+    fs::copy(&source, &target).map_err(|e| format!("Failed to export crash report: {}", e))?;
+    Ok(target)
+}
+
+async fn data_dir(manager: &SharedServerManager) -> PathBuf {
+    manager.lock().await.data_dir().clone()
+}
+
+/// List crash reports, newest first
+#[tauri::command]
+pub async fn list_crash_reports(manager: tauri::State<'_, SharedServerManager>) -> Result<Vec<CrashSummary>, String> {
+    Ok(list_reports(&data_dir(&manager).await))
+}
+
+/// Get a full crash report including the backtrace
+#[tauri::command]
+pub async fn get_crash_report(manager: tauri::State<'_, SharedServerManager>, id: String) -> Result<CrashReport, String> {
+    read_report(&data_dir(&manager).await, &id)
+}
+
+/// Delete a crash report
+#[tauri::command]
+pub async fn delete_crash_report(manager: tauri::State<'_, SharedServerManager>, id: String) -> Result<(), String> {
+    delete_report(&data_dir(&manager).await, &id)
+}
+
+/// Export a crash report to the Downloads folder and reveal it
+#[tauri::command]
+pub async fn export_crash_report(manager: tauri::State<'_, SharedServerManager>, id: String) -> Result<String, String> {
+    let path = export_report(&data_dir(&manager).await, &id)?;
+    if let Some(parent) = path.parent() {
+        let _ = open::that(parent);
+    }
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Open the crash reports window
+pub fn open_crash_reports_window(app: &AppHandle) {
+    // Static UI; report contents are inserted with textContent/escaping on the JS side
+    let script = r#"
+        const tauriApi = window.__TAURI__;
+
+        document.documentElement.innerHTML = `
+<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <title>Crash Reports</title>
+    <style>
+        __BASE_STYLE__
+        .layout { flex: 1; display: flex; min-height: 0; }
+        #list { width: 280px; border-right: 1px solid rgba(255, 255, 255, 0.06); overflow-y: auto; }
+        .item { padding: 12px 16px; border-bottom: 1px solid rgba(255, 255, 255, 0.04); cursor: pointer; }
+        .item:hover, .item.selected { background: #0a0a0a; }
+        .item .kind { font-size: 11px; text-transform: uppercase; letter-spacing: 0.04em; }
+        .item .msg { margin-top: 4px; color: #a1a1aa; overflow: hidden; text-overflow: ellipsis; white-space: nowrap; }
+        #detail { flex: 1; overflow-y: auto; padding: 16px; }
+        #detail h2 { font-size: 16px; margin-bottom: 8px; }
+        #detail .meta { margin-bottom: 12px; }
+        #detail pre { white-space: pre-wrap; word-break: break-all; color: #a1a1aa; }
+        .actions { display: flex; gap: 8px; margin-bottom: 16px; }
+    </style>
+</head>
+<body>
+    <div class="toolbar">
+        <button id="refreshBtn">Refresh</button>
+        <span class="muted">Crash reports are stored locally and never uploaded. Native crashes aren't captured as minidumps; they show up as unclean exits.</span>
+    </div>
+    <div class="layout">
+        <div id="list" role="listbox" aria-label="Crash reports"></div>
+        <div id="detail"><div class="empty-state">Select a report</div></div>
+    </div>
+</body>
+</html>`;
+
+        const $ = id => document.getElementById(id);
+        let selected = null;
+
+        function formatTime(ts) {
+            return new Date(ts * 1000).toLocaleString();
+        }
+
+        async function refresh() {
+            const reports = await tauriApi.core.invoke('list_crash_reports');
+            if (reports.length === 0) {
+                $('list').innerHTML = '<div class="empty-state" style="padding: 24px">No crash reports</div>';
+                $('detail').innerHTML = '<div class="empty-state">Nothing to show</div>';
+                return;
+            }
+            $('list').innerHTML = reports.map(r =>
+                '<div class="item' + (r.id === selected ? ' selected' : '') + '" role="option" tabindex="0" data-id="' + escapeHtml(r.id) + '">' +
+                '<div class="kind ' + (r.kind === 'panic' ? 'fail' : 'warn') + '">' + escapeHtml(r.kind.replace('_', ' ')) + '</div>' +
+                '<div class="muted">' + escapeHtml(formatTime(r.timestamp)) + ' · v' + escapeHtml(r.version) + '</div>' +
+                '<div class="msg">' + escapeHtml(r.message) + '</div></div>'
+            ).join('');
+            document.querySelectorAll('.item').forEach(el => {
+                el.onclick = () => show(el.dataset.id);
+                el.onkeydown = (e) => { if (e.key === 'Enter') show(el.dataset.id); };
+            });
+        }
+
+        async function show(id) {
+            selected = id;
+            const r = await tauriApi.core.invoke('get_crash_report', { id });
+            $('detail').innerHTML =
+                '<h2>' + escapeHtml(r.kind === 'panic' ? 'Panic' : 'Unclean exit') + '</h2>' +
+                '<div class="meta muted">' + escapeHtml(formatTime(r.timestamp)) + ' · v' + escapeHtml(r.version) + ' · ' + escapeHtml(r.os) + '/' + escapeHtml(r.arch) + '</div>' +
+                '<div class="actions"><button id="exportBtn">Export</button><button class="danger" id="deleteBtn">Delete</button></div>' +
+                '<pre class="mono">' + escapeHtml(r.message) +
+                (r.location ? '\n\nat ' + escapeHtml(r.location) : '') +
+                (r.thread ? '\nthread: ' + escapeHtml(r.thread) : '') +
+                (r.backtrace ? '\n\n' + escapeHtml(r.backtrace) : '') + '</pre>';
+            $('exportBtn').onclick = () => tauriApi.core.invoke('export_crash_report', { id });
+            $('deleteBtn').onclick = async () => {
+                await tauriApi.core.invoke('delete_crash_report', { id });
+                selected = null;
+                $('detail').innerHTML = '<div class="empty-state">Select a report</div>';
+                refresh();
+            };
+            refresh();
+        }
+
+        $('refreshBtn').onclick = refresh;
+        refresh();
+    "#;
+
+    open_injected_window(app, "crashes", "Crash Reports", (900.0, 560.0), true, script);
+}
+
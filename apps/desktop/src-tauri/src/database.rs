@@ -0,0 +1,832 @@
+// Database configuration: SQLite or PostgreSQL, with the PostgreSQL password kept in the keychain
+//
+// The data dir .env holds DATABASE_URL without the password; `server_database_url`
+// adds it back from the keychain when the sidecar is started. The embedded backend is
+// a PostgreSQL server the app runs itself, see postgres.rs.
+
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use rusqlite::{Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Url};
+use tokio_postgres::config::{Host, SslMode};
+use tokio_postgres::Config;
+use tokio_postgres_rustls::MakeRustlsConnect;
+use crate::backup::sqlite_db_path;
+use crate::doctor::bundled_migrations;
+use crate::jobs::{start_job, JobKind};
+use crate::keychain;
+use crate::logs::{log_line, SharedLogStore};
+use crate::postgres;
+use crate::server::{read_database_url, remove_database_url, stop_server, write_database_url, SharedServerManager};
+use crate::windows::open_injected_window;
+
+/// Keychain entry holding the PostgreSQL password
+pub const PASSWORD_KEY: &str = "database-password";
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const DEFAULT_PG_PORT: u16 = 5432;
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    Sqlite,
+    Postgres,
+    /// PostgreSQL run by the app from the data dir
+    Embedded,
+}
+
+/// Current configuration as shown in the Database window (never includes the password)
+#[derive(Clone, Serialize)]
+pub struct DatabaseConfig {
+    pub backend: Backend,
+    pub host: String,
+    pub port: u16,
+    pub database: String,
+    pub user: String,
+    /// disable, prefer or require
+    pub ssl_mode: String,
+    /// A password is stored (keychain or inline in .env)
+    pub password_saved: bool,
+    pub sqlite_path: String,
+}
+
+#[derive(Deserialize)]
+pub struct DatabaseForm {
+    pub backend: Backend,
+    #[serde(default)]
+    pub host: String,
+    #[serde(default)]
+    pub port: Option<u16>,
+    #[serde(default)]
+    pub database: String,
+    #[serde(default)]
+    pub user: String,
+    /// None or empty keeps the saved password
+    #[serde(default)]
+    pub password: Option<String>,
+    #[serde(default)]
+    pub ssl_mode: String,
+}
+
+/// What stopped a connection test, so the window can say what to change
+#[derive(Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProblemKind {
+    InvalidSettings,
+    Timeout,
+    Unreachable,
+    Tls,
+    Authentication,
+    DatabaseMissing,
+    /// The user can't create the tables migrations need
+    Permission,
+    /// Migrated by a newer Moneywright than this one
+    SchemaNewer,
+    Other,
+}
+
+#[derive(Clone, Serialize)]
+pub struct ConnectionProblem {
+    pub kind: ProblemKind,
+    pub message: String,
+    /// SQLSTATE of a server error
+    pub code: Option<String>,
+}
+
+impl ConnectionProblem {
+    fn new(kind: ProblemKind, message: impl Into<String>) -> Self {
+        ConnectionProblem {
+            kind,
+            message: message.into(),
+            code: None,
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct ConnectionTestResult {
+    /// Connected, and the server can run on the database as it is
+    pub ok: bool,
+    pub server_version: Option<String>,
+    pub latency_ms: u64,
+    pub migrations_applied: Option<i64>,
+    pub migrations_bundled: Option<usize>,
+    pub problem: Option<ConnectionProblem>,
+}
+
+impl ConnectionTestResult {
+    fn failed(problem: ConnectionProblem, started: Instant) -> Self {
+        ConnectionTestResult {
+            ok: false,
+            server_version: None,
+            latency_ms: started.elapsed().as_millis() as u64,
+            migrations_applied: None,
+            migrations_bundled: None,
+            problem: Some(problem),
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct ApplyResult {
+    pub backend: Backend,
+    /// False in development builds, where the API runs separately
+    pub restarted: bool,
+    pub bundled_migrations: Option<usize>,
+    pub migrations_before: Option<i64>,
+    pub migrations_after: Option<i64>,
+}
+
+fn ssl_mode_name(mode: SslMode) -> &'static str {
+    match mode {
+        SslMode::Disable => "disable",
+        SslMode::Require => "require",
+        _ => "prefer",
+    }
+}
+
+fn parse_url(url: &str) -> Result<Config, String> {
+    url.parse::<Config>()
+        .map_err(|e| format!("Invalid PostgreSQL URL: {}", e))
+}
+
+/// Password from the keychain, falling back to one written inline in .env
+fn saved_password(database_url: Option<&str>) -> Option<String> {
+    if let Ok(Some(password)) = keychain::get_secret(PASSWORD_KEY) {
+        return Some(password);
+    }
+    let config = parse_url(database_url?).ok()?;
+    config
+        .get_password()
+        .map(|p| String::from_utf8_lossy(p).to_string())
+}
+
+/// DATABASE_URL for the sidecar, with the keychain password filled in
+pub fn server_database_url(data_dir: &Path) -> Option<String> {
+    let database_url = read_database_url(data_dir)?;
+    let Ok(mut url) = Url::parse(&database_url) else {
+        return Some(database_url);
+    };
+    if url.password().is_none() {
+        match keychain::get_secret(PASSWORD_KEY) {
+            Ok(Some(password)) => {
+                let _ = url.set_password(Some(&password));
+            }
+            Ok(None) => {}
+            Err(e) => eprintln!("Warning: {}", e),
+        }
+    }
+    Some(url.to_string())
+}
+
+/// Save a PostgreSQL URL, moving an inline password into the keychain
+pub fn save_database_url(data_dir: &Path, database_url: &str) -> Result<(), String> {
+    let config = parse_url(database_url)?;
+    let mut url = Url::parse(database_url).map_err(|e| format!("Invalid PostgreSQL URL: {}", e))?;
+    if let Some(password) = config.get_password() {
+        keychain::set_secret(PASSWORD_KEY, &String::from_utf8_lossy(password))?;
+        let _ = url.set_password(None);
+    }
+    write_database_url(data_dir, url.as_str())
+}
+
+fn current_config(data_dir: &Path) -> DatabaseConfig {
+    let database_url = read_database_url(data_dir);
+    let mut config = DatabaseConfig {
+        backend: Backend::Sqlite,
+        host: "localhost".to_string(),
+        port: DEFAULT_PG_PORT,
+        database: "moneywright".to_string(),
+        user: String::new(),
+        ssl_mode: "prefer".to_string(),
+        password_saved: false,
+        sqlite_path: sqlite_db_path(data_dir).to_string_lossy().to_string(),
+    };
+
+    let Some(pg) = database_url.as_deref().and_then(|url| parse_url(url).ok()) else {
+        return config;
+    };
+    config.backend = if postgres::is_active(data_dir) {
+        Backend::Embedded
+    } else {
+        Backend::Postgres
+    };
+    if let Some(Host::Tcp(host)) = pg.get_hosts().first() {
+        config.host = host.clone();
+    }
+    if let Some(port) = pg.get_ports().first() {
+        config.port = *port;
+    }
+    if let Some(database) = pg.get_dbname() {
+        config.database = database.to_string();
+    }
+    config.user = pg.get_user().unwrap_or_default().to_string();
+    config.ssl_mode = ssl_mode_name(pg.get_ssl_mode()).to_string();
+    config.password_saved = saved_password(database_url.as_deref()).is_some();
+    config
+}
+
+/// Build a DATABASE_URL (without password) from the form
+fn form_url(form: &DatabaseForm) -> Result<String, String> {
+    let host = form.host.trim();
+    let database = form.database.trim();
+    let user = form.user.trim();
+    if host.is_empty() {
+        return Err("Host is required".to_string());
+    }
+    if database.is_empty() {
+        return Err("Database name is required".to_string());
+    }
+    if user.is_empty() {
+        return Err("User is required".to_string());
+    }
+
+    let mut url = Url::parse("postgresql://localhost").map_err(|e| e.to_string())?;
+    url.set_host(Some(host)).map_err(|e| format!("Invalid host: {}", e))?;
+    url.set_port(Some(form.port.unwrap_or(DEFAULT_PG_PORT)))
+        .map_err(|_| "Invalid port".to_string())?;
+    url.set_username(user).map_err(|_| "Invalid user name".to_string())?;
+    url.set_path(&format!("/{}", database));
+    if matches!(form.ssl_mode.as_str(), "disable" | "require") {
+        url.query_pairs_mut().append_pair("sslmode", &form.ssl_mode);
+    }
+    Ok(url.to_string())
+}
+
+/// Connection config for the form, using the saved password when none was entered
+pub fn form_config(form: &DatabaseForm, data_dir: &Path) -> Result<(String, Option<String>, Config), String> {
+    let url = form_url(form)?;
+    let password = form
+        .password
+        .clone()
+        .filter(|p| !p.is_empty())
+        .or_else(|| saved_password(read_database_url(data_dir).as_deref()));
+
+    let mut config = parse_url(&url)?;
+    config.connect_timeout(CONNECT_TIMEOUT);
+    if let Some(password) = &password {
+        config.password(password);
+    }
+    Ok((url, password, config))
+}
+
+fn tls_connector() -> Result<MakeRustlsConnect, String> {
+    let roots = rustls::RootCertStore {
+        roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+    };
+    let config = rustls::ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .map_err(|e| format!("TLS setup failed: {}", e))?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    Ok(MakeRustlsConnect::new(config))
+}
+
+pub fn describe_error(e: &tokio_postgres::Error) -> String {
+    match e.as_db_error() {
+        Some(db) => format!("{} ({})", db.message(), db.code().code()),
+        None => e.to_string(),
+    }
+}
+
+/// Connection config of the running embedded cluster
+fn embedded_config(data_dir: &Path) -> Result<Config, String> {
+    if !postgres::is_active(data_dir) {
+        return Err("The embedded PostgreSQL is set up when you apply".to_string());
+    }
+    let url = read_database_url(data_dir).ok_or("No database URL saved")?;
+    let mut config = parse_url(&url)?;
+    config.connect_timeout(CONNECT_TIMEOUT);
+    if let Some(password) = keychain::get_secret(postgres::PASSWORD_KEY)? {
+        config.password(&password);
+    }
+    Ok(config)
+}
+
+/// Sort a failed connection into what the user has to change
+fn classify(e: &tokio_postgres::Error) -> ConnectionProblem {
+    if let Some(db) = e.as_db_error() {
+        let kind = match db.code().code() {
+            // invalid_password, invalid_authorization_specification
+            "28P01" | "28000" => ProblemKind::Authentication,
+            // invalid_catalog_name
+            "3D000" => ProblemKind::DatabaseMissing,
+            // insufficient_privilege
+            "42501" => ProblemKind::Permission,
+            _ => ProblemKind::Other,
+        };
+        return ConnectionProblem {
+            kind,
+            message: describe_error(e),
+            code: Some(db.code().code().to_string()),
+        };
+    }
+    let mut text = e.to_string();
+    let mut source = std::error::Error::source(e);
+    while let Some(inner) = source {
+        text = format!("{}: {}", text, inner);
+        source = inner.source();
+    }
+    let lower = text.to_lowercase();
+    let kind = if lower.contains("tls") || lower.contains("certificate") || lower.contains("ssl") {
+        ProblemKind::Tls
+    } else if e.is_closed() || lower.contains("refused") || lower.contains("resolve") || lower.contains("unreachable") {
+        ProblemKind::Unreachable
+    } else {
+        ProblemKind::Other
+    };
+    ConnectionProblem::new(kind, text)
+}
+
+async fn try_connect(config: &Config) -> Result<tokio_postgres::Client, ConnectionProblem> {
+    let tls = tls_connector().map_err(|e| ConnectionProblem::new(ProblemKind::Tls, e))?;
+    let (client, connection) = tokio::time::timeout(CONNECT_TIMEOUT, config.connect(tls))
+        .await
+        .map_err(|_| ConnectionProblem::new(ProblemKind::Timeout, "Connection timed out"))?
+        .map_err(|e| classify(&e))?;
+
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = connection.await {
+            eprintln!("Warning: PostgreSQL connection error: {}", e);
+        }
+    });
+    Ok(client)
+}
+
+pub async fn connect(config: &Config) -> Result<tokio_postgres::Client, String> {
+    try_connect(config).await.map_err(|problem| problem.message)
+}
+
+/// Connect and check the server could run on the database: its migrations must not be
+/// newer than this app's, and the user must be able to create what the next ones add
+async fn check_postgres(config: &Config, bundled: Option<usize>) -> ConnectionTestResult {
+    let started = Instant::now();
+    let client = match try_connect(config).await {
+        Ok(client) => client,
+        Err(problem) => return ConnectionTestResult::failed(problem, started),
+    };
+    let server_version = client
+        .query_one("SELECT version()", &[])
+        .await
+        .ok()
+        .map(|row| row.get::<_, String>(0));
+    let latency_ms = started.elapsed().as_millis() as u64;
+    let applied = postgres_migrations(&client).await;
+
+    let privileges = client
+        .query_one(
+            "SELECT has_schema_privilege(current_user, 'public', 'CREATE'), \
+             has_database_privilege(current_user, current_database(), 'CREATE'), \
+             to_regnamespace('drizzle') IS NOT NULL",
+            &[],
+        )
+        .await;
+    let problem = match privileges {
+        Err(e) => Some(classify(&e)),
+        Ok(row) => {
+            let (public, database, drizzle): (bool, bool, bool) = (row.get(0), row.get(1), row.get(2));
+            let newer = matches!((applied, bundled), (Some(applied), Some(bundled)) if applied > bundled as i64);
+            if !public {
+                Some(ConnectionProblem::new(
+                    ProblemKind::Permission,
+                    "The user can't create tables in the public schema (GRANT CREATE ON SCHEMA public)",
+                ))
+            } else if !drizzle && !database {
+                Some(ConnectionProblem::new(
+                    ProblemKind::Permission,
+                    "The user can't create the schema migrations are tracked in (GRANT CREATE ON DATABASE)",
+                ))
+            } else if newer {
+                Some(ConnectionProblem::new(
+                    ProblemKind::SchemaNewer,
+                    format!(
+                        "The database has {} migrations, more than the {} this version knows; it was used by a newer Moneywright",
+                        applied.unwrap_or_default(),
+                        bundled.unwrap_or_default()
+                    ),
+                ))
+            } else {
+                None
+            }
+        }
+    };
+
+    ConnectionTestResult {
+        ok: problem.is_none(),
+        server_version,
+        latency_ms,
+        migrations_applied: applied,
+        migrations_bundled: bundled,
+        problem,
+    }
+}
+
+fn bundled_postgres_migrations(app: &AppHandle) -> Option<usize> {
+    app.path().resource_dir().ok().and_then(|dir| bundled_migrations(&dir, true))
+}
+
+/// Applied drizzle migrations in a PostgreSQL database (0 for a fresh one)
+pub async fn postgres_migrations(client: &tokio_postgres::Client) -> Option<i64> {
+    let row = client
+        .query_one("SELECT to_regclass('drizzle.__drizzle_migrations') IS NOT NULL", &[])
+        .await
+        .ok()?;
+    if !row.get::<_, bool>(0) {
+        return Some(0);
+    }
+    client
+        .query_one("SELECT COUNT(*) FROM drizzle.__drizzle_migrations", &[])
+        .await
+        .ok()
+        .map(|row| row.get::<_, i64>(0))
+}
+
+/// Applied drizzle migrations in the SQLite database (0 if it doesn't exist yet)
+fn sqlite_migrations(data_dir: &Path) -> Option<i64> {
+    let db_path = sqlite_db_path(data_dir);
+    if !db_path.exists() {
+        return Some(0);
+    }
+    Connection::open_with_flags(&db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .and_then(|conn| conn.query_row("SELECT COUNT(*) FROM __drizzle_migrations", [], |row| row.get::<_, i64>(0)))
+        .ok()
+}
+
+async fn applied_migrations(backend: Backend, config: &Config, data_dir: &Path) -> Option<i64> {
+    match backend {
+        Backend::Sqlite => sqlite_migrations(data_dir),
+        Backend::Postgres | Backend::Embedded => postgres_migrations(&connect(config).await.ok()?).await,
+    }
+}
+
+/// Get the current database configuration
+#[tauri::command]
+pub async fn get_database_config(manager: tauri::State<'_, SharedServerManager>) -> Result<DatabaseConfig, String> {
+    let data_dir = manager.lock().await.data_dir().clone();
+    Ok(current_config(&data_dir))
+}
+
+/// Try connecting with the given settings without saving them, checking the server
+/// could use the database
+#[tauri::command]
+pub async fn test_database_connection(
+    app: AppHandle,
+    manager: tauri::State<'_, SharedServerManager>,
+    form: DatabaseForm,
+) -> Result<ConnectionTestResult, String> {
+    let data_dir = manager.lock().await.data_dir().clone();
+    let started = Instant::now();
+
+    if form.backend == Backend::Sqlite {
+        let version = Connection::open_in_memory()
+            .and_then(|conn| conn.query_row("SELECT sqlite_version()", [], |row| row.get::<_, String>(0)))
+            .map_err(|e| e.to_string())?;
+        return Ok(ConnectionTestResult {
+            ok: true,
+            server_version: Some(format!("SQLite {}", version)),
+            latency_ms: started.elapsed().as_millis() as u64,
+            migrations_applied: sqlite_migrations(&data_dir),
+            migrations_bundled: app.path().resource_dir().ok().and_then(|dir| bundled_migrations(&dir, false)),
+            problem: None,
+        });
+    }
+
+    let config = match form.backend {
+        Backend::Embedded => embedded_config(&data_dir),
+        _ => form_config(&form, &data_dir).map(|(_, _, config)| config),
+    };
+    match config {
+        Ok(config) => Ok(check_postgres(&config, bundled_postgres_migrations(&app)).await),
+        Err(e) => Ok(ConnectionTestResult::failed(
+            ConnectionProblem::new(ProblemKind::InvalidSettings, e),
+            started,
+        )),
+    }
+}
+
+/// Undo a cancelled switch: stop the server mid-migration (migrations run in a
+/// transaction, so nothing half-applied is left), restore the previous settings and
+/// start again. Returns the message reported for the cancelled job.
+async fn roll_back(
+    app: &AppHandle,
+    manager: &SharedServerManager,
+    log_store: &SharedLogStore,
+    data_dir: &Path,
+    previous_url: Option<String>,
+    previous_password: Option<String>,
+) -> String {
+    if let Err(e) = stop_server(manager.clone()).await {
+        eprintln!("Warning: {}", e);
+    }
+
+    let restored = match &previous_url {
+        Some(url) => write_database_url(data_dir, url),
+        None => remove_database_url(data_dir),
+    }
+    .and_then(|_| match &previous_password {
+        Some(password) => keychain::set_secret(PASSWORD_KEY, password),
+        None => keychain::delete_secret(PASSWORD_KEY),
+    });
+    if let Err(e) = restored {
+        return format!("Cancelled, but the previous database settings could not be restored: {}", e);
+    }
+    log_line(app, log_store, "Database change cancelled, previous settings restored", "info").await;
+
+    match crate::restart_server(app.clone(), manager.clone(), log_store.clone()).await {
+        Ok(()) => "Cancelled. The previous database settings were restored.".to_string(),
+        Err(e) => format!("Cancelled and restored the previous settings, but the server failed to start: {}", e),
+    }
+}
+
+/// Save the configuration, restart the server and report which migrations ran
+#[tauri::command]
+pub async fn apply_database_config(
+    app: AppHandle,
+    manager: tauri::State<'_, SharedServerManager>,
+    log_store: tauri::State<'_, SharedLogStore>,
+    form: DatabaseForm,
+) -> Result<ApplyResult, String> {
+    let manager = manager.inner().clone();
+    let log_store = log_store.inner().clone();
+    let data_dir = manager.lock().await.data_dir().clone();
+
+    let (url, password, config) = match form.backend {
+        Backend::Postgres => {
+            let (url, password, config) = form_config(&form, &data_dir)?;
+            // Don't switch to a database the server can't use
+            let check = check_postgres(&config, bundled_postgres_migrations(&app)).await;
+            if let Some(problem) = check.problem {
+                return Err(problem.message);
+            }
+            (Some(url), password, config)
+        }
+        Backend::Embedded => {
+            let (url, password) = postgres::prepare(&app, &log_store, &data_dir).await?;
+            let mut config = parse_url(&url)?;
+            config.connect_timeout(CONNECT_TIMEOUT);
+            config.password(&password);
+            (Some(url), Some(password), config)
+        }
+        Backend::Sqlite => (None, None, Config::new()),
+    };
+    let migrations_before = applied_migrations(form.backend, &config, &data_dir).await;
+    let previous_url = read_database_url(&data_dir);
+    let previous_password = keychain::get_secret(PASSWORD_KEY).ok().flatten();
+
+    match &url {
+        Some(url) => {
+            if let Some(password) = &password {
+                keychain::set_secret(PASSWORD_KEY, password)?;
+            }
+            write_database_url(&data_dir, url)?;
+            let msg = match form.backend {
+                Backend::Embedded => "Database switched to the embedded PostgreSQL",
+                _ => "Database switched to PostgreSQL",
+            };
+            log_line(&app, &log_store, msg, "info").await;
+        }
+        None => {
+            remove_database_url(&data_dir)?;
+            if let Err(e) = keychain::delete_secret(PASSWORD_KEY) {
+                eprintln!("Warning: {}", e);
+            }
+            log_line(&app, &log_store, "Database switched to SQLite", "info").await;
+        }
+    }
+
+    // In dev mode the API runs separately (`bun run dev`)
+    let restarted = cfg!(not(debug_assertions));
+    if restarted {
+        // The server runs pending migrations on start
+        let job = start_job(&app, JobKind::Migration, "Restarting server and running migrations", true);
+        let result = tokio::select! {
+            restarted = crate::restart_server(app.clone(), manager.clone(), log_store.clone()) => {
+                restarted.map_err(|e| format!("Settings saved, but the server failed to restart: {}", e))
+            }
+            _ = job.cancelled() => {
+                Err(roll_back(&app, &manager, &log_store, &data_dir, previous_url, previous_password).await)
+            }
+        };
+        job.finish(&result);
+        result?;
+        crate::refresh_main_window(&app);
+    }
+
+    let migrations_after = applied_migrations(form.backend, &config, &data_dir).await;
+    let bundled = app
+        .path()
+        .resource_dir()
+        .ok()
+        .and_then(|dir| bundled_migrations(&dir, form.backend != Backend::Sqlite));
+
+    Ok(ApplyResult {
+        backend: form.backend,
+        restarted,
+        bundled_migrations: bundled,
+        migrations_before,
+        migrations_after,
+    })
+}
+
+/// Open the database settings window
+pub fn open_database_window(app: &AppHandle) {
+    // Static UI; values are assigned to inputs, never inserted as HTML
+    let script = r#"
+        const tauriApi = window.__TAURI__;
+
+        document.documentElement.innerHTML = `
+<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <title>Database</title>
+    <style>
+        __BASE_STYLE__
+        #content { flex: 1; overflow-y: auto; padding: 20px 24px; }
+        label.option { display: flex; align-items: center; gap: 8px; margin: 6px 0; cursor: pointer; }
+        .grid { display: grid; grid-template-columns: 110px 1fr; gap: 10px 12px; align-items: center; margin-top: 16px; }
+        .grid input, .grid select { width: 100%; }
+        .password { display: flex; gap: 8px; }
+        .password input { flex: 1; }
+        #result { margin-top: 18px; line-height: 1.5; white-space: pre-wrap; }
+        .footer { display: flex; align-items: center; gap: 12px; padding: 14px 24px; border-top: 1px solid rgba(255, 255, 255, 0.06); }
+        .footer .spacer { flex: 1; }
+    </style>
+</head>
+<body>
+    <div id="content">
+        <label class="option"><input type="radio" name="backend" value="sqlite"> SQLite (stored in the data folder)</label>
+        <label class="option"><input type="radio" name="backend" value="embedded"> PostgreSQL (managed by Moneywright)</label>
+        <label class="option"><input type="radio" name="backend" value="postgres"> PostgreSQL</label>
+        <p class="muted" id="embeddedNote" style="margin-top: 8px">Runs a private PostgreSQL server from the data folder. The first time, about 30 MB are downloaded.</p>
+        <p class="muted mono" id="sqlitePath" style="margin-top: 8px"></p>
+        <div class="grid" id="pgFields">
+            <label for="host">Host</label><input type="text" id="host">
+            <label for="port">Port</label><input type="number" id="port" min="1" max="65535">
+            <label for="database">Database</label><input type="text" id="database">
+            <label for="user">User</label><input type="text" id="user" autocomplete="off">
+            <label for="password">Password</label>
+            <div class="password">
+                <input type="password" id="password" autocomplete="off">
+                <button id="showBtn" aria-label="Show password">Show</button>
+            </div>
+            <label for="sslMode">TLS</label>
+            <select id="sslMode">
+                <option value="prefer">Prefer</option>
+                <option value="require">Require</option>
+                <option value="disable">Disable</option>
+            </select>
+        </div>
+        <div id="result" role="status"></div>
+    </div>
+    <div class="footer">
+        <button id="testBtn">Test Connection</button>
+        <button id="migrateBtn" style="display: none">Move My Data</button>
+        <span class="spacer"></span>
+        <button id="cancelBtn" style="display: none">Cancel</button>
+        <button id="applyBtn" class="primary">Apply &amp; Restart</button>
+    </div>
+</body>
+</html>`;
+
+        const $ = id => document.getElementById(id);
+        const backend = () => document.querySelector('input[name=backend]:checked').value;
+
+        function form() {
+            return {
+                backend: backend(),
+                host: $('host').value.trim(),
+                port: parseInt($('port').value, 10) || null,
+                database: $('database').value.trim(),
+                user: $('user').value.trim(),
+                password: $('password').value || null,
+                ssl_mode: $('sslMode').value,
+            };
+        }
+
+        function showResult(text, cls) {
+            $('result').className = cls || '';
+            $('result').textContent = text;
+        }
+
+        function updateBackend() {
+            const postgres = backend() === 'postgres';
+            $('pgFields').style.display = postgres ? 'grid' : 'none';
+            $('sqlitePath').style.display = backend() === 'sqlite' ? 'block' : 'none';
+            $('embeddedNote').style.display = backend() === 'embedded' ? 'block' : 'none';
+            // Moving data only goes from SQLite to a PostgreSQL database
+            $('migrateBtn').style.display = currentBackend === 'sqlite' && backend() !== 'sqlite' ? '' : 'none';
+        }
+
+        function describeMigrations(r) {
+            if (!r.restarted) return 'Saved. Restart the development API to use the new database.';
+            const lines = ['Saved. The server was restarted.'];
+            if (r.migrations_after != null) {
+                const ran = r.migrations_before != null ? r.migrations_after - r.migrations_before : null;
+                if (ran != null) lines.push(ran > 0 ? 'Applied ' + ran + ' migration(s).' : 'No new migrations were needed.');
+                if (r.bundled_migrations != null) {
+                    lines.push(r.migrations_after + ' of ' + r.bundled_migrations + ' migrations applied.');
+                    if (r.migrations_after < r.bundled_migrations) lines.push('Some migrations did not run. Check View > View Logs for errors.');
+                }
+            } else {
+                lines.push('Could not read the migration state.');
+            }
+            return lines.join('\n');
+        }
+
+        document.querySelectorAll('input[name=backend]').forEach(r => r.onchange = updateBackend);
+        $('showBtn').onclick = () => {
+            const hidden = $('password').type === 'password';
+            $('password').type = hidden ? 'text' : 'password';
+            $('showBtn').textContent = hidden ? 'Hide' : 'Show';
+        };
+
+        $('testBtn').onclick = async () => {
+            $('testBtn').disabled = true;
+            showResult('Connecting...', 'muted');
+            try {
+                const r = await tauriApi.core.invoke('test_database_connection', { form: form() });
+                const lines = [];
+                if (r.server_version) lines.push('Connected in ' + r.latency_ms + ' ms', r.server_version);
+                if (r.migrations_applied != null && r.migrations_bundled != null) {
+                    lines.push(r.migrations_applied + ' of ' + r.migrations_bundled + ' migrations applied.');
+                }
+                if (r.problem) lines.push(r.problem.message + (r.problem.code ? ' [' + r.problem.code + ']' : ''));
+                showResult(lines.join('\n'), r.ok ? 'pass' : 'fail');
+            } catch (e) {
+                showResult(String(e), 'fail');
+            }
+            $('testBtn').disabled = false;
+        };
+
+        let migrationJob = null;
+        let moving = false;
+        tauriApi.event.listen('job-progress', (e) => {
+            const job = e.payload;
+            if (job.kind !== 'migration') return;
+            migrationJob = job.state === 'running' ? job.id : null;
+            $('cancelBtn').style.display = migrationJob != null ? '' : 'none';
+            if (moving && migrationJob != null) {
+                const percent = job.progress != null ? ' (' + Math.round(job.progress * 100) + '%)' : '';
+                showResult((job.message || 'Moving data') + percent + '...', 'muted');
+            }
+        });
+
+        $('migrateBtn').onclick = async () => {
+            if (!confirm('Copy all data from SQLite into this PostgreSQL database and switch to it? The server is stopped meanwhile. The SQLite file is kept.')) return;
+            moving = true;
+            ['applyBtn', 'testBtn', 'migrateBtn'].forEach(id => $(id).disabled = true);
+            showResult('Moving data...', 'muted');
+            try {
+                const r = await tauriApi.core.invoke('migrate_to_postgres', { form: form() });
+                const lines = ['Moved ' + r.rows + ' rows in ' + r.tables.length + ' tables. Moneywright now uses PostgreSQL.'];
+                if (r.skipped.length) lines.push('Not copied (unknown to PostgreSQL): ' + r.skipped.join(', '));
+                lines.push('The SQLite file was kept at ' + r.sqlite_path);
+                showResult(lines.join('\n'), 'pass');
+                load();
+            } catch (e) {
+                showResult(String(e), 'fail');
+            }
+            moving = false;
+            ['applyBtn', 'testBtn', 'migrateBtn'].forEach(id => $(id).disabled = false);
+        };
+        $('cancelBtn').onclick = () => {
+            if (migrationJob != null) tauriApi.core.invoke('cancel_job', { id: migrationJob });
+        };
+
+        $('applyBtn').onclick = async () => {
+            $('applyBtn').disabled = true;
+            $('testBtn').disabled = true;
+            showResult(backend() === 'embedded' ? 'Setting up PostgreSQL and restarting the server...' : 'Applying and restarting the server...', 'muted');
+            try {
+                const r = await tauriApi.core.invoke('apply_database_config', { form: form() });
+                showResult(describeMigrations(r), 'pass');
+                load();
+            } catch (e) {
+                showResult(String(e), 'fail');
+            }
+            $('applyBtn').disabled = false;
+            $('testBtn').disabled = false;
+        };
+
+        let currentBackend = null;
+        async function load() {
+            const c = await tauriApi.core.invoke('get_database_config');
+            currentBackend = c.backend;
+            document.querySelector('input[name=backend][value=' + c.backend + ']').checked = true;
+            $('sqlitePath').textContent = c.sqlite_path;
+            $('host').value = c.host;
+            $('port').value = c.port;
+            $('database').value = c.database;
+            $('user').value = c.user;
+            $('sslMode').value = c.ssl_mode;
+            $('password').value = '';
+            $('password').placeholder = c.password_saved ? 'Saved in keychain (leave blank to keep)' : '';
+            updateBackend();
+        }
+        load();
+    "#;
+
+    open_injected_window(app, "database", "Database", (520.0, 500.0), false, script);
+}
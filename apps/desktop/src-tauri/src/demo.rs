@@ -0,0 +1,484 @@
+// Demo mode: a throwaway server filled with sample data
+//
+// "Try with Sample Data" (Profiles menu, onboarding, or the --demo flag) starts an
+// extra sidecar against a fresh folder in the temp dir, seeds it with a few months
+// of made-up accounts and transactions, and shows it in a watermarked window. The
+// real books are never touched, nor is the main window's sign-in: the demo window
+// has its own private cookie store. Closing the window or quitting deletes the folder;
+// folders left behind by a crash are removed on the next launch.
+
+use std::fs;
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use chrono::{Datelike, Local, Months, NaiveDate};
+use rusqlite::{params, Connection};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
+use crate::backup::sqlite_db_path;
+use crate::logs::{log_line, SharedLogStore};
+use crate::server::{init_data_dir, start_server, stop_server, ServerManager, SharedServerManager};
+
+const WINDOW_LABEL: &str = "demo";
+/// Temp folders are named `<prefix><pid>` so stale ones can be matched to dead processes
+const DIR_PREFIX: &str = "moneywright-demo-";
+/// Months of history to generate, ending today
+const HISTORY_MONTHS: u32 = 6;
+const CURRENCY: &str = "USD";
+/// The demo user (local mode always signs in as this id)
+const USER_ID: &str = "default";
+const PROFILE_ID: &str = "demo-profile";
+
+/// Injected into every page of the demo window
+const WATERMARK_SCRIPT: &str = r#"
+(function () {
+    function addWatermark() {
+        if (document.getElementById('__moneywright_demo')) return;
+        const badge = document.createElement('div');
+        badge.id = '__moneywright_demo';
+        badge.setAttribute('role', 'note');
+        badge.textContent = 'DEMO · Sample data · Deleted when this window closes';
+        badge.style.cssText = 'position:fixed;bottom:12px;left:50%;transform:translateX(-50%);z-index:2147483647;' +
+            'padding:4px 12px;border-radius:999px;background:rgba(245,158,11,0.92);color:#1c1917;' +
+            'font:600 11px -apple-system,BlinkMacSystemFont,sans-serif;letter-spacing:0.04em;pointer-events:none;';
+        document.body.appendChild(badge);
+    }
+    if (document.body) addWatermark();
+    else document.addEventListener('DOMContentLoaded', addWatermark);
+})();
+"#;
+
+struct DemoSession {
+    manager: SharedServerManager,
+    dir: PathBuf,
+}
+
+static SESSION: Mutex<Option<DemoSession>> = Mutex::new(None);
+
+fn session() -> std::sync::MutexGuard<'static, Option<DemoSession>> {
+    SESSION.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn demo_dir() -> PathBuf {
+    std::env::temp_dir().join(format!("{}{}", DIR_PREFIX, std::process::id()))
+}
+
+/// Delete demo folders whose app process is gone (crash or forced quit)
+pub fn remove_stale_demo_dirs() {
+    remove_stale_dirs(DIR_PREFIX);
+}
+
+/// Delete temp folders named `<prefix><pid>` whose app process is gone (also used by the sandbox)
+pub fn remove_stale_dirs(prefix: &str) {
+    let Ok(entries) = fs::read_dir(std::env::temp_dir()) else {
+        return;
+    };
+    let mut system = sysinfo::System::new();
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let Some(pid) = name.strip_prefix(prefix).and_then(|p| p.parse::<u32>().ok()) else {
+            continue;
+        };
+        let pid = sysinfo::Pid::from_u32(pid);
+        system.refresh_processes(sysinfo::ProcessesToUpdate::Some(&[pid]), true);
+        if system.process(pid).is_none() {
+            if let Err(e) = fs::remove_dir_all(entry.path()) {
+                eprintln!("Warning: Failed to remove old scratch data {}: {}", entry.path().display(), e);
+            }
+        }
+    }
+}
+
+/// Remove a scratch folder, retrying while the stopped server still holds files open
+pub fn remove_scratch_dir(dir: &Path) {
+    for attempt in 0..5 {
+        match fs::remove_dir_all(dir) {
+            Ok(()) => return,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return,
+            Err(e) if attempt == 4 => eprintln!("Warning: Failed to remove scratch data {}: {}", dir.display(), e),
+            Err(_) => std::thread::sleep(Duration::from_millis(300)),
+        }
+    }
+}
+
+pub fn free_port() -> Result<u16, String> {
+    TcpListener::bind(("127.0.0.1", 0))
+        .and_then(|listener| listener.local_addr())
+        .map(|addr| addr.port())
+        .map_err(|e| format!("No free port for a scratch server: {}", e))
+}
+
+// ---------------------------------------------------------------------------
+// Sample data
+// ---------------------------------------------------------------------------
+
+/// Small deterministic generator, so every demo looks the same
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self) -> f64 {
+        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (self.0 >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Amount in [min, max], rounded to cents
+    fn amount(&mut self, min: f64, max: f64) -> f64 {
+        cents(min + (max - min) * self.next())
+    }
+}
+
+fn cents(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+struct SampleTransaction {
+    account: &'static str,
+    date: NaiveDate,
+    credit: bool,
+    amount: f64,
+    description: &'static str,
+    category: &'static str,
+    subscription: bool,
+}
+
+struct SampleAccount {
+    id: &'static str,
+    kind: &'static str,
+    institution: &'static str,
+    name: &'static str,
+    document_type: &'static str,
+    opening_balance: f64,
+}
+
+const ACCOUNTS: [SampleAccount; 3] = [
+    SampleAccount {
+        id: "demo-checking",
+        kind: "checking_account",
+        institution: "Chase",
+        name: "Everyday Checking",
+        document_type: "bank_statement",
+        opening_balance: 4200.0,
+    },
+    SampleAccount {
+        id: "demo-savings",
+        kind: "savings_account",
+        institution: "Ally Bank",
+        name: "Emergency Fund",
+        document_type: "bank_statement",
+        opening_balance: 12000.0,
+    },
+    SampleAccount {
+        id: "demo-card",
+        kind: "credit_card",
+        institution: "American Express",
+        name: "Blue Cash Everyday",
+        document_type: "credit_card_statement",
+        opening_balance: 0.0,
+    },
+];
+
+fn sample_transactions(today: NaiveDate) -> Vec<SampleTransaction> {
+    let mut rng = Lcg(0x6d6f_6e65_7977_7269);
+    let mut list = Vec::new();
+    let first_month = today
+        .with_day(1)
+        .and_then(|d| d.checked_sub_months(Months::new(HISTORY_MONTHS - 1)))
+        .unwrap_or(today);
+
+    for m in 0..HISTORY_MONTHS {
+        let Some(month) = first_month.checked_add_months(Months::new(m)) else {
+            continue;
+        };
+        let day = |d: u32| month.with_day(d).unwrap_or(month);
+        let mut push = |account, d: u32, credit, amount, description, category, subscription| {
+            list.push(SampleTransaction {
+                account,
+                date: day(d),
+                credit,
+                amount,
+                description,
+                category,
+                subscription,
+            });
+        };
+
+        // Checking: income, rent, bills and a monthly transfer to savings
+        push("demo-checking", 1, true, 3150.0, "ACME CORP PAYROLL", "paycheck", false);
+        push("demo-checking", 15, true, 3150.0, "ACME CORP PAYROLL", "paycheck", false);
+        push("demo-checking", 1, false, 1850.0, "GREENVIEW APARTMENTS RENT", "rent", false);
+        push("demo-checking", 8, false, rng.amount(85.0, 140.0), "CITY POWER & LIGHT", "utilities", false);
+        push("demo-checking", 16, false, 500.0, "TRANSFER TO SAVINGS", "transfer", false);
+        push("demo-savings", 16, true, 500.0, "TRANSFER FROM CHECKING", "transfer", false);
+        push("demo-savings", 28, true, rng.amount(38.0, 46.0), "INTEREST PAYMENT", "interest", false);
+
+        // Card: everyday spending and subscriptions
+        push("demo-card", 12, false, 79.99, "XFINITY INTERNET", "phone_internet", false);
+        push("demo-card", 3, false, 15.49, "NETFLIX.COM", "entertainment", true);
+        push("demo-card", 9, false, 11.99, "SPOTIFY USA", "entertainment", true);
+        for (d, store) in [(4, "WHOLE FOODS MARKET"), (11, "TRADER JOE'S"), (18, "WHOLE FOODS MARKET"), (25, "TRADER JOE'S")] {
+            let amount = rng.amount(55.0, 165.0);
+            push("demo-card", d, false, amount, store, "groceries", false);
+        }
+        for (d, place) in [(5, "BLUE BOTTLE COFFEE"), (10, "CHIPOTLE"), (17, "OLIVE GARDEN"), (22, "BLUE BOTTLE COFFEE"), (27, "SWEETGREEN")] {
+            let amount = rng.amount(6.0, 72.0);
+            push("demo-card", d, false, amount, place, "food_dining", false);
+        }
+        for d in [7, 21] {
+            let amount = rng.amount(38.0, 62.0);
+            push("demo-card", d, false, amount, "SHELL OIL", "gas", false);
+        }
+        let shopping = rng.amount(25.0, 240.0);
+        push("demo-card", 13, false, shopping, "AMAZON.COM", "shopping", false);
+    }
+
+    // Pay each month's card spend from checking on the 20th of the next month
+    let mut payments = Vec::new();
+    for m in 1..HISTORY_MONTHS {
+        let Some(month) = first_month.checked_add_months(Months::new(m)) else {
+            continue;
+        };
+        let Some(previous) = month.checked_sub_months(Months::new(1)) else {
+            continue;
+        };
+        let spent: f64 = list
+            .iter()
+            .filter(|t| t.account == "demo-card" && t.date.year() == previous.year() && t.date.month() == previous.month())
+            .map(|t| t.amount)
+            .sum();
+        let date = month.with_day(20).unwrap_or(month);
+        for (account, credit, description) in [
+            ("demo-checking", false, "AMEX AUTOPAY"),
+            ("demo-card", true, "AUTOPAY PAYMENT - THANK YOU"),
+        ] {
+            payments.push(SampleTransaction {
+                account,
+                date,
+                credit,
+                amount: cents(spent),
+                description,
+                category: "credit_card_payment",
+                subscription: false,
+            });
+        }
+    }
+    list.extend(payments);
+
+    list.retain(|t| t.date <= today);
+    list.sort_by_key(|t| t.date);
+    list
+}
+
+fn transaction_hash(index: usize, t: &SampleTransaction) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(format!("{}|{}|{}|{}|{}", t.account, t.date, t.description, t.amount, index));
+    hex::encode(hasher.finalize())
+}
+
+/// Insert the sample user, profile, accounts and transactions; returns the transaction count
+fn seed(db_path: &Path) -> Result<usize, String> {
+    let mut conn = Connection::open(db_path).map_err(|e| format!("Failed to open demo database: {}", e))?;
+    conn.busy_timeout(Duration::from_secs(10)).map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let sql_err = |e: rusqlite::Error| format!("Failed to seed demo data: {}", e);
+
+    tx.execute(
+        "INSERT INTO users (id, name, country) VALUES (?1, 'Demo User', 'US')
+         ON CONFLICT(id) DO UPDATE SET country = 'US'",
+        params![USER_ID],
+    )
+    .map_err(sql_err)?;
+    tx.execute(
+        "INSERT INTO profiles (id, user_id, name, relationship, summary) VALUES (?1, ?2, 'Me', 'self', ?3)",
+        params![PROFILE_ID, USER_ID, "Sample profile: salaried, renting, one checking, one savings and one credit card account."],
+    )
+    .map_err(sql_err)?;
+
+    let today = Local::now().date_naive();
+    let transactions = sample_transactions(today);
+    let period_start = transactions.first().map(|t| t.date).unwrap_or(today);
+
+    for account in &ACCOUNTS {
+        tx.execute(
+            "INSERT INTO accounts (id, profile_id, user_id, type, institution, account_name, currency)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![account.id, PROFILE_ID, USER_ID, account.kind, account.institution, account.name, CURRENCY],
+        )
+        .map_err(sql_err)?;
+
+        // Cards track the amount owed, bank accounts the amount held
+        let owed = account.document_type == "credit_card_statement";
+        let mut balance = account.opening_balance;
+        let own: Vec<(usize, &SampleTransaction)> = transactions
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| t.account == account.id)
+            .collect();
+        let statement_id = format!("{}-statement", account.id);
+        let closing: f64 = own.iter().fold(balance, |b, (_, t)| cents(if t.credit != owed { b + t.amount } else { b - t.amount }));
+
+        tx.execute(
+            "INSERT INTO statements (id, account_id, profile_id, user_id, document_type, original_filename, file_type,
+                                     period_start, period_end, opening_balance, closing_balance, status, transaction_count)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, 'csv', ?7, ?8, ?9, ?10, 'completed', ?11)",
+            params![
+                statement_id,
+                account.id,
+                PROFILE_ID,
+                USER_ID,
+                account.document_type,
+                format!("{}.csv", account.id),
+                period_start.to_string(),
+                today.to_string(),
+                account.opening_balance,
+                closing,
+                own.len() as i64,
+            ],
+        )
+        .map_err(sql_err)?;
+
+        for (index, t) in own {
+            balance = cents(if t.credit != owed { balance + t.amount } else { balance - t.amount });
+            tx.execute(
+                "INSERT INTO transactions (id, account_id, statement_id, profile_id, user_id, date, type, amount, currency,
+                                           balance, original_description, category, category_confidence, is_subscription, hash)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, 1.0, ?13, ?14)",
+                params![
+                    format!("demo-tx-{}", index),
+                    account.id,
+                    statement_id,
+                    PROFILE_ID,
+                    USER_ID,
+                    t.date.to_string(),
+                    if t.credit { "credit" } else { "debit" },
+                    t.amount,
+                    CURRENCY,
+                    balance,
+                    t.description,
+                    t.category,
+                    t.subscription,
+                    transaction_hash(index, t),
+                ],
+            )
+            .map_err(sql_err)?;
+        }
+    }
+
+    tx.commit().map_err(sql_err)?;
+    Ok(transactions.len())
+}
+
+// ---------------------------------------------------------------------------
+// Session
+// ---------------------------------------------------------------------------
+
+fn show_window(app: &AppHandle, url: &str) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window(WINDOW_LABEL) {
+        let _ = window.show();
+        let _ = window.set_focus();
+        return Ok(());
+    }
+    let url = url.parse().map_err(|e| format!("Invalid demo URL: {}", e))?;
+    let builder = WebviewWindowBuilder::new(app, WINDOW_LABEL, WebviewUrl::External(url));
+    // Signing in to the demo must not sign the main window out, see webview.rs
+    crate::webview::isolate(crate::webview::configure(builder), demo_dir().join("webview"), WINDOW_LABEL)
+        .incognito(true)
+        .title("Moneywright — Demo (sample data)")
+        .inner_size(1280.0, 800.0)
+        .min_inner_size(800.0, 600.0)
+        .initialization_script(WATERMARK_SCRIPT)
+        .build()
+        .map_err(|e| format!("Failed to open demo window: {}", e))?;
+    Ok(())
+}
+
+/// Start the demo server (or show it if already running), returning its URL
+pub async fn start(app: &AppHandle) -> Result<String, String> {
+    let existing = session().as_ref().map(|s| s.manager.clone());
+    if let Some(manager) = existing {
+        let url = manager.lock().await.url();
+        show_window(app, &url)?;
+        return Ok(url);
+    }
+
+    let log_store = app.state::<SharedLogStore>().inner().clone();
+    let dir = demo_dir();
+    remove_scratch_dir(&dir);
+    init_data_dir(&dir)?;
+
+    // The demo skips the AI setup step; AI features only work with a local Ollama
+    let manager: SharedServerManager = Arc::new(tokio::sync::Mutex::new(
+        ServerManager::for_profile(dir.clone(), free_port()?, "Demo".to_string())
+            .with_env("OLLAMA_BASE_URL", "http://localhost:11434"),
+    ));
+    *session() = Some(DemoSession {
+        manager: manager.clone(),
+        dir: dir.clone(),
+    });
+
+    log_line(app, &log_store, format!("Starting demo server in {}", dir.display()), "info").await;
+    let started = start_server(app.clone(), manager.clone(), log_store.clone()).await;
+    let seeded = match started {
+        Ok(()) => {
+            let db = sqlite_db_path(&dir);
+            tauri::async_runtime::spawn_blocking(move || seed(&db))
+                .await
+                .map_err(|e| format!("Seeding task failed: {}", e))
+                .and_then(|r| r)
+        }
+        Err(e) => Err(format!("Failed to start demo server: {}", e)),
+    };
+    let count = match seeded {
+        Ok(count) => count,
+        Err(e) => {
+            end(app).await;
+            return Err(e);
+        }
+    };
+    log_line(app, &log_store, format!("Demo ready with {} sample transactions", count), "info").await;
+
+    let url = manager.lock().await.url();
+    show_window(app, &url)?;
+    Ok(url)
+}
+
+/// Stop the demo server and delete its data
+pub async fn end(app: &AppHandle) {
+    let Some(demo) = session().take() else {
+        return;
+    };
+    if let Some(window) = app.get_webview_window(WINDOW_LABEL) {
+        let _ = window.destroy();
+    }
+    let _ = stop_server(demo.manager).await;
+    let _ = tauri::async_runtime::spawn_blocking(move || remove_scratch_dir(&demo.dir)).await;
+
+    let log_store = app.state::<SharedLogStore>().inner().clone();
+    log_line(app, &log_store, "Demo ended, sample data deleted", "info").await;
+}
+
+/// Synchronous cleanup for app exit
+pub fn end_on_exit() {
+    let Some(demo) = session().take() else {
+        return;
+    };
+    let _ = tauri::async_runtime::block_on(stop_server(demo.manager));
+    remove_scratch_dir(&demo.dir);
+}
+
+pub fn is_demo_window(label: &str) -> bool {
+    label == WINDOW_LABEL
+}
+
+/// Start a demo session with sample data and open its window
+#[tauri::command]
+pub async fn start_demo(app: AppHandle) -> Result<String, String> {
+    start(&app).await
+}
+
+/// End the demo session and delete its sample data
+#[tauri::command]
+pub async fn end_demo(app: AppHandle) -> Result<(), String> {
+    end(&app).await;
+    Ok(())
+}
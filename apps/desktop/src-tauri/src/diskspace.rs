@@ -0,0 +1,148 @@
+// Free space checks before work that writes a lot to disk
+//
+// A full disk used to show up only as cryptic migration or SQLite errors in the logs.
+// Starting the server, taking a backup and installing an update now check the volume
+// they write to first. Below REFUSE_BYTES (plus what the operation itself needs) they
+// refuse with an error saying how much is free; below WARN_BYTES they go ahead but warn.
+// Either way the web app gets a `disk-space-low` event and a maintenance notification
+// is sent, at most once per operation every NOTIFY_INTERVAL.
+//
+// The doctor's disk space check uses the same thresholds.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use serde::Serialize;
+use tauri::AppHandle;
+use crate::events::{self, Event};
+use crate::notifications::{self, Kind};
+
+/// Below this much free space (beyond what the operation needs) it's refused
+pub const REFUSE_BYTES: u64 = 500 * 1024 * 1024;
+/// Below this much free space it's allowed with a warning
+pub const WARN_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+/// Room an update needs to be unpacked and installed
+pub const UPDATE_BYTES: u64 = 300 * 1024 * 1024;
+/// Low space notifications for the same operation are repeated at most this often
+const NOTIFY_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+static LAST_NOTIFIED: Mutex<Option<HashMap<Operation, Instant>>> = Mutex::new(None);
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Operation {
+    ServerStart,
+    Backup,
+    Update,
+}
+
+impl Operation {
+    fn describe(self) -> &'static str {
+        match self {
+            Operation::ServerStart => "start the server",
+            Operation::Backup => "back up the database",
+            Operation::Update => "install the update",
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct LowDiskSpace {
+    pub operation: Operation,
+    /// The volume checked, as the path being written to
+    pub path: String,
+    pub free_bytes: u64,
+    /// What the operation itself is expected to write
+    pub needed_bytes: u64,
+    /// The operation didn't go ahead
+    pub refused: bool,
+}
+
+/// Free space on the volume holding `path`
+pub fn free_space(path: &Path) -> Option<u64> {
+    // Targets like a new backups folder may not exist yet; their volume is the nearest ancestor's
+    let existing = path.ancestors().find(|p| p.exists())?;
+    let path = fs::canonicalize(existing).ok()?;
+    let disks = sysinfo::Disks::new_with_refreshed_list();
+    disks
+        .list()
+        .iter()
+        .filter(|d| path.starts_with(d.mount_point()))
+        .max_by_key(|d| d.mount_point().as_os_str().len())
+        .map(|d| d.available_space())
+}
+
+/// "1.2 GB" style size for messages
+pub fn format_bytes(bytes: u64) -> String {
+    if bytes >= 1024 * 1024 * 1024 {
+        format!("{:.1} GB", bytes as f64 / 1_073_741_824.0)
+    } else {
+        format!("{} MB", bytes / (1024 * 1024))
+    }
+}
+
+/// Check there's room for an operation writing about `needed` bytes under `path`
+/// Err when there's too little; a warning is sent when it's getting low. Volumes whose
+/// free space can't be read pass.
+pub async fn preflight(app: &AppHandle, operation: Operation, path: &Path, needed: u64) -> Result<(), String> {
+    let target = path.to_path_buf();
+    let free = tauri::async_runtime::spawn_blocking(move || free_space(&target)).await.ok().flatten();
+    let Some(free) = free else {
+        return Ok(());
+    };
+    let refused = free < REFUSE_BYTES.saturating_add(needed);
+    if !refused && free >= WARN_BYTES.saturating_add(needed) {
+        return Ok(());
+    }
+
+    let low = LowDiskSpace {
+        operation,
+        path: path.to_string_lossy().to_string(),
+        free_bytes: free,
+        needed_bytes: needed,
+        refused,
+    };
+    let _ = events::emit(app, Event::DiskSpaceLow(&low));
+    let message = if refused {
+        format!(
+            "Not enough disk space to {}: only {} free on the volume of {}. Free up at least {} and try again.",
+            operation.describe(),
+            format_bytes(free),
+            path.display(),
+            format_bytes(REFUSE_BYTES.saturating_add(needed) - free),
+        )
+    } else {
+        format!("Disk space is running low: {} free on the volume of {}", format_bytes(free), path.display())
+    };
+    eprintln!("Warning: {}", message);
+    notify_once(app, operation, message.clone());
+    if refused {
+        Err(message)
+    } else {
+        Ok(())
+    }
+}
+
+/// Send the notification unless this operation had one recently
+/// It's sent from a task, as callers may hold the server manager that delivery needs
+fn notify_once(app: &AppHandle, operation: Operation, body: String) {
+    {
+        let mut last = LAST_NOTIFIED.lock().unwrap_or_else(|e| e.into_inner());
+        let last = last.get_or_insert_with(HashMap::new);
+        if last.get(&operation).is_some_and(|at| at.elapsed() < NOTIFY_INTERVAL) {
+            return;
+        }
+        last.insert(operation, Instant::now());
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        notifications::notify(&app, Kind::Maintenance, "Moneywright is low on disk space", body).await;
+    });
+}
+
+/// Where the app itself is installed, which an update rewrites
+pub fn install_dir() -> Option<PathBuf> {
+    Some(std::env::current_exe().ok()?.parent()?.to_path_buf())
+}
@@ -0,0 +1,197 @@
+// Per-display zoom for the app windows
+//
+// The zoom of the main, extra, profile and demo windows is remembered per display in
+// `display.per_display` (keyed by display name and resolution), falling back to
+// `display.zoom`. It is re-applied when a window moves to another display or the
+// display's scale factor changes, which also makes the webview re-render at the new
+// density instead of staying blurry or tiny on mixed-DPI setups.
+//
+// Bundled pages also follow the OS text size (see a11y.rs): native windows are zoomed
+// by it and opened that much larger, and the splash, lock and error pages in the app
+// windows get it on top of the display zoom.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, LogicalSize, Manager, Monitor, Runtime, WebviewWindow};
+use crate::a11y;
+use crate::events::{self, Event};
+use tokio::sync::watch;
+use crate::settings::{DisplaySettings, Settings, SharedSettings};
+
+pub const MIN_ZOOM: f64 = 0.5;
+pub const MAX_ZOOM: f64 = 3.0;
+/// Steps for Zoom In/Out and the View > Zoom presets
+pub const PRESETS: &[f64] = &[0.5, 0.67, 0.75, 0.8, 0.9, 1.0, 1.1, 1.25, 1.5, 1.75, 2.0, 2.5, 3.0];
+
+/// Settings in effect, for window events outside of async code
+static CURRENT: Mutex<Option<DisplaySettings>> = Mutex::new(None);
+/// Display and zoom last applied per window label
+static APPLIED: Mutex<Option<HashMap<String, (String, f64)>>> = Mutex::new(None);
+
+pub enum ZoomChange {
+    In,
+    Out,
+    Reset,
+    To(f64),
+}
+
+/// Windows showing the web app; native tool windows keep their fixed layout
+fn is_app_window(label: &str) -> bool {
+    label == "main"
+        || label.starts_with(crate::layouts::VIEW_PREFIX)
+        || label.starts_with(crate::profiles::WINDOW_PREFIX)
+        || crate::demo::is_demo_window(label)
+}
+
+/// Name and resolution of a display, e.g. "DELL U2720Q (3840x2160)"
+pub fn monitor_key(monitor: &Monitor) -> String {
+    let size = monitor.size();
+    let name = monitor.name().map(String::as_str).unwrap_or("Display");
+    format!("{} ({}x{})", name, size.width, size.height)
+}
+
+/// Display a window is on
+fn display_key(window: &WebviewWindow) -> Option<String> {
+    Some(monitor_key(&window.current_monitor().ok()??))
+}
+
+fn zoom_for(settings: &DisplaySettings, key: &str) -> f64 {
+    settings.per_display.get(key).copied().unwrap_or(settings.zoom)
+}
+
+/// Whether a window shows one of the bundled pages (native windows, splash, lock, error)
+fn shows_bundled_page(window: &WebviewWindow) -> bool {
+    window.url().is_ok_and(|url| crate::protocol::is_page(&url))
+}
+
+/// Apply the zoom for the window's current display; `force` re-applies an unchanged one
+fn apply(window: &WebviewWindow, settings: &DisplaySettings, force: bool) {
+    let app_window = is_app_window(window.label());
+    let bundled = shows_bundled_page(window);
+    if !app_window && !bundled {
+        return;
+    }
+    let Some(key) = display_key(window) else {
+        return;
+    };
+    let zoom = match (app_window, bundled) {
+        (true, false) => zoom_for(settings, &key),
+        (true, true) => zoom_for(settings, &key) * a11y::text_scale(),
+        (false, _) => a11y::text_scale(),
+    };
+    {
+        let mut applied = APPLIED.lock().unwrap_or_else(|e| e.into_inner());
+        let applied = applied.get_or_insert_with(HashMap::new);
+        let entry = (key, zoom);
+        if !force && applied.get(window.label()) == Some(&entry) {
+            return;
+        }
+        applied.insert(window.label().to_string(), entry);
+    }
+    if let Err(e) = window.set_zoom(zoom) {
+        eprintln!("Warning: Failed to set zoom: {}", e);
+    }
+}
+
+/// Window moved or its display's scale changed
+pub fn on_window_changed(app: &AppHandle, label: &str, scale_changed: bool) {
+    let Some(settings) = CURRENT.lock().unwrap_or_else(|e| e.into_inner()).clone() else {
+        return;
+    };
+    if let Some(window) = app.get_webview_window(label) {
+        apply(&window, &settings, scale_changed);
+    }
+}
+
+/// Window finished loading a page
+pub fn on_page_load(window: &WebviewWindow) {
+    if let Some(settings) = CURRENT.lock().unwrap_or_else(|e| e.into_inner()).clone() {
+        apply(window, &settings, true);
+    }
+}
+
+/// Forget a closed window so a new one with the same label gets its zoom
+pub fn forget_window(label: &str) {
+    if let Some(applied) = APPLIED.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+        applied.remove(label);
+    }
+}
+
+/// Logical size of the primary display, for keeping enlarged windows on screen
+fn screen_size<R: Runtime>(app: &AppHandle<R>) -> Option<(f64, f64)> {
+    let monitor = app.primary_monitor().ok()??;
+    let size = monitor.size().to_logical::<f64>(monitor.scale_factor());
+    Some((size.width, size.height))
+}
+
+/// Scale a size up, as far as 90% of the screen allows
+fn fit<R: Runtime>(app: &AppHandle<R>, size: (f64, f64), scale: f64) -> (f64, f64) {
+    let (width, height) = (size.0 * scale, size.1 * scale);
+    match screen_size(app) {
+        Some((screen_w, screen_h)) => (width.min(screen_w * 0.9).max(size.0), height.min(screen_h * 0.9).max(size.1)),
+        None => (width, height),
+    }
+}
+
+/// Size for a native window at the OS text size; builders pass their normal size
+pub fn text_scaled_size<R: Runtime>(app: &AppHandle<R>, size: (f64, f64)) -> (f64, f64) {
+    fit(app, size, a11y::text_scale())
+}
+
+/// The OS text size changed: re-zoom bundled pages and resize the native windows
+pub fn on_text_scale_changed(app: &AppHandle, previous: f64, scale: f64) {
+    let settings = CURRENT.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    for window in app.webview_windows().values() {
+        if let Some(settings) = &settings {
+            apply(window, settings, true);
+        }
+        if is_app_window(window.label()) || !shows_bundled_page(window) {
+            continue;
+        }
+        let (Ok(size), Ok(factor)) = (window.inner_size(), window.scale_factor()) else {
+            continue;
+        };
+        let size = size.to_logical::<f64>(factor);
+        let (width, height) = fit(app, (size.width / previous, size.height / previous), scale);
+        let _ = window.set_size(LogicalSize::new(width, height));
+    }
+}
+
+/// Re-apply zoom to all app windows whenever `[display]` changes
+pub fn spawn_display_sync(app: AppHandle, mut rx: watch::Receiver<Settings>) {
+    *CURRENT.lock().unwrap_or_else(|e| e.into_inner()) = Some(rx.borrow().display.clone());
+    tauri::async_runtime::spawn(async move {
+        while rx.changed().await.is_ok() {
+            let settings = rx.borrow_and_update().display.clone();
+            *CURRENT.lock().unwrap_or_else(|e| e.into_inner()) = Some(settings.clone());
+            for window in app.webview_windows().values() {
+                apply(window, &settings, false);
+            }
+        }
+    });
+}
+
+/// Change the zoom of the focused app window (or the main one) and remember it for its display
+pub async fn change_zoom(app: &AppHandle, change: ZoomChange) -> Result<(), String> {
+    let window = app
+        .webview_windows()
+        .into_values()
+        .find(|w| is_app_window(w.label()) && w.is_focused().unwrap_or(false))
+        .or_else(|| app.get_webview_window("main"))
+        .ok_or_else(|| "No window to zoom".to_string())?;
+    let key = display_key(&window).ok_or_else(|| "Could not determine the window's display".to_string())?;
+
+    let settings = app.state::<SharedSettings>().inner().clone();
+    let store = settings.lock().await;
+    let current = zoom_for(&store.get().display, &key);
+    let zoom = match change {
+        ZoomChange::In => PRESETS.iter().copied().find(|z| *z > current + 0.001).unwrap_or(MAX_ZOOM),
+        ZoomChange::Out => PRESETS.iter().rev().copied().find(|z| *z < current - 0.001).unwrap_or(MIN_ZOOM),
+        ZoomChange::Reset => 1.0,
+        ZoomChange::To(zoom) => zoom,
+    };
+    let updated = store.update(&serde_json::json!({ "display": { "per_display": { key: zoom } } }))?;
+    drop(store);
+    let _ = events::emit(app, Event::SettingsChanged(&updated));
+    Ok(())
+}
@@ -0,0 +1,623 @@
+// "Doctor" self-diagnostics: checks the local installation and reports pass/warn/fail
+
+use std::fs;
+use std::io::Read;
+use std::net::{SocketAddr, TcpStream};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use rusqlite::{Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Manager};
+use crate::arch;
+use crate::avcheck;
+use crate::backup::sqlite_db_path;
+use crate::cleanups::{self, Action, Cleanup};
+use crate::diskspace;
+use crate::heartbeat;
+use crate::integrity;
+use crate::keychain;
+use crate::ports::{self, Holder, Inspection};
+use crate::transport;
+use crate::server::{fetch_health, get_server_url, read_database_url, server_port, sidecar_path, HealthResponse, ServerStatus, SharedServerManager};
+use crate::windows::open_injected_window;
+
+const UPDATE_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+/// How far back the cleanups check and the report look
+const CLEANUP_WINDOW_SECS: u64 = 30 * 24 * 60 * 60;
+/// This many cleanups within the window count as a recurring conflict
+const CLEANUP_RECURRING: usize = 3;
+
+#[derive(Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+#[derive(Clone, Serialize)]
+pub struct DoctorCheck {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+    /// What the user can do about it, in order
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub steps: Vec<String>,
+}
+
+impl DoctorCheck {
+    pub fn new(id: &'static str, name: &'static str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self { id, name, status, detail: detail.into(), steps: Vec::new() }
+    }
+
+    pub fn with_steps(mut self, steps: Vec<String>) -> Self {
+        self.steps = steps;
+        self
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct DoctorReport {
+    pub generated_at: u64,
+    pub version: String,
+    pub os: String,
+    pub arch: String,
+    pub data_dir: String,
+    pub checks: Vec<DoctorCheck>,
+    pub passed: usize,
+    pub warnings: usize,
+    pub failures: usize,
+    /// Leftover processes stopped and locks taken over in the last 30 days, newest first
+    pub cleanups: Vec<Cleanup>,
+}
+
+#[derive(Deserialize)]
+struct MigrationJournal {
+    entries: Vec<serde_json::Value>,
+}
+
+pub fn sha256_file(path: &Path) -> Result<String, String> {
+    let mut file = fs::File::open(path).map_err(|e| e.to_string())?;
+    let mut hasher = Sha256::new();
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf).map_err(|e| e.to_string())?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+fn check_sidecar() -> DoctorCheck {
+    const ID: &str = "sidecar";
+    const NAME: &str = "Server binary";
+
+    let Some(path) = sidecar_path() else {
+        return DoctorCheck::new(ID, NAME, CheckStatus::Fail, "Could not determine the application directory");
+    };
+
+    match fs::metadata(&path) {
+        Ok(meta) if meta.len() == 0 => {
+            DoctorCheck::new(ID, NAME, CheckStatus::Fail, format!("{} is empty (truncated install?)", path.display()))
+        }
+        Ok(meta) => match sha256_file(&path) {
+            Ok(hash) => match integrity::verify_sidecar(&path) {
+                Ok(()) => DoctorCheck::new(
+                    ID,
+                    NAME,
+                    CheckStatus::Pass,
+                    format!("{} ({} bytes, sha256 {})", path.display(), meta.len(), hash),
+                ),
+                Err(problem) => DoctorCheck::new(ID, NAME, CheckStatus::Fail, problem),
+            },
+            Err(e) => DoctorCheck::new(ID, NAME, CheckStatus::Fail, format!("Cannot read {}: {}", path.display(), e)),
+        },
+        Err(_) if cfg!(debug_assertions) => {
+            DoctorCheck::new(ID, NAME, CheckStatus::Warn, "Not bundled (development build)")
+        }
+        Err(e) => DoctorCheck::new(ID, NAME, CheckStatus::Fail, format!("{} is missing: {}", path.display(), e)),
+    }
+}
+
+fn check_architecture(data_dir: &Path) -> DoctorCheck {
+    const ID: &str = "architecture";
+    const NAME: &str = "Architecture";
+
+    let Some(translation) = arch::translation() else {
+        return DoctorCheck::new(ID, NAME, CheckStatus::Pass, format!("Running natively ({})", std::env::consts::ARCH));
+    };
+    let server = match arch::native_sidecar(data_dir) {
+        Some(path) => format!("the server runs natively from {}", path.display()),
+        None => "the server is translated too".to_string(),
+    };
+    DoctorCheck::new(
+        ID,
+        NAME,
+        CheckStatus::Warn,
+        format!(
+            "The {} build runs through {} on an {} computer; {}. Install the {} version for full speed",
+            translation.build, translation.layer, translation.native, server, translation.native
+        ),
+    )
+}
+
+async fn check_port(status: &ServerStatus) -> DoctorCheck {
+    const ID: &str = "port";
+    const NAME: &str = "Server port";
+
+    // No port to probe when the server listens on a socket, see transport.rs
+    if let Some(socket) = transport::socket_path() {
+        return match (fetch_health(&get_server_url()).await, status) {
+            (Some(h), _) if h.status == "healthy" => DoctorCheck::new(
+                ID,
+                NAME,
+                CheckStatus::Pass,
+                format!("Moneywright is healthy on socket {}", socket.display()),
+            ),
+            (Some(h), _) => DoctorCheck::new(
+                ID,
+                NAME,
+                CheckStatus::Fail,
+                format!("Server on socket {} reports status \"{}\"", socket.display(), h.status),
+            ),
+            (None, ServerStatus::Running) => DoctorCheck::new(
+                ID,
+                NAME,
+                CheckStatus::Fail,
+                format!("Server is marked running but doesn't answer on socket {}", socket.display()),
+            ),
+            (None, _) => DoctorCheck::new(ID, NAME, CheckStatus::Pass, format!("Server will listen on socket {}", socket.display())),
+        };
+    }
+
+    let port = server_port();
+    let addr = SocketAddr::from(([127, 0, 0, 1], port));
+    let listening = tauri::async_runtime::spawn_blocking(move || {
+        TcpStream::connect_timeout(&addr, Duration::from_millis(500)).is_ok()
+    })
+    .await
+    .unwrap_or(false);
+
+    if !listening {
+        return match status {
+            ServerStatus::Running => DoctorCheck::new(
+                ID,
+                NAME,
+                CheckStatus::Fail,
+                format!("Server is marked running but nothing is listening on port {}", port),
+            ),
+            _ => DoctorCheck::new(ID, NAME, CheckStatus::Pass, format!("Port {} is free", port)),
+        };
+    }
+
+    let health = fetch_health(&get_server_url()).await;
+
+    match (health, status) {
+        (Some(HealthResponse { database: Some(db), .. }), _) if !db.connected => DoctorCheck::new(
+            ID,
+            NAME,
+            CheckStatus::Fail,
+            format!("Server is up but cannot reach its {} database", db.db_type),
+        ),
+        (Some(h), _) if h.status == "healthy" => DoctorCheck::new(
+            ID,
+            NAME,
+            CheckStatus::Pass,
+            format!(
+                "Moneywright {} is healthy on port {}",
+                h.version.unwrap_or_else(|| "(unknown version)".to_string()),
+                port
+            ),
+        ),
+        (Some(h), _) => DoctorCheck::new(
+            ID,
+            NAME,
+            CheckStatus::Fail,
+            format!("Server on port {} reports status \"{}\"", port, h.status),
+        ),
+        (None, ServerStatus::Running) => DoctorCheck::new(
+            ID,
+            NAME,
+            CheckStatus::Fail,
+            format!("Port {} is open but the health check failed", port),
+        ),
+        (None, _) => {
+            let holder = tauri::async_runtime::spawn_blocking(move || ports::holder(port)).await.ok();
+            let detail = match holder {
+                Some(holder) if holder.is_foreign() || matches!(holder, Holder::OtherProgram { .. }) => holder.describe(port),
+                _ => format!("Port {} is held by another process", port),
+            };
+            DoctorCheck::new(ID, NAME, CheckStatus::Warn, detail)
+        }
+    }
+}
+
+fn check_process_inspection() -> DoctorCheck {
+    const ID: &str = "process_inspection";
+    const NAME: &str = "Process cleanup";
+
+    let inspection = ports::inspection();
+    let status = match inspection {
+        Inspection::Tool => CheckStatus::Pass,
+        Inspection::ProcessScan => CheckStatus::Warn,
+        Inspection::Restricted => CheckStatus::Fail,
+    };
+    let detail = ports::describe(inspection)
+        .unwrap_or_else(|| "Leftover servers on the port can be found and stopped".to_string());
+    DoctorCheck::new(ID, NAME, status, detail)
+}
+
+fn check_cleanups(cleanups: &[Cleanup]) -> DoctorCheck {
+    const ID: &str = "cleanups";
+    const NAME: &str = "Forced cleanups";
+
+    let Some(last) = cleanups.first() else {
+        return DoctorCheck::new(ID, NAME, CheckStatus::Pass, "No leftover processes or stale locks in the last 30 days");
+    };
+    let killed = cleanups
+        .iter()
+        .filter(|c| matches!(c.action, Action::Killed | Action::KillFailed))
+        .count();
+    let mut detail = format!("{} in the last 30 days", cleanups.len());
+    if killed > 0 {
+        detail.push_str(&format!(" ({} had to be killed)", killed));
+    }
+    detail.push_str(&format!("; the last one {} at {}", last.describe(), last.timestamp));
+    if let Some(exe) = &last.exe {
+        detail.push_str(&format!(" ({})", exe));
+    }
+    // One after a crash is expected; kills and repeats point at something else holding the port
+    let status = if killed > 0 || cleanups.len() >= CLEANUP_RECURRING {
+        detail.push_str(". If this keeps happening, include this report when contacting support");
+        CheckStatus::Warn
+    } else {
+        CheckStatus::Pass
+    };
+    DoctorCheck::new(ID, NAME, status, detail)
+}
+
+fn check_data_dir(data_dir: &Path) -> DoctorCheck {
+    const ID: &str = "data_dir";
+    const NAME: &str = "Data directory";
+
+    let probe = data_dir.join(".doctor-write-test");
+    match fs::write(&probe, b"ok").and_then(|_| fs::remove_file(&probe)) {
+        Ok(()) => DoctorCheck::new(ID, NAME, CheckStatus::Pass, format!("{} is writable", data_dir.display())),
+        Err(e) => DoctorCheck::new(
+            ID,
+            NAME,
+            CheckStatus::Fail,
+            format!("{} is not writable: {}", data_dir.display(), e),
+        ),
+    }
+}
+
+fn check_heartbeat(data_dir: &Path) -> DoctorCheck {
+    const ID: &str = "heartbeat";
+    const NAME: &str = "Heartbeat";
+
+    let Some(beat) = heartbeat::read(data_dir) else {
+        return DoctorCheck::new(ID, NAME, CheckStatus::Warn, "No heartbeat has been written yet");
+    };
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let age = now.saturating_sub(beat.timestamp);
+    // This app is running, so an old beat means the writer is stuck or can't write
+    if age > beat.interval_secs * 3 {
+        return DoctorCheck::new(
+            ID,
+            NAME,
+            CheckStatus::Warn,
+            format!("The last heartbeat is {} minutes old; check that the data directory is writable", age / 60),
+        );
+    }
+    DoctorCheck::new(
+        ID,
+        NAME,
+        CheckStatus::Pass,
+        format!("Written {} s ago with the server {}", age, beat.status),
+    )
+}
+
+fn check_database(data_dir: &Path) -> DoctorCheck {
+    const ID: &str = "database";
+    const NAME: &str = "Database integrity";
+
+    if read_database_url(data_dir).is_some() {
+        return DoctorCheck::new(ID, NAME, CheckStatus::Pass, "PostgreSQL configured (integrity is managed by the database server)");
+    }
+
+    let db_path = sqlite_db_path(data_dir);
+    if !db_path.exists() {
+        return DoctorCheck::new(ID, NAME, CheckStatus::Warn, "No SQLite database yet (created on first start)");
+    }
+
+    let result = Connection::open_with_flags(&db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .and_then(|conn| conn.query_row("PRAGMA quick_check", [], |row| row.get::<_, String>(0)));
+
+    match result {
+        Ok(ref r) if r == "ok" => DoctorCheck::new(ID, NAME, CheckStatus::Pass, "SQLite quick_check passed"),
+        Ok(r) => DoctorCheck::new(ID, NAME, CheckStatus::Fail, format!("SQLite quick_check reported: {}", r)),
+        Err(e) => DoctorCheck::new(ID, NAME, CheckStatus::Fail, format!("Cannot open database: {}", e)),
+    }
+}
+
+fn migration_journal_path(resource_dir: &Path, is_postgres: bool) -> PathBuf {
+    resource_dir
+        .join("drizzle")
+        .join(if is_postgres { "pg" } else { "sqlite" })
+        .join("meta")
+        .join("_journal.json")
+}
+
+/// Number of migrations shipped with the app for the given backend
+pub fn bundled_migrations(resource_dir: &Path, is_postgres: bool) -> Option<usize> {
+    fs::read_to_string(migration_journal_path(resource_dir, is_postgres))
+        .ok()
+        .and_then(|c| serde_json::from_str::<MigrationJournal>(&c).ok())
+        .map(|journal| journal.entries.len())
+}
+
+fn check_migrations(data_dir: &Path, resource_dir: Option<PathBuf>) -> DoctorCheck {
+    const ID: &str = "migrations";
+    const NAME: &str = "Migrations";
+
+    let is_postgres = read_database_url(data_dir).is_some();
+    let Some(resource_dir) = resource_dir else {
+        return DoctorCheck::new(ID, NAME, CheckStatus::Fail, "Application resources could not be located");
+    };
+
+    let journal_path = migration_journal_path(&resource_dir, is_postgres);
+    let bundled = match bundled_migrations(&resource_dir, is_postgres) {
+        Some(count) => count,
+        None if cfg!(debug_assertions) => {
+            return DoctorCheck::new(ID, NAME, CheckStatus::Warn, "Migrations not bundled (development build)");
+        }
+        None => {
+            return DoctorCheck::new(
+                ID,
+                NAME,
+                CheckStatus::Fail,
+                format!("Migration journal missing at {}", journal_path.display()),
+            );
+        }
+    };
+
+    if is_postgres {
+        return DoctorCheck::new(ID, NAME, CheckStatus::Pass, format!("{} migrations bundled", bundled));
+    }
+
+    let db_path = sqlite_db_path(data_dir);
+    if !db_path.exists() {
+        return DoctorCheck::new(ID, NAME, CheckStatus::Pass, format!("{} migrations will run on first start", bundled));
+    }
+
+    let applied = Connection::open_with_flags(&db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .and_then(|conn| conn.query_row("SELECT COUNT(*) FROM __drizzle_migrations", [], |row| row.get::<_, i64>(0)));
+
+    match applied {
+        Ok(n) if n as usize == bundled => {
+            DoctorCheck::new(ID, NAME, CheckStatus::Pass, format!("All {} migrations applied", bundled))
+        }
+        Ok(n) if (n as usize) < bundled => DoctorCheck::new(
+            ID,
+            NAME,
+            CheckStatus::Warn,
+            format!("{} of {} migrations applied (pending ones run on next start)", n, bundled),
+        ),
+        Ok(n) => DoctorCheck::new(
+            ID,
+            NAME,
+            CheckStatus::Fail,
+            format!("Database has {} migrations but only {} are bundled (database is newer than the app)", n, bundled),
+        ),
+        Err(e) => DoctorCheck::new(ID, NAME, CheckStatus::Warn, format!("Could not read migration state: {}", e)),
+    }
+}
+
+fn check_disk_space(data_dir: &Path) -> DoctorCheck {
+    const ID: &str = "disk_space";
+    const NAME: &str = "Disk space";
+
+    match diskspace::free_space(data_dir) {
+        Some(free) => {
+            let detail = format!("{:.1} GB free on the data volume", free as f64 / 1_073_741_824.0);
+            let status = if free < diskspace::REFUSE_BYTES {
+                CheckStatus::Fail
+            } else if free < diskspace::WARN_BYTES {
+                CheckStatus::Warn
+            } else {
+                CheckStatus::Pass
+            };
+            DoctorCheck::new(ID, NAME, status, detail)
+        }
+        None => DoctorCheck::new(ID, NAME, CheckStatus::Warn, "Could not determine free space"),
+    }
+}
+
+fn check_keychain() -> DoctorCheck {
+    const ID: &str = "keychain";
+    const NAME: &str = "Keychain access";
+    const PROBE_KEY: &str = "doctor-probe";
+
+    let result = keychain::set_secret(PROBE_KEY, "ok")
+        .and_then(|_| keychain::get_secret(PROBE_KEY))
+        .and_then(|value| {
+            keychain::delete_secret(PROBE_KEY)?;
+            Ok(value)
+        });
+
+    match result {
+        Ok(Some(v)) if v == "ok" => DoctorCheck::new(ID, NAME, CheckStatus::Pass, "Secrets can be stored and read"),
+        Ok(_) => DoctorCheck::new(ID, NAME, CheckStatus::Fail, "Stored secret could not be read back"),
+        Err(e) => DoctorCheck::new(ID, NAME, CheckStatus::Warn, e),
+    }
+}
+
+async fn check_updates(app: &AppHandle) -> DoctorCheck {
+    const ID: &str = "updates";
+    const NAME: &str = "Update server";
+
+    let updater = match crate::updater::channel_updater(app).await {
+        Ok(u) => u,
+        Err(e) => return DoctorCheck::new(ID, NAME, CheckStatus::Warn, format!("Updater unavailable: {}", e)),
+    };
+
+    match tokio::time::timeout(UPDATE_CHECK_TIMEOUT, updater.check()).await {
+        Ok(Ok(Some(update))) => DoctorCheck::new(
+            ID,
+            NAME,
+            CheckStatus::Pass,
+            format!("Reachable - version {} is available", update.version),
+        ),
+        Ok(Ok(None)) => DoctorCheck::new(ID, NAME, CheckStatus::Pass, "Reachable - up to date"),
+        Ok(Err(e)) => DoctorCheck::new(ID, NAME, CheckStatus::Warn, format!("Update check failed: {}", e)),
+        Err(_) => DoctorCheck::new(ID, NAME, CheckStatus::Warn, "Update server did not respond in time"),
+    }
+}
+
+/// Run all diagnostics
+pub async fn run_diagnostics(app: &AppHandle) -> DoctorReport {
+    let (data_dir, status) = {
+        let manager = app.state::<SharedServerManager>();
+        let mgr = manager.lock().await;
+        (mgr.data_dir().clone(), mgr.status().clone())
+    };
+    let resource_dir = app.path().resource_dir().ok();
+
+    // Hashing the server binary, SQLite and filesystem checks block, keep them off the
+    // async runtime; the port is probed meanwhile
+    let blocking_dir = data_dir.clone();
+    let blocking = tauri::async_runtime::spawn_blocking(move || {
+        let cleanups = cleanups::recent(&blocking_dir, CLEANUP_WINDOW_SECS);
+        let checks = vec![
+            check_sidecar(),
+            check_architecture(&blocking_dir),
+            check_data_dir(&blocking_dir),
+            check_heartbeat(&blocking_dir),
+            check_database(&blocking_dir),
+            check_migrations(&blocking_dir, resource_dir),
+            check_disk_space(&blocking_dir),
+            check_keychain(),
+            check_process_inspection(),
+            check_cleanups(&cleanups),
+            avcheck::check_security_software(),
+        ];
+        (checks, cleanups)
+    });
+    let port = check_port(&status).await;
+    let (mut checks, cleanups) = match blocking.await {
+        Ok(results) => results,
+        Err(e) => (
+            vec![DoctorCheck::new("internal", "Diagnostics", CheckStatus::Fail, e.to_string())],
+            Vec::new(),
+        ),
+    };
+    // Reported after the binary and architecture checks
+    checks.insert(checks.len().min(2), port);
+
+    checks.push(check_updates(app).await);
+
+    let count = |s: CheckStatus| checks.iter().filter(|c| c.status == s).count();
+    DoctorReport {
+        generated_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        data_dir: data_dir.to_string_lossy().to_string(),
+        passed: count(CheckStatus::Pass),
+        warnings: count(CheckStatus::Warn),
+        failures: count(CheckStatus::Fail),
+        checks,
+        cleanups,
+    }
+}
+
+/// Run the self-diagnostic checks
+#[tauri::command]
+pub async fn run_doctor(app: AppHandle) -> Result<DoctorReport, String> {
+    Ok(run_diagnostics(&app).await)
+}
+
+/// Open the diagnostics window (from the error page)
+#[tauri::command]
+pub async fn open_diagnostics(app: AppHandle) -> Result<(), String> {
+    open_doctor_window(&app);
+    Ok(())
+}
+
+/// Open the diagnostics window
+pub fn open_doctor_window(app: &AppHandle) {
+    // Static UI; check details are inserted with escaping on the JS side
+    let script = r#"
+        const tauriApi = window.__TAURI__;
+
+        document.documentElement.innerHTML = `
+<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <title>Diagnostics</title>
+    <style>
+        __BASE_STYLE__
+        #summary { margin-left: auto; }
+        #checks { flex: 1; overflow-y: auto; padding: 8px 16px; }
+        .check { display: flex; gap: 12px; padding: 12px 0; border-bottom: 1px solid rgba(255, 255, 255, 0.04); }
+        .badge { width: 44px; flex-shrink: 0; font-size: 11px; font-weight: 600; text-transform: uppercase; padding-top: 2px; }
+        .check .name { font-weight: 500; }
+        .check .detail { color: #a1a1aa; margin-top: 2px; word-break: break-all; }
+        .check .steps { color: #d4d4d8; margin: 6px 0 0; padding-left: 20px; }
+        .check .steps li { margin-top: 2px; word-break: break-all; }
+    </style>
+</head>
+<body>
+    <div class="toolbar">
+        <button id="runBtn" class="primary">Run Again</button>
+        <button id="copyBtn">Copy Report</button>
+        <span id="summary" class="muted" role="status" aria-live="polite"></span>
+    </div>
+    <div id="checks" role="list" aria-label="Diagnostic checks"><div class="empty-state">Running diagnostics...</div></div>
+</body>
+</html>`;
+
+        const $ = id => document.getElementById(id);
+        let lastReport = null;
+
+        async function run() {
+            $('runBtn').disabled = true;
+            $('summary').textContent = 'Running...';
+            try {
+                const report = await tauriApi.core.invoke('run_doctor');
+                lastReport = report;
+                $('checks').innerHTML = report.checks.map(c =>
+                    '<div class="check" role="listitem"><div class="badge ' + c.status + '">' + c.status + '</div>' +
+                    '<div><div class="name">' + escapeHtml(c.name) + '</div><div class="detail">' + escapeHtml(c.detail) + '</div>' +
+                    (c.steps ? '<ol class="steps">' + c.steps.map(s => '<li>' + escapeHtml(s) + '</li>').join('') + '</ol>' : '') +
+                    '</div></div>'
+                ).join('');
+                $('summary').textContent = report.passed + ' passed · ' + report.warnings + ' warnings · ' + report.failures + ' failed';
+            } catch (e) {
+                $('checks').innerHTML = '<div class="check fail">' + escapeHtml(String(e)) + '</div>';
+                $('summary').textContent = '';
+            }
+            $('runBtn').disabled = false;
+        }
+
+        $('runBtn').onclick = run;
+        $('copyBtn').onclick = () => {
+            if (lastReport) navigator.clipboard.writeText(JSON.stringify(lastReport, null, 2));
+        };
+        run();
+    "#;
+
+    open_injected_window(app, "doctor", "Diagnostics", (720.0, 560.0), true, script);
+}
@@ -0,0 +1,334 @@
+// Resumable update downloads
+//
+// The updater plugin downloads a package into memory in one go, so a connection that
+// drops near the end of a 100 MB download starts over. Here the package is written to
+// `updates/<version>.part` in the data directory as it arrives, with the release it
+// belongs to in `updates/download.json`, and the next attempt continues with a Range
+// request: after a blip (retried a few times straight away), a pause, or a restart of
+// the app. When the server ignores the range or the file changed (ETag), it sends the
+// whole package again and the part is replaced.
+//
+// `pause_update_download` stops after the current chunk and keeps the part; background
+// downloads then wait for `resume_update_download`, also across restarts, while the
+// update dialog's Install button continues it. `cancel_update_download` stops and
+// deletes it. The finished package is checked against the release's minisign
+// signature with the updater's public key, as the plugin does, before it's installed.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use base64::Engine;
+use minisign_verify::{PublicKey, Signature};
+use reqwest::header::{ACCEPT, ETAG, IF_RANGE, RANGE};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use tauri::Runtime;
+use tauri_plugin_updater::Update;
+use crate::netproxy;
+use crate::netusage::{self, Subsystem};
+
+pub const PAUSED: &str = "Download paused";
+pub const CANCELLED: &str = "Download cancelled";
+const STATE_FILE: &str = "download.json";
+/// Attempts after a failed request or stream before giving up for now
+const RETRIES: u32 = 3;
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+/// A stream that stalls this long counts as dropped
+const READ_TIMEOUT: Duration = Duration::from_secs(60);
+
+static DIR: OnceLock<PathBuf> = OnceLock::new();
+/// A download is running
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+/// What the user asked of the running download
+static REQUEST: Mutex<Request> = Mutex::new(Request::Continue);
+
+#[derive(Clone, Copy, PartialEq)]
+enum Request {
+    Continue,
+    Pause,
+    Cancel,
+}
+
+/// The release a part belongs to; a different one discards it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Partial {
+    version: String,
+    url: String,
+    signature: String,
+    etag: Option<String>,
+    /// Size of the whole package, once the server said
+    total: Option<u64>,
+    /// Started by the background update rather than the update dialog
+    background: bool,
+    paused: bool,
+}
+
+/// Why a transfer stopped before the end
+enum Stop {
+    Paused,
+    Cancelled,
+    Failed(String),
+}
+
+/// Marks the download as running; dropping it (also when the task is dropped) ends it
+/// and counts what came in
+struct Active {
+    received: u64,
+}
+
+impl Drop for Active {
+    fn drop(&mut self) {
+        ACTIVE.store(false, Ordering::SeqCst);
+        netusage::record(Subsystem::Updates, 0, self.received);
+    }
+}
+
+pub fn init(data_dir: &Path) {
+    let _ = DIR.set(data_dir.join("updates"));
+    // A part of the version now running is of no use any more
+    if load().is_some_and(|partial| partial.version == env!("CARGO_PKG_VERSION")) {
+        discard();
+    }
+}
+
+fn load() -> Option<Partial> {
+    let text = fs::read_to_string(DIR.get()?.join(STATE_FILE)).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+fn save(partial: &Partial) -> Result<(), String> {
+    let dir = DIR.get().ok_or_else(|| "Update downloads aren't set up".to_string())?;
+    fs::create_dir_all(dir).map_err(|e| format!("Cannot create {}: {}", dir.display(), e))?;
+    let json = serde_json::to_string_pretty(partial).map_err(|e| e.to_string())?;
+    fs::write(dir.join(STATE_FILE), json).map_err(|e| format!("Cannot save the download state: {}", e))
+}
+
+/// Delete the part and its state
+pub fn discard() {
+    if let Some(dir) = DIR.get() {
+        let _ = fs::remove_dir_all(dir);
+    }
+}
+
+fn requested() -> Request {
+    *REQUEST.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn set_request(request: Request) {
+    *REQUEST.lock().unwrap_or_else(|e| e.into_inner()) = request;
+}
+
+/// The download was paused and background updates should leave it alone
+pub fn is_paused() -> bool {
+    load().is_some_and(|partial| partial.paused)
+}
+
+/// Whether a paused download was started in the background, None if nothing is paused
+pub fn paused_background() -> Option<bool> {
+    load().filter(|partial| partial.paused).map(|partial| partial.background)
+}
+
+/// Let background updates continue a paused download
+pub fn unpause() -> Result<(), String> {
+    match load() {
+        Some(mut partial) if partial.paused => {
+            partial.paused = false;
+            save(&partial)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Check the package against the release signature and the updater's public key
+fn verify<R: Runtime>(app: &tauri::AppHandle<R>, data: &[u8], signature: &str) -> Result<(), String> {
+    let decode = |value: &str| {
+        base64::engine::general_purpose::STANDARD
+            .decode(value)
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+    };
+    let pubkey = app
+        .config()
+        .plugins
+        .0
+        .get("updater")
+        .and_then(|updater| updater.get("pubkey"))
+        .and_then(|key| key.as_str())
+        .ok_or_else(|| "The updater has no public key configured".to_string())?;
+    let key = decode(pubkey)
+        .and_then(|key| PublicKey::decode(&key).ok())
+        .ok_or_else(|| "The updater's public key is invalid".to_string())?;
+    let signature = decode(signature)
+        .and_then(|signature| Signature::decode(&signature).ok())
+        .ok_or_else(|| "The update's signature is invalid".to_string())?;
+    key.verify(data, &signature, true)
+        .map_err(|e| format!("The downloaded update failed signature verification: {}", e))
+}
+
+/// One request for the rest of the package, appended to the part
+async fn transfer(
+    client: &reqwest::Client,
+    update: &Update,
+    path: &Path,
+    partial: &mut Partial,
+    active: &mut Active,
+    on_progress: &mut impl FnMut(u64, Option<u64>),
+) -> Result<(), Stop> {
+    let mut offset = fs::metadata(path).map(|meta| meta.len()).unwrap_or(0);
+    if offset > 0 && partial.total == Some(offset) {
+        return Ok(());
+    }
+
+    let mut request = client.get(update.download_url.clone()).headers(update.headers.clone());
+    if !update.headers.contains_key(ACCEPT) {
+        request = request.header(ACCEPT, "application/octet-stream");
+    }
+    if offset > 0 {
+        request = request.header(RANGE, format!("bytes={}-", offset));
+        if let Some(etag) = &partial.etag {
+            request = request.header(IF_RANGE, etag);
+        }
+    }
+    let mut response = request.send().await.map_err(|e| Stop::Failed(e.to_string()))?;
+    let append = match response.status() {
+        StatusCode::PARTIAL_CONTENT => true,
+        StatusCode::RANGE_NOT_SATISFIABLE => {
+            // The part is longer than the package, it belongs to something else
+            let _ = fs::remove_file(path);
+            partial.total = None;
+            return Err(Stop::Failed("The partial download didn't match the package".to_string()));
+        }
+        status if status.is_success() => false,
+        status => return Err(Stop::Failed(format!("Download request failed with status: {}", status))),
+    };
+    if !append {
+        offset = 0;
+    }
+    partial.etag = response.headers().get(ETAG).and_then(|etag| etag.to_str().ok()).map(str::to_string);
+    partial.total = response.content_length().map(|length| offset + length);
+    save(partial).map_err(Stop::Failed)?;
+
+    let file = if append { OpenOptions::new().append(true).open(path) } else { File::create(path) };
+    let mut file = file.map_err(|e| Stop::Failed(format!("Cannot write {}: {}", path.display(), e)))?;
+    on_progress(offset, partial.total);
+    loop {
+        match requested() {
+            Request::Continue => {}
+            Request::Pause => return Err(Stop::Paused),
+            Request::Cancel => return Err(Stop::Cancelled),
+        }
+        let Some(chunk) = response.chunk().await.map_err(|e| Stop::Failed(e.to_string()))? else {
+            break;
+        };
+        file.write_all(&chunk).map_err(|e| Stop::Failed(format!("Cannot write {}: {}", path.display(), e)))?;
+        offset += chunk.len() as u64;
+        active.received += chunk.len() as u64;
+        on_progress(offset, partial.total);
+    }
+    file.sync_all().map_err(|e| Stop::Failed(format!("Cannot write {}: {}", path.display(), e)))?;
+    match partial.total {
+        Some(total) if total != offset => Err(Stop::Failed(format!("The download ended after {} of {} bytes", offset, total))),
+        _ => {
+            partial.total = Some(offset);
+            save(partial).map_err(Stop::Failed)
+        }
+    }
+}
+
+/// Download and verify the package of `update`, continuing a part an earlier attempt left
+/// `on_progress` gets the bytes so far (including the part) and the total, when known
+pub async fn fetch<R: Runtime>(
+    app: &tauri::AppHandle<R>,
+    update: &Update,
+    background: bool,
+    mut on_progress: impl FnMut(u64, Option<u64>),
+) -> Result<Vec<u8>, String> {
+    if ACTIVE.swap(true, Ordering::SeqCst) {
+        return Err("An update download is already running".to_string());
+    }
+    let mut active = Active { received: 0 };
+    set_request(Request::Continue);
+
+    let dir = DIR.get().ok_or_else(|| "Update downloads aren't set up".to_string())?;
+    let url = update.download_url.to_string();
+    let mut partial = match load() {
+        Some(partial) if partial.version == update.version && partial.url == url && partial.signature == update.signature => partial,
+        _ => {
+            discard();
+            Partial {
+                version: update.version.clone(),
+                url,
+                signature: update.signature.clone(),
+                etag: None,
+                total: None,
+                background,
+                paused: false,
+            }
+        }
+    };
+    partial.background = background;
+    partial.paused = false;
+    save(&partial)?;
+    let path = dir.join(format!("{}.part", update.version));
+
+    let client = netproxy::client_builder(app)
+        .await?
+        .connect_timeout(CONNECT_TIMEOUT)
+        .read_timeout(READ_TIMEOUT)
+        .user_agent(concat!("moneywright-desktop/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(|e| e.to_string())?;
+    let mut attempt = 0;
+    loop {
+        match transfer(&client, update, &path, &mut partial, &mut active, &mut on_progress).await {
+            Ok(()) => break,
+            Err(Stop::Paused) => {
+                partial.paused = true;
+                save(&partial)?;
+                return Err(PAUSED.to_string());
+            }
+            Err(Stop::Cancelled) => {
+                discard();
+                return Err(CANCELLED.to_string());
+            }
+            Err(Stop::Failed(e)) if attempt < RETRIES => {
+                attempt += 1;
+                eprintln!("Update download interrupted ({}), retrying: {}", attempt, e);
+                tokio::time::sleep(RETRY_DELAY * attempt).await;
+            }
+            Err(Stop::Failed(e)) => return Err(format!("{} (the next attempt continues where this one stopped)", e)),
+        }
+    }
+
+    let bytes = fs::read(&path).map_err(|e| format!("Cannot read {}: {}", path.display(), e));
+    // Whatever the outcome, a finished part isn't downloaded again
+    discard();
+    let bytes = bytes?;
+    verify(app, &bytes, &update.signature)?;
+    Ok(bytes)
+}
+
+/// Stop the running update download after the current chunk, keeping what arrived
+#[tauri::command]
+pub async fn pause_update_download() -> Result<(), String> {
+    if !ACTIVE.load(Ordering::SeqCst) {
+        return Err("No update download is running".to_string());
+    }
+    set_request(Request::Pause);
+    Ok(())
+}
+
+/// Stop the running or paused update download and delete what arrived
+#[tauri::command]
+pub async fn cancel_update_download() -> Result<(), String> {
+    if ACTIVE.load(Ordering::SeqCst) {
+        set_request(Request::Cancel);
+    } else {
+        discard();
+    }
+    Ok(())
+}
@@ -0,0 +1,408 @@
+// Server environment: the data dir .env, editable from the Server Environment window
+//
+// The sidecar loads .env when it starts, without overriding variables it was started
+// with, so the keys the shell sets itself (PORT, DATA_DIR, ...) can't be changed here
+// and DATABASE_URL belongs to the Database window. ENCRYPTION_KEY is read-only: a new
+// key would leave everything encrypted with the old one unreadable.
+//
+// Saving rewrites the file in place, keeping comments and order, by writing a temporary
+// file and renaming it over the old one; the previous version is kept as `.env.bak`.
+// Changes apply when the server restarts, which the window offers right away.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use serde::Serialize;
+use tauri::{AppHandle, Url};
+use crate::logs::{log_line, SharedLogStore};
+use crate::server::SharedServerManager;
+use crate::windows::open_injected_window;
+
+const ENV_FILE: &str = ".env";
+const BACKUP_FILE: &str = ".env.bak";
+const MAX_VALUE_LEN: usize = 4096;
+
+/// Set by the shell when it starts the sidecar, so a value in .env never applies
+const SET_BY_APP: &[&str] = &[
+    "PORT",
+    "HOST",
+    "DATA_DIR",
+    "SHUTDOWN_TOKEN",
+    "SERVER_SOCKET",
+    "TLS_CERT_FILE",
+    "TLS_KEY_FILE",
+    "MIGRATIONS_PATH",
+    "PUBLIC_DIR",
+    "LOG_LEVEL",
+    "MONEYWRIGHT_FEATURES",
+    "MONEYWRIGHT_MAINTENANCE_WINDOW",
+];
+const BOOLEAN_KEYS: &[&str] = &["AUTH_ENABLED", "ENABLE_LOGGING"];
+const URL_KEYS: &[&str] = &["APP_URL", "OLLAMA_BASE_URL", "LLM_API_BASE_URL"];
+
+#[derive(Clone, Serialize)]
+pub struct EnvVar {
+    pub key: String,
+    /// None for secrets, which are only ever replaced
+    pub value: Option<String>,
+    pub secret: bool,
+    pub editable: bool,
+    /// Why a key can't be edited, or what else affects it
+    pub note: Option<&'static str>,
+}
+
+#[derive(Serialize)]
+pub struct EnvConfig {
+    pub path: String,
+    pub vars: Vec<EnvVar>,
+    pub backup_exists: bool,
+}
+
+#[derive(Serialize)]
+pub struct EnvSaveResult {
+    /// Keys set or removed
+    pub changed: Vec<String>,
+    /// The server runs with the old values until it restarts
+    pub restart_needed: bool,
+}
+
+/// DATABASE_URL may carry the password inline
+fn is_secret(key: &str) -> bool {
+    key == "DATABASE_URL" || key.ends_with("_KEY") || ["SECRET", "PASSWORD", "TOKEN"].iter().any(|word| key.contains(word))
+}
+
+/// Why a key can't be changed here, None if it can
+fn locked_reason(key: &str) -> Option<&'static str> {
+    match key {
+        "DATABASE_URL" => Some("Change the database in Database Settings"),
+        "ENCRYPTION_KEY" => Some("Changing it would make encrypted data unreadable"),
+        _ if SET_BY_APP.contains(&key) => Some("Set by the app when it starts the server"),
+        _ => None,
+    }
+}
+
+fn note(key: &str) -> Option<&'static str> {
+    locked_reason(key).or(match key {
+        "APP_URL" => Some("server.external_url takes precedence when set"),
+        "JWT_SECRET" => Some("Changing it signs everyone out"),
+        _ => None,
+    })
+}
+
+/// Key and unquoted value of an assignment line, read like the server does
+fn parse_line(line: &str) -> Option<(&str, &str)> {
+    let trimmed = line.trim();
+    if trimmed.is_empty() || trimmed.starts_with('#') {
+        return None;
+    }
+    let (key, value) = trimmed.split_once('=')?;
+    let value = value.trim();
+    let unquoted = ['"', '\'']
+        .iter()
+        .find(|q| value.len() >= 2 && value.starts_with(**q) && value.ends_with(**q))
+        .map(|_| &value[1..value.len() - 1])
+        .unwrap_or(value);
+    Some((key.trim(), unquoted))
+}
+
+/// Values as the server ends up with them (the last assignment of a key wins)
+fn parse(content: &str) -> BTreeMap<String, String> {
+    content
+        .lines()
+        .filter_map(parse_line)
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+/// A variable from the data dir .env, None when the file or the key is missing
+pub fn value(data_dir: &Path, key: &str) -> Option<String> {
+    let content = fs::read_to_string(data_dir.join(ENV_FILE)).ok()?;
+    parse(&content).remove(key)
+}
+
+pub fn validate(key: &str, value: Option<&str>) -> Result<(), String> {
+    let valid_key = key.len() <= 128
+        && key.chars().next().is_some_and(|c| c.is_ascii_uppercase() || c == '_')
+        && key.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_');
+    if !valid_key {
+        return Err(format!("\"{}\" isn't a valid name; use capital letters, digits and underscores", key));
+    }
+    if let Some(reason) = locked_reason(key) {
+        return Err(format!("{} can't be changed here: {}", key, reason));
+    }
+    let Some(value) = value else {
+        return Ok(());
+    };
+    if value.contains(['\n', '\r', '\0']) {
+        return Err(format!("{} must be a single line", key));
+    }
+    if value.len() > MAX_VALUE_LEN {
+        return Err(format!("{} is longer than {} characters", key, MAX_VALUE_LEN));
+    }
+    if BOOLEAN_KEYS.contains(&key) && !matches!(value, "true" | "false") {
+        return Err(format!("{} must be \"true\" or \"false\"", key));
+    }
+    if URL_KEYS.contains(&key) {
+        let url = Url::parse(value).map_err(|e| format!("{} isn't a valid URL: {}", key, e))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(format!("{} must start with http:// or https://", key));
+        }
+    }
+    Ok(())
+}
+
+/// Quote values the server would otherwise trim or unquote
+fn format_value(value: &str) -> String {
+    let needs_quotes = value != value.trim()
+        || value.contains('#')
+        || value.starts_with(['"', '\''])
+        || value.ends_with(['"', '\'']);
+    if needs_quotes {
+        format!("\"{}\"", value)
+    } else {
+        value.to_string()
+    }
+}
+
+/// The file with `changes` applied in place: set keys keep their line (duplicates are
+/// dropped), removed keys lose theirs, new keys are added at the end
+fn apply_changes(content: &str, changes: &BTreeMap<String, Option<String>>) -> String {
+    let mut written: Vec<&str> = Vec::new();
+    let mut lines: Vec<String> = Vec::new();
+    for line in content.lines() {
+        let Some((key, _)) = parse_line(line) else {
+            lines.push(line.to_string());
+            continue;
+        };
+        match changes.get_key_value(key) {
+            None => lines.push(line.to_string()),
+            Some((_, None)) => {}
+            Some((key, Some(value))) => {
+                if !written.contains(&key.as_str()) {
+                    lines.push(format!("{}={}", key, format_value(value)));
+                    written.push(key.as_str());
+                }
+            }
+        }
+    }
+    for (key, value) in changes {
+        if let Some(value) = value.as_ref().filter(|_| !written.contains(&key.as_str())) {
+            lines.push(format!("{}={}", key, format_value(value)));
+        }
+    }
+    let mut content = lines.join("\n");
+    content.push('\n');
+    content
+}
+
+/// Replace the file through a temporary one, keeping the old version as the backup
+fn write_atomic(data_dir: &Path, content: &str) -> Result<(), String> {
+    let path = data_dir.join(ENV_FILE);
+    let tmp = data_dir.join(".env.tmp");
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    // It holds secrets
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(&tmp).map_err(|e| format!("Failed to write {}: {}", tmp.display(), e))?;
+    file.write_all(content.as_bytes())
+        .and_then(|_| file.sync_all())
+        .map_err(|e| format!("Failed to write {}: {}", tmp.display(), e))?;
+    drop(file);
+    if path.exists() {
+        fs::copy(&path, data_dir.join(BACKUP_FILE)).map_err(|e| format!("Failed to back up .env: {}", e))?;
+    }
+    fs::rename(&tmp, &path).map_err(|e| format!("Failed to replace .env: {}", e))
+}
+
+/// Variables in the data dir .env, secrets without their values
+#[tauri::command]
+pub async fn get_env_config(manager: tauri::State<'_, SharedServerManager>) -> Result<EnvConfig, String> {
+    let data_dir = manager.lock().await.data_dir().clone();
+    let path = data_dir.join(ENV_FILE);
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+    let vars = parse(&content)
+        .into_iter()
+        .map(|(key, value)| {
+            let secret = is_secret(&key);
+            EnvVar {
+                value: (!secret).then_some(value),
+                secret,
+                editable: locked_reason(&key).is_none(),
+                note: note(&key),
+                key,
+            }
+        })
+        .collect();
+    Ok(EnvConfig {
+        path: path.to_string_lossy().to_string(),
+        vars,
+        backup_exists: data_dir.join(BACKUP_FILE).exists(),
+    })
+}
+
+/// Set (Some) or remove (None) variables in the data dir .env
+#[tauri::command]
+pub async fn set_env_config(
+    app: AppHandle,
+    manager: tauri::State<'_, SharedServerManager>,
+    log_store: tauri::State<'_, SharedLogStore>,
+    changes: BTreeMap<String, Option<String>>,
+) -> Result<EnvSaveResult, String> {
+    if changes.is_empty() {
+        return Err("Nothing to save".to_string());
+    }
+    for (key, value) in &changes {
+        validate(key, value.as_deref())?;
+    }
+    let (data_dir, running) = {
+        let mgr = manager.lock().await;
+        (mgr.data_dir().clone(), mgr.is_running())
+    };
+    let content = fs::read_to_string(data_dir.join(ENV_FILE)).unwrap_or_default();
+    let updated = apply_changes(&content, &changes);
+    let write_dir = data_dir.clone();
+    tauri::async_runtime::spawn_blocking(move || write_atomic(&write_dir, &updated))
+        .await
+        .map_err(|e| e.to_string())??;
+
+    let changed: Vec<String> = changes.into_keys().collect();
+    // Names only, values may be secrets
+    log_line(&app, &log_store, format!("Server environment changed: {}", changed.join(", ")), "info").await;
+    Ok(EnvSaveResult { changed, restart_needed: running })
+}
+
+/// Open the Server Environment window
+pub fn open_env_window(app: &AppHandle) {
+    // Static UI; keys and values are assigned to inputs or escaped, never inserted as HTML
+    let script = r#"
+        const tauriApi = window.__TAURI__;
+
+        document.documentElement.innerHTML = `
+<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <title>Server Environment</title>
+    <style>
+        __BASE_STYLE__
+        #content { flex: 1; overflow-y: auto; padding: 16px 24px; }
+        table { width: 100%; border-collapse: collapse; }
+        th, td { text-align: left; padding: 6px 8px; border-bottom: 1px solid rgba(255, 255, 255, 0.06); vertical-align: middle; }
+        td.key { white-space: nowrap; }
+        td input { width: 100%; }
+        td.note { font-size: 12px; }
+        .add { display: flex; gap: 8px; margin-top: 16px; }
+        .add input { flex: 1; }
+        #result { margin-top: 14px; white-space: pre-wrap; }
+        .footer { display: flex; align-items: center; gap: 12px; padding: 14px 24px; border-top: 1px solid rgba(255, 255, 255, 0.06); }
+        .footer .spacer { flex: 1; }
+    </style>
+</head>
+<body>
+    <div id="content">
+        <p class="muted mono" id="path"></p>
+        <table>
+            <thead><tr><th>Name</th><th>Value</th><th></th></tr></thead>
+            <tbody id="vars"></tbody>
+        </table>
+        <div class="add">
+            <input type="text" id="newKey" placeholder="NAME" aria-label="New variable name" autocomplete="off" spellcheck="false">
+            <input type="text" id="newValue" placeholder="value" aria-label="New variable value" autocomplete="off" spellcheck="false">
+            <button id="addBtn">Add</button>
+        </div>
+        <div id="result" role="status" aria-live="polite"></div>
+    </div>
+    <div class="footer">
+        <span class="muted">Applies when the server restarts</span>
+        <span class="spacer"></span>
+        <button id="saveBtn" class="primary">Save</button>
+    </div>
+</body>
+</html>`;
+
+        const $ = id => document.getElementById(id);
+        // key -> new value, or null to remove
+        let changes = {};
+
+        function row(v) {
+            const tr = document.createElement('tr');
+            tr.innerHTML = '<td class="key mono">' + escapeHtml(v.key) + '</td><td></td><td class="note muted"></td>';
+            const input = document.createElement('input');
+            input.type = v.secret ? 'password' : 'text';
+            input.value = v.value || '';
+            input.placeholder = v.secret ? 'unchanged' : '';
+            input.disabled = !v.editable;
+            input.setAttribute('aria-label', v.key);
+            input.autocomplete = 'off';
+            input.spellcheck = false;
+            input.oninput = () => {
+                // An empty secret field keeps the stored value
+                if (v.secret && !input.value) delete changes[v.key];
+                else changes[v.key] = input.value;
+            };
+            tr.children[1].appendChild(input);
+            if (v.editable) {
+                const remove = document.createElement('button');
+                remove.textContent = 'Remove';
+                remove.setAttribute('aria-label', 'Remove ' + v.key);
+                remove.onclick = () => { changes[v.key] = null; tr.remove(); };
+                tr.children[2].appendChild(remove);
+            }
+            if (v.note) tr.children[2].append(' ' + v.note);
+            return tr;
+        }
+
+        async function load() {
+            changes = {};
+            try {
+                const config = await tauriApi.core.invoke('get_env_config');
+                $('path').textContent = config.path + (config.backup_exists ? '  (previous version in .env.bak)' : '');
+                const body = $('vars');
+                body.innerHTML = '';
+                config.vars.forEach(v => body.appendChild(row(v)));
+                if (!config.vars.length) body.innerHTML = '<tr><td colspan="3" class="muted">No variables set</td></tr>';
+            } catch (e) {
+                $('result').className = 'fail';
+                $('result').textContent = String(e);
+            }
+        }
+
+        $('addBtn').onclick = () => {
+            const key = $('newKey').value.trim().toUpperCase();
+            if (!key) return;
+            changes[key] = $('newValue').value;
+            $('vars').appendChild(row({ key, value: $('newValue').value, secret: false, editable: true, note: 'new' }));
+            $('newKey').value = '';
+            $('newValue').value = '';
+        };
+
+        $('saveBtn').onclick = async () => {
+            $('result').className = '';
+            $('result').textContent = '';
+            try {
+                const r = await tauriApi.core.invoke('set_env_config', { changes });
+                $('result').className = 'pass';
+                $('result').textContent = 'Saved ' + r.changed.join(', ') + '.';
+                await load();
+                if (r.restart_needed && confirm('Restart the server now so the changes take effect?')) {
+                    $('result').textContent += ' Restarting the server...';
+                    await tauriApi.core.invoke('restart_server_cmd');
+                    $('result').textContent = 'Saved and restarted the server.';
+                }
+            } catch (e) {
+                $('result').className = 'fail';
+                $('result').textContent = String(e);
+            }
+        };
+
+        load();
+    "#;
+
+    open_injected_window(app, "server_env", "Server Environment", (720.0, 520.0), true, script);
+}
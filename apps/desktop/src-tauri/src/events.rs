@@ -0,0 +1,152 @@
+// Events the shell sends to the web app and its own windows
+//
+// Every event goes through `emit`/`emit_to` with a variant of `Event`, so the names and
+// payloads live in one place instead of string literals across modules. The payload on
+// the wire is the variant's inner value, unchanged from before the bus existed. The web
+// app can ask for the contract with the `event_contract` command; `VERSION` is bumped
+// whenever an event is renamed, removed or its payload changes incompatibly (adding an
+// event or an optional field doesn't need a bump).
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Runtime};
+use crate::a11y::A11yPrefs;
+use crate::control::ImportRequest;
+use crate::diskspace::LowDiskSpace;
+use crate::importer::{DetectedFile, ImportProgress};
+use crate::jobs::JobInfo;
+use crate::logs::LogPayload;
+use crate::server::{MigrationProgress, RestartAttempt, StartupProgress, Unhealthy};
+use crate::serverstats::ServerStats;
+use crate::settings::Settings;
+use crate::updater::{DownloadProgress, UpdateReadyInfo};
+use crate::upgrade::UpgradeProgress;
+
+pub const VERSION: u32 = 1;
+
+#[derive(Clone, Serialize)]
+#[serde(untagged)]
+pub enum Event<'a> {
+    /// "starting", "running", "stopped" or "error"
+    ServerStatus(&'a str),
+    /// The server crashed and is about to be started again
+    ServerRestartAttempt(&'a RestartAttempt),
+    /// The starting server began or finished applying database migrations
+    ServerMigrating(&'a MigrationProgress),
+    /// The starting server reached the next step of its start
+    ServerStartupProgress(&'a StartupProgress),
+    /// The running server stopped answering health checks
+    ServerUnhealthy(&'a Unhealthy),
+    /// Server and shell log lines, batched
+    ServerLogBatch(&'a [LogPayload]),
+    /// CPU and memory use of the running server
+    ServerStats(&'a ServerStats),
+    /// Desktop settings after a change
+    SettingsChanged(&'a Settings),
+    /// A background job started, progressed or finished
+    JobProgress(&'a JobInfo),
+    /// Download progress of a user-started update
+    UpdateProgress(&'a DownloadProgress),
+    /// Download progress of an automatic update
+    BackgroundUpdateProgress(&'a DownloadProgress),
+    /// An update is installed and waits for a restart
+    UpdateReady(&'a UpdateReadyInfo),
+    /// A file was handed to the app to import (control socket, file association)
+    ImportFileRequested(&'a ImportRequest),
+    /// Per-account progress of a converter import
+    ImportProgress(&'a ImportProgress),
+    /// Exports found in Downloads changed (import window only)
+    ImportFilesDetected(&'a [DetectedFile]),
+    /// Preview a file in the open import window
+    ImportPreviewRequested(&'a str),
+    /// System high contrast or reduced motion changed
+    SystemA11yChanged(&'a A11yPrefs),
+    /// Free space is low where the server, a backup or an update writes
+    DiskSpaceLow(&'a LowDiskSpace),
+    /// A new version is migrating the database, or finished, failed or was rolled back
+    UpgradeProgress(&'a UpgradeProgress),
+}
+
+/// Name and description of every event, for `event_contract`
+const EVENTS: &[(&str, &str)] = &[
+    ("server-status", "Server state: \"starting\", \"running\", \"stopped\", \"paused\" or \"error\""),
+    ("server-restart-attempt", "The server crashed and restarts after delay_secs, as { profile, attempt, max_attempts, delay_secs }"),
+    ("server-migrating", "The starting server began or finished applying migrations, as { profile, pending, done }"),
+    ("server-startup-progress", "The starting server reached a step of its start, as { profile, phase, pending, elapsed_ms }"),
+    ("server-unhealthy", "The running server stopped answering health checks, as { failures, interval_secs, restarting }"),
+    ("server-log-batch", "Log lines as [{ message, log_type }]"),
+    ("server-stats", "Every 10 s while the server runs, as { pid, cpu_percent, memory_bytes, uptime_secs, processes }; cpu_percent is of one core"),
+    ("settings-changed", "Desktop settings after a change"),
+    ("job-progress", "A background job started, progressed or finished"),
+    ("update-progress", "Download progress of a user-started update"),
+    ("background-update-progress", "Download progress of an automatic update"),
+    ("update-ready", "An update is installed and waits for a restart"),
+    ("import-file-requested", "A file was handed to the app to import, as { path }"),
+    ("import-progress", "Per-account progress of a converter import"),
+    ("import-files-detected", "Exports found in Downloads changed (import window only)"),
+    ("import-preview-requested", "Preview a file in the open import window"),
+    ("system-a11y-changed", "System high contrast or reduced motion changed"),
+    ("disk-space-low", "Free space is low, as { operation, path, free_bytes, needed_bytes, refused }; refused operations didn't run"),
+    ("upgrade-progress", "Database upgrade after an app update, as { from, to, stage, applied, total, snapshot, error }; stage is \"migrating\", \"done\", \"failed\" or \"rolled_back\""),
+];
+
+impl Event<'_> {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Event::ServerStatus(_) => "server-status",
+            Event::ServerRestartAttempt(_) => "server-restart-attempt",
+            Event::ServerMigrating(_) => "server-migrating",
+            Event::ServerStartupProgress(_) => "server-startup-progress",
+            Event::ServerUnhealthy(_) => "server-unhealthy",
+            Event::ServerLogBatch(_) => "server-log-batch",
+            Event::ServerStats(_) => "server-stats",
+            Event::SettingsChanged(_) => "settings-changed",
+            Event::JobProgress(_) => "job-progress",
+            Event::UpdateProgress(_) => "update-progress",
+            Event::BackgroundUpdateProgress(_) => "background-update-progress",
+            Event::UpdateReady(_) => "update-ready",
+            Event::ImportFileRequested(_) => "import-file-requested",
+            Event::ImportProgress(_) => "import-progress",
+            Event::ImportFilesDetected(_) => "import-files-detected",
+            Event::ImportPreviewRequested(_) => "import-preview-requested",
+            Event::SystemA11yChanged(_) => "system-a11y-changed",
+            Event::DiskSpaceLow(_) => "disk-space-low",
+            Event::UpgradeProgress(_) => "upgrade-progress",
+        }
+    }
+}
+
+/// Send an event to every window
+pub fn emit<R: Runtime>(app: &AppHandle<R>, event: Event) -> Result<(), String> {
+    app.emit(event.name(), &event)
+        .map_err(|e| format!("Failed to emit {}: {}", event.name(), e))
+}
+
+/// Send an event to one window
+pub fn emit_to<R: Runtime>(app: &AppHandle<R>, window: &str, event: Event) -> Result<(), String> {
+    app.emit_to(window, event.name(), &event)
+        .map_err(|e| format!("Failed to emit {}: {}", event.name(), e))
+}
+
+#[derive(Serialize)]
+pub struct EventInfo {
+    name: &'static str,
+    description: &'static str,
+}
+
+#[derive(Serialize)]
+pub struct EventContract {
+    version: u32,
+    events: Vec<EventInfo>,
+}
+
+/// Events the shell emits and the contract version, for the web app to check against
+#[tauri::command]
+pub async fn event_contract() -> Result<EventContract, String> {
+    Ok(EventContract {
+        version: VERSION,
+        events: EVENTS
+            .iter()
+            .map(|&(name, description)| EventInfo { name, description })
+            .collect(),
+    })
+}
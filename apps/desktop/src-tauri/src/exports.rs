@@ -0,0 +1,894 @@
+// Scheduled exports: recurring CSV/JSON snapshots written to a folder or WebDAV
+//
+// Data comes from the server's regular API (the same endpoints the web app uses),
+// authenticated with the main window's session. Report pages of the web app can be
+// included as PDFs (see pdf.rs), which with a monthly schedule and the default
+// Archive folder keeps a paper trail. Tasks live in exports.json in the data dir;
+// WebDAV passwords are kept in the keychain.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Manager, Url};
+use crate::history;
+use crate::importer::session_cookies;
+use crate::jobs::{start_job, JobHandle, JobKind};
+use crate::keychain;
+use crate::logs::{log_line, SharedLogStore};
+use crate::pdf::{render_pdf, REPORT_PAGES};
+use crate::proxy::is_loopback;
+use crate::scheduler::{describe_delay, format_local, heavy_work_deferral, interval_due, monthly_due, CatchUp, Due, MAX_CATCH_UP};
+use crate::server::{get_server_url, SharedServerManager};
+use crate::settings::SharedSettings;
+use crate::transport;
+use crate::windows::open_injected_window;
+
+const EXPORTS_FILE: &str = "exports.json";
+/// How often due tasks are checked
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+/// Largest page the transactions endpoint returns
+const PAGE_SIZE: usize = 100;
+const MAX_INTERVAL_HOURS: u32 = 24 * 31;
+
+/// Datasets that can be exported. Budgets aren't exposed by the API, so monthly
+/// income/expense trends are offered for spreadsheet budgeting instead.
+const DATASETS: &[&str] = &["transactions", "net_worth", "monthly_trends"];
+
+/// Serializes read-modify-write cycles on the tasks file
+static TASKS_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ExportTask {
+    pub id: String,
+    pub name: String,
+    pub enabled: bool,
+    pub datasets: Vec<String>,
+    pub format: ExportFormat,
+    pub interval_hours: u32,
+    /// Report pages rendered to PDF, see pdf::REPORT_PAGES
+    #[serde(default)]
+    pub reports: Vec<String>,
+    /// Run at the start of this day of each month (1-28) instead of every `interval_hours`
+    #[serde(default)]
+    pub monthly_day: Option<u32>,
+    /// Local folder, if exporting to disk
+    #[serde(default)]
+    pub directory: Option<String>,
+    /// WebDAV collection URL, if uploading
+    #[serde(default)]
+    pub webdav_url: Option<String>,
+    #[serde(default)]
+    pub webdav_user: Option<String>,
+    /// Limit to one profile (all profiles when None)
+    #[serde(default)]
+    pub profile_id: Option<String>,
+    #[serde(default)]
+    pub last_run: Option<u64>,
+    #[serde(default)]
+    pub last_error: Option<String>,
+}
+
+/// Task as edited in the window; `webdav_password` of None keeps the saved one
+#[derive(Deserialize)]
+pub struct ExportTaskInput {
+    pub id: Option<String>,
+    pub name: String,
+    pub enabled: bool,
+    pub datasets: Vec<String>,
+    pub format: ExportFormat,
+    pub interval_hours: u32,
+    #[serde(default)]
+    pub reports: Vec<String>,
+    #[serde(default)]
+    pub monthly_day: Option<u32>,
+    pub directory: Option<String>,
+    pub webdav_url: Option<String>,
+    pub webdav_user: Option<String>,
+    pub webdav_password: Option<String>,
+    pub profile_id: Option<String>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct ExportsFile {
+    tasks: Vec<ExportTask>,
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn exports_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(EXPORTS_FILE)
+}
+
+fn read_tasks(data_dir: &Path) -> Vec<ExportTask> {
+    fs::read_to_string(exports_path(data_dir))
+        .ok()
+        .and_then(|content| serde_json::from_str::<ExportsFile>(&content).ok())
+        .map(|file| file.tasks)
+        .unwrap_or_default()
+}
+
+fn write_tasks(data_dir: &Path, tasks: Vec<ExportTask>) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(&ExportsFile { tasks }).map_err(|e| e.to_string())?;
+    fs::write(exports_path(data_dir), json).map_err(|e| format!("Failed to save export tasks: {}", e))
+}
+
+fn update_tasks<T>(data_dir: &Path, f: impl FnOnce(&mut Vec<ExportTask>) -> Result<T, String>) -> Result<T, String> {
+    let _guard = TASKS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut tasks = read_tasks(data_dir);
+    let result = f(&mut tasks)?;
+    write_tasks(data_dir, tasks)?;
+    Ok(result)
+}
+
+/// The scheduled tasks for a settings transfer, with the keychain entries they use
+pub fn transfer_tasks(data_dir: &Path) -> Result<(Value, Vec<String>), String> {
+    let tasks = read_tasks(data_dir);
+    let keys = secret_keys(&tasks);
+    let tasks = serde_json::to_value(tasks).map_err(|e| e.to_string())?;
+    Ok((tasks, keys))
+}
+
+/// Read transferred tasks without storing them
+pub fn check_tasks(tasks: Value) -> Result<Vec<ExportTask>, String> {
+    let tasks: Vec<ExportTask> = serde_json::from_value(tasks).map_err(|e| format!("Invalid export tasks: {}", e))?;
+    for (i, task) in tasks.iter().enumerate() {
+        if task.id.is_empty() || tasks[..i].iter().any(|t| t.id == task.id) {
+            return Err(format!("Invalid export tasks: bad task id \"{}\"", task.id));
+        }
+    }
+    Ok(tasks)
+}
+
+/// Keychain entries the tasks' passwords are kept under
+pub fn secret_keys(tasks: &[ExportTask]) -> Vec<String> {
+    tasks.iter().map(|task| webdav_key(&task.id)).collect()
+}
+
+/// Replace the scheduled tasks with transferred ones, returning how many there are
+pub fn receive_tasks(data_dir: &Path, received: Vec<ExportTask>) -> Result<usize, String> {
+    update_tasks(data_dir, |tasks| {
+        *tasks = received;
+        Ok(tasks.len())
+    })
+}
+
+fn new_task_id() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    format!("{:x}", nanos)
+}
+
+/// Forget a task's WebDAV password (a keychain problem shouldn't block saving)
+fn forget_webdav_password(task_id: &str) {
+    if let Err(e) = keychain::delete_secret(&webdav_key(task_id)) {
+        eprintln!("Warning: {}", e);
+    }
+}
+
+fn webdav_key(task_id: &str) -> String {
+    format!("export-webdav-{}", task_id)
+}
+
+/// Where report-only tasks without a destination write: Documents/Moneywright/Archive
+fn archive_dir() -> Option<PathBuf> {
+    dirs::document_dir().map(|dir| dir.join("Moneywright").join("Archive"))
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+}
+
+fn validate(input: &ExportTaskInput) -> Result<(), String> {
+    if input.name.trim().is_empty() {
+        return Err("Name is required".to_string());
+    }
+    if input.datasets.is_empty() && input.reports.is_empty() {
+        return Err("Choose at least one dataset or report".to_string());
+    }
+    if let Some(unknown) = input.datasets.iter().find(|d| !DATASETS.contains(&d.as_str())) {
+        return Err(format!("Unknown dataset: {}", unknown));
+    }
+    if let Some(unknown) = input.reports.iter().find(|r| !REPORT_PAGES.iter().any(|(name, _)| name == r)) {
+        return Err(format!("Unknown report: {}", unknown));
+    }
+    match input.monthly_day {
+        Some(day) if !(1..=28).contains(&day) => return Err("Day of the month must be between 1 and 28".to_string()),
+        Some(_) => {}
+        None if !(1..=MAX_INTERVAL_HOURS).contains(&input.interval_hours) => {
+            return Err(format!("Interval must be between 1 and {} hours", MAX_INTERVAL_HOURS));
+        }
+        None => {}
+    }
+    let directory = input.directory.as_deref().map(str::trim).filter(|d| !d.is_empty());
+    let webdav = input.webdav_url.as_deref().map(str::trim).filter(|u| !u.is_empty());
+    // Reports alone go to the Archive folder by default
+    if directory.is_none() && webdav.is_none() && (!input.datasets.is_empty() || archive_dir().is_none()) {
+        return Err("Choose a folder or a WebDAV URL".to_string());
+    }
+    if let Some(dir) = directory {
+        if !Path::new(dir).is_absolute() {
+            return Err("Export folder must be an absolute path".to_string());
+        }
+    }
+    if let Some(url) = webdav {
+        let parsed = Url::parse(url).map_err(|e| format!("Invalid WebDAV URL: {}", e))?;
+        // Plain http would send the password in the clear, unless the server is on this computer
+        let allowed = match parsed.scheme() {
+            "https" => true,
+            "http" => is_loopback(&parsed),
+            _ => false,
+        };
+        if !allowed || parsed.host_str().is_none() {
+            return Err("WebDAV URL must start with https:// (http:// only for localhost)".to_string());
+        }
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Fetching and rendering
+// ---------------------------------------------------------------------------
+
+async fn get_json(client: &reqwest::Client, cookies: &str, path: &str, query: &[(&str, String)]) -> Result<Value, String> {
+    let request = client
+        .get(format!("{}{}", get_server_url(), path))
+        .header(reqwest::header::COOKIE, cookies)
+        .query(query);
+    let response = transport::send(request)
+        .await
+        .map_err(|e| format!("Request to {} failed: {}", path, e))?;
+    let status = response.status();
+    let body: Value = response.json().await.unwrap_or_default();
+    if status.is_success() {
+        Ok(body)
+    } else {
+        Err(body["message"]
+            .as_str()
+            .map(String::from)
+            .unwrap_or_else(|| format!("{} returned {}", path, status)))
+    }
+}
+
+fn profile_query(task: &ExportTask) -> Vec<(&'static str, String)> {
+    task.profile_id.iter().map(|id| ("profileId", id.clone())).collect()
+}
+
+async fn fetch_transactions(
+    client: &reqwest::Client,
+    cookies: &str,
+    task: &ExportTask,
+    job: &JobHandle<tauri::Wry>,
+) -> Result<Vec<Value>, String> {
+    let mut transactions = Vec::new();
+    for page in 1.. {
+        if job.is_cancelled() {
+            return Err("Export cancelled".to_string());
+        }
+        let mut query = profile_query(task);
+        query.extend([
+            ("page", page.to_string()),
+            ("limit", PAGE_SIZE.to_string()),
+            ("sortBy", "date".to_string()),
+            ("sortOrder", "asc".to_string()),
+            ("includeHidden", "true".to_string()),
+        ]);
+        let body = get_json(client, cookies, "/api/transactions", &query).await?;
+        let batch = body["transactions"].as_array().cloned().unwrap_or_default();
+        let total = body["total"].as_u64().unwrap_or(0) as usize;
+        let done = batch.len() < PAGE_SIZE;
+        transactions.extend(batch);
+        if total > 0 {
+            job.progress(Some(transactions.len() as f64 / total as f64), None);
+        }
+        if done || transactions.len() >= total {
+            break;
+        }
+    }
+    Ok(transactions)
+}
+
+fn csv_bytes(headers: &[&str], rows: Vec<Vec<String>>) -> Result<Vec<u8>, String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(headers).map_err(|e| e.to_string())?;
+    for row in rows {
+        writer.write_record(&row).map_err(|e| e.to_string())?;
+    }
+    writer.into_inner().map_err(|e| e.to_string())
+}
+
+/// Plain text for a CSV cell (strings unquoted, null empty)
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn render_transactions(transactions: &[Value], format: ExportFormat) -> Result<Vec<u8>, String> {
+    if format == ExportFormat::Json {
+        return serde_json::to_vec_pretty(transactions).map_err(|e| e.to_string());
+    }
+    const COLUMNS: &[&str] = &[
+        "date", "type", "amount", "currency", "originalDescription", "summary", "category",
+        "accountId", "profileId", "isSubscription", "isHidden", "id",
+    ];
+    let rows = transactions
+        .iter()
+        .map(|t| COLUMNS.iter().map(|c| cell(&t[*c])).collect())
+        .collect();
+    csv_bytes(COLUMNS, rows)
+}
+
+fn render_net_worth(summary: &Value, format: ExportFormat) -> Result<Vec<u8>, String> {
+    let net_worth = &summary["netWorth"];
+    if format == ExportFormat::Json {
+        return serde_json::to_vec_pretty(net_worth).map_err(|e| e.to_string());
+    }
+    let breakdown = &net_worth["breakdown"];
+    let rows = [
+        ("total", &net_worth["total"]),
+        ("total_assets", &net_worth["totalAssets"]),
+        ("total_liabilities", &net_worth["totalLiabilities"]),
+        ("cash", &breakdown["cash"]["total"]),
+        ("investments", &breakdown["investments"]["total"]),
+        ("credit_cards", &breakdown["liabilities"]["creditCards"]["total"]),
+        ("loans", &breakdown["liabilities"]["loans"]["total"]),
+        ("currency", &net_worth["currency"]),
+        ("calculated_at", &net_worth["calculatedAt"]),
+    ]
+    .iter()
+    .map(|(name, value)| vec![name.to_string(), cell(value)])
+    .collect();
+    csv_bytes(&["metric", "value"], rows)
+}
+
+fn render_trends(body: &Value, format: ExportFormat) -> Result<Vec<u8>, String> {
+    if format == ExportFormat::Json {
+        return serde_json::to_vec_pretty(body).map_err(|e| e.to_string());
+    }
+    const COLUMNS: &[&str] = &["month", "income", "expenses", "net", "hasFullData"];
+    let currency = cell(&body["currency"]);
+    let rows = body["trends"]
+        .as_array()
+        .map(|trends| {
+            trends
+                .iter()
+                .map(|t| {
+                    let mut row: Vec<String> = COLUMNS.iter().map(|c| cell(&t[*c])).collect();
+                    row.push(currency.clone());
+                    row
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    csv_bytes(&["month", "income", "expenses", "net", "has_full_data", "currency"], rows)
+}
+
+async fn fetch_dataset(
+    client: &reqwest::Client,
+    cookies: &str,
+    task: &ExportTask,
+    dataset: &str,
+    job: &JobHandle<tauri::Wry>,
+) -> Result<Vec<u8>, String> {
+    match dataset {
+        "transactions" => {
+            let transactions = fetch_transactions(client, cookies, task, job).await?;
+            render_transactions(&transactions, task.format)
+        }
+        "net_worth" => {
+            let summary = get_json(client, cookies, "/api/summary", &profile_query(task)).await?;
+            render_net_worth(&summary, task.format)
+        }
+        "monthly_trends" => {
+            let mut query = profile_query(task);
+            query.push(("months", "120".to_string()));
+            let trends = get_json(client, cookies, "/api/summary/monthly-trends", &query).await?;
+            render_trends(&trends, task.format)
+        }
+        other => Err(format!("Unknown dataset: {}", other)),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Targets
+// ---------------------------------------------------------------------------
+
+/// Write via a temporary file so a failed or cancelled run never leaves a partial export
+fn write_local(dir: &Path, file_name: &str, bytes: &[u8]) -> Result<PathBuf, String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let target = dir.join(file_name);
+    let staging = dir.join(format!(".{}.tmp", file_name));
+    fs::write(&staging, bytes).map_err(|e| format!("Failed to write {}: {}", target.display(), e))?;
+    fs::rename(&staging, &target).map_err(|e| {
+        let _ = fs::remove_file(&staging);
+        format!("Failed to write {}: {}", target.display(), e)
+    })?;
+    Ok(target)
+}
+
+async fn upload_webdav(
+    client: &reqwest::Client,
+    task: &ExportTask,
+    url: &str,
+    file_name: &str,
+    bytes: Vec<u8>,
+) -> Result<String, String> {
+    let target = format!("{}/{}", url.trim_end_matches('/'), file_name);
+    let mut request = client.put(&target).body(bytes);
+    if let Some(user) = &task.webdav_user {
+        let password = keychain::get_secret(&webdav_key(&task.id))?;
+        request = request.basic_auth(user, password);
+    }
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("WebDAV upload failed: {}", e))?;
+    if response.status().is_success() {
+        Ok(target)
+    } else {
+        Err(format!("WebDAV upload to {} returned {}", target, response.status()))
+    }
+}
+
+/// Run one task as a job, returning the written files/URLs
+async fn run_task(app: &AppHandle, task: &ExportTask) -> Result<Vec<String>, String> {
+    let job = start_job(app, JobKind::Export, format!("Exporting {}", task.name), true);
+    let result = export(app, task, &job).await;
+    job.finish(&result);
+    result
+}
+
+async fn export(app: &AppHandle, task: &ExportTask, job: &JobHandle<tauri::Wry>) -> Result<Vec<String>, String> {
+    let cookies = session_cookies(app)?;
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let date = chrono::Local::now().format("%Y-%m-%d");
+
+    // Fetch everything first, so a cancelled run writes nothing
+    let mut files = Vec::new();
+    for dataset in &task.datasets {
+        job.progress(None, Some(dataset.clone()));
+        let bytes = fetch_dataset(&client, &cookies, task, dataset, job).await?;
+        let file_name = format!("moneywright-{}-{}.{}", dataset.replace('_', "-"), date, task.format.extension());
+        files.push((file_name, bytes));
+    }
+    for report in &task.reports {
+        let Some((name, route)) = REPORT_PAGES.iter().find(|(name, _)| name == report) else {
+            continue;
+        };
+        if job.is_cancelled() {
+            return Err("Export cancelled".to_string());
+        }
+        job.progress(None, Some(format!("{} report", name)));
+        let bytes = render_pdf(app, route).await?;
+        files.push((format!("moneywright-report-{}-{}.pdf", name, date), bytes));
+    }
+    if job.is_cancelled() {
+        return Err("Export cancelled".to_string());
+    }
+
+    let mut written = Vec::new();
+    for (file_name, bytes) in files {
+        if let Some(dir) = &task.directory {
+            let path = write_local(Path::new(dir), &file_name, &bytes)?;
+            written.push(path.to_string_lossy().to_string());
+        }
+        if let Some(url) = &task.webdav_url {
+            written.push(upload_webdav(&client, task, url, &file_name, bytes).await?);
+        }
+    }
+    Ok(written)
+}
+
+/// Run a task and record the outcome on it
+async fn run_and_record(app: &AppHandle, data_dir: &Path, task: &ExportTask) -> Result<Vec<String>, String> {
+    let result = run_task(app, task).await;
+    let log_store = app.state::<SharedLogStore>().inner().clone();
+    let msg = match &result {
+        Ok(files) => format!("Export \"{}\" wrote {} file(s)", task.name, files.len()),
+        Err(e) => format!("Export \"{}\" failed: {}", task.name, e),
+    };
+    log_line(app, &log_store, msg, if result.is_ok() { "info" } else { "error" }).await;
+
+    record_run(data_dir, &task.id, unix_now(), result.as_ref().err().cloned())?;
+    result
+}
+
+fn record_run(data_dir: &Path, id: &str, last_run: u64, last_error: Option<String>) -> Result<(), String> {
+    update_tasks(data_dir, |tasks| {
+        if let Some(stored) = tasks.iter_mut().find(|t| t.id == id) {
+            stored.last_run = Some(last_run);
+            stored.last_error = last_error;
+        }
+        Ok(())
+    })
+}
+
+/// Run due export tasks in the background, catching up on runs missed while
+/// asleep or closed (see scheduler.rs)
+pub fn start_export_scheduler(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut catch_up = CatchUp::default();
+        let mut deferred = HashSet::new();
+        loop {
+            tokio::time::sleep(CHECK_INTERVAL).await;
+
+            let manager = app.state::<SharedServerManager>().inner().clone();
+            let (data_dir, running) = {
+                let mgr = manager.lock().await;
+                (mgr.data_dir().clone(), mgr.is_running())
+            };
+            // Tasks stay due and run once the server is back
+            if !running && cfg!(not(debug_assertions)) {
+                continue;
+            }
+
+            // Outside the maintenance window or on battery, due tasks wait
+            let deferral = heavy_work_deferral(&app.state::<SharedSettings>().lock().await.get());
+            if deferral.is_none() {
+                deferred.clear();
+            }
+
+            for task in read_tasks(&data_dir) {
+                let now = unix_now();
+                if !task.enabled {
+                    catch_up.ready(&task.id, Due::No, now);
+                    continue;
+                }
+                // A last run in the future means the clock went back
+                if task.last_run.is_some_and(|t| t > now) {
+                    let _ = record_run(&data_dir, &task.id, now, task.last_error.clone());
+                    continue;
+                }
+                let due = match task.monthly_day {
+                    Some(day) => monthly_due(task.last_run, day, now),
+                    None => interval_due(task.last_run, task.interval_hours as u64 * 3600, now),
+                };
+                match due {
+                    Due::Stale(at) => {
+                        let msg = format!(
+                            "Skipped the run due at {}: missed by more than {}",
+                            format_local(at),
+                            describe_delay(MAX_CATCH_UP.as_secs())
+                        );
+                        let log_store = app.state::<SharedLogStore>().inner().clone();
+                        log_line(&app, &log_store, format!("Export \"{}\": {}", task.name, msg), "info").await;
+                        let _ = record_run(&data_dir, &task.id, at, Some(msg));
+                    }
+                    Due::OnTime(_) | Due::Late(_) if deferral.is_some() => {
+                        if deferred.insert(task.id.clone()) {
+                            let reason = deferral.as_deref().unwrap_or_default();
+                            let log_store = app.state::<SharedLogStore>().inner().clone();
+                            log_line(&app, &log_store, format!("Export \"{}\" deferred: {}", task.name, reason), "info").await;
+                        }
+                    }
+                    due => {
+                        if catch_up.ready(&task.id, due, now) {
+                            let _ = history::scheduled(run_and_record(&app, &data_dir, &task)).await;
+                        }
+                    }
+                }
+            }
+        }
+    });
+}
+
+// ---------------------------------------------------------------------------
+// Commands
+// ---------------------------------------------------------------------------
+
+/// List scheduled export tasks
+#[tauri::command]
+pub async fn list_export_tasks(manager: tauri::State<'_, SharedServerManager>) -> Result<Vec<ExportTask>, String> {
+    let data_dir = manager.lock().await.data_dir().clone();
+    Ok(read_tasks(&data_dir))
+}
+
+/// Create or update an export task
+#[tauri::command]
+pub async fn save_export_task(
+    manager: tauri::State<'_, SharedServerManager>,
+    task: ExportTaskInput,
+) -> Result<ExportTask, String> {
+    validate(&task)?;
+    let data_dir = manager.lock().await.data_dir().clone();
+    let id = task.id.clone().unwrap_or_else(new_task_id);
+
+    let webdav_url = non_empty(task.webdav_url);
+    let directory = non_empty(task.directory).or_else(|| {
+        webdav_url
+            .is_none()
+            .then(|| archive_dir().map(|dir| dir.to_string_lossy().to_string()))
+            .flatten()
+    });
+    let webdav_user = non_empty(task.webdav_user).filter(|_| webdav_url.is_some());
+    match (&webdav_user, non_empty(task.webdav_password)) {
+        (Some(_), Some(password)) => keychain::set_secret(&webdav_key(&id), &password)?,
+        (None, _) => forget_webdav_password(&id),
+        _ => {}
+    }
+
+    update_tasks(&data_dir, |tasks| {
+        let existing = tasks.iter().position(|t| t.id == id);
+        let previous = existing.map(|i| tasks[i].clone());
+        let saved = ExportTask {
+            id: id.clone(),
+            name: task.name.trim().to_string(),
+            enabled: task.enabled,
+            datasets: task.datasets,
+            format: task.format,
+            interval_hours: task.interval_hours,
+            reports: task.reports,
+            monthly_day: task.monthly_day,
+            directory,
+            webdav_url,
+            webdav_user,
+            profile_id: non_empty(task.profile_id),
+            last_run: previous.as_ref().and_then(|p| p.last_run),
+            last_error: previous.and_then(|p| p.last_error),
+        };
+        match existing {
+            Some(i) => tasks[i] = saved.clone(),
+            None => tasks.push(saved.clone()),
+        }
+        Ok(saved)
+    })
+}
+
+/// Delete an export task (files already written are kept)
+#[tauri::command]
+pub async fn delete_export_task(manager: tauri::State<'_, SharedServerManager>, id: String) -> Result<(), String> {
+    let data_dir = manager.lock().await.data_dir().clone();
+    update_tasks(&data_dir, |tasks| {
+        tasks.retain(|t| t.id != id);
+        Ok(())
+    })?;
+    forget_webdav_password(&id);
+    Ok(())
+}
+
+/// Run an export task now
+#[tauri::command]
+pub async fn run_export_task(
+    app: AppHandle,
+    manager: tauri::State<'_, SharedServerManager>,
+    id: String,
+) -> Result<Vec<String>, String> {
+    let data_dir = manager.lock().await.data_dir().clone();
+    let task = read_tasks(&data_dir)
+        .into_iter()
+        .find(|t| t.id == id)
+        .ok_or_else(|| format!("No export task with id {}", id))?;
+    run_and_record(&app, &data_dir, &task).await
+}
+
+/// Open the scheduled exports window
+pub fn open_exports_window(app: &AppHandle) {
+    // Static UI; task names and paths are inserted with escaping on the JS side
+    let script = r#"
+        const tauriApi = window.__TAURI__;
+
+        document.documentElement.innerHTML = `
+<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <title>Scheduled Exports</title>
+    <style>
+        __BASE_STYLE__
+        #content { flex: 1; overflow-y: auto; padding: 12px 16px; }
+        table { width: 100%; border-collapse: collapse; margin-bottom: 16px; }
+        th, td { text-align: left; padding: 6px 8px 6px 0; border-bottom: 1px solid rgba(255, 255, 255, 0.04); }
+        th { color: #71717a; font-weight: 500; }
+        td.actions { text-align: right; white-space: nowrap; }
+        td.actions button { padding: 3px 8px; font-size: 12px; }
+        form { display: none; grid-template-columns: 130px 1fr; gap: 8px 12px; align-items: center; }
+        form input[type=text], form input[type=password], form input[type=number], form select { width: 100%; }
+        .checks { display: flex; gap: 16px; flex-wrap: wrap; }
+        .checks label { display: flex; align-items: center; gap: 6px; cursor: pointer; }
+        .buttons { grid-column: 2; display: flex; gap: 8px; }
+    </style>
+</head>
+<body>
+    <div class="toolbar">
+        <button id="newBtn" class="primary">New Export</button>
+        <span id="status" class="muted" role="status" style="margin-left: auto"></span>
+    </div>
+    <div id="content">
+        <table aria-label="Export tasks">
+            <thead><tr><th>Name</th><th>Every</th><th>Destination</th><th>Last run</th><th></th></tr></thead>
+            <tbody id="tasks"></tbody>
+        </table>
+        <form id="form" aria-label="Export task">
+            <label for="name">Name</label><input type="text" id="name">
+            <span>Data</span>
+            <div class="checks">
+                <label><input type="checkbox" class="dataset" value="transactions"> Transactions</label>
+                <label><input type="checkbox" class="dataset" value="net_worth"> Net worth</label>
+                <label><input type="checkbox" class="dataset" value="monthly_trends"> Monthly trends</label>
+            </div>
+            <label for="format">Format</label>
+            <select id="format"><option value="csv">CSV</option><option value="json">JSON</option></select>
+            <span>Reports (PDF)</span>
+            <div class="checks">
+                <label><input type="checkbox" class="report" value="dashboard"> Dashboard</label>
+                <label><input type="checkbox" class="report" value="accounts"> Accounts</label>
+                <label><input type="checkbox" class="report" value="transactions"> Transactions</label>
+                <label><input type="checkbox" class="report" value="investments"> Investments</label>
+                <label><input type="checkbox" class="report" value="loans"> Loans</label>
+                <label><input type="checkbox" class="report" value="insurance"> Insurance</label>
+                <label><input type="checkbox" class="report" value="subscriptions"> Subscriptions</label>
+            </div>
+            <label for="schedule">Schedule</label>
+            <select id="schedule"><option value="interval">Every few hours</option><option value="monthly">Monthly</option></select>
+            <label for="interval" id="intervalLabel">Every (hours)</label><input type="number" id="interval" min="1" max="744">
+            <label for="monthlyDay" id="monthlyDayLabel">Day of month</label><input type="number" id="monthlyDay" min="1" max="28">
+            <label for="profile">Profile</label><select id="profile"><option value="">All profiles</option></select>
+            <label for="directory">Folder</label><input type="text" id="directory" placeholder="Documents/Moneywright/Archive if only reports are chosen">
+            <label for="webdavUrl">WebDAV URL</label><input type="text" id="webdavUrl" placeholder="https://cloud.example.com/remote.php/dav/files/me/exports">
+            <label for="webdavUser">WebDAV user</label><input type="text" id="webdavUser" autocomplete="off">
+            <label for="webdavPassword">WebDAV password</label><input type="password" id="webdavPassword" autocomplete="off">
+            <span></span>
+            <div class="checks"><label><input type="checkbox" id="enabled" checked> Enabled</label></div>
+            <div class="buttons"><button type="submit" class="primary">Save</button><button type="button" id="cancelBtn">Cancel</button></div>
+        </form>
+    </div>
+</body>
+</html>`;
+
+        const $ = id => document.getElementById(id);
+        let tasks = [];
+        let editing = null;
+
+        function setStatus(text, cls) {
+            $('status').className = cls || 'muted';
+            $('status').textContent = text;
+        }
+
+        function lastRun(t) {
+            if (t.last_error) return '<span class="fail" title="' + escapeHtml(t.last_error) + '">Failed</span>';
+            return t.last_run ? new Date(t.last_run * 1000).toLocaleString() : '<span class="muted">Never</span>';
+        }
+
+        async function refresh() {
+            tasks = await tauriApi.core.invoke('list_export_tasks');
+            $('tasks').innerHTML = tasks.length === 0
+                ? '<tr><td class="muted" colspan="5">No scheduled exports</td></tr>'
+                : tasks.map((t, i) =>
+                    '<tr><td>' + escapeHtml(t.name) + (t.enabled ? '' : ' <span class="muted">(off)</span>') + '</td>' +
+                    '<td>' + (t.monthly_day ? 'Monthly, day ' + t.monthly_day : t.interval_hours + ' h') + '</td>' +
+                    '<td class="mono">' + escapeHtml([t.directory, t.webdav_url].filter(Boolean).join(', ')) + '</td>' +
+                    '<td>' + lastRun(t) + '</td>' +
+                    '<td class="actions"><button data-action="run" data-index="' + i + '">Run Now</button> ' +
+                    '<button data-action="edit" data-index="' + i + '">Edit</button> ' +
+                    '<button data-action="delete" data-index="' + i + '" class="danger">Delete</button></td></tr>'
+                ).join('');
+        }
+
+        function updateSchedule() {
+            const monthly = $('schedule').value === 'monthly';
+            $('interval').style.display = $('intervalLabel').style.display = monthly ? 'none' : '';
+            $('monthlyDay').style.display = $('monthlyDayLabel').style.display = monthly ? '' : 'none';
+        }
+
+        function edit(task) {
+            editing = task ? task.id : null;
+            $('name').value = task ? task.name : 'Weekly export';
+            document.querySelectorAll('.dataset').forEach(c => c.checked = task ? task.datasets.includes(c.value) : c.value === 'transactions');
+            $('format').value = task ? task.format : 'csv';
+            document.querySelectorAll('.report').forEach(c => c.checked = task ? (task.reports || []).includes(c.value) : false);
+            $('schedule').value = task && task.monthly_day ? 'monthly' : 'interval';
+            $('interval').value = task ? task.interval_hours : 168;
+            $('monthlyDay').value = task && task.monthly_day ? task.monthly_day : 1;
+            updateSchedule();
+            $('profile').value = task && task.profile_id ? task.profile_id : '';
+            $('directory').value = task && task.directory || '';
+            $('webdavUrl').value = task && task.webdav_url || '';
+            $('webdavUser').value = task && task.webdav_user || '';
+            $('webdavPassword').value = '';
+            $('webdavPassword').placeholder = task && task.webdav_user ? 'Saved in keychain (leave blank to keep)' : '';
+            $('enabled').checked = task ? task.enabled : true;
+            $('form').style.display = 'grid';
+        }
+
+        $('form').onsubmit = async (e) => {
+            e.preventDefault();
+            const task = {
+                id: editing,
+                name: $('name').value,
+                enabled: $('enabled').checked,
+                datasets: Array.from(document.querySelectorAll('.dataset')).filter(c => c.checked).map(c => c.value),
+                format: $('format').value,
+                interval_hours: parseInt($('interval').value, 10) || 0,
+                reports: Array.from(document.querySelectorAll('.report')).filter(c => c.checked).map(c => c.value),
+                monthly_day: $('schedule').value === 'monthly' ? (parseInt($('monthlyDay').value, 10) || 0) : null,
+                directory: $('directory').value || null,
+                webdav_url: $('webdavUrl').value || null,
+                webdav_user: $('webdavUser').value || null,
+                webdav_password: $('webdavPassword').value || null,
+                profile_id: $('profile').value || null,
+            };
+            try {
+                await tauriApi.core.invoke('save_export_task', { task });
+                $('form').style.display = 'none';
+                setStatus('Saved', 'pass');
+                refresh();
+            } catch (err) {
+                setStatus(String(err), 'fail');
+            }
+        };
+
+        $('tasks').onclick = async (e) => {
+            const btn = e.target.closest('button');
+            if (!btn) return;
+            const task = tasks[Number(btn.dataset.index)];
+            if (btn.dataset.action === 'edit') return edit(task);
+            if (btn.dataset.action === 'delete' && !btn.dataset.armed) {
+                // Second click confirms
+                btn.dataset.armed = '1';
+                btn.textContent = 'Confirm';
+                return;
+            }
+            setStatus(btn.dataset.action === 'run' ? 'Exporting...' : 'Deleting...');
+            try {
+                if (btn.dataset.action === 'run') {
+                    const files = await tauriApi.core.invoke('run_export_task', { id: task.id });
+                    setStatus('Wrote ' + files.length + ' file(s)', 'pass');
+                } else {
+                    await tauriApi.core.invoke('delete_export_task', { id: task.id });
+                    setStatus('Deleted', 'pass');
+                }
+            } catch (err) {
+                setStatus(String(err), 'fail');
+            }
+            refresh();
+        };
+
+        $('schedule').onchange = updateSchedule;
+        $('newBtn').onclick = () => edit(null);
+        $('cancelBtn').onclick = () => { $('form').style.display = 'none'; };
+        tauriApi.core.invoke('list_import_profiles').then(profiles => {
+            (profiles || []).forEach(p => {
+                const option = document.createElement('option');
+                option.value = p.id;
+                option.textContent = p.name;
+                $('profile').appendChild(option);
+            });
+        }).catch(() => {});
+        refresh();
+    "#;
+
+    open_injected_window(app, "exports", "Scheduled Exports", (760.0, 600.0), true, script);
+}
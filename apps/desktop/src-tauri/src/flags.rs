@@ -0,0 +1,166 @@
+// Feature flags gating experimental subsystems behind explicit opt-ins
+//
+// Flags are stored in the `[features]` section of settings.toml. A local
+// `feature-overrides.toml` in the data dir (never fetched remotely) can force
+// a flag on or off, e.g. when support asks a user to disable something.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::Serialize;
+use serde_json::json;
+use tauri::AppHandle;
+use crate::events::{self, Event};
+use crate::server::SharedServerManager;
+use crate::settings::{FeatureSettings, SharedSettings};
+
+const OVERRIDES_FILE: &str = "feature-overrides.toml";
+const AUDIT_FILE: &str = "feature-flags.log";
+
+pub struct FlagDef {
+    pub key: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+}
+
+/// All known flags; keys match the fields of `FeatureSettings`
+pub const FLAGS: &[FlagDef] = &[
+    FlagDef {
+        key: "local_ai",
+        name: "Local AI",
+        description: "Categorize and analyze transactions with a locally running model",
+    },
+    FlagDef {
+        key: "lan_mode",
+        name: "LAN mode",
+        description: "Make the server reachable from other devices on the local network",
+    },
+    FlagDef {
+        key: "mail_ingestion",
+        name: "Mail ingestion",
+        description: "Import statements forwarded to a watched mailbox",
+    },
+];
+
+#[derive(Clone, Serialize)]
+pub struct FeatureFlag {
+    pub key: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub enabled: bool,
+    /// "default", "settings" or "override"
+    pub source: &'static str,
+}
+
+fn setting_value(features: &FeatureSettings, key: &str) -> Option<bool> {
+    match key {
+        "local_ai" => Some(features.local_ai),
+        "lan_mode" => Some(features.lan_mode),
+        "mail_ingestion" => Some(features.mail_ingestion),
+        _ => None,
+    }
+}
+
+/// Read the local overrides file (missing or invalid files mean no overrides)
+fn read_overrides(data_dir: &Path) -> toml::Table {
+    fs::read_to_string(data_dir.join(OVERRIDES_FILE))
+        .ok()
+        .and_then(|c| c.parse::<toml::Table>().ok())
+        .unwrap_or_default()
+}
+
+/// Resolve every flag from settings and local overrides
+pub fn resolve_flags(features: &FeatureSettings, data_dir: &Path) -> Vec<FeatureFlag> {
+    let overrides = read_overrides(data_dir);
+    let defaults = FeatureSettings::default();
+
+    FLAGS
+        .iter()
+        .map(|def| {
+            let configured = setting_value(features, def.key).unwrap_or(false);
+            let (enabled, source) = match overrides.get(def.key).and_then(|v| v.as_bool()) {
+                Some(forced) => (forced, "override"),
+                None if Some(configured) != setting_value(&defaults, def.key) => (configured, "settings"),
+                None => (configured, "default"),
+            };
+            FeatureFlag {
+                key: def.key,
+                name: def.name,
+                description: def.description,
+                enabled,
+                source,
+            }
+        })
+        .collect()
+}
+
+/// Keys of all enabled flags, passed to the server so it can gate the same features
+pub fn enabled_flag_keys(features: &FeatureSettings, data_dir: &Path) -> Vec<&'static str> {
+    resolve_flags(features, data_dir)
+        .into_iter()
+        .filter(|f| f.enabled)
+        .map(|f| f.key)
+        .collect()
+}
+
+/// Append a change to the audit log (one JSON object per line)
+fn append_audit(data_dir: &Path, key: &str, previous: bool, enabled: bool) -> Result<(), String> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let entry = json!({
+        "timestamp": timestamp,
+        "flag": key,
+        "previous": previous,
+        "enabled": enabled,
+        "version": env!("CARGO_PKG_VERSION"),
+    });
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(data_dir.join(AUDIT_FILE))
+        .map_err(|e| format!("Failed to open flag audit log: {}", e))?;
+    writeln!(file, "{}", entry).map_err(|e| format!("Failed to write flag audit log: {}", e))
+}
+
+/// List all feature flags with their effective state
+#[tauri::command]
+pub async fn list_feature_flags(
+    settings: tauri::State<'_, SharedSettings>,
+    manager: tauri::State<'_, SharedServerManager>,
+) -> Result<Vec<FeatureFlag>, String> {
+    let data_dir = manager.lock().await.data_dir().clone();
+    let features = settings.lock().await.get().features;
+    Ok(resolve_flags(&features, &data_dir))
+}
+
+/// Opt in to (or out of) an experimental feature
+/// Takes effect for the server on its next restart
+#[tauri::command]
+pub async fn set_feature_flag(
+    app: AppHandle,
+    settings: tauri::State<'_, SharedSettings>,
+    manager: tauri::State<'_, SharedServerManager>,
+    key: String,
+    enabled: bool,
+) -> Result<Vec<FeatureFlag>, String> {
+    let data_dir = manager.lock().await.data_dir().clone();
+    let store = settings.lock().await;
+
+    let previous = setting_value(&store.get().features, &key)
+        .ok_or_else(|| format!("Unknown feature flag: {}", key))?;
+    if previous == enabled {
+        return Ok(resolve_flags(&store.get().features, &data_dir));
+    }
+
+    let updated = store.update(&json!({ "features": { key.as_str(): enabled } }))?;
+    drop(store);
+
+    append_audit(&data_dir, &key, previous, enabled)?;
+    let _ = events::emit(&app, Event::SettingsChanged(&updated));
+
+    Ok(resolve_flags(&updated.features, &data_dir))
+}
@@ -0,0 +1,362 @@
+// Offline currency conversion: cached FX rates and the quick converter popover
+//
+// Rates come from the same source the server uses (fawazahmed0/currency-api, USD
+// based) and are kept in fx_rates.json in the data dir, refreshed in the background
+// every few hours while online. Conversion only reads the cache, so the converter
+// keeps working offline with the last rates, showing their date.
+//
+// The converter lists the user's own currencies first: those of their accounts and
+// holdings, read from the SQLite database (PostgreSQL setups get the full list).
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use rusqlite::{Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use crate::backup::sqlite_db_path;
+use crate::logs::{log_line, SharedLogStore};
+use crate::netusage::{self, Subsystem};
+use crate::server::{read_database_url, SharedServerManager};
+use crate::windows::open_injected_window;
+
+const FX_FILE: &str = "fx_rates.json";
+const RATE_URLS: &[&str] = &[
+    "https://cdn.jsdelivr.net/npm/@fawazahmed0/currency-api@latest/v1/currencies/usd.json",
+    "https://latest.currency-api.pages.dev/v1/currencies/usd.json",
+];
+/// Rates older than this are refreshed
+const MAX_AGE: Duration = Duration::from_secs(12 * 3600);
+/// How often the cache age is checked
+const CHECK_INTERVAL: Duration = Duration::from_secs(3600);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
+const WINDOW_LABEL: &str = "converter";
+
+#[derive(Clone, Serialize, Deserialize)]
+struct RatesFile {
+    /// Day the rates are for, as published
+    date: String,
+    /// Unix time they were downloaded
+    fetched_at: u64,
+    /// Units per US dollar, keyed by lowercase code
+    rates: BTreeMap<String, f64>,
+}
+
+#[derive(Serialize)]
+pub struct FxInfo {
+    date: Option<String>,
+    fetched_at: Option<u64>,
+    /// The user's currencies, most used first (uppercase codes)
+    own: Vec<String>,
+    /// Every currency with a rate (uppercase codes)
+    all: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct Conversion {
+    amount: f64,
+    /// Units of `to` per unit of `from`
+    rate: f64,
+    /// Day of the rates used
+    date: String,
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn rates_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(FX_FILE)
+}
+
+fn read_rates(data_dir: &Path) -> Option<RatesFile> {
+    let content = fs::read_to_string(rates_path(data_dir)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Download the latest rates, trying the fallback mirror if the first fails
+async fn fetch_rates() -> Result<RatesFile, String> {
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let mut last_error = String::new();
+    for url in RATE_URLS {
+        let response = match client.get(*url).send().await {
+            Ok(response) if response.status().is_success() => response,
+            Ok(response) => {
+                last_error = format!("{} returned {}", url, response.status());
+                continue;
+            }
+            Err(e) => {
+                last_error = e.to_string();
+                continue;
+            }
+        };
+        // { "date": "2024-01-20", "usd": { "eur": 0.92, ... } }
+        let bytes = response.bytes().await.map_err(|e| e.to_string())?;
+        netusage::record(Subsystem::ExchangeRates, 0, bytes.len() as u64);
+        let body: serde_json::Value = serde_json::from_slice(&bytes).map_err(|e| e.to_string())?;
+        let Some(rates) = body["usd"].as_object() else {
+            last_error = format!("{} returned no rates", url);
+            continue;
+        };
+        let mut rates: BTreeMap<String, f64> = rates
+            .iter()
+            .filter_map(|(code, rate)| rate.as_f64().filter(|r| *r > 0.0).map(|r| (code.clone(), r)))
+            .collect();
+        rates.insert("usd".to_string(), 1.0);
+        return Ok(RatesFile {
+            date: body["date"].as_str().unwrap_or_default().to_string(),
+            fetched_at: unix_now(),
+            rates,
+        });
+    }
+    Err(format!("Failed to download exchange rates: {}", last_error))
+}
+
+/// Download rates and replace the cache
+async fn refresh(data_dir: &Path) -> Result<RatesFile, String> {
+    let rates = fetch_rates().await?;
+    let json = serde_json::to_string(&rates).map_err(|e| e.to_string())?;
+    fs::write(rates_path(data_dir), json).map_err(|e| format!("Failed to save exchange rates: {}", e))?;
+    Ok(rates)
+}
+
+async fn data_dir(app: &AppHandle) -> PathBuf {
+    app.state::<SharedServerManager>().lock().await.data_dir().clone()
+}
+
+/// Keep the rate cache fresh while online; failures keep the previous rates
+pub fn start_fx_refresh(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut logged_failure = false;
+        loop {
+            let data_dir = data_dir(&app).await;
+            let age = read_rates(&data_dir).map(|r| unix_now().saturating_sub(r.fetched_at));
+            let stale = age.is_none_or(|age| age >= MAX_AGE.as_secs());
+            if stale && netusage::limit_reason(&app).await.is_none() {
+                match refresh(&data_dir).await {
+                    Ok(_) => logged_failure = false,
+                    // Offline is normal; say so once rather than every hour
+                    Err(e) if !logged_failure => {
+                        logged_failure = true;
+                        let log_store = app.state::<SharedLogStore>().inner().clone();
+                        log_line(&app, &log_store, e, "info").await;
+                    }
+                    Err(_) => {}
+                }
+            }
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
+
+/// Currencies of the user's accounts and holdings, most used first
+fn own_currencies(data_dir: &Path) -> Vec<String> {
+    if read_database_url(data_dir).is_some() {
+        return Vec::new();
+    }
+    let Ok(conn) = Connection::open_with_flags(sqlite_db_path(data_dir), OpenFlags::SQLITE_OPEN_READ_ONLY) else {
+        return Vec::new();
+    };
+    let query = "SELECT UPPER(currency), COUNT(*) AS uses FROM (
+            SELECT currency FROM accounts UNION ALL SELECT currency FROM investment_holdings
+        ) GROUP BY UPPER(currency) ORDER BY uses DESC";
+    conn.prepare(query)
+        .and_then(|mut statement| {
+            statement
+                .query_map([], |row| row.get::<_, String>(0))?
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .unwrap_or_default()
+}
+
+/// Cached rates' date and the currencies to offer
+#[tauri::command]
+pub async fn get_fx_rates(app: AppHandle) -> Result<FxInfo, String> {
+    let data_dir = data_dir(&app).await;
+    let rates = read_rates(&data_dir);
+    let own = tauri::async_runtime::spawn_blocking({
+        let data_dir = data_dir.clone();
+        move || own_currencies(&data_dir)
+    })
+    .await
+    .unwrap_or_default();
+    Ok(FxInfo {
+        date: rates.as_ref().map(|r| r.date.clone()),
+        fetched_at: rates.as_ref().map(|r| r.fetched_at),
+        own,
+        all: rates
+            .map(|r| r.rates.into_keys().map(|code| code.to_uppercase()).collect())
+            .unwrap_or_default(),
+    })
+}
+
+/// Convert with the cached rates (works offline)
+#[tauri::command]
+pub async fn convert_currency(app: AppHandle, amount: f64, from: String, to: String) -> Result<Conversion, String> {
+    if !amount.is_finite() {
+        return Err("Enter an amount".to_string());
+    }
+    let data_dir = data_dir(&app).await;
+    let rates = read_rates(&data_dir)
+        .ok_or_else(|| "No exchange rates yet; connect to the internet once to download them".to_string())?;
+    let rate_of = |code: &str| {
+        rates
+            .rates
+            .get(&code.to_lowercase())
+            .copied()
+            .ok_or_else(|| format!("No rate for {}", code.to_uppercase()))
+    };
+    let rate = rate_of(&to)? / rate_of(&from)?;
+    Ok(Conversion { amount: amount * rate, rate, date: rates.date.clone() })
+}
+
+/// Download the latest rates now
+#[tauri::command]
+pub async fn refresh_fx_rates(app: AppHandle) -> Result<String, String> {
+    let data_dir = data_dir(&app).await;
+    refresh(&data_dir).await.map(|rates| rates.date)
+}
+
+/// Open (or focus) the quick converter, a small window that closes when it loses focus
+pub fn open_converter_window(app: &AppHandle) {
+    // Static UI; currency codes are set as option text, never inserted as HTML
+    let script = r#"
+        const tauriApi = window.__TAURI__;
+
+        document.documentElement.innerHTML = `
+<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <title>Currency Converter</title>
+    <style>
+        __BASE_STYLE__
+        #content { flex: 1; padding: 14px 16px; display: grid; grid-template-columns: 1fr 90px; gap: 8px; align-content: start; }
+        #content input, #content select { width: 100%; }
+        #swapBtn { grid-column: 1 / -1; justify-self: center; padding: 2px 10px; }
+        #result { font-size: 20px; font-weight: 600; align-self: center; }
+        #rate { grid-column: 1 / -1; }
+    </style>
+</head>
+<body>
+    <div id="content">
+        <input type="number" id="amount" value="1" step="any" aria-label="Amount">
+        <select id="from" aria-label="From currency"></select>
+        <button id="swapBtn" title="Swap currencies">&#8645;</button>
+        <div id="result" role="status" aria-live="polite"></div>
+        <select id="to" aria-label="To currency"></select>
+        <p id="rate" class="muted"></p>
+    </div>
+    <div class="toolbar">
+        <button id="refreshBtn">Update Rates</button>
+        <span id="status" class="muted" style="margin-left: auto"></span>
+    </div>
+</body>
+</html>`;
+
+        const $ = id => document.getElementById(id);
+        const current = tauriApi.window.getCurrentWindow();
+
+        function fill(select, own, all) {
+            select.innerHTML = '';
+            const add = (parent, code) => {
+                const option = document.createElement('option');
+                option.value = code;
+                option.textContent = code;
+                parent.appendChild(option);
+            };
+            if (own.length) {
+                const group = document.createElement('optgroup');
+                group.label = 'Your currencies';
+                own.forEach(code => add(group, code));
+                select.appendChild(group);
+            }
+            const rest = document.createElement('optgroup');
+            rest.label = 'All currencies';
+            all.filter(code => !own.includes(code)).forEach(code => add(rest, code));
+            select.appendChild(rest);
+        }
+
+        async function convert() {
+            const amount = parseFloat($('amount').value);
+            if (!$('from').value || !$('to').value || isNaN(amount)) {
+                $('result').textContent = '';
+                return;
+            }
+            localStorage.setItem('converterPair', JSON.stringify([$('from').value, $('to').value]));
+            try {
+                const result = await tauriApi.core.invoke('convert_currency', { amount, from: $('from').value, to: $('to').value });
+                const format = code => new Intl.NumberFormat(undefined, { style: 'currency', currency: code, maximumFractionDigits: 2 });
+                let text;
+                try {
+                    text = format($('to').value).format(result.amount);
+                } catch (e) {
+                    text = result.amount.toFixed(2) + ' ' + $('to').value;
+                }
+                $('result').textContent = text;
+                $('rate').textContent = '1 ' + $('from').value + ' = ' + result.rate.toPrecision(6) + ' ' + $('to').value + ' (rates of ' + result.date + ')';
+            } catch (e) {
+                $('result').textContent = '';
+                $('rate').textContent = String(e);
+            }
+        }
+
+        async function load() {
+            try {
+                const info = await tauriApi.core.invoke('get_fx_rates');
+                const all = info.all.length ? info.all : info.own;
+                fill($('from'), info.own, all);
+                fill($('to'), info.own, all);
+                const saved = JSON.parse(localStorage.getItem('converterPair') || 'null');
+                const [from, to] = saved || [info.own[0] || 'USD', info.own[1] || (info.own[0] === 'EUR' ? 'USD' : 'EUR')];
+                $('from').value = from;
+                $('to').value = to;
+                $('status').textContent = info.fetched_at
+                    ? 'Updated ' + new Date(info.fetched_at * 1000).toLocaleDateString()
+                    : 'No rates downloaded yet';
+                convert();
+            } catch (e) {
+                $('status').textContent = String(e);
+            }
+        }
+
+        $('amount').oninput = convert;
+        $('from').onchange = convert;
+        $('to').onchange = convert;
+        $('swapBtn').onclick = () => {
+            const from = $('from').value;
+            $('from').value = $('to').value;
+            $('to').value = from;
+            convert();
+        };
+        $('refreshBtn').onclick = async () => {
+            $('refreshBtn').disabled = true;
+            $('status').textContent = 'Updating...';
+            try {
+                await tauriApi.core.invoke('refresh_fx_rates');
+                await load();
+            } catch (e) {
+                $('status').textContent = 'Offline, using saved rates';
+            }
+            $('refreshBtn').disabled = false;
+        };
+        document.addEventListener('keydown', e => {
+            if (e.key === 'Escape') current.close();
+        });
+        // A popover: clicking elsewhere dismisses it
+        window.addEventListener('blur', () => current.close());
+        load().then(() => { $('amount').focus(); $('amount').select(); });
+    "#;
+
+    open_injected_window(app, WINDOW_LABEL, "Currency Converter", (340.0, 230.0), false, script);
+    if let Some(window) = app.get_webview_window(WINDOW_LABEL) {
+        let _ = window.set_always_on_top(true);
+    }
+}
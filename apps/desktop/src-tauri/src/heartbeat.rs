@@ -0,0 +1,98 @@
+// Heartbeat file: proof of life written to the data dir while the app runs
+//
+// Every INTERVAL, `heartbeat.json` is replaced with the time, the server's status and
+// the versions. A crash or power loss leaves the last one behind, so the app died
+// within INTERVAL after its timestamp; the unclean-exit crash report and the doctor
+// read it back for that. Headless operators can watch the file with their own
+// tooling: a timestamp older than a few intervals means the app is gone or hung,
+// and status "exited" means it shut down cleanly.
+//
+// The file is written to a temporary name and renamed, so readers never see half of it.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use crate::server::SharedServerManager;
+
+const HEARTBEAT_FILE: &str = "heartbeat.json";
+const INTERVAL: Duration = Duration::from_secs(60);
+
+static PATH: OnceLock<PathBuf> = OnceLock::new();
+/// When this session started, carried in every beat
+static STARTED: OnceLock<u64> = OnceLock::new();
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Heartbeat {
+    /// Unix time in seconds of this beat
+    pub timestamp: u64,
+    /// Server status ("starting", "running", "stopped", "error"), or "exited" after a clean shutdown
+    pub status: String,
+    pub version: String,
+    pub sidecar_version: Option<String>,
+    pub pid: u32,
+    /// Unix time in seconds the app started
+    pub started: u64,
+    /// Seconds between beats, for telling a stale file from a fresh one
+    pub interval_secs: u64,
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// The last beat written to a data dir, None if there is none
+pub fn read(data_dir: &Path) -> Option<Heartbeat> {
+    let content = fs::read_to_string(data_dir.join(HEARTBEAT_FILE)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn write(status: &str, sidecar_version: Option<String>) {
+    let Some(path) = PATH.get() else {
+        return;
+    };
+    let beat = Heartbeat {
+        timestamp: unix_now(),
+        status: status.to_string(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        sidecar_version,
+        pid: std::process::id(),
+        started: STARTED.get().copied().unwrap_or(0),
+        interval_secs: INTERVAL.as_secs(),
+    };
+    let tmp = path.with_extension("json.tmp");
+    let result = serde_json::to_string_pretty(&beat)
+        .map_err(|e| e.to_string())
+        .and_then(|json| fs::write(&tmp, json).map_err(|e| e.to_string()))
+        .and_then(|_| fs::rename(&tmp, path).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        eprintln!("Warning: Failed to write heartbeat: {}", e);
+    }
+}
+
+/// Beat every INTERVAL from now on; called after the crash handler read the previous one
+pub fn start(app: AppHandle, data_dir: &Path) {
+    let _ = PATH.set(data_dir.join(HEARTBEAT_FILE));
+    let _ = STARTED.set(unix_now());
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let (status, sidecar_version) = {
+                let manager = app.state::<SharedServerManager>();
+                let mgr = manager.lock().await;
+                (mgr.status().as_str(), mgr.sidecar_version().map(String::from))
+            };
+            write(status, sidecar_version);
+            tokio::time::sleep(INTERVAL).await;
+        }
+    });
+}
+
+/// Last beat on a clean shutdown
+pub fn mark_exited() {
+    write("exited", None);
+}
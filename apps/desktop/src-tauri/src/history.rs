@@ -0,0 +1,290 @@
+// Task history: what ran, when, for how long and how it ended
+//
+// The job center only keeps the last few jobs in memory; this keeps a record across
+// restarts. Jobs are recorded when they finish (see jobs.rs), and tasks that aren't
+// jobs (update checks, the nightly restart) record themselves with `begin`. Work the
+// scheduler starts runs inside `scheduled`, so its entries are marked as scheduled
+// rather than manual.
+//
+// Entries are appended to `task-history.jsonl` in the data dir, one JSON object per
+// line, and trimmed to the newest MAX_ENTRIES once the file grows past twice that.
+
+use std::fs::{self, OpenOptions};
+use std::future::Future;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use crate::windows::open_injected_window;
+
+const HISTORY_FILE: &str = "task-history.jsonl";
+const MAX_ENTRIES: usize = 1000;
+
+static PATH: OnceLock<PathBuf> = OnceLock::new();
+/// Serializes appends and trimming
+static WRITE: Mutex<()> = Mutex::new(());
+
+tokio::task_local! {
+    static SCHEDULED: ();
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Done,
+    Failed,
+    Cancelled,
+    /// Didn't run, e.g. the nightly restart with jobs in progress
+    Skipped,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// "backup", "export", "import", "update", "migration", "recovery", "update_check", "restart"
+    pub kind: String,
+    pub title: String,
+    /// "scheduled" or "manual"
+    pub trigger: String,
+    /// Unix times in seconds
+    pub started: u64,
+    pub finished: u64,
+    pub duration_ms: u64,
+    pub outcome: Outcome,
+    /// Error or skip reason
+    pub detail: Option<String>,
+}
+
+fn unix_now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Where history is kept; called once the data dir is known
+pub fn init(data_dir: &std::path::Path) {
+    let _ = PATH.set(data_dir.join(HISTORY_FILE));
+}
+
+/// Run scheduler work so the tasks it starts are recorded as scheduled
+pub async fn scheduled<F: Future>(work: F) -> F::Output {
+    SCHEDULED.scope((), work).await
+}
+
+/// "scheduled" inside `scheduled`, otherwise "manual"
+pub fn current_trigger() -> &'static str {
+    match SCHEDULED.try_with(|_| ()) {
+        Ok(()) => "scheduled",
+        Err(_) => "manual",
+    }
+}
+
+fn read_entries(path: &PathBuf) -> Vec<HistoryEntry> {
+    fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+fn append(entry: &HistoryEntry) -> Result<(), String> {
+    let Some(path) = PATH.get() else {
+        return Ok(());
+    };
+    let line = serde_json::to_string(entry).map_err(|e| e.to_string())?;
+    let _guard = WRITE.lock().unwrap_or_else(|e| e.into_inner());
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    writeln!(file, "{}", line).map_err(|e| e.to_string())?;
+    drop(file);
+
+    // Trim now and then rather than on every append
+    let lines = fs::read_to_string(path).map(|c| c.lines().count()).unwrap_or(0);
+    if lines > MAX_ENTRIES * 2 {
+        let entries = read_entries(path);
+        let keep = &entries[entries.len().saturating_sub(MAX_ENTRIES)..];
+        let content: String = keep
+            .iter()
+            .filter_map(|e| serde_json::to_string(e).ok())
+            .map(|line| line + "\n")
+            .collect();
+        fs::write(path, content).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Add an entry, logging to stderr if the file can't be written
+pub fn record(entry: HistoryEntry) {
+    if let Err(e) = append(&entry) {
+        eprintln!("Warning: Failed to record task history: {}", e);
+    }
+}
+
+/// A task being timed for the history
+pub struct TaskRun {
+    kind: &'static str,
+    title: String,
+    trigger: &'static str,
+    started_ms: u64,
+}
+
+/// Start timing a task that isn't a job
+pub fn begin(kind: &'static str, title: impl Into<String>) -> TaskRun {
+    TaskRun {
+        kind,
+        title: title.into(),
+        trigger: current_trigger(),
+        started_ms: unix_now_ms(),
+    }
+}
+
+impl TaskRun {
+    fn end(self, outcome: Outcome, detail: Option<String>) {
+        let finished_ms = unix_now_ms();
+        record(HistoryEntry {
+            kind: self.kind.to_string(),
+            title: self.title,
+            trigger: self.trigger.to_string(),
+            started: self.started_ms / 1000,
+            finished: finished_ms / 1000,
+            duration_ms: finished_ms.saturating_sub(self.started_ms),
+            outcome,
+            detail,
+        });
+    }
+
+    pub fn finish<T>(self, result: &Result<T, String>) {
+        match result {
+            Ok(_) => self.end(Outcome::Done, None),
+            Err(e) => self.end(Outcome::Failed, Some(e.clone())),
+        }
+    }
+
+    pub fn skip(self, reason: impl Into<String>) {
+        self.end(Outcome::Skipped, Some(reason.into()));
+    }
+}
+
+/// Recorded tasks, newest first, optionally filtered by kind and outcome
+#[tauri::command]
+pub async fn get_task_history(
+    limit: Option<usize>,
+    kind: Option<String>,
+    outcome: Option<Outcome>,
+) -> Result<Vec<HistoryEntry>, String> {
+    let Some(path) = PATH.get().cloned() else {
+        return Ok(Vec::new());
+    };
+    let entries = tauri::async_runtime::spawn_blocking(move || read_entries(&path))
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(entries
+        .into_iter()
+        .rev()
+        .filter(|e| kind.as_ref().is_none_or(|k| &e.kind == k))
+        .filter(|e| outcome.is_none_or(|o| e.outcome == o))
+        .take(limit.unwrap_or(MAX_ENTRIES))
+        .collect())
+}
+
+/// Open the task history window
+pub fn open_history_window(app: &AppHandle) {
+    // Static UI; task titles and errors are inserted with escaping on the JS side
+    let script = r#"
+        const tauriApi = window.__TAURI__;
+
+        document.documentElement.innerHTML = `
+<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <title>Task History</title>
+    <style>
+        __BASE_STYLE__
+        #summary { margin-left: auto; }
+        #list { flex: 1; overflow-y: auto; padding: 0 16px 16px; }
+        table { width: 100%; border-collapse: collapse; }
+        th { position: sticky; top: 0; background: #030303; }
+        th, td { text-align: left; padding: 8px; border-bottom: 1px solid rgba(255, 255, 255, 0.06); vertical-align: top; }
+        td.num { text-align: right; font-variant-numeric: tabular-nums; white-space: nowrap; }
+        td.detail { color: #a1a1aa; word-break: break-word; }
+        .skipped, .cancelled { color: #71717a; }
+    </style>
+</head>
+<body>
+    <div class="toolbar">
+        <select id="kind" aria-label="Task">
+            <option value="">All tasks</option>
+            <option value="backup">Backups</option>
+            <option value="export">Exports</option>
+            <option value="import">Imports</option>
+            <option value="update">Update downloads</option>
+            <option value="update_check">Update checks</option>
+            <option value="restart">Restarts</option>
+            <option value="migration">Migrations</option>
+            <option value="recovery">Database recovery</option>
+        </select>
+        <select id="outcome" aria-label="Outcome">
+            <option value="">Any outcome</option>
+            <option value="done">Done</option>
+            <option value="failed">Failed</option>
+            <option value="cancelled">Cancelled</option>
+            <option value="skipped">Skipped</option>
+        </select>
+        <button id="refreshBtn">Refresh</button>
+        <span id="summary" class="muted" role="status" aria-live="polite"></span>
+    </div>
+    <div id="list"></div>
+</body>
+</html>`;
+
+        const $ = id => document.getElementById(id);
+
+        function duration(ms) {
+            if (ms < 1000) return ms + ' ms';
+            const s = Math.round(ms / 1000);
+            if (s < 60) return s + ' s';
+            const m = Math.floor(s / 60);
+            return m < 60 ? m + ' min ' + (s % 60) + ' s' : Math.floor(m / 60) + ' h ' + (m % 60) + ' min';
+        }
+
+        async function load() {
+            try {
+                const entries = await tauriApi.core.invoke('get_task_history', {
+                    kind: $('kind').value || null,
+                    outcome: $('outcome').value || null,
+                });
+                if (!entries.length) {
+                    $('list').innerHTML = '<div class="empty-state">No tasks recorded yet</div>';
+                    $('summary').textContent = '';
+                    return;
+                }
+                $('list').innerHTML = '<table><thead><tr><th>Task</th><th>Trigger</th><th>Started</th><th>Duration</th><th>Outcome</th><th>Detail</th></tr></thead><tbody>' +
+                    entries.map(e =>
+                        '<tr><td>' + escapeHtml(e.title) + '</td><td class="muted">' + e.trigger + '</td>' +
+                        '<td class="num">' + new Date(e.started * 1000).toLocaleString() + '</td>' +
+                        '<td class="num">' + duration(e.duration_ms) + '</td>' +
+                        '<td class="' + (e.outcome === 'done' ? 'pass' : e.outcome === 'failed' ? 'fail' : e.outcome) + '">' + e.outcome + '</td>' +
+                        '<td class="detail">' + escapeHtml(e.detail || '') + '</td></tr>'
+                    ).join('') + '</tbody></table>';
+                const failed = entries.filter(e => e.outcome === 'failed').length;
+                $('summary').textContent = entries.length + ' tasks · ' + failed + ' failed';
+            } catch (e) {
+                $('list').innerHTML = '<div class="empty-state fail">' + escapeHtml(String(e)) + '</div>';
+            }
+        }
+
+        $('kind').onchange = load;
+        $('outcome').onchange = load;
+        $('refreshBtn').onclick = load;
+        window.addEventListener('focus', load);
+        load();
+    "#;
+
+    open_injected_window(app, "task_history", "Task History", (900.0, 560.0), true, script);
+}
@@ -0,0 +1,190 @@
+// Locking the app when the machine is left unattended (`[idle]` in settings.toml)
+//
+// The system-wide idle time (no keyboard or mouse input in any app) is polled; once it
+// passes `idle.lock_after_minutes` the app windows are covered with the lock screen,
+// as with "Lock Moneywright". With `idle.expire_session` the web session is also
+// signed out on the server and its cookies dropped, so unlocking leads to the sign-in
+// page rather than straight back into the accounts. It locks once per idle period;
+// input resets it.
+//
+// Idle time comes from GetLastInputInfo on Windows, IOHIDSystem on macOS and
+// xprintidle or GNOME's idle monitor on Linux. Where none is available (e.g. Wayland
+// outside GNOME) the feature is off and that's logged once.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use serde::Serialize;
+use tauri::{AppHandle, Manager, Url};
+use crate::importer::session_cookies;
+use crate::logs::{log_line, SharedLogStore};
+use crate::server::get_server_url;
+use crate::settings::SharedSettings;
+use crate::transport;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Locked during the current idle period
+static LOCKED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Serialize)]
+pub struct IdleStatus {
+    /// Seconds since the last input, None when the OS doesn't report it
+    idle_seconds: Option<u64>,
+    /// 0 when idle locking is off
+    lock_after_minutes: u32,
+    expire_session: bool,
+}
+
+#[cfg(target_os = "windows")]
+fn idle_time() -> Option<Duration> {
+    use windows_sys::Win32::System::SystemInformation::GetTickCount;
+    use windows_sys::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+
+    let mut info = LASTINPUTINFO { cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32, dwTime: 0 };
+    // SAFETY: `info` is a properly sized LASTINPUTINFO that outlives the call
+    if unsafe { GetLastInputInfo(&mut info) } == 0 {
+        return None;
+    }
+    // Both are milliseconds since boot, wrapping after 49.7 days
+    let ticks = unsafe { GetTickCount() };
+    Some(Duration::from_millis(ticks.wrapping_sub(info.dwTime) as u64))
+}
+
+#[cfg(target_os = "macos")]
+fn idle_time() -> Option<Duration> {
+    // `"HIDIdleTime" = 1234567890` in nanoseconds
+    let output = crate::a11y::command_output("ioreg", &["-c", "IOHIDSystem", "-d", "4"])?;
+    let nanos: u64 = output
+        .lines()
+        .find_map(|line| line.split("\"HIDIdleTime\" = ").nth(1))?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(Duration::from_nanos(nanos))
+}
+
+#[cfg(target_os = "linux")]
+fn idle_time() -> Option<Duration> {
+    use crate::a11y::command_output;
+
+    // X11, in milliseconds
+    if let Some(millis) = command_output("xprintidle", &[]).and_then(|out| out.parse::<u64>().ok()) {
+        return Some(Duration::from_millis(millis));
+    }
+    // GNOME (X11 and Wayland): `(uint64 1234,)` in milliseconds
+    let output = command_output(
+        "gdbus",
+        &[
+            "call",
+            "--session",
+            "--dest",
+            "org.gnome.Mutter.IdleMonitor",
+            "--object-path",
+            "/org/gnome/Mutter/IdleMonitor/Core",
+            "--method",
+            "org.gnome.Mutter.IdleMonitor.GetIdletime",
+        ],
+    )?;
+    let millis: u64 = output.split("uint64 ").nth(1)?.trim_end_matches([',', ')']).parse().ok()?;
+    Some(Duration::from_millis(millis))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn idle_time() -> Option<Duration> {
+    None
+}
+
+/// Sign the main window's session out on the server and drop its cookies
+async fn expire_session(app: &AppHandle) -> Result<(), String> {
+    let cookies = session_cookies(app)?;
+    let request = reqwest::Client::new()
+        .post(format!("{}/api/auth/logout", get_server_url()))
+        .header(reqwest::header::COOKIE, cookies)
+        .timeout(Duration::from_secs(10));
+    let response = transport::send(request)
+        .await
+        .map_err(|e| format!("Failed to reach the server: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Server returned {}", response.status()));
+    }
+
+    // Over the socket transport the shell holds the session itself
+    if transport::socket_path().is_some() {
+        transport::clear_cookies();
+        return Ok(());
+    }
+
+    // The logout response clears cookies in reqwest, not in the webview
+    let window = app
+        .get_webview_window("main")
+        .ok_or_else(|| "Main window is not available".to_string())?;
+    let url = Url::parse(&get_server_url()).map_err(|e| e.to_string())?;
+    let stored = window.cookies_for_url(url).map_err(|e| format!("Failed to read session: {}", e))?;
+    for cookie in stored {
+        window.delete_cookie(cookie).map_err(|e| format!("Failed to delete cookie: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Lock (and optionally sign out) once the machine has been idle long enough
+pub fn start_idle_watcher(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let log_store = app.state::<SharedLogStore>().inner().clone();
+        let mut reported_unavailable = false;
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            let settings = app.state::<SharedSettings>().lock().await.get().idle;
+            if settings.lock_after_minutes == 0 {
+                LOCKED.store(false, Ordering::Relaxed);
+                continue;
+            }
+            let Ok(idle) = tauri::async_runtime::spawn_blocking(idle_time).await else {
+                continue;
+            };
+            let Some(idle) = idle else {
+                if !reported_unavailable {
+                    reported_unavailable = true;
+                    log_line(&app, &log_store, "Idle lock unavailable: the system idle time can't be read", "error").await;
+                }
+                continue;
+            };
+
+            if idle < Duration::from_secs(settings.lock_after_minutes as u64 * 60) {
+                LOCKED.store(false, Ordering::Relaxed);
+                continue;
+            }
+            if LOCKED.swap(true, Ordering::Relaxed) {
+                continue;
+            }
+            if settings.expire_session {
+                match expire_session(&app).await {
+                    Ok(()) => log_line(&app, &log_store, "Signed out of the web session after inactivity", "info").await,
+                    Err(e) => log_line(&app, &log_store, format!("Failed to expire web session: {}", e), "error").await,
+                }
+            }
+            crate::protocol::lock_windows(&app);
+            log_line(
+                &app,
+                &log_store,
+                format!("Locked after {} minutes without input", settings.lock_after_minutes),
+                "info",
+            )
+            .await;
+        }
+    });
+}
+
+/// System idle time and the idle lock settings
+#[tauri::command]
+pub async fn get_idle_status(settings: tauri::State<'_, SharedSettings>) -> Result<IdleStatus, String> {
+    let idle = settings.lock().await.get().idle;
+    let idle_seconds = tauri::async_runtime::spawn_blocking(idle_time)
+        .await
+        .map_err(|e| e.to_string())?
+        .map(|d| d.as_secs());
+    Ok(IdleStatus {
+        idle_seconds,
+        lock_after_minutes: idle.lock_after_minutes,
+        expire_session: idle.expire_session,
+    })
+}
@@ -0,0 +1,1292 @@
+// Migration from Mint, YNAB and Quicken
+//
+// Export files are detected in the Downloads folder (watched while the import window
+// is open) or dropped onto the window, converted in Rust to Moneywright's CSV
+// statement layout, previewed with the column mapping, and uploaded account by
+// account to the server's statement importer using the main window's session.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+use chrono::NaiveDate;
+use notify::{RecursiveMode, Watcher};
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Manager, Url};
+use crate::events::{self, Event};
+use crate::jobs::{start_job, JobHandle, JobKind};
+use crate::notifications::{notify, Kind};
+use crate::server::get_server_url;
+use crate::transport;
+use crate::windows::open_injected_window;
+
+/// How often the download watcher checks whether the import window is still open
+const WINDOW_CHECK_INTERVAL: Duration = Duration::from_secs(3);
+/// A download arrives as a burst of file events; rescan once they settle
+const SETTLE_DELAY: Duration = Duration::from_millis(500);
+/// Enough of a file to tell the export format from its header
+const SNIFF_BYTES: u64 = 8 * 1024;
+/// Currency symbols allowed around amounts
+const CURRENCY_SYMBOLS: &[char] = &['$', '€', '£', '¥', '₹'];
+const PREVIEW_ROWS: usize = 20;
+/// Exports larger than this are refused (Mint exports of ~15 years are a few MB)
+const MAX_FILE_BYTES: u64 = 50 * 1024 * 1024;
+const UPLOAD_TIMEOUT: Duration = Duration::from_secs(120);
+const WINDOW_LABEL: &str = "import";
+
+#[derive(Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportSource {
+    Mint,
+    Ynab,
+    Quicken,
+}
+
+#[derive(Clone, Serialize)]
+pub struct ImportTransaction {
+    /// ISO date (YYYY-MM-DD)
+    pub date: String,
+    pub description: String,
+    /// Negative for money leaving the account
+    pub amount_cents: i64,
+    pub category: Option<String>,
+    pub account: String,
+    pub notes: Option<String>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct ColumnMapping {
+    pub source: String,
+    pub target: &'static str,
+}
+
+#[derive(Clone, Serialize)]
+pub struct AccountSummary {
+    pub name: String,
+    pub transactions: usize,
+    pub first_date: Option<String>,
+    pub last_date: Option<String>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct ImportPreview {
+    pub path: String,
+    pub source: ImportSource,
+    pub mapping: Vec<ColumnMapping>,
+    pub accounts: Vec<AccountSummary>,
+    pub sample: Vec<ImportTransaction>,
+    pub total: usize,
+    pub skipped: usize,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Clone, Serialize, PartialEq)]
+pub struct DetectedFile {
+    pub path: String,
+    pub name: String,
+    pub source: ImportSource,
+    pub size: u64,
+    pub modified: u64,
+}
+
+#[derive(Clone, Serialize)]
+pub struct ImportProgress {
+    account: String,
+    index: usize,
+    total: usize,
+    /// "uploading", "done" or "error"
+    status: &'static str,
+    message: Option<String>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct ImportResult {
+    pub uploaded: Vec<String>,
+    pub failed: Vec<String>,
+}
+
+struct Converted {
+    source: ImportSource,
+    mapping: Vec<ColumnMapping>,
+    transactions: Vec<ImportTransaction>,
+    skipped: usize,
+    warnings: Vec<String>,
+}
+
+fn mapping(pairs: &[(&str, &'static str)]) -> Vec<ColumnMapping> {
+    pairs
+        .iter()
+        .map(|(source, target)| ColumnMapping { source: source.to_string(), target })
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Detection
+// ---------------------------------------------------------------------------
+
+fn normalize_header(header: &str) -> String {
+    header.trim_start_matches('\u{feff}').to_lowercase()
+}
+
+/// The first SNIFF_BYTES of a file; a character cut off at the end is replaced
+fn read_head(path: &Path) -> Option<String> {
+    let mut head = Vec::new();
+    fs::File::open(path).ok()?.take(SNIFF_BYTES).read_to_end(&mut head).ok()?;
+    Some(String::from_utf8_lossy(&head).into_owned())
+}
+
+/// Identify the export format from the file name and first bytes
+pub fn sniff(path: &Path) -> Option<ImportSource> {
+    let ext = path.extension()?.to_str()?.to_lowercase();
+    match ext.as_str() {
+        "qif" => Some(ImportSource::Quicken),
+        "json" => {
+            let head = read_head(path)?;
+            (head.contains("\"budget\"") || head.contains("\"transactions\"")).then_some(ImportSource::Ynab)
+        }
+        "csv" => {
+            let head = read_head(path)?;
+            let header = normalize_header(head.lines().next()?);
+            if header.contains("original description") && header.contains("transaction type") {
+                Some(ImportSource::Mint)
+            } else if header.contains("outflow") && header.contains("inflow") && header.contains("payee") {
+                Some(ImportSource::Ynab)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Export files from Mint/YNAB/Quicken in the Downloads folder, newest first
+pub fn detect_files() -> Vec<DetectedFile> {
+    let Some(dir) = dirs::download_dir() else { return Vec::new() };
+    let Ok(entries) = fs::read_dir(dir) else { return Vec::new() };
+
+    let mut files: Vec<DetectedFile> = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|entry| {
+            let path = entry.path();
+            let meta = entry.metadata().ok()?;
+            if !meta.is_file() || meta.len() > MAX_FILE_BYTES {
+                return None;
+            }
+            let source = sniff(&path)?;
+            Some(DetectedFile {
+                name: entry.file_name().to_string_lossy().to_string(),
+                path: path.to_string_lossy().to_string(),
+                source,
+                size: meta.len(),
+                modified: meta
+                    .modified()
+                    .ok()
+                    .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0),
+            })
+        })
+        .collect();
+    files.sort_by_key(|f| std::cmp::Reverse(f.modified));
+    files
+}
+
+// ---------------------------------------------------------------------------
+// Field parsing
+// ---------------------------------------------------------------------------
+
+/// Parse the date formats used by the supported exports (US month-first by default)
+fn parse_date(raw: &str) -> Option<NaiveDate> {
+    // Quicken writes dates like 1/15'24 or 1/ 5/2024
+    let cleaned: String = raw.trim().replace('\'', "/").replace(' ', "");
+    // Two-digit years first: %Y would happily read "24" as the year 24
+    for fmt in ["%Y-%m-%d", "%m/%d/%y", "%m/%d/%Y", "%m-%d-%Y", "%d/%m/%Y", "%d.%m.%Y"] {
+        if let Ok(date) = NaiveDate::parse_from_str(&cleaned, fmt) {
+            return Some(date);
+        }
+    }
+    None
+}
+
+/// Parse an amount like "$1,234.56", "-12.30", "$-12.30" or "(12.30)" into cents,
+/// rounding anything past the cents half away from zero
+fn parse_amount(raw: &str) -> Option<i64> {
+    let mut text = raw.trim();
+    let mut negative = false;
+    if let Some(inner) = text.strip_prefix('(').and_then(|t| t.strip_suffix(')')) {
+        negative = true;
+        text = inner;
+    }
+    // The sign and the currency symbol come in either order: -$12.30 or $-12.30
+    let mut signed = false;
+    let number = text
+        .trim_start_matches(|c: char| {
+            if matches!(c, '-' | '+') && !signed {
+                signed = true;
+                negative ^= c == '-';
+                return true;
+            }
+            c.is_whitespace() || CURRENCY_SYMBOLS.contains(&c)
+        })
+        .trim_end_matches(|c: char| c.is_whitespace() || CURRENCY_SYMBOLS.contains(&c));
+
+    let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+    let whole: String = whole.chars().filter(|c| *c != ',').collect();
+    if (whole.is_empty() && fraction.is_empty())
+        || !whole.chars().all(|c| c.is_ascii_digit())
+        || !fraction.chars().all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+    let units: i64 = if whole.is_empty() { 0 } else { whole.parse().ok()? };
+    let digit = |i: usize| fraction.as_bytes().get(i).map_or(0, |b| (b - b'0') as i64);
+    let cents = digit(0) * 10 + digit(1) + i64::from(digit(2) >= 5);
+    let total = units.checked_mul(100)?.checked_add(cents)?;
+    Some(if negative { -total } else { total })
+}
+
+fn non_empty(value: Option<&str>) -> Option<String> {
+    value.map(str::trim).filter(|v| !v.is_empty()).map(String::from)
+}
+
+// ---------------------------------------------------------------------------
+// Converters
+// ---------------------------------------------------------------------------
+
+fn column_index(headers: &csv::StringRecord) -> HashMap<String, usize> {
+    headers
+        .iter()
+        .enumerate()
+        .map(|(i, h)| (normalize_header(h).trim().to_string(), i))
+        .collect()
+}
+
+fn read_csv(path: &Path) -> Result<(HashMap<String, usize>, Vec<csv::StringRecord>), String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_path(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let headers = column_index(reader.headers().map_err(|e| format!("Invalid CSV header: {}", e))?);
+    let rows = reader
+        .records()
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Invalid CSV: {}", e))?;
+    Ok((headers, rows))
+}
+
+fn convert_mint(path: &Path) -> Result<Converted, String> {
+    let (cols, rows) = read_csv(path)?;
+    let col = |name: &str| cols.get(name).copied();
+    let (Some(date_i), Some(amount_i)) = (col("date"), col("amount")) else {
+        return Err("Mint export is missing the Date or Amount column".to_string());
+    };
+    let desc_i = col("description");
+    let type_i = col("transaction type");
+    let category_i = col("category");
+    let account_i = col("account name");
+    let notes_i = col("notes");
+
+    let mut transactions = Vec::new();
+    let mut skipped = 0;
+    for row in &rows {
+        let date = row.get(date_i).and_then(parse_date);
+        let amount = row.get(amount_i).and_then(parse_amount);
+        let (Some(date), Some(amount)) = (date, amount) else {
+            skipped += 1;
+            continue;
+        };
+        // Mint amounts are unsigned; the transaction type carries the direction
+        let debit = type_i.and_then(|i| row.get(i)).map(|t| t.eq_ignore_ascii_case("debit")).unwrap_or(false);
+        transactions.push(ImportTransaction {
+            date: date.to_string(),
+            description: non_empty(desc_i.and_then(|i| row.get(i))).unwrap_or_default(),
+            amount_cents: if debit { -amount.abs() } else { amount.abs() },
+            category: non_empty(category_i.and_then(|i| row.get(i))),
+            account: non_empty(account_i.and_then(|i| row.get(i))).unwrap_or_else(|| "Mint".to_string()),
+            notes: non_empty(notes_i.and_then(|i| row.get(i))),
+        });
+    }
+
+    Ok(Converted {
+        source: ImportSource::Mint,
+        mapping: mapping(&[
+            ("Date", "date"),
+            ("Description", "description"),
+            ("Amount + Transaction Type", "amount"),
+            ("Category", "category"),
+            ("Account Name", "account"),
+            ("Notes", "notes"),
+        ]),
+        transactions,
+        skipped,
+        warnings: Vec::new(),
+    })
+}
+
+fn convert_ynab_csv(path: &Path) -> Result<Converted, String> {
+    let (cols, rows) = read_csv(path)?;
+    let col = |name: &str| cols.get(name).copied();
+    let Some(date_i) = col("date") else {
+        return Err("YNAB export is missing the Date column".to_string());
+    };
+    let account_i = col("account");
+    let payee_i = col("payee");
+    let category_i = col("category").or_else(|| col("category group/category"));
+    let memo_i = col("memo");
+    let outflow_i = col("outflow");
+    let inflow_i = col("inflow");
+
+    let mut transactions = Vec::new();
+    let mut skipped = 0;
+    let mut transfers = 0;
+    for row in &rows {
+        let Some(date) = row.get(date_i).and_then(parse_date) else {
+            skipped += 1;
+            continue;
+        };
+        let outflow = outflow_i.and_then(|i| row.get(i)).and_then(parse_amount).unwrap_or(0);
+        let inflow = inflow_i.and_then(|i| row.get(i)).and_then(parse_amount).unwrap_or(0);
+        let description = non_empty(payee_i.and_then(|i| row.get(i))).unwrap_or_default();
+        if description.starts_with("Transfer : ") {
+            transfers += 1;
+        }
+        transactions.push(ImportTransaction {
+            date: date.to_string(),
+            description,
+            amount_cents: inflow.abs() - outflow.abs(),
+            category: non_empty(category_i.and_then(|i| row.get(i))),
+            account: non_empty(account_i.and_then(|i| row.get(i))).unwrap_or_else(|| "YNAB".to_string()),
+            notes: non_empty(memo_i.and_then(|i| row.get(i))),
+        });
+    }
+
+    let mut warnings = Vec::new();
+    if transfers > 0 {
+        warnings.push(format!(
+            "{} transfers between accounts appear once in each account",
+            transfers
+        ));
+    }
+
+    Ok(Converted {
+        source: ImportSource::Ynab,
+        mapping: mapping(&[
+            ("Date", "date"),
+            ("Payee", "description"),
+            ("Inflow - Outflow", "amount"),
+            ("Category", "category"),
+            ("Account", "account"),
+            ("Memo", "notes"),
+        ]),
+        transactions,
+        skipped,
+        warnings,
+    })
+}
+
+/// Build an id -> name lookup from a YNAB entity list
+fn ynab_names(budget: &Value, key: &str) -> HashMap<String, String> {
+    budget[key]
+        .as_array()
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| Some((item["id"].as_str()?.to_string(), item["name"].as_str()?.to_string())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn convert_ynab_json(path: &Path) -> Result<Converted, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let root: Value = serde_json::from_str(&content).map_err(|e| format!("Invalid YNAB JSON: {}", e))?;
+    // Budget exports from the API are wrapped in {"data": {"budget": ...}}
+    let budget = if root["data"]["budget"].is_object() {
+        &root["data"]["budget"]
+    } else if root["budget"].is_object() {
+        &root["budget"]
+    } else {
+        &root
+    };
+    let items = budget["transactions"]
+        .as_array()
+        .ok_or_else(|| "YNAB export has no transactions".to_string())?;
+
+    let accounts = ynab_names(budget, "accounts");
+    let payees = ynab_names(budget, "payees");
+    let categories = ynab_names(budget, "categories");
+    let lookup = |names: &HashMap<String, String>, id: &Value| id.as_str().and_then(|id| names.get(id).cloned());
+
+    let mut transactions = Vec::new();
+    let mut skipped = 0;
+    for item in items {
+        if item["deleted"].as_bool() == Some(true) {
+            continue;
+        }
+        let date = item["date"].as_str().and_then(parse_date);
+        // YNAB amounts are in milliunits
+        let amount = item["amount"].as_i64().map(|m| m / 10);
+        let (Some(date), Some(amount)) = (date, amount) else {
+            skipped += 1;
+            continue;
+        };
+        transactions.push(ImportTransaction {
+            date: date.to_string(),
+            description: lookup(&payees, &item["payee_id"])
+                .or_else(|| non_empty(item["payee_name"].as_str()))
+                .unwrap_or_default(),
+            amount_cents: amount,
+            category: lookup(&categories, &item["category_id"]).or_else(|| non_empty(item["category_name"].as_str())),
+            account: lookup(&accounts, &item["account_id"])
+                .or_else(|| non_empty(item["account_name"].as_str()))
+                .unwrap_or_else(|| "YNAB".to_string()),
+            notes: non_empty(item["memo"].as_str()),
+        });
+    }
+
+    Ok(Converted {
+        source: ImportSource::Ynab,
+        mapping: mapping(&[
+            ("date", "date"),
+            ("payee_id -> payees.name", "description"),
+            ("amount (milliunits)", "amount"),
+            ("category_id -> categories.name", "category"),
+            ("account_id -> accounts.name", "account"),
+            ("memo", "notes"),
+        ]),
+        transactions,
+        skipped,
+        warnings: Vec::new(),
+    })
+}
+
+fn convert_qif(path: &Path) -> Result<Converted, String> {
+    let content = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    // Older Quicken versions write Windows-1252; lossy UTF-8 keeps the structure intact
+    let content = String::from_utf8_lossy(&content);
+    let default_account = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "Quicken".to_string());
+
+    let mut account = default_account;
+    let mut in_account_block = false;
+    let mut in_transactions = false;
+    let mut ignored_sections = HashSet::new();
+    let mut transactions = Vec::new();
+    let mut skipped = 0;
+
+    let mut date = None;
+    let mut amount = None;
+    let mut payee = None;
+    let mut memo = None;
+    let mut category = None;
+
+    for line in content.lines() {
+        let line = line.trim_end_matches('\r');
+        if let Some(header) = line.strip_prefix('!') {
+            let header = header.trim();
+            in_account_block = header.eq_ignore_ascii_case("Account");
+            in_transactions = false;
+            if let Some(kind) = header.strip_prefix("Type:") {
+                match kind.trim() {
+                    "Bank" | "CCard" | "Cash" | "Oth A" | "Oth L" => in_transactions = true,
+                    other => {
+                        ignored_sections.insert(other.to_string());
+                    }
+                }
+            }
+            continue;
+        }
+
+        let (code, value) = match line.chars().next() {
+            Some(c) => (c, line[c.len_utf8()..].trim()),
+            None => continue,
+        };
+
+        if in_account_block {
+            match code {
+                'N' => account = value.to_string(),
+                '^' => in_account_block = false,
+                _ => {}
+            }
+            continue;
+        }
+        if !in_transactions {
+            continue;
+        }
+
+        match code {
+            'D' => date = parse_date(value),
+            'T' | 'U' => amount = parse_amount(value),
+            'P' => payee = non_empty(Some(value)),
+            'M' => memo = non_empty(Some(value)),
+            'L' => {
+                // [Account] categories are transfers
+                category = if value.starts_with('[') {
+                    Some("Transfer".to_string())
+                } else {
+                    non_empty(Some(value))
+                };
+            }
+            '^' => {
+                match (date.take(), amount.take()) {
+                    (Some(d), Some(a)) => transactions.push(ImportTransaction {
+                        date: d.to_string(),
+                        description: payee.take().unwrap_or_default(),
+                        amount_cents: a,
+                        category: category.take(),
+                        account: account.clone(),
+                        notes: memo.take(),
+                    }),
+                    _ => skipped += 1,
+                }
+                payee = None;
+                memo = None;
+                category = None;
+            }
+            _ => {}
+        }
+    }
+
+    let mut warnings: Vec<String> = ignored_sections
+        .into_iter()
+        .map(|kind| format!("Skipped unsupported QIF section: {}", kind))
+        .collect();
+    warnings.sort();
+
+    Ok(Converted {
+        source: ImportSource::Quicken,
+        mapping: mapping(&[
+            ("D", "date"),
+            ("P", "description"),
+            ("T", "amount"),
+            ("L", "category"),
+            ("!Account N", "account"),
+            ("M", "notes"),
+        ]),
+        transactions,
+        skipped,
+        warnings,
+    })
+}
+
+fn convert(path: &Path) -> Result<Converted, String> {
+    let meta = fs::metadata(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    if meta.len() > MAX_FILE_BYTES {
+        return Err(format!("{} is too large to import", path.display()));
+    }
+    let source = sniff(path).ok_or_else(|| "Not a recognized Mint, YNAB or Quicken export".to_string())?;
+    let is_json = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("json"));
+
+    let mut converted = match source {
+        ImportSource::Mint => convert_mint(path)?,
+        ImportSource::Ynab if is_json => convert_ynab_json(path)?,
+        ImportSource::Ynab => convert_ynab_csv(path)?,
+        ImportSource::Quicken => convert_qif(path)?,
+    };
+    converted.transactions.sort_by(|a, b| a.date.cmp(&b.date));
+    if converted.skipped > 0 {
+        converted
+            .warnings
+            .push(format!("{} rows without a valid date or amount were skipped", converted.skipped));
+    }
+    Ok(converted)
+}
+
+fn group_by_account(transactions: &[ImportTransaction]) -> BTreeMap<&str, Vec<&ImportTransaction>> {
+    let mut groups: BTreeMap<&str, Vec<&ImportTransaction>> = BTreeMap::new();
+    for tx in transactions {
+        groups.entry(tx.account.as_str()).or_default().push(tx);
+    }
+    groups
+}
+
+/// Render transactions in the CSV layout the statement importer expects
+fn to_statement_csv(transactions: &[&ImportTransaction]) -> Result<Vec<u8>, String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer
+        .write_record(["Date", "Description", "Amount", "Category", "Notes"])
+        .map_err(|e| e.to_string())?;
+    for tx in transactions {
+        let sign = if tx.amount_cents < 0 { "-" } else { "" };
+        let cents = tx.amount_cents.unsigned_abs();
+        let amount = format!("{}{}.{:02}", sign, cents / 100, cents % 100);
+        writer
+            .write_record([
+                tx.date.as_str(),
+                tx.description.as_str(),
+                amount.as_str(),
+                tx.category.as_deref().unwrap_or(""),
+                tx.notes.as_deref().unwrap_or(""),
+            ])
+            .map_err(|e| e.to_string())?;
+    }
+    writer.into_inner().map_err(|e| e.to_string())
+}
+
+// ---------------------------------------------------------------------------
+// Server upload
+// ---------------------------------------------------------------------------
+
+/// Cookie header carrying the main window's session (the API authenticates by cookie)
+pub fn session_cookies(app: &AppHandle) -> Result<String, String> {
+    if transport::socket_path().is_some() {
+        return transport::cookie_header().ok_or_else(|| "Sign in to Moneywright in the main window first".to_string());
+    }
+    let window = app
+        .get_webview_window("main")
+        .ok_or_else(|| "Main window is not available".to_string())?;
+    let url = Url::parse(&get_server_url()).map_err(|e| e.to_string())?;
+    let cookies = window
+        .cookies_for_url(url)
+        .map_err(|e| format!("Failed to read session: {}", e))?;
+    if cookies.is_empty() {
+        return Err("Sign in to Moneywright in the main window first".to_string());
+    }
+    Ok(cookies
+        .iter()
+        .map(|c| format!("{}={}", c.name(), c.value()))
+        .collect::<Vec<_>>()
+        .join("; "))
+}
+
+/// A multipart/form-data body built in memory, so it can also go over the socket
+/// transport (reqwest's own multipart bodies are streamed), with its content type
+fn multipart_body(fields: &[(&str, &str)], file: (&str, &str, &str, &[u8])) -> (String, Vec<u8>) {
+    let boundary = format!("moneywright-{:x}", UNIX_EPOCH.elapsed().unwrap_or_default().as_nanos());
+    let mut body = Vec::new();
+    for (name, value) in fields {
+        body.extend_from_slice(
+            format!("--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n", boundary, name, value).as_bytes(),
+        );
+    }
+    let (name, file_name, mime, content) = file;
+    body.extend_from_slice(
+        format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\nContent-Type: {}\r\n\r\n",
+            boundary, name, file_name, mime
+        )
+        .as_bytes(),
+    );
+    body.extend_from_slice(content);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+    (format!("multipart/form-data; boundary={}", boundary), body)
+}
+
+async fn upload_statement(
+    client: &reqwest::Client,
+    cookies: &str,
+    profile_id: &str,
+    account: &str,
+    csv: Vec<u8>,
+) -> Result<Vec<String>, String> {
+    let file_name: String = account
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    let (content_type, body) = multipart_body(
+        &[("profileId", profile_id), ("documentType", "bank_statement")],
+        ("file", &format!("{}.csv", file_name), "text/csv", &csv),
+    );
+
+    let request = client
+        .post(format!("{}/api/statements/upload", get_server_url()))
+        .header(reqwest::header::COOKIE, cookies)
+        .header(reqwest::header::CONTENT_TYPE, content_type)
+        .body(body);
+    let response = transport::send(request)
+        .await
+        .map_err(|e| format!("Upload failed: {}", e))?;
+
+    let status = response.status();
+    let body: Value = response.json().await.unwrap_or_default();
+    if status.is_success() {
+        // Statement ids, so a cancelled import can be rolled back
+        return Ok(body["statementIds"]
+            .as_array()
+            .map(|ids| ids.iter().filter_map(|id| id.as_str().map(String::from)).collect())
+            .unwrap_or_default());
+    }
+    Err(body["message"]
+        .as_str()
+        .map(String::from)
+        .unwrap_or_else(|| format!("Server returned {}", status)))
+}
+
+/// Delete statements uploaded by a cancelled import, returning how many were removed
+async fn delete_statements(client: &reqwest::Client, cookies: &str, ids: &[String]) -> usize {
+    let mut removed = 0;
+    for id in ids {
+        let request = client
+            .delete(format!("{}/api/statements/{}", get_server_url(), id))
+            .header(reqwest::header::COOKIE, cookies);
+        let response = transport::send(request).await;
+        match response {
+            Ok(r) if r.status().is_success() => removed += 1,
+            Ok(r) => eprintln!("Warning: Failed to remove statement {}: {}", id, r.status()),
+            Err(e) => eprintln!("Warning: Failed to remove statement {}: {}", id, e),
+        }
+    }
+    removed
+}
+
+// ---------------------------------------------------------------------------
+// Commands
+// ---------------------------------------------------------------------------
+
+/// List Mint/YNAB/Quicken exports found in the Downloads folder
+#[tauri::command]
+pub async fn detect_import_files() -> Result<Vec<DetectedFile>, String> {
+    tauri::async_runtime::spawn_blocking(detect_files)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Convert an export and return the mapping, per-account summary and sample rows
+#[tauri::command]
+pub async fn preview_import(path: String) -> Result<ImportPreview, String> {
+    let file = PathBuf::from(&path);
+    let converted = tauri::async_runtime::spawn_blocking(move || convert(&file))
+        .await
+        .map_err(|e| e.to_string())??;
+
+    let accounts = group_by_account(&converted.transactions)
+        .into_iter()
+        .map(|(name, txs)| AccountSummary {
+            name: name.to_string(),
+            transactions: txs.len(),
+            first_date: txs.first().map(|t| t.date.clone()),
+            last_date: txs.last().map(|t| t.date.clone()),
+        })
+        .collect();
+
+    Ok(ImportPreview {
+        path,
+        source: converted.source,
+        mapping: converted.mapping,
+        accounts,
+        sample: converted.transactions.iter().take(PREVIEW_ROWS).cloned().collect(),
+        total: converted.transactions.len(),
+        skipped: converted.skipped,
+        warnings: converted.warnings,
+    })
+}
+
+/// Profiles the import can be assigned to
+#[tauri::command]
+pub async fn list_import_profiles(app: AppHandle) -> Result<Value, String> {
+    let cookies = session_cookies(&app)?;
+    let request = reqwest::Client::new()
+        .get(format!("{}/api/profiles", get_server_url()))
+        .header(reqwest::header::COOKIE, cookies);
+    let response = transport::send(request)
+        .await
+        .map_err(|e| format!("Failed to load profiles: {}", e))?;
+    if !response.status().is_success() {
+        return Err("Sign in to Moneywright in the main window first".to_string());
+    }
+    let body: Value = response.json().await.map_err(|e| e.to_string())?;
+    Ok(body["profiles"].clone())
+}
+
+/// Convert an export and upload each account as a statement, emitting `import-progress`
+/// `accounts` limits the upload to the selected account names
+#[tauri::command]
+pub async fn run_import(
+    app: AppHandle,
+    path: String,
+    profile_id: String,
+    accounts: Option<Vec<String>>,
+) -> Result<ImportResult, String> {
+    let cookies = session_cookies(&app)?;
+    let file = PathBuf::from(&path);
+    let title = format!("Importing {}", file.file_name().unwrap_or_default().to_string_lossy());
+    let job = start_job(&app, JobKind::Import, title, true);
+    let result = import(&app, &job, file, &cookies, &profile_id, accounts).await;
+    // No notification for an import the user cancelled themselves
+    let cancelled = job.is_cancelled();
+    job.finish(&result);
+    let body = match &result {
+        Ok(imported) if imported.failed.is_empty() => format!("Imported {} account(s)", imported.uploaded.len()),
+        Ok(imported) => format!(
+            "Imported {} account(s), {} failed",
+            imported.uploaded.len(),
+            imported.failed.len()
+        ),
+        Err(e) => format!("Import failed: {}", e),
+    };
+    if !cancelled {
+        notify(&app, Kind::Imports, "Moneywright import", body).await;
+    }
+    result
+}
+
+async fn import(
+    app: &AppHandle,
+    job: &JobHandle<tauri::Wry>,
+    file: PathBuf,
+    cookies: &str,
+    profile_id: &str,
+    accounts: Option<Vec<String>>,
+) -> Result<ImportResult, String> {
+    let converted = tauri::async_runtime::spawn_blocking(move || convert(&file))
+        .await
+        .map_err(|e| e.to_string())??;
+    if job.is_cancelled() {
+        return Err("Import cancelled".to_string());
+    }
+
+    let groups: Vec<(&str, Vec<&ImportTransaction>)> = group_by_account(&converted.transactions)
+        .into_iter()
+        .filter(|(name, _)| accounts.as_ref().is_none_or(|selected| selected.iter().any(|s| s == name)))
+        .collect();
+    if groups.is_empty() {
+        return Err("No transactions to import".to_string());
+    }
+
+    let client = reqwest::Client::builder()
+        .timeout(UPLOAD_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let total = groups.len();
+    let mut result = ImportResult { uploaded: Vec::new(), failed: Vec::new() };
+    let mut statement_ids = Vec::new();
+
+    // An upload in progress is allowed to finish so its statement can be removed too
+    for (index, (account, txs)) in groups.into_iter().enumerate() {
+        if job.is_cancelled() {
+            let removed = delete_statements(&client, cookies, &statement_ids).await;
+            return Err(format!(
+                "Import cancelled after {} of {} accounts ({} uploaded statements removed)",
+                index, total, removed
+            ));
+        }
+        job.progress(Some(index as f64 / total as f64), Some(account.to_string()));
+        let progress = |status, message| ImportProgress {
+            account: account.to_string(),
+            index,
+            total,
+            status,
+            message,
+        };
+        let _ = events::emit(app, Event::ImportProgress(&progress("uploading", None)));
+
+        let outcome = match to_statement_csv(&txs) {
+            Ok(csv) => upload_statement(&client, cookies, profile_id, account, csv).await,
+            Err(e) => Err(e),
+        };
+        match outcome {
+            Ok(ids) => {
+                statement_ids.extend(ids);
+                result.uploaded.push(account.to_string());
+                let _ = events::emit(app, Event::ImportProgress(&progress("done", None)));
+            }
+            Err(e) => {
+                result.failed.push(account.to_string());
+                let _ = events::emit(app, Event::ImportProgress(&progress("error", Some(e))));
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// Emit `import-files-detected` whenever the set of exports in Downloads changes,
+/// for as long as the import window is open. The folder is watched for changes and
+/// only rescanned after one.
+fn start_download_watcher(app: AppHandle) {
+    let Some(dir) = dirs::download_dir() else { return };
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        if event.is_ok_and(|e| !e.kind.is_access()) {
+            let _ = tx.send(());
+        }
+    });
+    let mut watcher = match watcher.and_then(|mut w| w.watch(&dir, RecursiveMode::NonRecursive).map(|_| w)) {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            eprintln!("Warning: Failed to watch {}: {}", dir.display(), e);
+            return;
+        }
+    };
+
+    tauri::async_runtime::spawn(async move {
+        // The window lists the files itself when it opens
+        let mut known = tauri::async_runtime::spawn_blocking(detect_files).await.unwrap_or_default();
+        loop {
+            match tokio::time::timeout(WINDOW_CHECK_INTERVAL, rx.recv()).await {
+                Ok(Some(())) => {}
+                Ok(None) => break,
+                Err(_) => {
+                    if app.get_webview_window(WINDOW_LABEL).is_none() {
+                        break;
+                    }
+                    continue;
+                }
+            }
+            tokio::time::sleep(SETTLE_DELAY).await;
+            while rx.try_recv().is_ok() {}
+            if app.get_webview_window(WINDOW_LABEL).is_none() {
+                break;
+            }
+            let files = tauri::async_runtime::spawn_blocking(detect_files).await.unwrap_or_default();
+            if files != known {
+                let _ = events::emit_to(&app, WINDOW_LABEL, Event::ImportFilesDetected(&files));
+                known = files;
+            }
+        }
+        let _ = watcher.unwatch(&dir);
+    });
+}
+
+/// Open the import window, optionally preselecting a file
+pub fn open_import_window(app: &AppHandle, path: Option<String>) {
+    let already_open = app.get_webview_window(WINDOW_LABEL).is_some();
+    let initial = serde_json::to_string(&path).unwrap_or_else(|_| "null".to_string());
+
+    // Static UI; file and account names are inserted with escaping on the JS side
+    let script = r#"
+        const tauriApi = window.__TAURI__;
+        const initialPath = __INITIAL_PATH__;
+
+        document.documentElement.innerHTML = `
+<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <title>Import from Mint, YNAB or Quicken</title>
+    <style>
+        __BASE_STYLE__
+        .layout { flex: 1; display: flex; min-height: 0; }
+        #files { width: 260px; border-right: 1px solid rgba(255, 255, 255, 0.06); overflow-y: auto; }
+        .file { padding: 10px 16px; border-bottom: 1px solid rgba(255, 255, 255, 0.04); cursor: pointer; }
+        .file:hover, .file.selected { background: #0a0a0a; }
+        .file .src { font-size: 11px; text-transform: uppercase; letter-spacing: 0.04em; color: #10b981; }
+        .drop-hint { padding: 16px; color: #52525b; line-height: 1.5; }
+        #preview { flex: 1; overflow-y: auto; padding: 16px; }
+        h2 { font-size: 15px; margin: 16px 0 8px; }
+        h2:first-child { margin-top: 0; }
+        table { width: 100%; border-collapse: collapse; }
+        th, td { text-align: left; padding: 5px 8px 5px 0; border-bottom: 1px solid rgba(255, 255, 255, 0.04); }
+        th { color: #71717a; font-weight: 500; }
+        td.amount { text-align: right; font-family: ui-monospace, SFMono-Regular, Menlo, Consolas, monospace; }
+        .warnings { margin-top: 8px; }
+        .actions { display: flex; gap: 10px; align-items: center; margin-top: 16px; }
+    </style>
+</head>
+<body>
+    <div class="toolbar">
+        <span class="muted">Exports in your Downloads folder are detected automatically. You can also drop a file here.</span>
+    </div>
+    <div class="layout">
+        <div id="files" role="listbox" aria-label="Detected export files"></div>
+        <div id="preview"><div class="empty-state">Select or drop an export file</div></div>
+    </div>
+</body>
+</html>`;
+
+        const $ = id => document.getElementById(id);
+        let current = null;
+
+        function formatAmount(cents) {
+            return (cents / 100).toFixed(2);
+        }
+
+        function renderFiles(files) {
+            if (files.length === 0) {
+                $('files').innerHTML = '<div class="drop-hint">No Mint, YNAB or Quicken exports found in Downloads.</div>';
+                return;
+            }
+            $('files').innerHTML = files.map(f =>
+                '<div class="file' + (current === f.path ? ' selected' : '') + '" role="option" tabindex="0" data-path="' + escapeHtml(f.path) + '">' +
+                '<div class="src">' + escapeHtml(f.source) + '</div><div>' + escapeHtml(f.name) + '</div></div>'
+            ).join('');
+            document.querySelectorAll('.file').forEach(el => {
+                el.onclick = () => preview(el.dataset.path);
+                el.onkeydown = (e) => { if (e.key === 'Enter') preview(el.dataset.path); };
+            });
+        }
+
+        async function preview(path) {
+            current = path;
+            $('preview').innerHTML = '<div class="empty-state">Converting...</div>';
+            let p;
+            try {
+                p = await tauriApi.core.invoke('preview_import', { path });
+            } catch (e) {
+                $('preview').innerHTML = '<div class="empty-state fail">' + escapeHtml(e) + '</div>';
+                return;
+            }
+            let profiles = [];
+            let profileError = null;
+            try {
+                profiles = await tauriApi.core.invoke('list_import_profiles');
+            } catch (e) {
+                profileError = String(e);
+            }
+
+            $('preview').innerHTML =
+                '<h2>' + escapeHtml(p.source.toUpperCase()) + ' export · ' + p.total + ' transactions</h2>' +
+                (p.warnings.length ? '<div class="warnings warn">' + p.warnings.map(escapeHtml).join('<br>') + '</div>' : '') +
+                '<h2>Column mapping</h2><table><tr><th>Export</th><th>Moneywright</th></tr>' +
+                p.mapping.map(m => '<tr><td class="mono">' + escapeHtml(m.source) + '</td><td>' + escapeHtml(m.target) + '</td></tr>').join('') +
+                '</table><h2>Accounts</h2><table><tr><th></th><th>Account</th><th>Transactions</th><th>Range</th><th>Status</th></tr>' +
+                p.accounts.map(a =>
+                    '<tr><td><input type="checkbox" class="acct" checked data-name="' + escapeHtml(a.name) + '" aria-label="Import ' + escapeHtml(a.name) + '"></td>' +
+                    '<td>' + escapeHtml(a.name) + '</td><td>' + a.transactions + '</td>' +
+                    '<td class="muted">' + escapeHtml(a.first_date || '') + ' – ' + escapeHtml(a.last_date || '') + '</td>' +
+                    '<td class="status" data-name="' + escapeHtml(a.name) + '"></td></tr>'
+                ).join('') +
+                '</table><h2>Sample</h2><table><tr><th>Date</th><th>Description</th><th>Category</th><th style="text-align:right">Amount</th></tr>' +
+                p.sample.map(t =>
+                    '<tr><td>' + escapeHtml(t.date) + '</td><td>' + escapeHtml(t.description) + '</td><td class="muted">' + escapeHtml(t.category || '') + '</td>' +
+                    '<td class="amount ' + (t.amount_cents < 0 ? 'fail' : 'pass') + '">' + formatAmount(t.amount_cents) + '</td></tr>'
+                ).join('') +
+                '</table><div class="actions">' +
+                (profileError
+                    ? '<span class="warn">' + escapeHtml(profileError) + '</span>'
+                    : '<select id="profile" aria-label="Profile">' + profiles.map(pr => '<option value="' + escapeHtml(pr.id) + '">' + escapeHtml(pr.name) + '</option>').join('') + '</select>' +
+                      '<button id="importBtn" class="primary">Import</button>' +
+                      '<button id="cancelBtn" style="display: none">Cancel</button>') +
+                '<span id="result" class="muted" role="status" aria-live="polite"></span></div>';
+
+            renderFiles(lastFiles);
+            if (!profileError) {
+                $('importBtn').onclick = () => runImport(path);
+                $('cancelBtn').onclick = () => {
+                    if (importJob != null) tauriApi.core.invoke('cancel_job', { id: importJob });
+                };
+            }
+        }
+
+        async function runImport(path) {
+            const accounts = Array.from(document.querySelectorAll('.acct')).filter(c => c.checked).map(c => c.dataset.name);
+            $('importBtn').disabled = true;
+            $('cancelBtn').style.display = '';
+            $('result').textContent = 'Importing...';
+            try {
+                const r = await tauriApi.core.invoke('run_import', { path, profileId: $('profile').value, accounts });
+                $('result').textContent = r.uploaded.length + ' imported' + (r.failed.length ? ', ' + r.failed.length + ' failed' : '') + '. Statements are processed in the background.';
+            } catch (e) {
+                $('result').textContent = String(e);
+            }
+            $('importBtn').disabled = false;
+            $('cancelBtn').style.display = 'none';
+            importJob = null;
+        }
+
+        let lastFiles = [];
+        let importJob = null;
+        tauriApi.event.listen('job-progress', (e) => {
+            if (e.payload.kind === 'import' && e.payload.state === 'running') importJob = e.payload.id;
+        });
+        tauriApi.event.listen('import-files-detected', (e) => { lastFiles = e.payload; renderFiles(lastFiles); });
+        tauriApi.event.listen('import-progress', (e) => {
+            const p = e.payload;
+            document.querySelectorAll('.status').forEach(el => {
+                if (el.dataset.name === p.account) {
+                    el.className = 'status ' + (p.status === 'done' ? 'pass' : p.status === 'error' ? 'fail' : 'muted');
+                    el.textContent = p.status === 'error' ? (p.message || 'Failed') : p.status === 'done' ? 'Uploaded' : 'Uploading...';
+                }
+            });
+        });
+        tauriApi.event.listen('tauri://drag-drop', (e) => {
+            const paths = e.payload && e.payload.paths;
+            if (paths && paths.length) preview(paths[0]);
+        });
+        tauriApi.event.listen('import-preview-requested', (e) => preview(e.payload));
+
+        tauriApi.core.invoke('detect_import_files').then(files => { lastFiles = files; renderFiles(files); });
+        if (initialPath) preview(initialPath);
+    "#
+    .replace("__INITIAL_PATH__", &initial);
+
+    if already_open {
+        if let Some(path) = path {
+            let _ = events::emit_to(app, WINDOW_LABEL, Event::ImportPreviewRequested(&path));
+        }
+    }
+
+    open_injected_window(app, WINDOW_LABEL, "Import from Mint, YNAB or Quicken", (960.0, 620.0), true, &script);
+    if !already_open {
+        start_download_watcher(app.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Write `content` to a file of this name in a fresh temp folder
+    fn temp_file(name: &str, content: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("moneywright-import-test-{}-{}", std::process::id(), name));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn parses_amounts_into_cents() {
+        assert_eq!(parse_amount("12.30"), Some(1230));
+        assert_eq!(parse_amount("-12.30"), Some(-1230));
+        assert_eq!(parse_amount("$1,234.56"), Some(123456));
+        assert_eq!(parse_amount("$-12.30"), Some(-1230));
+        assert_eq!(parse_amount("-$12.30"), Some(-1230));
+        assert_eq!(parse_amount("(12.30)"), Some(-1230));
+        assert_eq!(parse_amount("+7"), Some(700));
+        assert_eq!(parse_amount(".5"), Some(50));
+        assert_eq!(parse_amount(" 12.30 € "), Some(1230));
+        // f64 would make 0.29 * 100 = 28.999...
+        assert_eq!(parse_amount("0.29"), Some(29));
+        assert_eq!(parse_amount("1.005"), Some(101));
+        assert_eq!(parse_amount("-1.004"), Some(-100));
+    }
+
+    #[test]
+    fn rejects_malformed_amounts() {
+        for raw in ["", "$", ".", "abc", "--12", "12-", "1.2.3", "12a", "- 1 2"] {
+            assert_eq!(parse_amount(raw), None, "{:?}", raw);
+        }
+        assert_eq!(parse_amount("99999999999999999999"), None);
+    }
+
+    #[test]
+    fn parses_export_dates() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d);
+        assert_eq!(parse_date("2024-01-15"), date(2024, 1, 15));
+        assert_eq!(parse_date("1/15/2024"), date(2024, 1, 15));
+        assert_eq!(parse_date("01/15/24"), date(2024, 1, 15));
+        assert_eq!(parse_date("1/15'24"), date(2024, 1, 15));
+        assert_eq!(parse_date("1/ 5/2024"), date(2024, 1, 5));
+        assert_eq!(parse_date("15.01.2024"), date(2024, 1, 15));
+        assert_eq!(parse_date("yesterday"), None);
+    }
+
+    #[test]
+    fn sniffs_formats_from_the_header() {
+        let mint = temp_file(
+            "mint.csv",
+            "\u{feff}\"Date\",\"Description\",\"Original Description\",\"Amount\",\"Transaction Type\"\n",
+        );
+        assert!(sniff(&mint) == Some(ImportSource::Mint));
+        let ynab = temp_file("ynab.csv", "Account,Flag,Date,Payee,Outflow,Inflow\n");
+        assert!(sniff(&ynab) == Some(ImportSource::Ynab));
+        let json = temp_file("budget.json", "{\"budget\": {\"transactions\": []}}");
+        assert!(sniff(&json) == Some(ImportSource::Ynab));
+        let other = temp_file("other.csv", "id,name\n1,a\n");
+        assert!(sniff(&other).is_none());
+        assert!(sniff(Path::new("/nonexistent/file.qif")) == Some(ImportSource::Quicken));
+    }
+
+    #[test]
+    fn sniff_reads_only_the_head() {
+        let body = "x".repeat(SNIFF_BYTES as usize * 2);
+        let big = temp_file("big.json", &format!("{}\"budget\"", body));
+        assert!(sniff(&big).is_none());
+    }
+
+    #[test]
+    fn converts_mint_csv() {
+        let path = temp_file(
+            "mint-export.csv",
+            "Date,Description,Original Description,Amount,Transaction Type,Category,Account Name,Labels,Notes\n\
+             1/15/2024,Coffee,COFFEE SHOP,4.50,debit,Food,Checking,,\n\
+             1/16/2024,Salary,ACME PAYROLL,\"2,000.00\",credit,Income,Checking,,January\n\
+             bad,Broken,,1.00,debit,,Checking,,\n",
+        );
+        let converted = convert(&path).unwrap();
+        assert!(converted.source == ImportSource::Mint);
+        assert_eq!(converted.skipped, 1);
+        assert_eq!(converted.transactions.len(), 2);
+        let coffee = &converted.transactions[0];
+        assert_eq!(coffee.date, "2024-01-15");
+        assert_eq!(coffee.amount_cents, -450);
+        assert_eq!(coffee.category.as_deref(), Some("Food"));
+        assert_eq!(converted.transactions[1].amount_cents, 200000);
+        assert_eq!(converted.transactions[1].notes.as_deref(), Some("January"));
+    }
+
+    #[test]
+    fn converts_ynab_csv() {
+        let path = temp_file(
+            "ynab-register.csv",
+            "Account,Flag,Date,Payee,Category Group/Category,Memo,Outflow,Inflow,Cleared\n\
+             Checking,,01/15/2024,Grocer,Everyday: Food,,$12.30,$0.00,Cleared\n\
+             Checking,,01/16/2024,Transfer : Savings,,,$100.00,$0.00,Cleared\n\
+             Savings,,01/16/2024,Transfer : Checking,,,$0.00,$100.00,Cleared\n",
+        );
+        let converted = convert(&path).unwrap();
+        assert!(converted.source == ImportSource::Ynab);
+        let amounts: Vec<i64> = converted.transactions.iter().map(|t| t.amount_cents).collect();
+        assert_eq!(amounts, vec![-1230, -10000, 10000]);
+        assert_eq!(converted.transactions[0].category.as_deref(), Some("Everyday: Food"));
+        assert_eq!(converted.warnings.len(), 1);
+    }
+
+    #[test]
+    fn converts_ynab_json() {
+        let path = temp_file(
+            "ynab-budget.json",
+            r#"{"data": {"budget": {
+                "accounts": [{"id": "a1", "name": "Checking"}],
+                "payees": [{"id": "p1", "name": "Grocer"}],
+                "categories": [{"id": "c1", "name": "Food"}],
+                "transactions": [
+                    {"date": "2024-01-15", "amount": -12300, "account_id": "a1", "payee_id": "p1", "category_id": "c1"},
+                    {"date": "2024-01-16", "amount": 5000, "account_id": "a1", "deleted": true},
+                    {"date": "2024-01-17", "account_id": "a1"}
+                ]
+            }}}"#,
+        );
+        let converted = convert(&path).unwrap();
+        assert_eq!(converted.transactions.len(), 1);
+        assert_eq!(converted.skipped, 1);
+        let tx = &converted.transactions[0];
+        assert_eq!(tx.amount_cents, -1230);
+        assert_eq!(tx.description, "Grocer");
+        assert_eq!(tx.account, "Checking");
+        assert_eq!(tx.category.as_deref(), Some("Food"));
+    }
+
+    #[test]
+    fn converts_qif() {
+        let path = temp_file(
+            "quicken.qif",
+            "!Account\nNVisa\nTCCard\n^\n!Type:CCard\nD1/15'24\nT-1,234.56\nPStore\nLShopping\n^\nD1/16'24\nT50.00\nL[Checking]\nMPayment\n^\nDbad\nT1.00\n^\n!Type:Invst\nD1/17'24\n^\n",
+        );
+        let converted = convert(&path).unwrap();
+        assert!(converted.source == ImportSource::Quicken);
+        assert_eq!(converted.transactions.len(), 2);
+        assert_eq!(converted.skipped, 1);
+        let purchase = &converted.transactions[0];
+        assert_eq!(purchase.account, "Visa");
+        assert_eq!(purchase.amount_cents, -123456);
+        assert_eq!(purchase.description, "Store");
+        assert_eq!(converted.transactions[1].category.as_deref(), Some("Transfer"));
+        assert!(converted.warnings.iter().any(|w| w.contains("Invst")));
+    }
+
+    #[test]
+    fn writes_statement_csv_with_exact_cents() {
+        let tx = |amount_cents| ImportTransaction {
+            date: "2024-01-15".to_string(),
+            description: "Coffee, large".to_string(),
+            amount_cents,
+            category: None,
+            account: "Checking".to_string(),
+            notes: None,
+        };
+        let (spent, refund) = (tx(-5), tx(123456));
+        let csv = String::from_utf8(to_statement_csv(&[&spent, &refund]).unwrap()).unwrap();
+        assert_eq!(
+            csv,
+            "Date,Description,Amount,Category,Notes\n\
+             2024-01-15,\"Coffee, large\",-0.05,,\n\
+             2024-01-15,\"Coffee, large\",1234.56,,\n"
+        );
+    }
+}
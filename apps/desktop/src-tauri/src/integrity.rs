@@ -0,0 +1,121 @@
+// Checking the bundled server binary before it's started
+//
+// The build records the SHA-256 and size of the sidecar it bundles (see build.rs), and
+// `start_server` compares the installed binary with them before running it. One that
+// was truncated by a botched update or replaced by something else is refused, and the
+// repair window says what's wrong instead of the server failing in odd ways.
+//
+// On macOS the bundler signs the sidecar, which changes its hash, so there the code
+// signature is checked instead: `codesign --verify` against a requirement naming the
+// team the release was built for (APPLE_TEAM_ID at build time), since a bare verify
+// also accepts an ad-hoc signature anyone can make. Builds without a team identity
+// fall back to the hash. Hashing takes a moment for a binary this size, so a passed check is kept
+// for the file's size and modification time until the app quits. Development builds
+// bundle no sidecar and have nothing to compare with.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::doctor::sha256_file;
+use crate::server::sidecar_path;
+
+const EXPECTED_SHA256: &str = env!("MONEYWRIGHT_SIDECAR_SHA256");
+const EXPECTED_SIZE: &str = env!("MONEYWRIGHT_SIDECAR_SIZE");
+
+/// The last binary that passed, as (path, size, modified)
+static VERIFIED: Mutex<Option<(PathBuf, u64, SystemTime)>> = Mutex::new(None);
+
+#[cfg(target_os = "macos")]
+enum Signature {
+    Valid,
+    /// Nothing to check against, the hash decides
+    Unsigned,
+    Invalid(String),
+}
+
+#[cfg(target_os = "macos")]
+const TEAM_ID: &str = env!("MONEYWRIGHT_APPLE_TEAM_ID");
+
+#[cfg(target_os = "macos")]
+fn check_signature(path: &Path) -> Signature {
+    if TEAM_ID.is_empty() {
+        return Signature::Unsigned;
+    }
+    let requirement = format!("=anchor apple generic and certificate leaf[subject.OU] = \"{}\"", TEAM_ID);
+    let output = match std::process::Command::new("codesign")
+        .arg("--verify")
+        .arg("--strict")
+        .arg("-R")
+        .arg(&requirement)
+        .arg(path)
+        .output()
+    {
+        Ok(output) => output,
+        // Without codesign the hash has to do
+        Err(_) => return Signature::Unsigned,
+    };
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    // A release with a team identity signs the sidecar, so an unsigned one was swapped too
+    if output.status.success() {
+        Signature::Valid
+    } else {
+        Signature::Invalid(stderr)
+    }
+}
+
+/// Check the server binary is the one this version shipped with
+pub fn verify_sidecar(path: &Path) -> Result<(), String> {
+    let meta = fs::metadata(path).map_err(|e| format!("The server binary {} is missing: {}", path.display(), e))?;
+    if meta.len() == 0 {
+        return Err(format!("The server binary {} is empty (truncated install or update?)", path.display()));
+    }
+    let key = (path.to_path_buf(), meta.len(), meta.modified().unwrap_or(UNIX_EPOCH));
+    if VERIFIED.lock().unwrap_or_else(|e| e.into_inner()).as_ref() == Some(&key) {
+        return Ok(());
+    }
+
+    #[cfg(target_os = "macos")]
+    match check_signature(path) {
+        Signature::Valid => {
+            *VERIFIED.lock().unwrap_or_else(|e| e.into_inner()) = Some(key);
+            return Ok(());
+        }
+        Signature::Invalid(detail) => {
+            return Err(format!("The server binary isn't validly signed by the Moneywright team, it may have been modified: {}", detail));
+        }
+        Signature::Unsigned => {}
+    }
+
+    if EXPECTED_SHA256.is_empty() {
+        return Ok(());
+    }
+    if let Ok(size) = EXPECTED_SIZE.parse::<u64>() {
+        if size != meta.len() {
+            return Err(format!(
+                "The server binary is {} bytes instead of {}, it was truncated or replaced",
+                meta.len(),
+                size
+            ));
+        }
+    }
+    let hash = sha256_file(path).map_err(|e| format!("Cannot read the server binary {}: {}", path.display(), e))?;
+    if hash != EXPECTED_SHA256 {
+        return Err(format!(
+            "The server binary doesn't match the one Moneywright {} shipped with, it may have been modified (sha256 {})",
+            env!("CARGO_PKG_VERSION"),
+            hash
+        ));
+    }
+    *VERIFIED.lock().unwrap_or_else(|e| e.into_inner()) = Some(key);
+    Ok(())
+}
+
+/// What's wrong with the bundled server binary, for the repair window
+pub fn bundled_problem() -> Option<String> {
+    if cfg!(debug_assertions) {
+        return None;
+    }
+    let path = sidecar_path()?;
+    verify_sidecar(&path).err()
+}
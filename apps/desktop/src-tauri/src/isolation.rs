@@ -0,0 +1,111 @@
+// Keeping OS users apart on a shared computer
+//
+// Each OS account has its own data dir (the app data dir), but ports are machine-wide:
+// the first user's Moneywright takes the default port and anyone else's would fail to
+// start on it. So at startup the main server's port is settled per user: the default
+// one if it's free or held by this user's own leftover server, otherwise a port from
+// USER_PORTS, saved in the data dir so the web session survives restarts.
+//
+// A custom data location may also be shared by mistake (e.g. a folder under
+// /Users/Shared); a live session marker from another process means two instances
+// would write to the same database, which is reported rather than silently allowed.
+
+use std::fs;
+use std::ops::RangeInclusive;
+use std::path::Path;
+use std::sync::Mutex;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind, Users};
+use tauri::AppHandle;
+use crate::logs::{log_line, SharedLogStore};
+use crate::ports::{self, Holder};
+use crate::server::SERVER_PORT;
+
+/// The chosen port, when not the default one
+const PORT_FILE: &str = "server-port";
+/// Ports for users who can't have the default one (profiles use SERVER_PORT + 1..=100)
+const USER_PORTS: RangeInclusive<u16> = SERVER_PORT + 200..=SERVER_PORT + 299;
+
+/// Messages from the checks, logged once the log store exists
+static NOTICES: Mutex<Vec<(String, &'static str)>> = Mutex::new(Vec::new());
+
+fn notice(message: String, level: &'static str) {
+    println!("{}", message);
+    if let Ok(mut notices) = NOTICES.lock() {
+        notices.push((message, level));
+    }
+}
+
+fn saved_port(data_dir: &Path) -> Option<u16> {
+    fs::read_to_string(data_dir.join(PORT_FILE)).ok()?.trim().parse().ok()
+}
+
+/// Port of this user's main server: the default unless another user holds it
+pub fn resolve_main_port(data_dir: &Path) -> u16 {
+    if let Some(port) = saved_port(data_dir) {
+        if !ports::holder(port).is_foreign() {
+            return port;
+        }
+    }
+    let holder = ports::holder(SERVER_PORT);
+    if !holder.is_foreign() {
+        let _ = fs::remove_file(data_dir.join(PORT_FILE));
+        return SERVER_PORT;
+    }
+
+    let Some(port) = USER_PORTS.clone().find(|port| ports::holder(*port) == Holder::Free) else {
+        notice(
+            format!(
+                "{}, and no port between {} and {} is free; the server can't start for this user",
+                holder.describe(SERVER_PORT),
+                USER_PORTS.start(),
+                USER_PORTS.end()
+            ),
+            "error",
+        );
+        return SERVER_PORT;
+    };
+    if let Err(e) = fs::write(data_dir.join(PORT_FILE), port.to_string()) {
+        eprintln!("Warning: Failed to save server port: {}", e);
+    }
+    notice(
+        format!("{} (probably their Moneywright); this account's server uses port {} instead", holder.describe(SERVER_PORT), port),
+        "info",
+    );
+    port
+}
+
+/// Warn when another running instance already uses this data dir
+pub fn check_data_dir(data_dir: &Path) {
+    let Some(pid) = crate::crash::live_session(data_dir) else {
+        return;
+    };
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[Pid::from_u32(pid)]),
+        true,
+        ProcessRefreshKind::nothing().with_user(UpdateKind::Always),
+    );
+    let owner = system
+        .process(Pid::from_u32(pid))
+        .and_then(|p| p.user_id())
+        .and_then(|uid| Users::new_with_refreshed_list().get_user_by_id(uid).map(|u| u.name().to_string()));
+    let by = owner.map(|name| format!(" of user {}", name)).unwrap_or_default();
+    notice(
+        format!(
+            "The data directory {} is already in use by another Moneywright (pid {}{}). Each OS user needs \
+             their own data directory; running both against the same database can corrupt it",
+            data_dir.display(),
+            pid,
+            by
+        ),
+        "error",
+    );
+}
+
+/// Log what the checks found, once logging is up
+pub async fn log_notices(app: &AppHandle, log_store: &SharedLogStore) {
+    let notices = NOTICES.lock().map(|mut n| std::mem::take(&mut *n)).unwrap_or_default();
+    for (message, level) in notices {
+        log_line(app, log_store, message, level).await;
+    }
+}
@@ -0,0 +1,332 @@
+// Job center: long-running shell tasks (imports, backups, updates, migrations, exports)
+//
+// Tasks register with `start_job` and report through the returned handle. Every
+// change is emitted as a `job-progress` event carrying the full `JobInfo`, so the
+// UI can show a single activity indicator without knowing about each subsystem.
+// Running jobs also count as in flight for the scheduler.
+//
+// Cancelable tasks watch their `CancelToken` and must remove anything half-written
+// before returning an error. Ended jobs are also written to the task history.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::Serialize;
+use tauri::{AppHandle, Runtime};
+use tokio::sync::watch;
+use crate::events::{self, Event};
+use crate::history::{self, HistoryEntry, Outcome};
+use crate::scheduler::{track_in_flight, InFlightGuard};
+
+/// Finished jobs kept for `list_jobs`
+const MAX_FINISHED: usize = 20;
+/// Progress changes smaller than this are not emitted
+const PROGRESS_STEP: f64 = 0.01;
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+static JOBS: Mutex<Vec<JobEntry>> = Mutex::new(Vec::new());
+
+#[derive(Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobKind {
+    Import,
+    Backup,
+    Update,
+    Migration,
+    Export,
+    Recovery,
+}
+
+impl JobKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            JobKind::Import => "import",
+            JobKind::Backup => "backup",
+            JobKind::Update => "update",
+            JobKind::Migration => "migration",
+            JobKind::Export => "export",
+            JobKind::Recovery => "recovery",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobState {
+    Running,
+    /// Cancel requested, waiting for the task to stop
+    Cancelling,
+    Done,
+    Failed,
+    Cancelled,
+}
+
+impl JobState {
+    fn is_finished(self) -> bool {
+        matches!(self, JobState::Done | JobState::Failed | JobState::Cancelled)
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct JobInfo {
+    pub id: u64,
+    pub kind: JobKind,
+    pub title: String,
+    pub state: JobState,
+    /// 0.0 to 1.0, or None when the task can't tell
+    pub progress: Option<f64>,
+    pub message: Option<String>,
+    pub cancelable: bool,
+    pub started: u64,
+    pub finished: Option<u64>,
+}
+
+struct JobEntry {
+    info: JobInfo,
+    cancel: watch::Sender<bool>,
+    /// Progress in the last emitted event, for throttling
+    emitted_progress: Option<f64>,
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn unix_now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn jobs() -> std::sync::MutexGuard<'static, Vec<JobEntry>> {
+    JOBS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Update a job and return its info if the change should be emitted
+fn update_job(id: u64, force: bool, f: impl FnOnce(&mut JobInfo)) -> Option<JobInfo> {
+    let mut jobs = jobs();
+    let entry = jobs.iter_mut().find(|j| j.info.id == id)?;
+    f(&mut entry.info);
+
+    let moved = match (entry.emitted_progress, entry.info.progress) {
+        (Some(last), Some(now)) => (now - last).abs() >= PROGRESS_STEP || now >= 1.0,
+        (last, now) => last != now,
+    };
+    if !force && !moved {
+        return None;
+    }
+    entry.emitted_progress = entry.info.progress;
+    let info = entry.info.clone();
+
+    if info.state.is_finished() {
+        // Drop the oldest finished jobs beyond the limit
+        let finished = jobs.iter().filter(|j| j.info.state.is_finished()).count();
+        if finished > MAX_FINISHED {
+            let mut excess = finished - MAX_FINISHED;
+            jobs.retain(|j| {
+                if excess > 0 && j.info.state.is_finished() {
+                    excess -= 1;
+                    false
+                } else {
+                    true
+                }
+            });
+        }
+    }
+    Some(info)
+}
+
+/// Cancellation signal for a job, cheap to clone into blocking tasks
+#[derive(Clone)]
+pub struct CancelToken(watch::Receiver<bool>);
+
+impl CancelToken {
+    pub fn is_cancelled(&self) -> bool {
+        *self.0.borrow()
+    }
+
+    /// Resolves once the job is cancelled (never, if it can't be)
+    pub async fn cancelled(&self) {
+        let mut rx = self.0.clone();
+        if rx.wait_for(|cancelled| *cancelled).await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    }
+}
+
+/// A registered job; finishing or dropping it marks the job as ended
+pub struct JobHandle<R: Runtime> {
+    id: u64,
+    app: AppHandle<R>,
+    cancel: CancelToken,
+    finished: bool,
+    /// "scheduled" or "manual", for the task history
+    trigger: &'static str,
+    started_ms: u64,
+    _in_flight: InFlightGuard,
+}
+
+impl<R: Runtime> JobHandle<R> {
+    fn emit(&self, info: Option<JobInfo>) {
+        if let Some(info) = info {
+            let _ = events::emit(&self.app, Event::JobProgress(&info));
+        }
+    }
+
+    fn record(&self, info: &JobInfo) {
+        let outcome = match info.state {
+            JobState::Done => Outcome::Done,
+            JobState::Cancelled => Outcome::Cancelled,
+            _ => Outcome::Failed,
+        };
+        let finished_ms = unix_now_ms();
+        history::record(HistoryEntry {
+            kind: info.kind.as_str().to_string(),
+            title: info.title.clone(),
+            trigger: self.trigger.to_string(),
+            started: info.started,
+            finished: finished_ms / 1000,
+            duration_ms: finished_ms.saturating_sub(self.started_ms),
+            outcome,
+            detail: info.message.clone().filter(|_| outcome != Outcome::Done),
+        });
+    }
+
+    /// Report progress (0.0 to 1.0) and an optional status line
+    pub fn progress(&self, progress: Option<f64>, message: Option<String>) {
+        let info = update_job(self.id, message.is_some(), |job| {
+            job.progress = progress.map(|p| p.clamp(0.0, 1.0));
+            if message.is_some() {
+                job.message = message;
+            }
+        });
+        self.emit(info);
+    }
+
+    /// True once the user asked to cancel this job
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    /// Resolves once the user asks to cancel this job
+    pub async fn cancelled(&self) {
+        self.cancel.cancelled().await
+    }
+
+    pub fn token(&self) -> CancelToken {
+        self.cancel.clone()
+    }
+
+    /// Mark the job as ended with the task's outcome
+    pub fn finish<T>(mut self, result: &Result<T, String>) {
+        self.finished = true;
+        let state = match result {
+            Ok(_) => JobState::Done,
+            Err(_) if self.is_cancelled() => JobState::Cancelled,
+            Err(_) => JobState::Failed,
+        };
+        let message = result.as_ref().err().cloned();
+        let info = update_job(self.id, true, |job| {
+            job.state = state;
+            job.finished = Some(unix_now());
+            if state == JobState::Done {
+                job.progress = Some(1.0);
+            }
+            if message.is_some() {
+                job.message = message;
+            }
+        });
+        if let Some(info) = &info {
+            self.record(info);
+        }
+        self.emit(info);
+    }
+}
+
+impl<R: Runtime> Drop for JobHandle<R> {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        let info = update_job(self.id, true, |job| {
+            job.state = JobState::Failed;
+            job.finished = Some(unix_now());
+            job.message = Some("Interrupted".to_string());
+        });
+        if let Some(info) = &info {
+            self.record(info);
+        }
+        self.emit(info);
+    }
+}
+
+/// Register a long-running task
+pub fn start_job<R: Runtime>(
+    app: &AppHandle<R>,
+    kind: JobKind,
+    title: impl Into<String>,
+    cancelable: bool,
+) -> JobHandle<R> {
+    let id = NEXT_ID.fetch_add(1, Ordering::SeqCst);
+    let (cancel, cancel_rx) = watch::channel(false);
+    let info = JobInfo {
+        id,
+        kind,
+        title: title.into(),
+        state: JobState::Running,
+        progress: None,
+        message: None,
+        cancelable,
+        started: unix_now(),
+        finished: None,
+    };
+    jobs().push(JobEntry {
+        info: info.clone(),
+        cancel,
+        emitted_progress: None,
+    });
+    let _ = events::emit(app, Event::JobProgress(&info));
+
+    JobHandle {
+        id,
+        app: app.clone(),
+        cancel: CancelToken(cancel_rx),
+        finished: false,
+        trigger: history::current_trigger(),
+        started_ms: unix_now_ms(),
+        _in_flight: track_in_flight(),
+    }
+}
+
+/// Running and recently finished jobs, oldest first
+#[tauri::command]
+pub async fn list_jobs() -> Result<Vec<JobInfo>, String> {
+    Ok(jobs().iter().map(|j| j.info.clone()).collect())
+}
+
+/// Ask a cancelable job to stop
+#[tauri::command]
+pub async fn cancel_job(app: AppHandle, id: u64) -> Result<(), String> {
+    let info = {
+        let mut jobs = jobs();
+        let entry = jobs
+            .iter_mut()
+            .find(|j| j.info.id == id)
+            .ok_or_else(|| format!("No job with id {}", id))?;
+        if entry.info.state != JobState::Running {
+            return Err("Job is not running".to_string());
+        }
+        if !entry.info.cancelable {
+            return Err(format!("{} can't be cancelled", entry.info.title));
+        }
+        entry.cancel.send_replace(true);
+        entry.info.state = JobState::Cancelling;
+        entry.info.clone()
+    };
+    let _ = events::emit(&app, Event::JobProgress(&info));
+    Ok(())
+}
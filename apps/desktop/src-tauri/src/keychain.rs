@@ -0,0 +1,34 @@
+// OS keychain access (macOS Keychain, Windows Credential Manager, Linux kernel keyring)
+
+use keyring::Entry;
+
+/// Service name all Moneywright secrets are stored under
+const SERVICE: &str = "com.moneywright.desktop";
+
+fn entry(key: &str) -> Result<Entry, String> {
+    Entry::new(SERVICE, key).map_err(|e| format!("Keychain unavailable: {}", e))
+}
+
+/// Read a secret, returning None if it doesn't exist
+pub fn get_secret(key: &str) -> Result<Option<String>, String> {
+    match entry(key)?.get_password() {
+        Ok(secret) => Ok(Some(secret)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(format!("Failed to read {} from keychain: {}", key, e)),
+    }
+}
+
+/// Store (or replace) a secret
+pub fn set_secret(key: &str, secret: &str) -> Result<(), String> {
+    entry(key)?
+        .set_password(secret)
+        .map_err(|e| format!("Failed to store {} in keychain: {}", key, e))
+}
+
+/// Delete a secret (missing entries are not an error)
+pub fn delete_secret(key: &str) -> Result<(), String> {
+    match entry(key)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(format!("Failed to delete {} from keychain: {}", key, e)),
+    }
+}
@@ -0,0 +1,242 @@
+// LAN access: opening Moneywright from a tablet or phone on the same network
+//
+// The server listens on `server.bind_address`, passed to it as `HOST`: "127.0.0.1" keeps
+// it to this computer, "0.0.0.0" lets other devices reach it at this computer's address.
+// Nothing else is accepted, since the app itself always connects through localhost.
+// Only the main server is opened up; extra profiles, the demo and the sandbox stay on
+// localhost whatever the setting says (see server.rs).
+//
+// The server has no sign-in unless AUTH_ENABLED is on, so the window says plainly that
+// anyone on the network could then open the books, and that the connection is plain
+// http unless `server.tls` is on (see tls.rs).
+// Enabling takes ticking that off first; the change restarts a running server.
+
+use std::net::{IpAddr, UdpSocket};
+use serde::Serialize;
+use serde_json::json;
+use tauri::{AppHandle, Manager};
+use crate::events::{self, Event};
+use crate::logs::{log_line, SharedLogStore};
+use crate::server::{server_port, ServerStatus, SharedServerManager};
+use crate::settings::{Settings, SharedSettings};
+use crate::windows::open_injected_window;
+
+/// Environment variable the server reads its listen address from
+pub const HOST_ENV: &str = "HOST";
+pub const LOCALHOST: &str = "127.0.0.1";
+pub const ALL_INTERFACES: &str = "0.0.0.0";
+pub const BIND_ADDRESSES: &[&str] = &[LOCALHOST, ALL_INTERFACES];
+
+#[derive(Serialize)]
+pub struct LanAccess {
+    enabled: bool,
+    bind_address: String,
+    /// Addresses other devices can try, by IP first
+    urls: Vec<String>,
+    /// Whether the server asks for a sign-in (AUTH_ENABLED)
+    auth_enabled: bool,
+    warnings: Vec<String>,
+}
+
+/// Whether the main server is reachable from other devices
+pub fn is_enabled(settings: &Settings) -> bool {
+    settings.server.bind_address == ALL_INTERFACES
+}
+
+/// This computer's address on the network it routes through; connecting a UDP socket
+/// only picks the route, nothing is sent
+pub fn lan_ip() -> Option<IpAddr> {
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("192.0.2.1:9").ok()?;
+    let ip = socket.local_addr().ok()?.ip();
+    (!ip.is_loopback() && !ip.is_unspecified()).then_some(ip)
+}
+
+fn lan_urls(scheme: &str, port: u16) -> Vec<String> {
+    let mut urls = Vec::new();
+    if let Some(ip) = lan_ip() {
+        urls.push(format!("{}://{}:{}", scheme, ip, port));
+    }
+    // Resolved over mDNS; most phones and tablets do, some Android versions don't
+    if let Some(host) = sysinfo::System::host_name().filter(|h| !h.is_empty() && !h.contains(' ')) {
+        let host = host.trim_end_matches(".local").to_lowercase();
+        urls.push(format!("{}://{}.local:{}", scheme, host, port));
+    }
+    urls
+}
+
+/// AUTH_ENABLED as the server sees it: `server.extra_env` wins over the data dir .env
+fn auth_enabled(settings: &Settings, data_dir: &std::path::Path) -> bool {
+    settings
+        .server
+        .extra_env
+        .get("AUTH_ENABLED")
+        .cloned()
+        .or_else(|| crate::envconfig::value(data_dir, "AUTH_ENABLED"))
+        .is_some_and(|v| v == "true")
+}
+
+async fn info(app: &AppHandle) -> LanAccess {
+    let settings = app.state::<SharedSettings>().lock().await.get();
+    let data_dir = app.state::<SharedServerManager>().lock().await.data_dir().clone();
+    let auth_enabled = auth_enabled(&settings, &data_dir);
+    let mut warnings = Vec::new();
+    if !auth_enabled {
+        warnings.push(
+            "Sign-in is off, so anyone on this network could open and change your finances without a password. \
+             Turn on AUTH_ENABLED in Server Environment first."
+                .to_string(),
+        );
+    }
+    if settings.server.tls {
+        warnings.push("Other devices have to trust this computer's certificate first: export it from HTTPS and install it on them.".to_string());
+    } else {
+        warnings.push(
+            "The connection is plain http: others on the network could read what's sent. Only use this on a network you trust, \
+             like your home Wi-Fi, never on public Wi-Fi."
+                .to_string(),
+        );
+    }
+    if crate::service::is_installed() {
+        warnings.push("The background service picks this up once it's reinstalled.".to_string());
+    }
+    let urls = lan_urls(if settings.server.tls { "https" } else { "http" }, server_port());
+    if urls.is_empty() {
+        warnings.push("This computer doesn't seem to be connected to a network.".to_string());
+    }
+    LanAccess {
+        enabled: is_enabled(&settings),
+        bind_address: settings.server.bind_address,
+        urls,
+        auth_enabled,
+        warnings,
+    }
+}
+
+/// Whether LAN access is on, the addresses to open on other devices, and what to watch out for
+#[tauri::command]
+pub async fn get_lan_access(app: AppHandle) -> Result<LanAccess, String> {
+    Ok(info(&app).await)
+}
+
+/// Turn LAN access on or off and restart a running server with it
+#[tauri::command]
+pub async fn set_lan_access(app: AppHandle, enabled: bool) -> Result<LanAccess, String> {
+    if enabled && crate::remote::url().is_some() {
+        return Err("Moneywright uses a remote server, so there's no local server to open up".to_string());
+    }
+    let address = if enabled { ALL_INTERFACES } else { LOCALHOST };
+    let settings = app.state::<SharedSettings>().inner().clone();
+    let store = settings.lock().await;
+    if store.get().server.bind_address == address {
+        drop(store);
+        return Ok(info(&app).await);
+    }
+    let updated = store.update(&json!({ "server": { "bind_address": address } }))?;
+    drop(store);
+    let _ = events::emit(&app, Event::SettingsChanged(&updated));
+
+    let manager = app.state::<SharedServerManager>().inner().clone();
+    let log_store = app.state::<SharedLogStore>().inner().clone();
+    let msg = if enabled {
+        "LAN access turned on: the server listens on all network interfaces"
+    } else {
+        "LAN access turned off: the server only listens on this computer"
+    };
+    log_line(&app, &log_store, msg, "info").await;
+    if !matches!(manager.lock().await.status(), ServerStatus::Stopped | ServerStatus::Paused) {
+        crate::restart_server(app.clone(), manager, log_store).await?;
+    }
+    Ok(info(&app).await)
+}
+
+/// Open the LAN access window
+pub fn open_lan_window(app: &AppHandle) {
+    let script = r#"
+        const tauriApi = window.__TAURI__;
+
+        document.documentElement.innerHTML = `
+<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <title>LAN Access</title>
+    <style>
+        __BASE_STYLE__
+        .content { flex: 1; overflow-y: auto; padding: 16px; display: flex; flex-direction: column; gap: 12px; }
+        .warning { border-left: 3px solid #f59e0b; padding: 6px 10px; background: rgba(245, 158, 11, 0.06); }
+        .warning.danger { border-color: #ef4444; background: rgba(239, 68, 68, 0.08); }
+        .urls { display: flex; flex-direction: column; gap: 6px; }
+        .url { display: flex; gap: 8px; align-items: center; }
+        .url span { flex: 1; }
+        .actions { display: flex; gap: 8px; align-items: center; }
+    </style>
+</head>
+<body>
+    <div class="content">
+        <p class="muted">Open Moneywright from a tablet or phone on the same network as this computer.</p>
+        <div id="state"></div>
+        <div id="warnings" role="list" aria-label="Warnings"></div>
+        <div id="urlsBox" hidden>
+            <p>On the other device, open:</p>
+            <div id="urls" class="urls"></div>
+        </div>
+        <label id="ackRow"><input type="checkbox" id="ack"> I understand the risks above</label>
+        <div class="actions">
+            <button id="toggleBtn" class="primary"></button>
+        </div>
+        <div id="status" class="muted" role="status" aria-live="polite"></div>
+    </div>
+</body>
+</html>`;
+
+        const $ = id => document.getElementById(id);
+        let access = null;
+
+        function render() {
+            $('state').textContent = access.enabled
+                ? 'LAN access is on: other devices on this network can reach Moneywright.'
+                : 'LAN access is off: Moneywright is only reachable from this computer.';
+            $('warnings').innerHTML = access.warnings.map((w, i) =>
+                '<div class="warning' + (i === 0 && !access.auth_enabled ? ' danger' : '') + '" role="listitem">' + escapeHtml(w) + '</div>'
+            ).join('');
+            $('urlsBox').hidden = !access.enabled || access.urls.length === 0;
+            $('urls').innerHTML = access.urls.map((u, i) =>
+                '<div class="url"><span class="mono">' + escapeHtml(u) + '</span><button data-i="' + i + '">Copy</button></div>'
+            ).join('');
+            $('urls').querySelectorAll('button').forEach(b => {
+                b.onclick = () => navigator.clipboard.writeText(access.urls[Number(b.dataset.i)]);
+            });
+            $('ackRow').hidden = access.enabled;
+            $('ack').checked = false;
+            $('toggleBtn').textContent = access.enabled ? 'Turn Off LAN Access' : 'Turn On LAN Access';
+            $('toggleBtn').disabled = !access.enabled;
+        }
+
+        async function load() {
+            try {
+                access = await tauriApi.core.invoke('get_lan_access');
+                render();
+            } catch (e) {
+                $('status').textContent = String(e);
+            }
+        }
+
+        $('ack').onchange = () => { $('toggleBtn').disabled = !$('ack').checked; };
+        $('toggleBtn').onclick = async () => {
+            $('toggleBtn').disabled = true;
+            $('status').textContent = 'Restarting the server...';
+            try {
+                access = await tauriApi.core.invoke('set_lan_access', { enabled: !access.enabled });
+                $('status').textContent = '';
+                render();
+            } catch (e) {
+                $('status').textContent = String(e);
+                $('toggleBtn').disabled = false;
+            }
+        };
+        load();
+    "#;
+
+    open_injected_window(app, "lan_access", "LAN Access", (560.0, 520.0), true, script);
+}
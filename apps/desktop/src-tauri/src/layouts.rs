@@ -0,0 +1,253 @@
+// Window layouts for working with several app windows at once
+//
+// Window > New Window opens extra windows on the main server ("view-1", "view-2",
+// ...). A preset gives the main window and those extra windows a page and a share of
+// the current display's work area. "Save Layout" records where the windows are and
+// which page they show, keyed by the set of connected displays, in layouts.json; it
+// is restored at launch and from the menu when the same displays are connected.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, PhysicalPosition, PhysicalSize, WebviewUrl, WebviewWindow, WebviewWindowBuilder};
+use crate::display::monitor_key;
+use crate::server::{get_server_url, is_server_url};
+
+const LAYOUTS_FILE: &str = "layouts.json";
+pub const VIEW_PREFIX: &str = "view-";
+
+/// Page and share of the work area, as fractions
+struct Pane {
+    route: &'static str,
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+pub struct Preset {
+    pub id: &'static str,
+    pub name: &'static str,
+    /// The first pane is the main window, the rest extra windows
+    panes: &'static [Pane],
+}
+
+const fn pane(route: &'static str, x: f64, width: f64) -> Pane {
+    Pane { route, x, y: 0.0, width, height: 1.0 }
+}
+
+pub const PRESETS: &[Preset] = &[
+    Preset {
+        id: "transactions_dashboard",
+        name: "Transactions Left, Dashboard Right",
+        panes: &[pane("/transactions", 0.0, 0.5), pane("/", 0.5, 0.5)],
+    },
+    Preset {
+        id: "accounts_transactions",
+        name: "Accounts Left, Transactions Right",
+        panes: &[pane("/accounts", 0.0, 0.5), pane("/transactions", 0.5, 0.5)],
+    },
+    Preset {
+        id: "dashboard_chat",
+        name: "Dashboard with Chat Alongside",
+        panes: &[pane("/", 0.0, 0.65), pane("/chat", 0.65, 0.35)],
+    },
+];
+
+#[derive(Clone, Serialize, Deserialize)]
+struct SavedWindow {
+    label: String,
+    route: String,
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+}
+
+/// Saved windows per display setup
+type SavedLayouts = BTreeMap<String, Vec<SavedWindow>>;
+
+fn layouts_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(LAYOUTS_FILE)
+}
+
+fn load(data_dir: &Path) -> SavedLayouts {
+    fs::read_to_string(layouts_path(data_dir))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save(data_dir: &Path, layouts: &SavedLayouts) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(layouts).map_err(|e| e.to_string())?;
+    let path = layouts_path(data_dir);
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, content).map_err(|e| format!("Failed to write layouts: {}", e))?;
+    fs::rename(&tmp, &path).map_err(|e| format!("Failed to write layouts: {}", e))
+}
+
+/// The connected displays, e.g. "DELL U2720Q (3840x2160) + Built-in Retina Display (3024x1964)"
+fn display_setup(app: &AppHandle) -> Option<String> {
+    let mut keys: Vec<String> = app.available_monitors().ok()?.iter().map(monitor_key).collect();
+    if keys.is_empty() {
+        return None;
+    }
+    keys.sort();
+    Some(keys.join(" + "))
+}
+
+/// Main window first, then the extra windows in the order they were opened
+fn app_windows(app: &AppHandle) -> Vec<WebviewWindow> {
+    let mut views: Vec<(u32, WebviewWindow)> = app
+        .webview_windows()
+        .into_iter()
+        .filter_map(|(label, window)| Some((label.strip_prefix(VIEW_PREFIX)?.parse().ok()?, window)))
+        .collect();
+    views.sort_by_key(|(n, _)| *n);
+    app.get_webview_window("main")
+        .into_iter()
+        .chain(views.into_iter().map(|(_, window)| window))
+        .collect()
+}
+
+/// First unused extra-window label
+fn next_view_label(app: &AppHandle) -> String {
+    (1..)
+        .map(|n| format!("{}{}", VIEW_PREFIX, n))
+        .find(|label| app.get_webview_window(label).is_none())
+        .unwrap_or_default()
+}
+
+fn open_view(app: &AppHandle, label: &str, route: &str) -> Result<WebviewWindow, String> {
+    if let Some(window) = app.get_webview_window(label) {
+        return Ok(window);
+    }
+    let url = format!("{}{}", get_server_url(), route)
+        .parse()
+        .map_err(|e| format!("Invalid URL: {}", e))?;
+    let window = crate::webview::configure(WebviewWindowBuilder::new(app, label, WebviewUrl::External(url)))
+        .title("Moneywright")
+        .inner_size(1280.0, 800.0)
+        .min_inner_size(480.0, 400.0)
+        .build()
+        .map_err(|e| format!("Failed to open window: {}", e))?;
+    crate::tls::trust_in_webview(&window);
+    Ok(window)
+}
+
+/// Open another window on the main server (Window > New Window)
+pub fn open_new_window(app: &AppHandle) -> Result<(), String> {
+    let window = open_view(app, &next_view_label(app), "/")?;
+    let _ = window.set_focus();
+    Ok(())
+}
+
+/// Page a window shows, None if it isn't on the server
+fn current_route(window: &WebviewWindow) -> Option<String> {
+    let url = window.url().ok()?;
+    is_server_url(&url).then(|| url.path().to_string())
+}
+
+fn show_route(window: &WebviewWindow, route: &str) {
+    if current_route(window).as_deref() != Some(route) {
+        // Using Tauri's webview eval API to navigate - this is safe as we control the URL
+        let _ = window.eval(format!("window.location.href = '{}{}'", get_server_url(), route));
+    }
+}
+
+/// Move a window and set its inner size
+fn place(window: &WebviewWindow, x: i32, y: i32, width: u32, height: u32) {
+    let _ = window.unmaximize();
+    let _ = window.set_position(PhysicalPosition::new(x, y));
+    let _ = window.set_size(PhysicalSize::new(width, height));
+    let _ = window.show();
+}
+
+/// Fit a window's outer frame into a rectangle of the work area
+fn place_outer(window: &WebviewWindow, x: i32, y: i32, width: u32, height: u32) {
+    // Title bar and borders, so neighbouring panes don't overlap
+    let (frame_w, frame_h) = match (window.outer_size(), window.inner_size()) {
+        (Ok(outer), Ok(inner)) => (outer.width.saturating_sub(inner.width), outer.height.saturating_sub(inner.height)),
+        _ => (0, 0),
+    };
+    place(window, x, y, width.saturating_sub(frame_w), height.saturating_sub(frame_h));
+}
+
+/// Arrange windows by a preset on the main window's display
+pub fn apply_preset(app: &AppHandle, id: &str) -> Result<(), String> {
+    let preset = PRESETS.iter().find(|p| p.id == id).ok_or_else(|| format!("Unknown layout: {}", id))?;
+    let main = app.get_webview_window("main").ok_or_else(|| "Main window not found".to_string())?;
+    let monitor = main
+        .current_monitor()
+        .ok()
+        .flatten()
+        .or_else(|| main.primary_monitor().ok().flatten())
+        .ok_or_else(|| "No display found".to_string())?;
+    let area = monitor.work_area();
+
+    let windows = app_windows(app);
+    for (i, pane) in preset.panes.iter().enumerate() {
+        let window = match windows.get(i) {
+            Some(window) => window.clone(),
+            None => open_view(app, &next_view_label(app), pane.route)?,
+        };
+        show_route(&window, pane.route);
+        place_outer(
+            &window,
+            area.position.x + (area.size.width as f64 * pane.x).round() as i32,
+            area.position.y + (area.size.height as f64 * pane.y).round() as i32,
+            (area.size.width as f64 * pane.width).round() as u32,
+            (area.size.height as f64 * pane.height).round() as u32,
+        );
+    }
+    Ok(())
+}
+
+/// Remember the current windows for the connected displays, returning how many
+pub fn save_layout(app: &AppHandle, data_dir: &Path) -> Result<usize, String> {
+    let setup = display_setup(app).ok_or_else(|| "No display found".to_string())?;
+    let windows: Vec<SavedWindow> = app_windows(app)
+        .iter()
+        .filter(|w| w.is_visible().unwrap_or(false))
+        .filter_map(|window| {
+            let position = window.outer_position().ok()?;
+            let size = window.inner_size().ok()?;
+            Some(SavedWindow {
+                label: window.label().to_string(),
+                route: current_route(window).unwrap_or_else(|| "/".to_string()),
+                x: position.x,
+                y: position.y,
+                width: size.width,
+                height: size.height,
+            })
+        })
+        .collect();
+
+    let mut layouts = load(data_dir);
+    let count = windows.len();
+    layouts.insert(setup, windows);
+    save(data_dir, &layouts)?;
+    Ok(count)
+}
+
+/// Restore the layout saved for the connected displays, false if there is none
+pub fn restore_layout(app: &AppHandle, data_dir: &Path) -> Result<bool, String> {
+    let Some(windows) = display_setup(app).and_then(|setup| load(data_dir).remove(&setup)) else {
+        return Ok(false);
+    };
+    for saved in &windows {
+        let window = if saved.label == "main" {
+            app.get_webview_window("main")
+        } else if saved.label.starts_with(VIEW_PREFIX) {
+            Some(open_view(app, &saved.label, &saved.route)?)
+        } else {
+            None
+        };
+        if let Some(window) = window {
+            show_route(&window, &saved.route);
+            place(&window, saved.x, saved.y, saved.width, saved.height);
+        }
+    }
+    Ok(true)
+}
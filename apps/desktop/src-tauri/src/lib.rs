@@ -1,32 +1,155 @@
 // Moneywright Desktop - Window app for running the Moneywright server
 
+mod a11y;
+mod alerts;
+mod analytics;
+mod arch;
+mod archive;
+mod avcheck;
+mod attachments;
+mod backup;
+mod benchmark;
+mod browsing;
+mod cleanups;
+mod climigrate;
+mod control;
+mod crash;
+mod database;
+mod demo;
+mod diskspace;
+mod display;
+mod download;
+mod doctor;
+mod envconfig;
+mod events;
+mod exports;
+mod flags;
+mod fx;
+mod heartbeat;
+mod history;
+mod idle;
+mod importer;
+mod integrity;
+mod isolation;
+mod jobs;
+mod keychain;
+mod lan;
+mod layouts;
+mod locale;
+mod loglevel;
+mod logs;
+mod markdown;
+mod netproxy;
+mod netusage;
+mod notifications;
+mod oauth;
+mod onboarding;
+mod orphans;
+mod pdf;
+mod pgmigrate;
+mod portconflict;
+mod ports;
+mod postgres;
+mod power;
+mod profiles;
+mod protocol;
+mod proxy;
+mod quarantine;
+mod recovery;
+mod relocation;
+mod remote;
+mod report;
+mod resources;
+mod sandbox;
+mod scheduler;
 mod server;
+mod serverstats;
+mod service;
+mod sessions;
+mod settings;
+mod shortcuts;
+mod tls;
+mod transfer;
+mod transport;
+mod uibuild;
+mod uninstall;
 mod updater;
-
-use server::{create_server_manager, get_server_url, start_server, stop_server, kill_process_on_port, SERVER_PORT, ServerStatus, SharedServerManager};
-use updater::{check_for_updates, download_and_install, background_download_and_install, UpdateState, SharedUpdateState, UpdateReadyInfo};
-use tauri::{AppHandle, Emitter, Manager, WebviewUrl, WebviewWindowBuilder};
-use tauri_plugin_updater::UpdaterExt;
-use tauri::menu::{Menu, MenuItem, Submenu, PredefinedMenuItem};
+mod upgrade;
+mod webview;
+mod webcache;
+mod windows;
+
+use analytics::{open_usage_window, record_feature, record_launch};
+use attachments::open_attachments_window;
+use backup::open_backups_window;
+use benchmark::{load_benchmark, StartupBenchmark, StartupTimer};
+use control::start_control_server;
+use crash::{install_crash_handler, mark_clean_exit, open_crash_reports_window};
+use database::open_database_window;
+use doctor::open_doctor_window;
+use exports::{open_exports_window, start_export_scheduler};
+use importer::open_import_window;
+use logs::{create_log_emitter, emit_log, flush_logs, log_line, LogStore, SharedLogStore};
+use scheduler::start_scheduler;
+use onboarding::{needs_onboarding, open_onboarding_window};
+use profiles::{create_profile_servers, open_profiles_window};
+use server::{create_server_manager, get_server_url, read_database_url, start_server, stop_server, SharedServerManager};
+use sessions::{create_session_tracker, start_session_checkpoints, SharedSessionTracker};
+use settings::{capture_protected, create_settings_store, spawn_autostart_sync, spawn_capture_protection_sync, spawn_settings_logger};
+use windows::window_script;
+use updater::{check_for_updates, download_and_install, background_download_and_install, UpdateState, SharedUpdateState};
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
+use tauri::menu::{CheckMenuItem, Menu, MenuItem, Submenu, PredefinedMenuItem, HELP_SUBMENU_ID, WINDOW_SUBMENU_ID};
 use serde::Serialize;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
 // Version is read from Cargo.toml at compile time
 const APP_VERSION: &str = concat!("v", env!("CARGO_PKG_VERSION"));
-const MAX_LOG_LINES: usize = 1000;
-
-#[derive(Clone, Serialize)]
-struct LogPayload {
-    message: String,
-    log_type: String,
-}
+/// Update checks for the "on_quit" policy: shortly after launch, then a few times a day
+const QUIT_INSTALL_FIRST_CHECK: std::time::Duration = std::time::Duration::from_secs(2 * 60);
+const QUIT_INSTALL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(6 * 60 * 60);
 
 #[derive(Clone, Serialize)]
 struct InitialState {
     version: String,
     url: String,
     status: String,
+    /// The starting server is applying database migrations
+    migrating: bool,
+}
+
+#[derive(Serialize)]
+struct AboutInfo {
+    version: String,
+    /// Reported by the running server, None while it's down
+    sidecar_version: Option<String>,
+    database: &'static str,
+    data_dir: String,
+    license: &'static str,
+    commit: &'static str,
+    target: &'static str,
+    profile: &'static str,
+    tauri_version: &'static str,
+    /// `updates.channel`
+    update_channel: String,
+}
+
+/// What the running server reports about itself, next to what the app bundles
+#[derive(Clone, Serialize)]
+struct ServerInfo {
+    app_version: String,
+    /// None while the server is down
+    server_version: Option<String>,
+    /// Applied migrations
+    schema_version: Option<usize>,
+    /// Migrations shipped with the app
+    bundled_migrations: Option<usize>,
+    /// "sqlite" or "postgres"
+    database: Option<String>,
+    runtime: Option<String>,
+    /// Ways the app and server are out of sync, empty when they match
+    mismatches: Vec<String>,
 }
 
 #[derive(Clone, Serialize)]
@@ -37,63 +160,21 @@ struct UpdateInfo {
     ready: bool, // true if update is downloaded and installed, waiting for restart
 }
 
-/// Log storage for backend logs
-pub struct LogStore {
-    logs: Vec<String>,
-}
-
-impl LogStore {
-    fn new() -> Self {
-        Self { logs: Vec::new() }
-    }
-
-    fn add(&mut self, message: String) {
-        self.logs.push(message);
-        // Keep only last MAX_LOG_LINES
-        if self.logs.len() > MAX_LOG_LINES {
-            self.logs.remove(0);
-        }
-    }
-
-    fn get_all(&self) -> Vec<String> {
-        self.logs.clone()
-    }
-
-    fn clear(&mut self) {
-        self.logs.clear();
-    }
-}
-
-pub type SharedLogStore = Arc<Mutex<LogStore>>;
-
-/// Emit a log message to the frontend
-fn emit_log(app: &AppHandle, message: &str, log_type: &str) {
-    let _ = app.emit("server-log", LogPayload {
-        message: message.to_string(),
-        log_type: log_type.to_string(),
-    });
-}
-
 /// Emit status update to the frontend
 fn emit_status(app: &AppHandle, status: &str) {
-    let _ = app.emit("server-status", status);
+    let _ = events::emit(app, events::Event::ServerStatus(status));
 }
 
 /// Get initial state for the UI
 #[tauri::command]
 async fn get_initial_state(manager: tauri::State<'_, SharedServerManager>) -> Result<InitialState, String> {
     let mgr = manager.lock().await;
-    let status = match mgr.status() {
-        ServerStatus::Starting => "starting",
-        ServerStatus::Running => "running",
-        ServerStatus::Stopped => "stopped",
-        ServerStatus::Error(_) => "error",
-    };
 
     Ok(InitialState {
         version: APP_VERSION.to_string(),
         url: get_server_url(),
-        status: status.to_string(),
+        status: mgr.status().as_str().to_string(),
+        migrating: mgr.is_migrating(),
     })
 }
 
@@ -140,43 +221,109 @@ async fn stop_server_cmd(app: AppHandle, manager: tauri::State<'_, SharedServerM
     }
 }
 
+/// Pause the server, keeping the app open
+#[tauri::command]
+async fn pause_server_cmd(app: AppHandle, manager: tauri::State<'_, SharedServerManager>) -> Result<(), String> {
+    pause(app, manager.inner().clone()).await
+}
+
+async fn pause(app: AppHandle, manager: SharedServerManager) -> Result<(), String> {
+    emit_log(&app, "Pausing server...", "info");
+    if let Err(e) = server::pause_server(manager).await {
+        emit_log(&app, &format!("Failed to pause server: {}", e), "error");
+        return Err(e);
+    }
+    emit_status(&app, "paused");
+    emit_log(&app, "Server paused", "info");
+    protocol::show_paused(&app);
+    if let Err(e) = setup_menu(&app) {
+        eprintln!("Warning: Failed to update the menu: {}", e);
+    }
+    Ok(())
+}
+
+/// Start a paused server again
+#[tauri::command]
+async fn resume_server_cmd(app: AppHandle, manager: tauri::State<'_, SharedServerManager>, log_store: tauri::State<'_, SharedLogStore>) -> Result<(), String> {
+    resume(app, manager.inner().clone(), log_store.inner().clone()).await
+}
+
+async fn resume(app: AppHandle, manager: SharedServerManager, log_store: SharedLogStore) -> Result<(), String> {
+    emit_status(&app, "starting");
+    emit_log(&app, "Resuming server...", "info");
+    let result = start_server(app.clone(), manager, log_store).await;
+    if let Err(e) = setup_menu(&app) {
+        eprintln!("Warning: Failed to update the menu: {}", e);
+    }
+    match &result {
+        Ok(()) => {
+            emit_status(&app, "running");
+            emit_log(&app, &format!("Server running at {}", get_server_url()), "success");
+            protocol::show_server(&app, None);
+        }
+        Err(e) => {
+            emit_status(&app, "error");
+            emit_log(&app, &format!("Failed to resume server: {}", e), "error");
+            protocol::show_error(&app, e);
+        }
+    }
+    result
+}
+
 /// Restart the server
 #[tauri::command]
 async fn restart_server_cmd(app: AppHandle, manager: tauri::State<'_, SharedServerManager>, log_store: tauri::State<'_, SharedLogStore>) -> Result<(), String> {
+    restart_server(app, manager.inner().clone(), log_store.inner().clone()).await
+}
+
+/// Stop and start the server again, reporting progress to the frontend
+async fn restart_server(app: AppHandle, manager: SharedServerManager, log_store: SharedLogStore) -> Result<(), String> {
     emit_log(&app, "Restarting server...", "info");
+    if let Some(tracker) = app.try_state::<SharedSessionTracker>() {
+        tracker.lock().await.server_restarted();
+    }
 
     // Stop first
-    let manager_inner = manager.inner().clone();
-    let log_store = log_store.inner().clone();
-    if let Err(e) = stop_server(manager_inner.clone()).await {
+    if let Err(e) = stop_server(manager.clone()).await {
         emit_log(&app, &format!("Warning: Failed to stop server: {}", e), "error");
     }
 
     // Small delay
     tokio::time::sleep(std::time::Duration::from_millis(500)).await;
 
-    // Start again
+    // Start again, with the splash screen instead of a dead page in the meantime
+    let previous = protocol::show_splash(&app);
     emit_status(&app, "starting");
-    match start_server(app.clone(), manager_inner.clone(), log_store).await {
+    match start_server(app.clone(), manager, log_store).await {
         Ok(_) => {
             emit_status(&app, "running");
             emit_log(&app, &format!("Server restarted at {}", get_server_url()), "success");
+            protocol::show_server(&app, previous);
             Ok(())
         }
         Err(e) => {
             emit_status(&app, "error");
             emit_log(&app, &format!("Failed to restart server: {}", e), "error");
+            protocol::show_error(&app, &e);
+            alerts::report_start_failure(&app, &e);
             Err(e)
         }
     }
 }
 
+/// Open the app in the default browser, which can't reach a server on a socket
+fn open_in_browser() -> Result<(), String> {
+    if transport::socket_path().is_some() {
+        return Err("The server listens on a socket (server.transport), which browsers can't reach".to_string());
+    }
+    open::that(get_server_url()).map_err(|e| format!("Failed to open browser: {}", e))
+}
+
 /// Open browser to the server URL
 #[tauri::command]
 async fn open_browser_cmd(app: AppHandle) -> Result<(), String> {
-    let url = get_server_url();
-    emit_log(&app, &format!("Opening browser: {}", url), "info");
-    open::that(&url).map_err(|e| format!("Failed to open browser: {}", e))
+    emit_log(&app, &format!("Opening browser: {}", get_server_url()), "info");
+    open_in_browser()
 }
 
 /// Open any URL in the default browser
@@ -185,9 +332,23 @@ async fn open_url(url: String) -> Result<(), String> {
     open::that(&url).map_err(|e| format!("Failed to open URL: {}", e))
 }
 
+/// Open a Help menu link in the default browser
+fn open_help_link(app: &AppHandle, id: &str) {
+    let url = match id {
+        "docs" => "https://moneywright.com/docs".to_string(),
+        // Tags are "v" plus the app version
+        "release_notes" => format!("https://github.com/moneywright/moneywright/releases/tag/v{}", app.package_info().version),
+        "community" => "https://github.com/moneywright/moneywright/discussions".to_string(),
+        _ => return,
+    };
+    if let Err(e) = open::that(&url) {
+        emit_log(app, &format!("Failed to open URL: {}", e), "error");
+    }
+}
+
 /// Get backend logs
 #[tauri::command]
-async fn get_logs(log_store: tauri::State<'_, SharedLogStore>) -> Result<Vec<String>, String> {
+async fn get_logs(log_store: tauri::State<'_, SharedLogStore>) -> Result<Vec<Arc<str>>, String> {
     let store = log_store.lock().await;
     Ok(store.get_all())
 }
@@ -200,16 +361,19 @@ async fn clear_logs(log_store: tauri::State<'_, SharedLogStore>) -> Result<(), S
     Ok(())
 }
 
+/// Get startup phase timings for recent launches, with cold/warm start averages
+#[tauri::command]
+async fn get_startup_benchmark(manager: tauri::State<'_, SharedServerManager>) -> Result<StartupBenchmark, String> {
+    let data_dir = manager.lock().await.data_dir().clone();
+    Ok(load_benchmark(&data_dir))
+}
+
 /// Quit the application
 #[tauri::command]
 async fn quit_app_cmd(app: AppHandle) -> Result<(), String> {
     emit_log(&app, "Shutting down...", "info");
 
-    // Kill server process synchronously (only in release mode)
-    #[cfg(not(debug_assertions))]
-    let _ = kill_process_on_port(SERVER_PORT);
-
-    // Exit the app
+    // Exit the app; the exit handler stops the server
     app.exit(0);
     Ok(())
 }
@@ -217,6 +381,9 @@ async fn quit_app_cmd(app: AppHandle) -> Result<(), String> {
 /// Download and install update
 #[tauri::command]
 async fn download_update(app: AppHandle) -> Result<(), String> {
+    if let Some(dir) = diskspace::install_dir() {
+        diskspace::preflight(&app, diskspace::Operation::Update, &dir, diskspace::UPDATE_BYTES).await?;
+    }
     download_and_install(app).await
 }
 
@@ -238,10 +405,16 @@ async fn check_update_available(app: AppHandle, update_state: tauri::State<'_, S
     }
 
     // Check for new updates
-    let updater = app.updater().map_err(|e| format!("Failed to initialize updater: {}", e))?;
-    let update = updater.check().await.map_err(|e| format!("Failed to check for updates: {}", e))?;
+    let run = history::begin("update_check", "Checking for updates");
+    let result = match updater::channel_updater(&app).await {
+        Ok(updater) => updater.check().await.map_err(|e| format!("Failed to check for updates: {}", e)),
+        Err(e) => Err(format!("Failed to initialize updater: {}", e)),
+    };
+    run.finish(&result);
+    let update = result?;
 
     match update {
+        Some(u) if updater::is_skipped(&app, &u.version).await => Ok(None),
         Some(u) => Ok(Some(UpdateInfo {
             current_version: u.current_version.to_string(),
             new_version: u.version.to_string(),
@@ -263,8 +436,21 @@ async fn start_background_update(app: AppHandle, update_state: tauri::State<'_,
         }
     }
 
-    // Download and install in background
-    let info = background_download_and_install(app).await?;
+    // Download and install in background, in the maintenance window and on mains power
+    scheduler::wait_for_heavy_work(&app, "Update download").await;
+    if let Some(reason) = netusage::limit_reason(&app).await {
+        return Err(format!("Update download skipped: {}", reason));
+    }
+    if let Some(dir) = diskspace::install_dir() {
+        diskspace::preflight(&app, diskspace::Operation::Update, &dir, diskspace::UPDATE_BYTES).await?;
+    }
+    let info = background_download_and_install(app.clone()).await?;
+    let body = if updater::has_staged() {
+        format!("Moneywright {} is ready and installs when you quit.", info.new_version)
+    } else {
+        format!("Moneywright {} is ready and installs on the next restart.", info.new_version)
+    };
+    notifications::notify(&app, notifications::Kind::Updates, "Update ready", body).await;
 
     // Store the ready state
     {
@@ -275,6 +461,36 @@ async fn start_background_update(app: AppHandle, update_state: tauri::State<'_,
     Ok(())
 }
 
+/// With `updates.policy` "on_quit", look for updates and download them without waiting
+/// for the web app to ask
+fn start_quit_install_checks(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(QUIT_INSTALL_FIRST_CHECK).await;
+        loop {
+            let updates = app.state::<settings::SharedSettings>().lock().await.get().updates;
+            if updates.auto_check && updates.policy == "on_quit" && !updater::has_staged() {
+                if let Err(e) = start_background_update(app.clone(), app.state()).await {
+                    eprintln!("Background update: {}", e);
+                }
+            }
+            tokio::time::sleep(QUIT_INSTALL_INTERVAL).await;
+        }
+    });
+}
+
+/// Continue a paused update download the way it was started (background or dialog)
+#[tauri::command]
+async fn resume_update_download(app: AppHandle, update_state: tauri::State<'_, SharedUpdateState>) -> Result<(), String> {
+    match download::paused_background() {
+        Some(true) => {
+            download::unpause()?;
+            start_background_update(app, update_state).await
+        }
+        Some(false) => download_update(app).await,
+        None => Err("No update download is paused".to_string()),
+    }
+}
+
 /// Restart the app to apply a ready update
 #[tauri::command]
 async fn restart_for_update(app: AppHandle, update_state: tauri::State<'_, SharedUpdateState>) -> Result<(), String> {
@@ -284,6 +500,14 @@ async fn restart_for_update(app: AppHandle, update_state: tauri::State<'_, Share
     }
     drop(state); // Release lock before restart
 
+    // An update kept for quitting goes in now, with the server stopped as on quit
+    if updater::has_staged() {
+        if let Some(manager) = app.try_state::<SharedServerManager>().filter(|_| !service::is_installed()) {
+            let _ = stop_server(manager.inner().clone()).await;
+        }
+        updater::install_staged();
+    }
+
     app.restart();
 }
 
@@ -304,13 +528,14 @@ fn open_logs_window(app: &AppHandle) {
 
     // Create logs window that loads from localhost with a special route
     // We'll inject the HTML after the window is created
+    let (width, height) = display::text_scaled_size(app, (1000.0, 500.0));
     let window = WebviewWindowBuilder::new(
         app,
         "logs",
-        WebviewUrl::App("/".into()),
+        WebviewUrl::CustomProtocol(protocol::page_url("window")),
     )
     .title("View Logs")
-    .inner_size(1000.0, 500.0)
+    .inner_size(width, height)
     .min_inner_size(400.0, 300.0)
     .visible(false) // Start hidden to avoid flash
     .build();
@@ -326,12 +551,11 @@ fn open_logs_window(app: &AppHandle) {
     <meta charset="UTF-8">
     <title>View Logs</title>
     <style>
-        @import url('https://fonts.googleapis.com/css2?family=DM+Sans:wght@400;500;600&family=DM+Mono:wght@400;500&display=swap');
 
         * { margin: 0; padding: 0; box-sizing: border-box; }
 
         body {
-            font-family: 'DM Sans', -apple-system, BlinkMacSystemFont, sans-serif;
+            font-family: system-ui, -apple-system, BlinkMacSystemFont, 'Segoe UI', sans-serif;
             font-size: 13px;
             background: #030303;
             color: #fafafa;
@@ -363,7 +587,7 @@ fn open_logs_window(app: &AppHandle) {
             color: #a1a1aa;
             border-radius: 6px;
             cursor: pointer;
-            font-family: 'DM Sans', sans-serif;
+            font-family: inherit;
             font-size: 12px;
             font-weight: 500;
             transition: all 0.15s ease;
@@ -382,6 +606,11 @@ fn open_logs_window(app: &AppHandle) {
             background: #1a1a1a;
         }
 
+        .toolbar button:focus-visible, #logs:focus-visible {
+            outline: 2px solid #10b981;
+            outline-offset: 1px;
+        }
+
         .toolbar button svg {
             width: 14px;
             height: 14px;
@@ -392,6 +621,21 @@ fn open_logs_window(app: &AppHandle) {
             opacity: 1;
         }
 
+        .toolbar select {
+            padding: 5px 8px;
+            background: #111111;
+            border: 1px solid rgba(255, 255, 255, 0.08);
+            color: #a1a1aa;
+            border-radius: 6px;
+            font-family: inherit;
+            font-size: 12px;
+        }
+
+        .toolbar .capture {
+            color: #f59e0b;
+            font-size: 12px;
+        }
+
         .toolbar .count {
             color: #52525b;
             font-size: 12px;
@@ -407,7 +651,7 @@ fn open_logs_window(app: &AppHandle) {
         }
 
         .log-line {
-            font-family: 'DM Mono', ui-monospace, SFMono-Regular, 'SF Mono', Menlo, monospace;
+            font-family: ui-monospace, SFMono-Regular, 'SF Mono', Menlo, Consolas, monospace;
             font-size: 12px;
             line-height: 1.6;
             padding: 3px 0;
@@ -458,7 +702,7 @@ fn open_logs_window(app: &AppHandle) {
     </style>
 </head>
 <body>
-    <div class="toolbar">
+    <div class="toolbar" role="toolbar" aria-label="Log actions">
         <button id="refreshBtn">
             <svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2" stroke-linecap="round" stroke-linejoin="round">
                 <path d="M21 12a9 9 0 0 0-9-9 9.75 9.75 0 0 0-6.74 2.74L3 8"/>
@@ -476,18 +720,23 @@ fn open_logs_window(app: &AppHandle) {
             </svg>
             Clear
         </button>
-        <span class="count" id="count"></span>
+        <select id="levelSelect" aria-label="Server log level" title="Server log level (restarts the server)">
+            <option value="error">Errors only</option>
+            <option value="warn">Warnings</option>
+            <option value="info">Info</option>
+            <option value="debug">Debug</option>
+        </select>
+        <button id="captureBtn" title="Log at debug level for 10 minutes, then switch back">Capture Debug Logs</button>
+        <select id="filesSelect" aria-label="Saved log files" title="Server output from earlier runs">
+            <option value="">Log Files...</option>
+        </select>
+        <span class="capture" id="capture" role="status"></span>
+        <span class="count" id="count" role="status"></span>
     </div>
-    <div id="logs"></div>
+    <div id="logs" role="region" aria-label="Log output" tabindex="0"></div>
 </body>
 </html>`;
 
-            function escapeHtml(text) {
-                const div = document.createElement('div');
-                div.textContent = text;
-                return div.innerHTML;
-            }
-
             function classifyLog(log) {
                 const lower = log.toLowerCase();
 
@@ -567,18 +816,113 @@ fn open_logs_window(app: &AppHandle) {
                 }
             }
 
+            function showLevel(info) {
+                document.getElementById('levelSelect').value = info.level;
+                const capturing = !!info.debug_until;
+                const until = capturing ? new Date(info.debug_until).toLocaleTimeString([], { hour: '2-digit', minute: '2-digit' }) : '';
+                document.getElementById('capture').textContent = capturing ? 'Debug logging until ' + until : '';
+                document.getElementById('captureBtn').textContent = capturing ? 'Stop Debug Capture' : 'Capture Debug Logs';
+                document.getElementById('captureBtn').dataset.capturing = capturing ? '1' : '';
+            }
+
+            async function refreshLevel() {
+                try {
+                    showLevel(await window.__TAURI__.core.invoke('get_server_log_level'));
+                } catch (e) {
+                    console.error('Failed to load log level:', e);
+                }
+            }
+
+            async function changeLevel() {
+                const select = document.getElementById('levelSelect');
+                select.disabled = true;
+                try {
+                    showLevel(await window.__TAURI__.core.invoke('set_server_log_level', { level: select.value }));
+                } catch (e) {
+                    document.getElementById('capture').textContent = String(e);
+                    refreshLevel();
+                } finally {
+                    select.disabled = false;
+                }
+            }
+
+            async function toggleCapture() {
+                const btn = document.getElementById('captureBtn');
+                const command = btn.dataset.capturing ? 'stop_debug_capture' : 'capture_debug_logs';
+                btn.disabled = true;
+                try {
+                    showLevel(await window.__TAURI__.core.invoke(command));
+                } catch (e) {
+                    document.getElementById('capture').textContent = String(e);
+                } finally {
+                    btn.disabled = false;
+                }
+            }
+
+            function formatSize(bytes) {
+                if (bytes < 1024) return bytes + ' B';
+                if (bytes < 1024 * 1024) return (bytes / 1024).toFixed(0) + ' KB';
+                return (bytes / 1024 / 1024).toFixed(1) + ' MB';
+            }
+
+            // The list is rebuilt on focus so files rotated since the window opened show up
+            async function refreshFiles() {
+                const select = document.getElementById('filesSelect');
+                try {
+                    const files = await window.__TAURI__.core.invoke('list_log_files');
+                    select.innerHTML = '<option value="">Log Files...</option>';
+                    for (const file of files) {
+                        const option = document.createElement('option');
+                        option.value = file.name;
+                        const when = file.current ? 'Current' : new Date(file.modified).toLocaleString();
+                        option.textContent = when + ' (' + formatSize(file.size) + ')';
+                        select.appendChild(option);
+                    }
+                    const folder = document.createElement('option');
+                    folder.value = '__folder__';
+                    folder.textContent = 'Open Logs Folder';
+                    select.appendChild(folder);
+                } catch (e) {
+                    console.error('Failed to list log files:', e);
+                }
+            }
+
+            async function openFile() {
+                const select = document.getElementById('filesSelect');
+                const name = select.value;
+                select.value = '';
+                if (!name) return;
+                try {
+                    if (name === '__folder__') {
+                        await window.__TAURI__.core.invoke('open_logs_folder');
+                    } else {
+                        await window.__TAURI__.core.invoke('open_log_file', { name });
+                    }
+                } catch (e) {
+                    document.getElementById('capture').textContent = String(e);
+                }
+            }
+
             document.getElementById('refreshBtn').onclick = refreshLogs;
             document.getElementById('clearBtn').onclick = clearLogs;
+            document.getElementById('filesSelect').onfocus = refreshFiles;
+            document.getElementById('filesSelect').onchange = openFile;
+            document.getElementById('levelSelect').onchange = changeLevel;
+            document.getElementById('captureBtn').onclick = toggleCapture;
 
             refreshLogs();
+            refreshLevel();
+            refreshFiles();
             setInterval(refreshLogs, 2000);
+            setInterval(refreshLevel, 30000);
         "#;
 
         // Wait a moment for the page to load, then inject our UI
+        let log_html = window_script(log_html, false);
         let win_clone = win.clone();
         tauri::async_runtime::spawn(async move {
             tokio::time::sleep(std::time::Duration::from_millis(500)).await;
-            let _ = win_clone.eval(log_html);
+            let _ = win_clone.eval(&log_html);
             // Show window after content is injected
             tokio::time::sleep(std::time::Duration::from_millis(50)).await;
             let _ = win_clone.show();
@@ -592,18 +936,78 @@ fn refresh_main_window(app: &AppHandle) {
     if let Some(window) = app.get_webview_window("main") {
         let url = get_server_url();
         // Using Tauri's webview eval API to navigate - this is safe as we control the URL
-        let _ = window.eval(&format!("window.location.href = '{}'", url));
+        let _ = window.eval(format!("window.location.href = '{}'", url));
     }
 }
 
-/// Clear cookies and browsing data from all windows
-fn clear_cookies(app: &AppHandle) {
-    if let Some(window) = app.get_webview_window("main") {
-        let _ = window.clear_all_browsing_data();
-        // Refresh the window after clearing - using Tauri's webview eval API with app-controlled URL
-        let url = get_server_url();
-        let _ = window.eval(&format!("window.location.href = '{}'", url));
+/// Versions, database and build details for the About window
+#[tauri::command]
+async fn get_about_info(app: AppHandle, manager: tauri::State<'_, SharedServerManager>) -> Result<AboutInfo, String> {
+    let update_channel = updater::current_channel(&app).await;
+    let mgr = manager.lock().await;
+    let data_dir = mgr.data_dir();
+    Ok(AboutInfo {
+        version: APP_VERSION.to_string(),
+        sidecar_version: mgr.sidecar_version().map(String::from),
+        database: if read_database_url(data_dir).is_some() { "PostgreSQL" } else { "SQLite" },
+        data_dir: data_dir.to_string_lossy().to_string(),
+        license: "AGPL-3.0",
+        commit: env!("MONEYWRIGHT_BUILD_COMMIT"),
+        target: env!("MONEYWRIGHT_BUILD_TARGET"),
+        profile: env!("MONEYWRIGHT_BUILD_PROFILE"),
+        tauri_version: tauri::VERSION,
+        update_channel,
+    })
+}
+
+/// Server version, schema version and database backend, compared with the app's
+#[tauri::command]
+async fn get_server_info(app: AppHandle, manager: tauri::State<'_, SharedServerManager>) -> Result<ServerInfo, String> {
+    let (url, data_dir) = {
+        let mgr = manager.lock().await;
+        (mgr.url(), mgr.data_dir().clone())
+    };
+    let build = server::fetch_build(&url).await;
+    let bundled = app
+        .path()
+        .resource_dir()
+        .ok()
+        .and_then(|dir| doctor::bundled_migrations(&dir, read_database_url(&data_dir).is_some()));
+
+    let mut mismatches = Vec::new();
+    if let Some(build) = &build {
+        // Development servers report "dev"
+        if build.version != APP_VERSION && build.version != "dev" {
+            mismatches.push(format!("The server is version {} but the app is {}", build.version, APP_VERSION));
+        }
+        match (build.schema_version, bundled) {
+            (Some(applied), Some(bundled)) if applied > bundled => mismatches.push(format!(
+                "The database has {} migrations but the app only bundles {}",
+                applied, bundled
+            )),
+            (Some(applied), Some(bundled)) if applied < bundled => mismatches.push(format!(
+                "{} of the {} bundled migrations are applied",
+                applied, bundled
+            )),
+            _ => {}
+        }
     }
+    Ok(ServerInfo {
+        app_version: APP_VERSION.to_string(),
+        server_version: build.as_ref().map(|b| b.version.clone()),
+        schema_version: build.as_ref().and_then(|b| b.schema_version),
+        bundled_migrations: bundled,
+        database: build.as_ref().map(|b| b.database.db_type.clone()),
+        runtime: build.and_then(|b| b.runtime),
+        mismatches,
+    })
+}
+
+/// Open the data directory in the file manager
+#[tauri::command]
+async fn reveal_data_dir(manager: tauri::State<'_, SharedServerManager>) -> Result<(), String> {
+    let data_dir = manager.lock().await.data_dir().clone();
+    open::that(&data_dir).map_err(|e| format!("Failed to open {}: {}", data_dir.display(), e))
 }
 
 /// Open the about window
@@ -615,13 +1019,14 @@ fn open_about_window(app: &AppHandle) {
         return;
     }
 
+    let (width, height) = display::text_scaled_size(app, (400.0, 600.0));
     let window = WebviewWindowBuilder::new(
         app,
         "about",
-        WebviewUrl::App("/".into()),
+        WebviewUrl::CustomProtocol(protocol::page_url("window")),
     )
     .title("About Moneywright")
-    .inner_size(400.0, 380.0)
+    .inner_size(width, height)
     .resizable(false)
     .maximizable(false)
     .minimizable(false)
@@ -629,12 +1034,7 @@ fn open_about_window(app: &AppHandle) {
     .build();
 
     if let Ok(win) = window {
-        let version = APP_VERSION;
-        // Use correct port for logo: 3000 in dev, 17777 in production
-        #[cfg(debug_assertions)]
-        let logo_url = "http://localhost:3000/logo.png";
-        #[cfg(not(debug_assertions))]
-        let logo_url = "http://localhost:17777/logo.png";
+        let logo_url = protocol::page_url("logo.png");
 
         // Injecting static HTML into our own about window using Tauri's webview eval API
         // Colors match web app's dark mode design tokens from index.css
@@ -650,10 +1050,9 @@ fn open_about_window(app: &AppHandle) {
     <meta charset="UTF-8">
     <title>About Moneywright</title>
     <style>
-        @import url('https://fonts.googleapis.com/css2?family=DM+Sans:wght@400;500;600&family=Outfit:wght@500;600&display=swap');
         * {{ margin: 0; padding: 0; box-sizing: border-box; }}
         body {{
-            font-family: 'DM Sans', -apple-system, BlinkMacSystemFont, sans-serif;
+            font-family: system-ui, -apple-system, BlinkMacSystemFont, 'Segoe UI', sans-serif;
             background: #030303;
             color: #fafafa;
             height: 100vh;
@@ -684,7 +1083,6 @@ fn open_about_window(app: &AppHandle) {
             border-radius: 16px;
         }}
         h1 {{
-            font-family: 'Outfit', sans-serif;
             font-size: 22px;
             font-weight: 600;
             letter-spacing: -0.02em;
@@ -715,7 +1113,7 @@ fn open_about_window(app: &AppHandle) {
             transition: color 0.15s ease;
             cursor: pointer;
         }}
-        .links a:hover {{
+        .links a:hover, .links a:focus-visible {{
             color: #10b981;
         }}
         .license {{
@@ -731,27 +1129,138 @@ fn open_about_window(app: &AppHandle) {
         .license a:hover {{
             color: #10b981;
         }}
+        .stats {{
+            margin-top: 20px;
+            display: grid;
+            grid-template-columns: auto auto;
+            gap: 4px 16px;
+            font-size: 11px;
+            color: #52525b;
+            text-align: left;
+        }}
+        .stats span:nth-child(even) {{
+            color: #a1a1aa;
+            text-align: right;
+        }}
+        .stats select {{
+            font: inherit;
+            color: inherit;
+            background: transparent;
+            border: none;
+            text-align: right;
+        }}
+        .data-dir {{
+            margin-top: 12px;
+            max-width: 336px;
+            font-size: 11px;
+            color: #52525b;
+        }}
+        .data-dir .path {{
+            display: block;
+            word-break: break-all;
+            margin-bottom: 4px;
+            user-select: text;
+            -webkit-user-select: text;
+        }}
+        .data-dir a {{
+            color: #71717a;
+            text-decoration: none;
+            cursor: pointer;
+        }}
+        .data-dir a:hover, .data-dir a:focus-visible {{
+            color: #10b981;
+        }}
     </style>
 </head>
 <body>
     <div class="logo-container">
         <div class="logo-glow"></div>
-        <img src="{}" class="logo" onerror="this.parentElement.style.display='none'" />
+        <img src="{}" class="logo" alt="" onerror="this.parentElement.style.display='none'" />
     </div>
     <h1>Moneywright</h1>
-    <div class="version">{1}</div>
+    <div class="version" id="version"></div>
     <div class="description">
         Private, AI-Powered Personal Finance Manager
     </div>
-    <div class="links">
+    <nav class="links" aria-label="Links">
         <a data-url="https://moneywright.com">Website</a>
         <a data-url="https://github.com/moneywright/moneywright">GitHub</a>
         <a data-url="https://moneywright.com/docs">Docs</a>
+    </nav>
+    <div class="license">Open Source · <a data-url="https://github.com/moneywright/moneywright/blob/main/LICENSE" id="license">AGPL-3.0</a></div>
+    <div class="stats" id="details" role="group" aria-label="Installation details"></div>
+    <div class="data-dir">
+        <span class="path" id="dataDir"></span>
+        <a role="button" tabindex="0" id="revealBtn">Show in Folder</a>
     </div>
-    <div class="license">Open Source · <a data-url="https://github.com/moneywright/moneywright/blob/main/LICENSE">AGPL-3.0</a></div>
+    <div class="stats" id="stats" role="group" aria-label="Session statistics"></div>
 </body>
 </html>`;
 
+            // Versions and paths come from the app rather than being baked into the page
+            tauriApi.core.invoke('get_about_info').then(info => {{
+                document.getElementById('version').textContent = info.version;
+                document.getElementById('license').textContent = info.license;
+                document.getElementById('dataDir').textContent = info.data_dir;
+                const rows = [
+                    ['Server', info.sidecar_version || 'Not running'],
+                    ['Database', info.database],
+                    ['Build', (info.commit || 'unknown') + ' (' + info.profile + ')'],
+                    ['Target', info.target],
+                    ['Tauri', info.tauri_version],
+                ];
+                document.getElementById('details').innerHTML = rows
+                    .map(([label, value]) => '<span>' + label + '</span><span>' + escapeHtml(value) + '</span>')
+                    .join('')
+                    + '<span>Updates</span><span><select id="channelSelect" aria-label="Update channel">'
+                    + '<option value="stable">Stable</option><option value="beta">Beta</option><option value="nightly">Nightly</option>'
+                    + '</select></span>';
+                const channelSelect = document.getElementById('channelSelect');
+                channelSelect.value = info.update_channel;
+                channelSelect.addEventListener('change', () => {{
+                    tauriApi.core.invoke('set_update_channel', {{ channel: channelSelect.value }})
+                        .catch(() => {{ channelSelect.value = info.update_channel; }});
+                }});
+                return tauriApi.core.invoke('get_server_info');
+            }}).then(server => {{
+                if (!server || !server.server_version) return;
+                const rows = [
+                    ['Schema', server.schema_version == null ? 'unknown' : server.schema_version + ' of ' + (server.bundled_migrations ?? '?') + ' migrations'],
+                    ['Runtime', server.runtime || 'unknown'],
+                ].concat(server.mismatches.map(m => ['Out of sync', m]));
+                document.getElementById('details').insertAdjacentHTML('beforeend', rows
+                    .map(([label, value]) => '<span>' + label + '</span><span>' + escapeHtml(value) + '</span>')
+                    .join(''));
+            }}).catch(() => {{}});
+            const revealBtn = document.getElementById('revealBtn');
+            revealBtn.addEventListener('click', () => tauriApi.core.invoke('reveal_data_dir'));
+            revealBtn.addEventListener('keydown', (e) => {{
+                if (e.key === 'Enter' || e.key === ' ') {{
+                    e.preventDefault();
+                    revealBtn.click();
+                }}
+            }});
+
+            // Session statistics (useful context when reporting stability problems)
+            function formatDuration(secs) {{
+                const h = Math.floor(secs / 3600);
+                const m = Math.floor((secs % 3600) / 60);
+                return h > 0 ? h + 'h ' + m + 'm' : m + 'm';
+            }}
+            tauriApi.core.invoke('get_session_stats').then(s => {{
+                const rows = [
+                    ['App uptime', formatDuration(s.current.app_uptime_secs)],
+                    ['Server uptime', formatDuration(s.current.server_uptime_secs)],
+                    ['Sessions', s.sessions],
+                    ['Server restarts', s.totals.server_restarts],
+                    ['Server crashes', s.totals.server_crashes],
+                    ['Unclean exits', s.app_crashes],
+                ];
+                document.getElementById('stats').innerHTML = rows
+                    .map(([label, value]) => '<span>' + label + '</span><span>' + value + '</span>')
+                    .join('');
+            }}).catch(() => {{}});
+
             // Attach click handlers to all links with data-url attribute
             document.querySelectorAll('a[data-url]').forEach(link => {{
                 link.addEventListener('click', (e) => {{
@@ -762,13 +1271,13 @@ fn open_about_window(app: &AppHandle) {
                     }}
                 }});
             }});
-        "#, logo_url, version);
+        "#, logo_url);
 
         let win_clone = win.clone();
         tauri::async_runtime::spawn(async move {
             tokio::time::sleep(std::time::Duration::from_millis(500)).await;
             // Using Tauri's webview eval API to inject static HTML - safe as content is hardcoded
-            let _ = win_clone.eval(&about_html);
+            let _ = win_clone.eval(window_script(&about_html, true));
             // Show window after content is injected
             tokio::time::sleep(std::time::Duration::from_millis(50)).await;
             let _ = win_clone.show();
@@ -787,29 +1296,186 @@ fn trigger_update_check(app: &AppHandle) {
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    let mut startup_timer = StartupTimer::start();
+
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_process::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_autostart::init(tauri_plugin_autostart::MacosLauncher::LaunchAgent, None))
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        // Bundled pages that work without the server, see protocol.rs
+        .register_uri_scheme_protocol(protocol::SCHEME, |_ctx, request| protocol::handle(&request))
+        // The app itself when the server listens on a socket, see transport.rs
+        .register_asynchronous_uri_scheme_protocol(transport::SCHEME, |_ctx, request, responder| {
+            tauri::async_runtime::spawn(async move {
+                responder.respond(transport::proxy(request).await);
+            });
+        })
         .invoke_handler(tauri::generate_handler![
             get_initial_state,
             start_server_cmd,
             stop_server_cmd,
+            pause_server_cmd,
+            resume_server_cmd,
             restart_server_cmd,
             open_browser_cmd,
             open_url,
+            get_about_info,
+            get_server_info,
+            events::event_contract,
+            resources::get_resource_status,
+            resources::repair_resources,
+            resources::reinstall_app,
+            webcache::reset_web_cache,
+            browsing::list_site_data,
+            browsing::clear_site_data,
+            idle::get_idle_status,
+            locale::get_locale_format,
+            notifications::send_notification,
+            fx::get_fx_rates,
+            transfer::export_desktop_settings,
+            transfer::import_desktop_settings,
+            loglevel::get_server_log_level,
+            loglevel::set_server_log_level,
+            uibuild::get_ui_build,
+            uibuild::set_ui_build,
+            loglevel::capture_debug_logs,
+            loglevel::stop_debug_capture,
+            proxy::check_reverse_proxy,
+            proxy::set_external_url,
+            lan::get_lan_access,
+            lan::set_lan_access,
+            tls::get_tls_status,
+            tls::set_tls,
+            tls::regenerate_tls_certificate,
+            tls::export_tls_certificate,
+            recovery::check_database_damage,
+            recovery::recover_database,
+            backup::open_backups,
+            history::get_task_history,
+            netusage::get_network_usage,
+            relocation::get_previous_data,
+            relocation::migrate_previous_data,
+            relocation::skip_previous_data,
+            alerts::send_test_alert,
+            alerts::set_alert_smtp_password,
+            fx::convert_currency,
+            fx::refresh_fx_rates,
+            notifications::get_notification_status,
+            oauth::oauth_redirect_uri,
+            oauth::start_oauth,
+            reveal_data_dir,
             get_logs,
             clear_logs,
+            logs::list_log_files,
+            logs::open_log_file,
+            logs::open_logs_folder,
             quit_app_cmd,
             download_update,
             check_update_available,
             show_update_window,
             start_background_update,
+            resume_update_download,
+            download::pause_update_download,
+            download::cancel_update_download,
             restart_for_update,
+            get_startup_benchmark,
+            crash::list_crash_reports,
+            crash::get_crash_report,
+            crash::delete_crash_report,
+            crash::export_crash_report,
+            doctor::run_doctor,
+            doctor::open_diagnostics,
+            backup::list_backups,
+            backup::create_backup_now,
+            backup::verify_backup,
+            backup::preview_restore,
+            backup::restore_backup,
+            backup::export_backup,
+            backup::delete_backup,
+            quarantine::list_quarantine,
+            quarantine::restore_from_quarantine,
+            quarantine::empty_quarantine,
+            serverstats::get_server_stats,
+            portconflict::get_port_conflict,
+            portconflict::stop_port_conflict,
+            database::get_database_config,
+            database::test_database_connection,
+            database::apply_database_config,
+            pgmigrate::migrate_to_postgres,
+            climigrate::detect_cli_install,
+            climigrate::migrate_cli_data,
+            service::get_service_status,
+            service::install_service,
+            service::uninstall_service,
+            remote::get_remote_server,
+            remote::test_remote_server,
+            remote::set_remote_server,
+            updater::set_update_channel,
+            updater::skip_update_version,
+            netproxy::get_proxy_status,
+            netproxy::set_proxy_password,
+            upgrade::get_upgrade_status,
+            upgrade::rollback_upgrade,
+            upgrade::open_release,
+            envconfig::get_env_config,
+            envconfig::set_env_config,
+            uninstall::get_uninstall_info,
+            uninstall::run_uninstall_cleanup,
+            uninstall::finish_uninstall,
+            settings::get_settings,
+            settings::update_settings,
+            flags::list_feature_flags,
+            flags::set_feature_flag,
+            analytics::get_usage_stats,
+            analytics::record_feature_usage,
+            analytics::export_usage_stats,
+            analytics::clear_usage_stats,
+            sessions::get_session_stats,
+            importer::detect_import_files,
+            importer::preview_import,
+            importer::list_import_profiles,
+            importer::run_import,
+            exports::list_export_tasks,
+            exports::save_export_task,
+            exports::delete_export_task,
+            exports::run_export_task,
+            attachments::get_attachment_usage,
+            attachments::find_orphan_attachments,
+            attachments::export_attachments,
+            attachments::open_attachments_folder,
+            archive::export_all_data,
+            report::create_problem_report,
+            profiles::list_profiles,
+            profiles::create_profile,
+            profiles::rename_profile,
+            profiles::delete_profile,
+            profiles::start_profile,
+            profiles::switch_profile,
+            profiles::stop_profile,
+            demo::start_demo,
+            demo::end_demo,
+            sandbox::start_sandbox,
+            sandbox::get_sandbox,
+            sandbox::end_sandbox,
+            a11y::get_system_a11y_prefs,
+            shortcuts::list_shortcuts,
+            power::get_power_status,
+            power::run_on_battery_anyway,
+            jobs::list_jobs,
+            jobs::cancel_job,
+            onboarding::get_onboarding_state,
+            onboarding::complete_onboarding,
         ])
         .setup(move |app| {
+            startup_timer.mark("tauri_init");
             let handle = app.handle().clone();
 
+            // Create batched log emitter (must exist before anything logs)
+            app.manage(create_log_emitter(&handle));
+
             // Create log store
             #[allow(unused_variables)]
             let log_store: SharedLogStore = Arc::new(Mutex::new(LogStore::new()));
@@ -822,9 +1488,119 @@ pub fn run() {
             // Create server manager with app handle (for data directory)
             let server_manager = create_server_manager(&handle);
             app.manage(server_manager.clone());
+            let data_dir = tauri::async_runtime::block_on(async {
+                isolation::log_notices(&handle, &log_store).await;
+                server_manager.lock().await.data_dir().clone()
+            });
+            history::init(&data_dir);
+            netusage::init(&data_dir);
+            cleanups::init(&data_dir);
+            orphans::init(&data_dir);
+            logs::init(&data_dir);
+            service::init(&data_dir);
+            upgrade::init(&data_dir);
+            download::init(&data_dir);
+            quarantine::init(&data_dir);
+
+            // Load desktop settings (settings.toml), migrating older versions
+            let settings = create_settings_store(&data_dir);
+            app.manage(settings.clone());
+            let transport = tauri::async_runtime::block_on(async { transport::init(&settings.lock().await.get(), &data_dir) });
+            if let Err(e) = transport {
+                eprintln!("Warning: {}", e);
+            }
+            tauri::async_runtime::block_on(async { remote::init(&settings.lock().await.get()) });
+            let settings_rx = tauri::async_runtime::block_on(async { settings.lock().await.subscribe() });
+            spawn_settings_logger(handle.clone(), log_store.clone(), settings_rx);
+
+            // Scheduled maintenance (nightly restart, backups, exports), re-checked on settings changes
+            let scheduler_rx = tauri::async_runtime::block_on(async { settings.lock().await.subscribe() });
+            start_scheduler(handle.clone(), scheduler_rx);
+            start_export_scheduler(handle.clone());
+            let autostart_rx = tauri::async_runtime::block_on(async { settings.lock().await.subscribe() });
+            spawn_autostart_sync(handle.clone(), autostart_rx);
+            let capture_rx = tauri::async_runtime::block_on(async { settings.lock().await.subscribe() });
+            spawn_capture_protection_sync(handle.clone(), capture_rx);
+            // Before setup_menu so the first menu already uses the configured shortcuts
+            let shortcuts_rx = tauri::async_runtime::block_on(async { settings.lock().await.subscribe() });
+            shortcuts::spawn_shortcut_sync(handle.clone(), shortcuts_rx);
+            let display_rx = tauri::async_runtime::block_on(async { settings.lock().await.subscribe() });
+            display::spawn_display_sync(handle.clone(), display_rx);
+            // The main window is built here rather than from the config, so it gets `[webview]`
+            let webview_rx = tauri::async_runtime::block_on(async { settings.lock().await.subscribe() });
+            webview::spawn_webview_sync(webview_rx);
+            tauri::async_runtime::block_on(async {
+                let rendering = webview::apply_rendering(&*settings.lock().await);
+                match rendering {
+                    Ok(true) => log_line(&handle, &log_store, "Software rendering is on (webview.software_rendering)", "info").await,
+                    Ok(false) => {}
+                    Err(e) => log_line(&handle, &log_store, format!("Failed to save the rendering setting: {}", e), "error").await,
+                }
+            });
+            webview::create_main_window(&handle)?;
+            a11y::start_a11y_watcher(handle.clone());
+            idle::start_idle_watcher(handle.clone());
+            alerts::start_watchdog(handle.clone());
+            server::start_liveness_watchdog(handle.clone(), server_manager.clone(), log_store.clone());
+            server::start_idle_tracker(handle.clone(), server_manager.clone(), log_store.clone());
+            start_quit_install_checks(handle.clone());
+            serverstats::start_stats_monitor(handle.clone(), server_manager.clone());
+            notifications::start_notification_delivery(handle.clone());
+            fx::start_fx_refresh(handle.clone());
+            arch::start(handle.clone());
+
+            // Capture panics and detect unclean exits of the previous session
+            let previous_unclean = install_crash_handler(data_dir.clone());
+            // After the crash handler, which dates an unclean exit by the previous beat
+            heartbeat::start(handle.clone(), &data_dir);
+
+            // Uptime, restart and crash statistics across sessions
+            let session_tracker = create_session_tracker(&data_dir, previous_unclean);
+            app.manage(session_tracker.clone());
+            start_session_checkpoints(session_tracker);
+
+            // Local control socket for moneywrightctl
+            start_control_server(handle.clone(), data_dir.clone());
+            startup_timer.mark("app_state");
+
+            // Extra profiles run their own servers on demand, see profiles.rs
+            app.manage(create_profile_servers(data_dir.clone()));
+            demo::remove_stale_demo_dirs();
+            sandbox::remove_stale_sandbox_dirs();
+            // Lets the main window accept the server's own certificate, see tls.rs
+            if let Some(window) = handle.get_webview_window("main") {
+                tls::trust_in_webview(&window);
+            }
 
             // Setup menu
             setup_menu(&handle)?;
+            profiles::update_main_title(&handle);
+            startup_timer.mark("menu");
+
+            // First run: the onboarding window starts the server once it's done
+            let first_run = needs_onboarding(&data_dir) || relocation::is_interrupted(&handle);
+            if first_run {
+                // An identifier rename leaves the data in the old folder, offer to bring it over
+                match relocation::find_previous_data_dir(&handle) {
+                    Some(_) => relocation::open_migration_window(&handle),
+                    None => open_onboarding_window(&handle),
+                }
+            }
+
+            // `--demo` opens a sample-data session next to the regular window
+            if std::env::args().any(|arg| arg == "--demo") {
+                let app_handle = handle.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = demo::start(&app_handle).await {
+                        emit_log(&app_handle, &e, "error");
+                    }
+                });
+            }
+
+            // The Windows uninstaller asks what should happen to the data before removing the app
+            if uninstall::requested() {
+                uninstall::open_uninstall_window(&handle);
+            }
 
             // In debug/dev mode, skip starting sidecar - use external dev servers
             // Run `bun run dev` separately to start API (17777) and Web (3000)
@@ -840,22 +1616,74 @@ pub fn run() {
             {
                 let manager = server_manager.clone();
                 let app_handle = handle.clone();
+                let layout_dir = data_dir.clone();
 
                 tauri::async_runtime::block_on(async move {
+                    if first_run {
+                        return;
+                    }
+                    let settings = settings.lock().await.get();
+                    if !settings.server.auto_start {
+                        println!("Server auto-start disabled in settings");
+                        return;
+                    }
+                    let window_hidden = app_handle
+                        .get_webview_window("main")
+                        .is_none_or(|window| !window.is_visible().unwrap_or(true));
+                    if settings.server.lazy_start && window_hidden {
+                        println!("Server starts once the window is shown (server.lazy_start)");
+                        server::defer_start(manager).await;
+                        return;
+                    }
                     match start_server(app_handle.clone(), manager, log_store).await {
                         Ok(_) => {
                             println!("Server started successfully at {}", get_server_url());
+                            // Windows as the user left them on this set of displays
+                            if let Err(e) = layouts::restore_layout(&app_handle, &layout_dir) {
+                                eprintln!("Warning: Failed to restore window layout: {}", e);
+                            }
+                            if settings.general.open_browser_on_start {
+                                if let Err(e) = open_in_browser() {
+                                    eprintln!("Warning: {}", e);
+                                }
+                            }
                         }
                         Err(e) => {
                             eprintln!("Failed to start server: {}", e);
+                            protocol::show_error(&app_handle, &e);
+                            alerts::report_start_failure(&app_handle, &e);
                         }
                     }
                 });
+                startup_timer.mark("server_start");
             }
 
+            let startup_ms = startup_timer.finish(&data_dir);
+            record_launch(&handle, startup_ms);
+
             Ok(())
         })
+        .on_page_load(|webview, payload| {
+            // Windows opened after the setting was applied pick it up here
+            if capture_protected() {
+                let _ = webview.window().set_content_protected(true);
+            }
+            if payload.event() == tauri::webview::PageLoadEvent::Finished {
+                if let Some(window) = webview.app_handle().get_webview_window(webview.label()) {
+                    display::on_page_load(&window);
+                    webcache::on_page_load(&window);
+                }
+            }
+        })
         .on_window_event(|window, event| {
+            // Per-display zoom follows the window across displays
+            match event {
+                tauri::WindowEvent::Moved(_) => display::on_window_changed(window.app_handle(), window.label(), false),
+                tauri::WindowEvent::ScaleFactorChanged { .. } => display::on_window_changed(window.app_handle(), window.label(), true),
+                tauri::WindowEvent::Destroyed => display::forget_window(window.label()),
+                tauri::WindowEvent::Focused(true) if window.label() == "main" => server::main_window_shown(window.app_handle()),
+                _ => {}
+            }
             if let tauri::WindowEvent::CloseRequested { api, .. } = event {
                 if window.label() == "main" {
                     #[cfg(target_os = "macos")]
@@ -867,30 +1695,206 @@ pub fn run() {
                     }
                     #[cfg(not(target_os = "macos"))]
                     {
-                        // Windows/Linux: Quit app and kill server (only in release mode)
-                        #[cfg(not(debug_assertions))]
-                        let _ = kill_process_on_port(SERVER_PORT);
+                        // Windows/Linux: Quit app, the exit handler stops the server
+                        let _ = api;
                         window.app_handle().exit(0);
                     }
+                } else if (window.label() == "onboarding" && onboarding::is_active())
+                    || (window.label() == relocation::WINDOW_LABEL && relocation::is_pending())
+                {
+                    // Main window is hidden during onboarding, so closing it means quitting
+                    window.app_handle().exit(0);
+                }
+            }
+            // Closing a profile's window stops its server
+            if let tauri::WindowEvent::Destroyed = event {
+                if let Some(id) = window.label().strip_prefix(profiles::WINDOW_PREFIX) {
+                    let app = window.app_handle().clone();
+                    let id = id.to_string();
+                    tauri::async_runtime::spawn(async move {
+                        let _ = profiles::shut_down(&app, &id).await;
+                    });
+                } else if demo::is_demo_window(window.label()) {
+                    // Closing the demo window wipes its data
+                    let app = window.app_handle().clone();
+                    tauri::async_runtime::spawn(async move {
+                        demo::end(&app).await;
+                    });
+                } else if sandbox::is_sandbox_window(window.label()) {
+                    // So does closing the sandbox window
+                    let app = window.app_handle().clone();
+                    tauri::async_runtime::spawn(async move {
+                        sandbox::end(&app).await;
+                    });
                 }
             }
         })
         .on_menu_event(|app, event| {
+            record_feature(app, &format!("menu.{}", event.id().as_ref()));
             match event.id().as_ref() {
                 "about" => open_about_window(app),
                 "check_updates" => trigger_update_check(app),
                 "refresh" => refresh_main_window(app),
+                "pause_server" => {
+                    let app = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        let manager = app.state::<SharedServerManager>().inner().clone();
+                        let log_store = app.state::<SharedLogStore>().inner().clone();
+                        let _ = if server::is_paused() {
+                            resume(app, manager, log_store).await
+                        } else {
+                            pause(app, manager).await
+                        };
+                    });
+                }
                 "open_browser" => {
-                    let _ = open::that(get_server_url());
+                    if let Err(e) = open_in_browser() {
+                        emit_log(app, &e, "error");
+                    }
                 }
                 "logs" => open_logs_window(app),
-                "clear_cookies" => clear_cookies(app),
-                "quit" => {
-                    // Kill server process synchronously before exit (only in release mode)
-                    #[cfg(not(debug_assertions))]
-                    let _ = kill_process_on_port(SERVER_PORT);
-                    app.exit(0);
+                "task_history" => history::open_history_window(app),
+                "crash_reports" => open_crash_reports_window(app),
+                "doctor" => open_doctor_window(app),
+                "reverse_proxy" => proxy::open_proxy_window(app),
+                "lan_access" => lan::open_lan_window(app),
+                "tls" => tls::open_tls_window(app),
+                "database" => open_database_window(app),
+                "server_env" => envconfig::open_env_window(app),
+                "service" => service::open_service_window(app),
+                "remote_server" => remote::open_remote_window(app),
+                "uninstall" => uninstall::open_uninstall_window(app),
+                "backups" => open_backups_window(app),
+                "exports" => open_exports_window(app),
+                "attachments" => open_attachments_window(app),
+                "preview_ui" => {
+                    let app = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = uibuild::toggle(&app).await {
+                            emit_log(&app, &e, "error");
+                        }
+                        // Clicking a check item toggles it, rebuild to show the real state
+                        let _ = setup_menu(&app);
+                    });
+                }
+                "hide_from_capture" => {
+                    let app = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = settings::toggle_capture_protection(&app).await {
+                            emit_log(&app, &e, "error");
+                        }
+                    });
+                }
+                "export_all" => {
+                    let app = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        let data_dir = app.state::<SharedServerManager>().lock().await.data_dir().clone();
+                        match archive::export_all(&app, data_dir, None).await {
+                            Ok(path) => emit_log(&app, &format!("Saved all data to {}", path.display()), "info"),
+                            Err(e) => emit_log(&app, &e, "error"),
+                        }
+                    });
+                }
+                "docs" | "release_notes" | "community" => open_help_link(app, event.id().as_ref()),
+                "shortcuts" => shortcuts::open_shortcuts_window(app),
+                "lock" => protocol::lock_windows(app),
+                "preferences" => notifications::open_preferences_window(app),
+                "converter" => fx::open_converter_window(app),
+                "transfer_settings" => transfer::open_transfer_window(app),
+                "new_window" => {
+                    if let Err(e) = layouts::open_new_window(app) {
+                        emit_log(app, &e, "error");
+                    }
+                }
+                id if id.starts_with("layout:") => {
+                    if let Err(e) = layouts::apply_preset(app, id.trim_start_matches("layout:")) {
+                        emit_log(app, &e, "error");
+                    }
                 }
+                "save_layout" | "restore_layout" => {
+                    let app = app.clone();
+                    let save = event.id().as_ref() == "save_layout";
+                    tauri::async_runtime::spawn(async move {
+                        let data_dir = app.state::<SharedServerManager>().lock().await.data_dir().clone();
+                        let result = if save {
+                            layouts::save_layout(&app, &data_dir).map(|n| format!("Saved layout of {} windows for these displays", n))
+                        } else {
+                            layouts::restore_layout(&app, &data_dir).map(|restored| {
+                                if restored { "Restored window layout" } else { "No layout saved for these displays" }.to_string()
+                            })
+                        };
+                        match result {
+                            Ok(msg) => emit_log(&app, &msg, "info"),
+                            Err(e) => emit_log(&app, &e, "error"),
+                        }
+                    });
+                }
+                "zoom_in" | "zoom_out" | "zoom_reset" => {
+                    let change = match event.id().as_ref() {
+                        "zoom_in" => display::ZoomChange::In,
+                        "zoom_out" => display::ZoomChange::Out,
+                        _ => display::ZoomChange::Reset,
+                    };
+                    let app = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = display::change_zoom(&app, change).await {
+                            emit_log(&app, &e, "error");
+                        }
+                    });
+                }
+                id if id.starts_with("zoom:") => {
+                    if let Ok(zoom) = id.trim_start_matches("zoom:").parse::<f64>() {
+                        let app = app.clone();
+                        tauri::async_runtime::spawn(async move {
+                            if let Err(e) = display::change_zoom(&app, display::ZoomChange::To(zoom)).await {
+                                emit_log(&app, &e, "error");
+                            }
+                        });
+                    }
+                }
+                "report_problem" => {
+                    let app = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        match report::report_problem(&app).await {
+                            Ok(path) => emit_log(&app, &format!("Saved diagnostics bundle to {}", path.display()), "info"),
+                            Err(e) => emit_log(&app, &e, "error"),
+                        }
+                    });
+                }
+                "profiles" => open_profiles_window(app),
+                id if id.starts_with("switch_profile:") => {
+                    let app = app.clone();
+                    let id = id.trim_start_matches("switch_profile:").to_string();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = profiles::switch_to(&app, &id).await {
+                            emit_log(&app, &e, "error");
+                        }
+                    });
+                }
+                id if id.starts_with("profile:") => {
+                    let app = app.clone();
+                    let id = id.trim_start_matches("profile:").to_string();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = profiles::launch(&app, &id).await {
+                            emit_log(&app, &e, "error");
+                        }
+                    });
+                }
+                "demo" => {
+                    let app = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = demo::start(&app).await {
+                            emit_log(&app, &e, "error");
+                        }
+                    });
+                }
+                "usage" => open_usage_window(app),
+                "import_legacy" => open_import_window(app, None),
+                "clear_cookies" => browsing::open_clear_data_window(app),
+                "reset_web_cache" => {
+                    webcache::reset_all(app);
+                }
+                "quit" => app.exit(0),
                 _ => {}
             }
         })
@@ -907,11 +1911,32 @@ pub fn run() {
                     }
                 }
                 tauri::RunEvent::ExitRequested { .. } | tauri::RunEvent::Exit => {
-                    // Kill server process synchronously - this is critical for cleanup
-                    // We use the direct kill approach because async may not complete before termination
+                    flush_logs(app);
+                    if let Some(tracker) = app.try_state::<SharedSessionTracker>() {
+                        tauri::async_runtime::block_on(async { tracker.lock().await.checkpoint() });
+                    }
+                    mark_clean_exit();
+                    heartbeat::mark_exited();
+                    if app.try_state::<profiles::SharedProfileServers>().is_some() {
+                        profiles::stop_all(app);
+                    }
+                    demo::end_on_exit();
+                    sandbox::end_on_exit();
+
+                    // Stop the server synchronously, letting it finish its writes - this is
+                    // critical for cleanup, and async work may not complete before termination
                     // Only in release mode - don't kill dev servers
+                    // A background service keeps its server running
                     #[cfg(not(debug_assertions))]
-                    let _ = kill_process_on_port(SERVER_PORT);
+                    if let Some(manager) = app.try_state::<SharedServerManager>().filter(|_| !service::is_installed()) {
+                        let _ = tauri::async_runtime::block_on(stop_server(manager.inner().clone()));
+                    }
+                    if let Some(manager) = app.try_state::<SharedServerManager>() {
+                        let data_dir = tauri::async_runtime::block_on(async { manager.lock().await.data_dir().clone() });
+                        postgres::stop(app, &data_dir);
+                    }
+                    // An update kept for quitting ("on_quit" policy) goes in now the server is down
+                    updater::install_staged();
                 }
                 _ => {}
             }
@@ -919,46 +1944,151 @@ pub fn run() {
 }
 
 fn setup_menu(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+    // Accelerators come from the shortcuts settings, and submenu titles carry Alt mnemonics
+    // (`&`, ignored on macOS) so the menu works without a pointer
     // App submenu (macOS)
-    let about = MenuItem::with_id(app, "about", "About Moneywright", true, None::<&str>)?;
-    let check_updates = MenuItem::with_id(app, "check_updates", "Check for Updates...", true, None::<&str>)?;
-    let quit = MenuItem::with_id(app, "quit", "Quit Moneywright", true, Some("CmdOrCtrl+Q"))?;
+    let about = MenuItem::with_id(app, "about", "About Moneywright", true, shortcuts::accelerator("about").as_deref())?;
+    let check_updates = MenuItem::with_id(app, "check_updates", "Check for Updates...", true, shortcuts::accelerator("check_updates").as_deref())?;
+    let preferences = MenuItem::with_id(app, "preferences", "Preferences...", true, shortcuts::accelerator("preferences").as_deref())?;
+    let lock = MenuItem::with_id(app, "lock", "Lock Moneywright", true, shortcuts::accelerator("lock").as_deref())?;
+    let quit = MenuItem::with_id(app, "quit", "Quit Moneywright", true, shortcuts::accelerator("quit").as_deref())?;
 
     let app_menu = Submenu::with_items(
         app,
-        "Moneywright",
+        "&Moneywright",
         true,
         &[
             &about,
             &check_updates,
             &PredefinedMenuItem::separator(app)?,
+            &preferences,
+            &PredefinedMenuItem::separator(app)?,
+            &lock,
             &quit,
         ],
     )?;
 
     // View submenu
-    let refresh = MenuItem::with_id(app, "refresh", "Refresh", true, Some("CmdOrCtrl+R"))?;
-    let open_browser = MenuItem::with_id(app, "open_browser", "Open in Browser", true, Some("CmdOrCtrl+Shift+O"))?;
-    let logs = MenuItem::with_id(app, "logs", "View Logs", true, Some("CmdOrCtrl+L"))?;
+    let refresh = MenuItem::with_id(app, "refresh", "Refresh", true, shortcuts::accelerator("refresh").as_deref())?;
+    let pause_label = if server::is_paused() { "Resume Server" } else { "Pause Server" };
+    let pause_server = MenuItem::with_id(app, "pause_server", pause_label, true, shortcuts::accelerator("pause_server").as_deref())?;
+    let preview_ui = CheckMenuItem::with_id(
+        app,
+        "preview_ui",
+        "Use Preview Web App",
+        resources::has_preview(app),
+        uibuild::preview_selected(app),
+        shortcuts::accelerator("preview_ui").as_deref(),
+    )?;
+    let hide_from_capture = CheckMenuItem::with_id(
+        app,
+        "hide_from_capture",
+        "Hide from Screen Capture",
+        true,
+        capture_protected(),
+        shortcuts::accelerator("hide_from_capture").as_deref(),
+    )?;
+    let open_browser = MenuItem::with_id(app, "open_browser", "Open in Browser", true, shortcuts::accelerator("open_browser").as_deref())?;
+    let logs = MenuItem::with_id(app, "logs", "View Logs", true, shortcuts::accelerator("logs").as_deref())?;
+    let task_history = MenuItem::with_id(app, "task_history", "Task History...", true, shortcuts::accelerator("task_history").as_deref())?;
+    let crash_reports = MenuItem::with_id(app, "crash_reports", "Crash Reports", true, shortcuts::accelerator("crash_reports").as_deref())?;
+    let doctor = MenuItem::with_id(app, "doctor", "Run Diagnostics...", true, shortcuts::accelerator("doctor").as_deref())?;
+    let reverse_proxy = MenuItem::with_id(app, "reverse_proxy", "Reverse Proxy Assistant...", true, shortcuts::accelerator("reverse_proxy").as_deref())?;
+    let lan_access = MenuItem::with_id(app, "lan_access", "LAN Access...", true, shortcuts::accelerator("lan_access").as_deref())?;
+    let tls = MenuItem::with_id(app, "tls", "HTTPS...", true, shortcuts::accelerator("tls").as_deref())?;
+    let exports = MenuItem::with_id(app, "exports", "Scheduled Exports...", true, shortcuts::accelerator("exports").as_deref())?;
+    let backups = MenuItem::with_id(app, "backups", "Backups...", true, shortcuts::accelerator("backups").as_deref())?;
+    let attachments = MenuItem::with_id(app, "attachments", "Attachments...", true, shortcuts::accelerator("attachments").as_deref())?;
+    let export_all = MenuItem::with_id(app, "export_all", "Download All My Data...", true, shortcuts::accelerator("export_all").as_deref())?;
+    let transfer_settings = MenuItem::with_id(app, "transfer_settings", "Transfer Settings...", true, shortcuts::accelerator("transfer_settings").as_deref())?;
+    let database = MenuItem::with_id(app, "database", "Database Settings...", true, shortcuts::accelerator("database").as_deref())?;
+    let server_env = MenuItem::with_id(app, "server_env", "Server Environment...", true, shortcuts::accelerator("server_env").as_deref())?;
+    let service = MenuItem::with_id(app, "service", "Background Service...", true, shortcuts::accelerator("service").as_deref())?;
+    let remote_server = MenuItem::with_id(app, "remote_server", "Remote Server...", true, shortcuts::accelerator("remote_server").as_deref())?;
+    let converter = MenuItem::with_id(app, "converter", "Currency Converter", true, shortcuts::accelerator("converter").as_deref())?;
+    let usage = MenuItem::with_id(app, "usage", "Usage Statistics", true, shortcuts::accelerator("usage").as_deref())?;
+    let import_legacy = MenuItem::with_id(app, "import_legacy", "Import from Mint, YNAB or Quicken...", true, shortcuts::accelerator("import_legacy").as_deref())?;
+
+    // Zoom of the app window, remembered per display
+    let zoom_menu = Submenu::with_items(
+        app,
+        "&Zoom",
+        true,
+        &[
+            &MenuItem::with_id(app, "zoom_in", "Zoom In", true, shortcuts::accelerator("zoom_in").as_deref())?,
+            &MenuItem::with_id(app, "zoom_out", "Zoom Out", true, shortcuts::accelerator("zoom_out").as_deref())?,
+            &MenuItem::with_id(app, "zoom_reset", "Actual Size", true, shortcuts::accelerator("zoom_reset").as_deref())?,
+            &PredefinedMenuItem::separator(app)?,
+        ],
+    )?;
+    for zoom in display::PRESETS {
+        let label = format!("{}%", (zoom * 100.0).round());
+        zoom_menu.append(&MenuItem::with_id(app, format!("zoom:{}", zoom), label, true, None::<&str>)?)?;
+    }
 
     let view_menu = Submenu::with_items(
         app,
-        "View",
+        "&View",
         true,
         &[
             &refresh,
+            &pause_server,
+            &zoom_menu,
             &open_browser,
+            &import_legacy,
+            &database,
+            &server_env,
+            &service,
+            &remote_server,
+            &backups,
+            &exports,
+            &attachments,
+            &export_all,
+            &transfer_settings,
+            &converter,
             &PredefinedMenuItem::separator(app)?,
             &logs,
+            &task_history,
+            &crash_reports,
+            &doctor,
+            &reverse_proxy,
+            &lan_access,
+            &tls,
+            &usage,
+            &PredefinedMenuItem::separator(app)?,
+            &preview_ui,
+            &hide_from_capture,
         ],
     )?;
 
+    // Profiles submenu, checked entries are running
+    let manage_profiles = MenuItem::with_id(app, "profiles", "Manage Profiles...", true, shortcuts::accelerator("profiles").as_deref())?;
+    let demo = MenuItem::with_id(app, "demo", "Try with Sample Data", true, shortcuts::accelerator("demo").as_deref())?;
+    let profile_items = profiles::menu_entries(app)
+        .into_iter()
+        .map(|(id, name, running)| CheckMenuItem::with_id(app, id, name, true, running, None::<&str>))
+        .collect::<Result<Vec<_>, _>>()?;
+    // Switch To: which profile the main window shows, checked entry is active
+    let switch_menu = Submenu::with_id(app, "switch_profile_menu", "Switch To", true)?;
+    for (id, name, active) in profiles::switch_entries(app) {
+        switch_menu.append(&CheckMenuItem::with_id(app, id, name, true, active, None::<&str>)?)?;
+    }
+    let profiles_menu = Submenu::with_id(app, "profiles_menu", "&Profiles", true)?;
+    for item in &profile_items {
+        profiles_menu.append(item)?;
+    }
+    profiles_menu.append(&PredefinedMenuItem::separator(app)?)?;
+    profiles_menu.append(&switch_menu)?;
+    profiles_menu.append(&manage_profiles)?;
+    profiles_menu.append(&demo)?;
+
     // Edit submenu (for copy/paste)
-    let clear_cookies = MenuItem::with_id(app, "clear_cookies", "Clear Cookies", true, None::<&str>)?;
+    let clear_cookies = MenuItem::with_id(app, "clear_cookies", "Clear Browsing Data...", true, shortcuts::accelerator("clear_cookies").as_deref())?;
+    let reset_web_cache = MenuItem::with_id(app, "reset_web_cache", "Reset Web Cache", true, shortcuts::accelerator("reset_web_cache").as_deref())?;
 
     let edit_menu = Submenu::with_items(
         app,
-        "Edit",
+        "&Edit",
         true,
         &[
             &PredefinedMenuItem::undo(app, None)?,
@@ -970,15 +2100,31 @@ fn setup_menu(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
             &PredefinedMenuItem::select_all(app, None)?,
             &PredefinedMenuItem::separator(app)?,
             &clear_cookies,
+            &reset_web_cache,
         ],
     )?;
 
-    // Window submenu
-    let window_menu = Submenu::with_items(
+    // Window submenu, with layout presets for several app windows
+    let new_window = MenuItem::with_id(app, "new_window", "New Window", true, shortcuts::accelerator("new_window").as_deref())?;
+    let save_layout = MenuItem::with_id(app, "save_layout", "Save Layout for These Displays", true, shortcuts::accelerator("save_layout").as_deref())?;
+    let restore_layout = MenuItem::with_id(app, "restore_layout", "Restore Saved Layout", true, shortcuts::accelerator("restore_layout").as_deref())?;
+    let layouts_menu = Submenu::with_items(app, "&Layout", true, &[])?;
+    for preset in layouts::PRESETS {
+        layouts_menu.append(&MenuItem::with_id(app, format!("layout:{}", preset.id), preset.name, true, None::<&str>)?)?;
+    }
+    layouts_menu.append(&PredefinedMenuItem::separator(app)?)?;
+    layouts_menu.append(&save_layout)?;
+    layouts_menu.append(&restore_layout)?;
+
+    let window_menu = Submenu::with_id_and_items(
         app,
-        "Window",
+        WINDOW_SUBMENU_ID,
+        "&Window",
         true,
         &[
+            &new_window,
+            &layouts_menu,
+            &PredefinedMenuItem::separator(app)?,
             &PredefinedMenuItem::minimize(app, None)?,
             &PredefinedMenuItem::maximize(app, None)?,
             &PredefinedMenuItem::separator(app)?,
@@ -986,9 +2132,33 @@ fn setup_menu(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
         ],
     )?;
 
+    // Help submenu (the well-known id gives it the search field on macOS)
+    let report_problem = MenuItem::with_id(app, "report_problem", "Report a Problem...", true, shortcuts::accelerator("report_problem").as_deref())?;
+    let docs = MenuItem::with_id(app, "docs", "Documentation", true, shortcuts::accelerator("docs").as_deref())?;
+    let keyboard_shortcuts = MenuItem::with_id(app, "shortcuts", "Keyboard Shortcuts", true, shortcuts::accelerator("shortcuts").as_deref())?;
+    let release_notes = MenuItem::with_id(app, "release_notes", "Release Notes", true, shortcuts::accelerator("release_notes").as_deref())?;
+    let community = MenuItem::with_id(app, "community", "Community Discussions", true, shortcuts::accelerator("community").as_deref())?;
+    let uninstall = MenuItem::with_id(app, "uninstall", "Uninstall Moneywright...", true, shortcuts::accelerator("uninstall").as_deref())?;
+    let help_menu = Submenu::with_id_and_items(
+        app,
+        HELP_SUBMENU_ID,
+        "&Help",
+        true,
+        &[
+            &docs,
+            &keyboard_shortcuts,
+            &PredefinedMenuItem::separator(app)?,
+            &release_notes,
+            &community,
+            &PredefinedMenuItem::separator(app)?,
+            &report_problem,
+            &uninstall,
+        ],
+    )?;
+
     let menu = Menu::with_items(
         app,
-        &[&app_menu, &edit_menu, &view_menu, &window_menu],
+        &[&app_menu, &edit_menu, &view_menu, &profiles_menu, &window_menu, &help_menu],
     )?;
 
     app.set_menu(menu)?;
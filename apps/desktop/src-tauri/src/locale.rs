@@ -0,0 +1,233 @@
+// The OS's locale formatting conventions, for the web app to match before the user
+// has set preferences of their own
+//
+// The locale comes from the OS (LANG/LC_* on Linux, AppleLocale on macOS, LocaleName
+// on Windows) and is canonicalized to a BCP-47 tag with ICU's locale parser. The
+// separators, currency placement and date pattern are the user's own settings where
+// the OS exposes them (`locale -k` on Linux, the International registry key on
+// Windows, overrides in the global domain on macOS) and None otherwise, in which case
+// the web app formats with `Intl` for `locale`. The first day of the week falls back
+// to the region's CLDR default.
+
+use icu_locale_core::Locale;
+use serde::Serialize;
+#[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows"))]
+use crate::a11y::command_output;
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct LocaleFormat {
+    /// BCP-47 tag, e.g. "en-US"
+    pub locale: String,
+    pub decimal_separator: Option<String>,
+    pub grouping_separator: Option<String>,
+    /// Group sizes from the right, the last one repeating: [3] for 1,234,567, [3, 2] for 12,34,567
+    pub grouping: Option<Vec<u8>>,
+    pub currency_symbol: Option<String>,
+    /// Symbol before the amount ($1) rather than after (1 €)
+    pub currency_before: Option<bool>,
+    /// Space between symbol and amount
+    pub currency_space: Option<bool>,
+    /// 0 is Sunday, 1 Monday, ... 6 Saturday
+    pub first_day_of_week: u8,
+    /// Short date in Unicode (CLDR) pattern letters, e.g. "M/d/yyyy"
+    pub short_date: Option<String>,
+}
+
+/// "en_US.UTF-8@euro" / "en_US" / "en-US" to a canonical BCP-47 tag
+fn canonical_tag(raw: &str) -> Option<String> {
+    let tag = raw.split(['.', '@']).next()?.replace('_', "-");
+    if tag.is_empty() || tag == "C" || tag == "POSIX" {
+        return None;
+    }
+    Locale::try_from_str(&tag).ok().map(|locale| locale.to_string())
+}
+
+/// Region of a tag, e.g. "US" for "en-US"
+fn region_of(tag: &str) -> Option<String> {
+    let locale = Locale::try_from_str(tag).ok()?;
+    locale.id.region.map(|r| r.as_str().to_string())
+}
+
+/// CLDR's first day of the week by region (weekData), Monday elsewhere
+fn region_first_day(region: Option<&str>) -> u8 {
+    const SUNDAY: &[&str] = &[
+        "AG", "AS", "BD", "BR", "BS", "BT", "BW", "BZ", "CA", "CN", "CO", "DM", "DO", "ET", "GT", "GU", "HK", "HN",
+        "ID", "IL", "IN", "JM", "JP", "KE", "KH", "KR", "LA", "MH", "MM", "MO", "MT", "MX", "MZ", "NI", "NP", "PA",
+        "PE", "PH", "PK", "PR", "PT", "PY", "SA", "SG", "SV", "TH", "TT", "TW", "UM", "US", "VE", "VI", "WS", "YE",
+        "ZA", "ZW",
+    ];
+    const SATURDAY: &[&str] = &["AE", "AF", "BH", "DJ", "DZ", "EG", "IQ", "IR", "JO", "KW", "LY", "OM", "QA", "SD", "SY"];
+    match region {
+        Some(r) if SUNDAY.contains(&r) => 0,
+        Some(r) if SATURDAY.contains(&r) => 6,
+        _ => 1,
+    }
+}
+
+/// "3;3" / "3;0" / "3;2;0" to group sizes
+fn parse_grouping(raw: &str) -> Option<Vec<u8>> {
+    let mut sizes: Vec<u8> = raw
+        .split(';')
+        .filter_map(|s| s.trim().parse().ok())
+        .take_while(|size| *size > 0 && *size < 127)
+        .collect();
+    // A repeated last size is implied
+    while sizes.len() > 1 && sizes[sizes.len() - 1] == sizes[sizes.len() - 2] {
+        sizes.pop();
+    }
+    (!sizes.is_empty()).then_some(sizes)
+}
+
+/// strftime date format (`d_fmt`) to a CLDR pattern
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn strftime_to_cldr(format: &str) -> Option<String> {
+    let mut pattern = String::new();
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            pattern.push(c);
+            continue;
+        }
+        pattern.push_str(match chars.next()? {
+            'd' => "dd",
+            'e' => "d",
+            'm' => "MM",
+            'y' => "yy",
+            'Y' => "yyyy",
+            'b' | 'h' => "MMM",
+            'B' => "MMMM",
+            'F' => "yyyy-MM-dd",
+            'D' => "MM/dd/yy",
+            '%' => "%",
+            _ => return None,
+        });
+    }
+    Some(pattern)
+}
+
+#[cfg(target_os = "linux")]
+fn detect() -> LocaleFormat {
+    use std::collections::HashMap;
+
+    let raw = ["LC_ALL", "LC_NUMERIC", "LANG"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok().filter(|v| !v.is_empty()));
+    let locale = raw.as_deref().and_then(canonical_tag).unwrap_or_else(|| "en-US".to_string());
+
+    // `key=value` / `key="value"` lines for the process's own locale settings
+    let values: HashMap<String, String> = command_output("locale", &["-k", "LC_NUMERIC", "LC_MONETARY", "LC_TIME"])
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            Some((key.to_string(), value.trim_matches('"').to_string()))
+        })
+        .collect();
+    let text = |key: &str| values.get(key).cloned();
+    let flag = |key: &str| values.get(key).and_then(|v| v.parse::<i32>().ok()).filter(|v| *v >= 0).map(|v| v == 1);
+
+    // first_weekday counts from week-1stday: 19971130 is a Sunday, 19971201 a Monday
+    let first_day = match (values.get("week-1stday").map(String::as_str), values.get("first_weekday")) {
+        (Some(base), Some(n)) => n.parse::<u8>().ok().filter(|n| (1..=7).contains(n)).map(|n| {
+            let base = if base == "19971201" { 1 } else { 0 };
+            (base + n - 1) % 7
+        }),
+        _ => None,
+    };
+
+    LocaleFormat {
+        decimal_separator: text("decimal_point").filter(|s| !s.is_empty()),
+        grouping_separator: text("thousands_sep"),
+        grouping: text("grouping").as_deref().and_then(parse_grouping),
+        currency_symbol: text("currency_symbol").filter(|s| !s.is_empty()),
+        currency_before: flag("p_cs_precedes"),
+        currency_space: flag("p_sep_by_space"),
+        first_day_of_week: first_day.unwrap_or_else(|| region_first_day(region_of(&locale).as_deref())),
+        short_date: text("d_fmt").as_deref().and_then(strftime_to_cldr),
+        locale,
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn detect() -> LocaleFormat {
+    let read = |key: &str| command_output("defaults", &["read", "-g", key]);
+    let locale = read("AppleLocale")
+        .as_deref()
+        .and_then(canonical_tag)
+        .unwrap_or_else(|| "en-US".to_string());
+
+    // Only set when the user changed it: `{ gregorian = 2; }`, 1 is Sunday
+    let first_day = read("AppleFirstWeekday").and_then(|dict| {
+        let n: u8 = dict.split("gregorian =").nth(1)?.trim().trim_end_matches(['}', ';', ' ']).parse().ok()?;
+        (1..=7).contains(&n).then(|| n - 1)
+    });
+    // Custom number symbols: `{ 0 = "."; 1 = ","; }` (decimal, grouping)
+    let symbols = read("AppleICUNumberSymbols").unwrap_or_default();
+    let symbol = |index: &str| {
+        let rest = symbols.split(&format!("{} = ", index)).nth(1)?;
+        let value = rest.split(';').next()?.trim().trim_matches('"');
+        (!value.is_empty()).then(|| value.to_string())
+    };
+
+    LocaleFormat {
+        decimal_separator: symbol("0"),
+        grouping_separator: symbol("1"),
+        first_day_of_week: first_day.unwrap_or_else(|| region_first_day(region_of(&locale).as_deref())),
+        locale,
+        ..Default::default()
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn detect() -> LocaleFormat {
+    use std::collections::HashMap;
+
+    // "    sDecimal    REG_SZ    ." lines
+    let values: HashMap<String, String> = command_output("reg", &["query", r"HKCU\Control Panel\International"])
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.trim().splitn(3, "    ");
+            let key = parts.next()?.trim();
+            let kind = parts.next()?.trim();
+            kind.starts_with("REG_").then(|| (key.to_string(), parts.next().unwrap_or("").trim().to_string()))
+        })
+        .collect();
+    let text = |key: &str| values.get(key).cloned();
+    let locale = text("LocaleName")
+        .as_deref()
+        .and_then(canonical_tag)
+        .unwrap_or_else(|| "en-US".to_string());
+    // 0 "$1", 1 "1$", 2 "$ 1", 3 "1 $"
+    let currency = text("iCurrency").and_then(|v| v.parse::<u8>().ok()).filter(|v| *v <= 3);
+    // 0 is Monday ... 6 Sunday
+    let first_day = text("iFirstDayOfWeek").and_then(|v| v.parse::<u8>().ok()).filter(|v| *v <= 6).map(|v| (v + 1) % 7);
+
+    LocaleFormat {
+        decimal_separator: text("sDecimal"),
+        grouping_separator: text("sThousand"),
+        grouping: text("sGrouping").as_deref().and_then(parse_grouping),
+        currency_symbol: text("sCurrency"),
+        currency_before: currency.map(|c| c % 2 == 0),
+        currency_space: currency.map(|c| c >= 2),
+        first_day_of_week: first_day.unwrap_or_else(|| region_first_day(region_of(&locale).as_deref())),
+        // Windows patterns use the same letters for the common cases (d, M, y)
+        short_date: text("sShortDate"),
+        locale,
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn detect() -> LocaleFormat {
+    LocaleFormat {
+        locale: "en-US".to_string(),
+        first_day_of_week: region_first_day(Some("US")),
+        ..Default::default()
+    }
+}
+
+/// Locale and number/date conventions of the OS
+#[tauri::command]
+pub async fn get_locale_format() -> Result<LocaleFormat, String> {
+    tauri::async_runtime::spawn_blocking(detect).await.map_err(|e| e.to_string())
+}
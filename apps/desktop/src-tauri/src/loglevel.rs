@@ -0,0 +1,139 @@
+// Server log verbosity (`server.log_level` in settings.toml)
+//
+// The sidecar reads `LOG_LEVEL` when it starts, so changing the level restarts a
+// running server. For bug reports `capture_debug_logs` switches to "debug" for a
+// while (10 minutes by default) without touching the saved level, then restarts the
+// server back at the saved level. The capture isn't persisted: quitting ends it.
+
+use std::sync::Mutex;
+use std::time::Duration;
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use serde_json::json;
+use tauri::{AppHandle, Manager};
+use crate::events::{self, Event};
+use crate::logs::{log_line, SharedLogStore};
+use crate::server::{ServerStatus, SharedServerManager};
+use crate::settings::SharedSettings;
+
+pub const LOG_LEVELS: &[&str] = &["error", "warn", "info", "debug"];
+const DEFAULT_CAPTURE_MINUTES: u32 = 10;
+const MAX_CAPTURE_MINUTES: u32 = 120;
+
+/// When the current debug capture ends, if one is running
+static DEBUG_UNTIL: Mutex<Option<DateTime<Local>>> = Mutex::new(None);
+
+#[derive(Serialize)]
+pub struct LogLevelInfo {
+    /// The saved level
+    level: String,
+    /// The level the server runs (or will next start) with
+    effective: String,
+    /// End of the debug capture, RFC 3339
+    debug_until: Option<String>,
+}
+
+fn debug_until() -> Option<DateTime<Local>> {
+    DEBUG_UNTIL.lock().unwrap().filter(|until| *until > Local::now())
+}
+
+/// The level to start the server with: "debug" during a capture, otherwise the saved one
+pub fn effective_level(saved: &str) -> String {
+    match debug_until() {
+        Some(_) => "debug".to_string(),
+        None => saved.to_string(),
+    }
+}
+
+async fn info(app: &AppHandle) -> LogLevelInfo {
+    let level = app.state::<SharedSettings>().lock().await.get().server.log_level;
+    LogLevelInfo {
+        effective: effective_level(&level),
+        level,
+        debug_until: debug_until().map(|until| until.to_rfc3339()),
+    }
+}
+
+/// Restart the server so it picks up a new level (a stopped server uses it on start)
+async fn apply(app: &AppHandle, reason: String) -> Result<(), String> {
+    let manager = app.state::<SharedServerManager>().inner().clone();
+    let log_store = app.state::<SharedLogStore>().inner().clone();
+    log_line(app, &log_store, reason, "info").await;
+    if matches!(manager.lock().await.status(), ServerStatus::Stopped | ServerStatus::Paused) {
+        return Ok(());
+    }
+    crate::restart_server(app.clone(), manager, log_store).await
+}
+
+/// The saved server log level and the one in effect
+#[tauri::command]
+pub async fn get_server_log_level(app: AppHandle) -> Result<LogLevelInfo, String> {
+    Ok(info(&app).await)
+}
+
+/// Save the server log level, restarting the server unless a debug capture overrides it
+#[tauri::command]
+pub async fn set_server_log_level(app: AppHandle, level: String) -> Result<LogLevelInfo, String> {
+    let before = info(&app).await;
+    let updated = app
+        .state::<SharedSettings>()
+        .lock()
+        .await
+        .update(&json!({ "server": { "log_level": level } }))?;
+    let _ = events::emit(&app, Event::SettingsChanged(&updated));
+
+    let after = info(&app).await;
+    if after.effective != before.effective {
+        apply(&app, format!("Server log level set to {}", after.effective)).await?;
+    }
+    Ok(after)
+}
+
+/// Run the server with debug logging for a few minutes, then go back to the saved level
+#[tauri::command]
+pub async fn capture_debug_logs(app: AppHandle, minutes: Option<u32>) -> Result<LogLevelInfo, String> {
+    let minutes = minutes.unwrap_or(DEFAULT_CAPTURE_MINUTES);
+    if !(1..=MAX_CAPTURE_MINUTES).contains(&minutes) {
+        return Err(format!("A debug capture lasts between 1 and {} minutes", MAX_CAPTURE_MINUTES));
+    }
+    let was_capturing = debug_until().is_some();
+    let until = Local::now() + chrono::Duration::minutes(minutes as i64);
+    *DEBUG_UNTIL.lock().unwrap() = Some(until);
+
+    let saved = app.state::<SharedSettings>().lock().await.get().server.log_level;
+    if !was_capturing && saved != "debug" {
+        apply(&app, format!("Capturing debug logs until {}", until.format("%H:%M"))).await?;
+    }
+
+    let app_handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(minutes as u64 * 60)).await;
+        // A later capture (or stop) replaced this one
+        if *DEBUG_UNTIL.lock().unwrap() != Some(until) {
+            return;
+        }
+        end_capture(&app_handle).await;
+    });
+    Ok(info(&app).await)
+}
+
+/// End a debug capture early
+#[tauri::command]
+pub async fn stop_debug_capture(app: AppHandle) -> Result<LogLevelInfo, String> {
+    if debug_until().is_some() {
+        end_capture(&app).await;
+    }
+    Ok(info(&app).await)
+}
+
+async fn end_capture(app: &AppHandle) {
+    *DEBUG_UNTIL.lock().unwrap() = None;
+    let saved = app.state::<SharedSettings>().lock().await.get().server.log_level;
+    if saved == "debug" {
+        return;
+    }
+    if let Err(e) = apply(app, format!("Debug capture ended, server log level back to {}", saved)).await {
+        let log_store = app.state::<SharedLogStore>().inner().clone();
+        log_line(app, &log_store, format!("Failed to restore the server log level: {}", e), "error").await;
+    }
+}
@@ -0,0 +1,362 @@
+// Log storage and batched log event emission to the frontend
+//
+// Log lines are stored as `Arc<str>` so the in-memory ring buffer and the
+// pending emit batches share one allocation per line instead of copying it.
+//
+// The ring buffer only covers the current run, so sidecar output is also appended to
+// `logs/server.log` in the data dir. That file is rotated when it grows past
+// MAX_FILE_BYTES or the day changes, and rotated files are kept for KEEP_DAYS.
+
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, UNIX_EPOCH};
+use chrono::{DateTime, Local, NaiveDate};
+use tauri::{AppHandle, Manager};
+use serde::Serialize;
+use crate::events::{self, Event};
+
+const MAX_LOG_LINES: usize = 1000;
+/// How long lines are buffered before being flushed as one event
+const FLUSH_INTERVAL: Duration = Duration::from_millis(100);
+/// Flush early once this many lines are pending
+const MAX_BATCH_LINES: usize = 200;
+
+const LOGS_DIR: &str = "logs";
+const CURRENT_FILE: &str = "server.log";
+const ROTATED_PREFIX: &str = "server-";
+/// The current log file is rotated once it grows past this
+const MAX_FILE_BYTES: u64 = 5 * 1024 * 1024;
+/// Rotated files older than this are deleted
+const KEEP_DAYS: u64 = 14;
+/// At most this many rotated files are kept, however recent
+const MAX_ROTATED_FILES: usize = 30;
+
+static LOGS_PATH: OnceLock<PathBuf> = OnceLock::new();
+static LOG_FILE: Mutex<Option<LogFile>> = Mutex::new(None);
+/// Warn about an unwritable log file once, not for every line
+static OPEN_FAILED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Clone, Serialize)]
+pub struct LogPayload {
+    pub message: Arc<str>,
+    pub log_type: &'static str,
+}
+
+/// Log storage for backend logs (ring buffer of the last MAX_LOG_LINES lines)
+pub struct LogStore {
+    logs: VecDeque<Arc<str>>,
+}
+
+impl LogStore {
+    pub fn new() -> Self {
+        Self { logs: VecDeque::with_capacity(MAX_LOG_LINES) }
+    }
+
+    pub fn add(&mut self, line: Arc<str>) {
+        if self.logs.len() == MAX_LOG_LINES {
+            self.logs.pop_front();
+        }
+        self.logs.push_back(line);
+    }
+
+    /// Snapshot of all stored lines (cheap: only reference counts are bumped)
+    pub fn get_all(&self) -> Vec<Arc<str>> {
+        self.logs.iter().cloned().collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.logs.clear();
+    }
+}
+
+pub type SharedLogStore = Arc<tokio::sync::Mutex<LogStore>>;
+
+/// Buffers log lines and emits them to the frontend in batches
+/// High-volume server output (e.g. during imports) would otherwise trigger one IPC emit per line
+pub struct LogEmitter {
+    app: AppHandle,
+    pending: Mutex<Vec<LogPayload>>,
+}
+
+impl LogEmitter {
+    pub fn new(app: AppHandle) -> Self {
+        Self {
+            app,
+            pending: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Queue a line for emission
+    /// Error lines flush immediately (together with anything queued before them, to keep ordering)
+    pub fn push(&self, message: Arc<str>, log_type: &'static str) {
+        let batch = {
+            let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+            pending.push(LogPayload { message, log_type });
+
+            if log_type == "error" || pending.len() >= MAX_BATCH_LINES {
+                std::mem::take(&mut *pending)
+            } else {
+                return;
+            }
+        };
+
+        self.emit(batch);
+    }
+
+    /// Emit everything that is currently buffered
+    pub fn flush(&self) {
+        let batch = {
+            let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+            if pending.is_empty() {
+                return;
+            }
+            std::mem::take(&mut *pending)
+        };
+
+        self.emit(batch);
+    }
+
+    fn emit(&self, batch: Vec<LogPayload>) {
+        let _ = events::emit(&self.app, Event::ServerLogBatch(&batch));
+    }
+}
+
+pub type SharedLogEmitter = Arc<LogEmitter>;
+
+/// Create the log emitter and start its periodic flush task
+pub fn create_log_emitter(app: &AppHandle) -> SharedLogEmitter {
+    let emitter = Arc::new(LogEmitter::new(app.clone()));
+
+    let emitter_clone = emitter.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(FLUSH_INTERVAL).await;
+            emitter_clone.flush();
+            flush_file();
+        }
+    });
+
+    emitter
+}
+
+/// Emit a log message to the frontend (batched)
+pub fn emit_log(app: &AppHandle, message: &str, log_type: &'static str) {
+    emit_log_line(app, Arc::from(message), log_type);
+}
+
+/// Emit an already shared log line to the frontend (batched)
+pub fn emit_log_line(app: &AppHandle, line: Arc<str>, log_type: &'static str) {
+    if let Some(emitter) = app.try_state::<SharedLogEmitter>() {
+        emitter.push(line, log_type);
+    }
+}
+
+/// Emit a log line to the frontend and store it, sharing a single allocation
+pub async fn log_line(app: &AppHandle, log_store: &SharedLogStore, message: impl Into<Arc<str>>, log_type: &'static str) {
+    let line: Arc<str> = message.into();
+    emit_log_line(app, line.clone(), log_type);
+    log_store.lock().await.add(line);
+}
+
+/// Flush any buffered log lines, used before exit
+pub fn flush_logs(app: &AppHandle) {
+    if let Some(emitter) = app.try_state::<SharedLogEmitter>() {
+        emitter.flush();
+    }
+    flush_file();
+}
+
+struct LogFile {
+    writer: BufWriter<File>,
+    size: u64,
+    /// Local day the file was started on
+    day: NaiveDate,
+}
+
+/// Set up the log files directory and drop expired rotated files
+pub fn init(data_dir: &Path) {
+    let dir = data_dir.join(LOGS_DIR);
+    prune(&dir);
+    let _ = LOGS_PATH.set(dir);
+}
+
+/// Append a sidecar output line to the current log file, rotating it first if it's due
+pub fn write_file(line: &str) {
+    let Some(dir) = LOGS_PATH.get() else {
+        return;
+    };
+    let now = Local::now();
+    let mut file = LOG_FILE.lock().unwrap_or_else(|e| e.into_inner());
+    if file.as_ref().is_some_and(|f| f.size >= MAX_FILE_BYTES || f.day != now.date_naive()) {
+        if let Some(mut old) = file.take() {
+            let _ = old.writer.flush();
+        }
+        rotate(dir);
+    }
+    if file.is_none() {
+        match open_current(dir, now.date_naive()) {
+            Ok(opened) => {
+                OPEN_FAILED.store(false, Ordering::Relaxed);
+                *file = Some(opened);
+            }
+            Err(e) => {
+                if !OPEN_FAILED.swap(true, Ordering::Relaxed) {
+                    eprintln!("Warning: {}", e);
+                }
+                return;
+            }
+        }
+    }
+    if let Some(f) = file.as_mut() {
+        let entry = format!("{} {}\n", now.format("%Y-%m-%d %H:%M:%S%.3f"), line);
+        if f.writer.write_all(entry.as_bytes()).is_ok() {
+            f.size += entry.len() as u64;
+        }
+    }
+}
+
+/// Write out whatever the log file has buffered
+fn flush_file() {
+    if let Some(f) = LOG_FILE.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+        let _ = f.writer.flush();
+    }
+}
+
+/// Open the current log file, rotating a leftover one that's too big or from an earlier day
+fn open_current(dir: &Path, today: NaiveDate) -> Result<LogFile, String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let path = dir.join(CURRENT_FILE);
+    if let Ok(meta) = fs::metadata(&path) {
+        let day = meta.modified().ok().map(|modified| DateTime::<Local>::from(modified).date_naive());
+        if meta.len() >= MAX_FILE_BYTES || day.is_some_and(|day| day != today) {
+            rotate(dir);
+        }
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let size = file.metadata().map(|meta| meta.len()).unwrap_or(0);
+    Ok(LogFile { writer: BufWriter::new(file), size, day: today })
+}
+
+/// Move the current log file aside, named after when it was last written
+fn rotate(dir: &Path) {
+    let current = dir.join(CURRENT_FILE);
+    let Ok(modified) = fs::metadata(&current).and_then(|meta| meta.modified()) else {
+        return;
+    };
+    let stamp = DateTime::<Local>::from(modified).format("%Y%m%d-%H%M%S");
+    let mut rotated = dir.join(format!("{}{}.log", ROTATED_PREFIX, stamp));
+    let mut n = 1;
+    while rotated.exists() {
+        rotated = dir.join(format!("{}{}-{}.log", ROTATED_PREFIX, stamp, n));
+        n += 1;
+    }
+    if let Err(e) = fs::rename(&current, &rotated) {
+        eprintln!("Warning: Failed to rotate {}: {}", current.display(), e);
+    }
+    prune(dir);
+}
+
+/// Rotated log files, newest first
+fn rotated_files(dir: &Path) -> Vec<(PathBuf, fs::Metadata)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<_> = entries
+        .flatten()
+        .filter(|entry| is_log_name(&entry.file_name().to_string_lossy()))
+        .filter(|entry| entry.file_name() != CURRENT_FILE)
+        .filter_map(|entry| Some((entry.path(), entry.metadata().ok()?)))
+        .collect();
+    files.sort_by_key(|(_, meta)| std::cmp::Reverse(meta.modified().unwrap_or(UNIX_EPOCH)));
+    files
+}
+
+/// Delete rotated files past KEEP_DAYS or MAX_ROTATED_FILES
+fn prune(dir: &Path) {
+    let max_age = Duration::from_secs(KEEP_DAYS * 24 * 60 * 60);
+    for (i, (path, meta)) in rotated_files(dir).into_iter().enumerate() {
+        let expired = meta
+            .modified()
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|age| age > max_age);
+        if i >= MAX_ROTATED_FILES || expired {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// Names this module writes: the current file or a rotated one, never a path
+fn is_log_name(name: &str) -> bool {
+    name == CURRENT_FILE
+        || (name.starts_with(ROTATED_PREFIX)
+            && name.ends_with(".log")
+            && !name.contains(['/', '\\'])
+            && !name.contains(".."))
+}
+
+#[derive(Serialize)]
+pub struct LogFileInfo {
+    pub name: String,
+    pub size: u64,
+    /// Unix time in milliseconds
+    pub modified: u64,
+    /// Still being written to
+    pub current: bool,
+}
+
+/// List the current and rotated log files, newest first
+#[tauri::command]
+pub async fn list_log_files() -> Result<Vec<LogFileInfo>, String> {
+    let Some(dir) = LOGS_PATH.get() else {
+        return Ok(Vec::new());
+    };
+    flush_file();
+    let info = |path: &Path, meta: &fs::Metadata, current: bool| LogFileInfo {
+        name: path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default(),
+        size: meta.len(),
+        modified: meta
+            .modified()
+            .ok()
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .map(|since| since.as_millis() as u64)
+            .unwrap_or(0),
+        current,
+    };
+    let current = dir.join(CURRENT_FILE);
+    let mut files: Vec<LogFileInfo> =
+        fs::metadata(&current).map(|meta| info(&current, &meta, true)).into_iter().collect();
+    files.extend(rotated_files(dir).iter().map(|(path, meta)| info(path, meta, false)));
+    Ok(files)
+}
+
+/// Open a log file from `list_log_files` in the default app
+#[tauri::command]
+pub async fn open_log_file(name: String) -> Result<(), String> {
+    let dir = LOGS_PATH.get().ok_or("Log files aren't set up")?;
+    if !is_log_name(&name) {
+        return Err(format!("Not a log file: {}", name));
+    }
+    let path = dir.join(&name);
+    if !path.is_file() {
+        return Err(format!("{} no longer exists", name));
+    }
+    flush_file();
+    open::that(&path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))
+}
+
+/// Open the log files folder in the file manager
+#[tauri::command]
+pub async fn open_logs_folder() -> Result<(), String> {
+    let dir = LOGS_PATH.get().ok_or("Log files aren't set up")?;
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    open::that(dir).map_err(|e| format!("Failed to open {}: {}", dir.display(), e))
+}
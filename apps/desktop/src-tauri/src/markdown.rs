@@ -0,0 +1,172 @@
+// Release notes markdown to HTML for the update dialog
+//
+// Release bodies are written by hand on GitHub, so they're parsed with pulldown-cmark
+// and only a small set of tags is written back out: paragraphs, headings, lists,
+// emphasis, code, quotes and links. Raw HTML and images in the markdown are dropped
+// and all text is escaped. Links get no href; the dialog opens their `data-url` with
+// the `open_url` command, and only http(s) links are kept at all.
+//
+// The dialogs put the result inside a JavaScript template literal, so backticks, `$`
+// and backslashes are escaped as entities too.
+
+use pulldown_cmark::{Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+
+/// Escape text for HTML inside a template literal
+fn escape(text: &str, out: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            '`' => out.push_str("&#96;"),
+            '$' => out.push_str("&#36;"),
+            '\\' => out.push_str("&#92;"),
+            c => out.push(c),
+        }
+    }
+}
+
+/// Headings are scaled down to fit a small dialog
+fn heading_tag(level: HeadingLevel) -> &'static str {
+    match level {
+        HeadingLevel::H1 | HeadingLevel::H2 => "h3",
+        _ => "h4",
+    }
+}
+
+/// Markdown rendered to HTML that's safe to put in a dialog
+pub fn to_html(markdown: &str) -> String {
+    let options = Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    let mut out = String::new();
+    // Whether each open link was kept, so its end tag matches
+    let mut links: Vec<bool> = Vec::new();
+    // Inside an image: its alt text is shown as plain text
+    let mut image_depth = 0;
+
+    for event in Parser::new_ext(markdown, options) {
+        match event {
+            Event::Start(Tag::Image { .. }) => image_depth += 1,
+            Event::End(TagEnd::Image) => image_depth -= 1,
+            Event::Start(_) | Event::End(_) if image_depth > 0 => {}
+            Event::Start(tag) => match tag {
+                Tag::Paragraph => out.push_str("<p>"),
+                Tag::Heading { level, .. } => {
+                    out.push('<');
+                    out.push_str(heading_tag(level));
+                    out.push('>');
+                }
+                Tag::BlockQuote(_) => out.push_str("<blockquote>"),
+                Tag::CodeBlock(_) => out.push_str("<pre><code>"),
+                Tag::List(Some(1)) => out.push_str("<ol>"),
+                Tag::List(Some(start)) => out.push_str(&format!("<ol start=\"{}\">", start)),
+                Tag::List(None) => out.push_str("<ul>"),
+                Tag::Item => out.push_str("<li>"),
+                Tag::Emphasis => out.push_str("<em>"),
+                Tag::Strong => out.push_str("<strong>"),
+                Tag::Strikethrough => out.push_str("<del>"),
+                Tag::Link { dest_url, title, .. } => {
+                    let kept = dest_url.starts_with("https://") || dest_url.starts_with("http://");
+                    if kept {
+                        out.push_str("<a href=\"#\" data-url=\"");
+                        escape(&dest_url, &mut out);
+                        out.push_str("\" title=\"");
+                        escape(if title.is_empty() { &dest_url } else { &title }, &mut out);
+                        out.push_str("\">");
+                    }
+                    links.push(kept);
+                }
+                _ => {}
+            },
+            Event::End(tag) => match tag {
+                TagEnd::Paragraph => out.push_str("</p>"),
+                TagEnd::Heading(level) => {
+                    out.push_str("</");
+                    out.push_str(heading_tag(level));
+                    out.push('>');
+                }
+                TagEnd::BlockQuote(_) => out.push_str("</blockquote>"),
+                TagEnd::CodeBlock => out.push_str("</code></pre>"),
+                TagEnd::List(true) => out.push_str("</ol>"),
+                TagEnd::List(false) => out.push_str("</ul>"),
+                TagEnd::Item => out.push_str("</li>"),
+                TagEnd::Emphasis => out.push_str("</em>"),
+                TagEnd::Strong => out.push_str("</strong>"),
+                TagEnd::Strikethrough => out.push_str("</del>"),
+                TagEnd::Link => out.push_str(if links.pop() == Some(true) { "</a>" } else { "" }),
+                _ => {}
+            },
+            Event::Text(text) => escape(&text, &mut out),
+            Event::Code(code) if image_depth == 0 => {
+                out.push_str("<code>");
+                escape(&code, &mut out);
+                out.push_str("</code>");
+            }
+            Event::Code(code) => escape(&code, &mut out),
+            Event::SoftBreak => out.push(' '),
+            Event::HardBreak if image_depth == 0 => out.push_str("<br>"),
+            Event::Rule => out.push_str("<hr>"),
+            Event::TaskListMarker(done) => out.push_str(if done { "&#9745; " } else { "&#9744; " }),
+            // Raw HTML, footnotes and math aren't rendered
+            _ => {}
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drops_script_blocks() {
+        let html = to_html("<script>alert(1)</script>\n\nAfter");
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("alert"));
+        assert_eq!(html, "<p>After</p>");
+    }
+
+    #[test]
+    fn drops_inline_html_but_keeps_text() {
+        let html = to_html("Hello <b onclick=\"steal()\">bold</b> <iframe src=\"x\"></iframe>world");
+        assert_eq!(html, "<p>Hello bold world</p>");
+    }
+
+    #[test]
+    fn keeps_only_http_links() {
+        assert_eq!(to_html("[click](javascript:alert(1))"), "<p>click</p>");
+        assert_eq!(to_html("[page](data:text/html;base64,PHNjcmlwdD4=)"), "<p>page</p>");
+        assert_eq!(
+            to_html("[site](https://example.com \"Home\")"),
+            "<p><a href=\"#\" data-url=\"https://example.com\" title=\"Home\">site</a></p>"
+        );
+    }
+
+    #[test]
+    fn images_become_their_alt_text() {
+        let html = to_html("![a *chart*](https://example.com/chart.png)");
+        assert!(!html.contains("<img"));
+        assert_eq!(html, "<p>a chart</p>");
+    }
+
+    #[test]
+    fn escapes_template_literal_characters() {
+        assert_eq!(to_html("`${document.cookie}`"), "<p><code>&#36;{document.cookie}</code></p>");
+        let html = to_html("ends \\` here ${x} and \\\\ too");
+        assert!(!html.contains('`'));
+        assert!(!html.contains('$'));
+        assert!(!html.contains('\\'));
+        assert_eq!(html, "<p>ends &#96; here &#36;{x} and &#92; too</p>");
+    }
+
+    #[test]
+    fn escapes_html_in_text_and_attributes() {
+        assert_eq!(to_html("a < b & \"c\""), "<p>a &lt; b &amp; &quot;c&quot;</p>");
+        assert_eq!(
+            to_html("[x](https://example.com/?q=\"><script>)"),
+            "<p><a href=\"#\" data-url=\"https://example.com/?q=&quot;&gt;&lt;script&gt;\" \
+             title=\"https://example.com/?q=&quot;&gt;&lt;script&gt;\">x</a></p>"
+        );
+    }
+}
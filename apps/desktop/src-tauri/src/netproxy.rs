@@ -0,0 +1,228 @@
+// Proxy for update checks and downloads
+//
+// `network.proxy` is "system" by default: the proxy from HTTPS_PROXY / HTTP_PROXY /
+// ALL_PROXY, or else the one set in the OS (System Settings on macOS, Internet Options
+// on Windows, GNOME's proxy settings on Linux). "manual" uses `network.proxy_host` and
+// `proxy_port`, with `proxy_username` and a password kept in the keychain for proxies
+// that want a login. "none" connects directly even when the environment names a proxy.
+//
+// Only update requests go through it; the server has its own settings. When
+// an update check can't connect, the error dialog says which proxy was used.
+
+use serde::Serialize;
+use tauri::{Manager, Runtime, Url};
+use crate::a11y::command_output;
+use crate::keychain;
+use crate::settings::{NetworkSettings, SharedSettings};
+
+pub const MODES: &[&str] = &["system", "manual", "none"];
+pub const PROXY_PASSWORD_KEY: &str = "network-proxy-password";
+
+const ENV_VARS: &[&str] = &["HTTPS_PROXY", "https_proxy", "ALL_PROXY", "all_proxy", "HTTP_PROXY", "http_proxy"];
+
+/// What the updater connects through, for the settings page
+#[derive(Debug, Clone, Serialize)]
+pub struct ProxyStatus {
+    pub mode: String,
+    /// The system proxy, whether or not it's used, without credentials
+    pub detected: Option<String>,
+    /// The proxy update checks go through, without credentials
+    pub effective: Option<String>,
+    pub has_password: bool,
+}
+
+/// A proxy host: a name or an IP address, no scheme or path
+pub fn validate_host(host: &str) -> Result<(), String> {
+    if host.is_empty() {
+        return Err("network.proxy_host is required for a manual proxy".to_string());
+    }
+    if host.len() > 253 || !host.chars().all(|c| c.is_ascii_alphanumeric() || ".-:[]_".contains(c)) {
+        return Err("network.proxy_host must be a host name or IP address, without http:// or a port".to_string());
+    }
+    Ok(())
+}
+
+/// Turn "host:port" or a full URL into a proxy URL
+fn parse_proxy(value: &str) -> Option<Url> {
+    let value = value.trim();
+    if value.is_empty() {
+        return None;
+    }
+    let url = if value.contains("://") { Url::parse(value) } else { Url::parse(&format!("http://{}", value)) };
+    url.ok().filter(|url| url.host_str().is_some())
+}
+
+/// The proxy without its credentials, safe to show and log
+pub fn redacted(url: &Url) -> String {
+    let mut url = url.clone();
+    let _ = url.set_username("");
+    let _ = url.set_password(None);
+    url.as_str().trim_end_matches('/').to_string()
+}
+
+#[cfg(target_os = "macos")]
+fn os_proxy() -> Option<String> {
+    let output = command_output("scutil", &["--proxy"])?;
+    let value = |key: &str| {
+        output
+            .lines()
+            .filter_map(|line| line.trim().split_once(" : "))
+            .find(|(name, _)| *name == key)
+            .map(|(_, value)| value.trim().to_string())
+    };
+    ["HTTPS", "HTTP"].iter().find_map(|kind| {
+        if value(&format!("{}Enable", kind)).as_deref() != Some("1") {
+            return None;
+        }
+        let host = value(&format!("{}Proxy", kind))?;
+        Some(match value(&format!("{}Port", kind)) {
+            Some(port) => format!("{}:{}", host, port),
+            None => host,
+        })
+    })
+}
+
+#[cfg(target_os = "windows")]
+fn os_proxy() -> Option<String> {
+    const KEY: &str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\Internet Settings";
+    // Lines look like "    ProxyServer    REG_SZ    host:port"
+    let value = |name: &str| {
+        let output = command_output("reg", &["query", KEY, "/v", name])?;
+        output
+            .lines()
+            .find(|line| line.trim_start().starts_with(name))
+            .and_then(|line| line.split_whitespace().nth(2))
+            .map(str::to_string)
+    };
+    if value("ProxyEnable").as_deref() != Some("0x1") {
+        return None;
+    }
+    let server = value("ProxyServer")?;
+    // Per-protocol lists read "http=host:port;https=host:port"
+    if server.contains('=') {
+        let entry = |scheme: &str| {
+            server
+                .split(';')
+                .find_map(|part| part.strip_prefix(&format!("{}=", scheme)).map(str::to_string))
+        };
+        return entry("https").or_else(|| entry("http"));
+    }
+    Some(server)
+}
+
+#[cfg(target_os = "linux")]
+fn os_proxy() -> Option<String> {
+    let get = |schema: &str, key: &str| {
+        command_output("gsettings", &["get", schema, key]).map(|value| value.trim_matches('\'').to_string())
+    };
+    if get("org.gnome.system.proxy", "mode").as_deref() != Some("manual") {
+        return None;
+    }
+    ["org.gnome.system.proxy.https", "org.gnome.system.proxy.http"].iter().find_map(|schema| {
+        let host = get(schema, "host").filter(|host| !host.is_empty())?;
+        let port = get(schema, "port").filter(|port| port != "0")?;
+        Some(format!("{}:{}", host, port))
+    })
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn os_proxy() -> Option<String> {
+    None
+}
+
+/// The proxy from the environment or the OS settings
+pub fn system_proxy() -> Option<Url> {
+    ENV_VARS
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find_map(|value| parse_proxy(&value))
+        .or_else(|| os_proxy().and_then(|value| parse_proxy(&value)))
+}
+
+/// The proxy for the current settings, with credentials; None to connect directly
+/// Blocking: it may run helper commands and read the keychain
+pub fn resolve(network: &NetworkSettings) -> Result<Option<Url>, String> {
+    match network.proxy.as_str() {
+        "manual" => {
+            let mut url = parse_proxy(&format!("{}:{}", network.proxy_host, network.proxy_port))
+                .ok_or_else(|| format!("Invalid proxy {}:{}", network.proxy_host, network.proxy_port))?;
+            if !network.proxy_username.is_empty() {
+                let password = keychain::get_secret(PROXY_PASSWORD_KEY)?.unwrap_or_default();
+                let _ = url.set_username(&network.proxy_username);
+                let _ = url.set_password(Some(&password).filter(|p| !p.is_empty()).map(String::as_str));
+            }
+            Ok(Some(url))
+        }
+        "none" => Ok(None),
+        _ => Ok(system_proxy()),
+    }
+}
+
+async fn network_settings<R: Runtime>(app: &tauri::AppHandle<R>) -> NetworkSettings {
+    match app.try_state::<SharedSettings>() {
+        Some(settings) => settings.lock().await.get().network,
+        None => NetworkSettings::default(),
+    }
+}
+
+/// The proxy for update requests, resolved off the async runtime
+pub async fn updater_proxy<R: Runtime>(app: &tauri::AppHandle<R>) -> (NetworkSettings, Result<Option<Url>, String>) {
+    let network = network_settings(app).await;
+    let settings = network.clone();
+    let proxy = tauri::async_runtime::spawn_blocking(move || resolve(&settings))
+        .await
+        .unwrap_or_else(|e| Err(e.to_string()));
+    (network, proxy)
+}
+
+/// An HTTP client builder for update requests the updater plugin doesn't make itself
+pub async fn client_builder<R: Runtime>(app: &tauri::AppHandle<R>) -> Result<reqwest::ClientBuilder, String> {
+    let (network, proxy) = updater_proxy(app).await;
+    let builder = reqwest::Client::builder();
+    Ok(match proxy? {
+        Some(proxy) => builder.proxy(reqwest::Proxy::all(proxy.as_str()).map_err(|e| e.to_string())?),
+        None if network.proxy == "none" => builder.no_proxy(),
+        None => builder,
+    })
+}
+
+/// A sentence for the update error dialog about the proxy that was (or wasn't) used
+pub async fn error_hint<R: Runtime>(app: &tauri::AppHandle<R>) -> String {
+    let (network, proxy) = updater_proxy(app).await;
+    match (network.proxy.as_str(), proxy) {
+        (_, Err(e)) => format!("The proxy settings couldn't be applied: {}", e),
+        ("manual", Ok(Some(url))) => format!(
+            "Connected through the manual proxy {}. Check its address and login under [network] in settings.toml.",
+            redacted(&url)
+        ),
+        (_, Ok(Some(url))) => format!(
+            "Connected through the system proxy {}. If it needs a login or is wrong, set a manual proxy under [network] in settings.toml.",
+            redacted(&url)
+        ),
+        ("none", Ok(None)) => "Connected directly, proxies are turned off. On a network that requires a proxy, set one under [network] in settings.toml.".to_string(),
+        (_, Ok(None)) => "No system proxy was found, so the connection was direct. On a network that requires a proxy, set one under [network] in settings.toml.".to_string(),
+    }
+}
+
+/// The system proxy and the one update checks use
+#[tauri::command]
+pub async fn get_proxy_status(app: tauri::AppHandle) -> Result<ProxyStatus, String> {
+    let (network, effective) = updater_proxy(&app).await;
+    let detected = tauri::async_runtime::spawn_blocking(system_proxy).await.map_err(|e| e.to_string())?;
+    let has_password = keychain::get_secret(PROXY_PASSWORD_KEY)?.is_some();
+    Ok(ProxyStatus {
+        mode: network.proxy,
+        detected: detected.as_ref().map(redacted),
+        effective: effective?.as_ref().map(redacted),
+        has_password,
+    })
+}
+
+/// Store the manual proxy's password in the keychain, or remove it when empty
+#[tauri::command]
+pub async fn set_proxy_password(password: Option<String>) -> Result<(), String> {
+    match password.filter(|p| !p.is_empty()) {
+        Some(password) => keychain::set_secret(PROXY_PASSWORD_KEY, &password),
+        None => keychain::delete_secret(PROXY_PASSWORD_KEY),
+    }
+}
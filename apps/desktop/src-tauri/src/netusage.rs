@@ -0,0 +1,217 @@
+// Data the shell itself moves over the network, per subsystem and month
+//
+// Only traffic leaving the computer counts; requests to the local server don't. Sizes
+// are payload bytes (response bodies received, request bodies sent), so headers and
+// TLS add a little on top. Traffic of the server itself (bank syncs, model downloads)
+// isn't seen here.
+//
+// With `network.monthly_limit_mb` set, automatic downloads (background updates, the
+// native server, exchange rate refreshes) stop once the month's total reaches it;
+// anything the user starts still runs.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use crate::settings::SharedSettings;
+
+const USAGE_FILE: &str = "network-usage.json";
+/// Months kept in the file
+const MAX_MONTHS: usize = 12;
+
+static PATH: OnceLock<PathBuf> = OnceLock::new();
+static USAGE_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Clone, Copy)]
+pub enum Subsystem {
+    Updates,
+    NativeServer,
+    ExchangeRates,
+    Alerts,
+    ProxyChecks,
+    Postgres,
+}
+
+const SUBSYSTEMS: [Subsystem; 6] = [
+    Subsystem::Updates,
+    Subsystem::NativeServer,
+    Subsystem::ExchangeRates,
+    Subsystem::Alerts,
+    Subsystem::ProxyChecks,
+    Subsystem::Postgres,
+];
+
+impl Subsystem {
+    fn key(self) -> &'static str {
+        match self {
+            Subsystem::Updates => "updates",
+            Subsystem::NativeServer => "native_server",
+            Subsystem::ExchangeRates => "exchange_rates",
+            Subsystem::Alerts => "alerts",
+            Subsystem::ProxyChecks => "proxy_checks",
+            Subsystem::Postgres => "postgres",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Subsystem::Updates => "App updates",
+            Subsystem::NativeServer => "Native server downloads",
+            Subsystem::ExchangeRates => "Exchange rates",
+            Subsystem::Alerts => "Alert webhooks",
+            Subsystem::ProxyChecks => "Reverse proxy checks",
+            Subsystem::Postgres => "PostgreSQL downloads",
+        }
+    }
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+struct Counters {
+    received: u64,
+    sent: u64,
+    requests: u64,
+}
+
+/// "YYYY-MM" to subsystem key to counters
+#[derive(Default, Serialize, Deserialize)]
+struct UsageFile {
+    months: BTreeMap<String, BTreeMap<String, Counters>>,
+}
+
+#[derive(Serialize)]
+pub struct SubsystemUsage {
+    key: &'static str,
+    label: &'static str,
+    received: u64,
+    sent: u64,
+    requests: u64,
+}
+
+#[derive(Serialize)]
+pub struct MonthUsage {
+    /// "YYYY-MM"
+    month: String,
+    received: u64,
+    sent: u64,
+    subsystems: Vec<SubsystemUsage>,
+}
+
+#[derive(Serialize)]
+pub struct NetworkUsage {
+    /// Monthly limit in bytes, None without one
+    limit: Option<u64>,
+    /// Automatic downloads are held back for the rest of the month
+    limit_reached: bool,
+    /// Newest first
+    months: Vec<MonthUsage>,
+}
+
+fn current_month() -> String {
+    Local::now().format("%Y-%m").to_string()
+}
+
+fn read_usage(path: &Path) -> UsageFile {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Where usage is kept; called once the data dir is known
+pub fn init(data_dir: &Path) {
+    let _ = PATH.set(data_dir.join(USAGE_FILE));
+}
+
+/// Count one request's payload bytes for this month
+pub fn record(subsystem: Subsystem, sent: u64, received: u64) {
+    let Some(path) = PATH.get() else {
+        return;
+    };
+    let _guard = USAGE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut usage = read_usage(path);
+    let counters = usage
+        .months
+        .entry(current_month())
+        .or_default()
+        .entry(subsystem.key().to_string())
+        .or_default();
+    counters.sent += sent;
+    counters.received += received;
+    counters.requests += 1;
+    while usage.months.len() > MAX_MONTHS {
+        usage.months.pop_first();
+    }
+    let result = serde_json::to_string_pretty(&usage)
+        .map_err(|e| e.to_string())
+        .and_then(|json| fs::write(path, json).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        eprintln!("Warning: Failed to save network usage: {}", e);
+    }
+}
+
+/// Bytes sent and received so far this month
+fn month_total(usage: &UsageFile, month: &str) -> u64 {
+    usage
+        .months
+        .get(month)
+        .map(|subsystems| subsystems.values().map(|c| c.sent + c.received).sum())
+        .unwrap_or(0)
+}
+
+fn limit_bytes(limit_mb: u32) -> Option<u64> {
+    (limit_mb > 0).then_some(limit_mb as u64 * 1024 * 1024)
+}
+
+/// Why automatic downloads should be skipped, None while under the monthly limit
+pub async fn limit_reason(app: &AppHandle) -> Option<String> {
+    let limit_mb = app.state::<SharedSettings>().lock().await.get().network.monthly_limit_mb;
+    let limit = limit_bytes(limit_mb)?;
+    let path = PATH.get()?;
+    (month_total(&read_usage(path), &current_month()) >= limit)
+        .then(|| format!("this month's data limit of {} MB is used up", limit_mb))
+}
+
+/// Bytes moved per subsystem for the last `months` months (all kept ones by default)
+#[tauri::command]
+pub async fn get_network_usage(app: AppHandle, months: Option<usize>) -> Result<NetworkUsage, String> {
+    let limit = limit_bytes(app.state::<SharedSettings>().lock().await.get().network.monthly_limit_mb);
+    let usage = PATH.get().map(|path| read_usage(path)).unwrap_or_default();
+    let this_month = current_month();
+    let limit_reached = limit.is_some_and(|limit| month_total(&usage, &this_month) >= limit);
+
+    let mut result: Vec<MonthUsage> = usage
+        .months
+        .iter()
+        .rev()
+        .take(months.unwrap_or(MAX_MONTHS))
+        .map(|(month, counters)| {
+            let subsystems: Vec<SubsystemUsage> = SUBSYSTEMS
+                .iter()
+                .filter_map(|s| {
+                    let c = counters.get(s.key())?;
+                    Some(SubsystemUsage {
+                        key: s.key(),
+                        label: s.label(),
+                        received: c.received,
+                        sent: c.sent,
+                        requests: c.requests,
+                    })
+                })
+                .collect();
+            MonthUsage {
+                month: month.clone(),
+                received: subsystems.iter().map(|s| s.received).sum(),
+                sent: subsystems.iter().map(|s| s.sent).sum(),
+                subsystems,
+            }
+        })
+        .collect();
+    // The current month is listed even before anything was downloaded
+    if result.first().is_none_or(|m| m.month != this_month) {
+        result.insert(0, MonthUsage { month: this_month, received: 0, sent: 0, subsystems: Vec::new() });
+    }
+    Ok(NetworkUsage { limit, limit_reached, months: result })
+}
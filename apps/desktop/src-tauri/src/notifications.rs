@@ -0,0 +1,427 @@
+// Delivery policy for native notifications (`[notifications]` in settings.toml)
+//
+// Every notification has a type (bills, budgets, imports, updates, maintenance) and
+// goes through `notify`, which applies that type's mode: "instant" shows it right
+// away, "digest" collects it for the weekly digest and "off" drops it. During quiet
+// hours instant notifications are held and shown once they end, as one summary if
+// there are several. The web app sends its own (bill reminders, budget alerts)
+// through `send_notification`, so the same policy applies.
+//
+// Held and digest notifications are kept in notifications.json in the data dir, so
+// they survive a restart; the digest is caught up at the next start if the app was
+// closed when it was due.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use chrono::{DateTime, Datelike, Local, NaiveTime, TimeDelta, Weekday};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+use crate::server::SharedServerManager;
+use crate::settings::{NotificationSettings, SharedSettings};
+use crate::windows::open_injected_window;
+
+const QUEUE_FILE: &str = "notifications.json";
+/// How often held notifications and the digest are checked
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+/// Titles listed in a summary before "and N more"
+const SUMMARY_TITLES: usize = 5;
+
+/// Values of the per-type settings
+pub const DELIVERY_MODES: &[&str] = &["instant", "digest", "off"];
+
+/// Serializes read-modify-write of notifications.json
+static QUEUE_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    Bills,
+    Budgets,
+    Imports,
+    Updates,
+    /// Scheduled restarts and backups
+    Maintenance,
+}
+
+impl Kind {
+    const ALL: [Kind; 5] = [Kind::Bills, Kind::Budgets, Kind::Imports, Kind::Updates, Kind::Maintenance];
+
+    fn mode(self, settings: &NotificationSettings) -> &str {
+        match self {
+            Kind::Bills => &settings.bills,
+            Kind::Budgets => &settings.budgets,
+            Kind::Imports => &settings.imports,
+            Kind::Updates => &settings.updates,
+            Kind::Maintenance => &settings.maintenance,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Kind::Bills => "Bills",
+            Kind::Budgets => "Budgets",
+            Kind::Imports => "Imports",
+            Kind::Updates => "Updates",
+            Kind::Maintenance => "Maintenance",
+        }
+    }
+}
+
+/// What happened to a notification
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Delivery {
+    Shown,
+    /// Waiting for quiet hours to end
+    Held,
+    /// Waiting for the weekly digest
+    Digest,
+    /// Its type is turned off
+    Dropped,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Note {
+    kind: Kind,
+    title: String,
+    body: String,
+    at: DateTime<Local>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct Queue {
+    held: Vec<Note>,
+    digest: Vec<Note>,
+    /// When the digest was last shown (or started collecting)
+    last_digest: Option<DateTime<Local>>,
+}
+
+#[derive(Serialize)]
+pub struct NotificationStatus {
+    quiet_now: bool,
+    held: usize,
+    digest: usize,
+    /// RFC 3339 time the digest is next due
+    next_digest: Option<String>,
+}
+
+fn parse_time(time: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(time, "%H:%M").ok()
+}
+
+/// Whether `now` falls in the quiet hours, which may span midnight
+fn in_quiet_hours(settings: &NotificationSettings, now: NaiveTime) -> bool {
+    if !settings.quiet_hours {
+        return false;
+    }
+    let (Some(start), Some(end)) = (parse_time(&settings.quiet_start), parse_time(&settings.quiet_end)) else {
+        return false;
+    };
+    if start <= end {
+        start <= now && now < end
+    } else {
+        now >= start || now < end
+    }
+}
+
+/// The latest digest time at or before `now`
+fn last_digest_slot(settings: &NotificationSettings, now: DateTime<Local>) -> Option<DateTime<Local>> {
+    let day: Weekday = settings.digest_day.parse().ok()?;
+    let time = parse_time(&settings.digest_time)?;
+    let days_back = (now.weekday().num_days_from_monday() + 7 - day.num_days_from_monday()) % 7;
+    let date = now.date_naive() - TimeDelta::days(days_back as i64);
+    let slot = date.and_time(time).and_local_timezone(Local).earliest()?;
+    Some(if slot > now { slot - TimeDelta::days(7) } else { slot })
+}
+
+fn queue_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(QUEUE_FILE)
+}
+
+/// Load, change and save notifications.json
+fn with_queue<T>(data_dir: &Path, change: impl FnOnce(&mut Queue) -> T) -> T {
+    let _guard = QUEUE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let path = queue_path(data_dir);
+    let mut queue: Queue = fs::read_to_string(&path)
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default();
+    let result = change(&mut queue);
+    match serde_json::to_string_pretty(&queue) {
+        Ok(text) => {
+            if let Err(e) = fs::write(&path, text) {
+                eprintln!("Warning: Failed to save notifications: {}", e);
+            }
+        }
+        Err(e) => eprintln!("Warning: Failed to save notifications: {}", e),
+    }
+    result
+}
+
+async fn data_dir(app: &AppHandle) -> PathBuf {
+    app.state::<SharedServerManager>().lock().await.data_dir().clone()
+}
+
+fn show(app: &AppHandle, title: &str, body: &str) {
+    let _ = app.notification().builder().title(title).body(body).show();
+}
+
+/// Titles of several notifications, one per line
+fn summary_body(notes: &[Note]) -> String {
+    let mut lines: Vec<String> = notes.iter().take(SUMMARY_TITLES).map(|n| format!("- {}", n.title)).collect();
+    if notes.len() > SUMMARY_TITLES {
+        lines.push(format!("and {} more", notes.len() - SUMMARY_TITLES));
+    }
+    lines.join("\n")
+}
+
+/// Show what was held during quiet hours
+fn show_held(app: &AppHandle, held: &[Note]) {
+    match held {
+        [] => {}
+        [note] => show(app, &note.title, &note.body),
+        notes => show(app, &format!("{} notifications during quiet hours", notes.len()), &summary_body(notes)),
+    }
+}
+
+/// Show the weekly digest: counts per type, then the titles
+fn show_digest(app: &AppHandle, notes: &[Note]) {
+    let counts: Vec<String> = Kind::ALL
+        .iter()
+        .filter_map(|kind| {
+            let count = notes.iter().filter(|n| n.kind == *kind).count();
+            (count > 0).then(|| format!("{} {}", count, kind.label().to_lowercase()))
+        })
+        .collect();
+    let body = format!("{}\n{}", counts.join(", "), summary_body(notes));
+    show(app, "Moneywright weekly digest", &body);
+}
+
+/// Show, hold or collect a notification according to `[notifications]`
+pub async fn notify(app: &AppHandle, kind: Kind, title: impl Into<String>, body: impl Into<String>) -> Delivery {
+    let settings = app.state::<SharedSettings>().lock().await.get().notifications;
+    let now = Local::now();
+    let note = Note { kind, title: title.into(), body: body.into(), at: now };
+
+    let delivery = match kind.mode(&settings) {
+        "off" => return Delivery::Dropped,
+        "digest" => Delivery::Digest,
+        _ if in_quiet_hours(&settings, now.time()) => Delivery::Held,
+        _ => {
+            show(app, &note.title, &note.body);
+            return Delivery::Shown;
+        }
+    };
+    let data_dir = data_dir(app).await;
+    with_queue(&data_dir, |queue| {
+        if delivery == Delivery::Held {
+            queue.held.push(note);
+        } else {
+            queue.last_digest.get_or_insert(now);
+            queue.digest.push(note);
+        }
+    });
+    delivery
+}
+
+/// Show held notifications once quiet hours end, and the digest when it's due
+pub fn start_notification_delivery(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let settings = app.state::<SharedSettings>().lock().await.get().notifications;
+            let now = Local::now();
+            if !in_quiet_hours(&settings, now.time()) {
+                let data_dir = data_dir(&app).await;
+                let slot = last_digest_slot(&settings, now);
+                let (held, digest) = with_queue(&data_dir, |queue| {
+                    let held = std::mem::take(&mut queue.held);
+                    let due = match (slot, queue.last_digest) {
+                        (Some(slot), Some(last)) => last < slot,
+                        (Some(_), None) => !queue.digest.is_empty(),
+                        (None, _) => false,
+                    };
+                    let digest = if due {
+                        queue.last_digest = Some(now);
+                        std::mem::take(&mut queue.digest)
+                    } else {
+                        Vec::new()
+                    };
+                    (held, digest)
+                });
+                show_held(&app, &held);
+                if !digest.is_empty() {
+                    show_digest(&app, &digest);
+                }
+            }
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
+
+/// Send a notification of the given type from the web app, subject to `[notifications]`
+#[tauri::command]
+pub async fn send_notification(app: AppHandle, kind: Kind, title: String, body: String) -> Result<Delivery, String> {
+    if title.trim().is_empty() {
+        return Err("A notification needs a title".to_string());
+    }
+    Ok(notify(&app, kind, title, body).await)
+}
+
+/// Quiet hours in effect and notifications waiting to be shown
+#[tauri::command]
+pub async fn get_notification_status(app: AppHandle) -> Result<NotificationStatus, String> {
+    let settings = app.state::<SharedSettings>().lock().await.get().notifications;
+    let now = Local::now();
+    let data_dir = data_dir(&app).await;
+    let (held, digest) = with_queue(&data_dir, |queue| (queue.held.len(), queue.digest.len()));
+    let next_digest = last_digest_slot(&settings, now).map(|slot| (slot + TimeDelta::days(7)).to_rfc3339());
+    Ok(NotificationStatus {
+        quiet_now: in_quiet_hours(&settings, now.time()),
+        held,
+        digest,
+        next_digest,
+    })
+}
+
+/// Open the Preferences window
+pub fn open_preferences_window(app: &AppHandle) {
+    // Static UI; values are assigned to inputs, never inserted as HTML
+    let script = r#"
+        const tauriApi = window.__TAURI__;
+
+        document.documentElement.innerHTML = `
+<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <title>Preferences</title>
+    <style>
+        __BASE_STYLE__
+        #content { flex: 1; overflow-y: auto; padding: 20px 24px; }
+        h2 { font-size: 14px; margin: 0 0 4px; }
+        .grid { display: grid; grid-template-columns: 140px 1fr; gap: 10px 12px; align-items: center; margin-top: 14px; }
+        .grid select, .grid input[type=time] { width: 100%; }
+        label.option { display: flex; align-items: center; gap: 8px; cursor: pointer; grid-column: 1 / -1; }
+        .range { display: flex; align-items: center; gap: 8px; }
+        .range input { flex: 1; }
+        #pending { margin-top: 16px; }
+    </style>
+</head>
+<body>
+    <div id="content">
+        <h2>Notifications</h2>
+        <p class="muted">Choose which notifications appear right away, which wait for the weekly digest, and when to keep quiet.</p>
+        <div class="grid">
+            <label for="bills">Bills</label><select id="bills" class="mode"></select>
+            <label for="budgets">Budgets</label><select id="budgets" class="mode"></select>
+            <label for="imports">Imports</label><select id="imports" class="mode"></select>
+            <label for="updates">Updates</label><select id="updates" class="mode"></select>
+            <label for="maintenance">Backups and restarts</label><select id="maintenance" class="mode"></select>
+            <label class="option"><input type="checkbox" id="quietHours"> Quiet hours (hold notifications until they end)</label>
+            <label for="quietStart">From</label>
+            <div class="range">
+                <input type="time" id="quietStart" aria-label="Quiet hours start">
+                <span>to</span>
+                <input type="time" id="quietEnd" aria-label="Quiet hours end">
+            </div>
+            <label for="digestDay">Weekly digest</label>
+            <div class="range">
+                <select id="digestDay">
+                    <option value="monday">Monday</option>
+                    <option value="tuesday">Tuesday</option>
+                    <option value="wednesday">Wednesday</option>
+                    <option value="thursday">Thursday</option>
+                    <option value="friday">Friday</option>
+                    <option value="saturday">Saturday</option>
+                    <option value="sunday">Sunday</option>
+                </select>
+                <span>at</span>
+                <input type="time" id="digestTime" aria-label="Digest time">
+            </div>
+        </div>
+        <p id="pending" class="muted"></p>
+    </div>
+    <div class="toolbar">
+        <button id="saveBtn" class="primary">Save</button>
+        <span id="status" class="muted" role="status" aria-live="polite" style="margin-left: auto"></span>
+    </div>
+</body>
+</html>`;
+
+        const $ = id => document.getElementById(id);
+        const TYPES = ['bills', 'budgets', 'imports', 'updates', 'maintenance'];
+        const MODES = [['instant', 'Show right away'], ['digest', 'Weekly digest'], ['off', 'Off']];
+
+        document.querySelectorAll('select.mode').forEach(select => {
+            MODES.forEach(([value, label]) => {
+                const option = document.createElement('option');
+                option.value = value;
+                option.textContent = label;
+                select.appendChild(option);
+            });
+        });
+
+        function updateQuiet() {
+            $('quietStart').disabled = $('quietEnd').disabled = !$('quietHours').checked;
+        }
+
+        async function loadPending() {
+            try {
+                const status = await tauriApi.core.invoke('get_notification_status');
+                const parts = [];
+                if (status.quiet_now) parts.push('Quiet hours are on now.');
+                if (status.held) parts.push(status.held + ' held until quiet hours end.');
+                if (status.digest) parts.push(status.digest + ' waiting for the digest.');
+                if (status.next_digest) parts.push('Next digest: ' + new Date(status.next_digest).toLocaleString() + '.');
+                $('pending').textContent = parts.join(' ');
+            } catch (e) {
+                $('pending').textContent = '';
+            }
+        }
+
+        async function load() {
+            try {
+                const settings = await tauriApi.core.invoke('get_settings');
+                const n = settings.notifications;
+                TYPES.forEach(type => { $(type).value = n[type]; });
+                $('quietHours').checked = n.quiet_hours;
+                $('quietStart').value = n.quiet_start;
+                $('quietEnd').value = n.quiet_end;
+                $('digestDay').value = n.digest_day;
+                $('digestTime').value = n.digest_time;
+                updateQuiet();
+            } catch (e) {
+                $('status').textContent = String(e);
+            }
+            loadPending();
+        }
+
+        $('quietHours').onchange = updateQuiet;
+        $('saveBtn').onclick = async () => {
+            const notifications = {
+                quiet_hours: $('quietHours').checked,
+                quiet_start: $('quietStart').value,
+                quiet_end: $('quietEnd').value,
+                digest_day: $('digestDay').value,
+                digest_time: $('digestTime').value,
+            };
+            TYPES.forEach(type => { notifications[type] = $(type).value; });
+            $('saveBtn').disabled = true;
+            try {
+                await tauriApi.core.invoke('update_settings', { changes: { notifications } });
+                $('status').textContent = 'Saved';
+                loadPending();
+            } catch (e) {
+                $('status').textContent = String(e);
+            }
+            $('saveBtn').disabled = false;
+        };
+        load();
+    "#;
+
+    open_injected_window(app, "preferences", "Preferences", (560.0, 560.0), true, script);
+}
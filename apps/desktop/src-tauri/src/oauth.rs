@@ -0,0 +1,160 @@
+// Shell-managed OAuth sign-in for third-party connections
+//
+// The web app calls `start_oauth` with the provider's authorization URL, built with
+// the redirect URI from `oauth_redirect_uri` (mw://localhost/oauth/callback). The
+// provider opens in a dedicated window; when it redirects to that URI the navigation
+// is caught, the window closes and the parameters are returned. With `callback` (a
+// server path) the calling window is also sent to `<its server><callback>?<params>`, so
+// the server finishes the exchange exactly as after a browser redirect, with the
+// user's session cookie. No bouncing through the system browser.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use serde::Serialize;
+use tauri::{AppHandle, Manager, Url, WebviewUrl, WebviewWindow, WebviewWindowBuilder};
+use tokio::sync::oneshot;
+use crate::protocol::page_url;
+use crate::server::get_server_url;
+
+const WINDOW_LABEL: &str = "oauth";
+const CALLBACK_PAGE: &str = "oauth/callback";
+/// Give up on a sign-in left open this long
+const TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Serialize)]
+pub struct OAuthResult {
+    code: Option<String>,
+    state: Option<String>,
+    /// The provider's `error` (e.g. "access_denied")
+    error: Option<String>,
+    /// Every parameter of the redirect
+    params: BTreeMap<String, String>,
+}
+
+type Pending = Arc<Mutex<Option<oneshot::Sender<Result<BTreeMap<String, String>, String>>>>>;
+
+fn redirect_uri() -> Url {
+    page_url(CALLBACK_PAGE)
+}
+
+/// Whether a navigation goes to our redirect URI
+fn is_redirect(url: &Url) -> bool {
+    let redirect = redirect_uri();
+    url.scheme() == redirect.scheme() && url.host_str() == redirect.host_str() && url.path() == redirect.path()
+}
+
+/// Query and fragment parameters (implicit flows put them in the fragment)
+fn params_of(url: &Url) -> BTreeMap<String, String> {
+    let mut params: BTreeMap<String, String> = url.query_pairs().into_owned().collect();
+    // Parsed as a query of a throwaway URL, form_urlencoded isn't exposed by tauri
+    if let Some(fragment) = url.fragment().and_then(|f| Url::parse(&format!("http://localhost/?{}", f)).ok()) {
+        params.extend(fragment.query_pairs().into_owned());
+    }
+    params
+}
+
+fn finish(pending: &Pending, result: Result<BTreeMap<String, String>, String>) {
+    if let Some(tx) = pending.lock().unwrap_or_else(|e| e.into_inner()).take() {
+        let _ = tx.send(result);
+    }
+}
+
+/// Redirect URI to register with providers and put in authorization URLs
+#[tauri::command]
+pub async fn oauth_redirect_uri() -> Result<String, String> {
+    Ok(redirect_uri().to_string())
+}
+
+/// Run an OAuth sign-in in a dedicated window and return the redirect's parameters
+#[tauri::command]
+pub async fn start_oauth(
+    app: AppHandle,
+    window: WebviewWindow,
+    url: String,
+    callback: Option<String>,
+) -> Result<OAuthResult, String> {
+    let url: Url = url.parse().map_err(|e| format!("Invalid authorization URL: {}", e))?;
+    if url.scheme() != "https" {
+        return Err("The authorization URL must use https".to_string());
+    }
+    if let Some(callback) = &callback {
+        if !callback.starts_with('/') || callback.starts_with("//") {
+            return Err("callback must be a path on the Moneywright server".to_string());
+        }
+    }
+    if app.get_webview_window(WINDOW_LABEL).is_some() {
+        return Err("Another sign-in is already in progress".to_string());
+    }
+    let expected_state = url.query_pairs().find(|(k, _)| k == "state").map(|(_, v)| v.into_owned());
+    let title = format!("Sign in - {}", url.host_str().unwrap_or("provider"));
+
+    let (tx, rx) = oneshot::channel();
+    let pending: Pending = Arc::new(Mutex::new(Some(tx)));
+    let on_redirect = pending.clone();
+    let nav_app = app.clone();
+    let auth_window = crate::webview::configure(WebviewWindowBuilder::new(&app, WINDOW_LABEL, WebviewUrl::External(url)))
+        .title(title)
+        .inner_size(520.0, 720.0)
+        .min_inner_size(400.0, 500.0)
+        .on_navigation(move |target| {
+            if !is_redirect(target) {
+                return true;
+            }
+            finish(&on_redirect, Ok(params_of(target)));
+            // Not from inside the webview's own navigation callback
+            if let Some(window) = nav_app.get_webview_window(WINDOW_LABEL) {
+                tauri::async_runtime::spawn(async move {
+                    let _ = window.close();
+                });
+            }
+            false
+        })
+        .build()
+        .map_err(|e| format!("Failed to open sign-in window: {}", e))?;
+
+    // Closing the window before the redirect cancels
+    let on_close = pending.clone();
+    auth_window.on_window_event(move |event| {
+        if let tauri::WindowEvent::Destroyed = event {
+            finish(&on_close, Err("Sign-in was cancelled".to_string()));
+        }
+    });
+
+    let params = match tokio::time::timeout(TIMEOUT, rx).await {
+        Ok(Ok(result)) => result?,
+        Ok(Err(_)) => return Err("Sign-in was cancelled".to_string()),
+        Err(_) => {
+            let _ = auth_window.close();
+            return Err("Sign-in timed out".to_string());
+        }
+    };
+
+    let state = params.get("state").cloned();
+    if expected_state.is_some() && state != expected_state {
+        return Err("Sign-in response did not match the request (state mismatch)".to_string());
+    }
+
+    if let Some(callback) = callback {
+        // The server of the calling window, which may be a profile's
+        let server = window
+            .url()
+            .ok()
+            .filter(|u| u.scheme() == "http")
+            .map(|u| u.origin().ascii_serialization())
+            .unwrap_or_else(get_server_url);
+        let mut target: Url = format!("{}{}", server, callback)
+            .parse()
+            .map_err(|e| format!("Invalid callback: {}", e))?;
+        target.query_pairs_mut().extend_pairs(params.iter());
+        window.navigate(target).map_err(|e| format!("Failed to return to Moneywright: {}", e))?;
+    }
+    let _ = window.set_focus();
+
+    Ok(OAuthResult {
+        code: params.get("code").cloned(),
+        state,
+        error: params.get("error").cloned(),
+        params,
+    })
+}
@@ -0,0 +1,246 @@
+// Registry of the server processes this app spawned, to clean up after a crash
+//
+// When the app crashes its sidecars can keep running, and the next launch used to
+// find them only by killing whatever listened on the server port. Each spawned
+// server is now recorded in `spawned.json` in the data dir with its PID, start time
+// and executable, and dropped again when it exits. Entries left by an earlier app
+// process are orphans: before a server starts they are stopped (SIGTERM, then a kill
+// after the grace period), but only while the PID still belongs to the same process
+// (same start time) running our sidecar binary, so a reused PID is never touched.
+//
+// Processes the server starts itself are listed with the entry too (kept current by the
+// stats monitor, see serverstats.rs) and stopped along with it, also when the server
+// itself is already gone; again only while their start time still matches.
+//
+// The port cleanup in ports.rs stays as the fallback for servers started before this
+// registry existed. Whatever is stopped here is recorded like it (see cleanups.rs).
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+use crate::arch;
+use crate::cleanups::{self, Action, Cleanup};
+use crate::ports;
+use crate::server::sidecar_path;
+
+const REGISTRY_FILE: &str = "spawned.json";
+
+static PATH: OnceLock<PathBuf> = OnceLock::new();
+/// Serializes read-modify-write of the registry
+static WRITE: Mutex<()> = Mutex::new(());
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Spawned {
+    pid: u32,
+    /// Process start time (Unix seconds), tells a reused PID apart
+    started: u64,
+    exe: PathBuf,
+    port: u16,
+    /// The app process that spawned it
+    app_pid: u32,
+    /// Processes the server started, as last seen
+    #[serde(default)]
+    children: Vec<Child>,
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+struct Child {
+    pid: u32,
+    started: u64,
+}
+
+/// A process to stop, with what's recorded about it
+struct Target {
+    pid: u32,
+    exe: PathBuf,
+    port: u16,
+}
+
+/// Where the registry is kept; called once the data dir is known
+pub fn init(data_dir: &Path) {
+    let _ = PATH.set(data_dir.join(REGISTRY_FILE));
+}
+
+fn read_entries(path: &Path) -> Vec<Spawned> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Change the registry under the write lock
+fn update(change: impl FnOnce(&mut Vec<Spawned>)) {
+    let Some(path) = PATH.get() else {
+        return;
+    };
+    let _guard = WRITE.lock().unwrap_or_else(|e| e.into_inner());
+    let mut entries = read_entries(path);
+    change(&mut entries);
+    let result = serde_json::to_string_pretty(&entries)
+        .map_err(|e| e.to_string())
+        .and_then(|json| fs::write(path, json).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        eprintln!("Warning: Failed to update {}: {}", path.display(), e);
+    }
+}
+
+fn load_process(system: &mut System, pid: Pid) {
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[pid]),
+        true,
+        ProcessRefreshKind::nothing().with_exe(UpdateKind::OnlyIfNotSet),
+    );
+}
+
+/// Record a server process that was just spawned
+pub fn register(pid: u32, port: u16) {
+    let mut system = System::new();
+    load_process(&mut system, Pid::from_u32(pid));
+    let Some(process) = system.process(Pid::from_u32(pid)) else {
+        return;
+    };
+    // Without the executable it could never be verified later, so it isn't recorded
+    let Some(exe) = process.exe().map(Path::to_path_buf) else {
+        return;
+    };
+    let entry = Spawned {
+        pid,
+        started: process.start_time(),
+        exe,
+        port,
+        app_pid: std::process::id(),
+        children: Vec::new(),
+    };
+    update(|entries| {
+        entries.retain(|e| e.pid != pid);
+        entries.push(entry);
+    });
+}
+
+/// Remember the processes a server started, given as (pid, start time)
+pub fn track_children(pid: u32, children: &[(u32, u64)]) {
+    let children: Vec<Child> = children.iter().map(|(pid, started)| Child { pid: *pid, started: *started }).collect();
+    let Some(path) = PATH.get() else {
+        return;
+    };
+    // Most samples find the same processes, the file is only written when they change
+    if read_entries(path).iter().any(|e| e.pid == pid && e.children == children) {
+        return;
+    }
+    update(|entries| {
+        if let Some(entry) = entries.iter_mut().find(|e| e.pid == pid) {
+            entry.children = children;
+        }
+    });
+}
+
+/// Drop a server process that exited
+pub fn unregister(pid: u32) {
+    update(|entries| entries.retain(|e| e.pid != pid));
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Stop servers left running by an earlier app process, returning how many were stopped
+pub fn reap(data_dir: &Path, grace: Duration) -> usize {
+    let Some(path) = PATH.get() else {
+        return 0;
+    };
+    let own = std::process::id();
+    let orphans: Vec<Spawned> = read_entries(path).into_iter().filter(|e| e.app_pid != own).collect();
+    if orphans.is_empty() {
+        return 0;
+    }
+
+    let sidecars: Vec<PathBuf> = sidecar_path().into_iter().chain(arch::native_sidecar(data_dir)).collect();
+    let mut system = System::new();
+    let mut targets: Vec<Target> = Vec::new();
+    for orphan in &orphans {
+        let pid = Pid::from_u32(orphan.pid);
+        load_process(&mut system, pid);
+        let server_matches = system.process(pid).is_some_and(|process| {
+            process.start_time() == orphan.started
+                && process.exe().is_some_and(|exe| same_file(exe, &orphan.exe))
+                && sidecars.iter().any(|sidecar| same_file(sidecar, &orphan.exe))
+        });
+        if server_matches {
+            targets.push(Target { pid: orphan.pid, exe: orphan.exe.clone(), port: orphan.port });
+        } else if system.process(pid).is_some() {
+            println!("Leaving process {}: it's no longer a server this app started", orphan.pid);
+        }
+        // Its recorded children, and ones started since the last sample
+        let mut children: Vec<u32> = orphan.children.iter().map(|child| child.pid).collect();
+        if server_matches {
+            for child in ports::descendants(orphan.pid) {
+                if !children.contains(&child) {
+                    children.push(child);
+                }
+            }
+        }
+        for child in children {
+            load_process(&mut system, Pid::from_u32(child));
+            let Some(process) = system.process(Pid::from_u32(child)) else {
+                continue;
+            };
+            let recorded = orphan.children.iter().find(|c| c.pid == child);
+            if recorded.is_some_and(|c| c.started != process.start_time()) {
+                println!("Leaving process {}: it's no longer one this app's server started", child);
+                continue;
+            }
+            let exe = process.exe().map(Path::to_path_buf).unwrap_or_default();
+            targets.push(Target { pid: child, exe, port: orphan.port });
+        }
+    }
+
+    let mut asked = Vec::new();
+    let mut remaining = Vec::new();
+    for target in targets {
+        println!("Stopping orphaned server process {} on port {}", target.pid, target.port);
+        if ports::terminate(target.pid) {
+            asked.push(target);
+        } else {
+            remaining.push(target);
+        }
+    }
+    let mut stopped = 0;
+    if !asked.is_empty() {
+        let pids: Vec<u32> = asked.iter().map(|t| t.pid).collect();
+        let running = ports::wait_for_exit(&pids, grace);
+        for target in asked {
+            if running.contains(&target.pid) {
+                remaining.push(target);
+            } else {
+                record(&target, Action::Terminated);
+                stopped += 1;
+            }
+        }
+    }
+    for target in remaining {
+        println!("Killing orphaned server process {}", target.pid);
+        let killed = system.process(Pid::from_u32(target.pid)).is_none_or(|p| p.kill());
+        if killed {
+            record(&target, Action::Killed);
+            stopped += 1;
+        } else {
+            eprintln!("Warning: Failed to stop orphaned server process {}", target.pid);
+            record(&target, Action::KillFailed);
+        }
+    }
+
+    update(|entries| entries.retain(|e| e.app_pid == own));
+    stopped
+}
+
+fn record(target: &Target, action: Action) {
+    let name = target.exe.file_name().map(|name| name.to_string_lossy().to_string());
+    let exe = Some(target.exe.to_string_lossy().to_string()).filter(|exe| !exe.is_empty());
+    cleanups::record(Cleanup::process(action, target.pid, name, exe, target.port));
+}
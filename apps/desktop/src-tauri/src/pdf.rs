@@ -0,0 +1,188 @@
+// Rendering pages of the web app to PDF without showing them
+//
+// A hidden window loads the page with the main window's session (the cookie store is
+// shared), waits for it to load and fetch its data, then prints it through the
+// platform webview: WebKitGTK's print operation to a file on Linux, WebView2's
+// PrintToPdf on Windows and WKWebView's createPDF on macOS. Used by scheduled exports
+// to keep a PDF paper trail of reports.
+
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::webview::{PageLoadEvent, PlatformWebview};
+use tauri::{AppHandle, Url, WebviewUrl, WebviewWindowBuilder};
+use tokio::sync::oneshot;
+use crate::server::get_server_url;
+
+const WINDOW_LABEL: &str = "report_pdf";
+/// Page size of the hidden window, roughly a portrait page at 96 dpi
+const PAGE_SIZE: (f64, f64) = (1100.0, 1500.0);
+/// Time for the page to fetch and draw its data after loading
+const RENDER_DELAY: Duration = Duration::from_secs(4);
+const LOAD_TIMEOUT: Duration = Duration::from_secs(60);
+const PRINT_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Report pages that can be rendered: name used in file names, and route
+pub const REPORT_PAGES: &[(&str, &str)] = &[
+    ("dashboard", "/"),
+    ("accounts", "/accounts"),
+    ("transactions", "/transactions"),
+    ("investments", "/investments"),
+    ("loans", "/loans"),
+    ("insurance", "/insurance"),
+    ("subscriptions", "/subscriptions"),
+];
+
+/// One render at a time, they share the hidden window's label
+static RENDERING: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+type Done = Arc<Mutex<Option<oneshot::Sender<Result<(), String>>>>>;
+
+fn finish(done: &Done, result: Result<(), String>) {
+    if let Some(tx) = done.lock().unwrap_or_else(|e| e.into_inner()).take() {
+        let _ = tx.send(result);
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn start_print(webview: PlatformWebview, path: &Path, done: Done) {
+    use webkit2gtk::{PrintOperation, PrintOperationExt};
+
+    let uri = match Url::from_file_path(path) {
+        Ok(uri) => uri,
+        Err(()) => return finish(&done, Err(format!("Invalid output path {}", path.display()))),
+    };
+    // The "Print to File" backend writes the PDF without a dialog
+    let settings = gtk::PrintSettings::new();
+    settings.set_printer("Print to File");
+    settings.set(gtk::PRINT_SETTINGS_OUTPUT_FILE_FORMAT.as_str(), Some("pdf"));
+    settings.set(gtk::PRINT_SETTINGS_OUTPUT_URI.as_str(), Some(uri.as_str()));
+
+    let operation = PrintOperation::new(&webview.inner());
+    operation.set_print_settings(&settings);
+    let on_failed = done.clone();
+    operation.connect_failed(move |_, e| finish(&on_failed, Err(format!("Printing failed: {}", e))));
+    operation.connect_finished(move |_| finish(&done, Ok(())));
+    operation.print();
+}
+
+#[cfg(target_os = "windows")]
+fn start_print(webview: PlatformWebview, path: &Path, done: Done) {
+    use webview2_com::Microsoft::Web::WebView2::Win32::{ICoreWebView2PrintSettings, ICoreWebView2_7};
+    use webview2_com::PrintToPdfCompletedHandler;
+    use windows_core::{Interface, HSTRING};
+
+    let on_done = done.clone();
+    let handler = PrintToPdfCompletedHandler::create(Box::new(move |result, written| {
+        let outcome = match result {
+            Ok(()) if written => Ok(()),
+            Ok(()) => Err("WebView2 could not write the PDF".to_string()),
+            Err(e) => Err(format!("Printing failed: {}", e)),
+        };
+        finish(&on_done, outcome);
+        Ok(())
+    }));
+    let path = HSTRING::from(path.to_string_lossy().as_ref());
+    // SAFETY: the controller belongs to the live window this runs on (its UI thread)
+    let started = unsafe { webview.controller().CoreWebView2() }
+        .and_then(|core| core.cast::<ICoreWebView2_7>())
+        .and_then(|core| unsafe { core.PrintToPdf(&path, None::<&ICoreWebView2PrintSettings>, &handler) });
+    if let Err(e) = started {
+        finish(&done, Err(format!("Printing failed: {}", e)));
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn start_print(webview: PlatformWebview, path: &Path, done: Done) {
+    use block2::RcBlock;
+    use objc2_foundation::{NSData, NSError};
+    use objc2_web_kit::WKWebView;
+
+    let path = path.to_path_buf();
+    let block = RcBlock::new(move |data: *mut NSData, error: *mut NSError| {
+        // SAFETY: WebKit passes either the PDF data or an error, valid for the call
+        let outcome = match unsafe { (data.as_ref(), error.as_ref()) } {
+            (Some(data), _) => fs::write(&path, data.to_vec()).map_err(|e| format!("Failed to write PDF: {}", e)),
+            (None, Some(error)) => Err(format!("Printing failed: {}", error.localizedDescription())),
+            (None, None) => Err("Printing failed".to_string()),
+        };
+        finish(&done, outcome);
+    });
+    // SAFETY: Tauri hands out the window's WKWebView, alive while this runs on the main thread
+    let view: &WKWebView = unsafe { &*webview.inner().cast() };
+    unsafe { view.createPDFWithConfiguration_completionHandler(None, &block) };
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn start_print(_webview: PlatformWebview, _path: &Path, done: Done) {
+    finish(&done, Err("PDF reports are not supported on this platform".to_string()));
+}
+
+/// Render a page of the main server (e.g. "/investments") to PDF bytes
+pub async fn render_pdf(app: &AppHandle, route: &str) -> Result<Vec<u8>, String> {
+    let _rendering = RENDERING.lock().await;
+    let url: Url = format!("{}{}", get_server_url(), route)
+        .parse()
+        .map_err(|e| format!("Invalid report page {}: {}", route, e))?;
+
+    let (loaded_tx, loaded_rx) = oneshot::channel::<()>();
+    let loaded = Arc::new(Mutex::new(Some(loaded_tx)));
+    let window = crate::webview::configure(WebviewWindowBuilder::new(app, WINDOW_LABEL, WebviewUrl::External(url)))
+        .title("Moneywright report")
+        .inner_size(PAGE_SIZE.0, PAGE_SIZE.1)
+        .visible(false)
+        .skip_taskbar(true)
+        .on_page_load(move |_, payload| {
+            if payload.event() == PageLoadEvent::Finished {
+                if let Some(tx) = loaded.lock().unwrap_or_else(|e| e.into_inner()).take() {
+                    let _ = tx.send(());
+                }
+            }
+        })
+        .build()
+        .map_err(|e| format!("Failed to open report window: {}", e))?;
+    crate::tls::trust_in_webview(&window);
+
+    let result = async {
+        tokio::time::timeout(LOAD_TIMEOUT, loaded_rx)
+            .await
+            .map_err(|_| format!("{} did not load in time", route))?
+            .map_err(|_| format!("{} did not load", route))?;
+        tokio::time::sleep(RENDER_DELAY).await;
+        // Without a session the app sends the page to its sign-in screen
+        if window.url().is_ok_and(|u| u.path().starts_with("/login")) {
+            return Err("Sign in to Moneywright in the main window first".to_string());
+        }
+
+        let staging = tempfile_path();
+        let (tx, rx) = oneshot::channel();
+        let done: Done = Arc::new(Mutex::new(Some(tx)));
+        let print_path = staging.clone();
+        let on_error = done.clone();
+        if let Err(e) = window.with_webview(move |webview| start_print(webview, &print_path, done)) {
+            finish(&on_error, Err(format!("Printing failed: {}", e)));
+        }
+        let printed = tokio::time::timeout(PRINT_TIMEOUT, rx)
+            .await
+            .map_err(|_| format!("Printing {} timed out", route))?
+            .map_err(|_| format!("Printing {} was interrupted", route))
+            .and_then(|r| r);
+        let bytes = printed.and_then(|()| fs::read(&staging).map_err(|e| format!("Failed to read PDF: {}", e)));
+        let _ = fs::remove_file(&staging);
+        bytes
+    }
+    .await;
+
+    let _ = window.destroy();
+    result
+}
+
+/// Scratch file for the platform to print into
+fn tempfile_path() -> std::path::PathBuf {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    std::env::temp_dir().join(format!("moneywright-report-{:x}.pdf", nanos))
+}
@@ -0,0 +1,418 @@
+// Moving the data from SQLite to PostgreSQL
+//
+// From the Database window: the server is stopped, pointed at the new (empty)
+// PostgreSQL database and started once so its migrations create the tables, then
+// stopped again. Every table is then copied in one transaction, rows read from the
+// SQLite file in batches by rowid and cast by PostgreSQL to the column types (SQLite
+// keeps booleans as 0/1 and timestamps as text). Tables are filled parents first, and
+// the transaction is only committed if every table has as many rows as in SQLite.
+// Progress runs through a Migration job, which can be cancelled.
+//
+// Any failure restores the SQLite settings and starts the server on it again. The
+// SQLite file itself is never changed and stays in the data dir.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Mutex;
+use rusqlite::types::ValueRef;
+use rusqlite::{Connection, OpenFlags};
+use serde::Serialize;
+use tauri::{AppHandle, State};
+use tokio_postgres::types::ToSql;
+use crate::backup::sqlite_db_path;
+use crate::database::{self, Backend, DatabaseForm, PASSWORD_KEY};
+use crate::jobs::{start_job, JobHandle, JobKind};
+use crate::keychain;
+use crate::logs::{log_line, SharedLogStore};
+use crate::postgres;
+use crate::server::{read_database_url, remove_database_url, start_server, stop_server, write_database_url, SharedServerManager};
+
+/// Rows per INSERT, fewer for wide tables (PostgreSQL allows 65535 parameters)
+const BATCH_ROWS: usize = 500;
+const MAX_PARAMS: usize = 60_000;
+
+#[derive(Clone, Serialize)]
+pub struct TableCopy {
+    pub table: String,
+    pub rows: i64,
+}
+
+#[derive(Clone, Serialize)]
+pub struct MigrationResult {
+    pub tables: Vec<TableCopy>,
+    pub rows: i64,
+    /// SQLite tables the PostgreSQL schema doesn't have
+    pub skipped: Vec<String>,
+    /// The SQLite file, left in place
+    pub sqlite_path: String,
+}
+
+/// A table to copy, with the columns both databases have and their PostgreSQL types
+struct Table {
+    name: String,
+    columns: Vec<(String, String)>,
+    rows: i64,
+}
+
+fn quote(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
+
+fn sqlite_tables(conn: &Connection) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare("SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' AND name != '__drizzle_migrations'")
+        .map_err(|e| e.to_string())?;
+    let names = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| e.to_string())?;
+    Ok(names)
+}
+
+fn sqlite_columns(conn: &Connection, table: &str) -> Result<HashSet<String>, String> {
+    let mut stmt = conn
+        .prepare(&format!("PRAGMA table_info({})", quote(table)))
+        .map_err(|e| e.to_string())?;
+    let names = stmt
+        .query_map([], |row| row.get::<_, String>(1))
+        .and_then(|rows| rows.collect::<Result<HashSet<_>, _>>())
+        .map_err(|e| e.to_string())?;
+    Ok(names)
+}
+
+fn sqlite_count(conn: &Connection, table: &str) -> Result<i64, String> {
+    conn.query_row(&format!("SELECT COUNT(*) FROM {}", quote(table)), [], |row| row.get(0))
+        .map_err(|e| format!("Failed to count {}: {}", table, e))
+}
+
+/// Values of a row as text, None for NULL
+type Row = Vec<Option<String>>;
+
+/// Up to `limit` rows after `after` (by rowid) as text, with the last rowid read
+fn read_batch(conn: &Connection, table: &Table, after: i64, limit: usize) -> Result<(Vec<Row>, i64), String> {
+    let columns: Vec<String> = table.columns.iter().map(|(name, _)| quote(name)).collect();
+    let sql = format!(
+        "SELECT rowid, {} FROM {} WHERE rowid > ?1 ORDER BY rowid LIMIT ?2",
+        columns.join(", "),
+        quote(&table.name)
+    );
+    let mut stmt = conn.prepare_cached(&sql).map_err(|e| e.to_string())?;
+    let mut rows = stmt.query(rusqlite::params![after, limit as i64]).map_err(|e| e.to_string())?;
+    let mut batch = Vec::new();
+    let mut last = after;
+    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+        last = row.get(0).map_err(|e| e.to_string())?;
+        let values = (1..=columns.len())
+            .map(|i| {
+                row.get_ref(i).map(|value| match value {
+                    ValueRef::Null => None,
+                    ValueRef::Integer(n) => Some(n.to_string()),
+                    ValueRef::Real(f) => Some(f.to_string()),
+                    ValueRef::Text(text) => Some(String::from_utf8_lossy(text).to_string()),
+                    ValueRef::Blob(bytes) => Some(format!("\\x{}", hex::encode(bytes))),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        batch.push(values);
+    }
+    Ok((batch, last))
+}
+
+/// Tables of the PostgreSQL schema, parents before the tables referencing them
+async fn postgres_tables(client: &tokio_postgres::Client) -> Result<Vec<String>, String> {
+    let names: Vec<String> = client
+        .query(
+            "SELECT table_name::text FROM information_schema.tables \
+             WHERE table_schema = 'public' AND table_type = 'BASE TABLE' ORDER BY table_name",
+            &[],
+        )
+        .await
+        .map_err(|e| database::describe_error(&e))?
+        .iter()
+        .map(|row| row.get(0))
+        .collect();
+    let mut parents: HashMap<String, HashSet<String>> = HashMap::new();
+    let references = client
+        .query(
+            "SELECT c.conrelid::regclass::text, c.confrelid::regclass::text FROM pg_constraint c \
+             JOIN pg_namespace n ON n.oid = c.connamespace \
+             WHERE c.contype = 'f' AND n.nspname = 'public' AND c.conrelid <> c.confrelid",
+            &[],
+        )
+        .await
+        .map_err(|e| database::describe_error(&e))?;
+    for row in references {
+        let (child, parent): (String, String) = (row.get(0), row.get(1));
+        parents.entry(child.trim_matches('"').to_string()).or_default().insert(parent.trim_matches('"').to_string());
+    }
+
+    let mut ordered: Vec<String> = Vec::new();
+    let mut remaining = names;
+    while !remaining.is_empty() {
+        let (ready, waiting): (Vec<String>, Vec<String>) = remaining.into_iter().partition(|name| {
+            parents
+                .get(name)
+                .is_none_or(|p| p.iter().all(|parent| ordered.contains(parent) || parent == name))
+        });
+        if ready.is_empty() {
+            // A reference cycle; the rest goes in name order and the constraint decides
+            ordered.extend(waiting);
+            break;
+        }
+        ordered.extend(ready);
+        remaining = waiting;
+    }
+    Ok(ordered)
+}
+
+async fn postgres_columns(client: &tokio_postgres::Client, table: &str) -> Result<Vec<(String, String)>, String> {
+    let rows = client
+        .query(
+            "SELECT column_name::text, udt_name::text FROM information_schema.columns \
+             WHERE table_schema = 'public' AND table_name = $1 ORDER BY ordinal_position",
+            &[&table],
+        )
+        .await
+        .map_err(|e| database::describe_error(&e))?;
+    Ok(rows.iter().map(|row| (row.get(0), row.get(1))).collect())
+}
+
+/// Columns and row count of each SQLite table
+fn sqlite_schema(conn: &Connection) -> Result<HashMap<String, (HashSet<String>, i64)>, String> {
+    sqlite_tables(conn)?
+        .into_iter()
+        .map(|name| {
+            let columns = sqlite_columns(conn, &name)?;
+            let rows = sqlite_count(conn, &name)?;
+            Ok((name, (columns, rows)))
+        })
+        .collect()
+}
+
+/// Match up the tables of both databases
+async fn plan(client: &tokio_postgres::Client, conn: &Mutex<Connection>) -> Result<(Vec<Table>, Vec<String>), String> {
+    let sqlite = sqlite_schema(&conn.lock().unwrap_or_else(|e| e.into_inner()))?;
+    let mut tables = Vec::new();
+    for name in postgres_tables(client).await? {
+        let Some((present, rows)) = sqlite.get(&name) else {
+            continue;
+        };
+        let columns: Vec<(String, String)> = postgres_columns(client, &name)
+            .await?
+            .into_iter()
+            .filter(|(column, _)| present.contains(column))
+            .collect();
+        if columns.is_empty() {
+            continue;
+        }
+        tables.push(Table { name, columns, rows: *rows });
+    }
+    let mut skipped: Vec<String> = sqlite
+        .into_keys()
+        .filter(|name| !tables.iter().any(|t| &t.name == name))
+        .collect();
+    skipped.sort();
+    Ok((tables, skipped))
+}
+
+/// Copy every table in one transaction, committed only when the row counts match
+async fn copy_tables(
+    client: &mut tokio_postgres::Client,
+    conn: &Mutex<Connection>,
+    tables: &[Table],
+    job: &JobHandle<tauri::Wry>,
+) -> Result<(), String> {
+    let total: i64 = tables.iter().map(|t| t.rows).sum::<i64>().max(1);
+    let tx = client.transaction().await.map_err(|e| database::describe_error(&e))?;
+    // SQLite timestamps without an offset are UTC
+    tx.batch_execute("SET LOCAL TIME ZONE 'UTC'").await.map_err(|e| database::describe_error(&e))?;
+    // The server's first start adds rows of its own (the local user)
+    let names: Vec<String> = tables.iter().map(|t| quote(&t.name)).collect();
+    if !names.is_empty() {
+        tx.batch_execute(&format!("TRUNCATE {} CASCADE", names.join(", ")))
+            .await
+            .map_err(|e| database::describe_error(&e))?;
+    }
+
+    let mut copied = 0i64;
+    for table in tables {
+        job.progress(Some(copied as f64 / total as f64), Some(format!("Copying {}", table.name)));
+        let columns: Vec<String> = table.columns.iter().map(|(name, _)| quote(name)).collect();
+        let limit = BATCH_ROWS.min(MAX_PARAMS / columns.len()).max(1);
+        let mut after = i64::MIN;
+        loop {
+            if job.is_cancelled() {
+                return Err("Cancelled".to_string());
+            }
+            let (batch, last) = read_batch(&conn.lock().unwrap_or_else(|e| e.into_inner()), table, after, limit)?;
+            if batch.is_empty() {
+                break;
+            }
+            after = last;
+
+            let mut placeholders = Vec::with_capacity(batch.len());
+            for r in 0..batch.len() {
+                let values: Vec<String> = table
+                    .columns
+                    .iter()
+                    .enumerate()
+                    .map(|(c, (_, ty))| format!("${}::text::{}", r * columns.len() + c + 1, quote(ty)))
+                    .collect();
+                placeholders.push(format!("({})", values.join(", ")));
+            }
+            let sql = format!(
+                "INSERT INTO {} ({}) VALUES {}",
+                quote(&table.name),
+                columns.join(", "),
+                placeholders.join(", ")
+            );
+            let params: Vec<&(dyn ToSql + Sync)> = batch
+                .iter()
+                .flatten()
+                .map(|value| value as &(dyn ToSql + Sync))
+                .collect();
+            tx.execute(&sql, &params)
+                .await
+                .map_err(|e| format!("Failed to copy {}: {}", table.name, database::describe_error(&e)))?;
+            copied += batch.len() as i64;
+            job.progress(Some(copied as f64 / total as f64), None);
+        }
+    }
+
+    job.progress(Some(1.0), Some("Checking row counts".to_string()));
+    for table in tables {
+        let count: i64 = tx
+            .query_one(&format!("SELECT COUNT(*) FROM {}", quote(&table.name)), &[])
+            .await
+            .map_err(|e| database::describe_error(&e))?
+            .get(0);
+        if count != table.rows {
+            return Err(format!(
+                "{} has {} rows in PostgreSQL but {} in SQLite, nothing was migrated",
+                table.name, count, table.rows
+            ));
+        }
+    }
+    tx.commit().await.map_err(|e| database::describe_error(&e))
+}
+
+/// Go back to SQLite after a failed migration and start the server on it
+async fn restore_sqlite(
+    app: &AppHandle,
+    manager: &SharedServerManager,
+    log_store: &SharedLogStore,
+    data_dir: &Path,
+    previous_password: Option<String>,
+) -> Result<(), String> {
+    if let Err(e) = stop_server(manager.clone()).await {
+        eprintln!("Warning: {}", e);
+    }
+    remove_database_url(data_dir)?;
+    match &previous_password {
+        Some(password) => keychain::set_secret(PASSWORD_KEY, password)?,
+        None => keychain::delete_secret(PASSWORD_KEY)?,
+    }
+    log_line(app, log_store, "Migration to PostgreSQL failed, back on SQLite", "error").await;
+    crate::restart_server(app.clone(), manager.clone(), log_store.clone()).await
+}
+
+async fn migrate(
+    app: &AppHandle,
+    manager: &SharedServerManager,
+    log_store: &SharedLogStore,
+    data_dir: &Path,
+    form: &DatabaseForm,
+    job: &JobHandle<tauri::Wry>,
+) -> Result<MigrationResult, String> {
+    job.progress(None, Some("Connecting to PostgreSQL".to_string()));
+    let (url, password, config) = match form.backend {
+        Backend::Embedded => {
+            let (url, password) = postgres::prepare(app, log_store, data_dir).await?;
+            let mut config = url.parse::<tokio_postgres::Config>().map_err(|e| e.to_string())?;
+            config.password(&password);
+            (url, Some(password), config)
+        }
+        _ => database::form_config(form, data_dir)?,
+    };
+    let client = database::connect(&config).await?;
+    if database::postgres_migrations(&client).await != Some(0) {
+        return Err("This PostgreSQL database already has Moneywright data. Migrate into an empty database.".to_string());
+    }
+    drop(client);
+
+    job.progress(None, Some("Stopping the server".to_string()));
+    stop_server(manager.clone()).await?;
+    if let Some(password) = &password {
+        keychain::set_secret(PASSWORD_KEY, password)?;
+    }
+    write_database_url(data_dir, &url)?;
+
+    // Its migrations create the tables
+    job.progress(None, Some("Creating the tables in PostgreSQL".to_string()));
+    start_server(app.clone(), manager.clone(), log_store.clone()).await?;
+    stop_server(manager.clone()).await?;
+
+    let conn = Connection::open_with_flags(sqlite_db_path(data_dir), OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map(Mutex::new)
+        .map_err(|e| format!("Failed to open the SQLite database: {}", e))?;
+    let mut client = database::connect(&config).await?;
+    let (tables, skipped) = plan(&client, &conn).await?;
+    copy_tables(&mut client, &conn, &tables, job).await?;
+
+    let rows = tables.iter().map(|t| t.rows).sum();
+    log_line(app, log_store, format!("Migrated {} rows in {} tables to PostgreSQL", rows, tables.len()), "info").await;
+    job.progress(Some(1.0), Some("Starting the server".to_string()));
+    crate::restart_server(app.clone(), manager.clone(), log_store.clone())
+        .await
+        .map_err(|e| format!("The data was copied, but the server failed to start on PostgreSQL: {}", e))?;
+    crate::refresh_main_window(app);
+
+    Ok(MigrationResult {
+        tables: tables
+            .into_iter()
+            .map(|t| TableCopy { table: t.name, rows: t.rows })
+            .collect(),
+        rows,
+        skipped,
+        sqlite_path: sqlite_db_path(data_dir).to_string_lossy().to_string(),
+    })
+}
+
+/// Move the SQLite data into the PostgreSQL database of the form and switch to it
+#[tauri::command]
+pub async fn migrate_to_postgres(
+    app: AppHandle,
+    manager: State<'_, SharedServerManager>,
+    log_store: State<'_, SharedLogStore>,
+    form: DatabaseForm,
+) -> Result<MigrationResult, String> {
+    let manager = manager.inner().clone();
+    let log_store = log_store.inner().clone();
+    let data_dir = manager.lock().await.data_dir().clone();
+
+    if cfg!(debug_assertions) {
+        return Err("Migrating needs the bundled server, which development builds don't run".to_string());
+    }
+    if form.backend == Backend::Sqlite {
+        return Err("Choose the PostgreSQL database to migrate to".to_string());
+    }
+    if read_database_url(&data_dir).is_some() {
+        return Err("Moneywright already uses PostgreSQL".to_string());
+    }
+    if !sqlite_db_path(&data_dir).exists() {
+        return Err("There is no SQLite database to migrate".to_string());
+    }
+
+    let previous_password = keychain::get_secret(PASSWORD_KEY).ok().flatten();
+    let job = start_job(&app, JobKind::Migration, "Migrating to PostgreSQL", true);
+    let mut result = migrate(&app, &manager, &log_store, &data_dir, &form, &job).await;
+    // Once the server was stopped, a failure goes back to SQLite
+    if result.is_err() && !manager.lock().await.is_running() {
+        if let Err(e) = restore_sqlite(&app, &manager, &log_store, &data_dir, previous_password).await {
+            result = result.map_err(|msg| format!("{} Going back to SQLite failed too: {}", msg, e));
+        }
+    }
+    job.finish(&result);
+    result
+}
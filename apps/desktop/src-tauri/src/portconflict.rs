@@ -0,0 +1,121 @@
+// Asking before stopping another program on the server port
+//
+// Leftover Moneywright servers are stopped without asking (see orphans.rs and ports.rs),
+// but the port used to be freed by stopping whatever listened on it, which could be a
+// dev server or any other app of the user. Now such a program is left alone: the server
+// doesn't start and this window says what holds the port. Only when the user chooses
+// to is that process (the same PID, still on the port) stopped and the server started.
+
+use std::sync::Mutex;
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use crate::ports;
+use crate::server::SharedServerManager;
+use crate::windows::open_injected_window;
+
+const WINDOW_LABEL: &str = "port-conflict";
+
+/// The program that kept the server from starting
+static CONFLICT: Mutex<Option<PortConflict>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PortConflict {
+    pub port: u16,
+    pub pid: u32,
+    pub name: String,
+}
+
+/// Remember the program on the port and ask the user about it
+pub fn report(app: &AppHandle, port: u16, pid: u32, name: &str) {
+    *CONFLICT.lock().unwrap_or_else(|e| e.into_inner()) = Some(PortConflict { port, pid, name: name.to_string() });
+    open_port_conflict_window(app);
+}
+
+/// The program holding the server port, if the last start ran into one
+#[tauri::command]
+pub async fn get_port_conflict() -> Result<Option<PortConflict>, String> {
+    Ok(CONFLICT.lock().unwrap_or_else(|e| e.into_inner()).clone())
+}
+
+/// Stop the program holding the server port; the caller starts the server afterwards
+#[tauri::command]
+pub async fn stop_port_conflict(app: AppHandle) -> Result<(), String> {
+    let conflict = CONFLICT
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .ok_or_else(|| "No program is holding the server port".to_string())?;
+    let grace = app.state::<SharedServerManager>().lock().await.shutdown_grace();
+    tauri::async_runtime::spawn_blocking(move || ports::stop_program(conflict.port, conflict.pid, grace))
+        .await
+        .map_err(|e| e.to_string())??;
+    *CONFLICT.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    Ok(())
+}
+
+/// Open the window asking whether to stop the program on the port
+fn open_port_conflict_window(app: &AppHandle) {
+    let script = r#"
+        const tauriApi = window.__TAURI__;
+
+        document.documentElement.innerHTML = `
+<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <title>Port in Use</title>
+    <style>
+        __BASE_STYLE__
+        .content { flex: 1; padding: 24px; display: flex; flex-direction: column; gap: 12px; }
+        .detail { font-family: ui-monospace, SFMono-Regular, Menlo, monospace; font-size: 12px; word-break: break-all; }
+        .actions { display: flex; gap: 8px; margin-top: 8px; }
+    </style>
+</head>
+<body>
+    <div class="content">
+        <h1>Another program is using Moneywright's port</h1>
+        <p id="message"></p>
+        <div id="program" class="detail"></div>
+        <p class="muted">Stopping it may lose unsaved work in that program. You can also quit it yourself and start the server again.</p>
+        <div class="actions">
+            <button id="stopBtn" class="primary">Stop It and Start Moneywright</button>
+            <button id="cancelBtn">Leave It Running</button>
+        </div>
+        <div id="status" class="muted" role="status" aria-live="polite"></div>
+    </div>
+</body>
+</html>`;
+
+        const $ = id => document.getElementById(id);
+        const current = tauriApi.window.getCurrentWindow();
+
+        async function load() {
+            const conflict = await tauriApi.core.invoke('get_port_conflict');
+            if (!conflict) {
+                $('message').textContent = 'The port is no longer in use by another program.';
+                $('stopBtn').disabled = true;
+                return;
+            }
+            $('message').textContent = 'The Moneywright server needs port ' + conflict.port + ', but another program is listening on it, so the server could not start.';
+            $('program').textContent = conflict.name + ' (process ' + conflict.pid + ')';
+        }
+
+        $('stopBtn').onclick = async () => {
+            $('stopBtn').disabled = true;
+            $('status').textContent = 'Stopping it...';
+            try {
+                await tauriApi.core.invoke('stop_port_conflict');
+                $('status').textContent = 'Starting the server...';
+                await tauriApi.core.invoke('restart_server_cmd');
+                current.close();
+            } catch (e) {
+                $('status').textContent = String(e);
+                $('stopBtn').disabled = false;
+            }
+        };
+        $('cancelBtn').onclick = () => current.close();
+        load();
+    "#;
+
+    open_injected_window(app, WINDOW_LABEL, "Port in Use", (520.0, 300.0), false, script);
+}
@@ -0,0 +1,435 @@
+// Finding and stopping a server left listening on a port (e.g. after a crash)
+//
+// The listening process is normally looked up with the OS tool: lsof on macOS, ss on
+// Linux, netstat on Windows. Hardened corporate images may not ship these or may block
+// running them, which used to make the cleanup silently do nothing. Without the tool,
+// leftover servers are found by scanning processes instead: a Moneywright sidecar with
+// PORT=<port> in its environment. Where even that is restricted nothing can be
+// stopped, and the first server start logs a warning saying so.
+//
+// Processes are stopped through sysinfo, so no `kill`/`taskkill` binary is needed.
+// They get SIGTERM first and a grace period to finish writing before they're killed;
+// Windows has no such signal, so there the server manager asks the server over HTTP
+// (see server.rs) and a leftover found here is killed right away.
+//
+// On a shared computer another OS user's Moneywright may hold the port. Their server
+// is never stopped (only processes of the current user are), and `holder` tells that
+// case apart so the main server can move to a port of its own. Without root, lsof and
+// ss don't show other users' processes, so a busy port with no visible listener is
+// reported as held by someone else too.
+//
+// Only Moneywright servers are stopped on their own. Another program of this user on the
+// port (a dev server, say) is reported as `Holder::OtherProgram`, and `stop_program` stops
+// it only after the user agreed to it in the port conflict window (see portconflict.rs).
+//
+// Every process stopped here is recorded with its name and executable (see cleanups.rs),
+// so the doctor can show what was stopped or killed and how often.
+
+use std::ffi::OsStr;
+use std::net::TcpListener;
+use std::path::Path;
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use std::time::Duration;
+use crate::cleanups::{self, Action, Cleanup};
+use sysinfo::{Pid, Process, ProcessRefreshKind, ProcessStatus, ProcessesToUpdate, Signal, System, Uid, UpdateKind, Users};
+
+/// File stem of the server sidecar binary
+const SIDECAR_NAME: &str = "moneywright";
+
+#[cfg(target_os = "macos")]
+const TOOL: &str = "lsof";
+#[cfg(target_os = "linux")]
+const TOOL: &str = "ss";
+#[cfg(target_os = "windows")]
+const TOOL: &str = "netstat";
+#[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+const TOOL: &str = "lsof";
+
+static INSPECTION: OnceLock<Inspection> = OnceLock::new();
+static WARNED: AtomicBool = AtomicBool::new(false);
+
+/// How the process listening on a port can be found on this machine
+#[derive(Clone, Copy, PartialEq)]
+pub enum Inspection {
+    /// The OS tool works
+    Tool,
+    /// The tool is missing or blocked, sidecars are found by scanning processes
+    ProcessScan,
+    /// Neither works, a leftover server can't be stopped
+    Restricted,
+}
+
+/// What is available here, detected once
+pub fn inspection() -> Inspection {
+    *INSPECTION.get_or_init(|| {
+        if tool_runs() {
+            Inspection::Tool
+        } else if can_scan_processes() {
+            Inspection::ProcessScan
+        } else {
+            Inspection::Restricted
+        }
+    })
+}
+
+/// Who is listening on a port
+#[derive(Clone, Debug, PartialEq)]
+pub enum Holder {
+    Free,
+    /// A Moneywright server of the current OS user (e.g. our own leftover server)
+    CurrentUser,
+    /// Another program of the current OS user, which is only stopped when the user agrees
+    OtherProgram { pid: u32, name: String },
+    /// Another OS user's process, with their user name when it can be looked up
+    OtherUser(Option<String>),
+    /// Busy, but the listener isn't visible to this user (another user's or a system process)
+    Hidden,
+}
+
+impl Holder {
+    /// Whether the port can't be used by this OS user's server
+    pub fn is_foreign(&self) -> bool {
+        matches!(self, Holder::OtherUser(_) | Holder::Hidden)
+    }
+
+    /// "another user's Moneywright (alice)" style description for messages
+    pub fn describe(&self, port: u16) -> String {
+        match self {
+            Holder::Free => format!("Port {} is free", port),
+            Holder::CurrentUser => format!("Port {} is in use by a Moneywright server of this user", port),
+            Holder::OtherProgram { pid, name } => {
+                format!("Port {} is in use by {} (process {}), which isn't a Moneywright server", port, name, pid)
+            }
+            Holder::OtherUser(Some(name)) => format!("Port {} is in use by another user ({})", port, name),
+            Holder::OtherUser(None) => format!("Port {} is in use by another user", port),
+            Holder::Hidden => format!("Port {} is in use by another user or a system service", port),
+        }
+    }
+}
+
+/// Explanation for the logs and diagnostics, None when the tool works
+pub fn describe(inspection: Inspection) -> Option<String> {
+    match inspection {
+        Inspection::Tool => None,
+        Inspection::ProcessScan => Some(format!(
+            "`{}` is not available; leftover servers are found by scanning processes instead",
+            TOOL
+        )),
+        Inspection::Restricted => Some(format!(
+            "`{}` is not available and processes can't be inspected; a server left running by a crash \
+             can't be stopped automatically. If the port stays busy, end the \"{}\" process manually",
+            TOOL, SIDECAR_NAME
+        )),
+    }
+}
+
+/// The warning to show once at the first server start, if any
+pub fn startup_warning() -> Option<String> {
+    let message = describe(inspection())?;
+    (!WARNED.swap(true, Ordering::Relaxed)).then_some(message)
+}
+
+/// Whether the OS tool can be started at all (its exit status varies by flags and version)
+fn tool_runs() -> bool {
+    let arg = match TOOL {
+        "ss" => "-V",
+        "netstat" => "-?",
+        _ => "-v",
+    };
+    Command::new(TOOL).arg(arg).output().is_ok()
+}
+
+/// Whether process environments can be read, checked on our own process
+fn can_scan_processes() -> bool {
+    if !sysinfo::IS_SUPPORTED_SYSTEM {
+        return false;
+    }
+    let own = Pid::from_u32(std::process::id());
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[own]),
+        true,
+        ProcessRefreshKind::nothing().with_environ(UpdateKind::Always),
+    );
+    system.process(own).is_some_and(|p| !p.environ().is_empty())
+}
+
+/// PIDs the OS tool reports as listening on the port (listeners only, not clients)
+fn pids_from_tool(port: u16) -> Result<Vec<u32>, String> {
+    #[cfg(target_os = "windows")]
+    let output = Command::new("netstat").args(["-ano", "-p", "TCP"]).output();
+    #[cfg(target_os = "linux")]
+    let output = Command::new("ss").args(["-tlnp", &format!("sport = :{}", port)]).output();
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    let output = Command::new("lsof").args(["-ti", &format!("tcp:{}", port), "-sTCP:LISTEN"]).output();
+
+    let output = output.map_err(|e| format!("Failed to run {}: {}", TOOL, e))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    #[cfg(target_os = "windows")]
+    let pids = {
+        // "  TCP    127.0.0.1:17777    0.0.0.0:0    LISTENING    1234"
+        let suffix = format!(":{}", port);
+        stdout
+            .lines()
+            .map(|line| line.split_whitespace().collect::<Vec<_>>())
+            .filter(|parts| parts.len() == 5 && parts[1].ends_with(&suffix) && parts[3] == "LISTENING")
+            .filter_map(|parts| parts[4].parse().ok())
+            .collect()
+    };
+    #[cfg(target_os = "linux")]
+    let pids = {
+        // "... users:(("moneywright",pid=1234,fd=20))"
+        stdout
+            .lines()
+            .filter_map(|line| {
+                let rest = &line[line.find("pid=")? + 4..];
+                let end = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+                rest[..end].parse().ok()
+            })
+            .collect()
+    };
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    let pids = {
+        stdout.lines().filter_map(|line| line.trim().parse().ok()).collect()
+    };
+
+    Ok(pids)
+}
+
+/// Moneywright sidecars started for the port, found by their environment
+fn pids_from_scan(port: u16) -> Vec<u32> {
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::All,
+        true,
+        ProcessRefreshKind::nothing().with_environ(UpdateKind::Always),
+    );
+    let env = format!("PORT={}", port);
+    let own = std::process::id();
+    system
+        .processes()
+        .iter()
+        .filter(|(pid, process)| {
+            pid.as_u32() != own
+                && Path::new(process.name()).file_stem() == Some(OsStr::new(SIDECAR_NAME))
+                && process.environ().iter().any(|e| e == env.as_str())
+        })
+        .map(|(pid, _)| pid.as_u32())
+        .collect()
+}
+
+/// PIDs listening on the port, as far as this machine lets us see
+fn listening_pids(port: u16) -> Result<Vec<u32>, String> {
+    Ok(match inspection() {
+        Inspection::Tool => pids_from_tool(port)?,
+        Inspection::ProcessScan => pids_from_scan(port),
+        Inspection::Restricted => Vec::new(),
+    })
+}
+
+/// Load the owners and executables of the given processes (and our own)
+fn processes_with_users(pids: &[Pid]) -> (System, Option<Uid>) {
+    let own = Pid::from_u32(std::process::id());
+    let mut wanted = pids.to_vec();
+    wanted.push(own);
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&wanted),
+        true,
+        ProcessRefreshKind::nothing()
+            .with_user(UpdateKind::Always)
+            .with_exe(UpdateKind::OnlyIfNotSet),
+    );
+    let own_uid = system.process(own).and_then(|p| p.user_id()).cloned();
+    (system, own_uid)
+}
+
+/// Find out whether a port is free, ours, or another OS user's
+pub fn holder(port: u16) -> Holder {
+    if TcpListener::bind(("127.0.0.1", port)).is_ok() {
+        return Holder::Free;
+    }
+    let pids: Vec<Pid> = listening_pids(port)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|pid| *pid > 0)
+        .map(Pid::from_u32)
+        .collect();
+    if pids.is_empty() {
+        // Busy but not one of our listeners, either way this user can't take it over
+        return Holder::Hidden;
+    }
+
+    let (system, own_uid) = processes_with_users(&pids);
+    let other = pids
+        .iter()
+        .filter_map(|pid| system.process(*pid)?.user_id())
+        .find(|uid| Some(*uid) != own_uid.as_ref());
+    if let Some(uid) = other {
+        let users = Users::new_with_refreshed_list();
+        return Holder::OtherUser(users.get_user_by_id(uid).map(|user| user.name().to_string()));
+    }
+    let program = pids
+        .iter()
+        .filter_map(|pid| system.process(*pid).map(|process| (pid, process)))
+        .find(|(_, process)| !is_sidecar(process));
+    match program {
+        Some((pid, process)) => Holder::OtherProgram {
+            pid: pid.as_u32(),
+            name: process.name().to_string_lossy().to_string(),
+        },
+        None => Holder::CurrentUser,
+    }
+}
+
+/// A Moneywright server, bundled (`moneywright`) or from a dev build (`moneywright-<target>`)
+fn is_sidecar(process: &Process) -> bool {
+    let stem = match process.exe() {
+        Some(exe) => exe.file_stem(),
+        None => Path::new(process.name()).file_stem(),
+    };
+    let name = stem.map(|stem| stem.to_string_lossy()).unwrap_or_default();
+    name == SIDECAR_NAME || name.starts_with(&format!("{}-", SIDECAR_NAME))
+}
+
+/// The processes started by `pid`, and the ones they started, as far as they still run
+pub fn descendants(pid: u32) -> Vec<u32> {
+    let mut system = System::new();
+    system.refresh_processes_specifics(ProcessesToUpdate::All, true, ProcessRefreshKind::nothing());
+    let mut tree = vec![Pid::from_u32(pid)];
+    let mut i = 0;
+    while i < tree.len() {
+        let parent = tree[i];
+        tree.extend(system.processes().iter().filter(|(_, p)| p.parent() == Some(parent)).map(|(pid, _)| *pid));
+        i += 1;
+    }
+    tree.into_iter().skip(1).map(|pid| pid.as_u32()).collect()
+}
+
+/// Send SIGTERM, false where the platform has no such signal or it couldn't be sent
+pub fn terminate(pid: u32) -> bool {
+    let pid = Pid::from_u32(pid);
+    let mut system = System::new();
+    system.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+    system.process(pid).and_then(|p| p.kill_with(Signal::Term)) == Some(true)
+}
+
+/// Wait up to `grace` for the processes to exit, returning the ones still running
+pub fn wait_for_exit(pids: &[u32], grace: Duration) -> Vec<u32> {
+    let pids: Vec<Pid> = pids.iter().map(|pid| Pid::from_u32(*pid)).collect();
+    let deadline = std::time::Instant::now() + grace;
+    let mut system = System::new();
+    loop {
+        system.refresh_processes(ProcessesToUpdate::Some(&pids), true);
+        let running: Vec<u32> = pids
+            .iter()
+            .filter(|pid| {
+                system
+                    .process(**pid)
+                    .is_some_and(|p| !matches!(p.status(), ProcessStatus::Zombie | ProcessStatus::Dead))
+            })
+            .map(|pid| pid.as_u32())
+            .collect();
+        if running.is_empty() || std::time::Instant::now() >= deadline {
+            return running;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+}
+
+/// Stop processes this app started: SIGTERM, then a kill for whatever is left after `grace`
+pub fn stop_pids(pids: &[u32], grace: Duration) {
+    let asked: Vec<u32> = pids.iter().copied().filter(|pid| terminate(*pid)).collect();
+    let mut remaining: Vec<u32> = pids.iter().copied().filter(|pid| !asked.contains(pid)).collect();
+    remaining.extend(wait_for_exit(&asked, grace));
+    let targets: Vec<Pid> = remaining.iter().map(|pid| Pid::from_u32(*pid)).collect();
+    let mut system = System::new();
+    system.refresh_processes(ProcessesToUpdate::Some(&targets), true);
+    for pid in targets {
+        if system.process(pid).is_some_and(|p| !p.kill()) {
+            eprintln!("Warning: Failed to stop process {}", pid);
+        }
+    }
+}
+
+/// Stop the Moneywright servers listening on the server port, so orphaned servers from
+/// previous runs don't hold it: SIGTERM, then a kill for whatever is left after `grace`
+/// Other programs on the port are left alone, see `stop_program`
+pub fn stop_process_on_port(port: u16, grace: Duration) -> Result<(), String> {
+    stop_listeners(port, grace, None)
+}
+
+/// Stop another program holding the port, once the user agreed to it; `pid` must still be
+/// the one listening, so a program that started in the meantime isn't stopped instead
+pub fn stop_program(port: u16, pid: u32, grace: Duration) -> Result<(), String> {
+    if !listening_pids(port)?.contains(&pid) {
+        return Err(format!("Process {} is no longer using port {}", pid, port));
+    }
+    stop_listeners(port, grace, Some(pid))
+}
+
+/// Stop the sidecars listening on the port, or only `program` (whatever it is)
+fn stop_listeners(port: u16, grace: Duration, program: Option<u32>) -> Result<(), String> {
+    let pids = listening_pids(port)?;
+    if pids.is_empty() {
+        return Ok(());
+    }
+
+    let pids: Vec<Pid> = pids
+        .into_iter()
+        .filter(|pid| *pid > 0 && program.is_none_or(|program| program == *pid))
+        .map(Pid::from_u32)
+        .collect();
+    let (system, own_uid) = processes_with_users(&pids);
+    let mut asked = Vec::new();
+    let mut remaining = Vec::new();
+    for pid in &pids {
+        if let Some(process) = system.process(*pid) {
+            // Never another OS user's server on a shared computer
+            if process.user_id().is_some_and(|uid| Some(uid) != own_uid.as_ref()) {
+                println!("Leaving process {} on port {} alone: it belongs to another user", pid, port);
+                continue;
+            }
+            if program.is_none() && !is_sidecar(process) {
+                println!("Leaving process {} ({}) on port {} alone: it isn't a Moneywright server", pid, process.name().to_string_lossy(), port);
+                continue;
+            }
+            if process.kill_with(Signal::Term) == Some(true) {
+                println!("Stopping server process {} on port {}", pid, port);
+                asked.push(pid.as_u32());
+            } else {
+                remaining.push(pid.as_u32());
+            }
+        }
+    }
+    if !asked.is_empty() {
+        let running = wait_for_exit(&asked, grace);
+        for pid in asked.iter().filter(|pid| !running.contains(pid)) {
+            record(&system, Action::Terminated, *pid, port);
+        }
+        remaining.extend(running);
+    }
+    for pid in remaining {
+        println!("Killing server process {} on port {}", pid, port);
+        if system.process(Pid::from_u32(pid)).is_some_and(|p| !p.kill()) {
+            eprintln!("Warning: Failed to stop process {} on port {}", pid, port);
+            record(&system, Action::KillFailed, pid, port);
+        } else {
+            record(&system, Action::Killed, pid, port);
+        }
+    }
+    // Give the OS a moment to release the port
+    std::thread::sleep(Duration::from_millis(500));
+    Ok(())
+}
+
+/// Record a stopped process with what was loaded about it
+fn record(system: &System, action: Action, pid: u32, port: u16) {
+    let process = system.process(Pid::from_u32(pid));
+    let name = process.map(|p| p.name().to_string_lossy().to_string());
+    let exe = process.and_then(|p| p.exe()).map(|exe| exe.to_string_lossy().to_string());
+    cleanups::record(Cleanup::process(action, pid, name, exe, port));
+}
@@ -0,0 +1,358 @@
+// Embedded PostgreSQL: a private server in the data dir, run by the app
+//
+// Choosing "PostgreSQL (managed by Moneywright)" in the Database window sets one up
+// without a database server installed: binaries bundled with the app under
+// `postgres/<version>/` in the resources, or else downloaded once from the
+// postgresql-binaries releases (checked against the published SHA-256) into
+// `<data dir>/postgres/<version>/`. `initdb` creates a cluster in
+// `<data dir>/postgres/data` that only listens on 127.0.0.1, with a random password
+// kept in the keychain, and DATABASE_URL points the server at it.
+//
+// The cluster is started before the main server when DATABASE_URL still points at it
+// and stopped when the app exits; one left running by a crash is simply used again.
+// It stays on the version it was created with (`embedded.json`), since a newer major
+// version can't open its files.
+
+use std::fs;
+use std::net::TcpListener;
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Output};
+use std::time::Duration;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Manager};
+use crate::keychain;
+use crate::logs::{log_line, SharedLogStore};
+use crate::netusage::{self, Subsystem};
+use crate::server::read_database_url;
+
+/// Version set up for new clusters
+const PG_VERSION: &str = "17.5.0";
+const BINARIES_URL: &str = "https://github.com/theseus-rs/postgresql-binaries/releases/download";
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(600);
+const POSTGRES_DIR: &str = "postgres";
+const CLUSTER_DIR: &str = "data";
+const STATE_FILE: &str = "embedded.json";
+const LOG_FILE: &str = "postgres.log";
+/// Seconds `pg_ctl` waits for the server to start or stop
+const PG_CTL_WAIT: &str = "60";
+const USER: &str = "moneywright";
+const DATABASE: &str = "moneywright";
+/// Keychain entry holding the cluster's password, kept apart from the DATABASE_URL one
+/// so switching to another database doesn't lose it
+pub const PASSWORD_KEY: &str = "embedded-postgres-password";
+
+#[derive(Serialize, Deserialize)]
+struct State {
+    /// Binaries the cluster was created with
+    version: String,
+    port: u16,
+}
+
+fn root(data_dir: &Path) -> PathBuf {
+    data_dir.join(POSTGRES_DIR)
+}
+
+fn cluster_dir(data_dir: &Path) -> PathBuf {
+    root(data_dir).join(CLUSTER_DIR)
+}
+
+fn read_state(data_dir: &Path) -> Option<State> {
+    let content = fs::read_to_string(root(data_dir).join(STATE_FILE)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn write_state(data_dir: &Path, state: &State) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(state).map_err(|e| e.to_string())?;
+    fs::write(root(data_dir).join(STATE_FILE), content).map_err(|e| format!("Failed to save {}: {}", STATE_FILE, e))
+}
+
+/// DATABASE_URL of the embedded cluster (the password comes from the keychain)
+fn database_url(port: u16) -> String {
+    format!("postgresql://{}@127.0.0.1:{}/{}?sslmode=disable", USER, port, DATABASE)
+}
+
+/// Whether DATABASE_URL points at the embedded cluster
+pub fn is_active(data_dir: &Path) -> bool {
+    read_state(data_dir).is_some_and(|state| read_database_url(data_dir).as_deref() == Some(database_url(state.port).as_str()))
+}
+
+/// Release name of the binaries for this platform
+fn target() -> Option<&'static str> {
+    match (std::env::consts::OS, std::env::consts::ARCH) {
+        ("macos", "aarch64") => Some("aarch64-apple-darwin"),
+        ("macos", "x86_64") => Some("x86_64-apple-darwin"),
+        ("linux", "aarch64") => Some("aarch64-unknown-linux-gnu"),
+        ("linux", "x86_64") => Some("x86_64-unknown-linux-gnu"),
+        ("windows", "x86_64") => Some("x86_64-pc-windows-msvc"),
+        _ => None,
+    }
+}
+
+fn tool(bin_dir: &Path, name: &str) -> PathBuf {
+    bin_dir.join(format!("{}{}", name, std::env::consts::EXE_SUFFIX))
+}
+
+/// Binaries of a version, bundled ones first
+fn bin_dir(app: &AppHandle, data_dir: &Path, version: &str) -> Option<PathBuf> {
+    let bundled = app.path().resource_dir().ok().map(|dir| dir.join(POSTGRES_DIR).join(version).join("bin"));
+    bundled
+        .into_iter()
+        .chain([root(data_dir).join(version).join("bin")])
+        .find(|dir| tool(dir, "pg_ctl").is_file())
+}
+
+/// Unpack a release archive into `dest`, dropping its top-level folder
+fn extract(archive: &[u8], dest: &Path) -> Result<(), String> {
+    let mut tar = tar::Archive::new(flate2::read::GzDecoder::new(archive));
+    for entry in tar.entries().map_err(|e| e.to_string())? {
+        let mut entry = entry.map_err(|e| e.to_string())?;
+        let path = entry.path().map_err(|e| e.to_string())?.into_owned();
+        // Nothing may land outside `dest`
+        if path.components().any(|c| !matches!(c, Component::Normal(_))) {
+            return Err(format!("Unexpected path in the archive: {}", path.display()));
+        }
+        let relative: PathBuf = path.components().skip(1).collect();
+        if relative.as_os_str().is_empty() {
+            continue;
+        }
+        let target = dest.join(relative);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        entry.unpack(&target).map_err(|e| format!("Failed to extract {}: {}", target.display(), e))?;
+    }
+    Ok(())
+}
+
+async fn fetch(client: &reqwest::Client, url: &str, file: &str) -> Result<Vec<u8>, String> {
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to download {}: {}", file, e))?;
+    if !response.status().is_success() {
+        return Err(format!("Failed to download {}: {}", file, response.status()));
+    }
+    let body = response.bytes().await.map_err(|e| format!("Failed to download {}: {}", file, e))?;
+    netusage::record(Subsystem::Postgres, 0, body.len() as u64);
+    Ok(body.to_vec())
+}
+
+/// Download and unpack the binaries of PG_VERSION, returning their bin dir
+async fn download(data_dir: &Path) -> Result<PathBuf, String> {
+    let target = target().ok_or("No PostgreSQL binaries are published for this platform")?;
+    let file = format!("postgresql-{}-{}.tar.gz", PG_VERSION, target);
+    let url = format!("{}/{}/{}", BINARIES_URL, PG_VERSION, file);
+    let client = reqwest::Client::builder()
+        .timeout(DOWNLOAD_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let checksum = fetch(&client, &format!("{}.sha256", url), &format!("{}.sha256", file)).await?;
+    let expected = String::from_utf8_lossy(&checksum)
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    let archive = fetch(&client, &url, &file).await?;
+    if hex::encode(Sha256::digest(&archive)) != expected {
+        return Err(format!("{} doesn't match its published checksum", file));
+    }
+
+    let version_dir = root(data_dir).join(PG_VERSION);
+    tauri::async_runtime::spawn_blocking(move || {
+        let partial = version_dir.with_file_name(format!("{}.partial", PG_VERSION));
+        let _ = fs::remove_dir_all(&partial);
+        extract(&archive, &partial)?;
+        let _ = fs::remove_dir_all(&version_dir);
+        fs::rename(&partial, &version_dir).map_err(|e| format!("Failed to save {}: {}", version_dir.display(), e))?;
+        Ok(version_dir.join("bin"))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+fn run(command: &mut Command) -> Result<Output, String> {
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        // No console window flashing up
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    command.output().map_err(|e| format!("Failed to run {}: {}", command.get_program().to_string_lossy(), e))
+}
+
+/// Error text of a failed tool run
+fn failure(output: &Output) -> String {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let text = if stderr.trim().is_empty() { stdout } else { stderr };
+    text.trim().lines().last().unwrap_or("unknown error").to_string()
+}
+
+fn is_running(bin_dir: &Path, cluster: &Path) -> bool {
+    run(Command::new(tool(bin_dir, "pg_ctl")).arg("status").arg("-D").arg(cluster))
+        .is_ok_and(|output| output.status.success())
+}
+
+fn start(bin_dir: &Path, data_dir: &Path, port: u16) -> Result<(), String> {
+    let cluster = cluster_dir(data_dir);
+    if is_running(bin_dir, &cluster) {
+        return Ok(());
+    }
+    let output = run(Command::new(tool(bin_dir, "pg_ctl"))
+        .arg("start")
+        .arg("-D")
+        .arg(&cluster)
+        .arg("-l")
+        .arg(root(data_dir).join(LOG_FILE))
+        .args(["-w", "-t", PG_CTL_WAIT, "-o", &format!("-p {}", port)]))?;
+    if !output.status.success() {
+        return Err(format!(
+            "PostgreSQL failed to start: {} (see {})",
+            failure(&output),
+            root(data_dir).join(LOG_FILE).display()
+        ));
+    }
+    Ok(())
+}
+
+fn random_password() -> Result<String, String> {
+    let mut password = [0u8; 24];
+    SecureRandom::fill(&SystemRandom::new(), &mut password).map_err(|_| "No secure random numbers available".to_string())?;
+    Ok(hex::encode(password))
+}
+
+fn free_port() -> Result<u16, String> {
+    TcpListener::bind(("127.0.0.1", 0))
+        .and_then(|listener| listener.local_addr())
+        .map(|addr| addr.port())
+        .map_err(|e| format!("No free port for PostgreSQL: {}", e))
+}
+
+/// Create the cluster, only reachable from this computer over TCP
+fn init_cluster(bin_dir: &Path, data_dir: &Path, password: &str) -> Result<(), String> {
+    let cluster = cluster_dir(data_dir);
+    let _ = fs::remove_dir_all(&cluster);
+    let pwfile = root(data_dir).join("pwfile");
+    fs::write(&pwfile, password).map_err(|e| format!("Failed to write {}: {}", pwfile.display(), e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = fs::set_permissions(&pwfile, fs::Permissions::from_mode(0o600));
+    }
+    let output = run(Command::new(tool(bin_dir, "initdb"))
+        .arg("-D")
+        .arg(&cluster)
+        .args(["-U", USER, "-A", "scram-sha-256", "-E", "UTF8", "--no-locale"])
+        .arg(format!("--pwfile={}", pwfile.display())));
+    let _ = fs::remove_file(&pwfile);
+    let output = output?;
+    if !output.status.success() {
+        return Err(format!("Failed to create the PostgreSQL cluster: {}", failure(&output)));
+    }
+
+    let conf = cluster.join("postgresql.conf");
+    let mut content = fs::read_to_string(&conf).map_err(|e| format!("Failed to read {}: {}", conf.display(), e))?;
+    content.push_str("\n# Managed by Moneywright\nlisten_addresses = '127.0.0.1'\nunix_socket_directories = ''\n");
+    fs::write(&conf, content).map_err(|e| format!("Failed to write {}: {}", conf.display(), e))
+}
+
+fn create_database(bin_dir: &Path, port: u16, password: &str) -> Result<(), String> {
+    let output = run(Command::new(tool(bin_dir, "createdb"))
+        .args(["-h", "127.0.0.1", "-p", &port.to_string(), "-U", USER, DATABASE])
+        .env("PGPASSWORD", password))?;
+    if !output.status.success() && !failure(&output).contains("already exists") {
+        return Err(format!("Failed to create the {} database: {}", DATABASE, failure(&output)));
+    }
+    Ok(())
+}
+
+/// Set up the embedded cluster if there isn't one yet and start it, returning the
+/// DATABASE_URL (without password) and the password
+pub async fn prepare(app: &AppHandle, log_store: &SharedLogStore, data_dir: &Path) -> Result<(String, String), String> {
+    fs::create_dir_all(root(data_dir)).map_err(|e| format!("Failed to create {}: {}", root(data_dir).display(), e))?;
+    let existing = read_state(data_dir).filter(|_| cluster_dir(data_dir).join("PG_VERSION").is_file());
+    let version = existing.as_ref().map_or(PG_VERSION, |state| state.version.as_str()).to_string();
+
+    let bin = match bin_dir(app, data_dir, &version) {
+        Some(bin) => bin,
+        None if version == PG_VERSION => {
+            log_line(app, log_store, format!("Downloading PostgreSQL {}", PG_VERSION), "info").await;
+            download(data_dir).await?
+        }
+        None => return Err(format!("The PostgreSQL {} binaries of the embedded database are missing", version)),
+    };
+
+    let (port, password) = match existing {
+        Some(state) => {
+            let password = keychain::get_secret(PASSWORD_KEY)?
+                .ok_or("The embedded PostgreSQL password is missing from the keychain")?;
+            (state.port, password)
+        }
+        None => {
+            log_line(app, log_store, "Creating the embedded PostgreSQL database", "info").await;
+            let password = random_password()?;
+            keychain::set_secret(PASSWORD_KEY, &password)?;
+            let port = free_port()?;
+            let (dir, init_bin, init_password) = (data_dir.to_path_buf(), bin.clone(), password.clone());
+            tauri::async_runtime::spawn_blocking(move || init_cluster(&init_bin, &dir, &init_password))
+                .await
+                .map_err(|e| e.to_string())??;
+            write_state(data_dir, &State { version, port })?;
+            (port, password)
+        }
+    };
+
+    let (dir, db_password) = (data_dir.to_path_buf(), password.clone());
+    tauri::async_runtime::spawn_blocking(move || {
+        start(&bin, &dir, port)?;
+        create_database(&bin, port, &db_password)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    log_line(app, log_store, format!("Embedded PostgreSQL running on port {}", port), "info").await;
+    Ok((database_url(port), password))
+}
+
+/// Start the embedded cluster before the server when DATABASE_URL points at it
+pub async fn ensure_running(app: &AppHandle, data_dir: &Path) -> Result<(), String> {
+    if !is_active(data_dir) {
+        return Ok(());
+    }
+    let state = read_state(data_dir).ok_or("The embedded PostgreSQL state is missing")?;
+    let bin = bin_dir(app, data_dir, &state.version)
+        .ok_or_else(|| format!("The PostgreSQL {} binaries of the embedded database are missing", state.version))?;
+    let dir = data_dir.to_path_buf();
+    tauri::async_runtime::spawn_blocking(move || start(&bin, &dir, state.port))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Stop the embedded cluster if it's running; called after the server is stopped
+pub fn stop(app: &AppHandle, data_dir: &Path) {
+    let Some(state) = read_state(data_dir) else {
+        return;
+    };
+    let Some(bin) = bin_dir(app, data_dir, &state.version) else {
+        return;
+    };
+    let cluster = cluster_dir(data_dir);
+    if !is_running(&bin, &cluster) {
+        return;
+    }
+    let result = run(Command::new(tool(&bin, "pg_ctl"))
+        .arg("stop")
+        .arg("-D")
+        .arg(&cluster)
+        .args(["-m", "fast", "-w", "-t", PG_CTL_WAIT]));
+    match result {
+        Ok(output) if output.status.success() => println!("Embedded PostgreSQL stopped"),
+        Ok(output) => eprintln!("Warning: Failed to stop PostgreSQL: {}", failure(&output)),
+        Err(e) => eprintln!("Warning: {}", e),
+    }
+}
+
@@ -0,0 +1,186 @@
+// Battery awareness: hold back heavy background work while running on battery
+//
+// Automatic backups, the nightly maintenance restart, scheduled exports and background
+// update downloads ask `deferral_reason` (through the scheduler's `heavy_work_deferral`)
+// before starting. With `power.defer_on_battery` set they wait
+// while the machine is on battery below `power.battery_threshold` percent. "Run
+// Anyway" lifts that until the machine is next plugged in. Machines without a
+// battery, or where the state can't be read, are treated as plugged in.
+//
+// Reading the state starts `pmset` or PowerShell on macOS and Windows, so it never
+// happens on a caller's thread: checks answer from a short-lived cache and refresh it
+// on a blocking thread when it's stale. The scheduler fills it before its first check.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use crate::logs::{log_line, SharedLogStore};
+use crate::settings::{PowerSettings, SharedSettings};
+
+/// Battery state is re-read at most this often
+const CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// Set by "Run Anyway", cleared once on mains power again
+static OVERRIDE: AtomicBool = AtomicBool::new(false);
+static CACHE: Mutex<Option<(Instant, Option<PowerStatus>)>> = Mutex::new(None);
+/// A refresh is running on a blocking thread
+static REFRESHING: AtomicBool = AtomicBool::new(false);
+
+#[derive(Clone, Copy, Serialize)]
+pub struct PowerStatus {
+    pub on_battery: bool,
+    /// Charge in percent, if reported
+    pub percent: Option<u8>,
+}
+
+#[derive(Serialize)]
+pub struct PowerInfo {
+    /// None when there is no battery or its state can't be read
+    pub status: Option<PowerStatus>,
+    /// Why heavy tasks are waiting right now, if they are
+    pub deferring: Option<String>,
+    pub overridden: bool,
+}
+
+#[cfg(target_os = "linux")]
+fn read_status() -> Option<PowerStatus> {
+    use std::fs;
+
+    let read = |path: std::path::PathBuf| fs::read_to_string(path).ok().map(|s| s.trim().to_string());
+    let mut mains_online = None;
+    let mut discharging = false;
+    let mut percents = Vec::new();
+
+    for entry in fs::read_dir("/sys/class/power_supply").ok()?.flatten() {
+        let path = entry.path();
+        match read(path.join("type")).as_deref() {
+            Some("Mains") | Some("USB") => {
+                let online = read(path.join("online")).as_deref() == Some("1");
+                mains_online = Some(mains_online.unwrap_or(false) || online);
+            }
+            // Peripheral batteries (mice, headsets) report scope "Device"
+            Some("Battery") if read(path.join("scope")).as_deref() != Some("Device") => {
+                discharging |= read(path.join("status")).as_deref() == Some("Discharging");
+                if let Some(percent) = read(path.join("capacity")).and_then(|c| c.parse::<u8>().ok()) {
+                    percents.push(percent);
+                }
+            }
+            _ => {}
+        }
+    }
+    if percents.is_empty() && !discharging {
+        return None;
+    }
+    Some(PowerStatus {
+        on_battery: mains_online.map(|online| !online).unwrap_or(discharging),
+        percent: percents.iter().min().copied(),
+    })
+}
+
+#[cfg(target_os = "macos")]
+fn read_status() -> Option<PowerStatus> {
+    // Now drawing from 'Battery Power'
+    //  -InternalBattery-0 (id=1234567)	85%; discharging; 4:12 remaining present: true
+    let output = std::process::Command::new("pmset").args(["-g", "batt"]).output().ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let battery = text.lines().find(|line| line.contains("InternalBattery"))?;
+    let percent = battery
+        .split_whitespace()
+        .find_map(|word| word.trim_end_matches(';').strip_suffix('%'))
+        .and_then(|p| p.parse::<u8>().ok());
+    Some(PowerStatus {
+        on_battery: text.contains("'Battery Power'"),
+        percent,
+    })
+}
+
+#[cfg(target_os = "windows")]
+fn read_status() -> Option<PowerStatus> {
+    use std::os::windows::process::CommandExt;
+    // No console window flashing up on every check
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    let output = std::process::Command::new("powershell")
+        .args([
+            "-NoProfile",
+            "-Command",
+            "Get-CimInstance Win32_Battery | Select-Object -First 1 | ForEach-Object { \"$($_.BatteryStatus) $($_.EstimatedChargeRemaining)\" }",
+        ])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let mut parts = text.split_whitespace();
+    // BatteryStatus 1 is "discharging"; everything else means external power
+    let battery_status = parts.next()?.parse::<u32>().ok()?;
+    Some(PowerStatus {
+        on_battery: battery_status == 1,
+        percent: parts.next().and_then(|p| p.parse::<u8>().ok()),
+    })
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn read_status() -> Option<PowerStatus> {
+    None
+}
+
+/// Read the battery state now and cache it
+pub async fn refresh() -> Option<PowerStatus> {
+    let status = tauri::async_runtime::spawn_blocking(read_status).await.ok().flatten();
+    *CACHE.lock().unwrap_or_else(|e| e.into_inner()) = Some((Instant::now(), status));
+    if !status.is_some_and(|s| s.on_battery) {
+        OVERRIDE.store(false, Ordering::SeqCst);
+    }
+    status
+}
+
+/// Last known battery state; a stale one is refreshed in the background
+pub fn power_status() -> Option<PowerStatus> {
+    let cached = *CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    let fresh = cached.is_some_and(|(read_at, _)| read_at.elapsed() < CACHE_TTL);
+    if !fresh && !REFRESHING.swap(true, Ordering::SeqCst) {
+        tauri::async_runtime::spawn(async {
+            refresh().await;
+            REFRESHING.store(false, Ordering::SeqCst);
+        });
+    }
+    cached.and_then(|(_, status)| status)
+}
+
+/// Why heavy work should wait right now, e.g. "on battery at 35%"
+pub fn deferral_reason(settings: &PowerSettings) -> Option<String> {
+    if !settings.defer_on_battery {
+        return None;
+    }
+    let status = power_status()?;
+    if !status.on_battery || OVERRIDE.load(Ordering::SeqCst) {
+        return None;
+    }
+    match status.percent {
+        Some(percent) if percent as u32 >= settings.battery_threshold => None,
+        Some(percent) => Some(format!("on battery at {}%", percent)),
+        None => Some("on battery".to_string()),
+    }
+}
+
+/// Battery state and whether heavy tasks are being held back
+#[tauri::command]
+pub async fn get_power_status(settings: tauri::State<'_, SharedSettings>) -> Result<PowerInfo, String> {
+    let power = settings.lock().await.get().power;
+    Ok(PowerInfo {
+        status: refresh().await,
+        deferring: deferral_reason(&power),
+        overridden: OVERRIDE.load(Ordering::SeqCst),
+    })
+}
+
+/// Let deferred tasks run on battery until the machine is next plugged in
+#[tauri::command]
+pub async fn run_on_battery_anyway(app: AppHandle) -> Result<(), String> {
+    OVERRIDE.store(true, Ordering::SeqCst);
+    let log_store = app.state::<SharedLogStore>().inner().clone();
+    log_line(&app, &log_store, "Running deferred tasks on battery until plugged in", "info").await;
+    Ok(())
+}
@@ -0,0 +1,717 @@
+// Profiles: separate sets of books (e.g. Personal and Business) running side by side
+//
+// The main profile is the regular data dir served on SERVER_PORT. Extra profiles are
+// listed in profiles.json in the main data dir, each with its own data folder and a
+// port allocated once and then kept. A running profile has its own sidecar (a
+// ServerManager in `ProfileServers`) and its own window, titled with the profile name;
+// the Profiles menu lists them all. The window keeps its cookies and storage in the
+// profile's data folder (see webview.rs), so signing in to one profile doesn't sign
+// out the others, and logins survive restarts.
+//
+// `switch_profile` is for working in one set of books at a time: it stops the main
+// server, points it at the chosen profile's data folder (its database and .env), starts
+// it again and reloads the main window; the Profiles > Switch To menu does the same. An
+// extra server already running that profile is stopped first, two servers never share
+// a folder. Tools that work on the books (backups, the .env, the database) follow the
+// switch; profiles.json, settings transfer and uninstall keep to the main data dir.
+// The app starts on the main profile.
+
+use std::collections::HashMap;
+use std::fs;
+use std::net::TcpListener;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State, WebviewUrl, WebviewWindowBuilder};
+use crate::logs::{log_line, SharedLogStore};
+use crate::server::{init_data_dir, start_server, server_port, stop_server, ServerManager, SharedServerManager, SERVER_PORT};
+use crate::windows::open_injected_window;
+
+const PROFILES_FILE: &str = "profiles.json";
+/// Id of the main profile in the UI and menu
+pub const MAIN_PROFILE: &str = "main";
+/// Ports handed out to extra profiles
+const PORT_RANGE: RangeInclusive<u16> = SERVER_PORT + 1..=SERVER_PORT + 100;
+/// Window labels of extra profiles start with this
+pub const WINDOW_PREFIX: &str = "profile-";
+
+static PROFILES_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Profile {
+    pub id: String,
+    pub name: String,
+    pub data_dir: String,
+    pub port: u16,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfilesFile {
+    /// Display name of the main profile
+    main_name: String,
+    profiles: Vec<Profile>,
+}
+
+impl Default for ProfilesFile {
+    fn default() -> Self {
+        Self {
+            main_name: "Personal".to_string(),
+            profiles: Vec::new(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct ProfileInfo {
+    pub id: String,
+    pub name: String,
+    pub data_dir: String,
+    pub port: u16,
+    pub main: bool,
+    /// Served by the main server and shown in the main window
+    pub active: bool,
+    pub running: bool,
+}
+
+/// Servers of running extra profiles, by profile id
+pub struct ProfileServers {
+    /// Main data dir, where profiles.json lives
+    data_dir: PathBuf,
+    servers: HashMap<String, SharedServerManager>,
+    /// Profile the main server runs
+    active: String,
+}
+
+pub type SharedProfileServers = Arc<Mutex<ProfileServers>>;
+
+pub fn create_profile_servers(data_dir: PathBuf) -> SharedProfileServers {
+    Arc::new(Mutex::new(ProfileServers {
+        data_dir,
+        servers: HashMap::new(),
+        active: MAIN_PROFILE.to_string(),
+    }))
+}
+
+fn profile_servers(app: &AppHandle) -> std::sync::MutexGuard<'_, ProfileServers> {
+    app.state::<SharedProfileServers>().inner().lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// The main profile's data dir, also after switching to another profile
+pub fn main_data_dir(app: &AppHandle) -> PathBuf {
+    profile_servers(app).data_dir.clone()
+}
+
+fn active(app: &AppHandle) -> String {
+    profile_servers(app).active.clone()
+}
+
+fn server(app: &AppHandle, id: &str) -> Option<SharedServerManager> {
+    profile_servers(app).servers.get(id).cloned()
+}
+
+fn profiles_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(PROFILES_FILE)
+}
+
+fn read_profiles(data_dir: &Path) -> ProfilesFile {
+    fs::read_to_string(profiles_path(data_dir))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn update_profiles<T>(data_dir: &Path, f: impl FnOnce(&mut ProfilesFile) -> Result<T, String>) -> Result<T, String> {
+    let _guard = PROFILES_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut file = read_profiles(data_dir);
+    let result = f(&mut file)?;
+    let json = serde_json::to_string_pretty(&file).map_err(|e| e.to_string())?;
+    fs::write(profiles_path(data_dir), json).map_err(|e| format!("Failed to save profiles: {}", e))?;
+    Ok(result)
+}
+
+/// Data dirs of extra profiles kept outside the main data dir
+pub fn external_data_dirs(data_dir: &Path) -> Vec<PathBuf> {
+    read_profiles(data_dir)
+        .profiles
+        .into_iter()
+        .map(|p| PathBuf::from(p.data_dir))
+        .filter(|dir| !dir.starts_with(data_dir))
+        .collect()
+}
+
+/// Read a transferred profile list without storing it; ids end up in window labels
+/// and menu ids, so they must look like ones made here
+pub fn check_profiles(profiles: serde_json::Value) -> Result<ProfilesFile, String> {
+    let file: ProfilesFile = serde_json::from_value(profiles).map_err(|e| format!("Invalid profile list: {}", e))?;
+    for (i, profile) in file.profiles.iter().enumerate() {
+        let valid_id = !profile.id.is_empty()
+            && profile.id != MAIN_PROFILE
+            && profile.id.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+        if !valid_id || file.profiles[..i].iter().any(|p| p.id == profile.id) {
+            return Err(format!("Invalid profile list: bad profile id \"{}\"", profile.id));
+        }
+    }
+    Ok(file)
+}
+
+/// The profile list for a settings transfer
+pub fn transfer_profiles(data_dir: &Path) -> Result<serde_json::Value, String> {
+    serde_json::to_value(read_profiles(data_dir)).map_err(|e| e.to_string())
+}
+
+/// Replace the profile list with a transferred one, returning how many profiles it has
+/// (ports are re-allocated on start if taken here)
+pub fn receive_profiles(data_dir: &Path, received: ProfilesFile) -> Result<usize, String> {
+    update_profiles(data_dir, |file| {
+        *file = received;
+        Ok(file.profiles.len())
+    })
+}
+
+fn port_is_free(port: u16) -> bool {
+    TcpListener::bind(("127.0.0.1", port)).is_ok()
+}
+
+/// First port in PORT_RANGE that no other profile uses and nothing listens on
+fn allocate_port(file: &ProfilesFile, except: Option<&str>) -> Result<u16, String> {
+    PORT_RANGE
+        .clone()
+        .find(|port| {
+            !file.profiles.iter().any(|p| p.port == *port && Some(p.id.as_str()) != except) && port_is_free(*port)
+        })
+        .ok_or_else(|| format!("No free port between {} and {}", PORT_RANGE.start(), PORT_RANGE.end()))
+}
+
+fn new_profile_id(name: &str, file: &ProfilesFile) -> String {
+    let slug: String = name
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect::<String>()
+        .split('-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    let base = if slug.is_empty() || slug == MAIN_PROFILE { "profile".to_string() } else { slug };
+    let mut id = base.clone();
+    let mut n = 2;
+    while file.profiles.iter().any(|p| p.id == id) {
+        id = format!("{}-{}", base, n);
+        n += 1;
+    }
+    id
+}
+
+fn validate_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Name is required".to_string());
+    }
+    if name.chars().count() > 40 {
+        return Err("Name must be 40 characters or less".to_string());
+    }
+    Ok(name.to_string())
+}
+
+pub fn window_label(id: &str) -> String {
+    format!("{}{}", WINDOW_PREFIX, id)
+}
+
+fn window_title(name: &str) -> String {
+    format!("Moneywright — {}", name)
+}
+
+/// Name the main window after the profile it shows once there is more than one
+pub fn update_main_title(app: &AppHandle) {
+    let file = read_profiles(&main_data_dir(app));
+    let active = active(app);
+    if let Some(window) = app.get_webview_window("main") {
+        let name = file.profiles.iter().find(|p| p.id == active).map_or(&file.main_name, |p| &p.name);
+        let title = if file.profiles.is_empty() {
+            "Moneywright".to_string()
+        } else {
+            window_title(name)
+        };
+        let _ = window.set_title(&title);
+    }
+}
+
+/// Rebuild the menu so the Profiles submenu reflects the current state
+fn profiles_changed(app: &AppHandle) {
+    update_main_title(app);
+    if let Err(e) = crate::setup_menu(app) {
+        eprintln!("Warning: Failed to update menu: {}", e);
+    }
+}
+
+/// Menu entries for the Profiles submenu: (menu id, label, running)
+pub fn menu_entries(app: &AppHandle) -> Vec<(String, String, bool)> {
+    let data_dir = main_data_dir(app);
+    let file = read_profiles(&data_dir);
+    let main_running = app
+        .state::<SharedServerManager>()
+        .try_lock()
+        .map(|m| m.is_running())
+        .unwrap_or(true);
+    let running = profile_servers(app);
+    let is_running = |id: &str| running.servers.contains_key(id) || (running.active == id && main_running);
+
+    let mut entries = vec![(format!("profile:{}", MAIN_PROFILE), file.main_name.clone(), is_running(MAIN_PROFILE))];
+    entries.extend(
+        file.profiles
+            .iter()
+            .map(|p| (format!("profile:{}", p.id), p.name.clone(), is_running(&p.id))),
+    );
+    entries
+}
+
+/// Entries for the Profiles > Switch To submenu: (menu id, label, active)
+pub fn switch_entries(app: &AppHandle) -> Vec<(String, String, bool)> {
+    let file = read_profiles(&main_data_dir(app));
+    let active = active(app);
+    std::iter::once((MAIN_PROFILE.to_string(), file.main_name))
+        .chain(file.profiles.into_iter().map(|p| (p.id, p.name)))
+        .map(|(id, name)| (format!("switch_profile:{}", id), name, id == active))
+        .collect()
+}
+
+fn open_profile_window(app: &AppHandle, profile: &Profile, url: &str) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window(&window_label(&profile.id)) {
+        let _ = window.show();
+        let _ = window.set_focus();
+        return Ok(());
+    }
+    let url = url.parse().map_err(|e| format!("Invalid profile URL: {}", e))?;
+    let builder = WebviewWindowBuilder::new(app, window_label(&profile.id), WebviewUrl::External(url));
+    let webview_dir = Path::new(&profile.data_dir).join("webview");
+    crate::webview::isolate(crate::webview::configure(builder), webview_dir, &format!("profile-{}", profile.id))
+        .title(window_title(&profile.name))
+        .inner_size(1280.0, 800.0)
+        .min_inner_size(800.0, 600.0)
+        .build()
+        .map_err(|e| format!("Failed to open window: {}", e))?;
+    Ok(())
+}
+
+/// Show the main window, which runs the active profile, returning its URL
+fn show_main(app: &AppHandle) -> String {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+    // Clicking a check item toggles it, rebuild to show the real state
+    profiles_changed(app);
+    crate::server::get_server_url()
+}
+
+/// Start a profile's server (if needed) and show its window
+pub async fn launch(app: &AppHandle, id: &str) -> Result<String, String> {
+    if id == MAIN_PROFILE && active(app) != MAIN_PROFILE {
+        return switch_to(app, MAIN_PROFILE).await;
+    }
+    if id == active(app) {
+        return Ok(show_main(app));
+    }
+
+    let data_dir = main_data_dir(app);
+    if let Some(manager) = server(app, id) {
+        let mgr = manager.lock().await;
+        if mgr.is_running() {
+            let profile = read_profiles(&data_dir).profiles.into_iter().find(|p| p.id == id);
+            if let Some(profile) = profile {
+                open_profile_window(app, &profile, &mgr.url())?;
+            }
+            profiles_changed(app);
+            return Ok(mgr.url());
+        }
+    }
+
+    // Keep the stored port unless something else took it meanwhile
+    let profile = update_profiles(&data_dir, |file| {
+        let index = file
+            .profiles
+            .iter()
+            .position(|p| p.id == id)
+            .ok_or_else(|| format!("No profile with id {}", id))?;
+        if !port_is_free(file.profiles[index].port) {
+            file.profiles[index].port = allocate_port(file, Some(id))?;
+        }
+        Ok(file.profiles[index].clone())
+    })?;
+    let profile_dir = PathBuf::from(&profile.data_dir);
+    init_data_dir(&profile_dir)?;
+
+    let manager: SharedServerManager = Arc::new(tokio::sync::Mutex::new(ServerManager::for_profile(
+        profile_dir,
+        profile.port,
+        profile.name.clone(),
+    )));
+    profile_servers(app).servers.insert(profile.id.clone(), manager.clone());
+
+    let log_store = app.state::<SharedLogStore>().inner().clone();
+    if let Err(e) = start_server(app.clone(), manager.clone(), log_store).await {
+        profile_servers(app).servers.remove(&profile.id);
+        let _ = stop_server(manager).await;
+        profiles_changed(app);
+        return Err(format!("Failed to start {}: {}", profile.name, e));
+    }
+
+    let url = manager.lock().await.url();
+    open_profile_window(app, &profile, &url)?;
+    profiles_changed(app);
+    Ok(url)
+}
+
+/// Serve a profile's books from the main server and show them in the main window
+pub async fn switch_to(app: &AppHandle, id: &str) -> Result<String, String> {
+    if id == active(app) {
+        return Ok(show_main(app));
+    }
+    if crate::remote::url().is_some() || crate::service::is_installed() {
+        return Err("The main server isn't run by the app (remote server or background service), so it can't switch profiles".to_string());
+    }
+    let main_dir = main_data_dir(app);
+    let (name, data_dir) = if id == MAIN_PROFILE {
+        (read_profiles(&main_dir).main_name, main_dir)
+    } else {
+        let profile = read_profiles(&main_dir)
+            .profiles
+            .into_iter()
+            .find(|p| p.id == id)
+            .ok_or_else(|| format!("No profile with id {}", id))?;
+        (profile.name, PathBuf::from(profile.data_dir))
+    };
+    // Two servers must not run on the same folder
+    shut_down(app, id).await?;
+    init_data_dir(&data_dir)?;
+
+    let manager = app.state::<SharedServerManager>().inner().clone();
+    let log_store = app.state::<SharedLogStore>().inner().clone();
+    stop_server(manager.clone()).await?;
+    manager.lock().await.set_data_dir(data_dir.clone());
+    profile_servers(app).active = id.to_string();
+    let msg = format!("Switching to {} ({})", name, data_dir.display());
+    log_line(app, &log_store, msg, "info").await;
+
+    // Restarting shows the splash screen and then reloads the main window
+    if let Err(e) = crate::restart_server(app.clone(), manager, log_store).await {
+        profiles_changed(app);
+        return Err(e);
+    }
+    Ok(show_main(app))
+}
+
+/// Stop a profile's server and close its window
+pub async fn shut_down(app: &AppHandle, id: &str) -> Result<(), String> {
+    let manager = profile_servers(app).servers.remove(id);
+    if let Some(window) = app.get_webview_window(&window_label(id)) {
+        let _ = window.destroy();
+    }
+    if let Some(manager) = manager {
+        stop_server(manager).await?;
+        profiles_changed(app);
+    }
+    Ok(())
+}
+
+/// Stop every extra profile's server, used on exit
+pub fn stop_all(app: &AppHandle) {
+    let managers: Vec<SharedServerManager> = profile_servers(app).servers.drain().map(|(_, m)| m).collect();
+    for manager in managers {
+        let _ = tauri::async_runtime::block_on(stop_server(manager));
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Commands
+// ---------------------------------------------------------------------------
+
+/// The main profile followed by extra profiles
+#[tauri::command]
+pub async fn list_profiles(
+    app: AppHandle,
+    manager: State<'_, SharedServerManager>,
+) -> Result<Vec<ProfileInfo>, String> {
+    let main_running = manager.lock().await.is_running();
+    let data_dir = main_data_dir(&app);
+    let active = active(&app);
+    let file = read_profiles(&data_dir);
+
+    let mut list = vec![ProfileInfo {
+        id: MAIN_PROFILE.to_string(),
+        name: file.main_name.clone(),
+        data_dir: data_dir.to_string_lossy().to_string(),
+        port: server_port(),
+        main: true,
+        active: active == MAIN_PROFILE,
+        running: active == MAIN_PROFILE && main_running,
+    }];
+    for profile in file.profiles {
+        let is_active = active == profile.id;
+        let running = match server(&app, &profile.id) {
+            Some(server) => server.lock().await.is_running(),
+            None => is_active && main_running,
+        };
+        list.push(ProfileInfo {
+            port: if is_active { server_port() } else { profile.port },
+            id: profile.id,
+            name: profile.name,
+            data_dir: profile.data_dir,
+            main: false,
+            active: is_active,
+            running,
+        });
+    }
+    Ok(list)
+}
+
+/// Add a profile; its data goes to `data_dir` or `<main data dir>/profiles/<id>`
+#[tauri::command]
+pub async fn create_profile(
+    app: AppHandle,
+    name: String,
+    data_dir: Option<String>,
+) -> Result<Profile, String> {
+    let name = validate_name(&name)?;
+    let main_dir = main_data_dir(&app);
+    let custom_dir = data_dir.map(|d| d.trim().to_string()).filter(|d| !d.is_empty());
+    if let Some(dir) = &custom_dir {
+        if !Path::new(dir).is_absolute() {
+            return Err("Data folder must be an absolute path".to_string());
+        }
+        if Path::new(dir) == main_dir {
+            return Err("That folder belongs to the main profile".to_string());
+        }
+    }
+
+    let profile = update_profiles(&main_dir, |file| {
+        let id = new_profile_id(&name, file);
+        let dir = custom_dir.unwrap_or_else(|| main_dir.join("profiles").join(&id).to_string_lossy().to_string());
+        if file.profiles.iter().any(|p| p.data_dir == dir) {
+            return Err("Another profile already uses that folder".to_string());
+        }
+        let profile = Profile {
+            port: allocate_port(file, None)?,
+            id,
+            name,
+            data_dir: dir,
+        };
+        file.profiles.push(profile.clone());
+        Ok(profile)
+    })?;
+    profiles_changed(&app);
+    Ok(profile)
+}
+
+/// Rename a profile (including the main one)
+#[tauri::command]
+pub async fn rename_profile(
+    app: AppHandle,
+    id: String,
+    name: String,
+) -> Result<(), String> {
+    let name = validate_name(&name)?;
+    let data_dir = main_data_dir(&app);
+    update_profiles(&data_dir, |file| {
+        if id == MAIN_PROFILE {
+            file.main_name = name.clone();
+        } else {
+            let profile = file
+                .profiles
+                .iter_mut()
+                .find(|p| p.id == id)
+                .ok_or_else(|| format!("No profile with id {}", id))?;
+            profile.name = name.clone();
+        }
+        Ok(())
+    })?;
+    if let Some(window) = app.get_webview_window(&window_label(&id)) {
+        let _ = window.set_title(&window_title(&name));
+    }
+    profiles_changed(&app);
+    Ok(())
+}
+
+/// Remove a stopped profile from the list; its data folder is left in place
+#[tauri::command]
+pub async fn delete_profile(
+    app: AppHandle,
+    id: String,
+) -> Result<(), String> {
+    if server(&app, &id).is_some() {
+        return Err("Stop the profile before removing it".to_string());
+    }
+    if active(&app) == id {
+        return Err("Switch to another profile before removing this one".to_string());
+    }
+    let data_dir = main_data_dir(&app);
+    update_profiles(&data_dir, |file| {
+        let before = file.profiles.len();
+        file.profiles.retain(|p| p.id != id);
+        if file.profiles.len() == before {
+            return Err(format!("No profile with id {}", id));
+        }
+        Ok(())
+    })?;
+    profiles_changed(&app);
+    Ok(())
+}
+
+/// Start a profile and open its window, returning its URL
+#[tauri::command]
+pub async fn start_profile(app: AppHandle, id: String) -> Result<String, String> {
+    launch(&app, &id).await
+}
+
+/// Restart the main server on a profile's books and reload the main window, returning
+/// the main URL
+#[tauri::command]
+pub async fn switch_profile(app: AppHandle, id: String) -> Result<String, String> {
+    switch_to(&app, &id).await
+}
+
+/// Stop an extra profile
+#[tauri::command]
+pub async fn stop_profile(app: AppHandle, id: String) -> Result<(), String> {
+    if id == MAIN_PROFILE {
+        return Err("The main profile is stopped from the main window".to_string());
+    }
+    shut_down(&app, &id).await
+}
+
+pub fn open_profiles_window(app: &AppHandle) {
+    // Static UI; names and paths are inserted with escaping on the JS side
+    let script = r#"
+        const tauriApi = window.__TAURI__;
+
+        document.documentElement.innerHTML = `
+<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <title>Profiles</title>
+    <style>
+        __BASE_STYLE__
+        #content { flex: 1; overflow-y: auto; padding: 12px 16px; }
+        table { width: 100%; border-collapse: collapse; }
+        th, td { text-align: left; padding: 6px 8px 6px 0; border-bottom: 1px solid rgba(255, 255, 255, 0.04); white-space: nowrap; }
+        th { color: #71717a; font-weight: 500; }
+        td.actions { text-align: right; }
+        td.actions button { padding: 3px 8px; font-size: 12px; }
+        form { display: flex; gap: 8px; margin-top: 16px; }
+        #profileName { width: 160px; }
+        #profileDir { flex: 1; }
+    </style>
+</head>
+<body>
+    <div class="toolbar">
+        <button id="refreshBtn">Refresh</button>
+        <span id="status" class="muted" role="status" style="margin-left: auto"></span>
+    </div>
+    <div id="content">
+        <table aria-label="Profiles">
+            <thead><tr><th>Profile</th><th>Port</th><th>Data folder</th><th>Status</th><th></th></tr></thead>
+            <tbody id="profiles"></tbody>
+        </table>
+        <form id="createForm">
+            <input id="profileName" placeholder="Name, e.g. Business" aria-label="Profile name" required>
+            <input id="profileDir" placeholder="Data folder (optional)" aria-label="Data folder">
+            <button type="submit" class="primary">Add Profile</button>
+        </form>
+        <p class="muted" style="margin-top: 12px">Each profile keeps its own books and runs its own server. Removing a profile keeps its data folder.</p>
+    </div>
+</body>
+</html>`;
+
+        const $ = id => document.getElementById(id);
+        let profiles = [];
+
+        function setStatus(text, cls) {
+            $('status').className = cls || 'muted';
+            $('status').textContent = text;
+        }
+
+        async function run(label, fn) {
+            setStatus(label + '...');
+            try {
+                const result = await fn();
+                setStatus(label + ' done', 'pass');
+                return result;
+            } catch (e) {
+                setStatus(String(e), 'fail');
+                return undefined;
+            } finally {
+                refresh();
+            }
+        }
+
+        async function refresh() {
+            profiles = await tauriApi.core.invoke('list_profiles');
+            $('profiles').innerHTML = profiles.map((p, i) =>
+                '<tr><td>' + escapeHtml(p.name) + (p.main ? ' <span class="muted">(main)</span>' : '') + (p.active ? ' <span class="pass">(active)</span>' : '') + '</td>' +
+                '<td class="mono">' + p.port + '</td>' +
+                '<td class="mono" title="' + escapeHtml(p.data_dir) + '">' + escapeHtml(p.data_dir) + '</td>' +
+                '<td class="' + (p.running ? 'pass' : 'muted') + '">' + (p.running ? 'Running' : 'Stopped') + '</td>' +
+                '<td class="actions">' +
+                    '<button data-action="open" data-index="' + i + '">' + (p.running || p.main ? 'Open' : 'Start') + '</button> ' +
+                    '<button data-action="switch" data-index="' + i + '" title="Show this profile in the main window"' + (p.active ? ' disabled' : '') + '>Switch</button> ' +
+                    (p.main ? '' : '<button data-action="stop" data-index="' + i + '"' + (p.running && !p.active ? '' : ' disabled') + '>Stop</button> ') +
+                    '<button data-action="rename" data-index="' + i + '">Rename</button>' +
+                    (p.main ? '' : ' <button data-action="delete" data-index="' + i + '" class="danger"' + (p.running || p.active ? ' disabled' : '') + '>Remove</button>') +
+                '</td></tr>'
+            ).join('');
+        }
+
+        $('profiles').onclick = async (e) => {
+            const btn = e.target.closest('button');
+            if (!btn) return;
+            const profile = profiles[Number(btn.dataset.index)];
+            switch (btn.dataset.action) {
+                case 'open':
+                    await run('Starting ' + profile.name, () => tauriApi.core.invoke('start_profile', { id: profile.id }));
+                    break;
+                case 'switch':
+                    await run('Switching to ' + profile.name, () => tauriApi.core.invoke('switch_profile', { id: profile.id }));
+                    break;
+                case 'stop':
+                    await run('Stopping ' + profile.name, () => tauriApi.core.invoke('stop_profile', { id: profile.id }));
+                    break;
+                case 'rename': {
+                    const cell = btn.closest('tr').firstChild;
+                    cell.innerHTML = '<input value="' + escapeHtml(profile.name) + '" aria-label="New name" style="width: 140px">';
+                    const input = cell.firstChild;
+                    input.focus();
+                    input.onkeydown = (ev) => {
+                        if (ev.key === 'Enter') run('Renaming', () => tauriApi.core.invoke('rename_profile', { id: profile.id, name: input.value }));
+                        if (ev.key === 'Escape') refresh();
+                    };
+                    break;
+                }
+                case 'delete':
+                    // Second click confirms
+                    if (btn.dataset.armed) {
+                        await run('Removing', () => tauriApi.core.invoke('delete_profile', { id: profile.id }));
+                    } else {
+                        btn.dataset.armed = '1';
+                        btn.textContent = 'Confirm';
+                    }
+                    break;
+            }
+        };
+        $('createForm').onsubmit = async (e) => {
+            e.preventDefault();
+            const created = await run('Adding', () => tauriApi.core.invoke('create_profile', {
+                name: $('profileName').value,
+                dataDir: $('profileDir').value || null,
+            }));
+            if (created) e.target.reset();
+        };
+        $('refreshBtn').onclick = refresh;
+        refresh();
+        setInterval(refresh, 3000);
+    "#;
+
+    open_injected_window(app, "profiles", "Profiles", (760.0, 420.0), true, script);
+}
@@ -0,0 +1,128 @@
+// Bundled pages served by the shell over the mw:// scheme
+//
+// Native windows used to load "/" from the local server before injecting their UI,
+// so they came up blank whenever the server was down. These pages are compiled into
+// the binary and need nothing but the shell:
+//
+//   /splash   startup screen shown while the server (re)starts
+//   /error    the server failed to start, with retry, diagnostics and report buttons
+//   /lock     privacy screen hiding the app until "Unlock"
+//   /window   empty page that native windows (onboarding, diagnostics, ...) build on
+//   /oauth/callback  redirect target of sign-ins, normally caught before it loads (oauth.rs)
+//
+// macOS and Linux address them as mw://localhost/<page>; Windows exposes custom
+// schemes as http://mw.localhost/<page> instead, so always go through `page_url`.
+
+use std::borrow::Cow;
+use tauri::http::{header, Request, Response, StatusCode};
+use tauri::{AppHandle, Manager, Url};
+use crate::server::get_server_url;
+use crate::transport;
+
+pub const SCHEME: &str = "mw";
+
+const SPLASH: &str = include_str!("../../ui/index.html");
+const ERROR: &str = include_str!("../../ui/error.html");
+const LOCK: &str = include_str!("../../ui/lock.html");
+const PAUSED: &str = include_str!("../../ui/paused.html");
+const LOGO: &[u8] = include_bytes!("../../ui/logo.png");
+/// Dark from the first frame so injected windows don't flash white
+const WINDOW: &str = r#"<!DOCTYPE html><html style="background:#030303"><head><meta charset="UTF-8"></head><body></body></html>"#;
+const OAUTH_CALLBACK: &str = r#"<!DOCTYPE html><html style="background:#030303;color:#a1a1aa;font-family:sans-serif"><head><meta charset="UTF-8"><title>Moneywright</title></head><body><p style="text-align:center;margin-top:40vh">Signed in. You can close this window.</p></body></html>"#;
+
+/// URL of a bundled page, e.g. `page_url("error")`
+pub fn page_url(page: &str) -> Url {
+    #[cfg(any(windows, target_os = "android"))]
+    let base = format!("http://{}.localhost/", SCHEME);
+    #[cfg(not(any(windows, target_os = "android")))]
+    let base = format!("{}://localhost/", SCHEME);
+    // Both parts are fixed, parsing can't fail
+    Url::parse(&base).and_then(|base| base.join(page)).expect("valid page URL")
+}
+
+/// Whether a URL is one of the bundled pages
+pub fn is_page(url: &Url) -> bool {
+    url.scheme() == SCHEME || (url.scheme() == "http" && url.host_str() == Some(&format!("{}.localhost", SCHEME)))
+}
+
+/// Serve a request for a bundled page or asset
+pub fn handle(request: &Request<Vec<u8>>) -> Response<Cow<'static, [u8]>> {
+    let (body, content_type): (&'static [u8], &str) = match request.uri().path() {
+        "/" | "/splash" => (SPLASH.as_bytes(), "text/html; charset=utf-8"),
+        "/error" => (ERROR.as_bytes(), "text/html; charset=utf-8"),
+        "/lock" => (LOCK.as_bytes(), "text/html; charset=utf-8"),
+        "/paused" => (PAUSED.as_bytes(), "text/html; charset=utf-8"),
+        "/window" => (WINDOW.as_bytes(), "text/html; charset=utf-8"),
+        "/oauth/callback" => (OAUTH_CALLBACK.as_bytes(), "text/html; charset=utf-8"),
+        "/logo.png" => (LOGO, "image/png"),
+        _ => {
+            return Response::builder()
+                .status(StatusCode::NOT_FOUND)
+                .body(Cow::Borrowed(&b"Not found"[..]))
+                .unwrap_or_default();
+        }
+    };
+    Response::builder()
+        .header(header::CONTENT_TYPE, content_type)
+        .body(Cow::Borrowed(body))
+        .unwrap_or_default()
+}
+
+/// Point the main window at a bundled page
+fn show_in_main(app: &AppHandle, url: Url) {
+    if let Some(window) = app.get_webview_window("main") {
+        if let Err(e) = window.navigate(url) {
+            eprintln!("Warning: Failed to show page: {}", e);
+        }
+    }
+}
+
+/// Whether a window shows a page of the local server (rather than a bundled one)
+fn on_server(url: &Url) -> bool {
+    (matches!(url.scheme(), "http" | "https") && url.host_str() == Some("localhost") && url.port().is_some())
+        || url.scheme() == transport::SCHEME
+}
+
+/// Show the splash screen in the main window while the server restarts,
+/// returning the server page it was on
+pub fn show_splash(app: &AppHandle) -> Option<Url> {
+    let previous = app
+        .get_webview_window("main")
+        .and_then(|window| window.url().ok())
+        .filter(on_server);
+    show_in_main(app, page_url("splash"));
+    previous
+}
+
+/// Leave the splash or error page for the server, returning to `previous` if given
+/// and it's still on the server (not after switching between http and https)
+pub fn show_server(app: &AppHandle, previous: Option<Url>) {
+    if let Some(url) = previous.filter(crate::server::is_server_url).or_else(|| get_server_url().parse().ok()) {
+        show_in_main(app, url);
+    }
+}
+
+/// Show the "couldn't start" page in the main window
+pub fn show_error(app: &AppHandle, message: &str) {
+    let mut url = page_url("error");
+    url.query_pairs_mut().append_pair("message", message);
+    show_in_main(app, url);
+}
+
+/// Show the "server paused" page in the main window
+pub fn show_paused(app: &AppHandle) {
+    show_in_main(app, page_url("paused"));
+}
+
+/// Cover every window showing the app with the lock screen;
+/// "Unlock" returns each to the page it was on
+pub fn lock_windows(app: &AppHandle) {
+    for window in app.webview_windows().values() {
+        let Some(current) = window.url().ok().filter(on_server) else {
+            continue;
+        };
+        let mut url = page_url("lock");
+        url.query_pairs_mut().append_pair("return", current.as_str());
+        let _ = window.navigate(url);
+    }
+}
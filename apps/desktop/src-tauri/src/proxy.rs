@@ -0,0 +1,493 @@
+// Reverse proxy assistant, for serving Moneywright from a NAS behind Caddy or nginx
+//
+// `server.external_url` is the address people use to reach the proxy; the server gets
+// it as `APP_URL`, which it uses for sign-in redirects and allowed origins. The
+// assistant probes that URL from here the way a browser would and turns common
+// misconfigurations into pass/warn/fail checks: the proxy not reaching the server,
+// redirect loops, stripped headers, rejected WebSocket upgrades and upload limits that
+// break statement imports. It also suggests a Caddyfile and an nginx block.
+
+use std::time::Duration;
+use serde::Serialize;
+use serde_json::json;
+use tauri::{AppHandle, Manager, Url};
+use crate::doctor::{CheckStatus, DoctorCheck};
+use crate::events::{self, Event};
+use crate::logs::{log_line, SharedLogStore};
+use crate::netusage::{self, Subsystem};
+use crate::server::{fetch_health, get_server_url, server_port, HealthResponse, ServerStatus, SharedServerManager};
+use crate::settings::SharedSettings;
+use crate::windows::open_injected_window;
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(15);
+/// Statement PDFs and attachments go up to about this size
+const UPLOAD_PROBE_BYTES: usize = 8 * 1024 * 1024;
+/// Headers the server sets on every response; a proxy that drops them weakens the app
+const SECURITY_HEADERS: &[&str] = &["content-security-policy", "x-content-type-options", "x-frame-options"];
+
+#[derive(Serialize)]
+pub struct ProxyReport {
+    url: String,
+    checks: Vec<DoctorCheck>,
+    /// Where the proxy should send requests
+    upstream: String,
+    caddy: String,
+    nginx: String,
+}
+
+/// Check an external URL for `server.external_url`: http(s), a host, and served from the root
+pub fn parse_external_url(url: &str) -> Result<Url, String> {
+    let parsed = Url::parse(url.trim()).map_err(|e| format!("\"{}\" isn't a URL: {}", url, e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err("The external URL must start with https:// or http://".to_string());
+    }
+    if parsed.host_str().is_none_or(str::is_empty) {
+        return Err("The external URL needs a host name".to_string());
+    }
+    if !parsed.username().is_empty() || parsed.password().is_some() {
+        return Err("The external URL must not contain a user name or password".to_string());
+    }
+    if parsed.path() != "/" || parsed.query().is_some() || parsed.fragment().is_some() {
+        return Err("Moneywright has to be served from the root of its own host name (no path)".to_string());
+    }
+    Ok(parsed)
+}
+
+/// The URL as the server expects it in `APP_URL` (no trailing slash)
+pub fn app_url(external_url: &str) -> String {
+    external_url.trim().trim_end_matches('/').to_string()
+}
+
+/// Whether the URL points at this computer
+pub fn is_loopback(url: &Url) -> bool {
+    match url.host_str() {
+        Some("localhost") => true,
+        Some(host) => host
+            .trim_matches(['[', ']'])
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback()),
+        None => false,
+    }
+}
+
+fn upstream() -> String {
+    let host = sysinfo::System::host_name().unwrap_or_else(|| "127.0.0.1".to_string());
+    format!("{}:{}", host, server_port())
+}
+
+fn caddy_config(host: &str, upstream: &str) -> String {
+    format!(
+        "{host} {{\n    request_body {{\n        max_size 100MB\n    }}\n    reverse_proxy {upstream} {{\n        # Stream AI chat replies as they arrive\n        flush_interval -1\n    }}\n}}\n"
+    )
+}
+
+fn nginx_config(host: &str, upstream: &str) -> String {
+    format!(
+        "server {{\n    listen 443 ssl;\n    server_name {host};\n    # ssl_certificate / ssl_certificate_key ...\n\n    client_max_body_size 100m;\n\n    location / {{\n        proxy_pass http://{upstream};\n        proxy_http_version 1.1;\n        proxy_set_header Host $host;\n        proxy_set_header X-Real-IP $remote_addr;\n        proxy_set_header X-Forwarded-For $proxy_add_x_forwarded_for;\n        proxy_set_header X-Forwarded-Proto $scheme;\n        proxy_set_header Upgrade $http_upgrade;\n        proxy_set_header Connection \"upgrade\";\n        # Stream AI chat replies as they arrive\n        proxy_buffering off;\n        proxy_read_timeout 300s;\n    }}\n}}\n"
+    )
+}
+
+fn describe_error(e: &reqwest::Error, host: &str) -> String {
+    let text = e.to_string();
+    let chain = format!("{:?}", e);
+    if chain.contains("certificate") || chain.contains("Certificate") {
+        format!("The TLS certificate for {} isn't trusted; check it covers this host name and hasn't expired", host)
+    } else if e.is_timeout() {
+        format!("{} didn't answer within {} seconds; check port forwarding and the firewall", host, PROBE_TIMEOUT.as_secs())
+    } else if e.is_connect() {
+        format!("Couldn't connect to {}: check DNS, port forwarding and the firewall ({})", host, text)
+    } else {
+        text
+    }
+}
+
+/// Whether the proxy reaches this Moneywright; on success the response headers come along
+async fn check_reachable(client: &reqwest::Client, base: &str, host: &str) -> (DoctorCheck, Option<reqwest::header::HeaderMap>) {
+    const ID: &str = "reachable";
+    const NAME: &str = "Reaches Moneywright";
+
+    let response = match client.get(format!("{}/health", base)).send().await {
+        Ok(response) => response,
+        Err(e) => return (DoctorCheck::new(ID, NAME, CheckStatus::Fail, describe_error(&e, host)), None),
+    };
+    let status = response.status();
+    if status.is_redirection() {
+        let location = response
+            .headers()
+            .get(reqwest::header::LOCATION)
+            .and_then(|l| l.to_str().ok())
+            .unwrap_or("")
+            .to_string();
+        let check = if base.starts_with("https://") && location.starts_with("http://") {
+            DoctorCheck::new(
+                ID,
+                NAME,
+                CheckStatus::Fail,
+                format!(
+                    "Redirects to {} — the server thinks the request came over http. Forward X-Forwarded-Proto, or check for a redirect loop between the proxy and a CDN",
+                    location
+                ),
+            )
+        } else {
+            DoctorCheck::new(ID, NAME, CheckStatus::Warn, format!("Redirects to {}; use the address it redirects to", location))
+        };
+        return (check, None);
+    }
+    if matches!(status.as_u16(), 502..=504) {
+        return (
+            DoctorCheck::new(
+                ID,
+                NAME,
+                CheckStatus::Fail,
+                format!(
+                    "The proxy answered {} — it can't reach Moneywright. Point it at http://{} and make sure the server is running",
+                    status,
+                    upstream()
+                ),
+            ),
+            None,
+        );
+    }
+    if matches!(status.as_u16(), 401 | 403 | 407) {
+        return (
+            DoctorCheck::new(
+                ID,
+                NAME,
+                CheckStatus::Warn,
+                format!("The proxy asks for a login ({}), so the checks behind it were skipped", status),
+            ),
+            None,
+        );
+    }
+    if !status.is_success() {
+        return (
+            DoctorCheck::new(
+                ID,
+                NAME,
+                CheckStatus::Fail,
+                format!("{}/health answered {}; the proxy may forward to another service or path", base, status),
+            ),
+            None,
+        );
+    }
+
+    let headers = response.headers().clone();
+    let Ok(health) = response.json::<HealthResponse>().await else {
+        let check = DoctorCheck::new(ID, NAME, CheckStatus::Fail, "Something other than Moneywright answers at this address");
+        return (check, None);
+    };
+    let remote = health.version.unwrap_or_else(|| "(unknown version)".to_string());
+    let local = fetch_health(&get_server_url()).await.and_then(|h| h.version);
+    let check = match local {
+        Some(local) if local != remote => DoctorCheck::new(
+            ID,
+            NAME,
+            CheckStatus::Warn,
+            format!("Answers with Moneywright {} but this app runs {}; the proxy may point at another install", remote, local),
+        ),
+        _ => DoctorCheck::new(ID, NAME, CheckStatus::Pass, format!("Reached Moneywright {} through the proxy", remote)),
+    };
+    (check, Some(headers))
+}
+
+fn check_headers(headers: &reqwest::header::HeaderMap) -> DoctorCheck {
+    const ID: &str = "headers";
+    const NAME: &str = "Response headers";
+
+    let missing: Vec<&str> = SECURITY_HEADERS.iter().copied().filter(|h| !headers.contains_key(*h)).collect();
+    if missing.is_empty() {
+        DoctorCheck::new(ID, NAME, CheckStatus::Pass, "Security headers from the server come through")
+    } else {
+        DoctorCheck::new(
+            ID,
+            NAME,
+            CheckStatus::Warn,
+            format!("The proxy drops {}; remove proxy_hide_header / header_down rules for them", missing.join(", ")),
+        )
+    }
+}
+
+/// The server only accepts the origin in APP_URL, so this shows whether the URL is applied
+async fn check_origin(client: &reqwest::Client, base: &str, saved: &str) -> DoctorCheck {
+    const ID: &str = "origin";
+    const NAME: &str = "External URL";
+
+    let response = client
+        .get(format!("{}/health", base))
+        .header(reqwest::header::ORIGIN, base)
+        .send()
+        .await;
+    let allowed = response.ok().and_then(|r| {
+        r.headers()
+            .get(reqwest::header::ACCESS_CONTROL_ALLOW_ORIGIN)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v == base)
+    });
+    match allowed {
+        Some(true) => DoctorCheck::new(ID, NAME, CheckStatus::Pass, format!("The server accepts {} as its address", base)),
+        _ if app_url(saved) != base => DoctorCheck::new(
+            ID,
+            NAME,
+            CheckStatus::Warn,
+            "This isn't the saved external URL yet; use it so sign-in redirects and cross-origin requests work",
+        ),
+        _ => DoctorCheck::new(
+            ID,
+            NAME,
+            CheckStatus::Warn,
+            "The server doesn't accept this address yet; restart it, and check the proxy forwards the Origin header",
+        ),
+    }
+}
+
+async fn check_websocket(client: &reqwest::Client, base: &str) -> DoctorCheck {
+    const ID: &str = "websocket";
+    const NAME: &str = "WebSocket upgrades";
+
+    let response = client
+        .get(format!("{}/health", base))
+        .header(reqwest::header::CONNECTION, "Upgrade")
+        .header(reqwest::header::UPGRADE, "websocket")
+        .header(reqwest::header::SEC_WEBSOCKET_VERSION, "13")
+        .header(reqwest::header::SEC_WEBSOCKET_KEY, "dGhlIHNhbXBsZSBub25jZQ==")
+        .send()
+        .await;
+    match response {
+        Ok(r) if r.status().is_success() || r.status() == reqwest::StatusCode::SWITCHING_PROTOCOLS => {
+            DoctorCheck::new(ID, NAME, CheckStatus::Pass, "Upgrade requests reach the server")
+        }
+        Ok(r) => DoctorCheck::new(
+            ID,
+            NAME,
+            CheckStatus::Warn,
+            format!(
+                "The proxy rejects upgrade requests ({}); for nginx add proxy_http_version 1.1 and the Upgrade/Connection headers",
+                r.status()
+            ),
+        ),
+        Err(e) => DoctorCheck::new(ID, NAME, CheckStatus::Warn, format!("Upgrade request failed: {}", e)),
+    }
+}
+
+async fn check_uploads(client: &reqwest::Client, base: &str) -> DoctorCheck {
+    const ID: &str = "uploads";
+    const NAME: &str = "Upload size";
+
+    // The server turns the POST away itself; only a proxy limit answers 413
+    netusage::record(Subsystem::ProxyChecks, UPLOAD_PROBE_BYTES as u64, 0);
+    let response = client
+        .post(format!("{}/health", base))
+        .header(reqwest::header::CONTENT_TYPE, "application/octet-stream")
+        .body(vec![0u8; UPLOAD_PROBE_BYTES])
+        .send()
+        .await;
+    let limit = format!("{} MB", UPLOAD_PROBE_BYTES / (1024 * 1024));
+    match response {
+        Ok(r) if r.status() == reqwest::StatusCode::PAYLOAD_TOO_LARGE => DoctorCheck::new(
+            ID,
+            NAME,
+            CheckStatus::Fail,
+            format!(
+                "The proxy refuses uploads of {}, so statement imports and attachments will fail; raise client_max_body_size (nginx) or request_body max_size (Caddy)",
+                limit
+            ),
+        ),
+        Ok(_) => DoctorCheck::new(ID, NAME, CheckStatus::Pass, format!("Uploads of {} get through", limit)),
+        Err(e) => DoctorCheck::new(
+            ID,
+            NAME,
+            CheckStatus::Warn,
+            format!("A {} upload was cut off ({}); the proxy may limit request sizes", limit, e),
+        ),
+    }
+}
+
+/// Probe an external URL through the reverse proxy
+#[tauri::command]
+pub async fn check_reverse_proxy(app: AppHandle, url: String) -> Result<ProxyReport, String> {
+    let parsed = parse_external_url(&url)?;
+    let base = app_url(parsed.as_str());
+    let host = parsed.host_str().unwrap_or_default().to_string();
+    let upstream = upstream();
+    let mut report = ProxyReport {
+        url: base.clone(),
+        checks: Vec::new(),
+        caddy: caddy_config(&host, &upstream),
+        nginx: nginx_config(&host, &upstream),
+        upstream,
+    };
+
+    const ID: &str = "url";
+    const NAME: &str = "Address";
+    if is_loopback(&parsed) {
+        report.checks.push(DoctorCheck::new(
+            ID,
+            NAME,
+            CheckStatus::Fail,
+            "This address is this computer; use the name other devices reach the proxy at",
+        ));
+        return Ok(report);
+    }
+    report.checks.push(if parsed.scheme() == "http" {
+        DoctorCheck::new(
+            ID,
+            NAME,
+            CheckStatus::Warn,
+            "Without https, passwords and sign-in cookies travel unencrypted; Caddy sets up a certificate on its own",
+        )
+    } else {
+        DoctorCheck::new(ID, NAME, CheckStatus::Pass, format!("{} uses https", host))
+    });
+
+    let client = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .timeout(PROBE_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let (reachable, headers) = check_reachable(&client, &base, &host).await;
+    report.checks.push(reachable);
+    // The rest only means something once the proxy reaches the server
+    let Some(headers) = headers else {
+        return Ok(report);
+    };
+    report.checks.push(check_headers(&headers));
+    let saved = app.state::<SharedSettings>().lock().await.get().server.external_url;
+    report.checks.push(check_origin(&client, &base, &saved).await);
+    report.checks.push(check_websocket(&client, &base).await);
+    report.checks.push(check_uploads(&client, &base).await);
+    Ok(report)
+}
+
+/// Save (or with None, clear) the external URL and restart the server with it
+#[tauri::command]
+pub async fn set_external_url(app: AppHandle, url: Option<String>) -> Result<(), String> {
+    let url = match url.filter(|u| !u.trim().is_empty()) {
+        Some(url) => app_url(parse_external_url(&url)?.as_str()),
+        None => String::new(),
+    };
+    let updated = app
+        .state::<SharedSettings>()
+        .lock()
+        .await
+        .update(&json!({ "server": { "external_url": url } }))?;
+    let _ = events::emit(&app, Event::SettingsChanged(&updated));
+
+    let manager = app.state::<SharedServerManager>().inner().clone();
+    let log_store = app.state::<SharedLogStore>().inner().clone();
+    let msg = match url.as_str() {
+        "" => "External URL cleared".to_string(),
+        url => format!("External URL set to {}", url),
+    };
+    log_line(&app, &log_store, msg, "info").await;
+    if matches!(manager.lock().await.status(), ServerStatus::Stopped | ServerStatus::Paused) {
+        return Ok(());
+    }
+    crate::restart_server(app.clone(), manager, log_store).await
+}
+
+/// Open the reverse proxy assistant
+pub fn open_proxy_window(app: &AppHandle) {
+    let script = r#"
+        const tauriApi = window.__TAURI__;
+
+        document.documentElement.innerHTML = `
+<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <title>Reverse Proxy Assistant</title>
+    <style>
+        __BASE_STYLE__
+        .content { flex: 1; overflow-y: auto; padding: 16px; display: flex; flex-direction: column; gap: 12px; }
+        .row { display: flex; gap: 8px; }
+        .row input { flex: 1; }
+        .check { display: flex; gap: 12px; padding: 10px 0; border-bottom: 1px solid rgba(255, 255, 255, 0.04); }
+        .badge { width: 44px; flex-shrink: 0; font-size: 11px; font-weight: 600; text-transform: uppercase; padding-top: 2px; }
+        .check .name { font-weight: 500; }
+        .check .detail { color: #a1a1aa; margin-top: 2px; }
+        pre { background: #0a0a0a; border: 1px solid rgba(255, 255, 255, 0.06); border-radius: 6px; padding: 10px; overflow-x: auto; }
+        .tabs { display: flex; gap: 8px; align-items: center; }
+        .tabs button[aria-pressed="true"] { color: #fafafa; border-color: rgba(255, 255, 255, 0.2); }
+    </style>
+</head>
+<body>
+    <div class="content">
+        <p class="muted">Enter the address people will use to reach Moneywright through your proxy (Caddy, nginx, Traefik...).</p>
+        <div class="row">
+            <input type="url" id="url" placeholder="https://money.example.com" aria-label="External URL">
+            <button id="checkBtn" class="primary">Check</button>
+            <button id="saveBtn" disabled>Use This URL</button>
+        </div>
+        <div id="status" class="muted" role="status" aria-live="polite"></div>
+        <div id="checks" role="list" aria-label="Proxy checks"></div>
+        <div id="configs" hidden>
+            <div class="tabs">
+                <span class="muted">Example configuration:</span>
+                <button id="caddyTab" aria-pressed="true">Caddy</button>
+                <button id="nginxTab" aria-pressed="false">nginx</button>
+                <button id="copyBtn">Copy</button>
+            </div>
+            <pre id="config" class="mono"></pre>
+            <p class="muted">Replace <span id="upstream" class="mono"></span> with this computer's IP address if the proxy can't resolve its name.</p>
+        </div>
+    </div>
+</body>
+</html>`;
+
+        const $ = id => document.getElementById(id);
+        let report = null;
+        let tab = 'caddy';
+
+        function showConfig() {
+            $('config').textContent = report ? report[tab] : '';
+            $('caddyTab').setAttribute('aria-pressed', String(tab === 'caddy'));
+            $('nginxTab').setAttribute('aria-pressed', String(tab === 'nginx'));
+        }
+
+        async function check() {
+            $('checkBtn').disabled = true;
+            $('saveBtn').disabled = true;
+            $('status').textContent = 'Checking...';
+            $('checks').innerHTML = '';
+            try {
+                report = await tauriApi.core.invoke('check_reverse_proxy', { url: $('url').value });
+                $('checks').innerHTML = report.checks.map(c =>
+                    '<div class="check" role="listitem"><div class="badge ' + c.status + '">' + c.status + '</div>' +
+                    '<div><div class="name">' + escapeHtml(c.name) + '</div><div class="detail">' + escapeHtml(c.detail) + '</div></div></div>'
+                ).join('');
+                const failed = report.checks.filter(c => c.status === 'fail').length;
+                $('status').textContent = failed ? failed + ' problem(s) found' : 'Done';
+                $('saveBtn').disabled = failed > 0;
+                $('upstream').textContent = report.upstream;
+                $('configs').hidden = false;
+                showConfig();
+            } catch (e) {
+                $('status').textContent = String(e);
+            }
+            $('checkBtn').disabled = false;
+        }
+
+        $('checkBtn').onclick = check;
+        $('url').onkeydown = (e) => { if (e.key === 'Enter') check(); };
+        $('saveBtn').onclick = async () => {
+            $('saveBtn').disabled = true;
+            $('status').textContent = 'Saving and restarting the server...';
+            try {
+                await tauriApi.core.invoke('set_external_url', { url: report.url });
+                $('status').textContent = 'Saved. The server now uses ' + report.url;
+            } catch (e) {
+                $('status').textContent = String(e);
+                $('saveBtn').disabled = false;
+            }
+        };
+        $('caddyTab').onclick = () => { tab = 'caddy'; showConfig(); };
+        $('nginxTab').onclick = () => { tab = 'nginx'; showConfig(); };
+        $('copyBtn').onclick = () => navigator.clipboard.writeText($('config').textContent);
+
+        tauriApi.core.invoke('get_settings').then(s => {
+            if (s.server.external_url) $('url').value = s.server.external_url;
+        }).catch(() => {});
+    "#;
+
+    open_injected_window(app, "reverse_proxy", "Reverse Proxy Assistant", (720.0, 620.0), true, script);
+}
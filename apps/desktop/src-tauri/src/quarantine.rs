@@ -0,0 +1,163 @@
+// Quarantine for files the app deletes on its own: old backups dropped by retention
+// and backups deleted in the backup browser
+//
+// A `backups.keep` set too low (or a clock that jumped ahead) used to delete the only
+// good backup for good. Now the file is moved to `quarantine/` in the data dir and
+// kept for `backups.quarantine_days` (0 purges it at the next cleanup) before it's
+// really deleted; `restore_from_quarantine` puts it back where it was. What's there is
+// listed in `quarantine/index.json`. A restored automatic backup counts toward
+// `backups.keep` again, so raise that first or the next backup moves it back. Scheduled
+// exports never delete earlier exports, so they don't come through here.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+
+const INDEX_FILE: &str = "index.json";
+
+static DIR: OnceLock<PathBuf> = OnceLock::new();
+/// Serializes changes to the index
+static INDEX: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantineEntry {
+    pub id: String,
+    /// "backup"
+    pub kind: String,
+    /// Where the file was, and goes back to on restore
+    pub original_path: PathBuf,
+    pub size: u64,
+    /// Unix seconds
+    pub quarantined_at: u64,
+}
+
+pub fn init(data_dir: &Path) {
+    let _ = DIR.set(data_dir.join("quarantine"));
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn dir() -> Result<&'static Path, String> {
+    DIR.get().map(PathBuf::as_path).ok_or_else(|| "The quarantine isn't set up".to_string())
+}
+
+fn read_index(dir: &Path) -> Vec<QuarantineEntry> {
+    fs::read_to_string(dir.join(INDEX_FILE))
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+fn write_index(dir: &Path, entries: &[QuarantineEntry]) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(entries).map_err(|e| e.to_string())?;
+    fs::write(dir.join(INDEX_FILE), json).map_err(|e| format!("Failed to write the quarantine index: {}", e))
+}
+
+/// Rename, or copy and delete when `to` is on another volume
+fn move_file(from: &Path, to: &Path) -> Result<(), String> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    fs::copy(from, to).map_err(|e| format!("Failed to move {}: {}", from.display(), e))?;
+    fs::remove_file(from).map_err(|e| {
+        let _ = fs::remove_file(to);
+        format!("Failed to move {}: {}", from.display(), e)
+    })
+}
+
+/// Move a file the app would have deleted into the quarantine
+pub fn quarantine(path: &Path, kind: &str) -> Result<(), String> {
+    let dir = dir()?;
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let _lock = INDEX.lock().unwrap_or_else(|e| e.into_inner());
+    let mut entries = read_index(dir);
+
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let now = unix_now();
+    let mut id = format!("{}-{}", now, name);
+    let mut n = 1;
+    while entries.iter().any(|entry| entry.id == id) {
+        n += 1;
+        id = format!("{}-{}-{}", now, n, name);
+    }
+    let size = fs::metadata(path).map(|meta| meta.len()).unwrap_or(0);
+    move_file(path, &dir.join(&id))?;
+    entries.push(QuarantineEntry {
+        id,
+        kind: kind.to_string(),
+        original_path: path.to_path_buf(),
+        size,
+        quarantined_at: now,
+    });
+    write_index(dir, &entries)
+}
+
+/// Delete what's been in the quarantine longer than `days`; returns how many went
+pub fn purge(days: u32) -> usize {
+    let Ok(dir) = dir() else {
+        return 0;
+    };
+    let _lock = INDEX.lock().unwrap_or_else(|e| e.into_inner());
+    let cutoff = unix_now().saturating_sub(days as u64 * 24 * 60 * 60);
+    let (expired, kept): (Vec<_>, Vec<_>) = read_index(dir)
+        .into_iter()
+        .partition(|entry| days == 0 || entry.quarantined_at < cutoff);
+    if expired.is_empty() {
+        return 0;
+    }
+    for entry in &expired {
+        if let Err(e) = fs::remove_file(dir.join(&entry.id)) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                eprintln!("Warning: Failed to remove {} from the quarantine: {}", entry.id, e);
+            }
+        }
+    }
+    let _ = write_index(dir, &kept);
+    expired.len()
+}
+
+/// Files in the quarantine, newest first
+#[tauri::command]
+pub async fn list_quarantine() -> Result<Vec<QuarantineEntry>, String> {
+    let dir = dir()?;
+    let _lock = INDEX.lock().unwrap_or_else(|e| e.into_inner());
+    let mut entries = read_index(dir);
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.quarantined_at));
+    Ok(entries)
+}
+
+/// Put a quarantined file back where it was; returns its path
+#[tauri::command]
+pub async fn restore_from_quarantine(id: String) -> Result<String, String> {
+    let dir = dir()?;
+    let _lock = INDEX.lock().unwrap_or_else(|e| e.into_inner());
+    let mut entries = read_index(dir);
+    let index = entries
+        .iter()
+        .position(|entry| entry.id == id)
+        .ok_or_else(|| "That file is no longer in the quarantine".to_string())?;
+    let target = entries[index].original_path.clone();
+    if target.exists() {
+        return Err(format!("{} already exists", target.display()));
+    }
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    move_file(&dir.join(&id), &target)?;
+    entries.remove(index);
+    write_index(dir, &entries)?;
+    Ok(target.to_string_lossy().to_string())
+}
+
+/// Delete everything in the quarantine now
+#[tauri::command]
+pub async fn empty_quarantine() -> Result<usize, String> {
+    Ok(purge(0))
+}
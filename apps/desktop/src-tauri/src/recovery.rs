@@ -0,0 +1,517 @@
+// Guided recovery when the SQLite database is damaged
+//
+// The server's output is watched for SQLite's corruption errors ("database disk image
+// is malformed", "file is not a database"). The first one opens the recovery window,
+// which confirms the damage with `PRAGMA quick_check` and offers to:
+//
+// 1. salvage what's readable into a new file: the sqlite3 CLI's `.recover` when it's
+//    installed, otherwise a table-by-table copy that skips unreadable row ranges;
+// 2. if nothing usable comes out, restore the newest backup that passes an integrity
+//    check.
+//
+// The damaged files are kept in the backups folder either way, and the report lists
+// per table how many rows were salvaged against how many the damaged file claimed.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+use rusqlite::{Connection, OpenFlags};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use crate::backup::{backups_dir, integrity_check, list_all, sqlite_db_path, table_counts};
+use crate::jobs::{start_job, JobKind};
+use crate::logs::{log_line, SharedLogStore};
+use crate::server::{read_database_url, SharedServerManager};
+use crate::settings::SharedSettings;
+use crate::windows::open_injected_window;
+
+const WINDOW_LABEL: &str = "recovery";
+/// SQLite error text for a damaged file, as the server logs it
+const SIGNATURES: &[&str] = &[
+    "database disk image is malformed",
+    "file is not a database",
+    "sqlite_corrupt",
+    "sqlite_notadb",
+];
+/// Rows copied per statement once a table can't be copied in one go
+const CHUNK_ROWS: i64 = 500;
+
+/// The window was already offered this session
+static OFFERED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Serialize)]
+pub struct DamageReport {
+    database: String,
+    /// "ok", or what quick_check found
+    check: String,
+    damaged: bool,
+    /// Newest backup that can be restored if salvaging fails
+    latest_backup: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct TableSalvage {
+    name: String,
+    /// Rows in the new database
+    salvaged: i64,
+    /// Rows the damaged file reported, None if it couldn't be counted
+    expected: Option<i64>,
+    /// Why rows are missing
+    problem: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct RecoveryReport {
+    /// "recover" (sqlite3 .recover), "copy" (table copy) or "backup"
+    method: &'static str,
+    /// Backup restored when salvaging didn't give a usable database
+    backup: Option<String>,
+    tables: Vec<TableSalvage>,
+    /// Rows `.recover` couldn't place, kept in its lost_and_found table
+    lost_and_found: i64,
+    /// Where the damaged files were moved
+    damaged_copy: String,
+    /// What was tried and didn't work
+    notes: Vec<String>,
+}
+
+pub fn is_corruption(line: &str) -> bool {
+    let line = line.to_lowercase();
+    SIGNATURES.iter().any(|signature| line.contains(signature))
+}
+
+/// Whether the server has reported a damaged database this session
+pub fn damage_reported() -> bool {
+    OFFERED.load(Ordering::SeqCst)
+}
+
+/// Offer recovery the first time the server reports a damaged database
+pub fn check_output(app: &AppHandle, line: &str) {
+    if !is_corruption(line) || OFFERED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let data_dir = app.state::<SharedServerManager>().lock().await.data_dir().clone();
+        if read_database_url(&data_dir).is_some() {
+            return;
+        }
+        let log_store = app.state::<SharedLogStore>().inner().clone();
+        log_line(&app, &log_store, "The server reports a damaged database; offering recovery", "error").await;
+        open_recovery_window(&app);
+    });
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn quick_check(db_path: &Path) -> String {
+    let result = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .and_then(|conn| conn.query_row("PRAGMA quick_check", [], |row| row.get::<_, String>(0)));
+    match result {
+        Ok(result) => result,
+        Err(e) => e.to_string(),
+    }
+}
+
+fn quote(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Move the damaged database and its journal files aside
+fn set_aside(db_path: &Path, dir: &Path) -> Result<PathBuf, String> {
+    let target = dir.join(format!("damaged-{}", unix_now()));
+    fs::create_dir_all(&target).map_err(|e| format!("Failed to create {}: {}", target.display(), e))?;
+    for suffix in ["", "-wal", "-shm"] {
+        let from = PathBuf::from(format!("{}{}", db_path.display(), suffix));
+        if from.exists() {
+            let to = target.join(format!("app.db{}", suffix));
+            fs::copy(&from, &to).map_err(|e| format!("Failed to copy {}: {}", from.display(), e))?;
+        }
+    }
+    Ok(target)
+}
+
+/// `sqlite3 damaged .recover | sqlite3 output`, when the CLI is installed
+fn recover_with_cli(damaged: &Path, output: &Path) -> Result<(), String> {
+    let mut dump = Command::new("sqlite3")
+        .arg(damaged)
+        .arg(".recover")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("sqlite3 isn't available: {}", e))?;
+    let stdout = dump.stdout.take().ok_or("sqlite3 produced no output")?;
+    let load = Command::new("sqlite3")
+        .arg(output)
+        .stdin(stdout)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map_err(|e| e.to_string())?;
+    let dumped = dump.wait().map_err(|e| e.to_string())?;
+    if !dumped.success() || !load.success() {
+        return Err("sqlite3 .recover failed".to_string());
+    }
+    Ok(())
+}
+
+/// Copy rows `lo..hi` by rowid, halving the range around unreadable pages
+fn copy_range(conn: &Connection, table: &str, lo: i64, hi: i64) -> Result<(), String> {
+    let sql = format!(
+        "INSERT OR IGNORE INTO main.{0} SELECT * FROM damaged.{0} WHERE rowid >= ?1 AND rowid < ?2",
+        quote(table)
+    );
+    match conn.execute(&sql, [lo, hi]) {
+        Ok(_) => Ok(()),
+        Err(_) if hi - lo > 1 => {
+            let mid = lo + (hi - lo) / 2;
+            let first = copy_range(conn, table, lo, mid);
+            let second = copy_range(conn, table, mid, hi);
+            first.and(second)
+        }
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Recreate the schema in a new file and copy every readable row across
+fn recover_by_copy(damaged: &Path, output: &Path) -> Result<Vec<(String, Option<String>)>, String> {
+    let conn = Connection::open_with_flags(
+        output,
+        OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_CREATE | OpenFlags::SQLITE_OPEN_URI,
+    )
+    .map_err(|e| format!("Failed to create {}: {}", output.display(), e))?;
+    let uri = format!("file:{}?mode=ro", damaged.to_string_lossy().replace('?', "%3f").replace('#', "%23"));
+    conn.execute("ATTACH DATABASE ?1 AS damaged", [uri])
+        .map_err(|e| format!("Failed to open the damaged database: {}", e))?;
+
+    let schema: Vec<(String, String, String)> = conn
+        .prepare("SELECT type, name, sql FROM damaged.sqlite_master WHERE sql IS NOT NULL AND name NOT LIKE 'sqlite_%'")
+        .and_then(|mut stmt| {
+            stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(|e| format!("The database's table list is unreadable: {}", e))?;
+
+    let mut results = Vec::new();
+    for (_, name, sql) in schema.iter().filter(|(kind, _, _)| kind == "table") {
+        if let Err(e) = conn.execute_batch(sql) {
+            results.push((name.clone(), Some(format!("couldn't recreate the table: {}", e))));
+            continue;
+        }
+        let all = format!("INSERT INTO main.{0} SELECT * FROM damaged.{0}", quote(name));
+        if conn.execute(&all, []).is_ok() {
+            results.push((name.clone(), None));
+            continue;
+        }
+        // Something in the table is unreadable: copy around it
+        let _ = conn.execute(&format!("DELETE FROM main.{}", quote(name)), []);
+        let bounds = conn.query_row(
+            &format!("SELECT MIN(rowid), MAX(rowid) FROM damaged.{}", quote(name)),
+            [],
+            |row| Ok((row.get::<_, Option<i64>>(0)?, row.get::<_, Option<i64>>(1)?)),
+        );
+        let problem = match bounds {
+            Ok((Some(min), Some(max))) => {
+                let mut failed = false;
+                let mut lo = min;
+                while lo <= max {
+                    let hi = lo.saturating_add(CHUNK_ROWS);
+                    failed |= copy_range(&conn, name, lo, hi).is_err();
+                    lo = hi;
+                }
+                failed.then(|| "some rows were unreadable".to_string())
+            }
+            Ok(_) => None,
+            Err(e) => Some(format!("the table is unreadable: {}", e)),
+        };
+        results.push((name.clone(), problem));
+    }
+    // Indexes and triggers go in after the data
+    for (_, name, sql) in schema.iter().filter(|(kind, _, _)| kind != "table") {
+        if let Err(e) = conn.execute_batch(sql) {
+            results.push((name.clone(), Some(format!("couldn't recreate it: {}", e))));
+        }
+    }
+    conn.execute("DETACH DATABASE damaged", []).map_err(|e| e.to_string())?;
+    Ok(results)
+}
+
+/// Whether a salvaged file can replace the database: intact and with its migration history
+fn usable(path: &Path) -> Result<(), String> {
+    integrity_check(path)?;
+    let counts = table_counts(path)?;
+    let migrations = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .and_then(|conn| conn.query_row("SELECT COUNT(*) FROM __drizzle_migrations", [], |row| row.get::<_, i64>(0)))
+        .unwrap_or(0);
+    if counts.is_empty() || migrations == 0 {
+        return Err("nothing usable could be salvaged".to_string());
+    }
+    Ok(())
+}
+
+fn install(source: &Path, db_path: &Path) -> Result<(), String> {
+    let staging = db_path.with_extension("db.recovered");
+    fs::copy(source, &staging).map_err(|e| format!("Failed to copy {}: {}", source.display(), e))?;
+    for suffix in ["-wal", "-shm"] {
+        let _ = fs::remove_file(format!("{}{}", db_path.display(), suffix));
+    }
+    fs::rename(&staging, db_path).map_err(|e| format!("Failed to replace the database: {}", e))
+}
+
+fn recover(db_path: &Path, dir: &Path, backups: Vec<PathBuf>) -> Result<RecoveryReport, String> {
+    let aside = set_aside(db_path, dir)?;
+    let damaged = aside.join("app.db");
+    let expected = table_counts(&damaged).ok();
+    let mut notes = Vec::new();
+
+    let output = aside.join("recovered.db");
+    let mut method = "recover";
+    let mut problems = Vec::new();
+    if let Err(e) = recover_with_cli(&damaged, &output).and_then(|_| usable(&output)) {
+        notes.push(format!(".recover: {}", e));
+        let _ = fs::remove_file(&output);
+        method = "copy";
+        match recover_by_copy(&damaged, &output) {
+            Ok(results) => problems = results,
+            Err(e) => notes.push(format!("Table copy: {}", e)),
+        }
+    }
+
+    if let Err(e) = usable(&output) {
+        if method == "copy" {
+            notes.push(format!("Table copy: {}", e));
+        }
+        let backup = backups
+            .into_iter()
+            .find(|path| integrity_check(path).is_ok())
+            .ok_or_else(|| format!("{}. No intact backup was found either; the damaged files are in {}", notes.join("; "), aside.display()))?;
+        install(&backup, db_path)?;
+        let salvaged = table_counts(&backup)?;
+        return Ok(RecoveryReport {
+            method: "backup",
+            backup: Some(backup.to_string_lossy().to_string()),
+            tables: salvaged
+                .into_iter()
+                .map(|(name, rows)| TableSalvage {
+                    expected: expected.as_ref().and_then(|e| e.get(&name).copied()),
+                    name,
+                    salvaged: rows,
+                    problem: None,
+                })
+                .collect(),
+            lost_and_found: 0,
+            damaged_copy: aside.to_string_lossy().to_string(),
+            notes,
+        });
+    }
+
+    install(&output, db_path)?;
+    let mut salvaged = table_counts(&output)?;
+    let lost_and_found = salvaged.remove("lost_and_found").unwrap_or(0);
+    let mut tables: Vec<TableSalvage> = salvaged
+        .into_iter()
+        .map(|(name, rows)| TableSalvage {
+            expected: expected.as_ref().and_then(|e| e.get(&name).copied()),
+            problem: problems.iter().find(|(n, _)| *n == name).and_then(|(_, p)| p.clone()),
+            name,
+            salvaged: rows,
+        })
+        .collect();
+    // Tables that exist in the damaged file but didn't make it over at all
+    for (name, problem) in problems {
+        if problem.is_some() && !tables.iter().any(|t| t.name == name) {
+            tables.push(TableSalvage {
+                expected: expected.as_ref().and_then(|e| e.get(&name).copied()),
+                name,
+                salvaged: 0,
+                problem,
+            });
+        }
+    }
+    Ok(RecoveryReport {
+        method,
+        backup: None,
+        tables,
+        lost_and_found,
+        damaged_copy: aside.to_string_lossy().to_string(),
+        notes,
+    })
+}
+
+/// What's wrong with the database, and the backup to fall back on
+#[tauri::command]
+pub async fn check_database_damage(app: AppHandle) -> Result<DamageReport, String> {
+    let data_dir = app.state::<SharedServerManager>().lock().await.data_dir().clone();
+    if read_database_url(&data_dir).is_some() {
+        return Err("Recovery is only available for SQLite databases".to_string());
+    }
+    let settings = app.state::<SharedSettings>().lock().await.get();
+    let db_path = sqlite_db_path(&data_dir);
+    let latest_backup = list_all(&data_dir, &settings).into_iter().find(|b| b.sqlite).map(|b| b.file_name);
+    let check = tauri::async_runtime::spawn_blocking(move || quick_check(&db_path))
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(DamageReport {
+        database: sqlite_db_path(&data_dir).to_string_lossy().to_string(),
+        damaged: check != "ok",
+        check,
+        latest_backup,
+    })
+}
+
+/// Stop the server, salvage or restore the database, and start again
+#[tauri::command]
+pub async fn recover_database(app: AppHandle) -> Result<RecoveryReport, String> {
+    let manager = app.state::<SharedServerManager>().inner().clone();
+    let log_store = app.state::<SharedLogStore>().inner().clone();
+    let data_dir = manager.lock().await.data_dir().clone();
+    if read_database_url(&data_dir).is_some() {
+        return Err("Recovery is only available for SQLite databases".to_string());
+    }
+    let settings = app.state::<SharedSettings>().lock().await.get();
+    let dir = backups_dir(&data_dir, &settings);
+    let backups = list_all(&data_dir, &settings)
+        .into_iter()
+        .filter(|b| b.sqlite)
+        .map(|b| PathBuf::from(b.path))
+        .collect();
+
+    let job = start_job(&app, JobKind::Recovery, "Recovering the database", false);
+    // In dev mode the API runs separately (`bun run dev`) and must be restarted by hand
+    #[cfg(not(debug_assertions))]
+    if let Err(e) = crate::server::stop_server(manager.clone()).await {
+        job.finish::<()>(&Err(e.clone()));
+        return Err(e);
+    }
+    let db_path = sqlite_db_path(&data_dir);
+    let result = tauri::async_runtime::spawn_blocking(move || recover(&db_path, &dir, backups))
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|r| r);
+    job.finish(&result);
+    match &result {
+        Ok(report) => {
+            let msg = match &report.backup {
+                Some(backup) => format!("Database restored from {} after salvaging failed", backup),
+                None => format!("Database salvaged ({}); the damaged copy is in {}", report.method, report.damaged_copy),
+            };
+            log_line(&app, &log_store, msg, "info").await;
+            OFFERED.store(false, Ordering::SeqCst);
+        }
+        Err(e) => log_line(&app, &log_store, format!("Recovering the database failed: {}", e), "error").await,
+    }
+
+    // The server comes back after a failed recovery too, on the database it had
+    #[cfg(not(debug_assertions))]
+    let restarted = crate::restart_server(app.clone(), manager, log_store).await;
+    let report = result?;
+    #[cfg(not(debug_assertions))]
+    restarted?;
+    crate::refresh_main_window(&app);
+    Ok(report)
+}
+
+/// Open the recovery window
+pub fn open_recovery_window(app: &AppHandle) {
+    let script = r#"
+        const tauriApi = window.__TAURI__;
+
+        document.documentElement.innerHTML = `
+<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <title>Recover Database</title>
+    <style>
+        __BASE_STYLE__
+        .content { flex: 1; overflow-y: auto; padding: 24px; display: flex; flex-direction: column; gap: 12px; }
+        .detail { font-family: ui-monospace, SFMono-Regular, Menlo, monospace; font-size: 12px; word-break: break-all; }
+        .actions { display: flex; gap: 8px; }
+        table { width: 100%; border-collapse: collapse; }
+        th, td { text-align: left; padding: 6px 8px; border-bottom: 1px solid rgba(255, 255, 255, 0.06); }
+        td.num { text-align: right; font-variant-numeric: tabular-nums; }
+    </style>
+</head>
+<body>
+    <div class="content">
+        <h1>Your database is damaged</h1>
+        <p>The server reported errors reading your database. Moneywright can try to salvage what's still readable, and restore your latest intact backup if that doesn't work. The damaged file is kept either way.</p>
+        <div id="check" class="detail fail" role="alert"></div>
+        <p id="backup" class="muted"></p>
+        <div class="actions">
+            <button id="recoverBtn" class="primary">Recover Database</button>
+            <button id="backupsBtn">Open Backups</button>
+            <button id="doctorBtn">Run Diagnostics</button>
+        </div>
+        <div id="status" class="muted" role="status" aria-live="polite"></div>
+        <div id="result"></div>
+    </div>
+</body>
+</html>`;
+
+        const $ = id => document.getElementById(id);
+
+        async function load() {
+            try {
+                const report = await tauriApi.core.invoke('check_database_damage');
+                $('check').textContent = report.damaged ? report.check : 'The quick check passes now, but the server reported errors.';
+                $('backup').textContent = report.latest_backup
+                    ? 'Latest backup: ' + report.latest_backup
+                    : 'There are no backups to fall back on.';
+            } catch (e) {
+                $('check').textContent = String(e);
+                $('recoverBtn').disabled = true;
+            }
+        }
+
+        function showReport(r) {
+            const how = r.method === 'backup'
+                ? 'Salvaging didn\'t give a usable database, so the backup ' + r.backup + ' was restored. Changes made after it are not included.'
+                : r.method === 'recover'
+                    ? 'Salvaged with SQLite\'s recovery tool.'
+                    : 'Salvaged by copying every readable row.';
+            let html = '<p>' + escapeHtml(how) + '</p>';
+            html += '<table><thead><tr><th>Table</th><th>Recovered</th><th>In damaged file</th><th></th></tr></thead><tbody>';
+            html += r.tables.map(t => {
+                const short = t.expected != null && t.salvaged < t.expected;
+                const note = t.problem || (short ? (t.expected - t.salvaged) + ' rows missing' : (t.expected == null ? 'couldn\'t be counted' : ''));
+                return '<tr><td>' + escapeHtml(t.name) + '</td><td class="num">' + t.salvaged + '</td><td class="num">' +
+                    (t.expected == null ? '?' : t.expected) + '</td><td class="' + (note ? 'warn' : 'pass') + '">' + escapeHtml(note || 'complete') + '</td></tr>';
+            }).join('');
+            html += '</tbody></table>';
+            if (r.lost_and_found) {
+                html += '<p class="warn">' + r.lost_and_found + ' rows couldn\'t be matched to a table; they are in the lost_and_found table of the recovered database.</p>';
+            }
+            if (r.notes.length) {
+                html += '<p class="muted">' + escapeHtml(r.notes.join(' · ')) + '</p>';
+            }
+            html += '<p class="muted">The damaged files were kept in <span class="detail">' + escapeHtml(r.damaged_copy) + '</span></p>';
+            $('result').innerHTML = html;
+        }
+
+        $('recoverBtn').onclick = async () => {
+            $('recoverBtn').disabled = true;
+            $('status').textContent = 'Recovering... this can take a few minutes for a large database.';
+            try {
+                const report = await tauriApi.core.invoke('recover_database');
+                $('status').textContent = 'Done. Moneywright is running with the recovered database.';
+                showReport(report);
+            } catch (e) {
+                $('status').textContent = String(e);
+                $('recoverBtn').disabled = false;
+            }
+        };
+        $('backupsBtn').onclick = () => tauriApi.core.invoke('open_backups');
+        $('doctorBtn').onclick = () => tauriApi.core.invoke('open_diagnostics');
+        load();
+    "#;
+
+    open_injected_window(app, WINDOW_LABEL, "Recover Database", (640.0, 560.0), true, script);
+}
@@ -0,0 +1,240 @@
+// Finding data left in the app data dir of an earlier release
+//
+// The default data dir is Tauri's app data dir, which is named after the bundle
+// identifier. When that changes (an identifier or vendor rename), an update would
+// start with an empty folder and the user's data would seem to be gone. On a first
+// run the sibling folders earlier releases used are checked for desktop data, and if
+// one has it the user is offered to move it over instead of going through onboarding.
+//
+// Moving renames the entries into the new folder (copying across file systems) and
+// leaves a `data-location` pointer in the old folder, so an older release, scripts
+// or moneywrightctl pointed at the old path still find the data.
+//
+// A move can stop halfway (a full disk, a file in use). The new folder keeps a
+// `moving-from` marker naming the old one until everything is across, and the files
+// that mark a desktop install move last, so the next start offers to finish the move.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use crate::onboarding::{copy_dir_recursive, open_onboarding_window};
+use crate::server::{default_data_dir, DATA_LOCATION_FILE};
+use crate::windows::open_injected_window;
+
+pub const WINDOW_LABEL: &str = "data_migration";
+
+/// Names of the app data dir in earlier releases, next to the current one
+/// (add the old identifier here when renaming it)
+const PREVIOUS_DIR_NAMES: &[&str] = &["Moneywright", "moneywright"];
+/// Files that only make sense for a running instance and aren't moved
+const SESSION_FILES: &[&str] = &["control.sock", "session.lock"];
+/// Marker in the new folder while a move is unfinished, holding the old folder's path
+pub const MOVING_FILE: &str = "moving-from";
+/// Moved last, so the old folder still looks like an install until the rest is across
+const INSTALL_FILES: &[&str] = &["onboarding.json", "settings.toml"];
+
+/// True while the window waits for a choice (closing it then quits)
+static PENDING: AtomicBool = AtomicBool::new(false);
+
+pub fn is_pending() -> bool {
+    PENDING.load(Ordering::SeqCst)
+}
+
+#[derive(Serialize)]
+pub struct PreviousData {
+    previous: String,
+    current: String,
+}
+
+/// Whether a folder holds a desktop install (CLI installs have neither file)
+fn has_desktop_data(dir: &Path) -> bool {
+    dir.join("onboarding.json").exists() || dir.join("settings.toml").exists()
+}
+
+/// Where a data dir's pointer file leads, if it has one
+fn pointer_target(dir: &Path) -> Option<PathBuf> {
+    let target = fs::read_to_string(dir.join(DATA_LOCATION_FILE)).ok()?;
+    let target = target.trim();
+    (!target.is_empty()).then(|| PathBuf::from(target))
+}
+
+/// The old folder of a move that didn't finish
+fn interrupted_move(current: &Path) -> Option<PathBuf> {
+    let previous = fs::read_to_string(current.join(MOVING_FILE)).ok()?;
+    let previous = PathBuf::from(previous.trim());
+    previous.is_dir().then_some(previous)
+}
+
+/// Whether an earlier move stopped halfway and should be offered again
+pub fn is_interrupted(app: &AppHandle) -> bool {
+    interrupted_move(&default_data_dir(app)).is_some()
+}
+
+/// The data dir an earlier release left behind, if there is one to move
+pub fn find_previous_data_dir(app: &AppHandle) -> Option<PathBuf> {
+    let current = default_data_dir(app);
+    if let Some(previous) = interrupted_move(&current) {
+        return Some(previous);
+    }
+    let parent = current.parent()?;
+    let current_real = fs::canonicalize(&current).unwrap_or_else(|_| current.clone());
+    PREVIOUS_DIR_NAMES
+        .iter()
+        .map(|name| parent.join(name))
+        .filter(|dir| fs::canonicalize(dir).is_ok_and(|real| real != current_real))
+        .find(|dir| {
+            if has_desktop_data(dir) {
+                return true;
+            }
+            // Relocated by the old release; already moved ones point here
+            pointer_target(dir).is_some_and(|target| target != current && has_desktop_data(&target))
+        })
+}
+
+/// Move one entry, replacing whatever this run already created in its place
+fn move_entry(from: &Path, to: &Path) -> Result<(), String> {
+    if to.is_dir() {
+        fs::remove_dir_all(to).map_err(|e| format!("Failed to replace {}: {}", to.display(), e))?;
+    } else if to.exists() {
+        fs::remove_file(to).map_err(|e| format!("Failed to replace {}: {}", to.display(), e))?;
+    }
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    // Different file system: copy, then remove the original
+    if from.is_dir() {
+        copy_dir_recursive(from, to)?;
+        fs::remove_dir_all(from).map_err(|e| format!("Failed to remove {}: {}", from.display(), e))
+    } else {
+        fs::copy(from, to).map_err(|e| format!("Failed to copy {}: {}", from.display(), e))?;
+        fs::remove_file(from).map_err(|e| format!("Failed to remove {}: {}", from.display(), e))
+    }
+}
+
+fn move_data(previous: &Path, current: &Path) -> Result<(), String> {
+    fs::create_dir_all(current).map_err(|e| format!("Failed to create {}: {}", current.display(), e))?;
+    let marker = current.join(MOVING_FILE);
+    fs::write(&marker, previous.to_string_lossy().as_bytes())
+        .map_err(|e| format!("Failed to write {}: {}", marker.display(), e))?;
+
+    let mut entries = fs::read_dir(previous)
+        .map_err(|e| format!("Failed to read {}: {}", previous.display(), e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    entries.sort_by_key(|entry| INSTALL_FILES.iter().any(|name| entry.file_name() == *name));
+    for entry in entries {
+        let name = entry.file_name();
+        if SESSION_FILES.iter().any(|skip| name == *skip) {
+            let _ = fs::remove_file(entry.path());
+            continue;
+        }
+        move_entry(&entry.path(), &current.join(&name))?;
+    }
+
+    // Older releases follow one pointer, so point at where the data actually is now
+    let location = pointer_target(current).unwrap_or_else(|| current.to_path_buf());
+    fs::write(previous.join(DATA_LOCATION_FILE), location.to_string_lossy().as_bytes())
+        .map_err(|e| format!("Failed to leave a pointer in {}: {}", previous.display(), e))?;
+    fs::remove_file(&marker).map_err(|e| format!("Failed to remove {}: {}", marker.display(), e))
+}
+
+/// The old and new locations for the window
+#[tauri::command]
+pub async fn get_previous_data(app: AppHandle) -> Result<PreviousData, String> {
+    let previous = find_previous_data_dir(&app).ok_or_else(|| "No data from an earlier version was found".to_string())?;
+    Ok(PreviousData {
+        previous: previous.to_string_lossy().to_string(),
+        current: default_data_dir(&app).to_string_lossy().to_string(),
+    })
+}
+
+/// Move the earlier release's data into the current data dir and restart with it
+#[tauri::command]
+pub async fn migrate_previous_data(app: AppHandle) -> Result<(), String> {
+    let previous = find_previous_data_dir(&app).ok_or_else(|| "No data from an earlier version was found".to_string())?;
+    let current = default_data_dir(&app);
+    tauri::async_runtime::spawn_blocking(move || move_data(&previous, &current))
+        .await
+        .map_err(|e| e.to_string())??;
+    PENDING.store(false, Ordering::SeqCst);
+    // Everything is keyed off the data dir at startup
+    app.restart();
+}
+
+/// Leave the old data where it is and set up a new install
+#[tauri::command]
+pub async fn skip_previous_data(app: AppHandle) -> Result<(), String> {
+    PENDING.store(false, Ordering::SeqCst);
+    // Not offered again, also after a move that stopped halfway
+    let _ = fs::remove_file(default_data_dir(&app).join(MOVING_FILE));
+    open_onboarding_window(&app);
+    if let Some(window) = app.get_webview_window(WINDOW_LABEL) {
+        let _ = window.close();
+    }
+    Ok(())
+}
+
+/// Offer to move the data found in an earlier release's folder (instead of onboarding)
+pub fn open_migration_window(app: &AppHandle) {
+    PENDING.store(true, Ordering::SeqCst);
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.hide();
+    }
+
+    let script = r#"
+        const tauriApi = window.__TAURI__;
+
+        document.documentElement.innerHTML = `
+<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <title>Moneywright Data Found</title>
+    <style>
+        __BASE_STYLE__
+        .content { flex: 1; padding: 24px; display: flex; flex-direction: column; gap: 12px; }
+        .path { font-family: ui-monospace, SFMono-Regular, Menlo, monospace; font-size: 12px; word-break: break-all; }
+        .actions { display: flex; gap: 8px; margin-top: 8px; }
+    </style>
+</head>
+<body>
+    <div class="content">
+        <h1>Your data is in another folder</h1>
+        <p>This version of Moneywright keeps its data in a new folder. Your data from an earlier version is still here:</p>
+        <div id="previous" class="path"></div>
+        <p class="muted">Moving it takes a moment and restarts the app. A note is left in the old folder so older versions and scripts still find it.</p>
+        <div class="actions">
+            <button id="moveBtn" class="primary">Move My Data</button>
+            <button id="freshBtn">Start Fresh</button>
+        </div>
+        <div id="status" class="muted" role="status" aria-live="polite"></div>
+    </div>
+</body>
+</html>`;
+
+        const $ = id => document.getElementById(id);
+
+        tauriApi.core.invoke('get_previous_data').then(data => {
+            $('previous').textContent = data.previous;
+            $('moveBtn').title = 'Move to ' + data.current;
+        }).catch(e => $('status').textContent = String(e));
+
+        $('moveBtn').onclick = async () => {
+            $('moveBtn').disabled = true;
+            $('freshBtn').disabled = true;
+            $('status').textContent = 'Moving your data...';
+            try {
+                await tauriApi.core.invoke('migrate_previous_data');
+            } catch (e) {
+                $('status').textContent = String(e);
+                $('moveBtn').disabled = false;
+                $('freshBtn').disabled = false;
+            }
+        };
+        $('freshBtn').onclick = () => tauriApi.core.invoke('skip_previous_data');
+    "#;
+
+    open_injected_window(app, WINDOW_LABEL, "Moneywright Data Found", (560.0, 320.0), false, script);
+}
@@ -0,0 +1,293 @@
+// Remote server mode: use a Moneywright running elsewhere, e.g. self-hosted on a NAS
+//
+// With `server.remote_url` set the app doesn't run a server of its own. The main window
+// loads the remote address, and starting the server only waits for it to answer
+// `/health`. Stopping it just marks it stopped: nothing on this computer is stopped or
+// killed, so there's no port cleanup and no orphan reaping (see server.rs). Extra
+// profiles and the demo still run locally. What works on the local database files
+// (backups, recovery, the PostgreSQL migration) doesn't reach the remote server's data.
+//
+// The mode is settled at launch like the transport; changing it restarts the app.
+
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use serde::Serialize;
+use serde_json::json;
+use tauri::{AppHandle, Manager, Url};
+use crate::events::{self, Event};
+use crate::server::HealthResponse;
+use crate::settings::{Settings, SharedSettings};
+use crate::windows::open_injected_window;
+
+/// One `/health` request; a NAS waking from sleep gets retried until the startup timeout
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+const RETRY_INTERVAL: Duration = Duration::from_secs(2);
+
+static REMOTE: OnceLock<Option<String>> = OnceLock::new();
+
+/// Settle the mode for this run; called once settings are loaded
+pub fn init(settings: &Settings) {
+    let url = Some(settings.server.remote_url.trim()).filter(|url| !url.is_empty()).map(base_url);
+    let _ = REMOTE.set(url);
+}
+
+/// The remote server the app uses instead of its own, None when it runs one
+pub fn url() -> Option<&'static str> {
+    REMOTE.get().and_then(|url| url.as_deref())
+}
+
+/// Check a URL for `server.remote_url`: http(s), a host and no credentials
+pub fn parse_remote_url(url: &str) -> Result<Url, String> {
+    let parsed = Url::parse(url.trim()).map_err(|e| format!("\"{}\" isn't a URL: {}", url, e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err("The server address must start with https:// or http://".to_string());
+    }
+    if parsed.host_str().is_none_or(str::is_empty) {
+        return Err("The server address needs a host name".to_string());
+    }
+    if !parsed.username().is_empty() || parsed.password().is_some() {
+        return Err("The server address must not contain a user name or password".to_string());
+    }
+    if parsed.query().is_some() || parsed.fragment().is_some() {
+        return Err("The server address must not have a query or fragment".to_string());
+    }
+    Ok(parsed)
+}
+
+/// The URL without a trailing slash, as other URLs are built on it
+fn base_url(url: &str) -> String {
+    url.trim().trim_end_matches('/').to_string()
+}
+
+#[derive(Clone, Serialize)]
+pub struct RemoteCheck {
+    pub url: String,
+    pub reachable: bool,
+    /// Version the server reports
+    pub version: Option<String>,
+    pub latency_ms: Option<u64>,
+    /// Why it isn't reachable
+    pub error: Option<String>,
+    /// Reachable, but something to know about (plain http, another version)
+    pub warning: Option<String>,
+}
+
+/// Ask a server for `/health` once
+pub async fn check(url: &str) -> RemoteCheck {
+    let base = base_url(url);
+    let mut result = RemoteCheck {
+        url: base.clone(),
+        reachable: false,
+        version: None,
+        latency_ms: None,
+        error: None,
+        warning: None,
+    };
+    let client = match reqwest::Client::builder().timeout(CHECK_TIMEOUT).build() {
+        Ok(client) => client,
+        Err(e) => {
+            result.error = Some(e.to_string());
+            return result;
+        }
+    };
+    let started = Instant::now();
+    let response = client.get(format!("{}/health", base)).send().await;
+    let health = match response {
+        Ok(response) if response.status().is_success() => response
+            .json::<HealthResponse>()
+            .await
+            .map_err(|_| "It answered, but not like a Moneywright server".to_string()),
+        Ok(response) => Err(format!("It answered with HTTP {}", response.status())),
+        Err(e) if e.is_timeout() => Err(format!("No answer within {} s", CHECK_TIMEOUT.as_secs())),
+        Err(e) if e.is_connect() => Err("Nothing accepted the connection (wrong address, or the server is down)".to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+    match health {
+        Ok(health) => {
+            result.reachable = true;
+            result.latency_ms = Some(started.elapsed().as_millis() as u64);
+            result.warning = if base.starts_with("http://") && !is_local(&base) {
+                Some("Without https, passwords and sign-in cookies travel unencrypted".to_string())
+            } else {
+                health
+                    .version
+                    .as_deref()
+                    .filter(|version| *version != env!("CARGO_PKG_VERSION"))
+                    .map(|version| format!("The server runs {}, this app is {}", version, env!("CARGO_PKG_VERSION")))
+            };
+            result.version = health.version;
+        }
+        Err(e) => result.error = Some(e),
+    }
+    result
+}
+
+/// Plain http is expected on this computer
+fn is_local(url: &str) -> bool {
+    Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .is_some_and(|host| host == "localhost" || host == "127.0.0.1" || host == "[::1]")
+}
+
+/// Wait for the remote server to answer, returning the version it reports
+pub async fn connect(url: &str, timeout: Duration) -> Result<Option<String>, String> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let result = check(url).await;
+        if result.reachable {
+            return Ok(result.version);
+        }
+        if Instant::now() + RETRY_INTERVAL >= deadline {
+            return Err(format!(
+                "Can't reach the Moneywright server at {}: {}",
+                url,
+                result.error.unwrap_or_default()
+            ));
+        }
+        tokio::time::sleep(RETRY_INTERVAL).await;
+    }
+}
+
+#[derive(Serialize)]
+pub struct RemoteStatus {
+    /// The URL in use for this run
+    pub active: Option<String>,
+    /// The saved URL, applied on the next launch
+    pub saved: String,
+}
+
+#[tauri::command]
+pub async fn get_remote_server(settings: tauri::State<'_, SharedSettings>) -> Result<RemoteStatus, String> {
+    Ok(RemoteStatus {
+        active: url().map(str::to_string),
+        saved: settings.lock().await.get().server.remote_url,
+    })
+}
+
+/// Check a server address without saving it
+#[tauri::command]
+pub async fn test_remote_server(url: String) -> Result<RemoteCheck, String> {
+    let parsed = parse_remote_url(&url)?;
+    Ok(check(parsed.as_str()).await)
+}
+
+/// Save (or with None, clear) the remote server and restart the app to switch
+#[tauri::command]
+pub async fn set_remote_server(app: AppHandle, url: Option<String>) -> Result<(), String> {
+    let url = match url.filter(|u| !u.trim().is_empty()) {
+        Some(url) => {
+            let parsed = parse_remote_url(&url)?;
+            let result = check(parsed.as_str()).await;
+            if !result.reachable {
+                return Err(result.error.unwrap_or_else(|| "The server isn't reachable".to_string()));
+            }
+            result.url
+        }
+        None => String::new(),
+    };
+    if url.as_str() == self::url().unwrap_or_default() {
+        return Ok(());
+    }
+    let updated = app
+        .state::<SharedSettings>()
+        .lock()
+        .await
+        .update(&json!({ "server": { "remote_url": url } }))?;
+    let _ = events::emit(&app, Event::SettingsChanged(&updated));
+    // The exit handler stops our own server when switching to a remote one
+    app.restart();
+}
+
+/// Open the remote server window
+pub fn open_remote_window(app: &AppHandle) {
+    let script = r#"
+        const tauriApi = window.__TAURI__;
+
+        document.documentElement.innerHTML = `
+<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <title>Remote Server</title>
+    <style>
+        __BASE_STYLE__
+        .content { flex: 1; overflow-y: auto; padding: 16px; display: flex; flex-direction: column; gap: 12px; }
+        .row { display: flex; gap: 8px; }
+        .row input { flex: 1; }
+        .result { padding: 10px 12px; border: 1px solid rgba(255, 255, 255, 0.06); border-radius: 6px; }
+    </style>
+</head>
+<body>
+    <div class="content">
+        <p class="muted">Use a Moneywright server you run yourself, e.g. on a NAS, instead of the one built into the app. Your data stays on that server; backups and other tools for the local database don't reach it.</p>
+        <div class="row">
+            <input type="url" id="url" placeholder="http://nas.local:17777" aria-label="Server address">
+            <button id="testBtn">Test</button>
+        </div>
+        <div id="result" class="result" role="status" aria-live="polite" hidden></div>
+        <p id="current" class="muted"></p>
+        <div class="row">
+            <button id="saveBtn" class="primary" disabled>Connect and Restart</button>
+            <button id="localBtn" hidden>Use the Built-in Server</button>
+        </div>
+    </div>
+</body>
+</html>`;
+
+        const $ = id => document.getElementById(id);
+
+        function showResult(check) {
+            $('result').hidden = false;
+            $('result').className = 'result ' + (check.reachable ? (check.warning ? 'warn' : 'pass') : 'fail');
+            $('result').textContent = check.reachable
+                ? 'Reachable' + (check.version ? ', Moneywright ' + check.version : '') + ' (' + check.latency_ms + ' ms)' + (check.warning ? '. ' + check.warning : '')
+                : check.error;
+        }
+
+        async function test() {
+            $('testBtn').disabled = true;
+            $('saveBtn').disabled = true;
+            $('result').hidden = false;
+            $('result').className = 'result';
+            $('result').textContent = 'Checking...';
+            try {
+                const check = await tauriApi.core.invoke('test_remote_server', { url: $('url').value });
+                showResult(check);
+                $('saveBtn').disabled = !check.reachable;
+            } catch (e) {
+                $('result').className = 'result fail';
+                $('result').textContent = String(e);
+            }
+            $('testBtn').disabled = false;
+        }
+
+        async function save(url) {
+            if (!confirm('Moneywright restarts to switch servers. Continue?')) return;
+            $('saveBtn').disabled = true;
+            $('localBtn').disabled = true;
+            try {
+                await tauriApi.core.invoke('set_remote_server', { url });
+            } catch (e) {
+                $('result').hidden = false;
+                $('result').className = 'result fail';
+                $('result').textContent = String(e);
+                $('localBtn').disabled = false;
+            }
+        }
+
+        $('testBtn').onclick = test;
+        $('url').oninput = () => { $('saveBtn').disabled = true; };
+        $('url').onkeydown = (e) => { if (e.key === 'Enter') test(); };
+        $('saveBtn').onclick = () => save($('url').value);
+        $('localBtn').onclick = () => save(null);
+
+        tauriApi.core.invoke('get_remote_server').then(s => {
+            $('url').value = s.active || s.saved;
+            $('current').textContent = s.active ? 'Connected to ' + s.active + '.' : 'Using the built-in server.';
+            $('localBtn').hidden = !s.active;
+        }).catch(() => {});
+    "#;
+
+    open_injected_window(app, "remote", "Remote Server", (560.0, 340.0), true, script);
+}
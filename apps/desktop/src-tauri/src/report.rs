@@ -0,0 +1,160 @@
+// Help > Report a Problem: diagnostics bundle plus a pre-filled GitHub issue
+//
+// The bundle is a zip in the Downloads folder with what's needed to act on a report:
+//
+//   environment.txt    app, OS, webview and database versions
+//   diagnostics.json   the Run Diagnostics report
+//   logs.txt           the in-memory log buffer
+//   sessions.json      uptime, restart and crash counters
+//   settings.toml      current settings (no secrets live there)
+//   crashes/<id>.json  the most recent crash reports
+//
+// Nothing is uploaded: the issue page only gets the environment summary, and the
+// folder with the bundle is revealed so the user can review it and attach it.
+
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, Url};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+use crate::crash::{list_reports, read_report};
+use crate::doctor::{run_diagnostics, CheckStatus, DoctorReport};
+use crate::jobs::{start_job, JobKind};
+use crate::logs::SharedLogStore;
+use crate::server::{read_database_url, SharedServerManager};
+use crate::sessions::SharedSessionTracker;
+use crate::settings::SharedSettings;
+
+const NEW_ISSUE_URL: &str = "https://github.com/moneywright/moneywright/issues/new";
+/// Older crash reports rarely help and only grow the bundle
+const MAX_CRASH_REPORTS: usize = 5;
+
+struct Bundle {
+    environment: String,
+    diagnostics: String,
+    logs: String,
+    sessions: String,
+    settings: String,
+    crashes: Vec<(String, String)>,
+}
+
+fn environment(app: &AppHandle, data_dir: &Path) -> String {
+    let os = sysinfo::System::long_os_version().unwrap_or_else(|| std::env::consts::OS.to_string());
+    let database = if read_database_url(data_dir).is_some() { "PostgreSQL" } else { "SQLite" };
+    [
+        format!("Moneywright: {}", app.package_info().version),
+        format!("OS: {} ({})", os, std::env::consts::ARCH),
+        format!("WebView: {}", tauri::webview_version().unwrap_or_else(|_| "unknown".to_string())),
+        format!("Database: {}", database),
+    ]
+    .join("\n")
+}
+
+/// Markdown issue body: environment and failed checks, with blanks for the user
+fn issue_body(environment: &str, report: &DoctorReport, bundle_name: &str) -> String {
+    let mut body = String::from("### What happened?\n\n\n\n### Steps to reproduce\n\n1. \n\n### Environment\n\n");
+    for line in environment.lines() {
+        body.push_str(&format!("- {}\n", line));
+    }
+    body.push_str(&format!(
+        "- Diagnostics: {} passed, {} warnings, {} failed\n",
+        report.passed, report.warnings, report.failures
+    ));
+    for check in report.checks.iter().filter(|c| c.status != CheckStatus::Pass) {
+        body.push_str(&format!("  - {}: {}\n", check.name, check.detail));
+    }
+    body.push_str(&format!(
+        "\n### Diagnostics bundle\n\n<!-- Drag {} from your Downloads folder here. It contains logs and settings, so look it over first. -->\n",
+        bundle_name
+    ));
+    body
+}
+
+fn write_bundle(bundle: &Bundle, target: &Path) -> Result<(), String> {
+    let file = File::create(target).map_err(|e| format!("Failed to create {}: {}", target.display(), e))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    let mut entries = vec![
+        ("environment.txt".to_string(), &bundle.environment),
+        ("diagnostics.json".to_string(), &bundle.diagnostics),
+        ("logs.txt".to_string(), &bundle.logs),
+        ("sessions.json".to_string(), &bundle.sessions),
+        ("settings.toml".to_string(), &bundle.settings),
+    ];
+    entries.extend(bundle.crashes.iter().map(|(id, report)| (format!("crashes/{}.json", id), report)));
+
+    for (name, content) in entries {
+        zip.start_file(name.as_str(), options)
+            .map_err(|e| format!("Failed to add {}: {}", name, e))?;
+        zip.write_all(content.as_bytes())
+            .map_err(|e| format!("Failed to add {}: {}", name, e))?;
+    }
+    zip.finish().map_err(|e| format!("Failed to write bundle: {}", e))?;
+    Ok(())
+}
+
+/// Save the diagnostics bundle, open a pre-filled issue and reveal the bundle
+pub async fn report_problem(app: &AppHandle) -> Result<PathBuf, String> {
+    let data_dir = app.state::<SharedServerManager>().lock().await.data_dir().clone();
+    let target = dirs::download_dir()
+        .or_else(dirs::home_dir)
+        .ok_or_else(|| "No Downloads folder found".to_string())?
+        .join(format!("moneywright-diagnostics-{}.zip", chrono::Local::now().format("%Y-%m-%d-%H%M%S")));
+
+    let job = start_job(app, JobKind::Export, "Preparing problem report", false);
+    job.progress(None, Some("Running diagnostics".to_string()));
+    let report = run_diagnostics(app).await;
+
+    let mut logs = app.state::<SharedLogStore>().lock().await.get_all().join("\n");
+    logs.push('\n');
+    let sessions = app.state::<SharedSessionTracker>().lock().await.stats();
+    let settings = app.state::<SharedSettings>().lock().await.get();
+    let crashes = list_reports(&data_dir)
+        .into_iter()
+        .take(MAX_CRASH_REPORTS)
+        .filter_map(|summary| read_report(&data_dir, &summary.id).ok())
+        .filter_map(|report| Some((report.id.clone(), serde_json::to_string_pretty(&report).ok()?)))
+        .collect();
+
+    let environment = environment(app, &data_dir);
+    let bundle = Bundle {
+        environment: environment.clone(),
+        diagnostics: serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?,
+        logs,
+        sessions: serde_json::to_string_pretty(&sessions).map_err(|e| e.to_string())?,
+        settings: toml::to_string_pretty(&settings).map_err(|e| e.to_string())?,
+        crashes,
+    };
+
+    job.progress(None, Some("Writing bundle".to_string()));
+    let bundle_target = target.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let result = write_bundle(&bundle, &bundle_target);
+        if result.is_err() {
+            let _ = fs::remove_file(&bundle_target);
+        }
+        result
+    })
+    .await
+    .map_err(|e| format!("Report task failed: {}", e))
+    .and_then(|r| r);
+    job.finish(&result);
+    result?;
+
+    let bundle_name = target.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let url = Url::parse_with_params(NEW_ISSUE_URL, &[("body", issue_body(&environment, &report, &bundle_name))])
+        .map_err(|e| e.to_string())?;
+    open::that(url.as_str()).map_err(|e| format!("Failed to open browser: {}", e))?;
+    if let Some(parent) = target.parent() {
+        let _ = open::that(parent);
+    }
+    Ok(target)
+}
+
+/// Help > Report a Problem, returning the bundle path
+#[tauri::command]
+pub async fn create_problem_report(app: AppHandle) -> Result<String, String> {
+    report_problem(&app).await.map(|path| path.to_string_lossy().to_string())
+}
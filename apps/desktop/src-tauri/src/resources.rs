@@ -0,0 +1,243 @@
+// Bundled server resources (migrations and the web app) and their repair
+//
+// The server needs `drizzle/<sqlite|pg>` and `public` from the app's resource folder
+// (and `public-preview` when the release has a preview of the web app, see uibuild.rs).
+// When that folder can't be found or is incomplete (a broken or half-removed install)
+// the server used to start without MIGRATIONS_PATH and fail with a cryptic error.
+// Now the start is refused with a repair window instead.
+//
+// After every start from an intact install the resources are mirrored into
+// `<data dir>/resources` (once per version). "Re-extract Resources" makes the server
+// use that copy until the install is intact again; "Reinstall" points to the download.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use crate::server::get_data_dir;
+use crate::uibuild::PREVIEW_DIR;
+use crate::windows::open_injected_window;
+
+const MIRROR_DIR: &str = "resources";
+/// Version of the app the mirror was copied from
+const MIRROR_VERSION_FILE: &str = "version";
+/// Present while the server runs from the mirror
+const REPAIRED_FILE: &str = "repaired";
+const WINDOW_LABEL: &str = "repair";
+
+/// Paths handed to the server
+pub struct ServerResources {
+    pub migrations: PathBuf,
+    pub public: PathBuf,
+    /// Preview build of the web app, when the release has one (see uibuild.rs)
+    pub preview: Option<PathBuf>,
+}
+
+#[derive(Serialize)]
+pub struct ResourceStatus {
+    /// What is wrong with the install, None if it is intact
+    problem: Option<String>,
+    /// What is wrong with the server binary, which only reinstalling fixes
+    sidecar_problem: Option<String>,
+    /// Whether a copy from this version is available to re-extract
+    saved_copy: bool,
+    /// Whether the server currently uses that copy
+    repaired: bool,
+    version: String,
+}
+
+fn mirror_dir(app: &AppHandle) -> PathBuf {
+    get_data_dir(app).join(MIRROR_DIR)
+}
+
+fn version(app: &AppHandle) -> String {
+    app.package_info().version.to_string()
+}
+
+/// Check a resource folder has what the server needs
+fn verify(root: &Path, is_postgres: bool) -> Result<ServerResources, String> {
+    let migrations = root.join("drizzle").join(if is_postgres { "pg" } else { "sqlite" });
+    let journal = migrations.join("meta").join("_journal.json");
+    if !journal.is_file() {
+        return Err(format!("Database migrations are missing ({} not found)", journal.display()));
+    }
+    let public = root.join("public");
+    if !has_files(&public) {
+        return Err(format!("The web app files are missing ({} is empty or not found)", public.display()));
+    }
+    let preview = Some(root.join(PREVIEW_DIR)).filter(|dir| has_files(dir));
+    Ok(ServerResources { migrations, public, preview })
+}
+
+fn has_files(dir: &Path) -> bool {
+    fs::read_dir(dir).map(|mut entries| entries.next().is_some()).unwrap_or(false)
+}
+
+/// Whether the install (or the saved copy in use) has a preview build of the web app
+pub fn has_preview(app: &AppHandle) -> bool {
+    let bundled = app.path().resource_dir().is_ok_and(|root| has_files(&root.join(PREVIEW_DIR)));
+    bundled || (mirror_dir(app).join(REPAIRED_FILE).exists() && has_files(&mirror_dir(app).join(PREVIEW_DIR)))
+}
+
+/// Check the install's resource folder
+fn verify_bundled(app: &AppHandle, is_postgres: bool) -> Result<(PathBuf, ServerResources), String> {
+    let root = app
+        .path()
+        .resource_dir()
+        .map_err(|e| format!("The app's resource folder could not be located: {}", e))?;
+    let resources = verify(&root, is_postgres)?;
+    Ok((root, resources))
+}
+
+/// Whether the mirror is from this version of the app
+fn mirror_is_current(app: &AppHandle) -> bool {
+    fs::read_to_string(mirror_dir(app).join(MIRROR_VERSION_FILE)).is_ok_and(|v| v.trim() == version(app))
+}
+
+/// Copy the bundled resources into the data dir, replacing an older copy
+fn refresh_mirror(app: &AppHandle, bundled: &Path) -> Result<(), String> {
+    let mirror = mirror_dir(app);
+    let tmp = mirror.with_extension("tmp");
+    let _ = fs::remove_dir_all(&tmp);
+    for name in ["drizzle", "public", PREVIEW_DIR] {
+        if name == PREVIEW_DIR && !bundled.join(name).is_dir() {
+            continue;
+        }
+        crate::onboarding::copy_dir_recursive(&bundled.join(name), &tmp.join(name))?;
+    }
+    fs::write(tmp.join(MIRROR_VERSION_FILE), version(app))
+        .map_err(|e| format!("Failed to write {}: {}", MIRROR_VERSION_FILE, e))?;
+    let _ = fs::remove_dir_all(&mirror);
+    fs::rename(&tmp, &mirror).map_err(|e| format!("Failed to save resources: {}", e))
+}
+
+/// Resources for the server to start with, or what is wrong with the install
+pub fn locate(app: &AppHandle, is_postgres: bool) -> Result<ServerResources, String> {
+    let mirror = mirror_dir(app);
+    match verify_bundled(app, is_postgres) {
+        Ok((root, resources)) => {
+            // Intact (again): back to the install's own files
+            let _ = fs::remove_file(mirror.join(REPAIRED_FILE));
+            if !mirror_is_current(app) {
+                let app = app.clone();
+                tauri::async_runtime::spawn_blocking(move || {
+                    if let Err(e) = refresh_mirror(&app, &root) {
+                        eprintln!("Warning: Failed to save a copy of the app's resources: {}", e);
+                    }
+                });
+            }
+            Ok(resources)
+        }
+        Err(problem) => {
+            if mirror.join(REPAIRED_FILE).exists() && mirror_is_current(app) {
+                if let Ok(resources) = verify(&mirror, is_postgres) {
+                    return Ok(resources);
+                }
+            }
+            Err(problem)
+        }
+    }
+}
+
+/// State of the install for the repair window
+#[tauri::command]
+pub async fn get_resource_status(app: AppHandle) -> Result<ResourceStatus, String> {
+    let is_postgres = crate::server::read_database_url(&get_data_dir(&app)).is_some();
+    let mirror = mirror_dir(&app);
+    let sidecar_problem = tauri::async_runtime::spawn_blocking(crate::integrity::bundled_problem)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(ResourceStatus {
+        problem: verify_bundled(&app, is_postgres).err(),
+        sidecar_problem,
+        saved_copy: mirror_is_current(&app) && verify(&mirror, is_postgres).is_ok(),
+        repaired: mirror.join(REPAIRED_FILE).exists(),
+        version: version(&app),
+    })
+}
+
+/// Run the server from the saved copy of the resources
+#[tauri::command]
+pub async fn repair_resources(app: AppHandle) -> Result<(), String> {
+    let is_postgres = crate::server::read_database_url(&get_data_dir(&app)).is_some();
+    let mirror = mirror_dir(&app);
+    if !mirror_is_current(&app) {
+        return Err(format!("No saved copy of the resources of version {}", version(&app)));
+    }
+    verify(&mirror, is_postgres).map_err(|e| format!("The saved copy is incomplete too: {}", e))?;
+    fs::write(mirror.join(REPAIRED_FILE), "").map_err(|e| format!("Failed to save repair: {}", e))
+}
+
+/// Open the download page of this version to reinstall it
+#[tauri::command]
+pub async fn reinstall_app(app: AppHandle) -> Result<(), String> {
+    let url = format!("https://github.com/moneywright/moneywright/releases/tag/v{}", version(&app));
+    open::that(url).map_err(|e| format!("Failed to open the download page: {}", e))
+}
+
+/// Open the repair window
+pub fn open_repair_window(app: &AppHandle) {
+    let script = r#"
+        const tauriApi = window.__TAURI__;
+
+        document.documentElement.innerHTML = `
+<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <title>Repair Moneywright</title>
+    <style>
+        __BASE_STYLE__
+        .content { flex: 1; padding: 24px; display: flex; flex-direction: column; gap: 12px; }
+        .detail { color: #ef4444; font-family: ui-monospace, SFMono-Regular, Menlo, monospace; font-size: 12px; word-break: break-all; }
+        .actions { display: flex; gap: 8px; margin-top: 8px; }
+    </style>
+</head>
+<body>
+    <div class="content">
+        <h1>Moneywright needs repair</h1>
+        <p>Files that came with the app are missing or damaged, so the server can't start. Your data is not affected.</p>
+        <div id="problem" class="detail" role="alert"></div>
+        <p id="hint" class="muted"></p>
+        <div class="actions">
+            <button id="extractBtn" class="primary">Re-extract Resources</button>
+            <button id="reinstallBtn">Reinstall Moneywright</button>
+            <button id="doctorBtn">Run Diagnostics</button>
+        </div>
+        <div id="status" class="muted" role="status" aria-live="polite"></div>
+    </div>
+</body>
+</html>`;
+
+        const $ = id => document.getElementById(id);
+
+        async function load() {
+            const status = await tauriApi.core.invoke('get_resource_status');
+            $('problem').textContent = status.sidecar_problem || status.problem || 'The install looks intact now.';
+            $('extractBtn').disabled = !status.saved_copy || !status.problem || !!status.sidecar_problem;
+            $('hint').textContent = status.sidecar_problem
+                ? 'The server program itself is damaged or was changed, so reinstalling is the way to fix this.'
+                : status.saved_copy
+                ? 'Re-extracting uses the copy of these files saved the last time Moneywright ' + status.version + ' started.'
+                : 'There is no saved copy of these files for version ' + status.version + ', so reinstalling is the way to fix this.';
+        }
+
+        $('extractBtn').onclick = async () => {
+            $('extractBtn').disabled = true;
+            $('status').textContent = 'Restarting the server...';
+            try {
+                await tauriApi.core.invoke('repair_resources');
+                await tauriApi.core.invoke('restart_server_cmd');
+                $('status').textContent = 'Repaired. Reinstalling is still recommended.';
+            } catch (e) {
+                $('status').textContent = String(e);
+                $('extractBtn').disabled = false;
+            }
+        };
+        $('reinstallBtn').onclick = () => tauriApi.core.invoke('reinstall_app');
+        $('doctorBtn').onclick = () => tauriApi.core.invoke('open_diagnostics');
+        load();
+    "#;
+
+    open_injected_window(app, WINDOW_LABEL, "Repair Moneywright", (560.0, 340.0), false, script);
+}
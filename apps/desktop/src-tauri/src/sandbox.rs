@@ -0,0 +1,219 @@
+// Developer sandbox: a throwaway server running on a copy of a backup
+//
+// For trying rules, imports or bulk edits without risking the books. "Open in Sandbox"
+// in the backup browser copies the chosen backup into a fresh folder in the temp dir
+// and starts an extra sidecar on it, on a random port, shown in a window marked
+// SANDBOX. Like the demo (see demo.rs) the folder is deleted when the window closes or
+// the app quits, and folders left by a crash go at the next launch.
+//
+// The sandbox can't reach the real data: it only gets the copy, and its window has a
+// private cookie store, so signing in there leaves the main window's session alone.
+// Its folder has no .env, so it always runs on SQLite, even when the books are in
+// PostgreSQL, and it refuses to start when the app itself was launched with
+// DATABASE_URL set, as the server would inherit it. Only one sandbox runs at a time;
+// opening another backup replaces it.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
+use crate::backup::{find_backup, integrity_check, sqlite_db_path};
+use crate::demo::{free_port, remove_scratch_dir, remove_stale_dirs};
+use crate::logs::{log_line, SharedLogStore};
+use crate::server::{init_data_dir, start_server, stop_server, ServerManager, SharedServerManager};
+use crate::settings::SharedSettings;
+
+const WINDOW_LABEL: &str = "sandbox";
+/// Temp folders are named `<prefix><pid>` so stale ones can be matched to dead processes
+const DIR_PREFIX: &str = "moneywright-sandbox-";
+
+/// Injected into every page of the sandbox window
+const BANNER_SCRIPT: &str = r#"
+(function () {
+    function addBanner() {
+        if (document.getElementById('__moneywright_sandbox')) return;
+        const banner = document.createElement('div');
+        banner.id = '__moneywright_sandbox';
+        banner.setAttribute('role', 'note');
+        banner.textContent = 'SANDBOX · Copy of a backup · Changes are deleted when this window closes';
+        banner.style.cssText = 'position:fixed;top:0;left:0;right:0;z-index:2147483647;padding:3px 12px;text-align:center;' +
+            'background:rgba(220,38,38,0.92);color:#fff;font:600 11px -apple-system,BlinkMacSystemFont,sans-serif;' +
+            'letter-spacing:0.06em;pointer-events:none;';
+        document.body.appendChild(banner);
+    }
+    if (document.body) addBanner();
+    else document.addEventListener('DOMContentLoaded', addBanner);
+})();
+"#;
+
+struct SandboxSession {
+    manager: SharedServerManager,
+    dir: PathBuf,
+    backup: String,
+}
+
+static SESSION: Mutex<Option<SandboxSession>> = Mutex::new(None);
+
+#[derive(Serialize)]
+pub struct SandboxInfo {
+    pub url: String,
+    /// File name of the backup it was seeded from
+    pub backup: String,
+    pub data_dir: String,
+}
+
+fn session() -> std::sync::MutexGuard<'static, Option<SandboxSession>> {
+    SESSION.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn sandbox_dir() -> PathBuf {
+    std::env::temp_dir().join(format!("{}{}", DIR_PREFIX, std::process::id()))
+}
+
+/// Delete sandbox folders whose app process is gone
+pub fn remove_stale_sandbox_dirs() {
+    remove_stale_dirs(DIR_PREFIX);
+}
+
+fn show_window(app: &AppHandle, url: &str, backup: &str) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window(WINDOW_LABEL) {
+        let _ = window.show();
+        let _ = window.set_focus();
+        return Ok(());
+    }
+    let url = url.parse().map_err(|e| format!("Invalid sandbox URL: {}", e))?;
+    let builder = WebviewWindowBuilder::new(app, WINDOW_LABEL, WebviewUrl::External(url));
+    // Signing in to the sandbox must not sign the main window out, see webview.rs
+    crate::webview::isolate(crate::webview::configure(builder), sandbox_dir().join("webview"), WINDOW_LABEL)
+        .incognito(true)
+        .title(format!("SANDBOX — Moneywright ({})", backup))
+        .inner_size(1280.0, 800.0)
+        .min_inner_size(800.0, 600.0)
+        .initialization_script(BANNER_SCRIPT)
+        .build()
+        .map_err(|e| format!("Failed to open sandbox window: {}", e))?;
+    Ok(())
+}
+
+/// Copy the backup into a fresh sandbox folder
+fn seed(backup: &Path, dir: &Path, real_dir: &Path) -> Result<(), String> {
+    // Never the books' own folder, whatever the temp dir is
+    if dir.starts_with(real_dir) || real_dir.starts_with(dir) {
+        return Err(format!("The sandbox folder {} overlaps the data folder", dir.display()));
+    }
+    integrity_check(backup)?;
+    remove_scratch_dir(dir);
+    init_data_dir(&dir.to_path_buf())?;
+    let db = sqlite_db_path(dir);
+    if let Some(parent) = db.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    fs::copy(backup, &db).map_err(|e| format!("Failed to copy the backup: {}", e))?;
+    Ok(())
+}
+
+/// Start a sandbox on a copy of `backup_path`, replacing a running one
+pub async fn start(app: &AppHandle, backup_path: &str) -> Result<SandboxInfo, String> {
+    if std::env::var_os("DATABASE_URL").is_some() {
+        return Err("Moneywright was started with DATABASE_URL set, which the sandbox would use too".to_string());
+    }
+    let (real_dir, settings) = {
+        let manager = app.state::<SharedServerManager>();
+        let real_dir = manager.lock().await.data_dir().clone();
+        (real_dir, app.state::<SharedSettings>().lock().await.get())
+    };
+    let backup = find_backup(&real_dir, &settings, backup_path)?;
+    if !backup.sqlite {
+        return Err("Only SQLite backups can be opened in the sandbox".to_string());
+    }
+    end(app).await;
+
+    let log_store = app.state::<SharedLogStore>().inner().clone();
+    let dir = sandbox_dir();
+    let (source, target) = (PathBuf::from(&backup.path), dir.clone());
+    tauri::async_runtime::spawn_blocking(move || seed(&source, &target, &real_dir))
+        .await
+        .map_err(|e| format!("Copying task failed: {}", e))??;
+
+    let manager: SharedServerManager = Arc::new(tokio::sync::Mutex::new(ServerManager::for_profile(
+        dir.clone(),
+        free_port()?,
+        "Sandbox".to_string(),
+    )));
+    *session() = Some(SandboxSession {
+        manager: manager.clone(),
+        dir: dir.clone(),
+        backup: backup.file_name.clone(),
+    });
+
+    let msg = format!("Starting sandbox from {} in {}", backup.file_name, dir.display());
+    log_line(app, &log_store, msg, "info").await;
+    if let Err(e) = start_server(app.clone(), manager.clone(), log_store).await {
+        end(app).await;
+        return Err(format!("Failed to start the sandbox: {}", e));
+    }
+
+    let url = manager.lock().await.url();
+    show_window(app, &url, &backup.file_name)?;
+    Ok(SandboxInfo {
+        url,
+        backup: backup.file_name,
+        data_dir: dir.to_string_lossy().to_string(),
+    })
+}
+
+/// Stop the sandbox server and delete its folder
+pub async fn end(app: &AppHandle) {
+    let Some(sandbox) = session().take() else {
+        return;
+    };
+    if let Some(window) = app.get_webview_window(WINDOW_LABEL) {
+        let _ = window.destroy();
+    }
+    let _ = stop_server(sandbox.manager).await;
+    let _ = tauri::async_runtime::spawn_blocking(move || remove_scratch_dir(&sandbox.dir)).await;
+
+    let log_store = app.state::<SharedLogStore>().inner().clone();
+    log_line(app, &log_store, format!("Sandbox from {} closed and deleted", sandbox.backup), "info").await;
+}
+
+/// Synchronous cleanup for app exit
+pub fn end_on_exit() {
+    let Some(sandbox) = session().take() else {
+        return;
+    };
+    let _ = tauri::async_runtime::block_on(stop_server(sandbox.manager));
+    remove_scratch_dir(&sandbox.dir);
+}
+
+pub fn is_sandbox_window(label: &str) -> bool {
+    label == WINDOW_LABEL
+}
+
+/// Open a copy of a backup in a sandbox window
+#[tauri::command]
+pub async fn start_sandbox(app: AppHandle, path: String) -> Result<SandboxInfo, String> {
+    start(&app, &path).await
+}
+
+/// The running sandbox, if any
+#[tauri::command]
+pub async fn get_sandbox() -> Result<Option<SandboxInfo>, String> {
+    let Some((manager, dir, backup)) = session().as_ref().map(|s| (s.manager.clone(), s.dir.clone(), s.backup.clone())) else {
+        return Ok(None);
+    };
+    let url = manager.lock().await.url();
+    Ok(Some(SandboxInfo {
+        url,
+        backup,
+        data_dir: dir.to_string_lossy().to_string(),
+    }))
+}
+
+/// Close the sandbox and delete its data
+#[tauri::command]
+pub async fn end_sandbox(app: AppHandle) -> Result<(), String> {
+    end(&app).await;
+    Ok(())
+}
@@ -0,0 +1,656 @@
+// Background scheduler for recurring maintenance (nightly server restart, automatic backups)
+//
+// Timers are checked against the wall clock every CHECK_INTERVAL instead of sleeping
+// until the next run, since monotonic timers stop while the machine sleeps and
+// ignore clock changes. Last runs are kept in schedule.json, so runs missed while
+// asleep or closed are caught up on wake/startup (spread out by a random delay),
+// unless they were missed by more than MAX_CATCH_UP. On battery, due tasks wait
+// for mains power (see power.rs), and with a maintenance window set they wait for
+// the window, keeping the machine responsive the rest of the day. Light work (rate
+// refreshes, notifications) isn't held back.
+//
+// The nightly restart is a heavy task too, so a restart time outside the window
+// runs when the window next opens.
+
+use std::collections::hash_map::RandomState;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::hash::BuildHasher;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use chrono::{DateTime, Datelike, Local, LocalResult, Months, NaiveTime, TimeDelta, TimeZone};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tokio::sync::watch;
+use crate::backup::{backups_dir, prune_backups, run_backup};
+use crate::history;
+use crate::logs::{log_line, SharedLogStore};
+use crate::notifications::{notify, Kind};
+use crate::power::deferral_reason;
+use crate::server::SharedServerManager;
+use crate::settings::{MaintenanceSettings, Settings, SharedSettings};
+
+const SCHEDULE_FILE: &str = "schedule.json";
+/// How often the wall clock is compared against the schedule
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+/// Runs this late still count as on time (no catch-up delay)
+const ON_TIME_GRACE: Duration = Duration::from_secs(5 * 60);
+/// Runs missed by longer than this are skipped instead of caught up
+pub const MAX_CATCH_UP: Duration = Duration::from_secs(24 * 3600);
+/// Upper bound of the random delay before a catch-up run
+const MAX_JITTER: Duration = Duration::from_secs(5 * 60);
+/// How often work waiting for the window or mains power re-checks
+const WAIT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Work that must not be interrupted by a scheduled restart
+static IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+
+/// Marks a task as in flight until dropped
+pub struct InFlightGuard;
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        IN_FLIGHT.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+pub fn track_in_flight() -> InFlightGuard {
+    IN_FLIGHT.fetch_add(1, Ordering::SeqCst);
+    InFlightGuard
+}
+
+pub fn tasks_in_flight() -> usize {
+    IN_FLIGHT.load(Ordering::SeqCst)
+}
+
+// ---------------------------------------------------------------------------
+// Due calculation
+// ---------------------------------------------------------------------------
+
+/// Where a task stands relative to its latest scheduled time (unix seconds)
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Due {
+    /// Not due, or already ran for the latest occurrence
+    No,
+    OnTime(u64),
+    /// Missed (asleep, closed, clock change) but recent enough to catch up
+    Late(u64),
+    /// Missed by more than MAX_CATCH_UP, skip this occurrence
+    Stale(u64),
+}
+
+fn classify(at: u64, now: u64) -> Due {
+    let late = now.saturating_sub(at);
+    if late <= ON_TIME_GRACE.as_secs() {
+        Due::OnTime(at)
+    } else if late < MAX_CATCH_UP.as_secs() {
+        Due::Late(at)
+    } else {
+        Due::Stale(at)
+    }
+}
+
+/// Due state of a task that runs every `interval` seconds; never-run tasks are due now
+pub fn interval_due(last_run: Option<u64>, interval: u64, now: u64) -> Due {
+    let Some(last) = last_run else {
+        return Due::OnTime(now);
+    };
+    if interval == 0 || now < last + interval {
+        return Due::No;
+    }
+    // Only the latest missed occurrence counts, earlier ones are folded into it
+    classify(last + (now - last) / interval * interval, now)
+}
+
+/// Latest occurrence of local `time` at or before `now`
+///
+/// A time skipped by a DST jump runs right after the jump, and a time that occurs
+/// twice when clocks go back runs at its first occurrence only.
+fn last_daily<Tz: TimeZone>(time: NaiveTime, now: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+    let zone = now.timezone();
+    let mut date = now.date_naive();
+    for _ in 0..3 {
+        let at = match date.and_time(time).and_local_timezone(zone.clone()) {
+            LocalResult::Single(at) => Some(at),
+            LocalResult::Ambiguous(first, _) => Some(first),
+            LocalResult::None => (date.and_time(time) + TimeDelta::hours(1))
+                .and_local_timezone(zone.clone())
+                .earliest(),
+        };
+        if let Some(at) = at.filter(|at| at <= now) {
+            return Some(at);
+        }
+        date = date.pred_opt()?;
+    }
+    None
+}
+
+/// Latest start of day `day` (1-28) of a month at or before `now`
+fn last_monthly<Tz: TimeZone>(day: u32, now: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+    let zone = now.timezone();
+    let today = now.date_naive();
+    let this_month = today.with_day(day)?;
+    let date = if this_month <= today {
+        this_month
+    } else {
+        this_month.checked_sub_months(Months::new(1))?
+    };
+    // Midnight can be skipped by a DST jump
+    date.and_hms_opt(0, 0, 0)?
+        .and_local_timezone(zone.clone())
+        .earliest()
+        .or_else(|| date.and_hms_opt(1, 0, 0)?.and_local_timezone(zone).earliest())
+}
+
+/// Due state of a task that runs at the start of day `day` of each month; never-run
+/// tasks are due now. A missed month is caught up however late, never Stale, since
+/// its run is still wanted afterwards.
+pub fn monthly_due(last_run: Option<u64>, day: u32, now: u64) -> Due {
+    let Some(last) = last_run else {
+        return Due::OnTime(now);
+    };
+    let Some(now_local) = DateTime::from_timestamp(now as i64, 0).map(|t| t.with_timezone(&Local)) else {
+        return Due::No;
+    };
+    match last_monthly(day, &now_local) {
+        Some(at) if (at.timestamp() as u64) > last => match classify(at.timestamp() as u64, now) {
+            Due::Stale(at) => Due::Late(at),
+            due => due,
+        },
+        _ => Due::No,
+    }
+}
+
+/// Due state of a task that runs daily at local `time`
+fn daily_due<Tz: TimeZone>(time: NaiveTime, last_run: u64, now: &DateTime<Tz>) -> Due {
+    match last_daily(time, now) {
+        Some(at) if (at.timestamp() as u64) > last_run => classify(at.timestamp() as u64, now.timestamp() as u64),
+        _ => Due::No,
+    }
+}
+
+/// Holds late tasks back for a random delay, so catch-ups after wake don't all
+/// start at once
+#[derive(Default)]
+pub struct CatchUp {
+    run_at: HashMap<String, u64>,
+    hasher: RandomState,
+}
+
+impl CatchUp {
+    /// Whether a task with this due state should run now
+    pub fn ready(&mut self, key: &str, due: Due, now: u64) -> bool {
+        match due {
+            Due::OnTime(_) => {
+                self.run_at.remove(key);
+                true
+            }
+            Due::Late(_) => {
+                let jitter = self.hasher.hash_one(key) % MAX_JITTER.as_secs();
+                let run_at = *self.run_at.entry(key.to_string()).or_insert(now + jitter);
+                if now >= run_at {
+                    self.run_at.remove(key);
+                }
+                now >= run_at
+            }
+            Due::No | Due::Stale(_) => {
+                self.run_at.remove(key);
+                false
+            }
+        }
+    }
+}
+
+/// "3 h" / "25 min" for log lines
+pub fn describe_delay(secs: u64) -> String {
+    if secs >= 3600 {
+        format!("{} h", secs / 3600)
+    } else {
+        format!("{} min", secs / 60)
+    }
+}
+
+pub fn format_local(timestamp: u64) -> String {
+    DateTime::from_timestamp(timestamp as i64, 0)
+        .map(|t| t.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default()
+}
+
+// ---------------------------------------------------------------------------
+// Heavy work
+// ---------------------------------------------------------------------------
+
+/// Why heavy work waits for the maintenance window, None inside it or without one
+fn window_deferral(settings: &MaintenanceSettings, now: NaiveTime) -> Option<String> {
+    if !settings.window {
+        return None;
+    }
+    let start = NaiveTime::parse_from_str(&settings.window_start, "%H:%M").ok()?;
+    let end = NaiveTime::parse_from_str(&settings.window_end, "%H:%M").ok()?;
+    let inside = if start <= end { start <= now && now < end } else { now >= start || now < end };
+    (!inside).then(|| format!("outside the maintenance window ({}-{})", settings.window_start, settings.window_end))
+}
+
+/// Why heavy work (backups, restarts, exports, update downloads) should wait right now
+pub fn heavy_work_deferral(settings: &Settings) -> Option<String> {
+    window_deferral(&settings.maintenance, Local::now().time()).or_else(|| deferral_reason(&settings.power))
+}
+
+/// Wait until heavy work may run, logging once if it has to wait
+pub async fn wait_for_heavy_work(app: &AppHandle, task: &str) {
+    let mut logged = false;
+    loop {
+        let settings = app.state::<SharedSettings>().lock().await.get();
+        let Some(reason) = heavy_work_deferral(&settings) else {
+            return;
+        };
+        if !logged {
+            let log_store = app.state::<SharedLogStore>().inner().clone();
+            log_line(app, &log_store, format!("{} deferred: {}", task, reason), "info").await;
+            logged = true;
+        }
+        tokio::time::sleep(WAIT_INTERVAL).await;
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tasks
+// ---------------------------------------------------------------------------
+
+#[derive(Clone, Copy)]
+enum Task {
+    NightlyRestart,
+    Backup,
+}
+
+const TASKS: [Task; 2] = [Task::NightlyRestart, Task::Backup];
+
+#[derive(Clone, Copy, PartialEq)]
+enum Plan {
+    Daily(NaiveTime),
+    Every(u64),
+}
+
+impl Plan {
+    /// Stored with the last run; a changed plan starts counting from now
+    fn id(self) -> String {
+        match self {
+            Plan::Daily(time) => format!("daily {}", time.format("%H:%M")),
+            Plan::Every(secs) => format!("every {}s", secs),
+        }
+    }
+
+    fn due(self, last_run: u64, now: DateTime<Local>) -> Due {
+        match self {
+            Plan::Daily(time) => daily_due(time, last_run, &now),
+            Plan::Every(secs) => interval_due(Some(last_run), secs, now.timestamp() as u64),
+        }
+    }
+}
+
+impl Task {
+    fn key(self) -> &'static str {
+        match self {
+            Task::NightlyRestart => "nightly_restart",
+            Task::Backup => "backup",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Task::NightlyRestart => "Nightly restart",
+            Task::Backup => "Automatic backup",
+        }
+    }
+
+    fn plan(self, settings: &Settings) -> Option<Plan> {
+        match self {
+            Task::NightlyRestart if settings.server.nightly_restart => {
+                NaiveTime::parse_from_str(&settings.server.nightly_restart_time, "%H:%M")
+                    .ok()
+                    .map(Plan::Daily)
+            }
+            Task::Backup if settings.backups.enabled => Some(Plan::Every(settings.backups.interval_hours as u64 * 3600)),
+            _ => None,
+        }
+    }
+
+    async fn run(self, app: &AppHandle, settings: &Settings) {
+        match self {
+            Task::NightlyRestart => nightly_restart(app).await,
+            Task::Backup => automatic_backup(app, settings).await,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct TaskRecord {
+    last_run: u64,
+    plan: String,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct ScheduleFile {
+    tasks: BTreeMap<String, TaskRecord>,
+}
+
+fn schedule_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(SCHEDULE_FILE)
+}
+
+fn read_schedule(data_dir: &Path) -> ScheduleFile {
+    fs::read_to_string(schedule_path(data_dir))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn write_schedule(data_dir: &Path, schedule: &ScheduleFile) {
+    let result = serde_json::to_string_pretty(schedule)
+        .map_err(|e| e.to_string())
+        .and_then(|json| fs::write(schedule_path(data_dir), json).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        eprintln!("Warning: Failed to save schedule: {}", e);
+    }
+}
+
+async fn nightly_restart(app: &AppHandle) {
+    let manager = app.state::<SharedServerManager>().inner().clone();
+    let log_store = app.state::<SharedLogStore>().inner().clone();
+    let run = history::begin("restart", "Nightly restart");
+
+    if !manager.lock().await.is_running() {
+        log_line(app, &log_store, "Nightly restart skipped: server is not running", "info").await;
+        run.skip("Server is not running");
+        return;
+    }
+
+    let in_flight = tasks_in_flight();
+    if in_flight > 0 {
+        let msg = format!("Nightly restart skipped: {} job(s) in progress", in_flight);
+        log_line(app, &log_store, msg, "info").await;
+        run.skip(format!("{} job(s) in progress", in_flight));
+        return;
+    }
+
+    log_line(app, &log_store, "Running scheduled nightly restart", "info").await;
+    let result = crate::restart_server(app.clone(), manager, log_store).await;
+    run.finish(&result);
+    let body = match result {
+        Ok(()) => "The server was restarted as scheduled.".to_string(),
+        Err(e) => format!("Scheduled restart failed: {}", e),
+    };
+
+    notify(app, Kind::Maintenance, "Moneywright", body).await;
+}
+
+async fn automatic_backup(app: &AppHandle, settings: &Settings) {
+    let log_store = app.state::<SharedLogStore>().inner().clone();
+    let data_dir = app.state::<SharedServerManager>().lock().await.data_dir().clone();
+    let dir = backups_dir(&data_dir, settings);
+
+    let result = run_backup(app, data_dir, dir.clone())
+        .await
+        .and_then(|path| prune_backups(&dir, settings.backups.keep as usize).map(|removed| (path, removed)));
+    crate::quarantine::purge(settings.backups.quarantine_days);
+    match result {
+        Ok((path, removed)) => {
+            let msg = format!("Automatic backup saved to {} ({} old backup(s) moved to the quarantine)", path.display(), removed);
+            log_line(app, &log_store, msg, "info").await;
+        }
+        Err(e) => {
+            log_line(app, &log_store, format!("Automatic backup failed: {}", e), "error").await;
+            notify(app, Kind::Maintenance, "Moneywright", format!("Automatic backup failed: {}", e)).await;
+        }
+    }
+}
+
+/// Run whatever is due, recording last runs in schedule.json. `deferred` holds the
+/// tasks already logged as waiting, with the reason.
+async fn run_due(
+    app: &AppHandle,
+    data_dir: &Path,
+    settings: &Settings,
+    catch_up: &mut CatchUp,
+    deferred: &mut HashMap<&'static str, String>,
+) {
+    let log_store = app.state::<SharedLogStore>().inner().clone();
+    let mut schedule = read_schedule(data_dir);
+    let mut changed = false;
+
+    for task in TASKS {
+        let Some(plan) = task.plan(settings) else {
+            catch_up.ready(task.key(), Due::No, 0);
+            continue;
+        };
+        let now = Local::now();
+        let now_secs = now.timestamp() as u64;
+
+        // Newly enabled or rescheduled tasks count from now rather than running
+        // at once. A last run in the future means the clock went back.
+        let record = match schedule.tasks.get(task.key()) {
+            Some(record) if record.plan == plan.id() && record.last_run <= now_secs => record.clone(),
+            _ => {
+                schedule.tasks.insert(task.key().to_string(), TaskRecord { last_run: now_secs, plan: plan.id() });
+                changed = true;
+                continue;
+            }
+        };
+
+        let due = plan.due(record.last_run, now);
+        if let Due::Stale(at) = due {
+            let msg = match deferred.remove(task.key()) {
+                // Waited so long it's now too late; worth telling the user
+                Some(reason) => {
+                    let msg = format!(
+                        "{} skipped: it was due at {} and waited more than {} ({})",
+                        task.label(),
+                        format_local(at),
+                        describe_delay(MAX_CATCH_UP.as_secs()),
+                        reason
+                    );
+                    notify(app, Kind::Maintenance, "Moneywright", msg.clone()).await;
+                    msg
+                }
+                None => format!(
+                    "{} skipped: it was due at {} and missed by more than {}",
+                    task.label(),
+                    format_local(at),
+                    describe_delay(MAX_CATCH_UP.as_secs())
+                ),
+            };
+            log_line(app, &log_store, msg, "info").await;
+            schedule.tasks.insert(task.key().to_string(), TaskRecord { last_run: at, plan: plan.id() });
+            changed = true;
+            continue;
+        }
+        if !catch_up.ready(task.key(), due, now_secs) {
+            continue;
+        }
+        // Heavy tasks wait for the window and mains power; the record stays put so they run then
+        if let Some(reason) = heavy_work_deferral(settings) {
+            if deferred.insert(task.key(), reason.clone()).is_none() {
+                log_line(app, &log_store, format!("{} deferred: {}", task.label(), reason), "info").await;
+            }
+            continue;
+        }
+        deferred.remove(task.key());
+        if let Due::Late(at) = due {
+            let msg = format!("Catching up on {} missed at {}", task.label().to_lowercase(), format_local(at));
+            log_line(app, &log_store, msg, "info").await;
+        }
+
+        // Record first, so a restart or crash mid-task doesn't repeat it
+        schedule.tasks.insert(task.key().to_string(), TaskRecord { last_run: now_secs, plan: plan.id() });
+        write_schedule(data_dir, &schedule);
+        changed = false;
+        history::scheduled(task.run(app, settings)).await;
+    }
+
+    if changed {
+        write_schedule(data_dir, &schedule);
+    }
+}
+
+/// Run scheduled tasks, re-checking whenever settings change
+pub fn start_scheduler(app: AppHandle, mut settings_rx: watch::Receiver<Settings>) {
+    tauri::async_runtime::spawn(async move {
+        let data_dir = app.state::<SharedServerManager>().lock().await.data_dir().clone();
+        let log_store = app.state::<SharedLogStore>().inner().clone();
+        let mut catch_up = CatchUp::default();
+        let mut deferred = HashMap::new();
+        // Battery state for the first check, which may catch up on missed runs
+        crate::power::refresh().await;
+        let mut last_check = Local::now();
+
+        loop {
+            // A long gap between checks means the machine slept or the clock changed
+            let gap = (Local::now() - last_check).num_seconds();
+            if gap > 3 * CHECK_INTERVAL.as_secs() as i64 || gap < 0 {
+                let msg = if gap < 0 {
+                    format!("Clock moved back by {}, re-checking schedule", describe_delay(gap.unsigned_abs()))
+                } else {
+                    format!("Resumed after {}, checking for missed tasks", describe_delay(gap as u64))
+                };
+                log_line(&app, &log_store, msg, "info").await;
+            }
+
+            let settings = settings_rx.borrow_and_update().clone();
+            run_due(&app, &data_dir, &settings, &mut catch_up, &mut deferred).await;
+            last_check = Local::now();
+
+            tokio::select! {
+                _ = tokio::time::sleep(CHECK_INTERVAL) => {}
+                changed = settings_rx.changed() => {
+                    if changed.is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use chrono_tz::America::{New_York, Sao_Paulo};
+
+    fn time(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn utc(y: i32, m: u32, d: u32, h: u32, min: u32) -> i64 {
+        Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap().timestamp()
+    }
+
+    #[test]
+    fn classifies_by_lateness() {
+        let at = 1_000_000;
+        assert_eq!(classify(at, at), Due::OnTime(at));
+        assert_eq!(classify(at, at + ON_TIME_GRACE.as_secs()), Due::OnTime(at));
+        assert_eq!(classify(at, at + ON_TIME_GRACE.as_secs() + 1), Due::Late(at));
+        assert_eq!(classify(at, at + MAX_CATCH_UP.as_secs() - 1), Due::Late(at));
+        assert_eq!(classify(at, at + MAX_CATCH_UP.as_secs()), Due::Stale(at));
+        // A clock that went back doesn't make a run late
+        assert_eq!(classify(at, at - 60), Due::OnTime(at));
+    }
+
+    #[test]
+    fn interval_counts_only_the_latest_missed_run() {
+        assert_eq!(interval_due(None, 3600, 500), Due::OnTime(500));
+        assert_eq!(interval_due(Some(1000), 3600, 4000), Due::No);
+        assert_eq!(interval_due(Some(1000), 3600, 4600), Due::OnTime(4600));
+        assert_eq!(interval_due(Some(1000), 3600, 12_000), Due::OnTime(11_800));
+        assert_eq!(interval_due(Some(1000), 3600, 12_600), Due::Late(11_800));
+        assert_eq!(interval_due(Some(1000), 0, 99_999), Due::No);
+    }
+
+    #[test]
+    fn last_daily_is_today_or_yesterday() {
+        let morning = New_York.with_ymd_and_hms(2024, 6, 1, 10, 0, 0).unwrap();
+        let night = New_York.with_ymd_and_hms(2024, 6, 1, 2, 0, 0).unwrap();
+        assert_eq!(last_daily(time(3, 0), &morning), Some(New_York.with_ymd_and_hms(2024, 6, 1, 3, 0, 0).unwrap()));
+        assert_eq!(last_daily(time(3, 0), &night), Some(New_York.with_ymd_and_hms(2024, 5, 31, 3, 0, 0).unwrap()));
+    }
+
+    #[test]
+    fn last_daily_runs_a_skipped_time_after_the_jump() {
+        // 02:30 doesn't exist on 2024-03-10 in New York (02:00 -> 03:00)
+        let now = New_York.with_ymd_and_hms(2024, 3, 10, 12, 0, 0).unwrap();
+        let at = last_daily(time(2, 30), &now).unwrap();
+        assert_eq!(at, New_York.with_ymd_and_hms(2024, 3, 10, 3, 30, 0).unwrap());
+
+        let last_run = New_York.with_ymd_and_hms(2024, 3, 9, 2, 30, 0).unwrap().timestamp() as u64;
+        let just_after = New_York.with_ymd_and_hms(2024, 3, 10, 3, 31, 0).unwrap();
+        assert_eq!(daily_due(time(2, 30), last_run, &just_after), Due::OnTime(at.timestamp() as u64));
+    }
+
+    #[test]
+    fn last_daily_runs_a_repeated_time_once() {
+        // 01:30 happens twice on 2024-11-03 in New York (02:00 EDT -> 01:00 EST)
+        let now = New_York.with_ymd_and_hms(2024, 11, 3, 12, 0, 0).unwrap();
+        let first = last_daily(time(1, 30), &now).unwrap();
+        assert_eq!(first.timestamp(), utc(2024, 11, 3, 5, 30));
+
+        let second = New_York.with_ymd_and_hms(2024, 11, 3, 1, 30, 0).latest().unwrap();
+        assert_eq!(second.timestamp(), utc(2024, 11, 3, 6, 30));
+        assert_eq!(daily_due(time(1, 30), first.timestamp() as u64, &second), Due::No);
+    }
+
+    #[test]
+    fn last_monthly_picks_this_or_last_month() {
+        let after = New_York.with_ymd_and_hms(2024, 6, 20, 9, 0, 0).unwrap();
+        let before = New_York.with_ymd_and_hms(2024, 6, 10, 9, 0, 0).unwrap();
+        let on_the_day = New_York.with_ymd_and_hms(2024, 6, 15, 0, 0, 0).unwrap();
+        assert_eq!(last_monthly(15, &after), Some(on_the_day));
+        assert_eq!(last_monthly(15, &before), Some(New_York.with_ymd_and_hms(2024, 5, 15, 0, 0, 0).unwrap()));
+        assert_eq!(last_monthly(15, &on_the_day), Some(on_the_day));
+    }
+
+    #[test]
+    fn last_monthly_survives_a_skipped_midnight() {
+        // Brazil started DST at midnight on 2018-11-04, so the day began at 01:00
+        let now = Sao_Paulo.with_ymd_and_hms(2018, 11, 10, 12, 0, 0).unwrap();
+        assert_eq!(last_monthly(4, &now), Some(Sao_Paulo.with_ymd_and_hms(2018, 11, 4, 1, 0, 0).unwrap()));
+    }
+
+    #[test]
+    fn catch_up_runs_on_time_tasks_at_once() {
+        let mut catch_up = CatchUp::default();
+        assert!(catch_up.ready("backup", Due::OnTime(100), 100));
+        assert!(!catch_up.ready("backup", Due::No, 100));
+        assert!(!catch_up.ready("backup", Due::Stale(100), 100_000));
+    }
+
+    #[test]
+    fn catch_up_holds_late_tasks_for_a_stable_delay() {
+        let mut catch_up = CatchUp::default();
+        let jitter = catch_up.hasher.hash_one("backup") % MAX_JITTER.as_secs();
+        let now = 1_000_000;
+        if jitter > 0 {
+            assert!(!catch_up.ready("backup", Due::Late(0), now));
+            // Re-checking doesn't draw a new delay
+            assert!(!catch_up.ready("backup", Due::Late(0), now + jitter - 1));
+        }
+        assert!(catch_up.ready("backup", Due::Late(0), now + jitter));
+        // The delay is forgotten once run, so the next late run waits again from then
+        let later = now + 10 * MAX_JITTER.as_secs();
+        assert_eq!(catch_up.ready("backup", Due::Late(0), later), jitter == 0);
+        assert!(catch_up.ready("backup", Due::Late(0), later + MAX_JITTER.as_secs()));
+    }
+
+    #[test]
+    fn catch_up_forgets_a_delay_when_the_task_is_no_longer_late() {
+        let mut catch_up = CatchUp::default();
+        let jitter = catch_up.hasher.hash_one("export") % MAX_JITTER.as_secs();
+        catch_up.ready("export", Due::Late(0), 0);
+        assert!(!catch_up.ready("export", Due::Stale(0), 1));
+        // A fresh delay starts at the next late check
+        let now = 5 * MAX_JITTER.as_secs();
+        assert_eq!(catch_up.ready("export", Due::Late(0), now), jitter == 0);
+        assert!(catch_up.ready("export", Due::Late(0), now + jitter));
+    }
+}
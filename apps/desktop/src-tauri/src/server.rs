@@ -1,126 +1,222 @@
 // Server process manager for the Moneywright sidecar binary
+//
+// A server that crashes after coming up is started again by `supervise`, waiting
+// CRASH_BACKOFF, then twice as long before each further attempt, up to
+// `server.crash_restart_max` attempts. A server that stayed up for STABLE_AFTER
+// starts counting again from the first attempt.
+//
+// A hung server is still a live process, so it used to show "running" forever. The
+// liveness watchdog pings the main server's /health every
+// `server.liveness_interval_secs` while it's Running; after
+// `server.liveness_failures` misses in a row it's put in the Error status,
+// `server-unhealthy` is emitted and, with `server.crash_restart`, it's restarted
+// like a crashed one. Without the restart it goes back to Running if it answers again.
+//
+// A started server counts as up once its /health answers, polled from READY_PROBE and
+// backing off to READY_PROBE_MAX; a "Listening on" line in its output still counts too,
+// for builds that log it before the probe gets through.
+//
+// A start fails if the server isn't up within `server.startup_timeout_secs`. Pending
+// migrations can take far longer (a large PostgreSQL database on first run), so while
+// the server reports applying them the deadline is `server.migration_timeout_secs`
+// instead, restarting the normal one once they're done, and `server-migrating` lets the
+// splash screen say so.
+//
+// Each step of a start is also reported as `server-startup-progress`: preparing the
+// data folder, the process spawned, migrations being applied and applied, the server
+// listening, and ready once the version handshake is done. Migrations come from the
+// `[DB]` lines the server prints, listening from its /health or its output.
+//
+// Pausing stops the main server the same way but keeps the app open, for laptops
+// where it shouldn't run all day. The status is Paused rather than Stopped, so nothing
+// that restarts a running server (settings changes, the nightly restart) brings it
+// back; only resuming does.
+//
+// The idle tracker pauses the main server by itself once the main window has been
+// hidden (closed to the dock on macOS, or minimized) for
+// `server.pause_after_hidden_minutes`, unless jobs are running or other devices use
+// the server. Showing the window again resumes it. With `server.lazy_start` a launch
+// with the window hidden doesn't start the server at all until it's shown. A server
+// paused by hand stays paused either way.
+//
+// A start holds `start_lock` from beginning to end, so starts don't overlap, but lets go
+// of the manager itself during slow steps (stopping leftover servers, snapshots before
+// an upgrade), so status queries and the tray don't wait on them. A stop in the
+// meantime cancels the start.
+//
+// Stopping is graceful: the server gets SIGTERM (on Windows, a request to
+// `/internal/shutdown` carrying the token it was started with) and
+// `server.shutdown_grace_secs` to finish its writes before it's killed.
+//
+// With `server.transport = "socket"` the main server listens on a Unix socket and
+// its URL is mwapp://localhost, see transport.rs.
 
 use std::fs;
-use std::path::PathBuf;
-use std::process::Command;
-use std::sync::Arc;
-use std::time::Duration;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
 use tokio::sync::Mutex;
-use tauri::{AppHandle, Emitter, Manager};
+use tauri::Manager;
 use tauri_plugin_shell::process::{CommandChild, CommandEvent};
 use tauri_plugin_shell::ShellExt;
-use serde::Serialize;
-use crate::SharedLogStore;
+use crate::arch;
+use crate::avcheck;
+use crate::database::server_database_url;
+use crate::diskspace::{self, Operation};
+use crate::events::{self, Event};
+use crate::flags::enabled_flag_keys;
+use crate::integrity;
+use crate::isolation;
+use crate::lan;
+use crate::loglevel;
+use crate::notifications::{self, Kind};
+use crate::logs::{self, log_line, SharedLogStore};
+use crate::portconflict;
+use crate::ports::{self, Holder};
+use crate::orphans;
+use crate::postgres;
+use crate::proxy;
+use crate::recovery;
+use crate::remote;
+use crate::resources;
+use crate::service;
+use crate::sessions::SharedSessionTracker;
+use crate::settings::SharedSettings;
+use crate::tls;
+use crate::transport;
+use crate::upgrade;
+use crate::webcache;
 
+/// Default port of the main server; on a shared computer another OS user may have it, see isolation.rs
+pub const SERVER_PORT: u16 = 17777;
+/// Port of this OS user's main server, settled at startup
+static MAIN_PORT: OnceLock<u16> = OnceLock::new();
+/// The main server is paused, for the menu (which can't wait for the manager)
+static PAUSED: AtomicBool = AtomicBool::new(false);
+/// Paused (or not started yet) by the idle tracker, so showing the main window starts it
+static IDLE_PAUSED: AtomicBool = AtomicBool::new(false);
+const FEATURES_ENV: &str = "MONEYWRIGHT_FEATURES";
+/// Used until settings are loaded
+const DEFAULT_STARTUP_TIMEOUT: Duration = Duration::from_secs(30);
+const DEFAULT_MIGRATION_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+/// Server output around pending migrations, "[DB] Applying 3 migrations..."
+const MIGRATING_PREFIX: &str = "[DB] Applying ";
+const MIGRATED_LINE: &str = "[DB] Migrations applied";
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(3);
+/// First readiness probe of a starting server, doubled up to READY_PROBE_MAX
+const READY_PROBE: Duration = Duration::from_millis(100);
+const READY_PROBE_MAX: Duration = Duration::from_secs(1);
+/// Wait before the first crash restart, doubled for each further attempt
+const CRASH_BACKOFF: Duration = Duration::from_secs(2);
+const MAX_CRASH_BACKOFF: Duration = Duration::from_secs(60);
+/// Up this long, a server's next crash starts over at the first attempt
+const STABLE_AFTER: Duration = Duration::from_secs(5 * 60);
+/// Used until settings are loaded
+const DEFAULT_SHUTDOWN_GRACE: Duration = Duration::from_secs(10);
+
+/// Error status of a server that stopped answering the liveness pings
+const UNRESPONSIVE: &str = "The server stopped responding to health checks";
+/// How often a turned-off liveness watchdog looks at the settings again
+const LIVENESS_IDLE: Duration = Duration::from_secs(60);
+/// How often the idle tracker looks at the main window
+const IDLE_POLL: Duration = Duration::from_secs(30);
+
+/// Payload of the `server-unhealthy` event
 #[derive(Clone, Serialize)]
-struct LogPayload {
-    message: String,
-    log_type: String,
+pub struct Unhealthy {
+    /// Pings missed in a row
+    pub failures: u32,
+    pub interval_secs: u64,
+    /// Whether it's being restarted (`server.crash_restart`)
+    pub restarting: bool,
 }
 
-/// Emit a log message to the frontend and store it
-fn emit_log(app: &AppHandle, message: &str, log_type: &str) {
-    let _ = app.emit("server-log", LogPayload {
-        message: message.to_string(),
-        log_type: log_type.to_string(),
-    });
+/// Payload of the `server-migrating` event
+#[derive(Clone, Serialize)]
+pub struct MigrationProgress {
+    /// Extra profile the server belongs to, None for the main one
+    pub profile: Option<String>,
+    /// Migrations being applied, when the server said
+    pub pending: Option<u32>,
+    /// False when they start, true once they're applied
+    pub done: bool,
 }
 
-/// Store a log message
-async fn store_log(log_store: &SharedLogStore, message: &str) {
-    let mut store = log_store.lock().await;
-    store.add(message.to_string());
+/// Payload of the `server-startup-progress` event
+#[derive(Clone, Serialize)]
+pub struct StartupProgress {
+    /// Extra profile the server belongs to, None for the main one
+    pub profile: Option<String>,
+    /// "data_dir", "spawned", "migrating", "migrated", "listening" or "ready"
+    pub phase: &'static str,
+    /// Migrations being applied, when the server said (with "migrating")
+    pub pending: Option<u32>,
+    /// Time since the start began
+    pub elapsed_ms: u64,
 }
 
-pub const SERVER_PORT: u16 = 17777;
-const STARTUP_TIMEOUT: Duration = Duration::from_secs(30);
-
-/// Kill any process listening on the server port
-/// This ensures we don't have orphaned processes from previous runs
-pub fn kill_process_on_port(port: u16) -> Result<(), String> {
-    #[cfg(target_os = "macos")]
-    {
-        // On macOS, use lsof to find ONLY the process LISTENING on the port
-        // Using -sTCP:LISTEN to filter out client connections
-        let output = Command::new("lsof")
-            .args(["-ti", &format!("tcp:{}", port), "-sTCP:LISTEN"])
-            .output()
-            .map_err(|e| format!("Failed to run lsof: {}", e))?;
-
-        if output.status.success() {
-            let pids = String::from_utf8_lossy(&output.stdout);
-            for pid in pids.lines() {
-                let pid = pid.trim();
-                if !pid.is_empty() {
-                    println!("Killing server process {} on port {}", pid, port);
-                    let _ = Command::new("kill")
-                        .args(["-9", pid])
-                        .output();
-                }
-            }
-            // Give the OS a moment to release the port
-            std::thread::sleep(Duration::from_millis(500));
-        }
-    }
-
-    #[cfg(target_os = "linux")]
-    {
-        // On Linux, use ss to find ONLY the process LISTENING on the port
-        let output = Command::new("ss")
-            .args(["-tlnp", &format!("sport = :{}", port)])
-            .output();
-
-        if let Ok(output) = output {
-            if output.status.success() {
-                let lines = String::from_utf8_lossy(&output.stdout);
-                // Parse ss output to extract PID (format: pid=1234)
-                for line in lines.lines() {
-                    if let Some(pid_start) = line.find("pid=") {
-                        let pid_str = &line[pid_start + 4..];
-                        if let Some(end) = pid_str.find(|c: char| !c.is_ascii_digit()) {
-                            let pid = &pid_str[..end];
-                            if !pid.is_empty() {
-                                println!("Killing server process {} on port {}", pid, port);
-                                let _ = Command::new("kill")
-                                    .args(["-9", pid])
-                                    .output();
-                            }
-                        }
-                    }
-                }
-                std::thread::sleep(Duration::from_millis(500));
-            }
-        }
-    }
+/// Payload of the `server-restart-attempt` event
+#[derive(Clone, Serialize)]
+pub struct RestartAttempt {
+    /// Extra profile the server belongs to, None for the main one
+    pub profile: Option<String>,
+    pub attempt: u32,
+    pub max_attempts: u32,
+    /// Wait before this attempt
+    pub delay_secs: u64,
+}
 
-    #[cfg(target_os = "windows")]
-    {
-        // On Windows, use netstat to find ONLY LISTENING processes
-        let output = Command::new("cmd")
-            .args(["/C", &format!("netstat -ano | findstr :{} | findstr LISTENING", port)])
-            .output()
-            .map_err(|e| format!("Failed to run netstat: {}", e))?;
-
-        if output.status.success() {
-            let lines = String::from_utf8_lossy(&output.stdout);
-            for line in lines.lines() {
-                let parts: Vec<&str> = line.split_whitespace().collect();
-                if let Some(pid) = parts.last() {
-                    if let Ok(pid_num) = pid.parse::<u32>() {
-                        if pid_num > 0 {
-                            println!("Killing server process {} on port {}", pid, port);
-                            let _ = Command::new("taskkill")
-                                .args(["/F", "/PID", pid])
-                                .output();
-                        }
-                    }
-                }
-            }
-            std::thread::sleep(Duration::from_millis(500));
-        }
-    }
+#[derive(Deserialize)]
+pub struct HealthDatabase {
+    #[serde(rename = "type")]
+    pub db_type: String,
+    pub connected: bool,
+}
 
-    Ok(())
+/// Response of the server's `/health` endpoint
+#[derive(Deserialize)]
+pub struct HealthResponse {
+    pub status: String,
+    pub version: Option<String>,
+    pub database: Option<HealthDatabase>,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct InfoDatabase {
+    #[serde(rename = "type")]
+    pub db_type: String,
+}
+
+/// Response of the server's `/info` endpoint
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerBuild {
+    pub version: String,
+    pub database: InfoDatabase,
+    /// Applied migrations, None if the server couldn't read them
+    pub schema_version: Option<usize>,
+    pub runtime: Option<String>,
+}
+
+/// Query `/info` on a server base URL, None if it doesn't answer (or predates the endpoint)
+pub async fn fetch_build(base_url: &str) -> Option<ServerBuild> {
+    let client = reqwest::Client::builder().timeout(HEALTH_TIMEOUT).build().ok()?;
+    let response = transport::send(client.get(format!("{}/info", base_url)).timeout(HEALTH_TIMEOUT))
+        .await
+        .ok()?;
+    response.json::<ServerBuild>().await.ok()
+}
+
+/// Query `/health` on a server base URL, None if it doesn't answer
+pub async fn fetch_health(base_url: &str) -> Option<HealthResponse> {
+    let client = reqwest::Client::builder().timeout(HEALTH_TIMEOUT).build().ok()?;
+    let response = transport::send(client.get(format!("{}/health", base_url)).timeout(HEALTH_TIMEOUT))
+        .await
+        .ok()?;
+    response.json::<HealthResponse>().await.ok()
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -128,13 +224,53 @@ pub enum ServerStatus {
     Starting,
     Running,
     Stopped,
+    /// Stopped by the user, who resumes it from the menu or the paused page
+    Paused,
     Error(String),
 }
 
+impl ServerStatus {
+    /// Status name as sent to the frontend
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ServerStatus::Starting => "starting",
+            ServerStatus::Running => "running",
+            ServerStatus::Stopped => "stopped",
+            ServerStatus::Paused => "paused",
+            ServerStatus::Error(_) => "error",
+        }
+    }
+}
+
 pub struct ServerManager {
     child: Option<CommandChild>,
     status: ServerStatus,
     data_dir: PathBuf,
+    port: u16,
+    /// Name of the extra profile this server belongs to (None for the main one)
+    profile: Option<String>,
+    /// Additional environment for the sidecar
+    env: Vec<(String, String)>,
+    /// Version the sidecar reported once it was up
+    sidecar_version: Option<String>,
+    /// When the server last came up, for telling crash loops from occasional crashes
+    running_since: Option<Instant>,
+    /// Crash restarts since the server was last stable
+    crash_restarts: u32,
+    /// Passed to the server, which only accepts shutdown requests carrying it
+    shutdown_token: Option<String>,
+    /// How long a stopping server may take before it's killed
+    shutdown_grace: Duration,
+    /// How long a start may take, and how long migrations within it may take
+    startup_timeout: Duration,
+    migration_timeout: Duration,
+    /// Set while the starting server applies migrations
+    migrating_since: Option<Instant>,
+    migrated_at: Option<Instant>,
+    /// When the start in progress began, for `server-startup-progress`
+    starting_since: Option<Instant>,
+    /// Held for the whole of a start, while the manager itself is let go during slow steps
+    start_lock: Arc<Mutex<()>>,
 }
 
 impl ServerManager {
@@ -143,20 +279,128 @@ impl ServerManager {
             child: None,
             status: ServerStatus::Stopped,
             data_dir,
+            port: server_port(),
+            profile: None,
+            env: Vec::new(),
+            sidecar_version: None,
+            running_since: None,
+            crash_restarts: 0,
+            shutdown_token: None,
+            shutdown_grace: DEFAULT_SHUTDOWN_GRACE,
+            startup_timeout: DEFAULT_STARTUP_TIMEOUT,
+            migration_timeout: DEFAULT_MIGRATION_TIMEOUT,
+            migrating_since: None,
+            migrated_at: None,
+            starting_since: None,
+            start_lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Server for an extra profile running alongside the main one, see profiles.rs
+    pub fn for_profile(data_dir: PathBuf, port: u16, name: String) -> Self {
+        Self {
+            port,
+            profile: Some(name),
+            ..Self::new(data_dir)
         }
     }
 
+    /// Extra environment variable for the sidecar
+    pub fn with_env(mut self, key: &str, value: &str) -> Self {
+        self.env.push((key.to_string(), value.to_string()));
+        self
+    }
+
     pub fn status(&self) -> &ServerStatus {
         &self.status
     }
 
+    /// The starting server is up, by its /health or its output; false if it already was
+    fn mark_running(&mut self) -> bool {
+        if self.status != ServerStatus::Starting {
+            return false;
+        }
+        self.status = ServerStatus::Running;
+        self.running_since = Some(Instant::now());
+        true
+    }
+
+    /// Report a step of the start in progress
+    fn startup_progress(&self, app: &tauri::AppHandle, phase: &'static str, pending: Option<u32>) {
+        let elapsed = self.starting_since.map(|since| since.elapsed()).unwrap_or_default();
+        let _ = events::emit(app, Event::ServerStartupProgress(&StartupProgress {
+            profile: self.profile.clone(),
+            phase,
+            pending,
+            elapsed_ms: elapsed.as_millis() as u64,
+        }));
+    }
+
+    /// Whether the starting server is applying migrations
+    pub fn is_migrating(&self) -> bool {
+        self.status == ServerStatus::Starting && self.migrating_since.is_some() && self.migrated_at.is_none()
+    }
+
+    /// When the start in progress fails for taking too long
+    fn startup_deadline(&self, started: Instant) -> Instant {
+        match (self.migrating_since, self.migrated_at) {
+            (_, Some(done)) => done + self.startup_timeout,
+            (Some(since), None) => since + self.migration_timeout,
+            (None, None) => started + self.startup_timeout,
+        }
+    }
+
     pub fn is_running(&self) -> bool {
         matches!(self.status, ServerStatus::Running)
     }
 
+    /// Process id of the server this app spawned
+    pub fn pid(&self) -> Option<u32> {
+        self.child.as_ref().map(|c| c.pid())
+    }
+
+    /// How long the server has been up
+    pub fn uptime(&self) -> Option<Duration> {
+        self.running_since.map(|since| since.elapsed())
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn shutdown_grace(&self) -> Duration {
+        self.shutdown_grace
+    }
+
     pub fn data_dir(&self) -> &PathBuf {
         &self.data_dir
     }
+
+    /// Serve another folder's books from the next start on, see profiles.rs
+    pub fn set_data_dir(&mut self, data_dir: PathBuf) {
+        self.data_dir = data_dir;
+    }
+
+    pub fn url(&self) -> String {
+        if let Some(url) = remote::url().filter(|_| self.profile.is_none()) {
+            return url.to_string();
+        }
+        match self.socket() {
+            Some(_) => transport::app_origin(),
+            None if self.profile.is_none() => format!("{}://localhost:{}", tls::scheme(), self.port),
+            None => format!("http://localhost:{}", self.port),
+        }
+    }
+
+    /// Socket the server listens on instead of its port (the main server only)
+    fn socket(&self) -> Option<&'static Path> {
+        self.profile.is_none().then(transport::socket_path).flatten()
+    }
+
+    /// Version from the `/health` handshake after the last start
+    pub fn sidecar_version(&self) -> Option<&str> {
+        self.sidecar_version.as_deref()
+    }
 }
 
 pub type SharedServerManager = Arc<Mutex<ServerManager>>;
@@ -166,7 +410,24 @@ pub type SharedServerManager = Arc<Mutex<ServerManager>>;
 /// - Windows: %APPDATA%\Moneywright
 /// - Linux: ~/.local/share/moneywright
 pub fn get_data_dir(app: &tauri::AppHandle) -> PathBuf {
-    // Use Tauri's app data directory
+    let default_dir = default_data_dir(app);
+
+    // A custom location chosen during onboarding is recorded in the default directory
+    if let Ok(custom) = fs::read_to_string(default_dir.join(DATA_LOCATION_FILE)) {
+        let custom = custom.trim();
+        if !custom.is_empty() {
+            return PathBuf::from(custom);
+        }
+    }
+
+    default_dir
+}
+
+/// Pointer file (in the default data dir) holding a user-chosen data location
+pub const DATA_LOCATION_FILE: &str = "data-location";
+
+/// Tauri's app data directory, where the data lives unless relocated
+pub fn default_data_dir(app: &tauri::AppHandle) -> PathBuf {
     app.path()
         .app_data_dir()
         .unwrap_or_else(|_| {
@@ -177,6 +438,13 @@ pub fn get_data_dir(app: &tauri::AppHandle) -> PathBuf {
         })
 }
 
+/// Path of the bundled sidecar binary
+/// Tauri places external binaries next to the main executable (without the target triple)
+pub fn sidecar_path() -> Option<PathBuf> {
+    let exe = std::env::current_exe().ok()?;
+    Some(exe.parent()?.join(format!("moneywright{}", std::env::consts::EXE_SUFFIX)))
+}
+
 /// Check if a CLI installation exists that we could migrate from
 /// CLI installs to:
 /// - macOS: ~/.moneywright
@@ -200,13 +468,9 @@ pub fn get_cli_install_dir() -> Option<PathBuf> {
         c
     };
 
-    for candidate in candidates {
-        if candidate.exists() && candidate.join("data").exists() {
-            return Some(candidate);
-        }
-    }
-
-    None
+    candidates
+        .into_iter()
+        .find(|candidate| candidate.exists() && candidate.join("data").exists())
 }
 
 /// Initialize the data directory, creating necessary subdirectories
@@ -227,7 +491,7 @@ pub fn init_data_dir(data_dir: &PathBuf) -> Result<(), String> {
 }
 
 /// Read DATABASE_URL from .env file if it exists
-pub fn read_database_url(data_dir: &PathBuf) -> Option<String> {
+pub fn read_database_url(data_dir: &Path) -> Option<String> {
     let env_path = data_dir.join(".env");
     if !env_path.exists() {
         return None;
@@ -245,7 +509,7 @@ pub fn read_database_url(data_dir: &PathBuf) -> Option<String> {
 }
 
 /// Write DATABASE_URL to .env file
-pub fn write_database_url(data_dir: &PathBuf, database_url: &str) -> Result<(), String> {
+pub fn write_database_url(data_dir: &Path, database_url: &str) -> Result<(), String> {
     let env_path = data_dir.join(".env");
 
     let content = if env_path.exists() {
@@ -281,6 +545,26 @@ pub fn write_database_url(data_dir: &PathBuf, database_url: &str) -> Result<(),
     Ok(())
 }
 
+/// Remove DATABASE_URL from the .env file (switches back to SQLite)
+pub fn remove_database_url(data_dir: &Path) -> Result<(), String> {
+    let env_path = data_dir.join(".env");
+    if !env_path.exists() {
+        return Ok(());
+    }
+
+    let existing = fs::read_to_string(&env_path)
+        .map_err(|e| format!("Failed to read .env: {}", e))?;
+    let lines: Vec<&str> = existing
+        .lines()
+        .filter(|line| !line.trim().starts_with("DATABASE_URL=") && line.trim() != "# PostgreSQL database URL")
+        .collect();
+
+    fs::write(&env_path, lines.join("\n").trim())
+        .map_err(|e| format!("Failed to write .env: {}", e))?;
+
+    Ok(())
+}
+
 pub fn create_server_manager(app: &tauri::AppHandle) -> SharedServerManager {
     let data_dir = get_data_dir(app);
 
@@ -288,18 +572,53 @@ pub fn create_server_manager(app: &tauri::AppHandle) -> SharedServerManager {
     if let Err(e) = init_data_dir(&data_dir) {
         eprintln!("Warning: {}", e);
     }
+    let _ = MAIN_PORT.set(isolation::resolve_main_port(&data_dir));
+    isolation::check_data_dir(&data_dir);
 
     println!("Data directory: {:?}", data_dir);
 
     Arc::new(Mutex::new(ServerManager::new(data_dir)))
 }
 
+/// Environment the settings give the server (also written for the background service)
+pub async fn settings_env(app: &tauri::AppHandle, data_dir: &Path) -> Vec<(String, String)> {
+    let mut env = Vec::new();
+    let Some(settings) = app.try_state::<SharedSettings>() else {
+        return env;
+    };
+    let settings = settings.lock().await.get();
+    env.push(("LOG_LEVEL".to_string(), loglevel::effective_level(&settings.server.log_level)));
+    env.push((lan::HOST_ENV.to_string(), settings.server.bind_address.clone()));
+    // Sign-in redirects and allowed origins use the address behind the reverse proxy
+    if !settings.server.external_url.is_empty() {
+        env.push(("APP_URL".to_string(), proxy::app_url(&settings.server.external_url)));
+    }
+    // Experimental features the user opted into (the server gates its side on these)
+    let enabled = enabled_flag_keys(&settings.features, data_dir);
+    if !enabled.is_empty() {
+        env.push((FEATURES_ENV.to_string(), enabled.join(",")));
+    }
+    // The server holds its own heavy jobs (vacuum, model downloads) for the same window
+    let maintenance = &settings.maintenance;
+    if maintenance.window {
+        env.push((
+            "MONEYWRIGHT_MAINTENANCE_WINDOW".to_string(),
+            format!("{}-{}", maintenance.window_start, maintenance.window_end),
+        ));
+    }
+    // What the user added in `server.extra_env`; validation keeps out the keys set above
+    env.extend(settings.server.extra_env.clone());
+    env
+}
+
 /// Start the moneywright server sidecar
 pub async fn start_server(
     app: tauri::AppHandle,
     manager: SharedServerManager,
     log_store: SharedLogStore,
 ) -> Result<(), String> {
+    let start_lock = manager.lock().await.start_lock.clone();
+    let _starting = start_lock.lock().await;
     let mut mgr = manager.lock().await;
 
     if mgr.is_running() {
@@ -307,52 +626,311 @@ pub async fn start_server(
     }
 
     mgr.status = ServerStatus::Starting;
+    if mgr.profile.is_none() {
+        PAUSED.store(false, Ordering::Relaxed);
+        IDLE_PAUSED.store(false, Ordering::Relaxed);
+    }
+    mgr.migrating_since = None;
+    mgr.migrated_at = None;
+    mgr.starting_since = Some(Instant::now());
 
     // Kill any existing process on the port (from previous crashed runs)
-    if let Err(e) = kill_process_on_port(SERVER_PORT) {
-        eprintln!("Warning: Failed to check for existing processes: {}", e);
+    if let Some(warning) = ports::startup_warning() {
+        log_line(&app, &log_store, warning, "error").await;
+    }
+    if let Some(settings) = app.try_state::<SharedSettings>() {
+        let settings = settings.lock().await.get().server;
+        mgr.shutdown_grace = Duration::from_secs(settings.shutdown_grace_secs as u64);
+        mgr.startup_timeout = Duration::from_secs(settings.startup_timeout_secs as u64);
+        mgr.migration_timeout = Duration::from_secs(settings.migration_timeout_secs as u64);
+    }
+    // A remote server only has to answer, nothing runs here, see remote.rs
+    if let Some(url) = remote::url().filter(|_| mgr.profile.is_none()) {
+        let timeout = mgr.startup_timeout;
+        drop(mgr);
+        log_line(&app, &log_store, format!("Connecting to the Moneywright server at {}", url), "info").await;
+        let result = remote::connect(url, timeout).await;
+        let mut mgr = manager.lock().await;
+        return match result {
+            Ok(version) => {
+                mgr.status = ServerStatus::Running;
+                mgr.running_since = Some(Instant::now());
+                mgr.sidecar_version = version;
+                Ok(())
+            }
+            Err(e) => {
+                mgr.status = ServerStatus::Error(e.clone());
+                drop(mgr);
+                log_line(&app, &log_store, e.clone(), "error").await;
+                Err(e)
+            }
+        };
+    }
+    // A full disk fails migrations cryptically, so it's refused up front
+    if let Err(e) = diskspace::preflight(&app, Operation::ServerStart, &mgr.data_dir, 0).await {
+        mgr.status = ServerStatus::Error(e.clone());
+        drop(mgr);
+        log_line(&app, &log_store, e.clone(), "error").await;
+        return Err(e);
+    }
+    // A background service runs the main server instead, see service.rs
+    if mgr.profile.is_none() && service::is_installed() {
+        tls::deactivate();
+        let (data_dir, port, url, timeout) = (mgr.data_dir.clone(), mgr.port, mgr.url(), mgr.startup_timeout);
+        drop(mgr);
+        log_line(&app, &log_store, "Starting the background service", "info").await;
+        let result = service::start_and_wait(&app, &data_dir, port, &url, timeout).await;
+        let mut mgr = manager.lock().await;
+        return match result {
+            Ok(()) => {
+                mgr.status = ServerStatus::Running;
+                mgr.running_since = Some(Instant::now());
+                Ok(())
+            }
+            Err(e) => {
+                mgr.status = ServerStatus::Error(e.clone());
+                drop(mgr);
+                log_line(&app, &log_store, e.clone(), "error").await;
+                Err(e)
+            }
+        };
+    }
+    // Servers a crashed app left running, then anything else still on the port
+    let (data_dir, port, grace) = (mgr.data_dir.clone(), mgr.port, mgr.shutdown_grace);
+    drop(mgr);
+    let (orphaned, holder) = tauri::async_runtime::spawn_blocking(move || {
+        let orphaned = orphans::reap(&data_dir, grace);
+        if let Err(e) = ports::stop_process_on_port(port, grace) {
+            eprintln!("Warning: Failed to check for existing processes: {}", e);
+        }
+        (orphaned, ports::holder(port))
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+    if orphaned > 0 {
+        let msg = format!("Stopped {} server(s) left running by an earlier session", orphaned);
+        log_line(&app, &log_store, msg, "info").await;
+    }
+    let mut mgr = relock(&manager).await?;
+    // Another OS user's server isn't ours to stop
+    if holder.is_foreign() {
+        let msg = format!(
+            "{}. Each user on this computer needs their own port; restart Moneywright to move to a free one",
+            holder.describe(mgr.port)
+        );
+        mgr.status = ServerStatus::Error(msg.clone());
+        drop(mgr);
+        log_line(&app, &log_store, msg.clone(), "error").await;
+        return Err(msg);
+    }
+    // Nor is another program's, unless the user says so
+    if let Holder::OtherProgram { pid, name } = &holder {
+        let msg = format!("{}. Moneywright won't stop it without asking", holder.describe(mgr.port));
+        if mgr.profile.is_none() {
+            portconflict::report(&app, mgr.port, *pid, name);
+        }
+        mgr.status = ServerStatus::Error(msg.clone());
+        drop(mgr);
+        log_line(&app, &log_store, msg.clone(), "error").await;
+        return Err(msg);
     }
 
     let data_dir = mgr.data_dir.clone();
+    let port = mgr.port;
+    // The folder may have been removed since launch
+    mgr.startup_progress(&app, "data_dir", None);
+    if let Err(e) = init_data_dir(&data_dir) {
+        mgr.status = ServerStatus::Error(e.clone());
+        drop(mgr);
+        log_line(&app, &log_store, e.clone(), "error").await;
+        return Err(e);
+    }
+    // Output of extra profiles is tagged with the profile name
+    let tag = match &mgr.profile {
+        Some(name) => format!("moneywright@{}", name),
+        None => "moneywright".to_string(),
+    };
+    // Session stats cover the main server only
+    let track_sessions = mgr.profile.is_none();
+    let profile = mgr.profile.clone();
+    // Extra profiles have their own databases; recovery works on the main one
+    let watch_corruption = mgr.profile.is_none();
+    let extra_env = mgr.env.clone();
 
-    // Get the sidecar command
+    // Get the sidecar command, preferring a native build when this one runs translated
     let shell = app.shell();
-    let mut sidecar = shell
-        .sidecar("moneywright")
-        .map_err(|e| format!("Failed to create sidecar command: {}", e))?
-        .env("PORT", SERVER_PORT.to_string())
-        .env("DATA_DIR", data_dir.to_string_lossy().to_string());
-
-    // Set DATABASE_URL if configured
-    let is_postgres = if let Some(database_url) = read_database_url(&data_dir) {
+    let native = arch::native_sidecar(&data_dir);
+    // A bundled binary that was tampered with or truncated isn't run, see integrity.rs
+    if native.is_none() {
+        // Hashing the binary takes a moment; the manager isn't held meanwhile
+        drop(mgr);
+        let problem = tauri::async_runtime::spawn_blocking(integrity::bundled_problem).await.ok().flatten();
+        mgr = relock(&manager).await?;
+        if let Some(problem) = problem {
+            let msg = format!("Moneywright's installation is damaged: {}", problem);
+            mgr.status = ServerStatus::Error(msg.clone());
+            drop(mgr);
+            log_line(&app, &log_store, msg.clone(), "error").await;
+            resources::open_repair_window(&app);
+            return Err(msg);
+        }
+    }
+    let sidecar = match native {
+        Some(native) => shell.command(native),
+        None => shell
+            .sidecar("moneywright")
+            .map_err(|e| format!("Failed to create sidecar command: {}", e))?,
+    };
+    let shutdown_token = new_shutdown_token()?;
+    let mut sidecar = sidecar
+        .env("SHUTDOWN_TOKEN", shutdown_token.as_str())
+        .env("PORT", port.to_string())
+        .env("DATA_DIR", data_dir.to_string_lossy().to_string())
+        .envs(extra_env);
+    if let Some(socket) = mgr.socket() {
+        // A socket file left by a crashed server would fail the bind
+        let _ = fs::remove_file(socket);
+        sidecar = sidecar.env("SERVER_SOCKET", socket.to_string_lossy().to_string());
+    }
+
+    let mut settings_env = settings_env(&app, &data_dir).await;
+    // Only the main server is opened up to the network
+    if profile.is_some() {
+        for (_, host) in settings_env.iter_mut().filter(|(key, _)| key == lan::HOST_ENV) {
+            *host = lan::LOCALHOST.to_string();
+        }
+    } else if let Some((_, host)) = settings_env.iter().find(|(key, host)| key == lan::HOST_ENV && host != lan::LOCALHOST) {
+        log_line(&app, &log_store, format!("LAN access is on, the server listens on {}", host), "info").await;
+    }
+    if let Some((_, enabled)) = settings_env.iter().find(|(key, _)| key == FEATURES_ENV) {
+        let msg = format!("Experimental features enabled: {}", enabled.replace(',', ", "));
+        log_line(&app, &log_store, msg, "info").await;
+    }
+    sidecar = sidecar.envs(settings_env);
+    // https for the main server, see tls.rs
+    if profile.is_none() {
+        let enabled = match app.try_state::<SharedSettings>() {
+            Some(settings) => settings.lock().await.get().server.tls,
+            None => false,
+        };
+        match tls::activate(&app, &data_dir, enabled) {
+            Ok(env) => sidecar = sidecar.envs(env),
+            Err(e) => {
+                let msg = format!("Couldn't set up https: {}", e);
+                mgr.status = ServerStatus::Error(msg.clone());
+                drop(mgr);
+                log_line(&app, &log_store, msg.clone(), "error").await;
+                return Err(msg);
+            }
+        }
+    }
+    if let Some(settings) = app.try_state::<SharedSettings>() {
+        let server = settings.lock().await.get().server;
+        if !server.extra_args.is_empty() {
+            let msg = format!("Extra server arguments (server.extra_args): {}", server.extra_args.join(" "));
+            log_line(&app, &log_store, msg, "info").await;
+            sidecar = sidecar.args(server.extra_args);
+        }
+        // Names only, the values may be secrets
+        if !server.extra_env.is_empty() {
+            let keys: Vec<&str> = server.extra_env.keys().map(String::as_str).collect();
+            let msg = format!("Extra server environment (server.extra_env): {}", keys.join(", "));
+            log_line(&app, &log_store, msg, "info").await;
+        }
+    }
+
+    // The embedded PostgreSQL cluster runs alongside the main server, see postgres.rs
+    if profile.is_none() {
+        drop(mgr);
+        let result = postgres::ensure_running(&app, &data_dir).await;
+        mgr = relock(&manager).await?;
+        if let Err(e) = result {
+            mgr.status = ServerStatus::Error(e.clone());
+            drop(mgr);
+            log_line(&app, &log_store, e.clone(), "error").await;
+            return Err(e);
+        }
+    }
+
+    // Set DATABASE_URL if configured (password comes from the keychain when not inline)
+    let is_postgres = if let Some(database_url) = server_database_url(&data_dir) {
         sidecar = sidecar.env("DATABASE_URL", database_url);
-        emit_log(&app, "Using PostgreSQL database", "info");
-        store_log(&log_store, "Using PostgreSQL database").await;
+        log_line(&app, &log_store, "Using PostgreSQL database", "info").await;
         true
     } else {
-        emit_log(&app, "Using SQLite database", "info");
-        store_log(&log_store, "Using SQLite database").await;
+        log_line(&app, &log_store, "Using SQLite database", "info").await;
         false
     };
 
-    // Set paths from app resources
-    if let Ok(resource_dir) = app.path().resource_dir() {
-        let migrations_type = if is_postgres { "pg" } else { "sqlite" };
-        let migrations_path = resource_dir.join("drizzle").join(migrations_type);
-        let public_path = resource_dir.join("public");
-        let log_msg = format!("Data directory: {}", data_dir.display());
-        emit_log(&app, &log_msg, "info");
-        store_log(&log_store, &log_msg).await;
-        sidecar = sidecar.env("MIGRATIONS_PATH", migrations_path.to_string_lossy().to_string());
-        sidecar = sidecar.env("PUBLIC_DIR", public_path.to_string_lossy().to_string());
+    let log_msg = format!("Data directory: {}", data_dir.display());
+    log_line(&app, &log_store, log_msg, "info").await;
+
+    // Set paths from app resources; without them the server fails cryptically, so a
+    // broken install stops here with the repair window (dev builds run without them)
+    let mut migrations_dir = None;
+    let ui_build = match app.try_state::<SharedSettings>() {
+        Some(settings) => settings.lock().await.get().server.ui_build,
+        None => String::new(),
+    };
+    match resources::locate(&app, is_postgres) {
+        Ok(resources) => {
+            migrations_dir = Some(resources.migrations.clone());
+            sidecar = sidecar.env("MIGRATIONS_PATH", resources.migrations.to_string_lossy().to_string());
+            let public = match resources.preview {
+                Some(preview) if ui_build == "preview" => {
+                    log_line(&app, &log_store, "Serving the preview web app", "info").await;
+                    preview
+                }
+                _ => resources.public,
+            };
+            sidecar = sidecar.env("PUBLIC_DIR", public.to_string_lossy().to_string());
+        }
+        Err(problem) if cfg!(debug_assertions) => {
+            log_line(&app, &log_store, format!("Resources not bundled: {}", problem), "info").await;
+        }
+        Err(problem) => {
+            let msg = format!("Moneywright's installation is damaged: {}", problem);
+            mgr.status = ServerStatus::Error(msg.clone());
+            drop(mgr);
+            log_line(&app, &log_store, msg.clone(), "error").await;
+            resources::open_repair_window(&app);
+            return Err(msg);
+        }
     }
 
+    // A new version migrates the database, which is snapshotted first, see upgrade.rs
+    let upgrade = match migrations_dir.filter(|_| profile.is_none()) {
+        Some(migrations_dir) => {
+            // The snapshot copies the whole database
+            drop(mgr);
+            let prepared = upgrade::prepare(&app, &log_store, &data_dir, &migrations_dir, is_postgres).await;
+            mgr = relock(&manager).await?;
+            match prepared {
+                Ok(upgrade) => upgrade,
+                Err(e) => {
+                    mgr.status = ServerStatus::Error(e.clone());
+                    drop(mgr);
+                    log_line(&app, &log_store, e.clone(), "error").await;
+                    return Err(e);
+                }
+            }
+        }
+        None => None,
+    };
+
     // Spawn the sidecar process
     let (mut rx, child) = sidecar
         .spawn()
-        .map_err(|e| format!("Failed to spawn sidecar: {}", e))?;
+        .map_err(|e| {
+            avcheck::spawn_failed(&e.to_string());
+            format!("Failed to spawn sidecar: {}", e)
+        })?;
 
+    let pid = child.pid();
+    orphans::register(pid, port);
     mgr.child = Some(child);
+    mgr.shutdown_token = Some(shutdown_token);
+    mgr.startup_progress(&app, "spawned", None);
 
     // Drop the lock before spawning the output handler
     drop(mgr);
@@ -367,46 +945,104 @@ pub async fn start_server(
                 CommandEvent::Stdout(line) => {
                     let line_str = String::from_utf8_lossy(&line).trim().to_string();
                     if !line_str.is_empty() {
-                        let log_line = format!("[moneywright] {}", line_str);
-                        println!("{}", log_line);
-                        emit_log(&app_clone, &line_str, "server");
-                        store_log(&log_store_clone, &log_line).await;
+                        let log_line_str = format!("[{}] {}", tag, line_str);
+                        println!("{}", log_line_str);
+                        logs::write_file(&log_line_str);
+                        log_line(&app_clone, &log_store_clone, log_line_str, "server").await;
+                        if watch_corruption {
+                            recovery::check_output(&app_clone, &line_str);
+                        }
+
+                        if let Some(rest) = line_str.strip_prefix(MIGRATING_PREFIX) {
+                            let pending = rest.split_whitespace().next().and_then(|n| n.parse().ok());
+                            let mut mgr = manager_clone.lock().await;
+                            mgr.migrating_since = Some(Instant::now());
+                            mgr.startup_progress(&app_clone, "migrating", pending);
+                            let _ = events::emit(&app_clone, Event::ServerMigrating(&MigrationProgress {
+                                profile: profile.clone(),
+                                pending,
+                                done: false,
+                            }));
+                        } else if line_str == MIGRATED_LINE {
+                            let mut mgr = manager_clone.lock().await;
+                            mgr.migrated_at = Some(Instant::now());
+                            mgr.startup_progress(&app_clone, "migrated", None);
+                            let _ = events::emit(&app_clone, Event::ServerMigrating(&MigrationProgress {
+                                profile: profile.clone(),
+                                pending: None,
+                                done: true,
+                            }));
+                        }
 
-                        // Check if server is ready
+                        // Fallback to the /health probe in wait_until_ready
                         if line_str.contains("Listening on") || line_str.contains("Server running") || line_str.contains("Server is running") {
                             let mut mgr = manager_clone.lock().await;
-                            mgr.status = ServerStatus::Running;
+                            if mgr.mark_running() {
+                                mgr.startup_progress(&app_clone, "listening", None);
+                            }
                         }
                     }
                 }
                 CommandEvent::Stderr(line) => {
                     let line_str = String::from_utf8_lossy(&line).trim().to_string();
                     if !line_str.is_empty() {
-                        let log_line = format!("[moneywright:err] {}", line_str);
-                        eprintln!("{}", log_line);
-                        emit_log(&app_clone, &line_str, "error");
-                        store_log(&log_store_clone, &log_line).await;
+                        let log_line_str = format!("[{}:err] {}", tag, line_str);
+                        eprintln!("{}", log_line_str);
+                        logs::write_file(&log_line_str);
+                        log_line(&app_clone, &log_store_clone, log_line_str, "error").await;
+                        if watch_corruption {
+                            recovery::check_output(&app_clone, &line_str);
+                        }
+                        avcheck::check_output(&line_str);
                     }
                 }
                 CommandEvent::Terminated(payload) => {
                     let mut mgr = manager_clone.lock().await;
+                    // stop_server marks the status Stopped before the process goes away
+                    let expected = matches!(mgr.status, ServerStatus::Stopped | ServerStatus::Paused);
+                    let stopped = if mgr.status == ServerStatus::Paused {
+                        ServerStatus::Paused
+                    } else {
+                        ServerStatus::Stopped
+                    };
+                    // Killed by a signal (SIGSEGV, SIGABRT, the OOM killer's SIGKILL) has no code
+                    let crashed = !expected && (payload.code.is_some_and(|code| code != 0) || payload.signal.is_some());
+                    // Crashes during startup are reported to whoever started it
+                    let was_running = mgr.status == ServerStatus::Running;
+                    if let Some(tracker) = app_clone.try_state::<SharedSessionTracker>().filter(|_| track_sessions) {
+                        tracker.lock().await.server_stopped(crashed);
+                    }
                     if let Some(code) = payload.code {
-                        if code != 0 {
+                        if !expected {
+                            avcheck::server_exited(code);
+                        }
+                        // A server killed after its grace period exits non-zero on Windows
+                        if code != 0 && !expected {
                             let msg = format!("Server exited with code {}", code);
-                            emit_log(&app_clone, &msg, "error");
-                            store_log(&log_store_clone, &msg).await;
+                            log_line(&app_clone, &log_store_clone, msg.as_str(), "error").await;
                             mgr.status = ServerStatus::Error(msg);
                         } else {
-                            emit_log(&app_clone, "Server stopped", "info");
-                            store_log(&log_store_clone, "Server stopped").await;
-                            mgr.status = ServerStatus::Stopped;
+                            log_line(&app_clone, &log_store_clone, "Server stopped", "info").await;
+                            mgr.status = stopped;
                         }
+                    } else if let Some(signal) = payload.signal.filter(|_| !expected) {
+                        let msg = format!("Server was killed by signal {}", signal);
+                        log_line(&app_clone, &log_store_clone, msg.as_str(), "error").await;
+                        mgr.status = ServerStatus::Error(msg);
                     } else {
-                        emit_log(&app_clone, "Server terminated", "info");
-                        store_log(&log_store_clone, "Server terminated").await;
-                        mgr.status = ServerStatus::Stopped;
+                        log_line(&app_clone, &log_store_clone, "Server terminated", "info").await;
+                        mgr.status = stopped;
                     }
                     mgr.child = None;
+                    mgr.sidecar_version = None;
+                    orphans::unregister(pid);
+                    if mgr.running_since.take().is_some_and(|since| since.elapsed() >= STABLE_AFTER) {
+                        mgr.crash_restarts = 0;
+                    }
+                    drop(mgr);
+                    if crashed && was_running {
+                        supervise(app_clone.clone(), manager_clone.clone(), log_store_clone.clone());
+                    }
                     break;
                 }
                 _ => {}
@@ -414,46 +1050,446 @@ pub async fn start_server(
         }
     });
 
-    // Wait for server to be ready (with timeout)
+    let result = wait_until_ready(&app, &manager, &log_store, &data_dir, track_sessions).await;
+    if let Some(upgrade) = upgrade {
+        upgrade::finish(&app, &log_store, upgrade, &result).await;
+    }
+    result
+}
+
+/// The manager again after a slow step of a start, unless it was stopped meanwhile
+async fn relock(manager: &SharedServerManager) -> Result<tokio::sync::MutexGuard<'_, ServerManager>, String> {
+    let mgr = manager.lock().await;
+    match mgr.status {
+        ServerStatus::Starting => Ok(mgr),
+        _ => Err("Server was stopped while starting".to_string()),
+    }
+}
+
+/// Wait for a spawned server to answer /health or log that it's listening (with timeout)
+async fn wait_until_ready(
+    app: &tauri::AppHandle,
+    manager: &SharedServerManager,
+    log_store: &SharedLogStore,
+    data_dir: &Path,
+    track_sessions: bool,
+) -> Result<(), String> {
     let start = std::time::Instant::now();
+    let mut probe_delay = READY_PROBE;
+    let mut next_probe = start;
     loop {
-        if start.elapsed() > STARTUP_TIMEOUT {
-            return Err("Server startup timed out".to_string());
+        let mut mgr = manager.lock().await;
+        if Instant::now() > mgr.startup_deadline(start) {
+            let msg = if mgr.is_migrating() {
+                format!(
+                    "Database migrations didn't finish within {} minutes (server.migration_timeout_secs); \
+                     they may still be running, see View Logs",
+                    mgr.migration_timeout.as_secs() / 60
+                )
+            } else {
+                format!(
+                    "Server startup timed out after {} s (server.startup_timeout_secs)",
+                    mgr.startup_timeout.as_secs()
+                )
+            };
+            // The process stays tracked (a migration may still finish its work), but a
+            // late "Listening on" no longer counts; Restart or Stop ends it
+            mgr.status = ServerStatus::Error(msg.clone());
+            return Err(msg);
         }
 
-        let mgr = manager.lock().await;
         match &mgr.status {
-            ServerStatus::Running => return Ok(()),
+            ServerStatus::Running => {
+                let url = mgr.url();
+                drop(mgr);
+                if let Some(tracker) = app.try_state::<SharedSessionTracker>().filter(|_| track_sessions) {
+                    tracker.lock().await.server_started();
+                }
+                // Handshake: record which server build actually came up
+                let version = fetch_health(&url).await.and_then(|h| h.version);
+                if let Some(version) = version.as_deref().filter(|_| track_sessions) {
+                    webcache::on_server_version(app, data_dir, version);
+                    // Development servers report "dev"
+                    if version != env!("CARGO_PKG_VERSION") && version != "dev" {
+                        let msg = format!("Server {} doesn't match the app ({})", version, env!("CARGO_PKG_VERSION"));
+                        log_line(app, log_store, msg, "error").await;
+                    }
+                }
+                let mut mgr = manager.lock().await;
+                mgr.sidecar_version = version;
+                mgr.startup_progress(app, "ready", None);
+                return Ok(());
+            }
             ServerStatus::Error(e) => return Err(e.clone()),
             ServerStatus::Stopped => return Err("Server stopped unexpectedly".to_string()),
+            ServerStatus::Paused => return Err("Server was paused while starting".to_string()),
             ServerStatus::Starting => {
+                let url = mgr.url();
                 drop(mgr);
-                std::thread::sleep(Duration::from_millis(100));
+                if Instant::now() >= next_probe {
+                    if fetch_health(&url).await.is_some() {
+                        let mut mgr = manager.lock().await;
+                        if mgr.mark_running() {
+                            mgr.startup_progress(app, "listening", None);
+                        }
+                        continue;
+                    }
+                    probe_delay = (probe_delay * 2).min(READY_PROBE_MAX);
+                    next_probe = Instant::now() + probe_delay;
+                }
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        }
+    }
+}
+
+fn crash_backoff(attempt: u32) -> Duration {
+    CRASH_BACKOFF
+        .saturating_mul(1 << attempt.saturating_sub(1).min(16))
+        .min(MAX_CRASH_BACKOFF)
+}
+
+/// Start a crashed server again, backing off between attempts
+fn supervise(app: tauri::AppHandle, manager: SharedServerManager, log_store: SharedLogStore) {
+    tauri::async_runtime::spawn(async move {
+        let Some(settings) = app.try_state::<SharedSettings>() else {
+            return;
+        };
+        let settings = settings.lock().await.get().server;
+        if !settings.crash_restart {
+            log_line(&app, &log_store, "Automatic restart after a crash is turned off", "info").await;
+            return;
+        }
+        let profile = manager.lock().await.profile.clone();
+        // Restarting won't get past a damaged database, the recovery window handles it
+        if profile.is_none() && recovery::damage_reported() {
+            log_line(&app, &log_store, "Not restarting the server: its database is damaged", "error").await;
+            return;
+        }
+
+        loop {
+            let attempt = {
+                let mut mgr = manager.lock().await;
+                mgr.crash_restarts += 1;
+                mgr.crash_restarts
+            };
+            if attempt > settings.crash_restart_max {
+                manager.lock().await.crash_restarts = 0;
+                let msg = format!(
+                    "The server keeps crashing; gave up after {} restart attempts",
+                    settings.crash_restart_max
+                );
+                log_line(&app, &log_store, msg.as_str(), "error").await;
+                if profile.is_none() {
+                    crate::emit_status(&app, "error");
+                }
+                notifications::notify(&app, Kind::Maintenance, "Moneywright server stopped", format!("{}. See View Logs for details.", msg)).await;
+                return;
+            }
+
+            let delay = crash_backoff(attempt);
+            let msg = format!(
+                "Restarting the server after a crash in {} s (attempt {} of {})",
+                delay.as_secs(),
+                attempt,
+                settings.crash_restart_max
+            );
+            log_line(&app, &log_store, msg, "info").await;
+            let _ = events::emit(&app, Event::ServerRestartAttempt(&RestartAttempt {
+                profile: profile.clone(),
+                attempt,
+                max_attempts: settings.crash_restart_max,
+                delay_secs: delay.as_secs(),
+            }));
+            tokio::time::sleep(delay).await;
+
+            // Started or stopped by hand in the meantime
+            if !matches!(manager.lock().await.status, ServerStatus::Error(_)) {
+                return;
+            }
+            let result = match profile {
+                None => crate::restart_server(app.clone(), manager.clone(), log_store.clone()).await,
+                Some(_) => start_server(app.clone(), manager.clone(), log_store.clone()).await,
+            };
+            match result {
+                Ok(()) => {
+                    log_line(&app, &log_store, format!("Server is back up after restart attempt {}", attempt), "info").await;
+                    return;
+                }
+                Err(e) => {
+                    log_line(&app, &log_store, format!("Restart attempt {} failed: {}", attempt, e), "error").await;
+                    // A failed start can leave the status at Starting
+                    let mut mgr = manager.lock().await;
+                    if mgr.status == ServerStatus::Starting {
+                        mgr.status = ServerStatus::Error(e);
+                    }
+                }
             }
         }
+    });
+}
+
+/// Ping the main server while it's Running and act on repeated misses, see the top of the file
+pub fn start_liveness_watchdog(app: tauri::AppHandle, manager: SharedServerManager, log_store: SharedLogStore) {
+    tauri::async_runtime::spawn(async move {
+        let mut failures = 0u32;
+        loop {
+            let Some(settings) = app.try_state::<SharedSettings>() else {
+                tokio::time::sleep(LIVENESS_IDLE).await;
+                continue;
+            };
+            let settings = settings.lock().await.get().server;
+            if settings.liveness_interval_secs == 0 {
+                failures = 0;
+                tokio::time::sleep(LIVENESS_IDLE).await;
+                continue;
+            }
+            let interval = Duration::from_secs(settings.liveness_interval_secs as u64);
+            tokio::time::sleep(interval).await;
+
+            let (status, url, pid) = {
+                let mgr = manager.lock().await;
+                (mgr.status.clone(), mgr.url(), mgr.child.as_ref().map(|c| c.pid()))
+            };
+            match status {
+                ServerStatus::Running => {}
+                // Marked by this watchdog and left alone: back to Running once it answers
+                ServerStatus::Error(e) if e == UNRESPONSIVE => {
+                    failures = 0;
+                    if fetch_health(&url).await.is_some() {
+                        let mut mgr = manager.lock().await;
+                        if mgr.status == ServerStatus::Error(UNRESPONSIVE.to_string()) {
+                            mgr.status = ServerStatus::Running;
+                            drop(mgr);
+                            log_line(&app, &log_store, "The server is responding again", "info").await;
+                            crate::emit_status(&app, "running");
+                        }
+                    }
+                    continue;
+                }
+                _ => {
+                    failures = 0;
+                    continue;
+                }
+            }
+            if fetch_health(&url).await.is_some() {
+                failures = 0;
+                continue;
+            }
+            failures += 1;
+            if failures < settings.liveness_failures {
+                continue;
+            }
+
+            {
+                let mut mgr = manager.lock().await;
+                // Stopped or restarted while the ping was out
+                if mgr.status != ServerStatus::Running || mgr.child.as_ref().map(|c| c.pid()) != pid {
+                    failures = 0;
+                    continue;
+                }
+                mgr.status = ServerStatus::Error(UNRESPONSIVE.to_string());
+            }
+            let msg = format!(
+                "{} ({} missed in a row, every {} s)",
+                UNRESPONSIVE,
+                failures,
+                interval.as_secs()
+            );
+            log_line(&app, &log_store, msg, "error").await;
+            crate::emit_status(&app, "error");
+            let _ = events::emit(&app, Event::ServerUnhealthy(&Unhealthy {
+                failures,
+                interval_secs: interval.as_secs(),
+                restarting: settings.crash_restart,
+            }));
+            failures = 0;
+            // A hung server is as good as a crashed one
+            supervise(app.clone(), manager.clone(), log_store.clone());
+        }
+    });
+}
+
+fn new_shutdown_token() -> Result<String, String> {
+    let mut token = [0u8; 16];
+    SecureRandom::fill(&SystemRandom::new(), &mut token).map_err(|_| "No secure random numbers available".to_string())?;
+    Ok(hex::encode(token))
+}
+
+/// Ask the server to shut down by itself, true if the request went out
+async fn request_shutdown(pid: u32, url: &str, token: Option<&str>) -> bool {
+    if cfg!(unix) {
+        return ports::terminate(pid);
     }
+    let Some(token) = token else {
+        return false;
+    };
+    let Ok(client) = reqwest::Client::builder().timeout(HEALTH_TIMEOUT).build() else {
+        return false;
+    };
+    transport::send(client.post(format!("{}/internal/shutdown", url)).header("X-Shutdown-Token", token))
+        .await
+        .is_ok_and(|response| response.status().is_success())
 }
 
-/// Stop the moneywright server
+/// Stop the moneywright server, letting it finish its writes first
 pub async fn stop_server(manager: SharedServerManager) -> Result<(), String> {
-    let mut mgr = manager.lock().await;
+    // Marked Stopped up front so the output handler treats the exit as expected
+    let (child, port, url, token, grace, main) = {
+        let mut mgr = manager.lock().await;
+        mgr.status = ServerStatus::Stopped;
+        let main = mgr.profile.is_none();
+        (mgr.child.take(), mgr.port, mgr.url(), mgr.shutdown_token.take(), mgr.shutdown_grace, main)
+    };
+
+    // A remote server isn't ours to stop, and nothing here holds the port
+    if main && remote::url().is_some() {
+        return Ok(());
+    }
+
+    // The background service's server is stopped through the service manager
+    if main && child.is_none() && service::is_installed() {
+        return tauri::async_runtime::spawn_blocking(move || service::stop_service(port, grace))
+            .await
+            .map_err(|e| e.to_string())?;
+    }
 
-    // First try to kill via the child handle
-    if let Some(child) = mgr.child.take() {
-        let _ = child.kill();
+    if let Some(child) = child {
+        let pid = child.pid();
+        // Looked up while the server runs, they're reparented once it's gone
+        let children = tauri::async_runtime::spawn_blocking(move || ports::descendants(pid)).await.unwrap_or_default();
+        let stopped = request_shutdown(pid, &url, token.as_deref()).await
+            && tauri::async_runtime::spawn_blocking(move || ports::wait_for_exit(&[pid], grace).is_empty())
+                .await
+                .unwrap_or(false);
+        if !stopped {
+            eprintln!("Server didn't stop within {} s, killing it", grace.as_secs());
+            let _ = child.kill();
+        }
+        if !children.is_empty() {
+            let _ = tauri::async_runtime::spawn_blocking(move || ports::stop_pids(&children, grace)).await;
+        }
     }
 
-    // Also kill any process on the port as a fallback
-    // This handles cases where child.kill() didn't work or process spawned children
-    if let Err(e) = kill_process_on_port(SERVER_PORT) {
-        eprintln!("Warning: Failed to kill process on port: {}", e);
+    // Also stop a Moneywright server still on the port, in case child.kill() didn't work
+    let result = tauri::async_runtime::spawn_blocking(move || ports::stop_process_on_port(port, grace))
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|result| result);
+    if let Err(e) = result {
+        eprintln!("Warning: Failed to stop process on port: {}", e);
     }
 
-    mgr.status = ServerStatus::Stopped;
     Ok(())
 }
 
+/// Whether the user paused the main server
+pub fn is_paused() -> bool {
+    PAUSED.load(Ordering::Relaxed)
+}
+
+/// Stop the main server until it's resumed, keeping the app open
+pub async fn pause_server(manager: SharedServerManager) -> Result<(), String> {
+    stop_server(manager.clone()).await?;
+    manager.lock().await.status = ServerStatus::Paused;
+    PAUSED.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Leave the main server stopped at launch until the main window is shown (`server.lazy_start`)
+#[cfg_attr(debug_assertions, allow(dead_code))]
+pub async fn defer_start(manager: SharedServerManager) {
+    manager.lock().await.status = ServerStatus::Paused;
+    PAUSED.store(true, Ordering::Relaxed);
+    IDLE_PAUSED.store(true, Ordering::Relaxed);
+}
+
+fn main_window_hidden(app: &tauri::AppHandle) -> bool {
+    app.get_webview_window("main")
+        .is_none_or(|window| !window.is_visible().unwrap_or(true) || window.is_minimized().unwrap_or(false))
+}
+
+/// The main window was shown: resume a server the idle tracker paused
+pub fn main_window_shown(app: &tauri::AppHandle) {
+    if !IDLE_PAUSED.swap(false, Ordering::Relaxed) {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let (Some(manager), Some(log_store)) = (app.try_state::<SharedServerManager>(), app.try_state::<SharedLogStore>())
+        else {
+            return;
+        };
+        let (manager, log_store) = (manager.inner().clone(), log_store.inner().clone());
+        log_line(&app, &log_store, "The window was shown, starting the server", "info").await;
+        let _ = crate::resume(app.clone(), manager, log_store).await;
+    });
+}
+
+/// Pause the main server while the main window stays hidden, see the top of the file
+pub fn start_idle_tracker(app: tauri::AppHandle, manager: SharedServerManager, log_store: SharedLogStore) {
+    tauri::async_runtime::spawn(async move {
+        let mut hidden_since: Option<Instant> = None;
+        loop {
+            tokio::time::sleep(IDLE_POLL).await;
+            if !main_window_hidden(&app) {
+                hidden_since = None;
+                // In case the window came back without a focus event
+                main_window_shown(&app);
+                continue;
+            }
+            let hidden = *hidden_since.get_or_insert_with(Instant::now);
+            let Some(settings) = app.try_state::<SharedSettings>() else {
+                continue;
+            };
+            let settings = settings.lock().await.get();
+            let minutes = settings.server.pause_after_hidden_minutes;
+            if minutes == 0 || hidden.elapsed() < Duration::from_secs(minutes as u64 * 60) {
+                continue;
+            }
+            // Other devices reach this server, and the background service is meant to keep running
+            if settings.features.lan_mode
+                || lan::is_enabled(&settings)
+                || !settings.server.external_url.is_empty()
+                || remote::url().is_some()
+                || service::is_installed()
+            {
+                continue;
+            }
+            if manager.lock().await.status != ServerStatus::Running || crate::scheduler::tasks_in_flight() > 0 {
+                continue;
+            }
+            let msg = format!("The window has been hidden for {} minutes, pausing the server", minutes);
+            log_line(&app, &log_store, msg, "info").await;
+            if crate::pause(app.clone(), manager.clone()).await.is_ok() {
+                IDLE_PAUSED.store(true, Ordering::Relaxed);
+            }
+        }
+    });
+}
+
+/// Port of the main server (the default one unless another OS user holds it)
+pub fn server_port() -> u16 {
+    MAIN_PORT.get().copied().unwrap_or(SERVER_PORT)
+}
+
 /// Get the server URL
 pub fn get_server_url() -> String {
-    format!("http://localhost:{}", SERVER_PORT)
+    if let Some(url) = remote::url() {
+        return url.to_string();
+    }
+    match transport::socket_path() {
+        Some(_) => transport::app_origin(),
+        None => format!("{}://localhost:{}", tls::scheme(), server_port()),
+    }
+}
+
+/// Whether a URL is on the main server; compares scheme, host and port because
+/// mwapp:// origins are opaque and never equal
+pub fn is_server_url(url: &tauri::Url) -> bool {
+    tauri::Url::parse(&get_server_url()).is_ok_and(|server| {
+        server.scheme() == url.scheme()
+            && server.host_str() == url.host_str()
+            && server.port_or_known_default() == url.port_or_known_default()
+    })
 }
@@ -0,0 +1,117 @@
+// CPU and memory use of the server process
+//
+// `get_server_stats` and the `server-stats` event (every STATS_INTERVAL while the server
+// runs) report the CPU, resident memory and uptime of the sidecar the app spawned,
+// together with any processes it started itself. CPU is measured between two samples,
+// so the System with the previous one is kept; the first call of the command waits
+// sysinfo's minimum interval to have something to compare with. A server the app
+// didn't spawn (remote, background service, external URL) has no stats. Each sample
+// also tells the orphan registry which processes the server started.
+
+use std::sync::Mutex;
+use std::time::Duration;
+use serde::Serialize;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+use tauri::{AppHandle, State};
+use crate::events::{self, Event};
+use crate::orphans;
+use crate::server::{ServerStatus, SharedServerManager};
+
+const STATS_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Processes sampled last time, for CPU usage
+static SYSTEM: Mutex<Option<System>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ServerStats {
+    pub pid: u32,
+    /// Percent of one core, so it can go past 100 on several
+    pub cpu_percent: f32,
+    /// Resident memory in bytes
+    pub memory_bytes: u64,
+    pub uptime_secs: u64,
+    /// The server and the processes it started
+    pub processes: usize,
+}
+
+/// Sample the server process tree; None once it's gone
+fn sample(pid: u32, uptime: Duration, wait_for_cpu: bool) -> Option<ServerStats> {
+    let mut guard = SYSTEM.lock().unwrap_or_else(|e| e.into_inner());
+    let fresh = guard.is_none();
+    let system = guard.get_or_insert_with(System::new);
+    let refresh = |system: &mut System| {
+        system.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::nothing().with_cpu().with_memory(),
+        );
+    };
+    refresh(system);
+    if fresh && wait_for_cpu {
+        std::thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+        refresh(system);
+    }
+
+    let root = Pid::from_u32(pid);
+    system.process(root)?;
+    // The server and its descendants
+    let mut tree = vec![root];
+    let mut i = 0;
+    while i < tree.len() {
+        let parent = tree[i];
+        tree.extend(system.processes().iter().filter(|(_, p)| p.parent() == Some(parent)).map(|(pid, _)| *pid));
+        i += 1;
+    }
+    let processes: Vec<_> = tree.iter().filter_map(|pid| system.process(*pid)).collect();
+    // So a crash of the app doesn't leave them running, see orphans.rs
+    let children: Vec<(u32, u64)> = processes.iter().skip(1).map(|p| (p.pid().as_u32(), p.start_time())).collect();
+    orphans::track_children(pid, &children);
+    Some(ServerStats {
+        pid,
+        cpu_percent: processes.iter().map(|p| p.cpu_usage()).sum(),
+        memory_bytes: processes.iter().map(|p| p.memory()).sum(),
+        uptime_secs: uptime.as_secs(),
+        processes: processes.len(),
+    })
+}
+
+/// The running server's pid and uptime, None if this app isn't running one
+async fn running(manager: &SharedServerManager) -> Option<(u32, Duration)> {
+    let mgr = manager.lock().await;
+    if *mgr.status() != ServerStatus::Running {
+        return None;
+    }
+    Some((mgr.pid()?, mgr.uptime().unwrap_or_default()))
+}
+
+/// Emit `server-stats` while the main server runs
+pub fn start_stats_monitor(app: AppHandle, manager: SharedServerManager) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(STATS_INTERVAL).await;
+            let Some((pid, uptime)) = running(&manager).await else {
+                // Nothing to compare the next server's first sample with
+                *SYSTEM.lock().unwrap_or_else(|e| e.into_inner()) = None;
+                continue;
+            };
+            let stats = tauri::async_runtime::spawn_blocking(move || sample(pid, uptime, false))
+                .await
+                .ok()
+                .flatten();
+            if let Some(stats) = stats {
+                let _ = events::emit(&app, Event::ServerStats(&stats));
+            }
+        }
+    });
+}
+
+/// CPU, memory and uptime of the main server, None if the app doesn't run one
+#[tauri::command]
+pub async fn get_server_stats(manager: State<'_, SharedServerManager>) -> Result<Option<ServerStats>, String> {
+    let Some((pid, uptime)) = running(&manager).await else {
+        return Ok(None);
+    };
+    tauri::async_runtime::spawn_blocking(move || sample(pid, uptime, true))
+        .await
+        .map_err(|e| e.to_string())
+}
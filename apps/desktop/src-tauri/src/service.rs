@@ -0,0 +1,526 @@
+// Background service: the main server kept running by the OS without the app open
+//
+// Installing registers a per-user service that starts at login and restarts the server
+// if it crashes: a launchd agent on macOS, a systemd user unit on Linux and a logon
+// task on Windows (the server doesn't speak the service control protocol a real
+// Windows service needs, and a task runs as the user, with their data dir). All of them
+// run `<data dir>/service/run.sh` (`run.cmd` on Windows) with the environment the app
+// would start the server with; it includes the database password, which the service
+// can't read from the keychain, so the script is only readable by the user. It's
+// written again each time the app starts the service, so settings changes apply.
+//
+// While it's installed the app doesn't run its own server: starting means starting the
+// service and waiting for it to answer, restarting restarts the service, and quitting
+// leaves it running. The embedded PostgreSQL needs the app, so it can't be combined.
+
+use crate::database::server_database_url;
+use crate::logs::{log_line, SharedLogStore};
+use crate::postgres;
+use crate::resources;
+use crate::server::{self, fetch_health, SharedServerManager};
+use crate::transport;
+use crate::windows::open_injected_window;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, State};
+
+const SERVICE_DIR: &str = "service";
+const LABEL: &str = "com.moneywright.server";
+const UNIT: &str = "moneywright-server.service";
+const TASK: &str = "Moneywright Server";
+const LOG_FILE: &str = "server.log";
+/// How often a starting service is asked for /health
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+static DATA_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+#[derive(Clone, Serialize)]
+pub struct ServiceStatus {
+    /// Background services exist on this OS
+    pub supported: bool,
+    pub installed: bool,
+    /// The server answers on its port
+    pub running: bool,
+    /// "launchd agent", "systemd user unit" or "logon task"
+    pub kind: &'static str,
+    /// The launchd plist or systemd unit, or the task name
+    pub registration: Option<String>,
+    pub log_file: String,
+}
+
+/// Where the service scripts live; called once the data dir is known
+pub fn init(data_dir: &Path) {
+    let _ = DATA_DIR.set(data_dir.to_path_buf());
+}
+
+fn service_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join(SERVICE_DIR)
+}
+
+fn script_path(data_dir: &Path) -> PathBuf {
+    let name = if cfg!(windows) { "run.cmd" } else { "run.sh" };
+    service_dir(data_dir).join(name)
+}
+
+/// Whether the main server runs as a background service
+pub fn is_installed() -> bool {
+    DATA_DIR.get().is_some_and(|dir| script_path(dir).is_file())
+}
+
+fn kind() -> &'static str {
+    match std::env::consts::OS {
+        "macos" => "launchd agent",
+        "linux" => "systemd user unit",
+        "windows" => "logon task",
+        _ => "service",
+    }
+}
+
+fn supported() -> bool {
+    matches!(std::env::consts::OS, "macos" | "linux" | "windows")
+}
+
+/// The launchd plist or systemd unit file
+fn registration_file() -> Option<PathBuf> {
+    match std::env::consts::OS {
+        "macos" => dirs::home_dir().map(|home| home.join("Library/LaunchAgents").join(format!("{}.plist", LABEL))),
+        "linux" => dirs::config_dir().map(|config| config.join("systemd/user").join(UNIT)),
+        _ => None,
+    }
+}
+
+fn run(command: &mut Command) -> Result<Output, String> {
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        // No console window flashing up
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    let program = command.get_program().to_string_lossy().to_string();
+    let output = command
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let text = if stderr.trim().is_empty() { stdout } else { stderr };
+        return Err(format!("{} failed: {}", program, text.trim()));
+    }
+    Ok(output)
+}
+
+/// Quote a value for a POSIX shell
+fn sh_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Everything the app would start the main server with
+async fn server_env(app: &AppHandle, data_dir: &Path, port: u16) -> Result<Vec<(String, String)>, String> {
+    let mut env = vec![
+        ("PORT".to_string(), port.to_string()),
+        ("DATA_DIR".to_string(), data_dir.to_string_lossy().to_string()),
+    ];
+    if let Some(socket) = transport::socket_path() {
+        env.push(("SERVER_SOCKET".to_string(), socket.to_string_lossy().to_string()));
+    }
+    env.extend(server::settings_env(app, data_dir).await);
+    let database_url = server_database_url(data_dir);
+    let is_postgres = database_url.is_some();
+    if let Some(url) = database_url {
+        env.push(("DATABASE_URL".to_string(), url));
+    }
+    let resources =
+        resources::locate(app, is_postgres).map_err(|e| format!("Moneywright's installation is damaged: {}", e))?;
+    env.push((
+        "MIGRATIONS_PATH".to_string(),
+        resources.migrations.to_string_lossy().to_string(),
+    ));
+    env.push(("PUBLIC_DIR".to_string(), resources.public.to_string_lossy().to_string()));
+    Ok(env)
+}
+
+/// Write the script the service runs, readable only by the user
+fn write_script(data_dir: &Path, sidecar: &Path, env: &[(String, String)]) -> Result<PathBuf, String> {
+    let dir = service_dir(data_dir);
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let path = script_path(data_dir);
+    let content = if cfg!(windows) {
+        let mut lines = vec!["@echo off".to_string()];
+        for (key, value) in env {
+            lines.push(format!("set \"{}={}\"", key, value.replace('%', "%%")));
+        }
+        lines.push(format!(
+            "\"{}\" >> \"{}\" 2>&1",
+            sidecar.display(),
+            dir.join(LOG_FILE).display()
+        ));
+        lines.join("\r\n")
+    } else {
+        let mut lines = vec!["#!/bin/sh".to_string()];
+        for (key, value) in env {
+            lines.push(format!("export {}={}", key, sh_quote(value)));
+        }
+        lines.push(format!("exec {}", sh_quote(&sidecar.to_string_lossy())));
+        lines.join("\n") + "\n"
+    };
+    fs::write(&path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o700)).map_err(|e| e.to_string())?;
+    }
+    Ok(path)
+}
+
+/// Register the script with the OS, to run at login
+fn register(data_dir: &Path, script: &Path) -> Result<(), String> {
+    let log = service_dir(data_dir).join(LOG_FILE);
+    match std::env::consts::OS {
+        "macos" => {
+            let plist = registration_file().ok_or("No home folder")?;
+            let content = format!(
+                r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{}</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>{}</string>
+    </array>
+    <key>RunAtLoad</key>
+    <true/>
+    <key>KeepAlive</key>
+    <dict>
+        <key>SuccessfulExit</key>
+        <false/>
+    </dict>
+    <key>StandardOutPath</key>
+    <string>{}</string>
+    <key>StandardErrorPath</key>
+    <string>{}</string>
+</dict>
+</plist>
+"#,
+                LABEL,
+                xml_escape(&script.to_string_lossy()),
+                xml_escape(&log.to_string_lossy()),
+                xml_escape(&log.to_string_lossy())
+            );
+            if let Some(dir) = plist.parent() {
+                fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+            }
+            fs::write(&plist, content).map_err(|e| format!("Failed to write {}: {}", plist.display(), e))?;
+            run(Command::new("launchctl").arg("load").arg("-w").arg(&plist)).map(|_| ())
+        }
+        "linux" => {
+            let unit = registration_file().ok_or("No config folder")?;
+            let content = format!(
+                "[Unit]\nDescription=Moneywright server\nAfter=network-online.target\n\n\
+                 [Service]\nExecStart=\"{}\"\nRestart=on-failure\nRestartSec=5\n\
+                 StandardOutput=append:{}\nStandardError=append:{}\n\n\
+                 [Install]\nWantedBy=default.target\n",
+                script.display(),
+                log.display(),
+                log.display()
+            );
+            if let Some(dir) = unit.parent() {
+                fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+            }
+            fs::write(&unit, content).map_err(|e| format!("Failed to write {}: {}", unit.display(), e))?;
+            run(Command::new("systemctl").args(["--user", "daemon-reload"]))?;
+            run(Command::new("systemctl").args(["--user", "enable", UNIT])).map(|_| ())
+        }
+        "windows" => {
+            // Run through wscript so no console window stays open
+            let launcher = service_dir(data_dir).join("run.vbs");
+            let vbs = format!(
+                "CreateObject(\"WScript.Shell\").Run \"\"\"{}\"\"\", 0, False\r\n",
+                script.display()
+            );
+            fs::write(&launcher, vbs).map_err(|e| format!("Failed to write {}: {}", launcher.display(), e))?;
+            let command = format!("wscript.exe \"{}\"", launcher.display());
+            run(Command::new("schtasks").args([
+                "/Create", "/F", "/SC", "ONLOGON", "/RL", "LIMITED", "/TN", TASK, "/TR", &command,
+            ]))
+            .map(|_| ())
+        }
+        os => Err(format!("Background services aren't supported on {}", os)),
+    }
+}
+
+fn unregister() -> Result<(), String> {
+    match std::env::consts::OS {
+        "macos" => {
+            let plist = registration_file().ok_or("No home folder")?;
+            if plist.exists() {
+                let _ = run(Command::new("launchctl").arg("unload").arg("-w").arg(&plist));
+                fs::remove_file(&plist).map_err(|e| format!("Failed to remove {}: {}", plist.display(), e))?;
+            }
+            Ok(())
+        }
+        "linux" => {
+            let unit = registration_file().ok_or("No config folder")?;
+            if unit.exists() {
+                let _ = run(Command::new("systemctl").args(["--user", "disable", "--now", UNIT]));
+                fs::remove_file(&unit).map_err(|e| format!("Failed to remove {}: {}", unit.display(), e))?;
+                let _ = run(Command::new("systemctl").args(["--user", "daemon-reload"]));
+            }
+            Ok(())
+        }
+        "windows" => {
+            let _ = run(Command::new("schtasks").args(["/Delete", "/F", "/TN", TASK]));
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+fn start_service() -> Result<(), String> {
+    match std::env::consts::OS {
+        "macos" => run(Command::new("launchctl").args(["start", LABEL])).map(|_| ()),
+        "linux" => run(Command::new("systemctl").args(["--user", "restart", UNIT])).map(|_| ()),
+        "windows" => run(Command::new("schtasks").args(["/Run", "/TN", TASK])).map(|_| ()),
+        os => Err(format!("Background services aren't supported on {}", os)),
+    }
+}
+
+/// Stop the service's server (it starts again at the next login, or when the app starts it)
+pub fn stop_service(port: u16, grace: Duration) -> Result<(), String> {
+    match std::env::consts::OS {
+        "macos" => run(Command::new("launchctl").args(["stop", LABEL])).map(|_| ()),
+        "linux" => run(Command::new("systemctl").args(["--user", "stop", UNIT])).map(|_| ()),
+        // A task that already started has nothing left to stop; the server is on the port
+        _ => crate::ports::stop_process_on_port(port, grace),
+    }
+}
+
+/// Stop the service and take it off the OS; its scripts stay until the caller removes them
+pub fn remove(port: u16, grace: Duration) -> Result<(), String> {
+    if let Err(e) = stop_service(port, grace) {
+        eprintln!("Warning: {}", e);
+    }
+    unregister()
+}
+
+/// Start the service with a fresh script and wait until the server answers, in place
+/// of starting the app's own
+pub async fn start_and_wait(
+    app: &AppHandle,
+    data_dir: &Path,
+    port: u16,
+    url: &str,
+    timeout: Duration,
+) -> Result<(), String> {
+    let env = server_env(app, data_dir, port).await?;
+    let sidecar = crate::arch::native_sidecar(data_dir)
+        .or_else(server::sidecar_path)
+        .ok_or("The server binary wasn't found")?;
+    write_script(data_dir, &sidecar, &env)?;
+
+    if fetch_health(url).await.is_none() {
+        tauri::async_runtime::spawn_blocking(start_service)
+            .await
+            .map_err(|e| e.to_string())??;
+    }
+    let started = Instant::now();
+    while fetch_health(url).await.is_none() {
+        if started.elapsed() >= timeout {
+            return Err(format!(
+                "The background service didn't answer within {} s (server.startup_timeout_secs); see {}",
+                timeout.as_secs(),
+                service_dir(data_dir).join(LOG_FILE).display()
+            ));
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+    Ok(())
+}
+
+/// State of the background service
+#[tauri::command]
+pub async fn get_service_status(manager: State<'_, SharedServerManager>) -> Result<ServiceStatus, String> {
+    let (data_dir, url) = {
+        let mgr = manager.lock().await;
+        (mgr.data_dir().clone(), mgr.url())
+    };
+    Ok(ServiceStatus {
+        supported: supported(),
+        installed: is_installed(),
+        running: fetch_health(&url).await.is_some(),
+        kind: kind(),
+        registration: match std::env::consts::OS {
+            "windows" => Some(TASK.to_string()),
+            _ => registration_file().map(|path| path.to_string_lossy().to_string()),
+        },
+        log_file: service_dir(&data_dir).join(LOG_FILE).to_string_lossy().to_string(),
+    })
+}
+
+/// Install the background service and move the server into it
+#[tauri::command]
+pub async fn install_service(
+    app: AppHandle,
+    manager: State<'_, SharedServerManager>,
+    log_store: State<'_, SharedLogStore>,
+) -> Result<(), String> {
+    let manager = manager.inner().clone();
+    let log_store = log_store.inner().clone();
+    let (data_dir, port) = {
+        let mgr = manager.lock().await;
+        (mgr.data_dir().clone(), mgr.port())
+    };
+    if !supported() {
+        return Err(format!(
+            "Background services aren't supported on {}",
+            std::env::consts::OS
+        ));
+    }
+    if cfg!(debug_assertions) {
+        return Err("Development builds don't run the bundled server".to_string());
+    }
+    if crate::remote::url().is_some() {
+        return Err("The app uses a remote server (server.remote_url), there's no server here to run in the background".to_string());
+    }
+    if postgres::is_active(&data_dir) {
+        return Err("The embedded PostgreSQL only runs while the app is open. Switch to SQLite or your own PostgreSQL server first.".to_string());
+    }
+
+    // The app's own server goes first; it can't share the port
+    server::stop_server(manager.clone()).await?;
+    let env = server_env(&app, &data_dir, port).await?;
+    let sidecar = crate::arch::native_sidecar(&data_dir)
+        .or_else(server::sidecar_path)
+        .ok_or("The server binary wasn't found")?;
+    let script = write_script(&data_dir, &sidecar, &env)?;
+    let dir = data_dir.clone();
+    let registered = tauri::async_runtime::spawn_blocking(move || register(&dir, &script))
+        .await
+        .map_err(|e| e.to_string())?;
+    if let Err(e) = registered {
+        let _ = fs::remove_dir_all(service_dir(&data_dir));
+        let _ = crate::restart_server(app.clone(), manager, log_store).await;
+        return Err(e);
+    }
+    log_line(
+        &app,
+        &log_store,
+        format!("Installed the server as a {}", kind()),
+        "info",
+    )
+    .await;
+    crate::restart_server(app.clone(), manager, log_store).await
+}
+
+/// Remove the background service and run the server from the app again
+#[tauri::command]
+pub async fn uninstall_service(
+    app: AppHandle,
+    manager: State<'_, SharedServerManager>,
+    log_store: State<'_, SharedLogStore>,
+) -> Result<(), String> {
+    let manager = manager.inner().clone();
+    let log_store = log_store.inner().clone();
+    let (data_dir, port, grace) = {
+        let mgr = manager.lock().await;
+        (mgr.data_dir().clone(), mgr.port(), mgr.shutdown_grace())
+    };
+    tauri::async_runtime::spawn_blocking(move || remove(port, grace))
+        .await
+        .map_err(|e| e.to_string())??;
+    let dir = service_dir(&data_dir);
+    fs::remove_dir_all(&dir).map_err(|e| format!("Failed to remove {}: {}", dir.display(), e))?;
+    log_line(&app, &log_store, format!("Removed the server's {}", kind()), "info").await;
+    crate::restart_server(app.clone(), manager, log_store).await
+}
+
+/// Open the background service window
+pub fn open_service_window(app: &AppHandle) {
+    // Static UI; values are assigned as text, never inserted as HTML
+    let script = r#"
+        const tauriApi = window.__TAURI__;
+
+        document.documentElement.innerHTML = `
+<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <title>Background Service</title>
+    <style>
+        __BASE_STYLE__
+        #content { flex: 1; overflow-y: auto; padding: 20px 24px; line-height: 1.5; }
+        #content p { margin-bottom: 12px; }
+        #result { margin-top: 12px; white-space: pre-wrap; }
+        .footer { display: flex; align-items: center; gap: 12px; padding: 14px 24px; border-top: 1px solid rgba(255, 255, 255, 0.06); }
+        .footer .spacer { flex: 1; }
+    </style>
+</head>
+<body>
+    <div id="content">
+        <p>Run the Moneywright server in the background, started when you log in, so syncs and scheduled work continue while the app is closed.</p>
+        <p id="state" role="status"></p>
+        <p class="muted mono" id="details"></p>
+        <div id="result" role="status"></div>
+    </div>
+    <div class="footer">
+        <span class="spacer"></span>
+        <button id="toggleBtn" class="primary"></button>
+    </div>
+</body>
+</html>`;
+
+        const $ = id => document.getElementById(id);
+        let status = null;
+
+        async function load() {
+            status = await tauriApi.core.invoke('get_service_status');
+            if (!status.supported) {
+                $('state').textContent = 'Background services aren\'t supported on this system.';
+                $('toggleBtn').style.display = 'none';
+                return;
+            }
+            $('state').textContent = status.installed
+                ? 'Installed as a ' + status.kind + (status.running ? ', running.' : ', not running.')
+                : 'Not installed. The server runs while the app is open.';
+            $('details').textContent = status.installed
+                ? [status.registration, 'Log: ' + status.log_file].filter(Boolean).join('\n')
+                : '';
+            $('toggleBtn').textContent = status.installed ? 'Remove Service' : 'Install Service';
+        }
+
+        $('toggleBtn').onclick = async () => {
+            const installed = status.installed;
+            if (installed && !confirm('Remove the background service? The server will only run while the app is open.')) return;
+            $('toggleBtn').disabled = true;
+            $('result').className = 'muted';
+            $('result').textContent = installed ? 'Removing...' : 'Installing and starting the service...';
+            try {
+                await tauriApi.core.invoke(installed ? 'uninstall_service' : 'install_service');
+                $('result').className = 'pass';
+                $('result').textContent = installed ? 'Removed.' : 'Installed. The server keeps running after you quit the app.';
+            } catch (e) {
+                $('result').className = 'fail';
+                $('result').textContent = String(e);
+            }
+            $('toggleBtn').disabled = false;
+            load();
+        };
+        load();
+    "#;
+
+    open_injected_window(app, "service", "Background Service", (480.0, 320.0), false, script);
+}
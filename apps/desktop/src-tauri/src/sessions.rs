@@ -0,0 +1,193 @@
+// Session and uptime statistics persisted across launches
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+const STATS_FILE: &str = "session-stats.json";
+/// How often the running session is written to disk, so crashes lose at most this much uptime
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Counters for one session, or summed over all sessions
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct SessionCounters {
+    pub app_uptime_secs: u64,
+    pub server_uptime_secs: u64,
+    pub server_starts: u64,
+    pub server_restarts: u64,
+    pub server_crashes: u64,
+}
+
+impl SessionCounters {
+    fn add(&mut self, other: &SessionCounters) {
+        self.app_uptime_secs += other.app_uptime_secs;
+        self.server_uptime_secs += other.server_uptime_secs;
+        self.server_starts += other.server_starts;
+        self.server_restarts += other.server_restarts;
+        self.server_crashes += other.server_crashes;
+    }
+}
+
+/// On-disk format: finished sessions are folded into `totals` on the next launch
+#[derive(Default, Serialize, Deserialize)]
+struct StatsFile {
+    first_session: u64,
+    sessions: u64,
+    app_crashes: u64,
+    totals: SessionCounters,
+    current: Option<SessionCounters>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct SessionStats {
+    pub first_session: u64,
+    pub sessions: u64,
+    /// Sessions that ended without a clean shutdown
+    pub app_crashes: u64,
+    pub session_started: u64,
+    pub current: SessionCounters,
+    /// All sessions including the current one
+    pub totals: SessionCounters,
+}
+
+pub struct SessionTracker {
+    path: PathBuf,
+    file: StatsFile,
+    started: Instant,
+    started_at: u64,
+    server_started: Option<Instant>,
+    /// Server uptime of runs in this session that already ended
+    server_uptime: Duration,
+    current: SessionCounters,
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+impl SessionTracker {
+    fn load(data_dir: &Path, previous_unclean: bool) -> Self {
+        let path = data_dir.join(STATS_FILE);
+        let mut file: StatsFile = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+
+        // Fold the previous session (its last checkpoint) into the totals
+        if let Some(previous) = file.current.take() {
+            file.totals.add(&previous);
+        }
+        if previous_unclean {
+            file.app_crashes += 1;
+        }
+        let started_at = unix_now();
+        if file.first_session == 0 {
+            file.first_session = started_at;
+        }
+        file.sessions += 1;
+
+        let tracker = Self {
+            path,
+            file,
+            started: Instant::now(),
+            started_at,
+            server_started: None,
+            server_uptime: Duration::ZERO,
+            current: SessionCounters::default(),
+        };
+        tracker.checkpoint();
+        tracker
+    }
+
+    fn current_counters(&self) -> SessionCounters {
+        let running = self.server_started.map(|s| s.elapsed()).unwrap_or_default();
+        SessionCounters {
+            app_uptime_secs: self.started.elapsed().as_secs(),
+            server_uptime_secs: (self.server_uptime + running).as_secs(),
+            ..self.current.clone()
+        }
+    }
+
+    /// Write the running session to disk
+    pub fn checkpoint(&self) {
+        let file = StatsFile {
+            first_session: self.file.first_session,
+            sessions: self.file.sessions,
+            app_crashes: self.file.app_crashes,
+            totals: self.file.totals.clone(),
+            current: Some(self.current_counters()),
+        };
+        match serde_json::to_string_pretty(&file) {
+            Ok(json) => {
+                if let Err(e) = fs::write(&self.path, json) {
+                    eprintln!("Warning: Failed to write session stats: {}", e);
+                }
+            }
+            Err(e) => eprintln!("Warning: Failed to serialize session stats: {}", e),
+        }
+    }
+
+    pub fn server_started(&mut self) {
+        if self.server_started.is_none() {
+            self.server_started = Some(Instant::now());
+            self.current.server_starts += 1;
+        }
+    }
+
+    pub fn server_stopped(&mut self, crashed: bool) {
+        if let Some(started) = self.server_started.take() {
+            self.server_uptime += started.elapsed();
+        }
+        if crashed {
+            self.current.server_crashes += 1;
+        }
+        self.checkpoint();
+    }
+
+    pub fn server_restarted(&mut self) {
+        self.current.server_restarts += 1;
+    }
+
+    pub fn stats(&self) -> SessionStats {
+        let current = self.current_counters();
+        let mut totals = self.file.totals.clone();
+        totals.add(&current);
+        SessionStats {
+            first_session: self.file.first_session,
+            sessions: self.file.sessions,
+            app_crashes: self.file.app_crashes,
+            session_started: self.started_at,
+            current,
+            totals,
+        }
+    }
+}
+
+pub type SharedSessionTracker = Arc<Mutex<SessionTracker>>;
+
+/// Start tracking this session; `previous_unclean` comes from the crash handler
+pub fn create_session_tracker(data_dir: &Path, previous_unclean: bool) -> SharedSessionTracker {
+    Arc::new(Mutex::new(SessionTracker::load(data_dir, previous_unclean)))
+}
+
+/// Periodically persist the running session
+pub fn start_session_checkpoints(tracker: SharedSessionTracker) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(CHECKPOINT_INTERVAL).await;
+            tracker.lock().await.checkpoint();
+        }
+    });
+}
+
+/// Get uptime, restart and crash statistics for this and previous sessions
+#[tauri::command]
+pub async fn get_session_stats(tracker: tauri::State<'_, SharedSessionTracker>) -> Result<SessionStats, String> {
+    Ok(tracker.lock().await.stats())
+}
@@ -0,0 +1,956 @@
+// Desktop settings stored as a versioned settings.toml in the data directory
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use chrono::{NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_autostart::ManagerExt;
+use tokio::sync::{watch, Mutex};
+use crate::events::{self, Event};
+use crate::logs::{log_line, SharedLogStore};
+
+const SETTINGS_FILE: &str = "settings.toml";
+/// Limit of `server.extra_args`
+const MAX_EXTRA_ARGS: usize = 32;
+
+/// Mirrors `general.hide_from_screen_capture` for windows opened later
+static CAPTURE_PROTECTED: AtomicBool = AtomicBool::new(false);
+
+/// Current settings schema version, bump when adding a migration below
+pub const SETTINGS_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    pub version: u32,
+    pub general: GeneralSettings,
+    pub server: ServerSettings,
+    pub updates: UpdateSettings,
+    pub backups: BackupSettings,
+    pub features: FeatureSettings,
+    pub power: PowerSettings,
+    pub display: DisplaySettings,
+    pub webview: WebviewSettings,
+    pub idle: IdleSettings,
+    pub notifications: NotificationSettings,
+    pub alerts: AlertSettings,
+    pub maintenance: MaintenanceSettings,
+    pub network: NetworkSettings,
+    /// Action id to accelerator, "" turns it off; see shortcuts.rs
+    #[serde(deserialize_with = "crate::shortcuts::deserialize")]
+    pub shortcuts: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct GeneralSettings {
+    /// Open the web app in the default browser once the server is up
+    pub open_browser_on_start: bool,
+    /// Record anonymous local usage counters (opt-in, see analytics.rs)
+    pub usage_stats: bool,
+    /// Start Moneywright when the user logs in
+    pub launch_at_login: bool,
+    /// Keep windows out of screenshots, recordings and screen shares (Windows and macOS)
+    pub hide_from_screen_capture: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ServerSettings {
+    /// Start the server sidecar when the app launches
+    pub auto_start: bool,
+    /// Start it only once the main window is shown, see server.rs
+    pub lazy_start: bool,
+    /// Pause the server once the main window has been hidden this long, 0 keeps it running
+    /// (not while it serves other devices through `external_url` or LAN mode)
+    pub pause_after_hidden_minutes: u32,
+    /// Restart the server once a day to clear leaks and re-run maintenance
+    pub nightly_restart: bool,
+    /// Local time of the nightly restart, "HH:MM"
+    pub nightly_restart_time: String,
+    /// Server log verbosity: "error", "warn", "info" or "debug" (applied on restart)
+    pub log_level: String,
+    /// Address of a reverse proxy in front of the server, e.g. "https://money.example.com"
+    /// (empty when it's only used on this computer), see proxy.rs
+    pub external_url: String,
+    /// Start the server again when it crashes after coming up
+    pub crash_restart: bool,
+    /// Restart attempts before giving up, waiting twice as long before each one
+    pub crash_restart_max: u32,
+    /// Seconds a stopping server gets to finish its writes before it's killed
+    pub shutdown_grace_secs: u32,
+    /// Seconds a starting server has to come up
+    pub startup_timeout_secs: u32,
+    /// Seconds pending database migrations may take during a start, see server.rs
+    pub migration_timeout_secs: u32,
+    /// "tcp", or "socket" for a Unix socket on macOS and Linux (applied on launch), see transport.rs
+    pub transport: String,
+    /// Address of a Moneywright server to use instead of running one, e.g. "http://nas.local:17777"
+    /// (empty runs the built-in server; applied on launch), see remote.rs
+    pub remote_url: String,
+    /// Seconds between liveness pings of the running server, 0 turns the watchdog off
+    pub liveness_interval_secs: u32,
+    /// Pings missed in a row before the server counts as hung
+    pub liveness_failures: u32,
+    /// Extra command line arguments for the server, e.g. ["--verbose"] (applied on restart;
+    /// the background service runs without them)
+    pub extra_args: Vec<String>,
+    /// Extra environment variables for the server, overriding the data dir .env; the ones
+    /// the app sets itself can't be changed (applied on restart)
+    pub extra_env: BTreeMap<String, String>,
+    /// Web app to serve: "stable", or "preview" when the release bundles one (applied on
+    /// restart), see uibuild.rs
+    pub ui_build: String,
+    /// Address the main server listens on: "127.0.0.1", or "0.0.0.0" for other devices on
+    /// the network (applied on restart), see lan.rs
+    pub bind_address: String,
+    /// Serve the main server over https with a self-signed certificate (applied on
+    /// restart), see tls.rs
+    pub tls: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct UpdateSettings {
+    pub auto_check: bool,
+    pub auto_download: bool,
+    /// "stable", "beta" or "nightly", see updater.rs
+    pub channel: String,
+    /// Versions not to offer again ("Skip This Version")
+    pub skipped_versions: Vec<String>,
+    /// "restart" installs a downloaded update right away and asks for a restart,
+    /// "on_quit" keeps it until the app quits; see updater.rs
+    pub policy: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct BackupSettings {
+    pub enabled: bool,
+    pub interval_hours: u32,
+    /// Number of automatic backups to keep
+    pub keep: u32,
+    /// Where backups are written; empty means `<data dir>/backups`
+    pub directory: String,
+    /// Days deleted backups stay in the quarantine before they're gone, see quarantine.rs
+    pub quarantine_days: u32,
+}
+
+/// Opt-ins for experimental subsystems, see flags.rs
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct FeatureSettings {
+    pub local_ai: bool,
+    pub lan_mode: bool,
+    pub mail_ingestion: bool,
+}
+
+/// Battery handling for heavy background tasks, see power.rs
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct PowerSettings {
+    /// Hold back backups, maintenance restarts and update downloads on battery
+    pub defer_on_battery: bool,
+    /// Run anyway once the battery is at least this full, in percent
+    pub battery_threshold: u32,
+}
+
+/// Zoom of the app windows, see display.rs
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct DisplaySettings {
+    /// Zoom on displays without their own entry, 1.0 is 100%
+    pub zoom: f64,
+    /// Zoom per display, keyed by display name and resolution
+    pub per_display: BTreeMap<String, f64>,
+}
+
+/// User agent and popup/autoplay policy of the app windows, see webview.rs
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct WebviewSettings {
+    /// Replaces the default user agent for windows opened afterwards; empty keeps it
+    pub user_agent: String,
+    /// `window.open` popups: "none", "auth" (only to `auth_hosts`, others open in the browser) or "all"
+    pub popups: String,
+    /// Sign-in and bank-linking hosts, subdomains included
+    pub auth_hosts: Vec<String>,
+    /// Let media play without a click (WebView2 on Windows asks; elsewhere the platform decides)
+    pub autoplay: bool,
+    /// Render without the GPU, for blank or flickering windows (Linux and Windows; applied
+    /// on restart, `--disable-gpu` turns it on and `--enable-gpu` off)
+    pub software_rendering: bool,
+}
+
+/// Locking the app when the machine is left unattended, see idle.rs
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct IdleSettings {
+    /// Lock the app windows after this many minutes without keyboard or mouse input, 0 turns it off
+    pub lock_after_minutes: u32,
+    /// Also sign out of the web app, so unlocking needs the password again
+    pub expire_session: bool,
+}
+
+/// When native notifications are shown, see notifications.rs
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct NotificationSettings {
+    /// Per type: "instant", "digest" (collected into the weekly digest) or "off"
+    pub bills: String,
+    pub budgets: String,
+    pub imports: String,
+    pub updates: String,
+    /// Scheduled restarts and backups
+    pub maintenance: String,
+    /// Hold instant notifications between `quiet_start` and `quiet_end` until it ends
+    pub quiet_hours: bool,
+    pub quiet_start: String,
+    pub quiet_end: String,
+    /// Weekday and time the digest is shown, e.g. "monday" at "09:00"
+    pub digest_day: String,
+    pub digest_time: String,
+}
+
+/// Alerts for unattended installs when the server won't start or stays down, see alerts.rs
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct AlertSettings {
+    /// POSTed a JSON payload on each alert, empty for none
+    pub webhook_url: String,
+    /// Alert once the server has been unreachable this long
+    pub downtime_minutes: u32,
+    /// SMTP server for email alerts, empty for none; the password lives in the keychain
+    pub smtp_host: String,
+    /// 465 connects over TLS, anything else upgrades with STARTTLS
+    pub smtp_port: u16,
+    pub smtp_username: String,
+    pub email_from: String,
+    pub email_to: String,
+}
+
+/// When heavy background work may run, see scheduler.rs
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct MaintenanceSettings {
+    /// Hold backups, the nightly restart, scheduled exports and update downloads until
+    /// the window between `window_start` and `window_end` (which may span midnight)
+    pub window: bool,
+    pub window_start: String,
+    pub window_end: String,
+}
+
+/// Data the shell may use each month (see netusage.rs) and how it reaches the internet
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct NetworkSettings {
+    /// Stop automatic downloads once this many MB were used in a month, 0 for no limit
+    pub monthly_limit_mb: u32,
+    /// Proxy for update checks and downloads: "system", "manual" or "none", see netproxy.rs
+    pub proxy: String,
+    pub proxy_host: String,
+    pub proxy_port: u16,
+    /// The password is kept in the keychain, not here
+    pub proxy_username: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            general: GeneralSettings::default(),
+            server: ServerSettings::default(),
+            updates: UpdateSettings::default(),
+            backups: BackupSettings::default(),
+            features: FeatureSettings::default(),
+            power: PowerSettings::default(),
+            display: DisplaySettings::default(),
+            webview: WebviewSettings::default(),
+            idle: IdleSettings::default(),
+            notifications: NotificationSettings::default(),
+            alerts: AlertSettings::default(),
+            maintenance: MaintenanceSettings::default(),
+            network: NetworkSettings::default(),
+            shortcuts: crate::shortcuts::defaults(),
+        }
+    }
+}
+
+impl Default for ServerSettings {
+    fn default() -> Self {
+        Self {
+            auto_start: true,
+            lazy_start: false,
+            pause_after_hidden_minutes: 0,
+            nightly_restart: false,
+            nightly_restart_time: "04:00".to_string(),
+            log_level: "info".to_string(),
+            external_url: String::new(),
+            crash_restart: true,
+            crash_restart_max: 5,
+            shutdown_grace_secs: 10,
+            startup_timeout_secs: 30,
+            migration_timeout_secs: 1800,
+            transport: "tcp".to_string(),
+            remote_url: String::new(),
+            liveness_interval_secs: 15,
+            liveness_failures: 3,
+            extra_args: Vec::new(),
+            extra_env: BTreeMap::new(),
+            ui_build: "stable".to_string(),
+            bind_address: crate::lan::LOCALHOST.to_string(),
+            tls: false,
+        }
+    }
+}
+
+impl Default for UpdateSettings {
+    fn default() -> Self {
+        Self {
+            auto_check: true,
+            auto_download: true,
+            channel: "stable".to_string(),
+            skipped_versions: Vec::new(),
+            policy: "restart".to_string(),
+        }
+    }
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_hours: 24,
+            keep: 7,
+            quarantine_days: 14,
+            directory: String::new(),
+        }
+    }
+}
+
+impl Default for PowerSettings {
+    fn default() -> Self {
+        Self {
+            defer_on_battery: true,
+            battery_threshold: 50,
+        }
+    }
+}
+
+impl Default for DisplaySettings {
+    fn default() -> Self {
+        Self { zoom: 1.0, per_display: BTreeMap::new() }
+    }
+}
+
+impl Default for WebviewSettings {
+    fn default() -> Self {
+        Self {
+            user_agent: String::new(),
+            popups: "auth".to_string(),
+            auth_hosts: ["plaid.com", "accounts.google.com", "login.microsoftonline.com", "appleid.apple.com"]
+                .map(String::from)
+                .to_vec(),
+            autoplay: false,
+            software_rendering: false,
+        }
+    }
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            bills: "instant".to_string(),
+            budgets: "instant".to_string(),
+            imports: "instant".to_string(),
+            updates: "instant".to_string(),
+            maintenance: "instant".to_string(),
+            quiet_hours: false,
+            quiet_start: "22:00".to_string(),
+            quiet_end: "07:00".to_string(),
+            digest_day: "monday".to_string(),
+            digest_time: "09:00".to_string(),
+        }
+    }
+}
+
+impl Default for NetworkSettings {
+    fn default() -> Self {
+        Self {
+            monthly_limit_mb: 0,
+            proxy: "system".to_string(),
+            proxy_host: String::new(),
+            proxy_port: 8080,
+            proxy_username: String::new(),
+        }
+    }
+}
+
+impl Default for AlertSettings {
+    fn default() -> Self {
+        Self {
+            webhook_url: String::new(),
+            downtime_minutes: 10,
+            smtp_host: String::new(),
+            smtp_port: 587,
+            smtp_username: String::new(),
+            email_from: String::new(),
+            email_to: String::new(),
+        }
+    }
+}
+
+impl Default for MaintenanceSettings {
+    fn default() -> Self {
+        Self {
+            window: false,
+            window_start: "02:00".to_string(),
+            window_end: "04:00".to_string(),
+        }
+    }
+}
+
+impl Settings {
+    /// Check value ranges, returning a message naming the offending setting
+    pub fn validate(&self) -> Result<(), String> {
+        if NaiveTime::parse_from_str(&self.server.nightly_restart_time, "%H:%M").is_err() {
+            return Err("server.nightly_restart_time must be a time like \"04:00\"".to_string());
+        }
+        if !crate::loglevel::LOG_LEVELS.contains(&self.server.log_level.as_str()) {
+            return Err("server.log_level must be \"error\", \"warn\", \"info\" or \"debug\"".to_string());
+        }
+        if !self.server.external_url.is_empty() {
+            crate::proxy::parse_external_url(&self.server.external_url).map_err(|e| format!("server.external_url: {}", e))?;
+        }
+        if !(1..=120).contains(&self.server.shutdown_grace_secs) {
+            return Err("server.shutdown_grace_secs must be between 1 and 120".to_string());
+        }
+        if !(10..=600).contains(&self.server.startup_timeout_secs) {
+            return Err("server.startup_timeout_secs must be between 10 and 600".to_string());
+        }
+        if !(60..=86400).contains(&self.server.migration_timeout_secs) {
+            return Err("server.migration_timeout_secs must be between 60 and 86400".to_string());
+        }
+        if !(1..=10).contains(&self.server.crash_restart_max) {
+            return Err("server.crash_restart_max must be between 1 and 10".to_string());
+        }
+        if self.server.liveness_interval_secs != 0 && !(5..=600).contains(&self.server.liveness_interval_secs) {
+            return Err("server.liveness_interval_secs must be 0 (off) or between 5 and 600".to_string());
+        }
+        if self.server.pause_after_hidden_minutes > 24 * 60 {
+            return Err("server.pause_after_hidden_minutes must be between 0 (off) and 1440".to_string());
+        }
+        if !(1..=20).contains(&self.server.liveness_failures) {
+            return Err("server.liveness_failures must be between 1 and 20".to_string());
+        }
+        if !crate::uibuild::BUILDS.contains(&self.server.ui_build.as_str()) {
+            return Err("server.ui_build must be \"stable\" or \"preview\"".to_string());
+        }
+        if !crate::lan::BIND_ADDRESSES.contains(&self.server.bind_address.as_str()) {
+            return Err("server.bind_address must be \"127.0.0.1\" or \"0.0.0.0\"".to_string());
+        }
+        if self.server.extra_args.len() > MAX_EXTRA_ARGS {
+            return Err(format!("server.extra_args can have at most {} arguments", MAX_EXTRA_ARGS));
+        }
+        if let Some(arg) = self.server.extra_args.iter().find(|a| a.trim().is_empty() || a.len() > 1024 || a.contains(['\0', '\r', '\n'])) {
+            return Err(format!("server.extra_args: \"{}\" must be a single line of up to 1024 characters", arg.escape_default()));
+        }
+        for (key, value) in &self.server.extra_env {
+            crate::envconfig::validate(key, Some(value)).map_err(|e| format!("server.extra_env: {}", e))?;
+        }
+        if !crate::transport::TRANSPORTS.contains(&self.server.transport.as_str()) {
+            return Err("server.transport must be \"tcp\" or \"socket\"".to_string());
+        }
+        if self.server.transport == "socket" {
+            if !cfg!(unix) {
+                return Err("server.transport \"socket\" is only available on macOS and Linux".to_string());
+            }
+            if !self.server.external_url.is_empty() {
+                return Err("server.transport \"socket\" can't be used with server.external_url".to_string());
+            }
+            if self.features.lan_mode {
+                return Err("server.transport \"socket\" can't be used with features.lan_mode".to_string());
+            }
+            if crate::lan::is_enabled(self) {
+                return Err("server.transport \"socket\" can't be used with server.bind_address \"0.0.0.0\"".to_string());
+            }
+            if self.server.tls {
+                return Err("server.transport \"socket\" can't be used with server.tls".to_string());
+            }
+            if !self.server.remote_url.is_empty() {
+                return Err("server.transport \"socket\" can't be used with server.remote_url".to_string());
+            }
+        }
+        if !self.server.remote_url.is_empty() {
+            crate::remote::parse_remote_url(&self.server.remote_url).map_err(|e| format!("server.remote_url: {}", e))?;
+        }
+        if !crate::updater::CHANNELS.contains(&self.updates.channel.as_str()) {
+            return Err("updates.channel must be \"stable\", \"beta\" or \"nightly\"".to_string());
+        }
+        if !crate::updater::POLICIES.contains(&self.updates.policy.as_str()) {
+            return Err("updates.policy must be \"restart\" or \"on_quit\"".to_string());
+        }
+        if !(1..=24 * 7).contains(&self.backups.interval_hours) {
+            return Err("backups.interval_hours must be between 1 and 168".to_string());
+        }
+        if !(1..=100).contains(&self.backups.keep) {
+            return Err("backups.keep must be between 1 and 100".to_string());
+        }
+        if self.backups.quarantine_days > 365 {
+            return Err("backups.quarantine_days must be at most 365".to_string());
+        }
+        if !self.backups.directory.is_empty() && !Path::new(&self.backups.directory).is_absolute() {
+            return Err("backups.directory must be an absolute path".to_string());
+        }
+        if self.power.battery_threshold > 100 {
+            return Err("power.battery_threshold must be between 0 and 100".to_string());
+        }
+        let zoom_range = crate::display::MIN_ZOOM..=crate::display::MAX_ZOOM;
+        if !zoom_range.contains(&self.display.zoom) {
+            return Err("display.zoom must be between 0.5 and 3".to_string());
+        }
+        if let Some(name) = self.display.per_display.iter().find(|(_, z)| !zoom_range.contains(z)).map(|(n, _)| n) {
+            return Err(format!("display.per_display.\"{}\" must be between 0.5 and 3", name));
+        }
+        if !crate::webview::POPUP_POLICIES.contains(&self.webview.popups.as_str()) {
+            return Err("webview.popups must be \"none\", \"auth\" or \"all\"".to_string());
+        }
+        if self.webview.user_agent.contains(['\r', '\n']) {
+            return Err("webview.user_agent must be a single line".to_string());
+        }
+        if let Some(host) = self.webview.auth_hosts.iter().find(|h| h.is_empty() || h.contains(['/', ':', ' '])) {
+            return Err(format!("webview.auth_hosts: \"{}\" must be a host name like \"plaid.com\"", host));
+        }
+        if self.idle.lock_after_minutes > 24 * 60 {
+            return Err("idle.lock_after_minutes must be between 0 (off) and 1440".to_string());
+        }
+        let notifications = &self.notifications;
+        for (name, mode) in [
+            ("bills", &notifications.bills),
+            ("budgets", &notifications.budgets),
+            ("imports", &notifications.imports),
+            ("updates", &notifications.updates),
+            ("maintenance", &notifications.maintenance),
+        ] {
+            if !crate::notifications::DELIVERY_MODES.contains(&mode.as_str()) {
+                return Err(format!("notifications.{} must be \"instant\", \"digest\" or \"off\"", name));
+            }
+        }
+        for (name, time) in [
+            ("quiet_start", &notifications.quiet_start),
+            ("quiet_end", &notifications.quiet_end),
+            ("digest_time", &notifications.digest_time),
+        ] {
+            if NaiveTime::parse_from_str(time, "%H:%M").is_err() {
+                return Err(format!("notifications.{} must be a time like \"22:00\"", name));
+            }
+        }
+        if notifications.digest_day.parse::<Weekday>().is_err() {
+            return Err("notifications.digest_day must be a weekday like \"monday\"".to_string());
+        }
+        let alerts = &self.alerts;
+        if !alerts.webhook_url.is_empty() && !alerts.webhook_url.starts_with("https://") && !alerts.webhook_url.starts_with("http://") {
+            return Err("alerts.webhook_url must be an http:// or https:// URL".to_string());
+        }
+        if !(1..=24 * 60).contains(&alerts.downtime_minutes) {
+            return Err("alerts.downtime_minutes must be between 1 and 1440".to_string());
+        }
+        if !alerts.smtp_host.is_empty() {
+            if alerts.smtp_port == 0 {
+                return Err("alerts.smtp_port must be a port number".to_string());
+            }
+            for (name, address) in [("email_from", &alerts.email_from), ("email_to", &alerts.email_to)] {
+                if address.parse::<lettre::message::Mailbox>().is_err() {
+                    return Err(format!("alerts.{} must be an email address", name));
+                }
+            }
+        }
+        let maintenance = &self.maintenance;
+        for (name, time) in [("window_start", &maintenance.window_start), ("window_end", &maintenance.window_end)] {
+            if NaiveTime::parse_from_str(time, "%H:%M").is_err() {
+                return Err(format!("maintenance.{} must be a time like \"02:00\"", name));
+            }
+        }
+        if maintenance.window && maintenance.window_start == maintenance.window_end {
+            return Err("maintenance.window_start and window_end must differ".to_string());
+        }
+        if self.network.monthly_limit_mb > 1024 * 1024 {
+            return Err("network.monthly_limit_mb must be at most 1048576 (1 TB)".to_string());
+        }
+        if !crate::netproxy::MODES.contains(&self.network.proxy.as_str()) {
+            return Err(format!("network.proxy must be one of {}", crate::netproxy::MODES.join(", ")));
+        }
+        if self.network.proxy == "manual" {
+            crate::netproxy::validate_host(&self.network.proxy_host)?;
+            if self.network.proxy_port == 0 {
+                return Err("network.proxy_port must be a port number".to_string());
+            }
+        }
+        if self.network.proxy_username.chars().any(|c| c.is_control()) {
+            return Err("network.proxy_username must be a single line".to_string());
+        }
+        crate::shortcuts::validate(&self.shortcuts)?;
+        Ok(())
+    }
+}
+
+/// Upgrade a raw settings table from `from` to the next version
+/// Version 0 is the unversioned flat layout (`auto_start = true` at the top level)
+fn migrate_step(table: &mut toml::Table, from: u32) {
+    if from == 0 {
+        let flat = [
+            ("open_browser_on_start", "general"),
+            ("auto_start", "server"),
+            ("auto_check_updates", "updates"),
+        ];
+        for (key, section) in flat {
+            if let Some(value) = table.remove(key) {
+                let key = if key == "auto_check_updates" { "auto_check" } else { key };
+                let section = table
+                    .entry(section)
+                    .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+                if let Some(section) = section.as_table_mut() {
+                    section.insert(key.to_string(), value);
+                }
+            }
+        }
+    }
+}
+
+fn migrate(table: &mut toml::Table) -> u32 {
+    let mut version = table
+        .get("version")
+        .and_then(|v| v.as_integer())
+        .map(|v| v.max(0) as u32)
+        .unwrap_or(0);
+
+    while version < SETTINGS_VERSION {
+        migrate_step(table, version);
+        version += 1;
+    }
+    table.insert("version".to_string(), toml::Value::Integer(version.into()));
+    version
+}
+
+/// Settings from the text of a settings.toml (e.g. another machine's), migrated like the file
+pub fn parse_settings(content: &str) -> Result<Settings, String> {
+    let mut table = content
+        .parse::<toml::Table>()
+        .map_err(|e| format!("Invalid settings: {}", e))?;
+    migrate(&mut table);
+    let mut settings: Settings = table.try_into().map_err(|e| format!("Invalid settings: {}", e))?;
+    settings.version = SETTINGS_VERSION;
+    settings.validate()?;
+    Ok(settings)
+}
+
+fn settings_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(SETTINGS_FILE)
+}
+
+/// Load settings from disk, migrating older versions and falling back to defaults
+fn load_settings(path: &Path) -> Settings {
+    let content = match fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(_) => return Settings::default(),
+    };
+
+    let mut table = match content.parse::<toml::Table>() {
+        Ok(t) => t,
+        Err(e) => {
+            eprintln!("Warning: Invalid {}, using defaults: {}", SETTINGS_FILE, e);
+            let _ = fs::rename(path, path.with_extension("toml.invalid"));
+            return Settings::default();
+        }
+    };
+
+    let original_version = table.get("version").and_then(|v| v.as_integer());
+    let version = migrate(&mut table);
+    if version > SETTINGS_VERSION {
+        // Written by a newer app - keep a copy so a downgrade doesn't lose anything
+        let _ = fs::copy(path, path.with_extension(format!("v{}.toml.bak", version)));
+    }
+
+    let mut settings = match table.try_into::<Settings>() {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("Warning: Invalid {}, using defaults: {}", SETTINGS_FILE, e);
+            let _ = fs::rename(path, path.with_extension("toml.invalid"));
+            return Settings::default();
+        }
+    };
+    settings.version = SETTINGS_VERSION;
+
+    // Out-of-range values fall back one by one; the file as it was is kept alongside
+    let reset = reset_invalid(&mut settings);
+    if !reset.is_empty() {
+        eprintln!("Warning: {} has invalid values, reset to defaults: {}", SETTINGS_FILE, reset.join(", "));
+        let _ = fs::copy(path, path.with_extension("toml.invalid"));
+    }
+
+    if original_version != Some(SETTINGS_VERSION as i64) || !reset.is_empty() {
+        if let Err(e) = save_settings(path, &settings) {
+            eprintln!("Warning: Failed to save migrated settings: {}", e);
+        }
+    }
+
+    settings
+}
+
+/// Put each setting `validate` rejects back to its default, returning their names.
+/// Errors start with the setting's name, e.g. "backups.keep must be between 1 and 100".
+fn reset_invalid(settings: &mut Settings) -> Vec<String> {
+    let mut reset: Vec<String> = Vec::new();
+    let defaults = serde_json::to_value(Settings::default()).unwrap_or(Value::Null);
+    while let Err(e) = settings.validate() {
+        let name = e.split([' ', ':']).next().unwrap_or_default();
+        // Keys of open maps (display.per_display."name") reset with their map
+        let name = name.split(".\"").next().unwrap_or_default().to_string();
+        let pointer = format!("/{}", name.replace('.', "/"));
+        let fixed = (!reset.contains(&name))
+            .then(|| defaults.pointer(&pointer).cloned())
+            .flatten()
+            .and_then(|default| {
+                let mut value = serde_json::to_value(&*settings).ok()?;
+                *value.pointer_mut(&pointer)? = default;
+                serde_json::from_value::<Settings>(value).ok()
+            });
+        match fixed {
+            Some(fixed) => *settings = fixed,
+            // Not a single setting after all
+            None => {
+                *settings = Settings::default();
+                reset.push("all settings".to_string());
+                break;
+            }
+        }
+        reset.push(name);
+    }
+    reset
+}
+
+fn save_settings(path: &Path, settings: &Settings) -> Result<(), String> {
+    let content = toml::to_string_pretty(settings).map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    // Write to a temp file first so a crash can't leave a truncated settings file
+    let tmp = path.with_extension("toml.tmp");
+    fs::write(&tmp, content).map_err(|e| format!("Failed to write settings: {}", e))?;
+    fs::rename(&tmp, path).map_err(|e| format!("Failed to write settings: {}", e))
+}
+
+/// Settings holding a map keyed by user data, where updates may add new keys
+const OPEN_MAPS: &[&str] = &["display.per_display", "server.extra_env"];
+
+/// Recursively apply a partial JSON object onto the current settings
+fn merge_changes(target: &mut Value, changes: &Value, prefix: &str) -> Result<(), String> {
+    let (Some(target), Some(changes)) = (target.as_object_mut(), changes.as_object()) else {
+        return Err(format!("{} must be an object", if prefix.is_empty() { "settings" } else { prefix }));
+    };
+
+    for (key, value) in changes {
+        let name = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+        if name == "version" {
+            return Err("version cannot be changed".to_string());
+        }
+        match target.get_mut(key) {
+            Some(existing) if existing.is_object() => merge_changes(existing, value, &name)?,
+            Some(existing) => *existing = value.clone(),
+            None if OPEN_MAPS.contains(&prefix) => {
+                target.insert(key.clone(), value.clone());
+            }
+            None => return Err(format!("Unknown setting: {}", name)),
+        }
+    }
+    Ok(())
+}
+
+pub struct SettingsStore {
+    path: PathBuf,
+    tx: watch::Sender<Settings>,
+}
+
+impl SettingsStore {
+    pub fn load(data_dir: &Path) -> Self {
+        let path = settings_path(data_dir);
+        let (tx, _) = watch::channel(load_settings(&path));
+        Self { path, tx }
+    }
+
+    pub fn get(&self) -> Settings {
+        self.tx.borrow().clone()
+    }
+
+    /// Receive the latest settings whenever they change
+    pub fn subscribe(&self) -> watch::Receiver<Settings> {
+        self.tx.subscribe()
+    }
+
+    /// Apply a partial update, validate, persist and notify subscribers
+    pub fn update(&self, changes: &Value) -> Result<Settings, String> {
+        let mut value = serde_json::to_value(self.get()).map_err(|e| e.to_string())?;
+        merge_changes(&mut value, changes, "")?;
+        let settings: Settings = serde_json::from_value(value).map_err(|e| format!("Invalid setting value: {}", e))?;
+        self.replace(settings)
+    }
+
+    /// Swap in a complete set of settings, validate, persist and notify subscribers
+    pub fn replace(&self, settings: Settings) -> Result<Settings, String> {
+        settings.validate()?;
+
+        save_settings(&self.path, &settings)?;
+        self.tx.send_if_modified(|current| {
+            let changed = *current != settings;
+            *current = settings.clone();
+            changed
+        });
+        Ok(settings)
+    }
+}
+
+pub type SharedSettings = Arc<Mutex<SettingsStore>>;
+
+pub fn create_settings_store(data_dir: &Path) -> SharedSettings {
+    Arc::new(Mutex::new(SettingsStore::load(data_dir)))
+}
+
+/// Get the current desktop settings
+#[tauri::command]
+pub async fn get_settings(settings: tauri::State<'_, SharedSettings>) -> Result<Settings, String> {
+    Ok(settings.lock().await.get())
+}
+
+/// Update desktop settings with a partial object, e.g. `{ "updates": { "auto_check": false } }`
+#[tauri::command]
+pub async fn update_settings(
+    app: AppHandle,
+    settings: tauri::State<'_, SharedSettings>,
+    changes: Value,
+) -> Result<Settings, String> {
+    let updated = settings.lock().await.update(&changes)?;
+    let _ = events::emit(&app, Event::SettingsChanged(&updated));
+    Ok(updated)
+}
+
+/// Names of the top-level sections that differ between two settings snapshots
+fn changed_sections(old: &Settings, new: &Settings) -> Vec<&'static str> {
+    let mut sections = Vec::new();
+    if old.general != new.general {
+        sections.push("general");
+    }
+    if old.server != new.server {
+        sections.push("server");
+    }
+    if old.updates != new.updates {
+        sections.push("updates");
+    }
+    if old.backups != new.backups {
+        sections.push("backups");
+    }
+    if old.features != new.features {
+        sections.push("features");
+    }
+    if old.power != new.power {
+        sections.push("power");
+    }
+    if old.display != new.display {
+        sections.push("display");
+    }
+    if old.webview != new.webview {
+        sections.push("webview");
+    }
+    if old.idle != new.idle {
+        sections.push("idle");
+    }
+    if old.notifications != new.notifications {
+        sections.push("notifications");
+    }
+    if old.alerts != new.alerts {
+        sections.push("alerts");
+    }
+    if old.maintenance != new.maintenance {
+        sections.push("maintenance");
+    }
+    if old.network != new.network {
+        sections.push("network");
+    }
+    if old.shortcuts != new.shortcuts {
+        sections.push("shortcuts");
+    }
+    sections
+}
+
+/// Record settings changes in the log so exported logs show when behaviour was reconfigured
+pub fn spawn_settings_logger(app: AppHandle, log_store: SharedLogStore, mut rx: watch::Receiver<Settings>) {
+    tauri::async_runtime::spawn(async move {
+        let mut previous = rx.borrow_and_update().clone();
+        while rx.changed().await.is_ok() {
+            let current = rx.borrow_and_update().clone();
+            let sections = changed_sections(&previous, &current);
+            if !sections.is_empty() {
+                let msg = format!("Settings updated: {}", sections.join(", "));
+                log_line(&app, &log_store, msg, "info").await;
+            }
+            previous = current;
+        }
+    });
+}
+
+/// Keep the OS login item in sync with `general.launch_at_login`
+pub fn spawn_autostart_sync(app: AppHandle, mut rx: watch::Receiver<Settings>) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let wanted = rx.borrow_and_update().general.launch_at_login;
+            let autolaunch = app.autolaunch();
+            if autolaunch.is_enabled().ok() != Some(wanted) {
+                let result = if wanted { autolaunch.enable() } else { autolaunch.disable() };
+                if let Err(e) = result {
+                    eprintln!("Warning: Failed to update launch at login: {}", e);
+                }
+            }
+            if rx.changed().await.is_err() {
+                break;
+            }
+        }
+    });
+}
+
+/// Whether new windows should be excluded from screen capture
+pub fn capture_protected() -> bool {
+    CAPTURE_PROTECTED.load(Ordering::SeqCst)
+}
+
+/// Apply `general.hide_from_screen_capture` to every open window, now and on change
+/// (SetWindowDisplayAffinity on Windows, NSWindow sharingType on macOS; no effect on Linux)
+pub fn spawn_capture_protection_sync(app: AppHandle, mut rx: watch::Receiver<Settings>) {
+    // Set right away so the menu built during setup shows the saved state
+    CAPTURE_PROTECTED.store(rx.borrow().general.hide_from_screen_capture, Ordering::SeqCst);
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let protected = rx.borrow_and_update().general.hide_from_screen_capture;
+            CAPTURE_PROTECTED.store(protected, Ordering::SeqCst);
+            for window in app.webview_windows().values() {
+                if let Err(e) = window.set_content_protected(protected) {
+                    eprintln!("Warning: Failed to update screen capture protection: {}", e);
+                }
+            }
+            if rx.changed().await.is_err() {
+                break;
+            }
+        }
+    });
+}
+
+/// Flip `general.hide_from_screen_capture` (View menu)
+pub async fn toggle_capture_protection(app: &AppHandle) -> Result<(), String> {
+    let settings = app.state::<SharedSettings>().inner().clone();
+    let store = settings.lock().await;
+    let hide = !store.get().general.hide_from_screen_capture;
+    let updated = store.update(&serde_json::json!({ "general": { "hide_from_screen_capture": hide } }))?;
+    drop(store);
+    let _ = events::emit(app, Event::SettingsChanged(&updated));
+    Ok(())
+}
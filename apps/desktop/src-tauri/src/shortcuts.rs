@@ -0,0 +1,332 @@
+// Keyboard shortcuts for menu items and global (system-wide) actions
+//
+// `ACTIONS` holds the defaults; the `[shortcuts]` section of settings.toml overrides
+// them per action, and an empty string turns one off. Menu accelerators are rebuilt
+// and global shortcuts re-registered whenever the section changes, so edits apply
+// without a restart.
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::Mutex;
+use serde::{Deserialize, Deserializer, Serialize};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+use tokio::sync::watch;
+use crate::logs::{log_line, SharedLogStore};
+use crate::settings::Settings;
+use crate::windows::open_injected_window;
+
+#[derive(Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+    /// Menu accelerator, active while the app is focused
+    Menu,
+    /// Registered with the OS, active everywhere
+    Global,
+}
+
+pub struct Action {
+    /// Menu item id for menu actions
+    pub id: &'static str,
+    pub label: &'static str,
+    pub default: &'static str,
+    pub scope: Scope,
+}
+
+const fn menu(id: &'static str, label: &'static str, default: &'static str) -> Action {
+    Action { id, label, default, scope: Scope::Menu }
+}
+
+pub const ACTIONS: &[Action] = &[
+    menu("about", "About Moneywright", "CmdOrCtrl+Alt+I"),
+    menu("check_updates", "Check for Updates", "CmdOrCtrl+Shift+U"),
+    menu("preferences", "Preferences", "CmdOrCtrl+,"),
+    menu("lock", "Lock Moneywright", "CmdOrCtrl+Shift+K"),
+    menu("quit", "Quit Moneywright", "CmdOrCtrl+Q"),
+    menu("refresh", "Refresh", "CmdOrCtrl+R"),
+    menu("pause_server", "Pause or Resume Server", ""),
+    menu("zoom_in", "Zoom In", "CmdOrCtrl+="),
+    menu("zoom_out", "Zoom Out", "CmdOrCtrl+-"),
+    menu("zoom_reset", "Actual Size", "CmdOrCtrl+0"),
+    menu("open_browser", "Open in Browser", "CmdOrCtrl+Shift+O"),
+    menu("import_legacy", "Import from Mint, YNAB or Quicken", "CmdOrCtrl+Shift+M"),
+    menu("database", "Database Settings", "CmdOrCtrl+Shift+D"),
+    menu("server_env", "Server Environment", "CmdOrCtrl+Alt+V"),
+    menu("service", "Background Service", ""),
+    menu("remote_server", "Remote Server", ""),
+    menu("backups", "Backups", "CmdOrCtrl+Shift+B"),
+    menu("exports", "Scheduled Exports", "CmdOrCtrl+Shift+E"),
+    menu("attachments", "Attachments", "CmdOrCtrl+Shift+A"),
+    menu("export_all", "Download All My Data", "CmdOrCtrl+Alt+E"),
+    menu("transfer_settings", "Transfer Settings", "CmdOrCtrl+Alt+T"),
+    menu("logs", "View Logs", "CmdOrCtrl+L"),
+    menu("task_history", "Task History", "CmdOrCtrl+Alt+Y"),
+    menu("crash_reports", "Crash Reports", "CmdOrCtrl+Alt+C"),
+    menu("doctor", "Run Diagnostics", "CmdOrCtrl+Alt+D"),
+    menu("reverse_proxy", "Reverse Proxy Assistant", "CmdOrCtrl+Alt+P"),
+    menu("lan_access", "LAN Access", ""),
+    menu("tls", "HTTPS", ""),
+    menu("usage", "Usage Statistics", "CmdOrCtrl+Alt+U"),
+    menu("converter", "Currency Converter", "CmdOrCtrl+Alt+X"),
+    menu("preview_ui", "Use Preview Web App", ""),
+    menu("hide_from_capture", "Hide from Screen Capture", "CmdOrCtrl+Shift+H"),
+    menu("profiles", "Manage Profiles", "CmdOrCtrl+Shift+P"),
+    menu("demo", "Try with Sample Data", "CmdOrCtrl+Alt+S"),
+    menu("clear_cookies", "Clear Browsing Data", "CmdOrCtrl+Shift+Delete"),
+    menu("reset_web_cache", "Reset Web Cache", "CmdOrCtrl+Shift+R"),
+    menu("new_window", "New Window", "CmdOrCtrl+N"),
+    menu("save_layout", "Save Layout for These Displays", "CmdOrCtrl+Alt+L"),
+    menu("restore_layout", "Restore Saved Layout", "CmdOrCtrl+Shift+L"),
+    menu("docs", "Documentation", "F1"),
+    menu("shortcuts", "Keyboard Shortcuts", "CmdOrCtrl+/"),
+    menu("release_notes", "Release Notes", "CmdOrCtrl+Alt+N"),
+    menu("community", "Community Discussions", "CmdOrCtrl+Alt+G"),
+    menu("report_problem", "Report a Problem", "CmdOrCtrl+Alt+R"),
+    // No default: too destructive for a stray key press
+    menu("uninstall", "Uninstall Moneywright", ""),
+    // Off by default: a system-wide shortcut takes the keys from every other app
+    Action { id: "show_app", label: "Show or Hide Moneywright", default: "", scope: Scope::Global },
+    Action { id: "quick_convert", label: "Quick Currency Converter", default: "", scope: Scope::Global },
+];
+
+/// Accelerators of the predefined Edit and Window menu items
+const RESERVED: &[(&str, &str)] = &[
+    ("CmdOrCtrl+Z", "Undo"),
+    ("CmdOrCtrl+Shift+Z", "Redo"),
+    ("CmdOrCtrl+X", "Cut"),
+    ("CmdOrCtrl+C", "Copy"),
+    ("CmdOrCtrl+V", "Paste"),
+    ("CmdOrCtrl+A", "Select All"),
+    ("CmdOrCtrl+M", "Minimize"),
+    ("CmdOrCtrl+W", "Close Window"),
+];
+
+/// Shortcuts in effect, for building the menu outside of async code
+static CURRENT: Mutex<BTreeMap<String, String>> = Mutex::new(BTreeMap::new());
+
+#[derive(Serialize)]
+pub struct ShortcutInfo {
+    pub id: &'static str,
+    pub label: &'static str,
+    pub scope: Scope,
+    /// Empty when turned off
+    pub accelerator: String,
+    pub default: &'static str,
+}
+
+/// Every action with its default accelerator
+pub fn defaults() -> BTreeMap<String, String> {
+    ACTIONS.iter().map(|a| (a.id.to_string(), a.default.to_string())).collect()
+}
+
+/// Settings section deserializer: actions missing from the file keep their default
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BTreeMap<String, String>, D::Error> {
+    let mut shortcuts = defaults();
+    shortcuts.extend(BTreeMap::<String, String>::deserialize(deserializer)?);
+    Ok(shortcuts)
+}
+
+fn action(id: &str) -> Option<&'static Action> {
+    ACTIONS.iter().find(|a| a.id == id)
+}
+
+/// Check for unknown actions, unparsable accelerators and keys used twice
+pub fn validate(shortcuts: &BTreeMap<String, String>) -> Result<(), String> {
+    let mut taken: Vec<(Shortcut, &str)> = Vec::new();
+    for (key, label) in RESERVED {
+        if let Ok(shortcut) = Shortcut::from_str(key) {
+            taken.push((shortcut, label));
+        }
+    }
+    for (id, accelerator) in shortcuts {
+        let action = action(id).ok_or_else(|| format!("Unknown shortcut action: {}", id))?;
+        if accelerator.is_empty() {
+            continue;
+        }
+        let shortcut = Shortcut::from_str(accelerator)
+            .map_err(|e| format!("shortcuts.{}: \"{}\" is not a valid shortcut ({})", id, accelerator, e))?;
+        if let Some((_, other)) = taken.iter().find(|(s, _)| *s == shortcut) {
+            return Err(format!("shortcuts.{}: {} is already used by {}", id, accelerator, other));
+        }
+        taken.push((shortcut, action.label));
+    }
+    Ok(())
+}
+
+/// Accelerator for a menu item, None if it has none or it's turned off
+pub fn accelerator(id: &str) -> Option<String> {
+    let current = CURRENT.lock().unwrap_or_else(|e| e.into_inner());
+    current
+        .get(id)
+        .cloned()
+        .or_else(|| action(id).map(|a| a.default.to_string()))
+        .filter(|a| !a.is_empty())
+}
+
+fn run_global(app: &AppHandle, id: &str) {
+    if id == "quick_convert" {
+        crate::fx::open_converter_window(app);
+    } else if id == "show_app" {
+        if let Some(window) = app.get_webview_window("main") {
+            let visible = window.is_visible().unwrap_or(false) && window.is_focused().unwrap_or(false);
+            if visible {
+                let _ = window.hide();
+            } else {
+                let _ = window.show();
+                let _ = window.unminimize();
+                let _ = window.set_focus();
+            }
+        }
+    }
+}
+
+/// Register the global actions, replacing whatever was registered before
+async fn register_globals(app: &AppHandle, shortcuts: &BTreeMap<String, String>) {
+    let global_shortcut = app.global_shortcut();
+    let _ = global_shortcut.unregister_all();
+    let log_store = app.state::<SharedLogStore>().inner().clone();
+
+    for action in ACTIONS.iter().filter(|a| a.scope == Scope::Global) {
+        let Some(accelerator) = shortcuts.get(action.id).filter(|a| !a.is_empty()) else {
+            continue;
+        };
+        let id = action.id;
+        let result = global_shortcut.on_shortcut(accelerator.as_str(), move |app, _, event| {
+            if event.state == ShortcutState::Pressed {
+                run_global(app, id);
+            }
+        });
+        if let Err(e) = result {
+            // Usually another app holds the same keys
+            let msg = format!("Global shortcut {} for {} unavailable: {}", accelerator, action.label, e);
+            log_line(app, &log_store, msg, "error").await;
+        }
+    }
+}
+
+/// Keep the menu accelerators and global shortcuts in sync with `[shortcuts]`
+pub fn spawn_shortcut_sync(app: AppHandle, mut rx: watch::Receiver<Settings>) {
+    // Set right away so the menu built during setup uses them
+    *CURRENT.lock().unwrap_or_else(|e| e.into_inner()) = rx.borrow().shortcuts.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut applied: Option<BTreeMap<String, String>> = None;
+        loop {
+            let shortcuts = rx.borrow_and_update().shortcuts.clone();
+            if applied.as_ref() != Some(&shortcuts) {
+                *CURRENT.lock().unwrap_or_else(|e| e.into_inner()) = shortcuts.clone();
+                // The first pass only registers globals; setup builds the menu itself
+                if applied.is_some() {
+                    if let Err(e) = crate::setup_menu(&app) {
+                        eprintln!("Warning: Failed to rebuild menu: {}", e);
+                    }
+                }
+                register_globals(&app, &shortcuts).await;
+                applied = Some(shortcuts);
+            }
+            if rx.changed().await.is_err() {
+                break;
+            }
+        }
+    });
+}
+
+/// Every action with its current and default shortcut
+#[tauri::command]
+pub async fn list_shortcuts() -> Result<Vec<ShortcutInfo>, String> {
+    Ok(ACTIONS
+        .iter()
+        .map(|a| ShortcutInfo {
+            id: a.id,
+            label: a.label,
+            scope: a.scope,
+            accelerator: accelerator(a.id).unwrap_or_default(),
+            default: a.default,
+        })
+        .collect())
+}
+
+/// Open the keyboard shortcuts window (Help > Keyboard Shortcuts)
+pub fn open_shortcuts_window(app: &AppHandle) {
+    // Static UI; labels and accelerators are inserted with escaping on the JS side
+    let script = r#"
+        const tauriApi = window.__TAURI__;
+
+        document.documentElement.innerHTML = `
+<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <title>Keyboard Shortcuts</title>
+    <style>
+        __BASE_STYLE__
+        #content { flex: 1; overflow-y: auto; padding: 16px; }
+        .notice { margin-bottom: 16px; line-height: 1.5; }
+        h2 { font-size: 13px; font-weight: 500; color: #71717a; margin: 16px 0 4px; }
+        table { width: 100%; border-collapse: collapse; }
+        td { padding: 6px 0; border-bottom: 1px solid rgba(255, 255, 255, 0.04); }
+        td.keys { width: 220px; }
+        td.keys input { width: 100%; }
+    </style>
+</head>
+<body>
+    <div class="toolbar">
+        <button id="saveBtn" class="primary">Save</button>
+        <button id="resetBtn">Reset All</button>
+        <span id="error" class="fail" role="alert"></span>
+    </div>
+    <div id="content">
+        <p class="notice muted">CmdOrCtrl is Cmd on macOS and Ctrl elsewhere. Leave a field empty to turn the shortcut off. Global shortcuts also work while Moneywright is in the background.</p>
+        <div id="tables"></div>
+    </div>
+</body>
+</html>`;
+
+        const $ = id => document.getElementById(id);
+        const scopes = [['menu', 'Menu'], ['global', 'Global']];
+        let confirmReset = false;
+
+        function inputs() {
+            return Array.from(document.querySelectorAll('input[data-id]'));
+        }
+
+        async function refresh() {
+            const shortcuts = await tauriApi.core.invoke('list_shortcuts');
+            $('tables').innerHTML = scopes.map(([scope, title]) => {
+                const rows = shortcuts.filter(s => s.scope === scope).map(s =>
+                    '<tr><td><label for="key-' + s.id + '">' + escapeHtml(s.label) + '</label></td>' +
+                    '<td class="keys"><input type="text" class="mono" id="key-' + s.id + '" data-id="' + s.id + '"' +
+                    ' data-default="' + escapeHtml(s.default) + '" value="' + escapeHtml(s.accelerator) + '" placeholder="Off"></td></tr>'
+                ).join('');
+                return '<h2>' + title + '</h2><table aria-label="' + title + ' shortcuts"><tbody>' + rows + '</tbody></table>';
+            }).join('');
+        }
+
+        async function save(shortcuts) {
+            $('error').textContent = '';
+            try {
+                await tauriApi.core.invoke('update_settings', { changes: { shortcuts } });
+                // The menu is rebuilt in the background; show what was saved
+                inputs().forEach(i => { i.value = shortcuts[i.dataset.id]; });
+            } catch (e) {
+                $('error').textContent = String(e);
+            }
+        }
+
+        $('saveBtn').onclick = () => {
+            save(Object.fromEntries(inputs().map(i => [i.dataset.id, i.value.trim()])));
+        };
+        $('resetBtn').onclick = () => {
+            if (!confirmReset) {
+                confirmReset = true;
+                $('resetBtn').textContent = 'Confirm';
+                return;
+            }
+            confirmReset = false;
+            $('resetBtn').textContent = 'Reset All';
+            save(Object.fromEntries(inputs().map(i => [i.dataset.id, i.dataset.default])));
+        };
+        refresh();
+    "#;
+
+    open_injected_window(app, "shortcuts", "Keyboard Shortcuts", (560.0, 640.0), true, script);
+}
@@ -0,0 +1,510 @@
+// HTTPS for the main server with a self-signed certificate (`server.tls`)
+//
+// With `server.tls` on, the main server gets TLS_CERT_FILE and TLS_KEY_FILE and serves
+// https://localhost instead of http. The certificate is made here the first time and
+// kept in `tls/` in the data dir, the key readable by this OS user only. It covers
+// localhost, 127.0.0.1, this computer's name (plain and .local) and its LAN address, so
+// a tablet using LAN access (see lan.rs) can trust it once it's exported and installed
+// there. It's valid for 825 days, the most Apple devices accept, and made again when
+// it's missing, expires within RENEW_BEFORE_DAYS or no longer covers the LAN address.
+//
+// There's no certificate authority, so every client trusts this one certificate:
+// - the shell's own requests to the server (`transport::send`) use it as their only root;
+// - WebKitGTK accepts it for localhost and WebView2 allows it when the server presents
+//   exactly this certificate; on macOS it's added to the login keychain as trusted for
+//   SSL, which asks for the user's password.
+// Extra profiles, the demo, the sandbox and the background service stay on http.
+// The main window may call the shell from https://localhost on the main server's port
+// only; that capability is added the first time https is switched on.
+//
+// Regenerating replaces the files and restarts a running server; other devices that
+// trusted the old certificate need the new one.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use chrono::{DateTime, Datelike, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use tauri::ipc::CapabilityBuilder;
+use tauri::webview::PlatformWebview;
+use tauri::{AppHandle, Manager, WebviewWindow};
+use crate::events::{self, Event};
+use crate::logs::{log_line, SharedLogStore};
+use crate::server::{ServerStatus, SharedServerManager};
+use crate::settings::SharedSettings;
+use crate::windows::open_injected_window;
+
+const TLS_DIR: &str = "tls";
+const CERT_FILE: &str = "cert.pem";
+const KEY_FILE: &str = "key.pem";
+const INFO_FILE: &str = "cert.json";
+/// Apple devices refuse server certificates valid for longer
+const VALID_DAYS: i64 = 825;
+const RENEW_BEFORE_DAYS: i64 = 30;
+const WINDOW_LABEL: &str = "tls";
+
+/// Whether the main server serves https, settled each time it starts
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+/// Whether the main window's https capability has been added
+static GRANTED: AtomicBool = AtomicBool::new(false);
+/// The certificate the main server uses and a client that trusts only it
+static CURRENT: Mutex<Option<Trusted>> = Mutex::new(None);
+
+struct Trusted {
+    pem: String,
+    client: reqwest::Client,
+}
+
+/// What's known about the certificate, kept next to it in `cert.json`
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CertInfo {
+    /// Host names and addresses it covers
+    pub names: Vec<String>,
+    pub created: DateTime<Utc>,
+    pub expires: DateTime<Utc>,
+    /// SHA-256 of the certificate, for checking it on another device
+    pub fingerprint: String,
+}
+
+#[derive(Serialize)]
+pub struct TlsStatus {
+    /// The saved setting
+    enabled: bool,
+    /// Whether the running main server uses it
+    active: bool,
+    certificate: Option<CertInfo>,
+    path: String,
+}
+
+fn tls_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join(TLS_DIR)
+}
+
+fn read_info(data_dir: &Path) -> Option<CertInfo> {
+    let dir = tls_dir(data_dir);
+    if !dir.join(CERT_FILE).exists() || !dir.join(KEY_FILE).exists() {
+        return None;
+    }
+    let content = fs::read_to_string(dir.join(INFO_FILE)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Names the certificate should cover right now
+fn wanted_names() -> Vec<String> {
+    let mut names = vec!["localhost".to_string(), "127.0.0.1".to_string(), "::1".to_string()];
+    if let Some(host) = sysinfo::System::host_name().filter(|h| !h.is_empty() && !h.contains(' ')) {
+        let host = host.trim_end_matches(".local").to_lowercase();
+        names.push(format!("{}.local", host));
+        names.push(host);
+    }
+    if let Some(ip) = crate::lan::lan_ip() {
+        names.push(ip.to_string());
+    }
+    let mut unique = Vec::new();
+    for name in names {
+        if !unique.contains(&name) {
+            unique.push(name);
+        }
+    }
+    unique
+}
+
+fn write_private(path: &Path, content: &str) -> Result<(), String> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(path).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    file.write_all(content.as_bytes())
+        .and_then(|_| file.sync_all())
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Make a new key and certificate, replacing the old ones
+fn generate(data_dir: &Path) -> Result<CertInfo, String> {
+    let names = wanted_names();
+    let mut params = rcgen::CertificateParams::new(names.clone()).map_err(|e| format!("Invalid certificate name: {}", e))?;
+    params.distinguished_name = rcgen::DistinguishedName::new();
+    params.distinguished_name.push(rcgen::DnType::CommonName, "Moneywright (this computer)");
+    params.distinguished_name.push(rcgen::DnType::OrganizationName, "Moneywright");
+    params.extended_key_usages = vec![rcgen::ExtendedKeyUsagePurpose::ServerAuth];
+    let created = Utc::now();
+    let expires = created + TimeDelta::days(VALID_DAYS);
+    let date = |t: DateTime<Utc>| rcgen::date_time_ymd(t.year(), t.month() as u8, t.day() as u8);
+    params.not_before = date(created - TimeDelta::days(1));
+    params.not_after = date(expires);
+
+    let key = rcgen::KeyPair::generate().map_err(|e| format!("Failed to create a key: {}", e))?;
+    let cert = params.self_signed(&key).map_err(|e| format!("Failed to create the certificate: {}", e))?;
+    let info = CertInfo {
+        names,
+        created,
+        expires,
+        fingerprint: hex::encode_upper(Sha256::digest(cert.der())),
+    };
+
+    let dir = tls_dir(data_dir);
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    write_private(&dir.join(KEY_FILE), &key.serialize_pem())?;
+    fs::write(dir.join(CERT_FILE), cert.pem()).map_err(|e| format!("Failed to write the certificate: {}", e))?;
+    let json = serde_json::to_string_pretty(&info).map_err(|e| e.to_string())?;
+    fs::write(dir.join(INFO_FILE), json).map_err(|e| format!("Failed to write {}: {}", INFO_FILE, e))?;
+
+    #[cfg(target_os = "macos")]
+    if let Err(e) = trust_in_keychain(&dir.join(CERT_FILE)) {
+        eprintln!("Warning: {}", e);
+    }
+    Ok(info)
+}
+
+/// The certificate to use, made again if it's missing, expiring or misses the LAN address
+fn ensure(data_dir: &Path) -> Result<CertInfo, String> {
+    let current = read_info(data_dir).filter(|info| {
+        info.expires - Utc::now() > TimeDelta::days(RENEW_BEFORE_DAYS)
+            && wanted_names().iter().all(|name| info.names.contains(name))
+    });
+    match current {
+        Some(info) => Ok(info),
+        None => generate(data_dir),
+    }
+}
+
+fn load(data_dir: &Path) -> Result<(), String> {
+    let pem = fs::read_to_string(tls_dir(data_dir).join(CERT_FILE))
+        .map_err(|e| format!("Failed to read the certificate: {}", e))?;
+    let root = reqwest::Certificate::from_pem(pem.as_bytes()).map_err(|e| format!("Invalid certificate: {}", e))?;
+    let client = reqwest::Client::builder()
+        .tls_built_in_root_certs(false)
+        .add_root_certificate(root)
+        .build()
+        .map_err(|e| e.to_string())?;
+    *CURRENT.lock().unwrap_or_else(|e| e.into_inner()) = Some(Trusted { pem, client });
+    Ok(())
+}
+
+fn current_pem() -> Option<String> {
+    CURRENT.lock().unwrap_or_else(|e| e.into_inner()).as_ref().map(|t| t.pem.clone())
+}
+
+/// Set up https for a start of the main server; the environment it needs, empty for http
+pub fn activate(app: &AppHandle, data_dir: &Path, enabled: bool) -> Result<Vec<(String, String)>, String> {
+    if !enabled {
+        deactivate();
+        return Ok(Vec::new());
+    }
+    ensure(data_dir)?;
+    load(data_dir)?;
+    ACTIVE.store(true, Ordering::Relaxed);
+    if !GRANTED.swap(true, Ordering::Relaxed) {
+        grant_ipc(app)?;
+    }
+    // WebKitGTK keeps allowed certificates in the context all windows share; WebView2
+    // asks the handler each window got when it was built
+    if cfg!(target_os = "linux") {
+        if let Some(window) = app.get_webview_window("main") {
+            trust_in_webview(&window);
+        }
+    }
+    let dir = tls_dir(data_dir);
+    Ok(vec![
+        ("TLS_CERT_FILE".to_string(), dir.join(CERT_FILE).to_string_lossy().to_string()),
+        ("TLS_KEY_FILE".to_string(), dir.join(KEY_FILE).to_string_lossy().to_string()),
+    ])
+}
+
+/// Let the main window call the shell when it's loaded from the main server over https
+fn grant_ipc(app: &AppHandle) -> Result<(), String> {
+    let capability = CapabilityBuilder::new("https-server")
+        .window("main")
+        .remote(format!("https://localhost:{}/*", crate::server::server_port()))
+        .permission("core:default")
+        .permission("core:window:default")
+        .permission("core:window:allow-close")
+        .permission("process:default")
+        .permission("updater:default");
+    app.add_capability(capability).map_err(|e| {
+        GRANTED.store(false, Ordering::Relaxed);
+        format!("Failed to allow the https origin: {}", e)
+    })
+}
+
+/// The main server runs without TLS (or not here, like the background service)
+pub fn deactivate() {
+    ACTIVE.store(false, Ordering::Relaxed);
+}
+
+pub fn is_active() -> bool {
+    ACTIVE.load(Ordering::Relaxed)
+}
+
+/// Scheme of the main server's URL
+pub fn scheme() -> &'static str {
+    if is_active() {
+        "https"
+    } else {
+        "http"
+    }
+}
+
+/// A client trusting the main server's certificate, for https requests to localhost
+pub fn client_for(url: &reqwest::Url) -> Option<reqwest::Client> {
+    if !is_active() || url.scheme() != "https" || url.host_str() != Some("localhost") {
+        return None;
+    }
+    CURRENT.lock().unwrap_or_else(|e| e.into_inner()).as_ref().map(|t| t.client.clone())
+}
+
+/// Whether a PEM certificate is the one in use; line endings may differ
+#[cfg(target_os = "windows")]
+fn is_current(pem: &str) -> bool {
+    let strip = |s: &str| s.chars().filter(|c| !c.is_whitespace()).collect::<String>();
+    current_pem().is_some_and(|current| strip(&current) == strip(pem))
+}
+
+#[cfg(target_os = "linux")]
+fn allow_in_webview(webview: PlatformWebview) {
+    use webkit2gtk::gio::TlsCertificate;
+    use webkit2gtk::{WebContextExt, WebViewExt};
+
+    let Some(pem) = current_pem() else {
+        return;
+    };
+    let Some(context) = webview.inner().context() else {
+        return;
+    };
+    match TlsCertificate::from_pem(&pem) {
+        Ok(certificate) => context.allow_tls_certificate_for_host(&certificate, "localhost"),
+        Err(e) => eprintln!("Warning: Failed to load the server certificate: {}", e),
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn allow_in_webview(webview: PlatformWebview) {
+    use webview2_com::Microsoft::Web::WebView2::Win32::{
+        ICoreWebView2_14, COREWEBVIEW2_SERVER_CERTIFICATE_ERROR_ACTION_ALWAYS_ALLOW,
+        COREWEBVIEW2_SERVER_CERTIFICATE_ERROR_ACTION_DEFAULT,
+    };
+    use webview2_com::{take_pwstr, ServerCertificateErrorDetectedEventHandler};
+    use windows_core::{Interface, PWSTR};
+
+    // Asked on each error, so a regenerated certificate is allowed without a new handler
+    let handler = ServerCertificateErrorDetectedEventHandler::create(Box::new(|_, args| {
+        let Some(args) = args else {
+            return Ok(());
+        };
+        let mut pem = PWSTR::null();
+        // SAFETY: WebView2 hands out the certificate for the duration of the event
+        unsafe { args.ServerCertificate()?.ToPemEncoding(&mut pem)? };
+        let action = if is_current(&take_pwstr(pem)) {
+            COREWEBVIEW2_SERVER_CERTIFICATE_ERROR_ACTION_ALWAYS_ALLOW
+        } else {
+            COREWEBVIEW2_SERVER_CERTIFICATE_ERROR_ACTION_DEFAULT
+        };
+        unsafe { args.SetAction(action) }
+    }));
+    let mut token = 0i64;
+    // SAFETY: the controller belongs to the live window this runs on (its UI thread)
+    let added = unsafe { webview.controller().CoreWebView2() }
+        .and_then(|core| core.cast::<ICoreWebView2_14>())
+        .and_then(|core| unsafe { core.add_ServerCertificateErrorDetected(&handler, &mut token) });
+    if let Err(e) = added {
+        eprintln!("Warning: Failed to accept the server certificate: {}", e);
+    }
+}
+
+/// WKWebView goes by the keychain, see `trust_in_keychain`
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+fn allow_in_webview(_webview: PlatformWebview) {}
+
+/// Let a window that shows the main server accept its certificate; call it once for
+/// each window, when it's built
+pub fn trust_in_webview(window: &WebviewWindow) {
+    if let Err(e) = window.with_webview(allow_in_webview) {
+        eprintln!("Warning: Failed to set up the window for https: {}", e);
+    }
+}
+
+/// Add the certificate to the login keychain, trusted for SSL; macOS asks for the password
+#[cfg(target_os = "macos")]
+fn trust_in_keychain(cert: &Path) -> Result<(), String> {
+    let keychain = dirs::home_dir()
+        .ok_or_else(|| "No home folder found".to_string())?
+        .join("Library/Keychains/login.keychain-db");
+    let status = std::process::Command::new("security")
+        .args(["add-trusted-cert", "-r", "trustRoot", "-p", "ssl", "-k"])
+        .arg(&keychain)
+        .arg(cert)
+        .status()
+        .map_err(|e| format!("Failed to run security: {}", e))?;
+    if !status.success() {
+        return Err("The certificate wasn't added to the keychain, so the window can't load the server over https".to_string());
+    }
+    Ok(())
+}
+
+async fn status(app: &AppHandle) -> TlsStatus {
+    let enabled = app.state::<SharedSettings>().lock().await.get().server.tls;
+    let data_dir = app.state::<SharedServerManager>().lock().await.data_dir().clone();
+    TlsStatus {
+        enabled,
+        active: is_active(),
+        certificate: read_info(&data_dir),
+        path: tls_dir(&data_dir).join(CERT_FILE).to_string_lossy().to_string(),
+    }
+}
+
+async fn restart_if_running(app: &AppHandle) -> Result<(), String> {
+    let manager = app.state::<SharedServerManager>().inner().clone();
+    let log_store = app.state::<SharedLogStore>().inner().clone();
+    if matches!(manager.lock().await.status(), ServerStatus::Stopped | ServerStatus::Paused) {
+        return Ok(());
+    }
+    crate::restart_server(app.clone(), manager, log_store).await
+}
+
+/// Whether https is on and the certificate in use
+#[tauri::command]
+pub async fn get_tls_status(app: AppHandle) -> Result<TlsStatus, String> {
+    Ok(status(&app).await)
+}
+
+/// Turn https for the main server on or off and restart it if it's running
+#[tauri::command]
+pub async fn set_tls(app: AppHandle, enabled: bool) -> Result<TlsStatus, String> {
+    let settings = app.state::<SharedSettings>().inner().clone();
+    let store = settings.lock().await;
+    if store.get().server.tls != enabled {
+        let updated = store.update(&json!({ "server": { "tls": enabled } }))?;
+        drop(store);
+        let _ = events::emit(&app, Event::SettingsChanged(&updated));
+        let log_store = app.state::<SharedLogStore>().inner().clone();
+        let msg = if enabled { "HTTPS turned on for the server" } else { "HTTPS turned off for the server" };
+        log_line(&app, &log_store, msg, "info").await;
+        restart_if_running(&app).await?;
+    }
+    Ok(status(&app).await)
+}
+
+/// Replace the certificate and key, restarting a server that uses them
+#[tauri::command]
+pub async fn regenerate_tls_certificate(app: AppHandle) -> Result<TlsStatus, String> {
+    let data_dir = app.state::<SharedServerManager>().lock().await.data_dir().clone();
+    let info = tauri::async_runtime::spawn_blocking(move || generate(&data_dir))
+        .await
+        .map_err(|e| e.to_string())??;
+    let log_store = app.state::<SharedLogStore>().inner().clone();
+    log_line(&app, &log_store, format!("New server certificate {}", info.fingerprint), "info").await;
+    if is_active() {
+        restart_if_running(&app).await?;
+    }
+    Ok(status(&app).await)
+}
+
+/// Copy the certificate (not the key) out for other devices to trust, defaulting to the
+/// Downloads folder, and reveal it
+#[tauri::command]
+pub async fn export_tls_certificate(app: AppHandle, destination: Option<String>) -> Result<String, String> {
+    let data_dir = app.state::<SharedServerManager>().lock().await.data_dir().clone();
+    let source = tls_dir(&data_dir).join(CERT_FILE);
+    if !source.exists() {
+        return Err("There's no certificate yet; turn on HTTPS or generate one first".to_string());
+    }
+    let target = match destination {
+        Some(d) => PathBuf::from(d),
+        None => dirs::download_dir()
+            .or_else(dirs::home_dir)
+            .ok_or_else(|| "No Downloads folder found".to_string())?
+            .join("moneywright-server.crt"),
+    };
+    fs::copy(&source, &target).map_err(|e| format!("Failed to export the certificate: {}", e))?;
+    if let Some(parent) = target.parent() {
+        let _ = open::that(parent);
+    }
+    Ok(target.to_string_lossy().to_string())
+}
+
+/// Open the HTTPS window
+pub fn open_tls_window(app: &AppHandle) {
+    let script = r#"
+        const tauriApi = window.__TAURI__;
+
+        document.documentElement.innerHTML = `
+<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <title>HTTPS</title>
+    <style>
+        __BASE_STYLE__
+        .content { flex: 1; overflow-y: auto; padding: 16px; display: flex; flex-direction: column; gap: 12px; }
+        dl { display: grid; grid-template-columns: 110px 1fr; gap: 6px 12px; }
+        dt { color: #a1a1aa; }
+        dd { word-break: break-all; }
+        .actions { display: flex; gap: 8px; flex-wrap: wrap; }
+    </style>
+</head>
+<body>
+    <div class="content">
+        <p class="muted">Serve Moneywright over https with a certificate made on this computer. Other devices trust it once you install the exported certificate on them.</p>
+        <label><input type="checkbox" id="enabled"> Use HTTPS for the server</label>
+        <div id="active" class="muted"></div>
+        <dl id="cert" hidden>
+            <dt>Covers</dt><dd id="names" class="mono"></dd>
+            <dt>Expires</dt><dd id="expires"></dd>
+            <dt>SHA-256</dt><dd id="fingerprint" class="mono"></dd>
+        </dl>
+        <div class="actions">
+            <button id="exportBtn">Export Certificate...</button>
+            <button id="regenBtn">Generate New Certificate</button>
+        </div>
+        <p class="muted">A new certificate has to be installed again on every device that trusted the old one.</p>
+        <div id="status" class="muted" role="status" aria-live="polite"></div>
+    </div>
+</body>
+</html>`;
+
+        const $ = id => document.getElementById(id);
+
+        function render(s) {
+            $('enabled').checked = s.enabled;
+            $('active').textContent = s.active
+                ? 'The server is using https.'
+                : (s.enabled ? 'The server uses https once it restarts.' : 'The server uses plain http.');
+            $('cert').hidden = !s.certificate;
+            $('exportBtn').disabled = !s.certificate;
+            if (s.certificate) {
+                $('names').textContent = s.certificate.names.join(', ');
+                $('expires').textContent = new Date(s.certificate.expires).toLocaleDateString();
+                $('fingerprint').textContent = s.certificate.fingerprint.match(/.{2}/g).join(':');
+            }
+        }
+
+        async function run(label, command, args) {
+            $('status').textContent = label;
+            try {
+                render(await tauriApi.core.invoke(command, args));
+                $('status').textContent = '';
+            } catch (e) {
+                $('status').textContent = String(e);
+            }
+        }
+
+        $('enabled').onchange = () => run('Restarting the server...', 'set_tls', { enabled: $('enabled').checked });
+        $('regenBtn').onclick = () => run('Generating...', 'regenerate_tls_certificate', {});
+        $('exportBtn').onclick = async () => {
+            try {
+                const path = await tauriApi.core.invoke('export_tls_certificate', {});
+                $('status').textContent = 'Saved to ' + path;
+            } catch (e) {
+                $('status').textContent = String(e);
+            }
+        };
+        tauriApi.core.invoke('get_tls_status').then(render).catch(e => { $('status').textContent = String(e); });
+    "#;
+
+    open_injected_window(app, WINDOW_LABEL, "HTTPS", (560.0, 440.0), true, script);
+}
@@ -0,0 +1,331 @@
+// Moving the desktop setup to another machine: settings, profiles and scheduled exports
+//
+// `export_desktop_settings` writes one JSON file with settings.toml, the profile list
+// and the scheduled export tasks. The passwords they use from the keychain (WebDAV
+// destinations, the alert SMTP login) are only included encrypted with a passphrase:
+// PBKDF2-SHA256 derives a key for ChaCha20-Poly1305. Without a passphrase they're left
+// out and have to be entered again on the new machine.
+//
+// The financial data and the database connection aren't part of it; they move with a
+// backup or a full export. Machine-specific paths (backup folder, profile data dirs,
+// export destinations) are kept as they are and may need adjusting after an import.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::num::NonZeroU32;
+use std::path::PathBuf;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN};
+use ring::pbkdf2;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Manager};
+use crate::events::{self, Event};
+use crate::keychain;
+use crate::logs::{log_line, SharedLogStore};
+use crate::settings::{parse_settings, SharedSettings};
+use crate::windows::open_injected_window;
+
+const FORMAT: &str = "moneywright-desktop-settings";
+const FORMAT_VERSION: u32 = 1;
+const KDF: &str = "pbkdf2-sha256";
+const ITERATIONS: u32 = 600_000;
+/// Refuse files asking for absurd work on import
+const MAX_ITERATIONS: u32 = 10_000_000;
+const SALT_LEN: usize = 16;
+const WINDOW_LABEL: &str = "settings_transfer";
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Sealed {
+    kdf: String,
+    iterations: u32,
+    salt: String,
+    nonce: String,
+    /// Hex of the encrypted JSON map of keychain entries, with the tag appended
+    ciphertext: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Bundle {
+    format: String,
+    version: u32,
+    app_version: String,
+    exported_at: String,
+    /// settings.toml as written by the exporting app
+    settings: String,
+    profiles: Value,
+    export_tasks: Value,
+    #[serde(default)]
+    secrets: Option<Sealed>,
+}
+
+#[derive(Serialize)]
+pub struct ImportSummary {
+    profiles: usize,
+    export_tasks: usize,
+    secrets: usize,
+    /// The file has encrypted passwords but no passphrase was given
+    secrets_skipped: bool,
+}
+
+fn derive_key(passphrase: &str, salt: &[u8], iterations: u32) -> Result<LessSafeKey, String> {
+    let iterations = NonZeroU32::new(iterations).ok_or_else(|| "Invalid key derivation settings".to_string())?;
+    let mut key = [0u8; 32];
+    pbkdf2::derive(pbkdf2::PBKDF2_HMAC_SHA256, iterations, salt, passphrase.as_bytes(), &mut key);
+    let key = UnboundKey::new(&CHACHA20_POLY1305, &key).map_err(|_| "Failed to set up encryption".to_string())?;
+    Ok(LessSafeKey::new(key))
+}
+
+fn seal(plaintext: &[u8], passphrase: &str) -> Result<Sealed, String> {
+    let rng = SystemRandom::new();
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    rng.fill(&mut salt).map_err(|_| "No secure random numbers available".to_string())?;
+    rng.fill(&mut nonce).map_err(|_| "No secure random numbers available".to_string())?;
+
+    let mut data = plaintext.to_vec();
+    derive_key(passphrase, &salt, ITERATIONS)?
+        .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::from(FORMAT.as_bytes()), &mut data)
+        .map_err(|_| "Failed to encrypt passwords".to_string())?;
+    Ok(Sealed {
+        kdf: KDF.to_string(),
+        iterations: ITERATIONS,
+        salt: hex::encode(salt),
+        nonce: hex::encode(nonce),
+        ciphertext: hex::encode(data),
+    })
+}
+
+fn open(sealed: &Sealed, passphrase: &str) -> Result<Vec<u8>, String> {
+    if sealed.kdf != KDF || sealed.iterations > MAX_ITERATIONS {
+        return Err("The passwords in this file use an unsupported encryption".to_string());
+    }
+    let damaged = |_| "The file is damaged".to_string();
+    let salt = hex::decode(&sealed.salt).map_err(damaged)?;
+    let nonce: [u8; NONCE_LEN] = hex::decode(&sealed.nonce)
+        .map_err(damaged)?
+        .try_into()
+        .map_err(|_| "The file is damaged".to_string())?;
+    let mut data = hex::decode(&sealed.ciphertext).map_err(damaged)?;
+    let plaintext = derive_key(passphrase, &salt, sealed.iterations)?
+        .open_in_place(Nonce::assume_unique_for_key(nonce), Aad::from(FORMAT.as_bytes()), &mut data)
+        .map_err(|_| "Wrong passphrase (or the file is damaged)".to_string())?;
+    Ok(plaintext.to_vec())
+}
+
+/// Where profiles.json and the export tasks live, also while another profile is switched to
+fn data_dir(app: &AppHandle) -> PathBuf {
+    crate::profiles::main_data_dir(app)
+}
+
+/// Keychain entries a transfer carries: the alert SMTP login and the tasks' WebDAV passwords
+fn secret_keys(task_keys: Vec<String>) -> Vec<String> {
+    std::iter::once(crate::alerts::SMTP_PASSWORD_KEY.to_string()).chain(task_keys).collect()
+}
+
+/// Write the desktop setup to a file, in Downloads unless `destination` is given
+#[tauri::command]
+pub async fn export_desktop_settings(
+    app: AppHandle,
+    destination: Option<String>,
+    passphrase: Option<String>,
+) -> Result<String, String> {
+    let data_dir = data_dir(&app);
+    let settings = app.state::<SharedSettings>().lock().await.get();
+    let (export_tasks, task_keys) = crate::exports::transfer_tasks(&data_dir)?;
+
+    let secrets = match passphrase.filter(|p| !p.is_empty()) {
+        Some(passphrase) => {
+            let mut entries = BTreeMap::new();
+            for key in secret_keys(task_keys) {
+                if let Some(secret) = keychain::get_secret(&key)? {
+                    entries.insert(key, secret);
+                }
+            }
+            let json = serde_json::to_vec(&entries).map_err(|e| e.to_string())?;
+            // Key derivation takes a moment on purpose
+            let sealed = tauri::async_runtime::spawn_blocking(move || seal(&json, &passphrase))
+                .await
+                .map_err(|e| e.to_string())??;
+            Some(sealed)
+        }
+        None => None,
+    };
+
+    let bundle = Bundle {
+        format: FORMAT.to_string(),
+        version: FORMAT_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        exported_at: chrono::Local::now().to_rfc3339(),
+        settings: toml::to_string_pretty(&settings).map_err(|e| format!("Failed to serialize settings: {}", e))?,
+        profiles: crate::profiles::transfer_profiles(&data_dir)?,
+        export_tasks,
+        secrets,
+    };
+
+    let target = match destination.filter(|d| !d.is_empty()) {
+        Some(d) => PathBuf::from(d),
+        None => dirs::download_dir()
+            .or_else(dirs::home_dir)
+            .ok_or_else(|| "No Downloads folder found".to_string())?
+            .join(format!("moneywright-desktop-settings-{}.json", chrono::Local::now().format("%Y-%m-%d"))),
+    };
+    let json = serde_json::to_string_pretty(&bundle).map_err(|e| e.to_string())?;
+    fs::write(&target, json).map_err(|e| format!("Failed to write {}: {}", target.display(), e))?;
+
+    let log_store = app.state::<SharedLogStore>().inner().clone();
+    log_line(&app, &log_store, format!("Desktop settings exported to {}", target.display()), "info").await;
+    Ok(target.to_string_lossy().to_string())
+}
+
+/// Replace this machine's desktop setup with an exported one
+#[tauri::command]
+pub async fn import_desktop_settings(
+    app: AppHandle,
+    path: String,
+    passphrase: Option<String>,
+) -> Result<ImportSummary, String> {
+    let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let bundle: Bundle = serde_json::from_str(&content)
+        .ok()
+        .filter(|b: &Bundle| b.format == FORMAT)
+        .ok_or_else(|| "Not a Moneywright desktop settings file".to_string())?;
+    if bundle.version > FORMAT_VERSION {
+        return Err(format!(
+            "This file was made by a newer Moneywright ({}); update to import it",
+            bundle.app_version
+        ));
+    }
+
+    // Check everything before changing anything
+    let settings = parse_settings(&bundle.settings)?;
+    let received_profiles = crate::profiles::check_profiles(bundle.profiles)?;
+    let received_tasks = crate::exports::check_tasks(bundle.export_tasks)?;
+    let passphrase = passphrase.filter(|p| !p.is_empty());
+    let secrets: BTreeMap<String, String> = match (&bundle.secrets, &passphrase) {
+        (Some(sealed), Some(passphrase)) => {
+            let json = tauri::async_runtime::spawn_blocking({
+                let sealed = sealed.clone();
+                let passphrase = passphrase.clone();
+                move || open(&sealed, &passphrase)
+            })
+            .await
+            .map_err(|e| e.to_string())??;
+            serde_json::from_slice(&json).map_err(|_| "The file is damaged".to_string())?
+        }
+        _ => BTreeMap::new(),
+    };
+    // Only the entries an export writes, for the tasks in this file
+    let allowed = secret_keys(crate::exports::secret_keys(&received_tasks));
+    if let Some(key) = secrets.keys().find(|key| !allowed.contains(key)) {
+        return Err(format!("The file has an unexpected password entry \"{}\"", key));
+    }
+
+    let data_dir = data_dir(&app);
+    let updated = app.state::<SharedSettings>().lock().await.replace(settings)?;
+    let _ = events::emit(&app, Event::SettingsChanged(&updated));
+    let profiles = crate::profiles::receive_profiles(&data_dir, received_profiles)?;
+    let export_tasks = crate::exports::receive_tasks(&data_dir, received_tasks)?;
+    for (key, secret) in &secrets {
+        keychain::set_secret(key, secret)?;
+    }
+
+    let log_store = app.state::<SharedLogStore>().inner().clone();
+    let msg = format!(
+        "Desktop settings imported from {} ({} profile(s), {} export task(s), {} password(s))",
+        path,
+        profiles,
+        export_tasks,
+        secrets.len()
+    );
+    log_line(&app, &log_store, msg, "info").await;
+    Ok(ImportSummary {
+        profiles,
+        export_tasks,
+        secrets: secrets.len(),
+        secrets_skipped: bundle.secrets.is_some() && passphrase.is_none(),
+    })
+}
+
+/// Open the window for exporting and importing the desktop setup
+pub fn open_transfer_window(app: &AppHandle) {
+    let script = r#"
+        const tauriApi = window.__TAURI__;
+
+        document.documentElement.innerHTML = `
+<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <title>Transfer Settings</title>
+    <style>
+        __BASE_STYLE__
+        .content { flex: 1; overflow-y: auto; padding: 24px; display: flex; flex-direction: column; gap: 10px; }
+        h2 { font-size: 14px; margin-top: 8px; }
+        input[type=text], input[type=password] { width: 100%; }
+        .actions { display: flex; gap: 8px; }
+    </style>
+</head>
+<body>
+    <div class="content">
+        <p class="muted">Copies settings, profiles and scheduled exports to another machine. Your financial data moves with a backup or a full export.</p>
+
+        <h2>Export</h2>
+        <input type="password" id="exportPass" placeholder="Passphrase (optional)" aria-label="Passphrase for saved passwords">
+        <p class="muted">With a passphrase, saved passwords (export destinations, alert email) are included, encrypted. Without one they're left out.</p>
+        <div class="actions"><button id="exportBtn" class="primary">Export to Downloads</button></div>
+
+        <h2>Import</h2>
+        <input type="text" id="importPath" placeholder="Path to moneywright-desktop-settings-....json" aria-label="Settings file">
+        <input type="password" id="importPass" placeholder="Passphrase (if the file has passwords)" aria-label="Passphrase">
+        <p class="muted">Replaces this machine's desktop settings, profiles and scheduled exports.</p>
+        <div class="actions"><button id="importBtn">Import</button></div>
+
+        <div id="status" class="muted" role="status" aria-live="polite"></div>
+    </div>
+</body>
+</html>`;
+
+        const $ = id => document.getElementById(id);
+
+        $('exportBtn').onclick = async () => {
+            $('exportBtn').disabled = true;
+            $('status').textContent = 'Exporting...';
+            try {
+                const path = await tauriApi.core.invoke('export_desktop_settings', {
+                    destination: null,
+                    passphrase: $('exportPass').value || null,
+                });
+                $('status').textContent = 'Saved to ' + path;
+            } catch (e) {
+                $('status').textContent = String(e);
+            }
+            $('exportBtn').disabled = false;
+        };
+
+        $('importBtn').onclick = async () => {
+            const path = $('importPath').value.trim();
+            if (!path) {
+                $('status').textContent = 'Enter the path of an exported settings file';
+                return;
+            }
+            if (!confirm('Replace the desktop settings, profiles and scheduled exports on this machine?')) return;
+            $('importBtn').disabled = true;
+            $('status').textContent = 'Importing...';
+            try {
+                const s = await tauriApi.core.invoke('import_desktop_settings', {
+                    path,
+                    passphrase: $('importPass').value || null,
+                });
+                $('status').textContent = 'Imported ' + s.profiles + ' profile(s), ' + s.export_tasks +
+                    ' scheduled export(s) and ' + s.secrets + ' password(s).' +
+                    (s.secrets_skipped ? ' Passwords were skipped: enter the passphrase to import them too.' : '');
+            } catch (e) {
+                $('status').textContent = String(e);
+            }
+            $('importBtn').disabled = false;
+        };
+    "#;
+
+    open_injected_window(app, WINDOW_LABEL, "Transfer Settings", (520.0, 520.0), false, script);
+}
@@ -0,0 +1,361 @@
+// Unix socket transport between the app and its server (`server.transport`)
+//
+// With "socket", the main server listens on `server.sock` in the data dir instead of
+// a TCP port, so only this OS user can reach it and no port can clash or be probed.
+// The webview can't talk to a socket, so the main window loads the app from the
+// mwapp:// scheme and `proxy` passes each of its requests on; the shell's own requests
+// to the server go through `send`, which takes the socket for mwapp:// URLs.
+//
+// Limits:
+// - macOS and Linux only; Windows always uses TCP.
+// - The main server only. Extra profiles and the demo still listen on ports.
+// - Responses are passed on whole, so streamed ones (chat answers) show up at once.
+// - Can't be combined with a reverse proxy or LAN mode, which both need a port.
+// - Pages on a custom scheme get no cookies, so the session lives in the jar here:
+//   persistent cookies in `session-cookies.json`, session cookies in memory.
+//
+// The transport is settled at launch; changing it takes a restart of the app.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::http::{header, Request, Response, StatusCode};
+use crate::settings::Settings;
+
+pub const SCHEME: &str = "mwapp";
+/// Values of `server.transport`
+pub const TRANSPORTS: &[&str] = &["tcp", "socket"];
+const SOCKET_FILE: &str = "server.sock";
+const COOKIE_FILE: &str = "session-cookies.json";
+/// Longest socket path macOS accepts (Linux allows a few more bytes)
+const MAX_SOCKET_PATH: usize = 103;
+/// The server's idle timeout, long enough for chat answers; requests can set a shorter one
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+/// Headers about one connection, not passed on
+const HOP_BY_HOP: &[&str] = &["connection", "keep-alive", "proxy-connection", "te", "trailer", "transfer-encoding", "upgrade"];
+
+static SOCKET: OnceLock<Option<PathBuf>> = OnceLock::new();
+static COOKIE_PATH: OnceLock<PathBuf> = OnceLock::new();
+static JAR: Mutex<BTreeMap<String, Cookie>> = Mutex::new(BTreeMap::new());
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Cookie {
+    value: String,
+    /// Unix time in seconds, None for cookies that end with the app
+    expires: Option<i64>,
+}
+
+/// Settle the transport for this run; called once settings and the data dir are known.
+/// An error means the socket can't be used and the server stays on TCP
+pub fn init(settings: &Settings, data_dir: &Path) -> Result<(), String> {
+    if !cfg!(unix) || settings.server.transport != "socket" {
+        let _ = SOCKET.set(None);
+        return Ok(());
+    }
+    let path = data_dir.join(SOCKET_FILE);
+    if path.as_os_str().len() > MAX_SOCKET_PATH {
+        let _ = SOCKET.set(None);
+        return Err(format!("{} is too long for a socket, using TCP", path.display()));
+    }
+    let cookie_path = data_dir.join(COOKIE_FILE);
+    let stored: BTreeMap<String, Cookie> = fs::read_to_string(&cookie_path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    *lock_jar() = stored;
+    let _ = COOKIE_PATH.set(cookie_path);
+    let _ = SOCKET.set(Some(path));
+    Ok(())
+}
+
+/// Socket the main server listens on, None when it uses TCP
+pub fn socket_path() -> Option<&'static Path> {
+    SOCKET.get().and_then(|socket| socket.as_deref())
+}
+
+/// Origin of the app in socket mode, e.g. for `get_server_url`
+pub fn app_origin() -> String {
+    format!("{}://localhost", SCHEME)
+}
+
+// ---------------------------------------------------------------------------
+// Cookie jar
+// ---------------------------------------------------------------------------
+
+fn lock_jar() -> std::sync::MutexGuard<'static, BTreeMap<String, Cookie>> {
+    JAR.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn now() -> i64 {
+    Utc::now().timestamp()
+}
+
+fn save_jar(jar: &BTreeMap<String, Cookie>) {
+    let Some(path) = COOKIE_PATH.get() else {
+        return;
+    };
+    let persistent: BTreeMap<&String, &Cookie> = jar.iter().filter(|(_, c)| c.expires.is_some()).collect();
+    let result = serde_json::to_string(&persistent)
+        .map_err(|e| e.to_string())
+        .and_then(|json| write_private(path, json.as_bytes()));
+    if let Err(e) = result {
+        eprintln!("Warning: Failed to save session cookies: {}", e);
+    }
+}
+
+/// Write a file only this OS user can read
+fn write_private(path: &Path, content: &[u8]) -> Result<(), String> {
+    use std::io::Write;
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(path).map_err(|e| e.to_string())?;
+    file.write_all(content).map_err(|e| e.to_string())
+}
+
+/// Take a Set-Cookie header into the jar (Path and Domain don't matter for one server)
+fn store_cookie(jar: &mut BTreeMap<String, Cookie>, set_cookie: &str) {
+    let mut parts = set_cookie.split(';');
+    let Some((name, value)) = parts.next().and_then(|pair| pair.split_once('=')) else {
+        return;
+    };
+    let (mut max_age, mut expires) = (None, None);
+    for attribute in parts {
+        let (key, val) = attribute.split_once('=').unwrap_or((attribute, ""));
+        match key.trim().to_ascii_lowercase().as_str() {
+            "max-age" => max_age = val.trim().parse::<i64>().ok().map(|secs| now() + secs),
+            "expires" => expires = DateTime::parse_from_rfc2822(val.trim()).ok().map(|t| t.timestamp()),
+            _ => {}
+        }
+    }
+    // Max-Age wins over Expires
+    let expires = max_age.or(expires);
+    let name = name.trim().to_string();
+    if expires.is_some_and(|at| at <= now()) {
+        jar.remove(&name);
+    } else {
+        jar.insert(name, Cookie { value: value.trim().to_string(), expires });
+    }
+}
+
+/// Cookie header with the session, None while signed out
+pub fn cookie_header() -> Option<String> {
+    let now = now();
+    let jar = lock_jar();
+    let pairs: Vec<String> = jar
+        .iter()
+        .filter(|(_, c)| c.expires.is_none_or(|at| at > now))
+        .map(|(name, c)| format!("{}={}", name, c.value))
+        .collect();
+    (!pairs.is_empty()).then(|| pairs.join("; "))
+}
+
+/// Cookies in the jar, for Clear Browsing Data
+pub fn cookie_count() -> usize {
+    lock_jar().len()
+}
+
+/// Sign the app out by forgetting its cookies, returning how many there were
+pub fn clear_cookies() -> usize {
+    let mut jar = lock_jar();
+    let count = jar.len();
+    jar.clear();
+    save_jar(&jar);
+    count
+}
+
+// ---------------------------------------------------------------------------
+// HTTP over the socket
+// ---------------------------------------------------------------------------
+
+/// One request on a fresh connection, closed by the server after the response
+#[cfg(unix)]
+async fn round_trip(
+    socket: &Path,
+    method: &str,
+    target: &str,
+    headers: &header::HeaderMap,
+    body: &[u8],
+    timeout: Duration,
+) -> Result<Response<Vec<u8>>, String> {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let mut request = format!(
+        "{} {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\nContent-Length: {}\r\n",
+        method,
+        target,
+        body.len()
+    )
+    .into_bytes();
+    for (name, value) in headers {
+        if HOP_BY_HOP.contains(&name.as_str()) || name == header::HOST || name == header::CONTENT_LENGTH {
+            continue;
+        }
+        request.extend_from_slice(name.as_str().as_bytes());
+        request.extend_from_slice(b": ");
+        request.extend_from_slice(value.as_bytes());
+        request.extend_from_slice(b"\r\n");
+    }
+    request.extend_from_slice(b"\r\n");
+    request.extend_from_slice(body);
+
+    let exchange = async {
+        let mut stream = tokio::net::UnixStream::connect(socket).await?;
+        stream.write_all(&request).await?;
+        let mut raw = Vec::new();
+        stream.read_to_end(&mut raw).await?;
+        Ok::<_, std::io::Error>(raw)
+    };
+    let raw = tokio::time::timeout(timeout, exchange)
+        .await
+        .map_err(|_| "The server didn't answer in time".to_string())?
+        .map_err(|e| format!("Failed to reach the server: {}", e))?;
+    parse_response(&raw)
+}
+
+#[cfg(not(unix))]
+async fn round_trip(
+    _socket: &Path,
+    _method: &str,
+    _target: &str,
+    _headers: &header::HeaderMap,
+    _body: &[u8],
+    _timeout: Duration,
+) -> Result<Response<Vec<u8>>, String> {
+    Err("The socket transport needs macOS or Linux".to_string())
+}
+
+fn parse_response(raw: &[u8]) -> Result<Response<Vec<u8>>, String> {
+    let end = raw
+        .windows(4)
+        .position(|w| w == b"\r\n\r\n")
+        .ok_or_else(|| "Incomplete response from the server".to_string())?;
+    let head = String::from_utf8_lossy(&raw[..end]);
+    let mut lines = head.split("\r\n");
+    let status = lines
+        .next()
+        .and_then(|line| line.split(' ').nth(1))
+        .and_then(|code| code.parse::<u16>().ok())
+        .ok_or_else(|| "Invalid response from the server".to_string())?;
+
+    let mut response = Response::builder().status(status);
+    let mut chunked = false;
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let (name, value) = (name.trim().to_ascii_lowercase(), value.trim());
+        if name == "transfer-encoding" {
+            chunked = value.eq_ignore_ascii_case("chunked");
+        }
+        if !HOP_BY_HOP.contains(&name.as_str()) {
+            response = response.header(name, value);
+        }
+    }
+    let body = &raw[end + 4..];
+    let body = if chunked { dechunk(body)? } else { body.to_vec() };
+    response.body(body).map_err(|e| e.to_string())
+}
+
+/// Body of a chunked response
+fn dechunk(mut data: &[u8]) -> Result<Vec<u8>, String> {
+    let truncated = || "Truncated response from the server".to_string();
+    let mut body = Vec::new();
+    loop {
+        let line_end = data.windows(2).position(|w| w == b"\r\n").ok_or_else(truncated)?;
+        let size = std::str::from_utf8(&data[..line_end])
+            .ok()
+            .and_then(|line| usize::from_str_radix(line.split(';').next().unwrap_or("").trim(), 16).ok())
+            .ok_or_else(|| "Invalid chunk in the server's response".to_string())?;
+        data = &data[line_end + 2..];
+        if size == 0 {
+            return Ok(body);
+        }
+        if data.len() < size + 2 {
+            return Err(truncated());
+        }
+        body.extend_from_slice(&data[..size]);
+        data = &data[size + 2..];
+    }
+}
+
+fn target(url: &tauri::Url) -> String {
+    match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    }
+}
+
+/// Send a request built with reqwest, over the socket for mwapp:// URLs and trusting the
+/// server's certificate for https://localhost. Bodies must be in memory; build multipart
+/// bodies by hand
+pub async fn send(request: reqwest::RequestBuilder) -> Result<reqwest::Response, String> {
+    let (client, request) = request.build_split();
+    let request = request.map_err(|e| e.to_string())?;
+    // The main server's own certificate, see tls.rs
+    if let Some(client) = crate::tls::client_for(request.url()) {
+        return client.execute(request).await.map_err(|e| e.to_string());
+    }
+    if request.url().scheme() != SCHEME {
+        return client.execute(request).await.map_err(|e| e.to_string());
+    }
+    let socket = socket_path().ok_or_else(|| "The server doesn't listen on a socket".to_string())?;
+    let body = match request.body() {
+        Some(body) => body
+            .as_bytes()
+            .ok_or_else(|| "Streamed bodies can't be sent over the socket".to_string())?,
+        None => &[],
+    };
+    let timeout = request.timeout().copied().unwrap_or(REQUEST_TIMEOUT);
+    let response = round_trip(socket, request.method().as_str(), &target(request.url()), request.headers(), body, timeout).await?;
+    Ok(reqwest::Response::from(response))
+}
+
+/// Serve a request of the main window from the server (the mwapp:// scheme handler)
+pub async fn proxy(request: Request<Vec<u8>>) -> Response<Vec<u8>> {
+    match forward(request).await {
+        Ok(response) => response,
+        Err(e) => Response::builder()
+            .status(StatusCode::BAD_GATEWAY)
+            .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+            .body(e.into_bytes())
+            .unwrap_or_default(),
+    }
+}
+
+async fn forward(request: Request<Vec<u8>>) -> Result<Response<Vec<u8>>, String> {
+    let socket = socket_path().ok_or_else(|| "The server doesn't listen on a socket".to_string())?;
+    let target = match request.uri().path_and_query() {
+        Some(target) => target.to_string(),
+        None => "/".to_string(),
+    };
+    let mut headers = request.headers().clone();
+    // Cookies come from the jar; the origin is the app itself, not a cross-site caller
+    headers.remove(header::COOKIE);
+    headers.remove(header::ORIGIN);
+    if let Some(value) = cookie_header().and_then(|c| header::HeaderValue::from_str(&c).ok()) {
+        headers.insert(header::COOKIE, value);
+    }
+
+    let mut response = round_trip(socket, request.method().as_str(), &target, &headers, request.body(), REQUEST_TIMEOUT).await?;
+    let set_cookies: Vec<String> = response
+        .headers()
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok().map(String::from))
+        .collect();
+    response.headers_mut().remove(header::SET_COOKIE);
+    if !set_cookies.is_empty() {
+        let mut jar = lock_jar();
+        for set_cookie in &set_cookies {
+            store_cookie(&mut jar, set_cookie);
+        }
+        save_jar(&jar);
+    }
+    Ok(response)
+}
@@ -0,0 +1,82 @@
+// Choosing between the stable and the preview web app (`server.ui_build` in settings.toml)
+//
+// Some releases bundle a second build of the web app next to `public`, in
+// `public-preview`. The server serves whichever folder PUBLIC_DIR points to, so trying
+// the preview is a matter of restarting the server with the other folder (see
+// server.rs) and the window reloads with it; switching back is the same, no reinstall.
+// A saved "preview" on a release without that folder falls back to the stable build.
+
+use serde::Serialize;
+use serde_json::json;
+use tauri::{AppHandle, Manager};
+use crate::events::{self, Event};
+use crate::logs::{log_line, SharedLogStore};
+use crate::server::{ServerStatus, SharedServerManager};
+use crate::settings::SharedSettings;
+
+pub const BUILDS: &[&str] = &["stable", "preview"];
+/// Folder of the preview build in the resources, next to `public`
+pub const PREVIEW_DIR: &str = "public-preview";
+
+#[derive(Serialize)]
+pub struct UiBuildInfo {
+    /// The saved build
+    build: String,
+    /// Whether this release bundles a preview build
+    preview_available: bool,
+}
+
+async fn info(app: &AppHandle) -> UiBuildInfo {
+    UiBuildInfo {
+        build: app.state::<SharedSettings>().lock().await.get().server.ui_build,
+        preview_available: crate::resources::has_preview(app),
+    }
+}
+
+/// The saved web app build and whether a preview is bundled
+#[tauri::command]
+pub async fn get_ui_build(app: AppHandle) -> Result<UiBuildInfo, String> {
+    Ok(info(&app).await)
+}
+
+/// Save the web app build and restart a running server with it
+#[tauri::command]
+pub async fn set_ui_build(app: AppHandle, build: String) -> Result<UiBuildInfo, String> {
+    if build == "preview" && !crate::resources::has_preview(&app) {
+        return Err("This version of Moneywright doesn't include a preview of the web app".to_string());
+    }
+    let settings = app.state::<SharedSettings>().inner().clone();
+    let store = settings.lock().await;
+    if store.get().server.ui_build == build {
+        drop(store);
+        return Ok(info(&app).await);
+    }
+    let updated = store.update(&json!({ "server": { "ui_build": build } }))?;
+    drop(store);
+    let _ = events::emit(&app, Event::SettingsChanged(&updated));
+
+    let manager = app.state::<SharedServerManager>().inner().clone();
+    let log_store = app.state::<SharedLogStore>().inner().clone();
+    log_line(&app, &log_store, format!("Switching to the {} web app", build), "info").await;
+    if !matches!(manager.lock().await.status(), ServerStatus::Stopped | ServerStatus::Paused) {
+        crate::restart_server(app.clone(), manager, log_store).await?;
+    }
+    if let Err(e) = crate::setup_menu(&app) {
+        eprintln!("Warning: Failed to update menu: {}", e);
+    }
+    Ok(info(&app).await)
+}
+
+/// Whether the preview is selected, for the menu check mark (built outside async code)
+pub fn preview_selected(app: &AppHandle) -> bool {
+    app.try_state::<SharedSettings>()
+        .and_then(|settings| settings.try_lock().ok().map(|store| store.get().server.ui_build == "preview"))
+        .unwrap_or(false)
+}
+
+/// Switch to the other build, from the menu
+pub async fn toggle(app: &AppHandle) -> Result<(), String> {
+    let current = app.state::<SharedSettings>().lock().await.get().server.ui_build;
+    let next = if current == "preview" { "stable" } else { "preview" };
+    set_ui_build(app.clone(), next.to_string()).await.map(|_| ())
+}
@@ -0,0 +1,439 @@
+// Uninstall assistant: take your data along, then remove what the app stored
+//
+// Uninstallers only remove the program, so the data dir and the keychain entries used
+// to stay behind without a word. The assistant offers three steps, each optional:
+//
+//   1. a final full archive (see archive.rs), SQLite only
+//   2. deleting the app's keychain entries
+//   3. deleting the data dir, overwriting every file with zeros first
+//
+// Nothing is deleted if the archive was asked for and failed. The data dir can be any
+// folder picked during onboarding, so it's only deleted when everything in it is
+// something the app creates (APP_ENTRIES); otherwise it's left alone and the other
+// files are named. Deleting it also removes the `data-location` pointer to it. Data the app doesn't
+// own outright (extra profiles in folders of their own, a custom backups folder, a
+// PostgreSQL database) is listed and left in place.
+//
+// It opens from Help > Uninstall Moneywright, and the Windows uninstaller starts the
+// app with `--uninstall-assist` (see windows/hooks.nsh) and waits for it to quit. The
+// zeros don't reach copies an SSD, a snapshot or a backup keeps; full-disk encryption
+// is what covers those.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+use crate::backup::{backups_dir, sqlite_db_path};
+use crate::server::{default_data_dir, read_database_url, stop_server, SharedServerManager, DATA_LOCATION_FILE};
+use crate::settings::SharedSettings;
+use crate::windows::open_injected_window;
+use crate::{archive, keychain, profiles, relocation};
+
+/// Command-line flag the Windows uninstaller starts the app with
+pub const FLAG: &str = "--uninstall-assist";
+const WINDOW_LABEL: &str = "uninstall";
+/// Size of the zero blocks files are overwritten with
+const WIPE_BLOCK: usize = 1024 * 1024;
+
+/// What the app creates in its data dir; an entry also matches with a suffix after a
+/// dot, like `settings.toml.invalid` or `.env.bak`
+const APP_ENTRIES: &[&str] = &[
+    // Folders
+    "data", "drizzle", "backups", "attachments", "crashes", "logs", "tls", "postgres", "service",
+    "sidecars", "resources", "quarantine", "updates", "profiles", "webview", "public-preview",
+    // Files
+    ".env", ".doctor-write-test", "settings.toml", "onboarding.json", "profiles.json", "exports.json",
+    "schedule.json", "layouts.json", "notifications.json", "fx_rates.json", "heartbeat.json",
+    "spawned.json", "startup-benchmarks.json", "session-stats.json", "usage-stats.json",
+    "network-usage.json", "task-history.jsonl", "cleanups.jsonl", "backup-verifications.json",
+    "feature-overrides.toml", "feature-flags.log", "upgrade.json", "download.json", "arch-notice",
+    "server-port", "server_version", "session.lock", "control.sock", "server.sock",
+    "session-cookies.json", relocation::MOVING_FILE, DATA_LOCATION_FILE,
+];
+
+/// Set once the data dir is gone, so quitting skips the exit handler's writes into it
+static DATA_REMOVED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Serialize)]
+pub struct UninstallInfo {
+    pub data_dir: String,
+    pub data_bytes: u64,
+    /// Entries in the data dir the app didn't create; the folder isn't deleted while there are any
+    pub foreign_entries: Vec<String>,
+    /// Whether a full archive can be made (SQLite only)
+    pub can_archive: bool,
+    /// Keychain entries the app uses that exist
+    pub keychain_entries: Vec<String>,
+    /// What the assistant won't delete, with where it is
+    pub left_in_place: Vec<String>,
+    /// Started by the uninstaller, which waits for the app to quit
+    pub from_uninstaller: bool,
+}
+
+#[derive(Deserialize)]
+pub struct UninstallOptions {
+    pub archive: bool,
+    pub delete_keychain: bool,
+    pub delete_data: bool,
+}
+
+#[derive(Serialize)]
+pub struct UninstallResult {
+    /// Where the archive was saved
+    pub archive: Option<String>,
+    pub keychain_deleted: usize,
+    pub data_deleted: bool,
+    /// What couldn't be deleted, with why
+    pub errors: Vec<String>,
+}
+
+/// Whether the app was started by the uninstaller
+pub fn requested() -> bool {
+    std::env::args().any(|arg| arg == FLAG)
+}
+
+/// Keychain entries the app may have created
+fn keychain_keys(data_dir: &Path) -> Vec<String> {
+    let mut keys = vec![
+        crate::database::PASSWORD_KEY.to_string(),
+        crate::postgres::PASSWORD_KEY.to_string(),
+        crate::alerts::SMTP_PASSWORD_KEY.to_string(),
+        crate::netproxy::PROXY_PASSWORD_KEY.to_string(),
+    ];
+    if let Ok((_, task_keys)) = crate::exports::transfer_tasks(data_dir) {
+        keys.extend(task_keys);
+    }
+    keys
+}
+
+/// Data of the app outside the data dir, which stays where it is
+fn left_in_place(data_dir: &Path, backups: &Path) -> Vec<String> {
+    let mut items: Vec<String> = profiles::external_data_dirs(data_dir)
+        .into_iter()
+        .map(|dir| format!("Profile data in {}", dir.display()))
+        .collect();
+    if !backups.starts_with(data_dir) {
+        items.push(format!("Backups in {}", backups.display()));
+    }
+    if read_database_url(data_dir).is_some() {
+        items.push("The PostgreSQL database (DATABASE_URL); drop it on the database server".to_string());
+    }
+    items
+}
+
+/// Total size of the regular files below a directory, not following links
+fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| Some((entry.path(), entry.path().symlink_metadata().ok()?)))
+        .map(|(path, meta)| if meta.is_dir() { dir_size(&path) } else if meta.is_file() { meta.len() } else { 0 })
+        .sum()
+}
+
+/// Overwrite a file with zeros and flush it to disk
+fn wipe_file(path: &Path, len: u64) -> Result<(), String> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let zeros = vec![0u8; WIPE_BLOCK];
+    let mut left = len;
+    while left > 0 {
+        let n = left.min(WIPE_BLOCK as u64) as usize;
+        file.write_all(&zeros[..n])
+            .map_err(|e| format!("Failed to overwrite {}: {}", path.display(), e))?;
+        left -= n as u64;
+    }
+    file.sync_all().map_err(|e| format!("Failed to overwrite {}: {}", path.display(), e))
+}
+
+/// Overwrite every regular file below a directory, collecting failures
+fn wipe_dir(dir: &Path, errors: &mut Vec<String>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(meta) = path.symlink_metadata() else {
+            continue;
+        };
+        if meta.is_dir() {
+            wipe_dir(&path, errors);
+        } else if meta.is_file() {
+            if let Err(e) = wipe_file(&path, meta.len()) {
+                errors.push(e);
+            }
+        }
+    }
+}
+
+/// Names in the data dir that aren't in APP_ENTRIES
+fn foreign_entries(data_dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(data_dir) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|name| {
+            !APP_ENTRIES
+                .iter()
+                .any(|known| name == known || name.strip_prefix(known).is_some_and(|rest| rest.starts_with('.')))
+        })
+        .collect();
+    names.sort();
+    names
+}
+
+/// Zero and remove the data dir, and the pointer to it if it was relocated; refused
+/// when it holds anything the app didn't create
+fn delete_data_dir(data_dir: &Path, default_dir: &Path) -> Vec<String> {
+    let foreign = foreign_entries(data_dir);
+    if !foreign.is_empty() {
+        return vec![format!(
+            "{} also holds files Moneywright didn't create ({}), so it was left in place",
+            data_dir.display(),
+            foreign.join(", ")
+        )];
+    }
+    let mut errors = Vec::new();
+    wipe_dir(data_dir, &mut errors);
+    if let Err(e) = fs::remove_dir_all(data_dir) {
+        errors.push(format!("Failed to remove {}: {}", data_dir.display(), e));
+    }
+    let pointer = default_dir.join(DATA_LOCATION_FILE);
+    if data_dir != default_dir && pointer.exists() {
+        if let Err(e) = fs::remove_file(&pointer) {
+            errors.push(format!("Failed to remove {}: {}", pointer.display(), e));
+        }
+    }
+    errors
+}
+
+/// What the assistant would archive, delete and leave alone
+#[tauri::command]
+pub async fn get_uninstall_info(app: AppHandle) -> Result<UninstallInfo, String> {
+    // The install's own folder, also while another profile is switched to
+    let data_dir = profiles::main_data_dir(&app);
+    let settings = app.state::<SharedSettings>().lock().await.get();
+    let backups = backups_dir(&data_dir, &settings);
+    tauri::async_runtime::spawn_blocking(move || UninstallInfo {
+        data_bytes: dir_size(&data_dir),
+        foreign_entries: foreign_entries(&data_dir),
+        can_archive: read_database_url(&data_dir).is_none() && sqlite_db_path(&data_dir).exists(),
+        keychain_entries: keychain_keys(&data_dir)
+            .into_iter()
+            .filter(|key| matches!(keychain::get_secret(key), Ok(Some(_))))
+            .collect(),
+        left_in_place: left_in_place(&data_dir, &backups),
+        from_uninstaller: requested(),
+        data_dir: data_dir.to_string_lossy().to_string(),
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Run the chosen steps; the servers are stopped before anything is deleted
+#[tauri::command]
+pub async fn run_uninstall_cleanup(
+    app: AppHandle,
+    manager: State<'_, SharedServerManager>,
+    options: UninstallOptions,
+) -> Result<UninstallResult, String> {
+    let manager = manager.inner().clone();
+    let data_dir = profiles::main_data_dir(&app);
+    let mut result = UninstallResult {
+        archive: None,
+        keychain_deleted: 0,
+        data_deleted: false,
+        errors: Vec::new(),
+    };
+
+    // Without the archive the user asked for, nothing gets deleted
+    if options.archive {
+        let path = archive::export_all(&app, data_dir.clone(), None)
+            .await
+            .map_err(|e| format!("The archive failed, nothing was deleted: {}", e))?;
+        result.archive = Some(path.to_string_lossy().to_string());
+    }
+
+    if options.delete_keychain {
+        let keys = keychain_keys(&data_dir);
+        for key in keys {
+            match keychain::get_secret(&key).and_then(|secret| secret.map(|_| keychain::delete_secret(&key)).transpose()) {
+                Ok(Some(())) => result.keychain_deleted += 1,
+                Ok(None) => {}
+                Err(e) => result.errors.push(e),
+            }
+        }
+    }
+
+    if options.delete_data {
+        let stop_app = app.clone();
+        tauri::async_runtime::spawn_blocking(move || profiles::stop_all(&stop_app))
+            .await
+            .map_err(|e| e.to_string())?;
+        let (port, grace) = {
+            let mgr = manager.lock().await;
+            (mgr.port(), mgr.shutdown_grace())
+        };
+        stop_server(manager).await?;
+        // Otherwise the OS keeps starting a server from the deleted data dir
+        if crate::service::is_installed() {
+            if let Err(e) = tauri::async_runtime::spawn_blocking(move || crate::service::remove(port, grace))
+                .await
+                .map_err(|e| e.to_string())
+                .and_then(|removed| removed)
+            {
+                result.errors.push(e);
+            }
+        }
+        let (stop_app, dir) = (app.clone(), data_dir.clone());
+        tauri::async_runtime::spawn_blocking(move || crate::postgres::stop(&stop_app, &dir))
+            .await
+            .map_err(|e| e.to_string())?;
+        let (dir, default_dir) = (data_dir.clone(), default_data_dir(&app));
+        let errors = tauri::async_runtime::spawn_blocking(move || delete_data_dir(&dir, &default_dir))
+            .await
+            .map_err(|e| e.to_string())?;
+        result.data_deleted = !data_dir.exists();
+        DATA_REMOVED.store(result.data_deleted, Ordering::SeqCst);
+        result.errors.extend(errors);
+    }
+    Ok(result)
+}
+
+/// Quit after the assistant, letting a waiting uninstaller continue
+#[tauri::command]
+pub async fn finish_uninstall(app: AppHandle) -> Result<(), String> {
+    if DATA_REMOVED.load(Ordering::SeqCst) {
+        // The exit handler would write session files back into the removed data dir
+        std::process::exit(0);
+    }
+    app.exit(0);
+    Ok(())
+}
+
+/// Open the uninstall assistant
+pub fn open_uninstall_window(app: &AppHandle) {
+    // Static UI; paths and errors are inserted with escaping on the JS side
+    let script = r#"
+        const tauriApi = window.__TAURI__;
+
+        document.documentElement.innerHTML = `
+<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <title>Uninstall Moneywright</title>
+    <style>
+        __BASE_STYLE__
+        .content { flex: 1; padding: 24px; display: flex; flex-direction: column; gap: 12px; overflow-y: auto; }
+        .path { font-family: ui-monospace, SFMono-Regular, Menlo, monospace; font-size: 12px; word-break: break-all; }
+        label.option { display: flex; gap: 10px; align-items: flex-start; }
+        label.option input { margin-top: 3px; }
+        ul { padding-left: 18px; }
+        .actions { display: flex; gap: 8px; margin-top: 8px; }
+    </style>
+</head>
+<body>
+    <div class="content">
+        <h1>Before you go</h1>
+        <p>Removing the program leaves your data on this computer. Choose what should happen to it:</p>
+        <div id="dataDir" class="path muted"></div>
+        <label class="option"><input type="checkbox" id="archive" checked>
+            <span>Save a full archive of my data to Downloads first<br><span id="archiveNote" class="muted">Readable without Moneywright: SQLite, CSV and JSON.</span></span></label>
+        <label class="option"><input type="checkbox" id="keychain">
+            <span>Delete saved passwords from the keychain<br><span id="keychainNote" class="muted"></span></span></label>
+        <label class="option"><input type="checkbox" id="data">
+            <span>Delete the data folder<br><span id="dataNote" class="muted">Files are overwritten before they are removed. Copies on SSDs, in snapshots or in backups can survive; full-disk encryption protects those.</span></span></label>
+        <div id="leftWrap" hidden>
+            <p class="muted">Not deleted, remove these yourself if you want them gone:</p>
+            <ul id="left" class="muted"></ul>
+        </div>
+        <div class="actions">
+            <button id="runBtn" class="primary">Continue</button>
+            <button id="skipBtn">Keep Everything</button>
+        </div>
+        <div id="status" class="muted" role="status" aria-live="polite"></div>
+    </div>
+</body>
+</html>`;
+
+        const $ = id => document.getElementById(id);
+        let info = null;
+
+        function formatBytes(bytes) {
+            if (bytes < 1024 * 1024) return Math.ceil(bytes / 1024) + ' KB';
+            if (bytes < 1024 * 1024 * 1024) return (bytes / 1024 / 1024).toFixed(1) + ' MB';
+            return (bytes / 1024 / 1024 / 1024).toFixed(2) + ' GB';
+        }
+
+        function finish() {
+            tauriApi.core.invoke('finish_uninstall').catch(() => {});
+        }
+
+        tauriApi.core.invoke('get_uninstall_info').then(i => {
+            info = i;
+            $('dataDir').textContent = i.data_dir + ' (' + formatBytes(i.data_bytes) + ')';
+            if (!i.can_archive) {
+                $('archive').checked = false;
+                $('archive').disabled = true;
+                $('archiveNote').textContent = 'Not available: there is no local SQLite database to archive.';
+            }
+            $('keychainNote').textContent = i.keychain_entries.length
+                ? i.keychain_entries.length + ' saved: ' + i.keychain_entries.join(', ')
+                : 'None saved.';
+            $('left').innerHTML = '';
+            i.left_in_place.forEach(item => {
+                const li = document.createElement('li');
+                li.textContent = item;
+                $('left').appendChild(li);
+            });
+            $('leftWrap').hidden = i.left_in_place.length === 0;
+            if (i.foreign_entries.length) {
+                const shown = i.foreign_entries.slice(0, 5).join(', ') + (i.foreign_entries.length > 5 ? ', ...' : '');
+                $('data').disabled = true;
+                $('dataNote').textContent = 'Not available: the folder also holds files Moneywright didn\'t create (' + shown + ').';
+            }
+            $('skipBtn').textContent = i.from_uninstaller ? 'Keep Everything' : 'Cancel';
+        }).catch(e => $('status').textContent = String(e));
+
+        $('runBtn').onclick = async () => {
+            const options = { archive: $('archive').checked, delete_keychain: $('keychain').checked, delete_data: $('data').checked };
+            if (options.delete_data && !options.archive
+                && !confirm('Delete all Moneywright data without saving an archive? This cannot be undone.')) {
+                return;
+            }
+            $('runBtn').disabled = true;
+            $('skipBtn').disabled = true;
+            $('status').textContent = options.archive ? 'Saving the archive...' : 'Working...';
+            try {
+                const result = await tauriApi.core.invoke('run_uninstall_cleanup', { options });
+                const done = [];
+                if (result.archive) done.push('Archive saved to ' + result.archive + '.');
+                if (options.delete_keychain) done.push(result.keychain_deleted + ' keychain entries deleted.');
+                if (result.data_deleted) done.push('Data folder deleted.');
+                $('status').textContent = done.concat(result.errors).join(' ') || 'Nothing to do.';
+                $('runBtn').textContent = 'Quit';
+                $('runBtn').disabled = false;
+                $('runBtn').onclick = finish;
+            } catch (e) {
+                $('status').textContent = String(e);
+                $('runBtn').disabled = false;
+                $('skipBtn').disabled = false;
+            }
+        };
+        $('skipBtn').onclick = () => {
+            if (info && info.from_uninstaller) finish();
+            else tauriApi.window.getCurrentWindow().close();
+        };
+    "#;
+
+    open_injected_window(app, WINDOW_LABEL, "Uninstall Moneywright", (580.0, 520.0), true, script);
+}
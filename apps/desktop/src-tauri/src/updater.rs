@@ -1,13 +1,33 @@
 // Auto-update functionality for Moneywright Desktop
+//
+// `updates.channel` picks the release manifest: stable uses the endpoint in
+// tauri.conf.json (the latest release), beta and nightly the `latest.json` of their
+// rolling releases. Versions only go up, so after switching back to stable a beta
+// build stays until a stable release is newer than it.
+//
+// Requests go through the proxy from `network.proxy` (see netproxy.rs).
+//
+// With `updates.policy` "on_quit", a background download isn't installed straight away
+// but kept (in memory, as the plugin holds it while downloading) and installed by the
+// exit handler once the server has stopped, so quitting is all it takes to update.
 
-use tauri::{Runtime, Manager, WebviewUrl, WebviewWindowBuilder, Emitter};
-use tauri_plugin_updater::UpdaterExt;
+use tauri::{Runtime, Manager, Url, WebviewUrl, WebviewWindowBuilder};
+use tauri_plugin_updater::{Update, Updater, UpdaterExt};
 use serde::Serialize;
+use serde_json::json;
 use std::sync::Arc;
 use tokio::sync::Mutex;
+use crate::events::{self, Event};
+use crate::download;
+use crate::history;
+use crate::markdown;
+use crate::settings::SharedSettings;
+use crate::jobs::{start_job, JobKind};
+use crate::netproxy;
+use crate::windows::window_script;
 
 #[derive(Clone, Serialize)]
-struct DownloadProgress {
+pub struct DownloadProgress {
     downloaded: usize,
     total: Option<u64>,
     percent: f64,
@@ -33,38 +53,175 @@ impl UpdateState {
 
 pub type SharedUpdateState = Arc<Mutex<UpdateState>>;
 
-/// Check for updates and show result to user
+pub const CHANNELS: &[&str] = &["stable", "beta", "nightly"];
+pub const POLICIES: &[&str] = &["restart", "on_quit"];
+const RELEASES_URL: &str = "https://github.com/moneywright/moneywright/releases";
+/// Skipped versions remembered in `updates.skipped_versions`, the oldest are dropped
+const MAX_SKIPPED: usize = 20;
+
+/// A verified update waiting for the app to quit
+static STAGED: std::sync::Mutex<Option<(Update, Vec<u8>)>> = std::sync::Mutex::new(None);
+
+/// The channel in `updates.channel`
+pub async fn current_channel<R: Runtime>(app: &tauri::AppHandle<R>) -> String {
+    match app.try_state::<SharedSettings>() {
+        Some(settings) => settings.lock().await.get().updates.channel,
+        None => CHANNELS[0].to_string(),
+    }
+}
+
+/// An updater reading the manifest of the selected channel, through the configured proxy
+pub async fn channel_updater<R: Runtime>(app: &tauri::AppHandle<R>) -> tauri_plugin_updater::Result<Updater> {
+    let channel = current_channel(app).await;
+    let mut builder = app.updater_builder();
+    if channel != "stable" {
+        let endpoint = Url::parse(&format!("{}/download/{}/latest.json", RELEASES_URL, channel))?;
+        builder = builder.endpoints(vec![endpoint])?;
+    }
+    let (network, proxy) = netproxy::updater_proxy(app).await;
+    match proxy {
+        Ok(Some(proxy)) => builder = builder.proxy(proxy),
+        // reqwest would still pick up HTTPS_PROXY and friends
+        Ok(None) if network.proxy == "none" => builder = builder.configure_client(|client| client.no_proxy()),
+        Ok(None) => {}
+        Err(e) => return Err(tauri_plugin_updater::Error::Network(e)),
+    }
+    builder.build()
+}
+
+/// A failure to reach the update server, where the proxy may be to blame
+fn is_connection_error(error: &tauri_plugin_updater::Error) -> bool {
+    match error {
+        tauri_plugin_updater::Error::Reqwest(e) => e.is_connect() || e.is_timeout() || e.is_request(),
+        tauri_plugin_updater::Error::Network(_) => true,
+        _ => false,
+    }
+}
+
+/// The policy in `updates.policy`
+pub async fn current_policy<R: Runtime>(app: &tauri::AppHandle<R>) -> String {
+    match app.try_state::<SharedSettings>() {
+        Some(settings) => settings.lock().await.get().updates.policy,
+        None => POLICIES[0].to_string(),
+    }
+}
+
+/// An update is downloaded and installs when the app quits
+pub fn has_staged() -> bool {
+    STAGED.lock().unwrap_or_else(|e| e.into_inner()).is_some()
+}
+
+/// Install the update kept for quitting, if any
+/// Called on exit after the server stopped; on Windows the installer ends the process
+pub fn install_staged() {
+    let staged = STAGED.lock().unwrap_or_else(|e| e.into_inner()).take();
+    if let Some((update, bytes)) = staged {
+        if let Err(e) = update.install(bytes) {
+            eprintln!("Failed to install Moneywright {} on quit: {}", update.version, e);
+        }
+    }
+}
+
+/// Switch the update channel; the next check uses it
+#[tauri::command]
+pub async fn set_update_channel(app: tauri::AppHandle, channel: String) -> Result<(), String> {
+    if !CHANNELS.contains(&channel.as_str()) {
+        return Err(format!("Unknown update channel \"{}\"", channel));
+    }
+    let updated = app
+        .state::<SharedSettings>()
+        .lock()
+        .await
+        .update(&json!({ "updates": { "channel": channel } }))?;
+    let _ = events::emit(&app, Event::SettingsChanged(&updated));
+    Ok(())
+}
+
+/// Whether the user chose to skip this version
+pub async fn is_skipped<R: Runtime>(app: &tauri::AppHandle<R>, version: &str) -> bool {
+    match app.try_state::<SharedSettings>() {
+        Some(settings) => settings.lock().await.get().updates.skipped_versions.iter().any(|v| v == version),
+        None => false,
+    }
+}
+
+/// Stop offering a version; newer ones are offered again
+#[tauri::command]
+pub async fn skip_update_version(app: tauri::AppHandle, version: String) -> Result<(), String> {
+    if version.is_empty() || !version.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+')) {
+        return Err(format!("\"{}\" isn't a version", version));
+    }
+    let settings = app.state::<SharedSettings>();
+    let settings = settings.lock().await;
+    let mut skipped = settings.get().updates.skipped_versions;
+    if skipped.contains(&version) {
+        return Ok(());
+    }
+    skipped.push(version);
+    let excess = skipped.len().saturating_sub(MAX_SKIPPED);
+    skipped.drain(..excess);
+    let updated = settings.update(&json!({ "updates": { "skipped_versions": skipped } }))?;
+    drop(settings);
+    let _ = events::emit(&app, Event::SettingsChanged(&updated));
+    Ok(())
+}
+
+/// Check for updates and show result to user; skipped versions count as up to date
 pub async fn check_for_updates<R: Runtime>(app: tauri::AppHandle<R>) {
-    match app.updater() {
+    let run = history::begin("update_check", "Checking for updates");
+    match channel_updater(&app).await {
         Ok(updater) => {
-            match updater.check().await {
+            let result = match updater.check().await {
+                Ok(Some(update)) if is_skipped(&app, &update.version).await => Ok(None),
+                result => result,
+            };
+            match result {
                 Ok(Some(update)) => {
+                    run.finish::<()>(&Ok(()));
                     show_update_available(&app, &update.current_version, &update.version, update.body.as_deref());
                 }
                 Ok(None) => {
+                    run.finish::<()>(&Ok(()));
                     show_no_update(&app);
                 }
                 Err(e) => {
-                    show_update_error(&app, &e.to_string());
+                    run.finish::<()>(&Err(e.to_string()));
+                    let hint = if is_connection_error(&e) { Some(netproxy::error_hint(&app).await) } else { None };
+                    show_update_error(&app, &e.to_string(), hint.as_deref());
                 }
             }
         }
         Err(e) => {
-            show_update_error(&app, &e.to_string());
+            run.finish::<()>(&Err(e.to_string()));
+            let hint = if is_connection_error(&e) { Some(netproxy::error_hint(&app).await) } else { None };
+            show_update_error(&app, &e.to_string(), hint.as_deref());
         }
     }
 }
 
-/// Download and install update in background (without restart)
-/// Returns update info if successful
+fn progress_percent(downloaded: u64, total: Option<u64>) -> f64 {
+    match total {
+        Some(total) if total > 0 => (downloaded as f64 / total as f64) * 100.0,
+        _ => 0.0,
+    }
+}
+
+/// Download and install update in background (without restart), or keep it for quitting
+/// with the "on_quit" policy; returns update info if successful
 pub async fn background_download_and_install<R: Runtime>(app: tauri::AppHandle<R>) -> Result<UpdateReadyInfo, String> {
-    let updater = app.updater().map_err(|e| format!("Failed to initialize updater: {}", e))?;
+    let updater = channel_updater(&app).await.map_err(|e| format!("Failed to initialize updater: {}", e))?;
 
     let update = updater
         .check()
         .await
         .map_err(|e| format!("Failed to check for updates: {}", e))?
         .ok_or_else(|| "No update available".to_string())?;
+    if is_skipped(&app, &update.version).await {
+        return Err(format!("Moneywright {} was skipped", update.version));
+    }
+    if download::is_paused() {
+        return Err("The update download is paused".to_string());
+    }
 
     let info = UpdateReadyInfo {
         current_version: update.current_version.to_string(),
@@ -74,41 +231,51 @@ pub async fn background_download_and_install<R: Runtime>(app: tauri::AppHandle<R
 
     // Download with progress reporting
     let app_clone = app.clone();
-    let mut downloaded: usize = 0;
-
-    let bytes = update
-        .download(
-            move |chunk_length, content_length| {
-                downloaded += chunk_length;
-                let percent = if let Some(total) = content_length {
-                    (downloaded as f64 / total as f64) * 100.0
-                } else {
-                    0.0
-                };
-                let _ = app_clone.emit("background-update-progress", DownloadProgress {
-                    downloaded,
-                    total: content_length,
-                    percent,
-                });
-            },
-            || {},
-        )
-        .await
-        .map_err(|e| format!("Download failed: {}", e))?;
+    let job = start_job(&app, JobKind::Update, format!("Downloading Moneywright {}", info.new_version), true);
 
-    // Install the update (stages it for next restart)
-    update.install(bytes).map_err(|e| format!("Install failed: {}", e))?;
+    let download = download::fetch(&app, &update, true, |downloaded, total| {
+        let percent = progress_percent(downloaded, total);
+        job.progress(total.map(|_| percent / 100.0), None);
+        let _ = events::emit(&app_clone, Event::BackgroundUpdateProgress(&DownloadProgress {
+            downloaded: downloaded as usize,
+            total,
+            percent,
+        }));
+    });
+    let result = tokio::select! {
+        bytes = download => bytes.map_err(|e| format!("Download failed: {}", e)),
+        _ = job.cancelled() => {
+            download::discard();
+            Err(download::CANCELLED.to_string())
+        }
+    };
+    let install_on_quit = current_policy(&app).await == "on_quit";
+    let result = result.and_then(|bytes| {
+        if install_on_quit {
+            *STAGED.lock().unwrap_or_else(|e| e.into_inner()) = Some((update, bytes));
+            Ok(())
+        } else {
+            // Install the update (stages it for next restart)
+            update.install(bytes).map_err(|e| format!("Install failed: {}", e))
+        }
+    });
+    job.finish(&result);
+    result?;
 
     // Emit that update is ready
-    let _ = app.emit("update-ready", &info);
+    let _ = events::emit(&app, Event::UpdateReady(&info));
 
     Ok(info)
 }
 
 /// Show dialog when update is available
 fn show_update_available<R: Runtime>(app: &tauri::AppHandle<R>, current: &str, new_version: &str, body: Option<&str>) {
-    let notes = body.unwrap_or("Bug fixes and improvements");
-    // Note: HTML content is static/hardcoded with only version strings from Tauri updater API
+    let notes = match body.map(str::trim).filter(|body| !body.is_empty()) {
+        Some(body) => markdown::to_html(body),
+        None => "<p>Bug fixes and improvements</p>".to_string(),
+    };
+    // Note: HTML content is static/hardcoded apart from the version strings and the release
+    // notes, which markdown.rs renders to escaped HTML
     let html = format!(r#"
         window._tauri = window.__TAURI__;
 
@@ -118,10 +285,9 @@ fn show_update_available<R: Runtime>(app: &tauri::AppHandle<R>, current: &str, n
 <head>
     <meta charset="UTF-8">
     <style>
-        @import url('https://fonts.googleapis.com/css2?family=DM+Sans:wght@400;500;600&family=Outfit:wght@500;600;700&display=swap');
         * {{ margin: 0; padding: 0; box-sizing: border-box; }}
         body {{
-            font-family: 'DM Sans', -apple-system, BlinkMacSystemFont, sans-serif;
+            font-family: system-ui, -apple-system, BlinkMacSystemFont, 'Segoe UI', sans-serif;
             background: linear-gradient(145deg, #050806 0%, #030303 50%, #040504 100%);
             color: #fafafa;
             height: 100vh;
@@ -201,7 +367,6 @@ fn show_update_available<R: Runtime>(app: &tauri::AppHandle<R>, current: &str, n
             filter: drop-shadow(0 2px 4px rgba(0,0,0,0.2));
         }}
         h2 {{
-            font-family: 'Outfit', sans-serif;
             font-size: 22px;
             font-weight: 600;
             letter-spacing: -0.02em;
@@ -239,6 +404,40 @@ fn show_update_available<R: Runtime>(app: &tauri::AppHandle<R>, current: &str, n
             line-height: 1.7;
             margin-bottom: 32px;
             max-width: 280px;
+            max-height: 120px;
+            overflow-y: auto;
+        }}
+        .notes h3, .notes h4 {{
+            font-size: 13px;
+            font-weight: 600;
+            color: #a1a1aa;
+            margin: 8px 0 2px;
+        }}
+        .notes ul, .notes ol {{
+            text-align: left;
+            padding-left: 18px;
+        }}
+        .notes p + p, .notes blockquote {{
+            margin-top: 6px;
+        }}
+        .notes blockquote {{
+            padding-left: 10px;
+            border-left: 2px solid rgba(255, 255, 255, 0.1);
+        }}
+        .notes code {{
+            font-size: 12px;
+            color: #a1a1aa;
+        }}
+        .notes pre {{
+            text-align: left;
+            white-space: pre-wrap;
+        }}
+        .notes a {{
+            color: #10b981;
+            text-decoration: none;
+        }}
+        .notes a:hover {{
+            text-decoration: underline;
         }}
         .progress-container {{
             width: 100%;
@@ -321,6 +520,18 @@ fn show_update_available<R: Runtime>(app: &tauri::AppHandle<R>, current: &str, n
             background: rgba(255, 255, 255, 0.08);
             color: #fafafa;
         }}
+        .skip {{
+            flex: none;
+            margin-top: 8px;
+            padding: 8px 12px;
+            background: none;
+            color: #71717a;
+            font-size: 12px;
+            font-weight: 500;
+        }}
+        .skip:hover {{
+            color: #a1a1aa;
+        }}
         .error-container {{
             display: none;
             width: 100%;
@@ -338,7 +549,7 @@ fn show_update_available<R: Runtime>(app: &tauri::AppHandle<R>, current: &str, n
     </style>
 </head>
 <body>
-    <div class="container">
+    <div class="container" role="main">
         <div class="icon-wrapper">
             <div class="icon-glow" id="iconGlow"></div>
             <div class="icon-box" id="iconBox">
@@ -357,11 +568,11 @@ fn show_update_available<R: Runtime>(app: &tauri::AppHandle<R>, current: &str, n
                     <path d="M5 12h14M12 5l7 7-7 7"/>
                 </svg>
             </span>
-            <span class="version-badge version-new">{}</span>
+            <span class="version-badge version-new" id="newVersion">{}</span>
         </div>
         <div class="notes" id="notes">{}</div>
         <div class="progress-container" id="progressContainer">
-            <div class="progress-track">
+            <div class="progress-track" id="progressTrack" role="progressbar" aria-label="Download progress" aria-valuemin="0" aria-valuemax="100" aria-valuenow="0">
                 <div class="progress-fill" id="progressFill"></div>
             </div>
             <div class="progress-info">
@@ -369,14 +580,17 @@ fn show_update_available<R: Runtime>(app: &tauri::AppHandle<R>, current: &str, n
                 <span class="progress-percent" id="progressText">0%</span>
             </div>
         </div>
-        <div class="status" id="status"></div>
+        <div class="status" id="status" role="status"></div>
         <div class="error-container" id="errorContainer">
-            <div class="error-text" id="errorText"></div>
+            <div class="error-text" id="errorText" role="alert"></div>
         </div>
         <div class="buttons" id="buttons">
             <button class="secondary" id="laterBtn">Later</button>
+            <button class="secondary" id="cancelBtn" style="display: none">Cancel Download</button>
+            <button class="secondary" id="pauseBtn" style="display: none">Pause</button>
             <button class="primary" id="updateBtn">Install Update</button>
         </div>
+        <button class="skip" id="skipBtn">Skip This Version</button>
     </div>
 </body>
 </html>`;
@@ -386,18 +600,59 @@ fn show_update_available<R: Runtime>(app: &tauri::AppHandle<R>, current: &str, n
         window._tauri.event.listen('update-progress', (event) => {{
             const {{ percent }} = event.payload;
             $('progressFill').style.width = percent + '%';
+            $('progressTrack').setAttribute('aria-valuenow', Math.round(percent));
             $('progressText').textContent = Math.round(percent) + '%';
             if (percent > 99) {{
                 $('progressLabel').textContent = 'Installing...';
             }}
         }});
 
+        // Release note links open in the browser, not in this window
+        $('notes').onclick = (e) => {{
+            const link = e.target.closest('a[data-url]');
+            if (!link) return;
+            e.preventDefault();
+            window._tauri.core.invoke('open_url', {{ url: link.dataset.url }});
+        }};
+
         $('laterBtn').onclick = () => window._tauri.window.getCurrentWindow().close();
+        $('skipBtn').onclick = async () => {{
+            try {{
+                await window._tauri.core.invoke('skip_update_version', {{ version: $('newVersion').textContent }});
+                window._tauri.window.getCurrentWindow().close();
+            }} catch (e) {{
+                $('errorContainer').style.display = 'block';
+                $('errorText').textContent = String(e);
+            }}
+        }};
+
+        // A paused download keeps what arrived; Resume continues from there
+        $('pauseBtn').onclick = async () => {{
+            $('pauseBtn').disabled = true;
+            try {{
+                await window._tauri.core.invoke('pause_update_download');
+            }} catch (e) {{
+                $('pauseBtn').disabled = false;
+            }}
+        }};
+        $('cancelBtn').onclick = async () => {{
+            try {{
+                await window._tauri.core.invoke('cancel_update_download');
+            }} finally {{
+                window._tauri.window.getCurrentWindow().close();
+            }}
+        }};
 
         $('updateBtn').onclick = async () => {{
             $('updateBtn').disabled = true;
             $('updateBtn').textContent = 'Downloading...';
             $('laterBtn').style.display = 'none';
+            $('cancelBtn').style.display = 'none';
+            $('pauseBtn').style.display = 'block';
+            $('pauseBtn').disabled = false;
+            $('errorContainer').style.display = 'none';
+            $('progressLabel').textContent = 'Downloading...';
+            $('skipBtn').style.display = 'none';
             $('notes').style.display = 'none';
             $('versionInfo').style.display = 'none';
             $('progressContainer').style.display = 'flex';
@@ -413,6 +668,19 @@ fn show_update_available<R: Runtime>(app: &tauri::AppHandle<R>, current: &str, n
                 $('status').textContent = 'Update installed successfully';
                 $('buttons').style.display = 'none';
             }} catch (e) {{
+                $('pauseBtn').style.display = 'none';
+                if (String(e) === '{paused}') {{
+                    $('title').textContent = 'Download Paused';
+                    $('progressLabel').textContent = 'Paused';
+                    $('cancelBtn').style.display = 'block';
+                    $('updateBtn').textContent = 'Resume';
+                    $('updateBtn').disabled = false;
+                    return;
+                }}
+                if (String(e) === '{cancelled}') {{
+                    window._tauri.window.getCurrentWindow().close();
+                    return;
+                }}
                 $('progressContainer').style.display = 'none';
                 $('iconGlow').style.background = 'rgba(239, 68, 68, 0.4)';
                 $('iconBox').style.background = 'linear-gradient(135deg, #ef4444 0%, #dc2626 100%)';
@@ -426,7 +694,7 @@ fn show_update_available<R: Runtime>(app: &tauri::AppHandle<R>, current: &str, n
                 $('laterBtn').textContent = 'Close';
             }}
         }};
-    "#, current, new_version, notes);
+    "#, current, new_version, notes, paused = download::PAUSED, cancelled = download::CANCELLED);
 
     open_update_window(app, "Software Update", 380.0, 400.0, &html);
 }
@@ -443,10 +711,9 @@ fn show_no_update<R: Runtime>(app: &tauri::AppHandle<R>) {
 <head>
     <meta charset="UTF-8">
     <style>
-        @import url('https://fonts.googleapis.com/css2?family=DM+Sans:wght@400;500;600&family=Outfit:wght@500;600;700&display=swap');
         * { margin: 0; padding: 0; box-sizing: border-box; }
         body {
-            font-family: 'DM Sans', -apple-system, BlinkMacSystemFont, sans-serif;
+            font-family: system-ui, -apple-system, BlinkMacSystemFont, 'Segoe UI', sans-serif;
             background: linear-gradient(145deg, #050806 0%, #030303 50%, #040504 100%);
             color: #fafafa;
             height: 100vh;
@@ -505,7 +772,6 @@ fn show_no_update<R: Runtime>(app: &tauri::AppHandle<R>) {
             filter: drop-shadow(0 2px 4px rgba(0,0,0,0.2));
         }
         h2 {
-            font-family: 'Outfit', sans-serif;
             font-size: 22px;
             font-weight: 600;
             letter-spacing: -0.02em;
@@ -536,7 +802,7 @@ fn show_no_update<R: Runtime>(app: &tauri::AppHandle<R>) {
     </style>
 </head>
 <body>
-    <div class="container">
+    <div class="container" role="main">
         <div class="icon-wrapper">
             <div class="icon-glow"></div>
             <div class="icon-box">
@@ -556,8 +822,12 @@ fn show_no_update<R: Runtime>(app: &tauri::AppHandle<R>) {
     open_update_window(app, "Software Update", 360.0, 320.0, &html);
 }
 
-/// Show dialog when update check fails
-fn show_update_error<R: Runtime>(app: &tauri::AppHandle<R>, error: &str) {
+/// Show dialog when update check fails, with a note on the proxy for connection errors
+fn show_update_error<R: Runtime>(app: &tauri::AppHandle<R>, error: &str, proxy_hint: Option<&str>) {
+    let proxy_note = match proxy_hint {
+        Some(hint) => format!(r#"<div class="proxy-note">{}</div>"#, hint),
+        None => String::new(),
+    };
     // Note: HTML content is static except for error message from Tauri updater API
     let html = format!(r#"
         window._tauri = window.__TAURI__;
@@ -568,10 +838,9 @@ fn show_update_error<R: Runtime>(app: &tauri::AppHandle<R>, error: &str) {
 <head>
     <meta charset="UTF-8">
     <style>
-        @import url('https://fonts.googleapis.com/css2?family=DM+Sans:wght@400;500;600&family=Outfit:wght@500;600;700&display=swap');
         * {{ margin: 0; padding: 0; box-sizing: border-box; }}
         body {{
-            font-family: 'DM Sans', -apple-system, BlinkMacSystemFont, sans-serif;
+            font-family: system-ui, -apple-system, BlinkMacSystemFont, 'Segoe UI', sans-serif;
             background: linear-gradient(145deg, #080505 0%, #030303 50%, #050404 100%);
             color: #fafafa;
             height: 100vh;
@@ -631,7 +900,6 @@ fn show_update_error<R: Runtime>(app: &tauri::AppHandle<R>, error: &str) {
             filter: drop-shadow(0 2px 4px rgba(0,0,0,0.2));
         }}
         h2 {{
-            font-family: 'Outfit', sans-serif;
             font-size: 22px;
             font-weight: 600;
             letter-spacing: -0.02em;
@@ -651,6 +919,14 @@ fn show_update_error<R: Runtime>(app: &tauri::AppHandle<R>, error: &str) {
             line-height: 1.6;
             word-break: break-word;
         }}
+        .proxy-note {{
+            margin-top: 10px;
+            padding-top: 10px;
+            border-top: 1px solid rgba(239, 68, 68, 0.15);
+            font-size: 12px;
+            color: #a1a1aa;
+            line-height: 1.5;
+        }}
         button {{
             padding: 14px 40px;
             border-radius: 12px;
@@ -670,7 +946,7 @@ fn show_update_error<R: Runtime>(app: &tauri::AppHandle<R>, error: &str) {
     </style>
 </head>
 <body>
-    <div class="container">
+    <div class="container" role="main">
         <div class="icon-wrapper">
             <div class="icon-glow"></div>
             <div class="icon-box">
@@ -682,15 +958,17 @@ fn show_update_error<R: Runtime>(app: &tauri::AppHandle<R>, error: &str) {
         </div>
         <h2>Update Check Failed</h2>
         <div class="error-box">
-            <div class="error-text">{}</div>
+            <div class="error-text" role="alert">{}</div>
+            {}
         </div>
         <button onclick="window._tauri.window.getCurrentWindow().close()">Close</button>
     </div>
 </body>
 </html>`;
-    "#, error);
+    "#, error, proxy_note);
 
-    open_update_window(app, "Software Update", 380.0, 360.0, &html);
+    let height = if proxy_hint.is_some() { 440.0 } else { 360.0 };
+    open_update_window(app, "Software Update", 380.0, height, &html);
 }
 
 /// Open a small update dialog window
@@ -700,13 +978,14 @@ fn open_update_window<R: Runtime>(app: &tauri::AppHandle<R>, title: &str, width:
         let _ = window.close();
     }
 
+    let size = crate::display::text_scaled_size(app, (width, height));
     let window = WebviewWindowBuilder::new(
         app,
         "update",
-        WebviewUrl::App("/".into()),
+        WebviewUrl::CustomProtocol(crate::protocol::page_url("window")),
     )
     .title(title)
-    .inner_size(width, height)
+    .inner_size(size.0, size.1)
     .resizable(false)
     .maximizable(false)
     .minimizable(false)
@@ -714,7 +993,7 @@ fn open_update_window<R: Runtime>(app: &tauri::AppHandle<R>, title: &str, width:
     .build();
 
     if let Ok(win) = window {
-        let html = html.to_string();
+        let html = window_script(html, true);
         let win_clone = win.clone();
         tauri::async_runtime::spawn(async move {
             tokio::time::sleep(std::time::Duration::from_millis(500)).await;
@@ -728,7 +1007,7 @@ fn open_update_window<R: Runtime>(app: &tauri::AppHandle<R>, title: &str, width:
 
 /// Download and install an update with progress reporting
 pub async fn download_and_install<R: Runtime>(app: tauri::AppHandle<R>) -> Result<(), String> {
-    let updater = app.updater().map_err(|e| format!("Failed to initialize updater: {}", e))?;
+    let updater = channel_updater(&app).await.map_err(|e| format!("Failed to initialize updater: {}", e))?;
 
     let update = updater
         .check()
@@ -738,30 +1017,29 @@ pub async fn download_and_install<R: Runtime>(app: tauri::AppHandle<R>) -> Resul
 
     // Download with progress reporting
     let app_clone = app.clone();
-    let mut downloaded: usize = 0;
-
-    let bytes = update
-        .download(
-            move |chunk_length, content_length| {
-                downloaded += chunk_length;
-                let percent = if let Some(total) = content_length {
-                    (downloaded as f64 / total as f64) * 100.0
-                } else {
-                    0.0
-                };
-                let _ = app_clone.emit("update-progress", DownloadProgress {
-                    downloaded,
-                    total: content_length,
-                    percent,
-                });
-            },
-            || {},
-        )
-        .await
-        .map_err(|e| format!("{}", e))?;
+    let job = start_job(&app, JobKind::Update, format!("Downloading Moneywright {}", update.version), true);
 
+    let download = download::fetch(&app, &update, false, |downloaded, total| {
+        let percent = progress_percent(downloaded, total);
+        job.progress(total.map(|_| percent / 100.0), None);
+        let _ = events::emit(&app_clone, Event::UpdateProgress(&DownloadProgress {
+            downloaded: downloaded as usize,
+            total,
+            percent,
+        }));
+    });
+    let result = tokio::select! {
+        bytes = download => bytes,
+        _ = job.cancelled() => {
+            download::discard();
+            Err(download::CANCELLED.to_string())
+        }
+    };
+    let result = result
     // Install the update
-    update.install(bytes).map_err(|e| format!("{}", e))?;
+    .and_then(|bytes| update.install(bytes).map_err(|e| format!("{}", e)));
+    job.finish(&result);
+    result?;
 
     // Restart the app to apply the update
     app.restart();
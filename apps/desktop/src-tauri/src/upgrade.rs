@@ -0,0 +1,450 @@
+// Database upgrades after an app update
+//
+// An update can bring a server whose schema is newer than the database's. The server
+// migrates on start, and a failed migration used to leave a half-upgraded database and
+// nothing to go back to. The version that last started successfully is recorded in
+// `upgrade.json` in the data dir. When it changes and the SQLite database has migrations
+// pending, a snapshot is taken into the backups folder before the server starts
+// (`app-<timestamp>-pre-upgrade.db`, which pruning leaves alone), and the server isn't
+// started without one. While it migrates, `upgrade-progress` events and a migration job
+// report how many migrations are applied, polled from `__drizzle_migrations`.
+//
+// If the server doesn't come up, the upgrade stays pending in `upgrade.json`: the next
+// start retries with the same snapshot instead of snapshotting a half-migrated database,
+// and the upgrade window offers to restore the snapshot (and to download the previous
+// version, which can run on it). PostgreSQL databases are migrated without a snapshot,
+// as they're backed up with their own tools.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+use tokio::sync::oneshot;
+use crate::backup::{backups_dir, integrity_check, migration_count, replace_database, snapshot, sqlite_db_path};
+use crate::diskspace::{self, Operation};
+use crate::events::{self, Event};
+use crate::jobs::{start_job, JobKind};
+use crate::logs::{log_line, SharedLogStore};
+use crate::notifications::{self, Kind};
+use crate::server::SharedServerManager;
+use crate::settings::{Settings, SharedSettings};
+use crate::windows::open_injected_window;
+
+const STATE_FILE: &str = "upgrade.json";
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+const WINDOW_LABEL: &str = "upgrade";
+
+static PATH: OnceLock<PathBuf> = OnceLock::new();
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+struct UpgradeState {
+    /// The version that last started the server, None before this was recorded
+    version: Option<String>,
+    pending: Option<PendingUpgrade>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PendingUpgrade {
+    /// None when the database predates the recorded versions
+    pub from: Option<String>,
+    pub to: String,
+    pub snapshot: String,
+    pub migrations_before: Option<i64>,
+    pub migrations_bundled: usize,
+    /// Why the last attempt failed
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Stage {
+    Migrating,
+    Done,
+    Failed,
+    RolledBack,
+}
+
+#[derive(Clone, Serialize)]
+pub struct UpgradeProgress {
+    pub from: Option<String>,
+    pub to: String,
+    pub stage: Stage,
+    /// Migrations in the database now
+    pub applied: Option<i64>,
+    /// Migrations the new version brings in total
+    pub total: usize,
+    pub snapshot: String,
+    pub error: Option<String>,
+}
+
+impl UpgradeProgress {
+    fn new(pending: &PendingUpgrade, stage: Stage, applied: Option<i64>) -> Self {
+        Self {
+            from: pending.from.clone(),
+            to: pending.to.clone(),
+            stage,
+            applied,
+            total: pending.migrations_bundled,
+            snapshot: pending.snapshot.clone(),
+            error: pending.error.clone(),
+        }
+    }
+}
+
+/// An upgrade in progress, handed back to `finish` once the server is up or failed
+pub struct Upgrade {
+    pending: PendingUpgrade,
+    db_path: PathBuf,
+    done: oneshot::Sender<Result<(), String>>,
+}
+
+/// Where the upgrade state is kept; called once the data dir is known
+pub fn init(data_dir: &Path) {
+    let _ = PATH.set(data_dir.join(STATE_FILE));
+}
+
+fn read_state() -> UpgradeState {
+    PATH.get()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn write_state(state: &UpgradeState) -> Result<(), String> {
+    let Some(path) = PATH.get() else {
+        return Ok(());
+    };
+    let json = serde_json::to_string_pretty(state).map_err(|e| e.to_string())?;
+    fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Record that this version runs the database, with nothing pending
+fn record_current() {
+    let state = UpgradeState {
+        version: Some(env!("CARGO_PKG_VERSION").to_string()),
+        pending: None,
+    };
+    if let Err(e) = write_state(&state) {
+        eprintln!("Warning: {}", e);
+    }
+}
+
+/// Migrations in the journal next to the bundled migrations
+fn bundled_migrations(migrations_dir: &Path) -> Option<usize> {
+    let journal = fs::read_to_string(migrations_dir.join("meta").join("_journal.json")).ok()?;
+    let journal: serde_json::Value = serde_json::from_str(&journal).ok()?;
+    journal["entries"].as_array().map(Vec::len)
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Before the main server starts: snapshot the database if this version will migrate it
+/// Err means the server must not start, as there'd be nothing to roll back to.
+pub async fn prepare(
+    app: &AppHandle,
+    log_store: &SharedLogStore,
+    data_dir: &Path,
+    migrations_dir: &Path,
+    is_postgres: bool,
+) -> Result<Option<Upgrade>, String> {
+    let current = env!("CARGO_PKG_VERSION");
+    let state = read_state();
+    let retry = state.pending.clone().filter(|p| p.to == current && Path::new(&p.snapshot).exists());
+    if retry.is_none() && state.version.as_deref() == Some(current) {
+        return Ok(None);
+    }
+
+    let db_path = sqlite_db_path(data_dir);
+    let bundled = bundled_migrations(migrations_dir);
+    let applied = {
+        let db_path = db_path.clone();
+        tauri::async_runtime::spawn_blocking(move || migration_count(&db_path)).await.ok().flatten()
+    };
+    // Nothing to protect: PostgreSQL, a fresh install, or a schema that's already current
+    let needs_snapshot = !is_postgres
+        && db_path.exists()
+        && match (applied, bundled) {
+            (Some(applied), Some(bundled)) => applied < bundled as i64,
+            _ => bundled.is_some(),
+        };
+    if retry.is_none() && !needs_snapshot {
+        record_current();
+        return Ok(None);
+    }
+
+    let pending = match retry {
+        Some(pending) => {
+            let msg = format!("Retrying the database upgrade to {}, the snapshot at {} is kept", current, pending.snapshot);
+            log_line(app, log_store, msg, "info").await;
+            pending
+        }
+        None => {
+            let settings = match app.try_state::<SharedSettings>() {
+                Some(settings) => settings.lock().await.get(),
+                None => Settings::default(),
+            };
+            let dir = backups_dir(data_dir, &settings);
+            let needed = fs::metadata(&db_path).map(|meta| meta.len()).unwrap_or(0);
+            diskspace::preflight(app, Operation::Backup, &dir, needed)
+                .await
+                .map_err(|e| format!("Can't upgrade the database without a snapshot: {}", e))?;
+            let target = dir.join(format!("app-{}-pre-upgrade.db", unix_now()));
+            let (source, snapshot_path) = (db_path.clone(), target.clone());
+            tauri::async_runtime::spawn_blocking(move || {
+                fs::create_dir_all(snapshot_path.parent().unwrap_or(Path::new(".")))
+                    .map_err(|e| format!("Failed to create backups directory: {}", e))?;
+                snapshot(&source, &snapshot_path, None)
+            })
+            .await
+            .map_err(|e| e.to_string())
+            .and_then(|r| r)
+            .map_err(|e| format!("Can't upgrade the database without a snapshot: {}", e))?;
+
+            let pending = PendingUpgrade {
+                from: state.version.clone(),
+                to: current.to_string(),
+                snapshot: target.to_string_lossy().to_string(),
+                migrations_before: applied,
+                migrations_bundled: bundled.unwrap_or(0),
+                error: None,
+            };
+            let msg = format!(
+                "Upgrading the database from {} to {}, snapshot saved at {}",
+                pending.from.as_deref().unwrap_or("an earlier version"),
+                current,
+                pending.snapshot
+            );
+            log_line(app, log_store, msg, "info").await;
+            pending
+        }
+    };
+    write_state(&UpgradeState {
+        version: state.version,
+        pending: Some(pending.clone()),
+    })?;
+
+    let (done, finished) = oneshot::channel();
+    watch(app.clone(), pending.clone(), db_path.clone(), finished);
+    Ok(Some(Upgrade { pending, db_path, done }))
+}
+
+/// Report migration progress until the start finishes
+fn watch(app: AppHandle, pending: PendingUpgrade, db_path: PathBuf, mut finished: oneshot::Receiver<Result<(), String>>) {
+    tauri::async_runtime::spawn(async move {
+        let title = format!("Upgrading the database to {}", pending.to);
+        let job = start_job(&app, JobKind::Migration, title, false);
+        let before = pending.migrations_before.unwrap_or(0);
+        let total = pending.migrations_bundled as i64;
+        let mut last = None;
+        let result = loop {
+            let path = db_path.clone();
+            let applied = tauri::async_runtime::spawn_blocking(move || migration_count(&path)).await.ok().flatten();
+            if applied != last {
+                last = applied;
+                let progress = applied
+                    .filter(|_| total > before)
+                    .map(|applied| (applied - before) as f64 / (total - before) as f64);
+                let message = applied.map(|applied| format!("{} of {} migrations applied", applied, total));
+                job.progress(progress, message);
+                let _ = events::emit(&app, Event::UpgradeProgress(&UpgradeProgress::new(&pending, Stage::Migrating, applied)));
+            }
+            tokio::select! {
+                result = &mut finished => break result.unwrap_or_else(|_| Err("The server didn't start".to_string())),
+                _ = tokio::time::sleep(POLL_INTERVAL) => {}
+            }
+        };
+        job.finish(&result);
+    });
+}
+
+/// After the main server started (or failed to): record the upgrade, or offer the rollback
+pub async fn finish(app: &AppHandle, log_store: &SharedLogStore, upgrade: Upgrade, result: &Result<(), String>) {
+    let Upgrade { mut pending, db_path, done } = upgrade;
+    let _ = done.send(result.clone());
+    let applied = tauri::async_runtime::spawn_blocking(move || migration_count(&db_path)).await.ok().flatten();
+    match result {
+        Ok(()) => {
+            record_current();
+            let msg = format!("Database upgraded to {}, the snapshot stays at {}", pending.to, pending.snapshot);
+            log_line(app, log_store, msg, "info").await;
+            let _ = events::emit(app, Event::UpgradeProgress(&UpgradeProgress::new(&pending, Stage::Done, applied)));
+        }
+        Err(e) => {
+            pending.error = Some(e.clone());
+            let state = UpgradeState {
+                version: pending.from.clone(),
+                pending: Some(pending.clone()),
+            };
+            if let Err(e) = write_state(&state) {
+                eprintln!("Warning: {}", e);
+            }
+            let msg = format!("The database upgrade to {} failed, the snapshot at {} can be restored", pending.to, pending.snapshot);
+            log_line(app, log_store, msg, "error").await;
+            let _ = events::emit(app, Event::UpgradeProgress(&UpgradeProgress::new(&pending, Stage::Failed, applied)));
+            open_upgrade_window(app);
+            // Sent from a task, as the caller may hold the server manager
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let body = "The database couldn't be upgraded. It was saved just before, and can be restored.";
+                notifications::notify(&app, Kind::Maintenance, "Moneywright couldn't upgrade your data", body).await;
+            });
+        }
+    }
+}
+
+/// The upgrade that failed, for the upgrade window
+#[tauri::command]
+pub async fn get_upgrade_status() -> Result<Option<PendingUpgrade>, String> {
+    Ok(read_state().pending)
+}
+
+/// Put the snapshot from before the upgrade back, leaving the server stopped
+/// Starting it again would run the same migrations, so the previous version is the way to use it.
+#[tauri::command]
+pub async fn rollback_upgrade(
+    app: AppHandle,
+    manager: State<'_, SharedServerManager>,
+    settings: State<'_, SharedSettings>,
+    log_store: State<'_, SharedLogStore>,
+) -> Result<Option<String>, String> {
+    let state = read_state();
+    let pending = state.pending.ok_or("There is no database upgrade to roll back")?;
+    let snapshot_path = PathBuf::from(&pending.snapshot);
+    let check_path = snapshot_path.clone();
+    tauri::async_runtime::spawn_blocking(move || integrity_check(&check_path))
+        .await
+        .map_err(|e| format!("Verification task failed: {}", e))?
+        .map_err(|e| format!("The snapshot at {} failed its check: {}", pending.snapshot, e))?;
+
+    let data_dir = manager.lock().await.data_dir().clone();
+    let dir = backups_dir(&data_dir, &settings.lock().await.get());
+    let job = start_job(&app, JobKind::Migration, format!("Restoring the database from before {}", pending.to), false);
+    let result = replace_database(manager.inner(), &sqlite_db_path(&data_dir), &snapshot_path, &dir).await;
+    job.finish(&result);
+    let saved = result?;
+
+    write_state(&UpgradeState {
+        version: pending.from.clone(),
+        pending: None,
+    })?;
+    let msg = format!(
+        "Restored the database from before the upgrade to {}, the upgraded one is saved at {}",
+        pending.to,
+        saved.display()
+    );
+    log_line(&app, &log_store, msg, "info").await;
+    crate::emit_status(&app, "stopped");
+    let _ = events::emit(&app, Event::UpgradeProgress(&UpgradeProgress::new(&pending, Stage::RolledBack, pending.migrations_before)));
+    Ok(pending.from)
+}
+
+/// Open the release page of the version the database was rolled back for
+#[tauri::command]
+pub async fn open_release(version: String) -> Result<(), String> {
+    if version.is_empty() || !version.chars().all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-') {
+        return Err(format!("\"{}\" isn't a version", version));
+    }
+    let url = format!("https://github.com/moneywright/moneywright/releases/tag/v{}", version);
+    open::that(url).map_err(|e| format!("Failed to open the download page: {}", e))
+}
+
+/// Open the upgrade window
+pub fn open_upgrade_window(app: &AppHandle) {
+    let script = r#"
+        const tauriApi = window.__TAURI__;
+
+        document.documentElement.innerHTML = `
+<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <title>Database Upgrade</title>
+    <style>
+        __BASE_STYLE__
+        .content { flex: 1; padding: 24px; display: flex; flex-direction: column; gap: 12px; }
+        .detail { color: #ef4444; font-family: ui-monospace, SFMono-Regular, Menlo, monospace; font-size: 12px; word-break: break-all; }
+        .actions { display: flex; gap: 8px; margin-top: 8px; }
+    </style>
+</head>
+<body>
+    <div class="content">
+        <h1>Moneywright couldn't upgrade your data</h1>
+        <p id="summary"></p>
+        <div id="error" class="detail" role="alert"></div>
+        <p id="snapshot" class="muted mono"></p>
+        <div class="actions">
+            <button id="retryBtn" class="primary">Try Again</button>
+            <button id="rollbackBtn">Restore Snapshot</button>
+            <button id="downloadBtn" hidden></button>
+            <button id="logsBtn">Open Log Files</button>
+        </div>
+        <div id="status" class="muted" role="status" aria-live="polite"></div>
+    </div>
+</body>
+</html>`;
+
+        const $ = id => document.getElementById(id);
+        let from = null;
+
+        async function load() {
+            const pending = await tauriApi.core.invoke('get_upgrade_status');
+            if (!pending) {
+                $('summary').textContent = 'There is no failed upgrade to deal with.';
+                $('retryBtn').disabled = true;
+                $('rollbackBtn').disabled = true;
+                return;
+            }
+            from = pending.from;
+            $('summary').textContent = 'Version ' + pending.to + ' has to update your database before it starts, and that failed. '
+                + 'Your data was saved just before the upgrade: restoring it puts everything back as it was'
+                + (from ? ' in version ' + from + '.' : '.');
+            $('error').textContent = pending.error || '';
+            $('snapshot').textContent = 'Snapshot: ' + pending.snapshot;
+        }
+
+        $('retryBtn').onclick = async () => {
+            $('retryBtn').disabled = true;
+            $('status').textContent = 'Starting the server...';
+            try {
+                await tauriApi.core.invoke('restart_server_cmd');
+                $('status').textContent = 'The upgrade went through this time.';
+                $('rollbackBtn').disabled = true;
+            } catch (e) {
+                $('status').textContent = String(e);
+                $('retryBtn').disabled = false;
+            }
+        };
+        $('rollbackBtn').onclick = async () => {
+            if (!confirm('Restore the database from before the upgrade? The partly upgraded database is kept in the backups folder.')) return;
+            $('rollbackBtn').disabled = true;
+            $('retryBtn').disabled = true;
+            $('status').textContent = 'Restoring...';
+            try {
+                const version = await tauriApi.core.invoke('rollback_upgrade');
+                $('status').textContent = version
+                    ? 'Restored. Install Moneywright ' + version + ' to keep using your data until the upgrade is fixed.'
+                    : 'Restored. Install the version you used before to keep using your data until the upgrade is fixed.';
+                if (version) {
+                    $('downloadBtn').textContent = 'Download Moneywright ' + version;
+                    $('downloadBtn').hidden = false;
+                }
+            } catch (e) {
+                $('status').textContent = String(e);
+                $('rollbackBtn').disabled = false;
+                $('retryBtn').disabled = false;
+            }
+        };
+        $('downloadBtn').onclick = () => tauriApi.core.invoke('open_release', { version: from });
+        $('logsBtn').onclick = () => tauriApi.core.invoke('open_logs_folder');
+        load();
+    "#;
+
+    open_injected_window(app, WINDOW_LABEL, "Database Upgrade", (600.0, 380.0), false, script);
+}
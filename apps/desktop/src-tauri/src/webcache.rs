@@ -0,0 +1,105 @@
+// Resetting the web app's cached assets in the webviews
+//
+// After a server update the webview can keep serving an old index.html or a service
+// worker's cached bundle, leaving a broken UI that "Clear Cookies" doesn't fix (and
+// that would sign the user out). The reset runs inside the app's own pages, so only
+// the server's origin is touched: service workers are unregistered, Cache Storage
+// and IndexedDB are deleted, the entry page is re-fetched past the HTTP cache and the
+// page reloads. Cookies and local storage are kept.
+//
+// The server version of the last start is kept in `server_version` in the data dir;
+// when it changes the reset runs by itself once the windows show the new server.
+
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{AppHandle, Manager, WebviewWindow};
+use crate::server::is_server_url;
+
+const VERSION_FILE: &str = "server_version";
+
+/// Reset windows as they load the server after an upgrade
+static PENDING: AtomicBool = AtomicBool::new(false);
+
+/// Clears service workers, Cache Storage and IndexedDB of the page's origin, then
+/// re-fetches the entry page past the HTTP cache (asset bundles are content-hashed)
+pub const CLEAR_CACHE_STEPS: &str = r#"
+        if (navigator.serviceWorker) {
+            const registrations = await navigator.serviceWorker.getRegistrations();
+            await Promise.all(registrations.map(r => r.unregister()));
+        }
+        if (window.caches) {
+            const keys = await caches.keys();
+            await Promise.all(keys.map(k => caches.delete(k)));
+        }
+        if (window.indexedDB && indexedDB.databases) {
+            const databases = await indexedDB.databases();
+            await Promise.all(databases.filter(d => d.name).map(d => new Promise(resolve => {
+                const request = indexedDB.deleteDatabase(d.name);
+                request.onsuccess = request.onerror = request.onblocked = () => resolve();
+            })));
+        }
+        await fetch(location.origin + '/', { cache: 'reload' });
+"#;
+
+/// Run clearing steps in a page and reload it, even if a step fails
+pub fn page_script(steps: &str) -> String {
+    format!(
+        "(async () => {{\n    try {{{}    }} catch (e) {{\n        console.error('Clearing site data failed', e);\n    }}\n    location.reload();\n}})();",
+        steps
+    )
+}
+
+/// Whether a window shows a page of the main server
+fn on_server(window: &WebviewWindow) -> bool {
+    window.url().is_ok_and(|url| is_server_url(&url))
+}
+
+fn reset_window(window: &WebviewWindow) {
+    if let Err(e) = window.eval(page_script(CLEAR_CACHE_STEPS)) {
+        eprintln!("Warning: Failed to reset web cache: {}", e);
+    }
+}
+
+/// Reset the web cache in every window showing the app (Edit > Reset Web Cache)
+pub fn reset_all(app: &AppHandle) -> usize {
+    let windows: Vec<WebviewWindow> = app.webview_windows().into_values().filter(on_server).collect();
+    for window in &windows {
+        reset_window(window);
+    }
+    windows.len()
+}
+
+/// Clear cached web assets and reload the app windows
+#[tauri::command]
+pub async fn reset_web_cache(app: AppHandle) -> Result<usize, String> {
+    Ok(reset_all(&app))
+}
+
+/// Record the version of the server that came up; an upgrade resets the web cache
+pub fn on_server_version(app: &AppHandle, data_dir: &Path, version: &str) {
+    let path = data_dir.join(VERSION_FILE);
+    let previous = fs::read_to_string(&path).ok();
+    if previous.as_deref().map(str::trim) == Some(version) {
+        return;
+    }
+    if let Err(e) = fs::write(&path, version) {
+        eprintln!("Warning: Failed to record server version: {}", e);
+    }
+    // First start has nothing cached yet
+    if previous.is_some() {
+        println!("Server upgraded to {}, resetting web cache", version);
+        PENDING.store(true, Ordering::Relaxed);
+        if reset_all(app) > 0 {
+            PENDING.store(false, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Window finished loading a page; finishes a reset that was waiting for the server
+pub fn on_page_load(window: &WebviewWindow) {
+    if PENDING.load(Ordering::Relaxed) && on_server(window) {
+        PENDING.store(false, Ordering::Relaxed);
+        reset_window(window);
+    }
+}
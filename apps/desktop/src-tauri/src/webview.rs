@@ -0,0 +1,157 @@
+// Webview options of the app windows, from `[webview]` in settings.toml
+//
+// Some bank sign-in and account-linking flows (Plaid-style) refuse the default Tauri
+// user agent or rely on `window.open` popups. The main, extra, profile and demo
+// windows are built through `configure`, which applies the user agent and the popup
+// and autoplay policy. The user agent is fixed when a window is built, so a change
+// applies to windows opened afterwards (the main window after a restart); the popup
+// and autoplay policy is read whenever a page asks.
+//
+// Every server is on localhost and cookies are scoped by host, not port, so windows of
+// other servers sharing the default store would overwrite each other's sign-in.
+// Profile, demo and sandbox windows get their own store through `isolate`.
+
+use std::path::PathBuf;
+use std::sync::Mutex;
+use sha2::{Digest, Sha256};
+use tauri::webview::{NewWindowResponse, PermissionKind, PermissionResponse};
+use tauri::{AppHandle, Url, WebviewWindow, WebviewWindowBuilder, Wry};
+use tokio::sync::watch;
+use crate::settings::{Settings, SettingsStore, WebviewSettings};
+
+/// Values of `webview.popups`
+pub const POPUP_POLICIES: &[&str] = &["none", "auth", "all"];
+
+/// Settings in effect, for building windows outside of async code
+static CURRENT: Mutex<Option<WebviewSettings>> = Mutex::new(None);
+
+fn current() -> WebviewSettings {
+    CURRENT.lock().unwrap_or_else(|e| e.into_inner()).clone().unwrap_or_default()
+}
+
+/// Keep the snapshot used for new windows in step with `[webview]`
+pub fn spawn_webview_sync(mut rx: watch::Receiver<Settings>) {
+    *CURRENT.lock().unwrap_or_else(|e| e.into_inner()) = Some(rx.borrow().webview.clone());
+    tauri::async_runtime::spawn(async move {
+        while rx.changed().await.is_ok() {
+            let settings = rx.borrow_and_update().webview.clone();
+            *CURRENT.lock().unwrap_or_else(|e| e.into_inner()) = Some(settings);
+        }
+    });
+}
+
+/// Whether a popup URL belongs to a sign-in host (or a subdomain of one)
+fn is_auth_host(url: &Url, hosts: &[String]) -> bool {
+    let Some(host) = url.host_str() else {
+        return false;
+    };
+    hosts.iter().any(|h| host == h || host.ends_with(&format!(".{}", h)))
+}
+
+/// What to do with a `window.open` from a page
+fn on_popup(url: Url) -> NewWindowResponse<Wry> {
+    let settings = current();
+    let allowed = match settings.popups.as_str() {
+        "all" => true,
+        "auth" => is_auth_host(&url, &settings.auth_hosts),
+        _ => false,
+    };
+    if allowed {
+        return NewWindowResponse::Allow;
+    }
+    // Not for a window inside the app, but the link should still go somewhere
+    if settings.popups != "none" && matches!(url.scheme(), "http" | "https") {
+        let _ = open::that(url.as_str());
+    }
+    NewWindowResponse::Deny
+}
+
+/// Switch the webviews to software rendering when `webview.software_rendering` is on;
+/// `--disable-gpu` / `--enable-gpu` on the command line change the setting first, so the
+/// choice sticks for later launches. Runs before the first webview is created, since the
+/// engines read these variables when they start.
+pub fn apply_rendering(settings: &SettingsStore) -> Result<bool, String> {
+    let mut software = settings.get().webview.software_rendering;
+    let requested = std::env::args().fold(None, |requested, arg| match arg.as_str() {
+        "--disable-gpu" => Some(true),
+        "--enable-gpu" => Some(false),
+        _ => requested,
+    });
+    if let Some(requested) = requested.filter(|r| *r != software) {
+        settings.update(&serde_json::json!({ "webview": { "software_rendering": requested } }))?;
+        software = requested;
+    }
+    if !software {
+        return Ok(false);
+    }
+
+    // Anything the user exported themselves wins
+    let vars: &[(&str, &str)] = if cfg!(target_os = "linux") {
+        // WebKitGTK: no DMA-BUF renderer or accelerated compositing, Mesa in software
+        &[
+            ("WEBKIT_DISABLE_DMABUF_RENDERER", "1"),
+            ("WEBKIT_DISABLE_COMPOSITING_MODE", "1"),
+            ("LIBGL_ALWAYS_SOFTWARE", "1"),
+        ]
+    } else if cfg!(windows) {
+        // Replaces the arguments Tauri passes, so its defaults are repeated
+        &[(
+            "WEBVIEW2_ADDITIONAL_BROWSER_ARGUMENTS",
+            "--disable-features=msWebOOUI,msPdfOOUI,msSmartScreenProtection --disable-gpu",
+        )]
+    } else {
+        // WKWebView has no switch for it
+        &[]
+    };
+    for (name, value) in vars {
+        if std::env::var_os(name).is_none() {
+            std::env::set_var(name, value);
+        }
+    }
+    Ok(true)
+}
+
+/// Apply `[webview]` to an app window being built
+pub fn configure(builder: WebviewWindowBuilder<'_, Wry, AppHandle>) -> WebviewWindowBuilder<'_, Wry, AppHandle> {
+    let settings = current();
+    let builder = if settings.user_agent.trim().is_empty() {
+        builder
+    } else {
+        builder.user_agent(settings.user_agent.trim())
+    };
+    builder
+        .on_new_window(|url, _features| on_popup(url))
+        // Only WebView2 (Windows) asks; elsewhere media follows the platform default
+        .on_permission_request(|_, kind| match kind {
+            PermissionKind::Autoplay if current().autoplay => PermissionResponse::Allow,
+            _ => PermissionResponse::Default,
+        })
+}
+
+/// Give a window its own cookies and storage, kept in `dir` (Windows, Linux) or in
+/// a store named after `key` (macOS 14 and later)
+pub fn isolate<'a>(
+    builder: WebviewWindowBuilder<'a, Wry, AppHandle>,
+    dir: PathBuf,
+    key: &str,
+) -> WebviewWindowBuilder<'a, Wry, AppHandle> {
+    let mut identifier = [0u8; 16];
+    identifier.copy_from_slice(&Sha256::digest(key.as_bytes())[..16]);
+    builder.data_directory(dir).data_store_identifier(identifier)
+}
+
+/// Build the main window from tauri.conf.json (`"create": false`) with `[webview]` applied
+pub fn create_main_window(app: &AppHandle) -> Result<WebviewWindow, String> {
+    let config = app
+        .config()
+        .app
+        .windows
+        .iter()
+        .find(|w| w.label == "main")
+        .ok_or_else(|| "Main window missing from tauri.conf.json".to_string())?;
+    let builder = WebviewWindowBuilder::from_config(app, config)
+        .map_err(|e| format!("Failed to configure main window: {}", e))?;
+    configure(builder)
+        .build()
+        .map_err(|e| format!("Failed to create main window: {}", e))
+}
@@ -0,0 +1,224 @@
+// Helpers for native shell windows whose UI is injected into a blank webview
+
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
+
+/// Shared styles for native windows - matches the web app's dark mode design tokens
+/// Substituted for `__BASE_STYLE__` in window scripts
+pub const BASE_STYLE: &str = r#"
+        * { margin: 0; padding: 0; box-sizing: border-box; }
+        body {
+            font-family: system-ui, -apple-system, BlinkMacSystemFont, 'Segoe UI', sans-serif;
+            font-size: 13px;
+            background: #030303;
+            color: #fafafa;
+            height: 100vh;
+            display: flex;
+            flex-direction: column;
+        }
+        ::-webkit-scrollbar { width: 8px; height: 8px; }
+        ::-webkit-scrollbar-track { background: transparent; }
+        ::-webkit-scrollbar-thumb { background: rgba(255, 255, 255, 0.1); border-radius: 4px; }
+        h1, h2 { font-weight: 600; letter-spacing: -0.02em; }
+        .toolbar {
+            padding: 12px 16px;
+            background: #0a0a0a;
+            border-bottom: 1px solid rgba(255, 255, 255, 0.06);
+            display: flex;
+            gap: 10px;
+            align-items: center;
+            flex-shrink: 0;
+        }
+        button {
+            padding: 6px 14px;
+            background: #111111;
+            border: 1px solid rgba(255, 255, 255, 0.08);
+            color: #a1a1aa;
+            border-radius: 6px;
+            cursor: pointer;
+            font-family: inherit;
+            font-size: 12px;
+            font-weight: 500;
+            transition: all 0.15s ease;
+        }
+        button:hover:not(:disabled) { background: #161616; border-color: rgba(255, 255, 255, 0.12); color: #fafafa; }
+        button:disabled { opacity: 0.5; cursor: not-allowed; }
+        button.primary { background: #10b981; color: #022c22; border-color: #10b981; }
+        button.primary:hover:not(:disabled) { background: #34d399; color: #022c22; }
+        button.danger:hover:not(:disabled) { color: #ef4444; border-color: rgba(239, 68, 68, 0.4); }
+        input, select, textarea {
+            background: #0a0a0a;
+            border: 1px solid rgba(255, 255, 255, 0.1);
+            border-radius: 6px;
+            color: #fafafa;
+            font-family: inherit;
+            font-size: 13px;
+            padding: 7px 10px;
+        }
+        input:focus, select:focus, textarea:focus, button:focus-visible, a:focus-visible, [tabindex]:focus-visible { outline: 2px solid #10b981; outline-offset: 1px; }
+        .muted { color: #71717a; }
+        .mono { font-family: ui-monospace, SFMono-Regular, Menlo, Consolas, monospace; font-size: 12px; }
+        .pass { color: #10b981; }
+        .warn { color: #f59e0b; }
+        .fail { color: #ef4444; }
+        .empty-state {
+            display: flex;
+            align-items: center;
+            justify-content: center;
+            height: 100%;
+            color: #52525b;
+        }
+"#;
+
+/// Functions shared by window scripts, run before each one
+const HELPERS_SCRIPT: &str = r#"
+function escapeHtml(text) {
+    const entities = { '&': '&amp;', '<': '&lt;', '>': '&gt;', '"': '&quot;', "'": '&#39;' };
+    return String(text == null ? '' : text).replace(/[&<>"']/g, c => entities[c]);
+}
+"#;
+
+/// Keyboard and screen-reader support, run after a window's UI has been injected
+///
+/// Marks decorative icons hidden, names icon-only buttons after their title, makes
+/// `data-url` links focusable, keeps Tab inside the window, focuses the primary
+/// control, and outlines controls in Windows high contrast mode. Nodes added later
+/// (re-rendered tables, log lines) get the same treatment. In dialogs, Escape closes
+/// the window. The OS high-contrast and reduced-motion preferences (see a11y.rs)
+/// are applied as `data-high-contrast` / `data-reduced-motion` on the root element.
+const A11Y_SCRIPT: &str = r#"
+(function () {
+    const dialog = __DIALOG__;
+    document.documentElement.lang = 'en';
+
+    const style = document.createElement('style');
+    style.textContent = `
+        a:focus-visible, [tabindex]:focus-visible, button:focus-visible { outline: 2px solid #10b981; outline-offset: 1px; }
+        @media (forced-colors: active) {
+            button, input, select, textarea { border: 1px solid ButtonText; }
+            button:focus-visible, a:focus-visible, [tabindex]:focus-visible, input:focus, select:focus, textarea:focus { outline: 2px solid Highlight; }
+            [role=progressbar] > * { background: Highlight; forced-color-adjust: none; }
+        }
+        html[data-high-contrast] body { background: #000 !important; color: #fff !important; }
+        html[data-high-contrast] .muted, html[data-high-contrast] .count, html[data-high-contrast] .prefix,
+        html[data-high-contrast] .description, html[data-high-contrast] .license, html[data-high-contrast] .stats,
+        html[data-high-contrast] th { color: #e4e4e7 !important; }
+        html[data-high-contrast] button, html[data-high-contrast] input, html[data-high-contrast] select,
+        html[data-high-contrast] textarea { color: #fff !important; border-color: rgba(255, 255, 255, 0.7) !important; }
+        html[data-high-contrast] .toolbar, html[data-high-contrast] td { border-color: rgba(255, 255, 255, 0.5) !important; }
+        html[data-high-contrast] :focus-visible { outline: 3px solid #fff !important; }
+        html[data-reduced-motion] *, html[data-reduced-motion] *::before, html[data-reduced-motion] *::after {
+            animation: none !important;
+            transition: none !important;
+            scroll-behavior: auto !important;
+        }`;
+    document.head.appendChild(style);
+
+    function applyPrefs(prefs) {
+        document.documentElement.toggleAttribute('data-high-contrast', !!prefs.high_contrast);
+        document.documentElement.toggleAttribute('data-reduced-motion', !!prefs.reduced_motion);
+    }
+    if (window.__TAURI__) {
+        window.__TAURI__.core.invoke('get_system_a11y_prefs').then(applyPrefs).catch(() => {});
+        window.__TAURI__.event.listen('system-a11y-changed', e => applyPrefs(e.payload));
+    }
+
+    function fix(root) {
+        root.querySelectorAll('svg:not([aria-hidden])').forEach(svg => {
+            svg.setAttribute('aria-hidden', 'true');
+            svg.setAttribute('focusable', 'false');
+        });
+        root.querySelectorAll('img:not([alt])').forEach(img => img.setAttribute('alt', ''));
+        root.querySelectorAll('a[data-url]:not([href])').forEach(a => a.setAttribute('href', '#'));
+        root.querySelectorAll('button:not([aria-label])').forEach(button => {
+            if (!button.textContent.trim() && button.title) button.setAttribute('aria-label', button.title);
+        });
+    }
+    fix(document);
+    new MutationObserver(records => {
+        for (const record of records) {
+            record.addedNodes.forEach(node => { if (node.nodeType === 1) fix(node); });
+        }
+    }).observe(document.body, { childList: true, subtree: true });
+
+    const FOCUSABLE = 'a[href], button:not([disabled]), input:not([disabled]), select:not([disabled]), textarea:not([disabled]), [tabindex]:not([tabindex="-1"])';
+    function focusable() {
+        return Array.from(document.querySelectorAll(FOCUSABLE)).filter(el => el.offsetParent !== null || el === document.activeElement);
+    }
+
+    document.addEventListener('keydown', (e) => {
+        if (e.key === 'Escape' && dialog) {
+            window.__TAURI__.window.getCurrentWindow().close();
+            return;
+        }
+        if (e.key !== 'Tab') return;
+        const items = focusable();
+        if (items.length === 0) return;
+        const first = items[0];
+        const last = items[items.length - 1];
+        if (e.shiftKey && (document.activeElement === first || !items.includes(document.activeElement))) {
+            e.preventDefault();
+            last.focus();
+        } else if (!e.shiftKey && document.activeElement === last) {
+            e.preventDefault();
+            first.focus();
+        }
+    });
+
+    if (!document.activeElement || document.activeElement === document.body) {
+        const start = document.querySelector('button.primary:not([disabled])') || focusable()[0];
+        if (start) start.focus();
+    }
+})();
+"#;
+
+/// Accessibility script for a window's injected UI; `dialog` windows close on Escape
+fn a11y_script(dialog: bool) -> String {
+    A11Y_SCRIPT.replace("__DIALOG__", if dialog { "true" } else { "false" })
+}
+
+/// A window's script with the shared helpers before it and accessibility support after
+/// it; `__BASE_STYLE__` is substituted with the shared window styles
+pub fn window_script(script: &str, dialog: bool) -> String {
+    format!("{}\n{}\n{}", HELPERS_SCRIPT, script.replace("__BASE_STYLE__", BASE_STYLE), a11y_script(dialog))
+}
+
+/// Open (or focus) a window and inject the given script once the blank page has loaded
+/// The script should replace `document.documentElement.innerHTML`; `__BASE_STYLE__` is
+/// substituted with the shared window styles
+pub fn open_injected_window(
+    app: &AppHandle,
+    label: &str,
+    title: &str,
+    size: (f64, f64),
+    resizable: bool,
+    script: &str,
+) {
+    // Check if window already exists
+    if let Some(window) = app.get_webview_window(label) {
+        let _ = window.show();
+        let _ = window.set_focus();
+        return;
+    }
+
+    let (width, height) = crate::display::text_scaled_size(app, size);
+    let window = WebviewWindowBuilder::new(app, label, WebviewUrl::CustomProtocol(crate::protocol::page_url("window")))
+        .title(title)
+        .inner_size(width, height)
+        .min_inner_size(size.0.min(400.0), size.1.min(300.0))
+        .resizable(resizable)
+        .maximizable(resizable)
+        .visible(false) // Start hidden to avoid flash
+        .build();
+
+    if let Ok(win) = window {
+        let script = window_script(script, false);
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+            let _ = win.eval(&script);
+            // Show window after content is injected
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            let _ = win.show();
+            let _ = win.set_focus();
+        });
+    }
+}
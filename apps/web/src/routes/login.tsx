@@ -8,10 +8,12 @@ import { Loader2, Star } from 'lucide-react'
 import { AuthLayout } from '@/components/auth/auth-layout'
 
 /**
- * Check if the session hint cookie exists
+ * Check if the session hint cookie exists. Over the desktop app's socket transport
+ * (mwapp:) cookies are kept by the app and never visible here, so always try.
  */
 function hasSessionHint(): boolean {
   if (typeof document === 'undefined') return false
+  if (window.location.protocol === 'mwapp:') return true
   return document.cookie.split(';').some((c) => c.trim().startsWith('_s='))
 }
 